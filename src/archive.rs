@@ -0,0 +1,139 @@
+//! Zero-copy mesh archives
+//!
+//! A mesh saved with [`Mesh::save_archive`] can be memory-mapped with
+//! [`MappedMesh::open`] and read in place through [`ArchivedMesh`], without
+//! deserializing it. Opening costs one validation pass over the archive (or
+//! nothing at all with [`MappedMesh::open_unchecked`]); only the parts that are
+//! actually read get paged in.
+//!
+//! Enabled by the `rkyv` feature.
+
+use crate::error::{ParseError, Result};
+use crate::parser::Token;
+pub use crate::types::mesh::ArchivedMesh;
+use crate::types::Mesh;
+use memmap2::Mmap;
+use rkyv::rancor::{Fallible, Source};
+use rkyv::string::{ArchivedString, StringResolver};
+use rkyv::with::{ArchiveWith, DeserializeWith, SerializeWith};
+use rkyv::Place;
+use std::fs::File;
+use std::path::Path;
+
+fn archive_error(error: impl std::fmt::Display) -> ParseError {
+    ParseError::CacheError(error.to_string())
+}
+
+impl Mesh {
+    /// Write this mesh as an rkyv archive to `path`
+    pub fn save_archive(&self, path: impl AsRef<Path>) -> Result<()> {
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(self).map_err(archive_error)?;
+        std::fs::write(path, &bytes)?;
+        Ok(())
+    }
+}
+
+/// A memory-mapped mesh archive
+pub struct MappedMesh {
+    mmap: Mmap,
+}
+
+impl MappedMesh {
+    /// Map and validate the archive at `path`
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be mapped or is not a valid archive.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only; modifying the file while it is mapped
+        // is the caller's responsibility, as with any memory-mapped file.
+        let mmap = unsafe { Mmap::map(&file)? };
+        rkyv::access::<ArchivedMesh, rkyv::rancor::Error>(&mmap).map_err(archive_error)?;
+        Ok(Self { mmap })
+    }
+
+    /// Map the archive at `path` without validating it
+    ///
+    /// # Safety
+    /// The file must have been written by [`Mesh::save_archive`] with the same
+    /// version of this crate and not modified since.
+    pub unsafe fn open_unchecked(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        let mmap = Mmap::map(&file)?;
+        Ok(Self { mmap })
+    }
+
+    /// The archived mesh, read in place
+    pub fn archived(&self) -> &ArchivedMesh {
+        // SAFETY: the archive was validated in `open`, or the caller vouched for
+        // it in `open_unchecked`.
+        unsafe { rkyv::access_unchecked::<ArchivedMesh>(&self.mmap) }
+    }
+
+    /// Deserialize the full mesh
+    pub fn to_mesh(&self) -> Result<Mesh> {
+        rkyv::deserialize::<Mesh, rkyv::rancor::Error>(self.archived()).map_err(archive_error)
+    }
+}
+
+/// Archives a [`Token`] as its value only; the source text is not preserved
+pub(crate) struct TokenAsValue;
+
+impl ArchiveWith<Token> for TokenAsValue {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    fn resolve_with(field: &Token, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedString::resolve_from_str(&field.value, resolver, out);
+    }
+}
+
+impl<S: Fallible + ?Sized> SerializeWith<Token, S> for TokenAsValue
+where
+    S::Error: Source,
+    str: rkyv::SerializeUnsized<S>,
+{
+    fn serialize_with(
+        field: &Token,
+        serializer: &mut S,
+    ) -> std::result::Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str(&field.value, serializer)
+    }
+}
+
+impl<D: Fallible + ?Sized> DeserializeWith<ArchivedString, Token, D> for TokenAsValue {
+    fn deserialize_with(field: &ArchivedString, _: &mut D) -> std::result::Result<Token, D::Error> {
+        Ok(crate::types::mesh_format::synthesized_token(field.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESH: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $PhysicalNames\n1\n1 1 \"edge\"\n$EndPhysicalNames\n\
+        $Nodes\n1 2 1 2\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+        $Elements\n1 1 1 1\n1 1 1 1\n1 1 2\n$EndElements\n";
+
+    #[test]
+    fn test_archive_round_trip() {
+        let mesh = crate::parse_msh(MESH).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mesh.rkyv");
+        mesh.save_archive(&path).unwrap();
+
+        let mapped = MappedMesh::open(&path).unwrap();
+        let archived = mapped.archived();
+        assert_eq!(archived.node_blocks.len(), 1);
+        assert_eq!(archived.physical_names[0].name.as_str(), "edge");
+        assert_eq!(archived.format.version.major, 4);
+
+        let restored = mapped.to_mesh().unwrap();
+        assert_eq!(restored.format.version.to_string(), "4.1");
+        assert_eq!(
+            restored.element_blocks[0].elements[0].nodes,
+            mesh.element_blocks[0].elements[0].nodes
+        );
+    }
+}
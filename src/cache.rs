@@ -0,0 +1,157 @@
+//! Binary cache of parsed meshes
+//!
+//! Parsing large ASCII meshes is dominated by number conversion. A cache file
+//! stores the parsed [`Mesh`] with bincode next to a hash of the MSH source it
+//! came from, so later runs can skip the parse as long as the source is unchanged.
+//!
+//! Enabled by the `cache` feature.
+
+use crate::error::{ParseError, Result};
+use crate::hash::fnv1a;
+use crate::types::Mesh;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"GMSHBIN\0";
+/// Magic, cache version, source length and source hash
+const HEADER_LEN: usize = 28;
+/// Bumped whenever the encoding of [`Mesh`] changes
+const CACHE_VERSION: u32 = 3;
+
+/// Identity of a cache file's source: `(length, FNV-1a hash)`
+fn source_key(source_path: &Path) -> Result<(u64, u64)> {
+    let bytes = std::fs::read(source_path)?;
+    Ok((bytes.len() as u64, fnv1a(&bytes)))
+}
+
+fn cache_error(error: impl std::fmt::Display) -> ParseError {
+    ParseError::CacheError(error.to_string())
+}
+
+impl Mesh {
+    /// Write this mesh to `cache_path`, keyed by the current contents of `source_path`
+    pub fn save_cache(
+        &self,
+        cache_path: impl AsRef<Path>,
+        source_path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let (len, hash) = source_key(source_path.as_ref())?;
+        let mut writer = BufWriter::new(File::create(cache_path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&CACHE_VERSION.to_le_bytes())?;
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(&hash.to_le_bytes())?;
+        bincode::serialize_into(&mut writer, self).map_err(cache_error)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read a mesh from `cache_path` if it is up to date with `source_path`
+    ///
+    /// Returns `Ok(None)` if the cache is missing, was written by an incompatible
+    /// version of this crate, or the source file changed since it was written.
+    ///
+    /// # Errors
+    /// Returns [`ParseError::CacheError`] if the cache file is truncated or
+    /// cannot be decoded.
+    pub fn load_cache(
+        cache_path: impl AsRef<Path>,
+        source_path: impl AsRef<Path>,
+    ) -> Result<Option<Mesh>> {
+        let file = match File::open(cache_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0u8; HEADER_LEN];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Err(cache_error("truncated cache header"))
+            }
+            Err(e) => return Err(e.into()),
+        }
+        if &header[..8] != MAGIC {
+            return Ok(None);
+        }
+        let version = u32::from_le_bytes(header[8..12].try_into().map_err(cache_error)?);
+        let len = u64::from_le_bytes(header[12..20].try_into().map_err(cache_error)?);
+        let hash = u64::from_le_bytes(header[20..28].try_into().map_err(cache_error)?);
+        if version != CACHE_VERSION || (len, hash) != source_key(source_path.as_ref())? {
+            return Ok(None);
+        }
+
+        let mesh = bincode::deserialize_from(reader).map_err(cache_error)?;
+        Ok(Some(mesh))
+    }
+}
+
+/// Parse `source_path`, going through the cache at `cache_path`
+///
+/// A fresh cache is loaded directly; otherwise the source is parsed and the
+/// cache rewritten.
+pub fn parse_msh_file_cached(
+    source_path: impl AsRef<Path>,
+    cache_path: impl AsRef<Path>,
+) -> Result<Mesh> {
+    if let Some(mesh) = Mesh::load_cache(&cache_path, &source_path)? {
+        return Ok(mesh);
+    }
+    let mesh = crate::parse_msh_file(&source_path)?;
+    mesh.save_cache(&cache_path, &source_path)?;
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::LINE;
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("mesh.msh");
+        let cache = dir.path().join("mesh.bin");
+        std::fs::write(&source, LINE).unwrap();
+
+        let parsed = parse_msh_file_cached(&source, &cache).unwrap();
+        let cached = Mesh::load_cache(&cache, &source).unwrap().unwrap();
+        assert_eq!(cached.format.version.to_string(), "4.1");
+        assert_eq!(cached.node_blocks[0].nodes[1].x, 1.0);
+        assert_eq!(
+            cached.element_blocks[0].elements[0].nodes,
+            parsed.element_blocks[0].elements[0].nodes
+        );
+    }
+
+    #[test]
+    fn test_stale_cache_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("mesh.msh");
+        let cache = dir.path().join("mesh.bin");
+        std::fs::write(&source, LINE).unwrap();
+        parse_msh_file_cached(&source, &cache).unwrap();
+
+        std::fs::write(&source, format!("{}\n", LINE)).unwrap();
+        assert!(Mesh::load_cache(&cache, &source).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_truncated_cache_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("mesh.msh");
+        let cache = dir.path().join("mesh.bin");
+        std::fs::write(&source, "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n").unwrap();
+        Mesh::dummy().save_cache(&cache, &source).unwrap();
+
+        let bytes = std::fs::read(&cache).unwrap();
+        std::fs::write(&cache, &bytes[..HEADER_LEN - 4]).unwrap();
+        assert!(matches!(
+            Mesh::load_cache(&cache, &source),
+            Err(ParseError::CacheError(_))
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! Mesh queries with the arguments and results of the Gmsh SDK
+//!
+//! Each function takes the mesh followed by the arguments of its Gmsh
+//! counterpart in `gmsh::model::mesh` (or `gmsh::model` for
+//! [`get_physical_groups`]), where a negative dimension or entity tag stands
+//! for all, and returns what Gmsh returns through its output arguments, e.g.
+//! coordinates as a flat `x y z` array. Entities unknown to the mesh give
+//! empty results rather than errors.
+
+use crate::filter::carried_physical_tags;
+use crate::types::{ElementTag, ElementType, EntityTag, Mesh, NodeTag, PhysicalTag};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Entities designated by the `dim` and `tag` arguments of a Gmsh query
+enum Selection {
+    All,
+    Dimension(i32),
+    Entities(BTreeSet<(i32, EntityTag)>),
+}
+
+impl Selection {
+    fn new(mesh: &Mesh, dim: i32, tag: i32, include_boundary: bool) -> Self {
+        if dim < 0 {
+            return Self::All;
+        }
+        let graph = mesh.entity_graph().filter(|_| include_boundary);
+        match (graph, tag < 0) {
+            (None, true) => Self::Dimension(dim),
+            (None, false) => Self::Entities([(dim, EntityTag(tag))].into()),
+            (Some(graph), true) => Self::Entities(
+                graph
+                    .entities()
+                    .filter(|entity| entity.0 == dim)
+                    .flat_map(|(dim, tag)| graph.closure(dim, tag))
+                    .collect(),
+            ),
+            (Some(graph), false) => Self::Entities(graph.closure(dim, EntityTag(tag))),
+        }
+    }
+
+    fn contains(&self, dim: i32, tag: EntityTag) -> bool {
+        match self {
+            Self::All => true,
+            Self::Dimension(d) => *d == dim,
+            Self::Entities(entities) => entities.contains(&(dim, tag)),
+        }
+    }
+}
+
+/// Elements of every block, plain and compact, with the dimension, entity and
+/// type of their block
+fn elements(
+    mesh: &Mesh,
+) -> impl Iterator<Item = ((i32, EntityTag, ElementType), ElementTag, &[NodeTag])> {
+    mesh.elements().map(|e| {
+        let block = (e.entity_dim(), e.entity_tag(), e.element_type());
+        (block, e.tag(), e.nodes())
+    })
+}
+
+/// `getNodes(dim, tag, includeBoundary, returnParametricCoord)`: the tags of
+/// the nodes classified on the entity `(dim, tag)`, their coordinates and
+/// their parametric coordinates
+///
+/// With `include_boundary`, the nodes of the entities bounding `(dim, tag)`,
+/// down to points, are included as well. As in Gmsh, parametric coordinates
+/// are only returned for a single entity, i.e. non-negative `dim` and `tag`
+/// without `include_boundary`, and only if its node block is parametric.
+pub fn get_nodes(
+    mesh: &Mesh,
+    dim: i32,
+    tag: i32,
+    include_boundary: bool,
+    return_parametric_coord: bool,
+) -> (Vec<NodeTag>, Vec<f64>, Vec<f64>) {
+    let selection = Selection::new(mesh, dim, tag, include_boundary);
+    let parametric = return_parametric_coord && dim >= 0 && tag >= 0 && !include_boundary;
+    let mut node_tags = Vec::new();
+    let mut coord = Vec::new();
+    let mut parametric_coord = Vec::new();
+    for block in &mesh.node_blocks {
+        if !selection.contains(block.entity_dim(), block.entity_tag()) {
+            continue;
+        }
+        for node in &block.nodes {
+            node_tags.push(node.tag);
+            coord.extend([node.x, node.y, node.z]);
+            if let Some(uvw) = node.parametric_coords.as_ref().filter(|_| parametric) {
+                parametric_coord.extend_from_slice(uvw);
+            }
+        }
+    }
+    (node_tags, coord, parametric_coord)
+}
+
+/// `getElements(dim, tag)`: the element types found on the entity
+/// `(dim, tag)`, in ascending order of their Gmsh code, and for each type the
+/// element tags and their node tags, back to back
+pub fn get_elements(
+    mesh: &Mesh,
+    dim: i32,
+    tag: i32,
+) -> (Vec<i32>, Vec<Vec<ElementTag>>, Vec<Vec<NodeTag>>) {
+    let selection = Selection::new(mesh, dim, tag, false);
+    let mut by_type: BTreeMap<i32, (Vec<ElementTag>, Vec<NodeTag>)> = BTreeMap::new();
+    for ((entity_dim, entity_tag, element_type), element, nodes) in elements(mesh) {
+        if selection.contains(entity_dim, entity_tag) {
+            let (element_tags, node_tags) = by_type.entry(element_type.to_i32()).or_default();
+            element_tags.push(element);
+            node_tags.extend_from_slice(nodes);
+        }
+    }
+    let element_types = by_type.keys().copied().collect();
+    let (element_tags, node_tags) = by_type.into_values().unzip();
+    (element_types, element_tags, node_tags)
+}
+
+/// `getElementsByType(elementType, tag)`: the elements of Gmsh type
+/// `element_type` on the entity `tag`, whose dimension is that of the type,
+/// with their node tags back to back
+pub fn get_elements_by_type(
+    mesh: &Mesh,
+    element_type: i32,
+    tag: i32,
+) -> (Vec<ElementTag>, Vec<NodeTag>) {
+    let mut element_tags = Vec::new();
+    let mut node_tags = Vec::new();
+    for ((_, entity_tag, block_type), element, nodes) in elements(mesh) {
+        if block_type.to_i32() == element_type && (tag < 0 || entity_tag == EntityTag(tag)) {
+            element_tags.push(element);
+            node_tags.extend_from_slice(nodes);
+        }
+    }
+    (element_tags, node_tags)
+}
+
+/// `getPhysicalGroups(dim)`: the `(dimension, tag)` of every physical group,
+/// sorted, either carried by an entity or named in `$PhysicalNames`
+///
+/// Tags are given without their orientation sign.
+pub fn get_physical_groups(mesh: &Mesh, dim: i32) -> Vec<(i32, PhysicalTag)> {
+    let named = mesh
+        .physical_names
+        .iter()
+        .map(|p| (p.dimension as i32, p.tag.magnitude()));
+    let groups: BTreeSet<_> = carried_physical_tags(mesh)
+        .into_iter()
+        .chain(named)
+        .filter(|group| dim < 0 || group.0 == dim)
+        .collect();
+    groups.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit square of four triangles around a centre node, with a node on
+    /// each corner point, the physical curve 5 on the bottom and right edges
+    /// and the physical surface 6, named "My surface"
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $PhysicalNames\n1\n2 6 \"My surface\"\n$EndPhysicalNames\n\
+        $Entities\n4 4 1 0\n1 0 0 0 0\n2 1 0 0 0\n3 1 1 0 0\n4 0 1 0 0\n\
+        1 0 0 0 1 0 0 1 5 2 1 -2\n2 1 0 0 1 1 0 1 5 2 2 -3\n3 0 1 0 1 1 0 0 2 3 -4\n\
+        4 0 0 0 0 1 0 0 2 4 -1\n1 0 0 0 1 1 0 1 6 4 1 2 3 4\n$EndEntities\n\
+        $Nodes\n5 5 1 5\n0 1 0 1\n1\n0 0 0\n0 2 0 1\n2\n1 0 0\n0 3 0 1\n3\n1 1 0\n\
+        0 4 0 1\n4\n0 1 0\n2 1 0 1\n5\n0.5 0.5 0\n$EndNodes\n\
+        $Elements\n5 8 1 8\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n1 4 1 1\n4 4 1\n\
+        2 1 2 4\n5 1 2 5\n6 2 3 5\n7 3 4 5\n8 4 1 5\n$EndElements\n";
+
+    fn tags(tags: &[u64]) -> Vec<NodeTag> {
+        tags.iter().copied().map(NodeTag).collect()
+    }
+
+    #[test]
+    fn test_get_nodes() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let (all, coord, parametric) = get_nodes(&mesh, -1, -1, false, true);
+        assert_eq!(all, tags(&[1, 2, 3, 4, 5]));
+        assert_eq!(coord[12..], [0.5, 0.5, 0.0]);
+        assert!(parametric.is_empty());
+
+        let (interior, _, _) = get_nodes(&mesh, 2, 1, false, false);
+        assert_eq!(interior, tags(&[5]));
+        let (mut closure, _, _) = get_nodes(&mesh, 2, 1, true, false);
+        closure.sort();
+        assert_eq!(closure, all);
+    }
+
+    #[test]
+    fn test_get_elements_of_surface() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let (elements, nodes) = get_elements_by_type(&mesh, 2, 1);
+        assert_eq!(elements, [5, 6, 7, 8].map(ElementTag));
+        assert_eq!(nodes[..3], tags(&[1, 2, 5]));
+
+        let (types, element_tags, node_tags) = get_elements(&mesh, 2, -1);
+        assert_eq!(types, [2]);
+        assert_eq!(element_tags, [elements]);
+        assert_eq!(node_tags, [nodes]);
+        assert_eq!(get_elements(&mesh, -1, -1).0, [1, 2]);
+        assert!(get_elements(&mesh, 2, 99).0.is_empty());
+    }
+
+    #[test]
+    fn test_get_physical_groups() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let groups = get_physical_groups(&mesh, -1);
+        assert_eq!(groups, [(1, PhysicalTag(5)), (2, PhysicalTag(6))]);
+        assert_eq!(get_physical_groups(&mesh, 2), [(2, PhysicalTag(6))]);
+    }
+}
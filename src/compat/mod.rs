@@ -0,0 +1,17 @@
+//! Accessors shaped like other mesh libraries, to ease porting code written
+//! against them
+//!
+//! [`gmsh_api`] mirrors the mesh queries of the Gmsh SDK:
+//!
+//! ```no_run
+//! use gmsh_parser::compat::gmsh_api;
+//! use gmsh_parser::parse_msh_file;
+//!
+//! let mesh = parse_msh_file("model.msh").unwrap();
+//! // nodeTags, coord, parametricCoord = gmsh.model.mesh.getNodes()
+//! let (node_tags, coord, _) = gmsh_api::get_nodes(&mesh, -1, -1, false, false);
+//! // elementTypes, elementTags, nodeTags = gmsh.model.mesh.getElements(2)
+//! let (types, element_tags, element_nodes) = gmsh_api::get_elements(&mesh, 2, -1);
+//! ```
+
+pub mod gmsh_api;
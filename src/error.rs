@@ -1,21 +1,180 @@
+use crate::parser::Span;
 use crate::types::FileType;
-use miette::{Diagnostic, SourceSpan};
+use miette::{Diagnostic, LabeledSpan, SourceSpan};
+use std::fmt;
 use std::sync::Arc;
 use thiserror::Error;
 
 /// Warning generated during parsing (non-fatal issues)
+///
+/// Warnings implement [`Diagnostic`], so they render like errors once given
+/// the text they point into:
+///
+/// ```no_run
+/// use gmsh_parser::parse_msh;
+///
+/// let text = std::fs::read_to_string("model.msh").unwrap();
+/// let mesh = parse_msh(&text).unwrap();
+/// for warning in &mesh.warnings {
+///     let report = miette::Report::new(warning.clone()).with_source_code(text.clone());
+///     eprintln!("{:?}", report);
+/// }
+/// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct ParseWarning {
     /// Description of the warning
     pub message: String,
+    /// Location of the offending data in the file, if known
+    pub span: Option<Span>,
+    pub kind: WarningKind,
+    pub severity: Severity,
+}
+
+/// What a [`ParseWarning`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub enum WarningKind {
+    /// A section the parser does not know, kept in
+    /// [`Mesh::raw_sections`](crate::Mesh::raw_sections)
+    UnknownSection,
+    /// Text outside of any section
+    UnexpectedContent,
+    /// Tokens left at the end of a line, ignored
+    ExtraTokens,
+    /// Counts or tag ranges of a section header that do not match its data
+    MetadataMismatch,
+    /// A `$End` marker missing in lenient mode
+    MissingEndMarker,
+    /// Elements of an unknown type, skipped in lenient mode
+    UnknownElementType,
+    /// Bytes that are not valid UTF-8, replaced
+    InvalidEncoding,
+    /// A construct accepted for compatibility with older files or tools
+    DeprecatedConstruct,
+    #[default]
+    Other,
+}
+
+impl WarningKind {
+    /// Severity of warnings of this kind unless set otherwise
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Self::UnknownSection | Self::DeprecatedConstruct => Severity::Advice,
+            Self::MissingEndMarker | Self::UnknownElementType => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+
+    /// Diagnostic code suffix, e.g. `unknown_section`
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::UnknownSection => "unknown_section",
+            Self::UnexpectedContent => "unexpected_content",
+            Self::ExtraTokens => "extra_tokens",
+            Self::MetadataMismatch => "metadata_mismatch",
+            Self::MissingEndMarker => "missing_end_marker",
+            Self::UnknownElementType => "unknown_element_type",
+            Self::InvalidEncoding => "invalid_encoding",
+            Self::DeprecatedConstruct => "deprecated_construct",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// How much a [`ParseWarning`] matters
+///
+/// [`Error`](Self::Error) marks problems that strict parsing rejects and
+/// lenient parsing recovered from, possibly dropping data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub enum Severity {
+    Advice,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl From<Severity> for miette::Severity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Advice => Self::Advice,
+            Severity::Warning => Self::Warning,
+            Severity::Error => Self::Error,
+        }
+    }
 }
 
 impl ParseWarning {
     pub fn new(message: impl Into<String>) -> Self {
         Self {
             message: message.into(),
+            span: None,
+            kind: WarningKind::Other,
+            severity: Severity::Warning,
         }
     }
+
+    /// Create a warning pointing at `span` in the file
+    pub fn at(message: impl Into<String>, span: impl Into<Span>) -> Self {
+        Self {
+            span: Some(span.into()),
+            ..Self::new(message)
+        }
+    }
+
+    /// Set the kind, and the severity to its default
+    pub fn with_kind(mut self, kind: WarningKind) -> Self {
+        self.kind = kind;
+        self.severity = kind.default_severity();
+        self
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// [`span`](Self::span) as a miette span
+    pub fn source_span(&self) -> Option<SourceSpan> {
+        self.span.map(|span| span.to_source_span())
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ParseWarning {}
+
+impl Diagnostic for ParseWarning {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(format!("gmsh::warning::{}", self.kind.code())))
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(self.severity.into())
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let text = self.kind.code().replace('_', " ");
+        let label = LabeledSpan::new_with_span(Some(text), self.source_span()?);
+        Some(Box::new(std::iter::once(label)))
+    }
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -172,6 +331,9 @@ pub enum ParseError {
     #[error("Unexpected end of file")]
     UnexpectedEof,
 
+    #[error("Empty line where tokens were expected")]
+    EmptyLine,
+
     #[error("Unexpected end of line")]
     UnexpectedEndOfLine {
         expected: String,
@@ -207,6 +369,122 @@ pub enum ParseError {
     #[error("Mesh validation error: {0}")]
     #[diagnostic(code(gmsh::validation))]
     MeshValidationError(String),
+
+    #[error("Geometry error: {0}")]
+    #[diagnostic(code(gmsh::geometry))]
+    GeometryError(String),
+
+    #[error("Not found: {0}")]
+    #[diagnostic(code(gmsh::not_found))]
+    NotFound(String),
+
+    #[error("Cache error: {0}")]
+    #[diagnostic(code(gmsh::cache))]
+    CacheError(String),
+
+    /// Parsing was stopped through a
+    /// [`CancellationToken`](crate::parser::CancellationToken)
+    #[error("Parsing was cancelled")]
+    #[diagnostic(code(gmsh::cancelled))]
+    Cancelled,
 }
 
 pub type Result<T> = std::result::Result<T, ParseError>;
+
+/// Builders for diagnostics pointing into a parsed file
+///
+/// Crates checking a mesh after parsing (boundary conditions, material
+/// assignments, ...) can report problems through [`ParseError`] so they render
+/// exactly like the parser's own errors. Spans come from a
+/// [`SourceMap`](crate::parser::SourceMap) or a [`Token`](crate::parser::Token).
+pub struct Diagnostics;
+
+impl Diagnostics {
+    /// An error labelling `span` of `source` with `message`
+    pub fn error_at(
+        span: impl Into<SourceSpan>,
+        message: impl Into<String>,
+        source: impl Into<Arc<String>>,
+    ) -> ParseError {
+        ParseError::InvalidData {
+            message: message.into(),
+            span: span.into(),
+            msh_content: source.into(),
+        }
+    }
+
+    /// An error labelling the whole of `span`, widened to its line, with `message`
+    ///
+    /// Useful when a check concerns a line rather than a single value.
+    pub fn error_at_line(
+        span: impl Into<SourceSpan>,
+        message: impl Into<String>,
+        source: impl Into<Arc<String>>,
+    ) -> ParseError {
+        let source = source.into();
+        let span = span.into();
+        let start = source
+            .get(..span.offset())
+            .and_then(|before| before.rfind('\n'))
+            .map_or(0, |newline| newline + 1);
+        let end = source
+            .get(span.offset()..)
+            .and_then(|after| after.find('\n'))
+            .map_or(source.len(), |newline| span.offset() + newline);
+        Self::error_at((start, end.saturating_sub(start)), message, source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use miette::GraphicalReportHandler;
+
+    #[test]
+    fn test_error_at_renders_like_parser_errors() {
+        let source = Arc::new("$Nodes\n1 2 1 2\n$EndNodes\n".to_string());
+        let error = Diagnostics::error_at((9, 1), "unexpected entity tag", source.clone());
+
+        let mut rendered = String::new();
+        GraphicalReportHandler::new()
+            .render_report(&mut rendered, &error)
+            .unwrap();
+        assert!(rendered.contains("Invalid data"));
+        assert!(rendered.contains("unexpected entity tag"));
+
+        let line = Diagnostics::error_at_line((9, 1), "bad block", source);
+        match line {
+            ParseError::InvalidData { span, .. } => assert_eq!((span.offset(), span.len()), (7, 7)),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_warnings_render_as_diagnostics() {
+        let text = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Comments\nhello\n$EndComments\n";
+        let mesh = crate::parse_msh(text).unwrap();
+        let warning = &mesh.warnings[0];
+        assert_eq!(warning.kind, WarningKind::UnknownSection);
+        assert_eq!(warning.severity, Severity::Advice);
+        assert_eq!(
+            warning.source_span().unwrap().offset(),
+            text.find("$Comments").unwrap()
+        );
+
+        let report = miette::Report::new(warning.clone()).with_source_code(text.to_string());
+        let mut rendered = String::new();
+        GraphicalReportHandler::new()
+            .render_report(&mut rendered, &*report)
+            .unwrap();
+        assert!(rendered.contains("gmsh::warning::unknown_section"));
+        assert!(rendered.contains("unknown section"));
+        assert!(rendered.contains("$Comments"));
+
+        let plain = ParseWarning::new("note");
+        assert_eq!(
+            (plain.kind, plain.severity),
+            (WarningKind::Other, Severity::Warning)
+        );
+        assert!(plain.labels().is_none());
+    }
+}
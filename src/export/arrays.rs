@@ -0,0 +1,221 @@
+//! Node coordinates and connectivity as flat arrays, for solvers
+
+use crate::error::{ParseError, Result};
+use crate::types::{ElementTag, ElementType, Indexing, Mesh, NodeTag};
+use std::collections::HashMap;
+
+/// Order of the node coordinates in [`MeshArrays::coordinates`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoordinateLayout {
+    /// `x0 y0 z0 x1 y1 z1 ...`, row-major `N x 3`
+    #[default]
+    Interleaved,
+    /// `x0 x1 ... y0 y1 ... z0 z1 ...`, column-major `N x 3`
+    Separate,
+}
+
+/// Conventions of a [`Mesh::to_arrays`] call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ArrayOptions {
+    pub layout: CoordinateLayout,
+    pub indexing: Indexing,
+}
+
+impl ArrayOptions {
+    pub fn with_layout(mut self, layout: CoordinateLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn with_indexing(mut self, indexing: Indexing) -> Self {
+        self.indexing = indexing;
+        self
+    }
+}
+
+/// Nodes and elements of a mesh as flat arrays, see [`Mesh::to_arrays`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshArrays {
+    pub layout: CoordinateLayout,
+    pub indexing: Indexing,
+    /// Node tags in file order; node indices refer to this array
+    pub node_tags: Vec<NodeTag>,
+    /// Node coordinates in the order given by `layout`
+    pub coordinates: Vec<f64>,
+    /// Element tags in file order
+    pub element_tags: Vec<ElementTag>,
+    pub element_types: Vec<ElementType>,
+    /// Start of the nodes of each element in `connectivity`, plus the end of
+    /// the last one, shifted like the indices (the first offset is 1 with
+    /// [`Indexing::OneBased`])
+    pub offsets: Vec<usize>,
+    /// Node indices of every element, back to back
+    pub connectivity: Vec<usize>,
+}
+
+impl MeshArrays {
+    pub fn num_nodes(&self) -> usize {
+        self.node_tags.len()
+    }
+
+    pub fn num_elements(&self) -> usize {
+        self.element_tags.len()
+    }
+
+    /// Coordinates of the node at position `i`
+    pub fn point(&self, i: usize) -> [f64; 3] {
+        match self.layout {
+            CoordinateLayout::Interleaved => {
+                let p = &self.coordinates[3 * i..3 * i + 3];
+                [p[0], p[1], p[2]]
+            }
+            CoordinateLayout::Separate => {
+                let n = self.num_nodes();
+                [
+                    self.coordinates[i],
+                    self.coordinates[n + i],
+                    self.coordinates[2 * n + i],
+                ]
+            }
+        }
+    }
+
+    /// Node indices of the element at position `j`
+    pub fn element_nodes(&self, j: usize) -> &[usize] {
+        let start = self.indexing.position(self.offsets[j]).unwrap_or(0);
+        let end = self.indexing.position(self.offsets[j + 1]).unwrap_or(0);
+        &self.connectivity[start..end]
+    }
+
+    /// The coordinates as an `N x 3` matrix viewing the array, row-major or
+    /// column-major depending on the layout
+    #[cfg(feature = "ndarray")]
+    pub fn coordinate_matrix(&self) -> ndarray::ArrayView2<'_, f64> {
+        use ndarray::ShapeBuilder;
+        let shape = (self.num_nodes(), 3);
+        let view = match self.layout {
+            CoordinateLayout::Interleaved => {
+                ndarray::ArrayView2::from_shape(shape, &self.coordinates)
+            }
+            CoordinateLayout::Separate => {
+                ndarray::ArrayView2::from_shape(shape.f(), &self.coordinates)
+            }
+        };
+        view.expect("coordinates hold three values per node")
+    }
+}
+
+impl Mesh {
+    /// Node coordinates and element connectivity as flat arrays, with the
+    /// coordinate layout and index base required by the caller
+    ///
+    /// Building the arrays in the target convention avoids a second pass over
+    /// large meshes.
+    ///
+    /// # Errors
+    /// Returns [`ParseError::MeshValidationError`] if an element uses a node
+    /// missing from the node blocks.
+    pub fn to_arrays(&self, options: &ArrayOptions) -> Result<MeshArrays> {
+        let nodes: Vec<_> = self.node_blocks.iter().flat_map(|b| &b.nodes).collect();
+        let position: HashMap<NodeTag, usize> =
+            nodes.iter().enumerate().map(|(i, n)| (n.tag, i)).collect();
+        let coordinates = match options.layout {
+            CoordinateLayout::Interleaved => nodes.iter().flat_map(|n| [n.x, n.y, n.z]).collect(),
+            CoordinateLayout::Separate => {
+                let mut values = Vec::with_capacity(3 * nodes.len());
+                values.extend(nodes.iter().map(|n| n.x));
+                values.extend(nodes.iter().map(|n| n.y));
+                values.extend(nodes.iter().map(|n| n.z));
+                values
+            }
+        };
+
+        let indexing = options.indexing;
+        let mut arrays = MeshArrays {
+            layout: options.layout,
+            indexing,
+            node_tags: nodes.iter().map(|n| n.tag).collect(),
+            coordinates,
+            element_tags: Vec::with_capacity(self.num_elements()),
+            element_types: Vec::with_capacity(self.num_elements()),
+            offsets: vec![indexing.index(0)],
+            connectivity: Vec::new(),
+        };
+        let elements = self
+            .elements()
+            .map(|e| (e.tag(), e.element_type(), e.nodes()));
+        for (tag, element_type, element_nodes) in elements {
+            for node in element_nodes {
+                let &i = position.get(node).ok_or_else(|| {
+                    ParseError::MeshValidationError(format!(
+                        "Element {} references missing node {}",
+                        tag, node
+                    ))
+                })?;
+                arrays.connectivity.push(indexing.index(i));
+            }
+            arrays.element_tags.push(tag);
+            arrays.element_types.push(element_type);
+            arrays
+                .offsets
+                .push(indexing.index(arrays.connectivity.len()));
+        }
+        Ok(arrays)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A triangle and one of its edges, with node tags 10, 20 and 30
+    const TRIANGLE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n1 3 10 30\n2 1 0 3\n10\n20\n30\n0 0 0\n1 0 0\n0 1 0\n$EndNodes\n\
+        $Elements\n2 2 1 2\n1 1 1 1\n1 10 20\n2 1 2 1\n2 10 20 30\n$EndElements\n";
+
+    #[test]
+    fn test_interleaved_zero_based_arrays() {
+        let mesh = crate::parse_msh(TRIANGLE).unwrap();
+        let arrays = mesh.to_arrays(&ArrayOptions::default()).unwrap();
+        assert_eq!(arrays.node_tags, [NodeTag(10), NodeTag(20), NodeTag(30)]);
+        assert_eq!(
+            arrays.coordinates,
+            [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+        );
+        assert_eq!(arrays.element_tags, [ElementTag(1), ElementTag(2)]);
+        assert_eq!(arrays.offsets, [0, 2, 5]);
+        assert_eq!(arrays.connectivity, [0, 1, 0, 1, 2]);
+        assert_eq!(arrays.element_nodes(1), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_separate_one_based_arrays() {
+        let mesh = crate::parse_msh(TRIANGLE).unwrap();
+        let options = ArrayOptions::default()
+            .with_layout(CoordinateLayout::Separate)
+            .with_indexing(Indexing::OneBased);
+        let arrays = mesh.to_arrays(&options).unwrap();
+        assert_eq!(
+            arrays.coordinates,
+            [0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]
+        );
+        assert_eq!(arrays.offsets, [1, 3, 6]);
+        assert_eq!(arrays.connectivity, [1, 2, 1, 2, 3]);
+        assert_eq!(arrays.element_nodes(1), [1, 2, 3]);
+        assert_eq!(arrays.point(2), [0.0, 1.0, 0.0]);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_coordinate_matrix_ignores_layout() {
+        let mesh = crate::parse_msh(TRIANGLE).unwrap();
+        let interleaved = mesh.to_arrays(&ArrayOptions::default()).unwrap();
+        let options = ArrayOptions::default().with_layout(CoordinateLayout::Separate);
+        let separate = mesh.to_arrays(&options).unwrap();
+        assert_eq!(
+            interleaved.coordinate_matrix(),
+            separate.coordinate_matrix()
+        );
+        assert_eq!(interleaved.coordinate_matrix()[[1, 0]], 1.0);
+    }
+}
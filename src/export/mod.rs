@@ -0,0 +1,313 @@
+//! Export of meshes to visualization formats
+//!
+//! Exports are described with a builder and written in one go:
+//!
+//! ```no_run
+//! use gmsh_parser::export::ExportFormat::Vtu;
+//! use gmsh_parser::filter::ElementFilter;
+//! use gmsh_parser::parse_msh_file;
+//!
+//! let mesh = parse_msh_file("result.msh").unwrap();
+//! mesh.export()
+//!     .filter(ElementFilter::dimension(3))
+//!     .with_field("T")
+//!     .format(Vtu)
+//!     .to_file("result.vtu")
+//!     .unwrap();
+//! ```
+//!
+//! Surface elements can also be written as STL with [`Mesh::export_stl`].
+//! [`Mesh::to_arrays`] gives the nodes and elements as flat arrays in the
+//! coordinate layout and index base a solver expects.
+
+mod arrays;
+mod stl;
+mod vtk;
+
+pub use crate::types::Indexing;
+pub use arrays::{ArrayOptions, CoordinateLayout, MeshArrays};
+pub use stl::{StlFormat, StlOptions};
+
+use crate::error::{ParseError, Result};
+use crate::filter::ElementFilter;
+use crate::types::{Mesh, NodeTag};
+use crate::writer::WriteOptions;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// VTK XML unstructured grid (`.vtu`), ASCII
+    #[default]
+    Vtu,
+    /// Legacy VTK unstructured grid (`.vtk`), ASCII
+    Vtk,
+}
+
+/// Builder for an export, created by [`Mesh::export`]
+#[derive(Debug, Clone)]
+pub struct Exporter<'a> {
+    mesh: &'a Mesh,
+    filter: ElementFilter,
+    fields: Vec<String>,
+    format: ExportFormat,
+    options: WriteOptions,
+}
+
+impl Mesh {
+    /// Start an export of this mesh (all elements, no fields, VTU format)
+    pub fn export(&self) -> Exporter<'_> {
+        Exporter {
+            mesh: self,
+            filter: ElementFilter::All,
+            fields: Vec::new(),
+            format: ExportFormat::default(),
+            options: WriteOptions::default(),
+        }
+    }
+}
+
+impl<'a> Exporter<'a> {
+    /// Only export elements matching `filter`; repeated calls combine with `and`
+    pub fn filter(mut self, filter: ElementFilter) -> Self {
+        self.filter = match self.filter {
+            ElementFilter::All => filter,
+            current => current.and(filter),
+        };
+        self
+    }
+
+    /// Include the view called `name`, at its last time step
+    ///
+    /// Node data is exported as point data and element data as cell data.
+    pub fn with_field(mut self, name: impl Into<String>) -> Self {
+        self.fields.push(name.into());
+        self
+    }
+
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn options(mut self, options: WriteOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Write the export to `path`
+    ///
+    /// # Errors
+    /// Returns an error if a requested field does not exist, an element type has
+    /// no equivalent in the output format, an element references a missing
+    /// node, or writing fails.
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.to_writer(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the export to `writer`
+    pub fn to_writer<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let data = self.stage()?;
+        let mut numbers = self.options.number_formatter();
+        match self.format {
+            ExportFormat::Vtu => vtk::write_vtu(&data, &mut numbers, writer)?,
+            ExportFormat::Vtk => vtk::write_legacy(&data, &mut numbers, writer)?,
+        }
+        Ok(())
+    }
+
+    /// Collect the points, cells and fields to write
+    fn stage(&self) -> Result<ExportData> {
+        let mut data = ExportData::default();
+        let mut element_index = HashMap::new();
+        let mut used_nodes = HashMap::new();
+
+        for element in self.mesh.iter_elements(&self.filter) {
+            let (cell_type, order) = vtk::cell_type(element.element_type()).ok_or_else(|| {
+                ParseError::MeshValidationError(format!(
+                    "{} elements cannot be exported to {:?}",
+                    element.element_type(),
+                    self.format
+                ))
+            })?;
+            let nodes = element.nodes();
+            let connectivity: Vec<NodeTag> = match order {
+                Some(order) => order.iter().map(|&i| nodes[i]).collect(),
+                None => nodes.to_vec(),
+            };
+            // Points in order of first use; cell connectivity becomes point indices
+            let indices = connectivity
+                .into_iter()
+                .map(|node| {
+                    let next = used_nodes.len();
+                    *used_nodes.entry(node).or_insert(next)
+                })
+                .collect();
+            element_index.insert(element.tag(), data.cells.len());
+            data.cells.push(Cell {
+                cell_type,
+                nodes: indices,
+                entity: (element.entity_dim(), element.entity_tag().0),
+            });
+        }
+
+        let mut points = vec![None; used_nodes.len()];
+        for node in self.mesh.node_blocks.iter().flat_map(|b| b.nodes.iter()) {
+            if let Some(&index) = used_nodes.get(&node.tag) {
+                points[index] = Some([node.x, node.y, node.z]);
+            }
+        }
+        let missing = used_nodes
+            .iter()
+            .filter(|(_, &index)| points[index].is_none())
+            .map(|(&tag, _)| tag)
+            .min();
+        if let Some(tag) = missing {
+            return Err(ParseError::MeshValidationError(format!(
+                "Node {} of an exported element does not exist",
+                tag
+            )));
+        }
+        data.points = points.into_iter().flatten().collect();
+        for name in &self.fields {
+            if let Some(view) = self.mesh.field(name) {
+                data.point_fields.push(Field::gather(
+                    name,
+                    &view.data,
+                    &used_nodes,
+                    data.points.len(),
+                ));
+            } else if let Some(view) = self
+                .mesh
+                .element_data
+                .iter()
+                .rev()
+                .find(|d| d.string_tags.first() == Some(name))
+            {
+                data.cell_fields.push(Field::gather(
+                    name,
+                    &view.data,
+                    &element_index,
+                    data.cells.len(),
+                ));
+            } else {
+                return Err(ParseError::NotFound(format!("View \"{}\"", name)));
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// Points, cells and fields ready to be written
+#[derive(Debug, Default)]
+struct ExportData {
+    points: Vec<[f64; 3]>,
+    cells: Vec<Cell>,
+    point_fields: Vec<Field>,
+    cell_fields: Vec<Field>,
+}
+
+#[derive(Debug)]
+struct Cell {
+    cell_type: u8,
+    /// Point indices in output ordering
+    nodes: Vec<usize>,
+    /// Gmsh entity `(dim, tag)` the element belongs to
+    entity: (i32, i32),
+}
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    components: usize,
+    /// `len * components` values; entries without data are NaN
+    values: Vec<f64>,
+}
+
+impl Field {
+    fn gather<T: Eq + Hash>(
+        name: &str,
+        data: &[(T, Vec<f64>)],
+        index: &HashMap<T, usize>,
+        len: usize,
+    ) -> Self {
+        let components = data.first().map_or(1, |(_, v)| v.len().max(1));
+        let mut values = vec![f64::NAN; len * components];
+        for (tag, v) in data {
+            if let Some(&i) = index.get(tag) {
+                for (c, value) in v.iter().take(components).enumerate() {
+                    values[i * components + c] = *value;
+                }
+            }
+        }
+        Self {
+            name: name.to_string(),
+            components,
+            values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::TRIANGLE;
+
+    #[test]
+    fn test_export_filtered_vtu() {
+        let mesh = crate::parse_msh(TRIANGLE).unwrap();
+        let mut out = Vec::new();
+        mesh.export()
+            .filter(ElementFilter::dimension(2))
+            .format(ExportFormat::Vtu)
+            .to_writer(&mut out)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("NumberOfCells=\"1\""));
+        assert!(text.contains("NumberOfPoints=\"3\""));
+    }
+
+    #[test]
+    fn test_export_vtk() {
+        let mesh = crate::parse_msh(TRIANGLE).unwrap();
+        let mut out = Vec::new();
+        mesh.export()
+            .format(ExportFormat::Vtk)
+            .to_writer(&mut out)
+            .unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("# vtk DataFile"));
+    }
+
+    #[test]
+    fn test_missing_field_is_an_error() {
+        let mesh = crate::parse_msh(TRIANGLE).unwrap();
+        assert!(mesh
+            .export()
+            .with_field("missing")
+            .to_writer(&mut Vec::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_missing_node_is_an_error() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 2 1 2\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+            $Elements\n1 1 1 1\n1 1 1 1\n1 1 2\n$EndElements\n";
+        let mut mesh = crate::parse_msh(content).unwrap();
+        mesh.element_blocks[0].elements[0].nodes[1] = NodeTag(3);
+        match mesh.export().to_writer(&mut Vec::new()) {
+            Err(ParseError::MeshValidationError(message)) => assert!(message.contains("Node 3")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
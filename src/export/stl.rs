@@ -0,0 +1,173 @@
+//! STL export of surface elements
+
+use crate::error::Result;
+use crate::filter::ElementFilter;
+use crate::geometry::node_coordinates;
+use crate::types::{ElementType, Mesh, PhysicalTag};
+use std::io::Write;
+
+/// Encoding of an STL file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StlFormat {
+    /// 80-byte header, triangle count and 50 bytes per triangle
+    #[default]
+    Binary,
+    /// `solid` / `facet` text
+    Ascii,
+}
+
+/// Options of [`Mesh::export_stl`]
+#[derive(Debug, Clone, Default)]
+pub struct StlOptions {
+    pub format: StlFormat,
+    /// Elements to export; non-surface elements are always skipped
+    pub filter: ElementFilter,
+    /// Name of the solid, written in the header
+    pub name: String,
+}
+
+impl StlOptions {
+    pub fn ascii(mut self) -> Self {
+        self.format = StlFormat::Ascii;
+        self
+    }
+
+    /// Only export the elements of the physical group `(dim, tag)`
+    pub fn physical_group(mut self, dim: i32, tag: PhysicalTag) -> Self {
+        self.filter = ElementFilter::physical_group(dim, tag);
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+impl Mesh {
+    /// Write the triangles and quadrangles matching `options.filter` as STL
+    ///
+    /// Quadrangles are split into two triangles along their first diagonal.
+    /// Only the corner nodes of higher-order elements are used, and facet
+    /// normals follow the node ordering. Other element types and elements with
+    /// missing nodes are skipped.
+    ///
+    /// # Errors
+    /// Returns an error if writing fails.
+    pub fn export_stl<W: Write>(&self, writer: &mut W, options: &StlOptions) -> Result<()> {
+        let triangles = self.stl_triangles(&options.filter);
+        match options.format {
+            StlFormat::Binary => {
+                let mut header = [b' '; 80];
+                let name = options.name.as_bytes();
+                let len = name.len().min(header.len());
+                header[..len].copy_from_slice(&name[..len]);
+                writer.write_all(&header)?;
+                writer.write_all(&(triangles.len() as u32).to_le_bytes())?;
+                for triangle in &triangles {
+                    let normal = normal(triangle);
+                    for v in normal.iter().chain(triangle.iter().flatten()) {
+                        writer.write_all(&(*v as f32).to_le_bytes())?;
+                    }
+                    writer.write_all(&[0, 0])?;
+                }
+            }
+            StlFormat::Ascii => {
+                writeln!(writer, "solid {}", options.name)?;
+                for triangle in &triangles {
+                    let [nx, ny, nz] = normal(triangle);
+                    writeln!(writer, "  facet normal {:e} {:e} {:e}", nx, ny, nz)?;
+                    writeln!(writer, "    outer loop")?;
+                    for [x, y, z] in triangle {
+                        writeln!(writer, "      vertex {:e} {:e} {:e}", x, y, z)?;
+                    }
+                    writeln!(writer, "    endloop")?;
+                    writeln!(writer, "  endfacet")?;
+                }
+                writeln!(writer, "endsolid {}", options.name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Corner coordinates of the triangles to export
+    fn stl_triangles(&self, filter: &ElementFilter) -> Vec<[[f64; 3]; 3]> {
+        let coords = node_coordinates(self);
+        let mut triangles = Vec::new();
+        for element in self.iter_elements(filter) {
+            let split: &[[usize; 3]] = match element.element_type() {
+                ElementType::Triangle3
+                | ElementType::Triangle6
+                | ElementType::Triangle9
+                | ElementType::Triangle10 => &[[0, 1, 2]],
+                ElementType::Quadrangle4 | ElementType::Quadrangle8 | ElementType::Quadrangle9 => {
+                    &[[0, 1, 2], [0, 2, 3]]
+                }
+                _ => continue,
+            };
+            let nodes = element.nodes();
+            for corners in split {
+                let points = corners.map(|i| nodes.get(i).and_then(|n| coords.get(n)).copied());
+                if let [Some(a), Some(b), Some(c)] = points {
+                    triangles.push([a, b, c]);
+                }
+            }
+        }
+        triangles
+    }
+}
+
+/// Unit normal of a triangle, or zero if it is degenerate
+fn normal([a, b, c]: &[[f64; 3]; 3]) -> [f64; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length > 0.0 {
+        n.map(|x| x / length)
+    } else {
+        [0.0; 3]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::TAGGED_SQUARE;
+
+    #[test]
+    fn test_export_stl() {
+        let mesh = crate::parse_msh(TAGGED_SQUARE).unwrap();
+        let triangles = 2;
+
+        let mut out = Vec::new();
+        mesh.export_stl(&mut out, &StlOptions::default()).unwrap();
+        assert_eq!(out.len(), 84 + 50 * triangles);
+        assert_eq!(
+            u32::from_le_bytes(out[80..84].try_into().unwrap()) as usize,
+            triangles
+        );
+        // The surface lies in the z = 0 plane, with counterclockwise triangles
+        let nz = f32::from_le_bytes(out[92..96].try_into().unwrap());
+        assert_eq!(nz, 1.0);
+
+        let mut out = Vec::new();
+        let options = StlOptions::default()
+            .ascii()
+            .physical_group(2, PhysicalTag(6))
+            .with_name("surface");
+        mesh.export_stl(&mut out, &options).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("solid surface\n"));
+        assert_eq!(text.matches("endfacet").count(), triangles);
+
+        let mut out = Vec::new();
+        let options = StlOptions::default().physical_group(1, PhysicalTag(5));
+        mesh.export_stl(&mut out, &options).unwrap();
+        assert_eq!(out.len(), 84);
+    }
+}
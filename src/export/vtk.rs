@@ -0,0 +1,230 @@
+//! VTK writers (XML `.vtu` and legacy `.vtk`, both ASCII)
+
+use super::{ExportData, Field};
+use crate::types::ElementType;
+use crate::writer::{FormatNumber, NumberFormatter};
+use std::io::{self, Write};
+
+/// VTK cell type of a Gmsh element type, with the node permutation if the
+/// orderings differ
+pub(super) fn cell_type(element_type: ElementType) -> Option<(u8, Option<&'static [usize]>)> {
+    let mapped = match element_type {
+        ElementType::Point => (1, None),
+        ElementType::Line2 => (3, None),
+        ElementType::Triangle3 => (5, None),
+        ElementType::Quadrangle4 => (9, None),
+        ElementType::Tetrahedron4 => (10, None),
+        ElementType::Hexahedron8 => (12, None),
+        ElementType::Prism6 => (13, None),
+        ElementType::Pyramid5 => (14, None),
+        ElementType::Line3 => (21, None),
+        ElementType::Triangle6 => (22, None),
+        ElementType::Quadrangle8 => (23, None),
+        // Gmsh numbers the last two edges (2,3), (1,3); VTK uses (1,3), (2,3)
+        ElementType::Tetrahedron10 => (24, Some(&[0, 1, 2, 3, 4, 5, 6, 7, 9, 8][..])),
+        ElementType::Quadrangle9 => (28, None),
+        _ => return None,
+    };
+    Some(mapped)
+}
+
+/// Values written per line in data arrays
+const VALUES_PER_LINE: usize = 12;
+
+fn write_values<W: Write, T: FormatNumber>(
+    writer: &mut W,
+    numbers: &mut NumberFormatter,
+    values: impl IntoIterator<Item = T>,
+) -> io::Result<()> {
+    let mut count = 0;
+    for value in values {
+        if count > 0 {
+            let separator: &[u8] = if count % VALUES_PER_LINE == 0 {
+                b"\n"
+            } else {
+                b" "
+            };
+            writer.write_all(separator)?;
+        }
+        writer.write_all(numbers.format(value).as_bytes())?;
+        count += 1;
+    }
+    if count > 0 {
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn write_vtu_array<W: Write>(
+    writer: &mut W,
+    numbers: &mut NumberFormatter,
+    kind: &str,
+    field: &Field,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "<DataArray type=\"{}\" Name=\"{}\" NumberOfComponents=\"{}\" format=\"ascii\">",
+        kind,
+        escape(&field.name),
+        field.components
+    )?;
+    write_values(writer, numbers, field.values.iter().copied())?;
+    writeln!(writer, "</DataArray>")
+}
+
+pub(super) fn write_vtu<W: Write>(
+    data: &ExportData,
+    numbers: &mut NumberFormatter,
+    writer: &mut W,
+) -> io::Result<()> {
+    writeln!(writer, "<?xml version=\"1.0\"?>")?;
+    writeln!(
+        writer,
+        "<VTKFile type=\"UnstructuredGrid\" version=\"1.0\" byte_order=\"LittleEndian\" header_type=\"UInt64\">"
+    )?;
+    writeln!(writer, "<UnstructuredGrid>")?;
+    writeln!(
+        writer,
+        "<Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">",
+        data.points.len(),
+        data.cells.len()
+    )?;
+
+    writeln!(writer, "<PointData>")?;
+    for field in &data.point_fields {
+        write_vtu_array(writer, numbers, "Float64", field)?;
+    }
+    writeln!(writer, "</PointData>")?;
+
+    writeln!(writer, "<CellData>")?;
+    for (name, values) in [
+        (
+            "gmsh:dim",
+            data.cells.iter().map(|c| c.entity.0).collect::<Vec<_>>(),
+        ),
+        (
+            "gmsh:entity",
+            data.cells.iter().map(|c| c.entity.1).collect(),
+        ),
+    ] {
+        writeln!(
+            writer,
+            "<DataArray type=\"Int32\" Name=\"{}\" format=\"ascii\">",
+            name
+        )?;
+        write_values(writer, numbers, values)?;
+        writeln!(writer, "</DataArray>")?;
+    }
+    for field in &data.cell_fields {
+        write_vtu_array(writer, numbers, "Float64", field)?;
+    }
+    writeln!(writer, "</CellData>")?;
+
+    writeln!(writer, "<Points>")?;
+    writeln!(
+        writer,
+        "<DataArray type=\"Float64\" NumberOfComponents=\"3\" format=\"ascii\">"
+    )?;
+    write_values(writer, numbers, data.points.iter().flatten().copied())?;
+    writeln!(writer, "</DataArray>")?;
+    writeln!(writer, "</Points>")?;
+
+    writeln!(writer, "<Cells>")?;
+    writeln!(
+        writer,
+        "<DataArray type=\"Int64\" Name=\"connectivity\" format=\"ascii\">"
+    )?;
+    write_values(
+        writer,
+        numbers,
+        data.cells.iter().flat_map(|c| c.nodes.iter().copied()),
+    )?;
+    writeln!(writer, "</DataArray>")?;
+    writeln!(
+        writer,
+        "<DataArray type=\"Int64\" Name=\"offsets\" format=\"ascii\">"
+    )?;
+    write_values(
+        writer,
+        numbers,
+        data.cells.iter().scan(0, |offset, c| {
+            *offset += c.nodes.len();
+            Some(*offset)
+        }),
+    )?;
+    writeln!(writer, "</DataArray>")?;
+    writeln!(
+        writer,
+        "<DataArray type=\"UInt8\" Name=\"types\" format=\"ascii\">"
+    )?;
+    write_values(writer, numbers, data.cells.iter().map(|c| c.cell_type))?;
+    writeln!(writer, "</DataArray>")?;
+    writeln!(writer, "</Cells>")?;
+
+    writeln!(writer, "</Piece>")?;
+    writeln!(writer, "</UnstructuredGrid>")?;
+    writeln!(writer, "</VTKFile>")
+}
+
+fn write_legacy_fields<W: Write>(
+    writer: &mut W,
+    numbers: &mut NumberFormatter,
+    fields: &[Field],
+    len: usize,
+) -> io::Result<()> {
+    writeln!(writer, "FIELD FieldData {}", fields.len())?;
+    for field in fields {
+        writeln!(
+            writer,
+            "{} {} {} double",
+            field.name.replace(char::is_whitespace, "_"),
+            field.components,
+            len
+        )?;
+        write_values(writer, numbers, field.values.iter().copied())?;
+    }
+    Ok(())
+}
+
+pub(super) fn write_legacy<W: Write>(
+    data: &ExportData,
+    numbers: &mut NumberFormatter,
+    writer: &mut W,
+) -> io::Result<()> {
+    writeln!(writer, "# vtk DataFile Version 3.0")?;
+    writeln!(writer, "gmsh-parser export")?;
+    writeln!(writer, "ASCII")?;
+    writeln!(writer, "DATASET UNSTRUCTURED_GRID")?;
+
+    writeln!(writer, "POINTS {} double", data.points.len())?;
+    for p in &data.points {
+        write_values(writer, numbers, p.iter().copied())?;
+    }
+
+    let size: usize = data.cells.iter().map(|c| c.nodes.len() + 1).sum();
+    writeln!(writer, "CELLS {} {}", data.cells.len(), size)?;
+    for cell in &data.cells {
+        let count = std::iter::once(cell.nodes.len());
+        write_values(writer, numbers, count.chain(cell.nodes.iter().copied()))?;
+    }
+    writeln!(writer, "CELL_TYPES {}", data.cells.len())?;
+    write_values(writer, numbers, data.cells.iter().map(|c| c.cell_type))?;
+
+    if !data.point_fields.is_empty() {
+        writeln!(writer, "POINT_DATA {}", data.points.len())?;
+        write_legacy_fields(writer, numbers, &data.point_fields, data.points.len())?;
+    }
+    if !data.cell_fields.is_empty() {
+        writeln!(writer, "CELL_DATA {}", data.cells.len())?;
+        write_legacy_fields(writer, numbers, &data.cell_fields, data.cells.len())?;
+    }
+    Ok(())
+}
+
+/// Escape a string for use in an XML attribute
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
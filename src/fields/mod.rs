@@ -0,0 +1,28 @@
+//! Analysis of post-processing fields on the mesh
+//!
+//! `rasterize` (with the `ndarray` feature) samples an element field onto a
+//! regular grid, e.g. to write it as an image or compare it with measurements.
+//!
+//! [`trace_streamlines`] integrates the streamlines of a node vector field:
+//!
+//! ```no_run
+//! use gmsh_parser::fields::{trace_streamlines, StreamlineParams};
+//! use gmsh_parser::parse_msh_file;
+//!
+//! let mesh = parse_msh_file("flow.msh").unwrap();
+//! let velocity = mesh.field("velocity").unwrap();
+//! let seeds = [[0.0, 0.5, 0.5], [0.0, 0.25, 0.5]];
+//! let lines = trace_streamlines(&mesh, velocity, &seeds, &StreamlineParams::default()).unwrap();
+//! for line in &lines {
+//!     println!("{:.3} long, stopped by {:?}", line.length(), line.termination);
+//! }
+//! ```
+
+#[cfg(feature = "ndarray")]
+mod raster;
+pub(crate) mod simplex;
+mod streamlines;
+
+#[cfg(feature = "ndarray")]
+pub use raster::{rasterize, GridSpec};
+pub use streamlines::{trace_streamlines, Streamline, StreamlineParams, Termination};
@@ -0,0 +1,198 @@
+//! Sampling of element data onto structured grids
+
+use super::simplex::{barycentric, is_inside, simplices};
+use crate::geometry::{bounds, node_coordinates, Tolerance};
+use crate::types::{ElementData, ElementTag, Mesh};
+use ndarray::{s, Array3};
+use std::collections::HashMap;
+
+/// A regular grid of sample points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridSpec {
+    /// Coordinates of the point with index `[0, 0, 0]`
+    pub origin: [f64; 3],
+    /// Distance between neighbouring points along each axis
+    pub spacing: [f64; 3],
+    /// Number of points along each axis
+    pub shape: [usize; 3],
+}
+
+impl GridSpec {
+    pub fn new(origin: [f64; 3], spacing: [f64; 3], shape: [usize; 3]) -> Self {
+        Self {
+            origin,
+            spacing,
+            shape,
+        }
+    }
+
+    /// A grid spanning the bounding box of the mesh nodes, with `shape` points
+    /// along each axis
+    ///
+    /// Axes with a single point sit at the lower bound. Returns `None` for a mesh
+    /// without nodes.
+    pub fn covering(mesh: &Mesh, shape: [usize; 3]) -> Option<Self> {
+        let nodes = mesh.node_blocks.iter().flat_map(|b| &b.nodes);
+        let (min, max) = bounds(nodes.map(|n| [n.x, n.y, n.z]))?;
+        let spacing = std::array::from_fn(|axis| {
+            if shape[axis] > 1 {
+                (max[axis] - min[axis]) / (shape[axis] - 1) as f64
+            } else {
+                0.0
+            }
+        });
+        Some(Self::new(min, spacing, shape))
+    }
+
+    /// Coordinates of the point at `index`
+    pub fn point(&self, index: [usize; 3]) -> [f64; 3] {
+        std::array::from_fn(|axis| self.origin[axis] + index[axis] as f64 * self.spacing[axis])
+    }
+
+    /// Indices along `axis` of the points within `[min, max]`
+    fn indices(
+        &self,
+        axis: usize,
+        min: f64,
+        max: f64,
+        tolerance: &Tolerance,
+    ) -> std::ops::Range<usize> {
+        let (origin, spacing, n) = (self.origin[axis], self.spacing[axis], self.shape[axis]);
+        let slack = tolerance.slack((max - min).abs().max(spacing.abs()));
+        if spacing <= 0.0 {
+            return if n > 0 && min - slack <= origin && origin <= max + slack {
+                0..1
+            } else {
+                0..0
+            };
+        }
+        let first = ((min - slack - origin) / spacing).ceil().max(0.0);
+        let last = ((max + slack - origin) / spacing).floor() + 1.0;
+        first as usize..(last.max(0.0) as usize).min(n)
+    }
+}
+
+/// Sample an element field at the points of `grid`
+///
+/// Every grid point takes the value of the element containing it, so the result
+/// is piecewise constant. Multi-component fields are sampled by their Euclidean
+/// norm. Points outside every element carrying data are `NaN`.
+///
+/// Volume elements are located in 3D. Surface elements are located by their
+/// projection onto the xy-plane, which suits planar 2D meshes sampled with a
+/// single layer of points along z. Elements are split into simplices through
+/// their corner nodes; types other than triangles, quadrangles, tetrahedra,
+/// hexahedra, prisms and pyramids are ignored.
+///
+/// ```no_run
+/// use gmsh_parser::fields::{rasterize, GridSpec};
+/// use gmsh_parser::parse_msh_file;
+///
+/// let mesh = parse_msh_file("result.msh").unwrap();
+/// let grid = GridSpec::covering(&mesh, [64, 64, 64]).unwrap();
+/// let image = rasterize(&mesh, &mesh.element_data[0], &grid);
+/// println!("value at the centre: {}", image[[32, 32, 32]]);
+/// ```
+pub fn rasterize(mesh: &Mesh, data: &ElementData, grid: &GridSpec) -> Array3<f64> {
+    let mut image = Array3::from_elem(grid.shape, f64::NAN);
+    let values: HashMap<ElementTag, f64> = data
+        .data
+        .iter()
+        .map(|(tag, v)| (*tag, v.iter().map(|c| c * c).sum::<f64>().sqrt()))
+        .collect();
+    let coordinates = node_coordinates(mesh);
+    let tolerance = mesh.tolerance();
+
+    for block in mesh.element_block_refs() {
+        let Some(simplices) = simplices(block.element_type()) else {
+            continue;
+        };
+        for element in block.elements() {
+            let Some(&value) = values.get(&element.tag()) else {
+                continue;
+            };
+            for simplex in simplices {
+                let corners: Option<Vec<[f64; 3]>> = simplex
+                    .iter()
+                    .map(|&i| coordinates.get(element.nodes().get(i)?).copied())
+                    .collect();
+                let Some(corners) = corners else {
+                    continue;
+                };
+                sample_simplex(&mut image, grid, &corners, value, &tolerance);
+            }
+        }
+    }
+    image
+}
+
+/// Set `value` at every grid point inside the triangle or tetrahedron `corners`
+fn sample_simplex(
+    image: &mut Array3<f64>,
+    grid: &GridSpec,
+    corners: &[[f64; 3]],
+    value: f64,
+    tolerance: &Tolerance,
+) {
+    let Some((min, max)) = bounds(corners.iter().copied()) else {
+        return;
+    };
+    let planar = corners.len() == 3;
+    for i in grid.indices(0, min[0], max[0], tolerance) {
+        for j in grid.indices(1, min[1], max[1], tolerance) {
+            if planar {
+                if contains(corners, grid.point([i, j, 0]), tolerance) {
+                    image.slice_mut(s![i, j, ..]).fill(value);
+                }
+                continue;
+            }
+            for k in grid.indices(2, min[2], max[2], tolerance) {
+                if contains(corners, grid.point([i, j, k]), tolerance) {
+                    image[[i, j, k]] = value;
+                }
+            }
+        }
+    }
+}
+
+fn contains(corners: &[[f64; 3]], p: [f64; 3], tolerance: &Tolerance) -> bool {
+    barycentric(corners, p).is_some_and(|w| is_inside(&w, tolerance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    #[test]
+    fn test_rasterize_planar_mesh() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+            $Elements\n1 2 1 2\n2 1 2 2\n1 1 2 3\n2 1 3 4\n$EndElements\n\
+            $ElementData\n1\n\"constant\"\n1\n0\n3\n0\n1\n2\n1 2\n2 2\n$EndElementData\n";
+        let mesh = parse_msh(content).unwrap();
+
+        let grid = GridSpec::covering(&mesh, [7, 9, 1]).unwrap();
+        let image = rasterize(&mesh, &mesh.element_data[0], &grid);
+        assert_eq!(image.shape(), &[7, 9, 1]);
+        assert!(image.iter().all(|&v| v == 2.0));
+    }
+
+    #[test]
+    fn test_rasterize_hexahedron() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 8 1 8\n3 1 0 8\n1\n2\n3\n4\n5\n6\n7\n8\n\
+            0 0 0\n1 0 0\n1 1 0\n0 1 0\n0 0 1\n1 0 1\n1 1 1\n0 1 1\n$EndNodes\n\
+            $Elements\n1 1 1 1\n3 1 5 1\n1 1 2 3 4 5 6 7 8\n$EndElements\n\
+            $ElementData\n1\n\"velocity\"\n1\n0\n3\n0\n3\n1\n1 3 4 0\n$EndElementData\n";
+        let mesh = parse_msh(content).unwrap();
+
+        // Points at -0.5, 0, 0.5, 1 and 1.5 along each axis
+        let grid = GridSpec::new([-0.5; 3], [0.5; 3], [5, 5, 5]);
+        let image = rasterize(&mesh, &mesh.element_data[0], &grid);
+        let inside = image.iter().filter(|v| !v.is_nan()).count();
+        assert_eq!(inside, 27);
+        assert_eq!(image[[1, 2, 3]], 5.0);
+        assert!(image[[0, 2, 2]].is_nan());
+    }
+}
@@ -0,0 +1,80 @@
+//! Splitting elements into simplices and locating points inside them
+
+use crate::geometry::Tolerance;
+use crate::types::ElementType;
+
+/// Split of an element into triangles or tetrahedra, as indices of its corner nodes
+///
+/// Only corner nodes are used, so higher-order elements are treated as linear.
+pub(crate) fn simplices(element_type: ElementType) -> Option<&'static [&'static [usize]]> {
+    use ElementType::*;
+    let split: &'static [&'static [usize]] = match element_type {
+        Triangle3 | Triangle6 | Triangle9 | Triangle10 => &[&[0, 1, 2]],
+        Quadrangle4 | Quadrangle8 | Quadrangle9 => &[&[0, 1, 2], &[0, 2, 3]],
+        Tetrahedron4 | Tetrahedron10 => &[&[0, 1, 2, 3]],
+        Hexahedron8 | Hexahedron20 | Hexahedron27 => &[
+            &[0, 1, 2, 6],
+            &[0, 2, 3, 6],
+            &[0, 3, 7, 6],
+            &[0, 7, 4, 6],
+            &[0, 4, 5, 6],
+            &[0, 5, 1, 6],
+        ],
+        Prism6 | Prism15 | Prism18 => &[&[0, 1, 2, 5], &[0, 1, 5, 4], &[0, 4, 5, 3]],
+        Pyramid5 | Pyramid13 | Pyramid14 => &[&[0, 1, 2, 4], &[0, 2, 3, 4]],
+        _ => return None,
+    };
+    Some(split)
+}
+
+/// Barycentric coordinates of `p` in a triangle or tetrahedron
+///
+/// Triangles are taken in their projection onto the xy-plane and leave the
+/// fourth coordinate at zero. Returns `None` for degenerate simplices.
+pub(crate) fn barycentric(corners: &[[f64; 3]], p: [f64; 3]) -> Option<[f64; 4]> {
+    match corners {
+        [a, b, c] => {
+            let cross = |a: [f64; 3], b: [f64; 3], q: [f64; 3]| {
+                (b[0] - a[0]) * (q[1] - a[1]) - (b[1] - a[1]) * (q[0] - a[0])
+            };
+            let area = cross(*a, *b, *c);
+            if area == 0.0 {
+                return None;
+            }
+            Some([
+                cross(*b, *c, p) / area,
+                cross(*c, *a, p) / area,
+                cross(*a, *b, p) / area,
+                0.0,
+            ])
+        }
+        [a, b, c, d] => {
+            let det = |a: [f64; 3], b: [f64; 3], d: [f64; 3], q: [f64; 3]| {
+                let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+                let v = [d[0] - a[0], d[1] - a[1], d[2] - a[2]];
+                let w = [q[0] - a[0], q[1] - a[1], q[2] - a[2]];
+                u[0] * (v[1] * w[2] - v[2] * w[1]) - u[1] * (v[0] * w[2] - v[2] * w[0])
+                    + u[2] * (v[0] * w[1] - v[1] * w[0])
+            };
+            let volume = det(*a, *b, *c, *d);
+            if volume == 0.0 {
+                return None;
+            }
+            Some([
+                det(p, *b, *c, *d) / volume,
+                det(*a, p, *c, *d) / volume,
+                det(*a, *b, p, *d) / volume,
+                det(*a, *b, *c, p) / volume,
+            ])
+        }
+        _ => None,
+    }
+}
+
+/// Whether barycentric coordinates describe a point inside the simplex
+///
+/// The coordinates may be negative by the relative tolerance, so that points
+/// lying on element faces are found.
+pub(crate) fn is_inside(weights: &[f64; 4], tolerance: &Tolerance) -> bool {
+    weights.iter().all(|&w| w >= -tolerance.relative)
+}
@@ -0,0 +1,383 @@
+//! Streamlines of node vector fields
+
+use super::simplex::{barycentric, is_inside, simplices};
+use crate::error::{ParseError, Result};
+use crate::geometry::{bounds, distance, node_coordinates, Tolerance};
+use crate::types::{Mesh, NodeData, NodeTag};
+use std::collections::HashMap;
+
+/// Integration settings for [`trace_streamlines`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamlineParams {
+    /// Distance between consecutive points, by default a fifth of the mean
+    /// element size
+    pub step: Option<f64>,
+    /// Largest number of steps per streamline
+    pub max_steps: usize,
+    /// Speed below which the flow is considered stagnant
+    pub min_speed: f64,
+    /// Trace against the flow instead of along it
+    pub backward: bool,
+    /// Tolerance for locating points on element faces and the mesh boundary,
+    /// by default [`Mesh::tolerance`]
+    pub tolerance: Option<Tolerance>,
+}
+
+impl Default for StreamlineParams {
+    fn default() -> Self {
+        Self {
+            step: None,
+            max_steps: 1000,
+            min_speed: 1e-12,
+            backward: false,
+            tolerance: None,
+        }
+    }
+}
+
+impl StreamlineParams {
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn with_min_speed(mut self, min_speed: f64) -> Self {
+        self.min_speed = min_speed;
+        self
+    }
+
+    pub fn backward(mut self) -> Self {
+        self.backward = true;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+}
+
+/// Why a streamline stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Termination {
+    /// The next step would leave the elements carrying data
+    LeftMesh,
+    /// [`StreamlineParams::max_steps`] was reached
+    MaxSteps,
+    /// The speed dropped below [`StreamlineParams::min_speed`]
+    Stagnation,
+    /// The seed is outside the elements carrying data
+    SeedOutside,
+}
+
+/// A traced streamline
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Streamline {
+    /// Points along the streamline, starting at the seed
+    pub points: Vec<[f64; 3]>,
+    pub termination: Termination,
+}
+
+impl Streamline {
+    /// Length of the polyline through the points
+    pub fn length(&self) -> f64 {
+        self.points.windows(2).map(|w| distance(w[0], w[1])).sum()
+    }
+}
+
+/// Trace the streamlines of a node vector field from `seeds`
+///
+/// The field is interpolated linearly inside each element from the values at
+/// its corner nodes, through the barycentric coordinates of the point in the
+/// triangles or tetrahedra the element splits into. Streamlines are integrated
+/// with fourth-order Runge-Kutta steps of fixed length along the normalized
+/// velocity, so their points are evenly spaced whatever the speed.
+///
+/// Volume elements are used if the mesh has any; otherwise surface elements are
+/// used in their projection onto the xy-plane, as for planar 2D meshes. Elements
+/// with a corner node missing from the field are treated as outside the mesh.
+///
+/// # Errors
+/// Returns an error if the field does not have three components per node or no
+/// element carries data.
+pub fn trace_streamlines(
+    mesh: &Mesh,
+    field: &NodeData,
+    seeds: &[[f64; 3]],
+    params: &StreamlineParams,
+) -> Result<Vec<Streamline>> {
+    let tolerance = params.tolerance.unwrap_or_else(|| mesh.tolerance());
+    let locator = Locator::new(mesh, field, tolerance)?;
+    let step = params.step.unwrap_or(locator.mean_size / 5.0);
+    let sign = if params.backward { -1.0 } else { 1.0 };
+
+    let direction = |p: [f64; 3]| -> Option<([f64; 3], f64)> {
+        let v = locator.velocity(p)?;
+        let speed = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+        let scale = if speed > 0.0 { sign / speed } else { 0.0 };
+        Some((v.map(|c| c * scale), speed))
+    };
+
+    let trace = |seed: [f64; 3]| -> Streamline {
+        let mut points = vec![seed];
+        let mut p = seed;
+        for _ in 0..params.max_steps {
+            let termination = match rk4_step(&direction, p, step, params.min_speed) {
+                Ok(next) => {
+                    points.push(next);
+                    p = next;
+                    continue;
+                }
+                Err(termination) => termination,
+            };
+            let termination = if points.len() == 1 && termination == Termination::LeftMesh {
+                Termination::SeedOutside
+            } else {
+                termination
+            };
+            return Streamline {
+                points,
+                termination,
+            };
+        }
+        Streamline {
+            points,
+            termination: Termination::MaxSteps,
+        }
+    };
+
+    Ok(seeds.iter().map(|&seed| trace(seed)).collect())
+}
+
+/// Advance `p` by `h` along the unit direction field, or tell why it cannot
+fn rk4_step<F>(
+    direction: &F,
+    p: [f64; 3],
+    h: f64,
+    min_speed: f64,
+) -> std::result::Result<[f64; 3], Termination>
+where
+    F: Fn([f64; 3]) -> Option<([f64; 3], f64)>,
+{
+    let offset = |k: [f64; 3], t: f64| [p[0] + t * k[0], p[1] + t * k[1], p[2] + t * k[2]];
+    let (k1, speed) = direction(p).ok_or(Termination::LeftMesh)?;
+    if speed < min_speed {
+        return Err(Termination::Stagnation);
+    }
+    let (k2, _) = direction(offset(k1, h / 2.0)).ok_or(Termination::LeftMesh)?;
+    let (k3, _) = direction(offset(k2, h / 2.0)).ok_or(Termination::LeftMesh)?;
+    let (k4, _) = direction(offset(k3, h)).ok_or(Termination::LeftMesh)?;
+    let next: [f64; 3] =
+        std::array::from_fn(|i| p[i] + h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]));
+    // The end point must also be inside, or the streamline would leave the mesh
+    direction(next).ok_or(Termination::LeftMesh)?;
+    Ok(next)
+}
+
+/// A triangle or tetrahedron with the field values at its corners
+struct Cell {
+    corners: Vec<[f64; 3]>,
+    values: Vec<[f64; 3]>,
+}
+
+/// Finds the cell containing a point through a uniform grid of buckets
+struct Locator {
+    cells: Vec<Cell>,
+    planar: bool,
+    min: [f64; 3],
+    extent: [f64; 3],
+    dims: [usize; 3],
+    buckets: Vec<Vec<usize>>,
+    mean_size: f64,
+    tolerance: Tolerance,
+}
+
+impl Locator {
+    fn new(mesh: &Mesh, field: &NodeData, tolerance: Tolerance) -> Result<Self> {
+        let values = field
+            .data
+            .iter()
+            .map(|(tag, v)| match v.as_slice() {
+                &[x, y, z] => Ok((*tag, [x, y, z])),
+                _ => Err(ParseError::GeometryError(format!(
+                    "Streamlines need a vector field with 3 components, node {} has {}",
+                    tag,
+                    v.len()
+                ))),
+            })
+            .collect::<Result<HashMap<NodeTag, [f64; 3]>>>()?;
+        let coordinates = node_coordinates(mesh);
+
+        let planar = !mesh.element_block_refs().any(|b| b.entity_dim() == 3);
+        let dim = if planar { 2 } else { 3 };
+        let mut cells = Vec::new();
+        for block in mesh.element_block_refs().filter(|b| b.entity_dim() == dim) {
+            let Some(split) = simplices(block.element_type()) else {
+                continue;
+            };
+            for element in block.elements() {
+                for simplex in split {
+                    let nodes: Option<Vec<NodeTag>> = simplex
+                        .iter()
+                        .map(|&i| element.nodes().get(i).copied())
+                        .collect();
+                    let Some(nodes) = nodes else {
+                        continue;
+                    };
+                    let corners: Option<Vec<_>> =
+                        nodes.iter().map(|n| coordinates.get(n).copied()).collect();
+                    let values: Option<Vec<_>> =
+                        nodes.iter().map(|n| values.get(n).copied()).collect();
+                    if let (Some(corners), Some(values)) = (corners, values) {
+                        cells.push(Cell { corners, values });
+                    }
+                }
+            }
+        }
+
+        let (min, max) =
+            bounds(cells.iter().flat_map(|c| c.corners.iter().copied())).ok_or_else(|| {
+                ParseError::GeometryError("No element carries data of the field".to_string())
+            })?;
+        let extent: [f64; 3] = std::array::from_fn(|i| max[i] - min[i]);
+        let per_axis = (cells.len() as f64).powf(1.0 / dim as f64).ceil() as usize;
+        let dims = std::array::from_fn(|i| {
+            if extent[i] > 0.0 && (i < 2 || !planar) {
+                per_axis.max(1)
+            } else {
+                1
+            }
+        });
+
+        let mut locator = Self {
+            cells: Vec::new(),
+            planar,
+            min,
+            extent,
+            dims,
+            buckets: vec![Vec::new(); dims[0] * dims[1] * dims[2]],
+            mean_size: 0.0,
+            tolerance,
+        };
+        let mut total_size = 0.0;
+        for (index, cell) in cells.iter().enumerate() {
+            let Some((lo, hi)) = bounds(cell.corners.iter().copied()) else {
+                continue;
+            };
+            total_size += distance(lo, hi);
+            let (lo, hi) = (locator.bucket_of(lo), locator.bucket_of(hi));
+            for i in lo[0]..=hi[0] {
+                for j in lo[1]..=hi[1] {
+                    for k in lo[2]..=hi[2] {
+                        locator.buckets[(k * dims[1] + j) * dims[0] + i].push(index);
+                    }
+                }
+            }
+        }
+        locator.mean_size = total_size / cells.len() as f64;
+        locator.cells = cells;
+        Ok(locator)
+    }
+
+    fn bucket_of(&self, p: [f64; 3]) -> [usize; 3] {
+        std::array::from_fn(|i| {
+            if self.dims[i] == 1 {
+                return 0;
+            }
+            let t = (p[i] - self.min[i]) / self.extent[i];
+            ((t * self.dims[i] as f64).floor().max(0.0) as usize).min(self.dims[i] - 1)
+        })
+    }
+
+    /// Interpolated field value at `p`, `None` outside every cell
+    fn velocity(&self, p: [f64; 3]) -> Option<[f64; 3]> {
+        let outside = (0..3).any(|i| {
+            let slack = self.tolerance.slack(self.extent[i].max(self.mean_size));
+            (i < 2 || !self.planar)
+                && (p[i] < self.min[i] - slack || p[i] > self.min[i] + self.extent[i] + slack)
+        });
+        if outside {
+            return None;
+        }
+
+        let [i, j, k] = self.bucket_of(p);
+        let bucket = &self.buckets[(k * self.dims[1] + j) * self.dims[0] + i];
+        bucket.iter().find_map(|&index| {
+            let cell = &self.cells[index];
+            let weights = barycentric(&cell.corners, p)?;
+            if !is_inside(&weights, &self.tolerance) {
+                return None;
+            }
+            let mut v = [0.0; 3];
+            for (weight, value) in weights.iter().zip(&cell.values) {
+                for axis in 0..3 {
+                    v[axis] += weight * value[axis];
+                }
+            }
+            Some(v)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SQUARE;
+
+    fn node_field(mesh: &Mesh, f: impl Fn([f64; 3]) -> [f64; 3]) -> NodeData {
+        let nodes = mesh.node_blocks.iter().flat_map(|b| &b.nodes);
+        NodeData {
+            string_tags: vec!["velocity".to_string()],
+            real_tags: vec![0.0],
+            integer_tags: vec![0, 3, 0],
+            data: nodes
+                .map(|n| (n.tag, f([n.x, n.y, n.z]).to_vec()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_rotation_keeps_radius() {
+        // The rotation about the centre is linear, so it is interpolated exactly
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let field = node_field(&mesh, |p| [-(p[1] - 0.5), p[0] - 0.5, 0.0]);
+        let params = StreamlineParams::default()
+            .with_step(1e-2)
+            .with_max_steps(100);
+
+        let lines = trace_streamlines(&mesh, &field, &[[0.8, 0.5, 0.0]], &params).unwrap();
+        let line = &lines[0];
+        assert_eq!(line.termination, Termination::MaxSteps);
+        assert_eq!(line.points.len(), 101);
+        for p in &line.points {
+            let radius = distance(*p, [0.5, 0.5, 0.0]);
+            assert!((radius - 0.3).abs() < 1e-8, "radius {}", radius);
+        }
+        assert!((line.length() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_streamline_leaves_mesh() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let field = node_field(&mesh, |_| [0.0, 2.0, 0.0]);
+        let params = StreamlineParams::default().with_step(0.1);
+        let seeds = [[0.5, 0.0, 0.0], [5.0, 5.0, 0.0]];
+
+        let lines = trace_streamlines(&mesh, &field, &seeds, &params).unwrap();
+        assert_eq!(lines[0].termination, Termination::LeftMesh);
+        let end = lines[0].points.last().unwrap();
+        assert!(end[1] > 0.89 && (end[0] - 0.5).abs() < 1e-12);
+        assert_eq!(lines[1].termination, Termination::SeedOutside);
+
+        let backward = params.backward();
+        let lines = trace_streamlines(&mesh, &field, &seeds[..1], &backward).unwrap();
+        assert_eq!(lines[0].points.len(), 1);
+    }
+}
@@ -0,0 +1,467 @@
+//! Composable element filters
+//!
+//! An [`ElementFilter`] describes a subset of the elements of a mesh. Filters are
+//! combined with [`and`](ElementFilter::and), [`or`](ElementFilter::or) and `!`,
+//! and accepted wherever the crate iterates over, extracts, counts or exports
+//! elements:
+//!
+//! ```no_run
+//! use gmsh_parser::filter::ElementFilter;
+//! use gmsh_parser::{parse_msh_file, ElementType, PhysicalTag};
+//!
+//! let mesh = parse_msh_file("model.msh").unwrap();
+//! let filter = ElementFilter::physical_group(3, PhysicalTag(1))
+//!     .and(ElementFilter::of_type(ElementType::Tetrahedron4))
+//!     .and(!ElementFilter::centroid(|[x, _, _]| x < 0.0));
+//! println!("{} elements", mesh.count_elements(&filter));
+//! ```
+
+use crate::error::Result;
+use crate::geometry::node_coordinates;
+use crate::sets::{physical_entities, ElementSet};
+use crate::types::element::Element;
+use crate::types::{
+    ElementBlock, ElementRef, ElementTag, ElementType, EntityTag, Mesh, NodeTag, PhysicalTag,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// Predicate on an element centroid
+pub type CentroidPredicate = Arc<dyn Fn([f64; 3]) -> bool + Send + Sync>;
+
+/// A composable predicate over the elements of a mesh
+#[derive(Clone, Default)]
+pub enum ElementFilter {
+    /// Every element
+    #[default]
+    All,
+    /// Elements of the given type
+    Type(ElementType),
+    /// Elements in blocks of the given entity dimension
+    Dimension(i32),
+    /// Elements of entities carrying the physical tag `(dim, tag)`
+    PhysicalGroup(i32, PhysicalTag),
+    /// Elements of the entity `(dim, tag)`
+    Entity(i32, EntityTag),
+    /// Elements whose tag lies in the range
+    TagRange(RangeInclusive<ElementTag>),
+    /// Elements whose centroid satisfies the predicate
+    Centroid(CentroidPredicate),
+    /// Elements matching every filter
+    And(Vec<ElementFilter>),
+    /// Elements matching at least one filter
+    Or(Vec<ElementFilter>),
+    /// Elements not matching the filter
+    Not(Box<ElementFilter>),
+}
+
+impl fmt::Debug for ElementFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElementFilter::All => write!(f, "All"),
+            ElementFilter::Type(t) => f.debug_tuple("Type").field(t).finish(),
+            ElementFilter::Dimension(d) => f.debug_tuple("Dimension").field(d).finish(),
+            ElementFilter::PhysicalGroup(d, t) => {
+                f.debug_tuple("PhysicalGroup").field(d).field(t).finish()
+            }
+            ElementFilter::Entity(d, t) => f.debug_tuple("Entity").field(d).field(t).finish(),
+            ElementFilter::TagRange(r) => f.debug_tuple("TagRange").field(r).finish(),
+            ElementFilter::Centroid(_) => write!(f, "Centroid(..)"),
+            ElementFilter::And(v) => f.debug_tuple("And").field(v).finish(),
+            ElementFilter::Or(v) => f.debug_tuple("Or").field(v).finish(),
+            ElementFilter::Not(inner) => f.debug_tuple("Not").field(inner).finish(),
+        }
+    }
+}
+
+impl std::ops::Not for ElementFilter {
+    type Output = ElementFilter;
+
+    fn not(self) -> ElementFilter {
+        ElementFilter::Not(Box::new(self))
+    }
+}
+
+impl ElementFilter {
+    pub fn of_type(element_type: ElementType) -> Self {
+        ElementFilter::Type(element_type)
+    }
+
+    pub fn dimension(dim: i32) -> Self {
+        ElementFilter::Dimension(dim)
+    }
+
+    pub fn physical_group(dim: i32, tag: PhysicalTag) -> Self {
+        ElementFilter::PhysicalGroup(dim, tag)
+    }
+
+    pub fn entity(dim: i32, tag: EntityTag) -> Self {
+        ElementFilter::Entity(dim, tag)
+    }
+
+    pub fn tag_range(range: RangeInclusive<ElementTag>) -> Self {
+        ElementFilter::TagRange(range)
+    }
+
+    /// Elements whose centroid (mean of the node coordinates) satisfies `predicate`
+    pub fn centroid<F>(predicate: F) -> Self
+    where
+        F: Fn([f64; 3]) -> bool + Send + Sync + 'static,
+    {
+        ElementFilter::Centroid(Arc::new(predicate))
+    }
+
+    /// Elements whose centroid lies in the axis-aligned box `[min, max]`
+    pub fn within_box(min: [f64; 3], max: [f64; 3]) -> Self {
+        Self::centroid(move |c| (0..3).all(|i| min[i] <= c[i] && c[i] <= max[i]))
+    }
+
+    pub fn and(self, other: ElementFilter) -> Self {
+        match self {
+            ElementFilter::And(mut filters) => {
+                filters.push(other);
+                ElementFilter::And(filters)
+            }
+            filter => ElementFilter::And(vec![filter, other]),
+        }
+    }
+
+    pub fn or(self, other: ElementFilter) -> Self {
+        match self {
+            ElementFilter::Or(mut filters) => {
+                filters.push(other);
+                ElementFilter::Or(filters)
+            }
+            filter => ElementFilter::Or(vec![filter, other]),
+        }
+    }
+
+    /// Resolve the filter against `mesh` for repeated evaluation
+    pub fn prepare<'a>(&'a self, mesh: &Mesh) -> PreparedFilter<'a> {
+        let mut groups = HashMap::new();
+        let mut needs_coordinates = false;
+        self.visit(&mut |filter| match filter {
+            ElementFilter::PhysicalGroup(dim, tag) => {
+                groups
+                    .entry((*dim, *tag))
+                    .or_insert_with(|| physical_entities(mesh, *dim, *tag).unwrap_or_default());
+            }
+            ElementFilter::Centroid(_) => needs_coordinates = true,
+            _ => {}
+        });
+
+        PreparedFilter {
+            filter: self,
+            groups,
+            coordinates: needs_coordinates.then(|| node_coordinates(mesh)),
+        }
+    }
+
+    fn visit(&self, f: &mut impl FnMut(&ElementFilter)) {
+        f(self);
+        match self {
+            ElementFilter::And(filters) | ElementFilter::Or(filters) => {
+                filters.iter().for_each(|filter| filter.visit(f))
+            }
+            ElementFilter::Not(inner) => inner.visit(f),
+            _ => {}
+        }
+    }
+}
+
+/// An [`ElementFilter`] resolved against a mesh
+pub struct PreparedFilter<'a> {
+    filter: &'a ElementFilter,
+    groups: HashMap<(i32, PhysicalTag), HashSet<EntityTag>>,
+    coordinates: Option<HashMap<NodeTag, [f64; 3]>>,
+}
+
+impl PreparedFilter<'_> {
+    pub fn matches(&self, element: ElementRef<'_>) -> bool {
+        self.eval(self.filter, element)
+    }
+
+    fn eval(&self, filter: &ElementFilter, element: ElementRef<'_>) -> bool {
+        match filter {
+            ElementFilter::All => true,
+            ElementFilter::Type(t) => element.element_type() == *t,
+            ElementFilter::Dimension(d) => element.entity_dim() == *d,
+            ElementFilter::PhysicalGroup(dim, tag) => {
+                element.entity_dim() == *dim
+                    && self
+                        .groups
+                        .get(&(*dim, *tag))
+                        .is_some_and(|entities| entities.contains(&element.entity_tag()))
+            }
+            ElementFilter::Entity(dim, tag) => {
+                element.entity_dim() == *dim && element.entity_tag() == *tag
+            }
+            ElementFilter::TagRange(range) => range.contains(&element.tag()),
+            ElementFilter::Centroid(predicate) => {
+                self.centroid(element.nodes()).is_some_and(|c| predicate(c))
+            }
+            ElementFilter::And(filters) => filters.iter().all(|f| self.eval(f, element)),
+            ElementFilter::Or(filters) => filters.iter().any(|f| self.eval(f, element)),
+            ElementFilter::Not(inner) => !self.eval(inner, element),
+        }
+    }
+
+    fn centroid(&self, nodes: &[NodeTag]) -> Option<[f64; 3]> {
+        let coordinates = self.coordinates.as_ref()?;
+        if nodes.is_empty() {
+            return None;
+        }
+        let mut sum = [0.0; 3];
+        for node in nodes {
+            let p = coordinates.get(node)?;
+            for i in 0..3 {
+                sum[i] += p[i];
+            }
+        }
+        let n = nodes.len() as f64;
+        Some([sum[0] / n, sum[1] / n, sum[2] / n])
+    }
+}
+
+impl Mesh {
+    /// Elements matching `filter`, in file order
+    pub fn iter_elements<'a>(
+        &'a self,
+        filter: &'a ElementFilter,
+    ) -> impl Iterator<Item = ElementRef<'a>> + 'a {
+        let prepared = filter.prepare(self);
+        self.elements()
+            .filter(move |&element| prepared.matches(element))
+    }
+
+    /// Number of elements matching `filter`
+    pub fn count_elements(&self, filter: &ElementFilter) -> usize {
+        let prepared = filter.prepare(self);
+        self.elements()
+            .filter(|&element| prepared.matches(element))
+            .count()
+    }
+
+    /// Sub-mesh made of the elements matching `filter` and the nodes they use
+    ///
+    /// Entities, physical names and the coordinate system are kept as they are.
+    /// Periodic links, ghost elements, parametrizations and post-processing
+    /// data are dropped; node and element sets are restricted to the extracted
+    /// nodes and elements.
+    pub fn extract(&self, filter: &ElementFilter) -> Result<Mesh> {
+        let prepared = filter.prepare(self);
+        let mut result = Mesh::new(self.format.clone());
+        result.physical_names = self.physical_names.clone();
+        result.entities = self.entities.clone();
+        result.partitioned_entities = self.partitioned_entities.clone();
+        result.coordinate_system = self.coordinate_system.clone();
+
+        let mut used_nodes = HashSet::new();
+        for block in self.element_block_refs() {
+            let elements: Vec<Element> = block
+                .elements()
+                .filter(|&element| prepared.matches(element))
+                .map(|element| Element::new(element.tag(), element.nodes().to_vec()))
+                .collect();
+            if elements.is_empty() {
+                continue;
+            }
+            used_nodes.extend(elements.iter().flat_map(|e| e.nodes.iter().copied()));
+            result.element_blocks.push(ElementBlock::new(
+                block.entity_dim(),
+                block.entity_tag(),
+                block.element_type(),
+                elements,
+            ));
+        }
+
+        for block in &self.node_blocks {
+            let mut block = block.clone();
+            block.nodes.retain(|node| used_nodes.contains(&node.tag));
+            if !block.nodes.is_empty() {
+                result.node_blocks.push(block);
+            }
+        }
+
+        let used_elements: HashSet<ElementTag> = result
+            .element_blocks
+            .iter()
+            .flat_map(|b| b.elements.iter().map(|e| e.tag))
+            .collect();
+        for set in &self.node_sets {
+            let mut set = set.clone();
+            set.tags.retain(|tag| used_nodes.contains(tag));
+            result.node_sets.push(set);
+        }
+        for set in &self.element_sets {
+            let mut set = set.clone();
+            set.tags.retain(|tag| used_elements.contains(tag));
+            result.element_sets.push(set);
+        }
+
+        result.validate()?;
+        Ok(result)
+    }
+
+    /// Sub-mesh made of the elements of dimension `dim` and the nodes they use,
+    /// e.g. the beams or shells of a structural model mixing them with solids
+    ///
+    /// Works as [`Mesh::extract`], then drops the entities no block refers to,
+    /// except the boundaries of those kept, and the physical names of groups
+    /// that no longer carry any entity or are of a higher dimension than `dim`.
+    /// For partitioned meshes only the partitioned entities are pruned, since
+    /// the blocks refer to them.
+    pub fn extract_dimension(&self, dim: i32) -> Result<Mesh> {
+        let mut result = self.extract(&ElementFilter::dimension(dim))?;
+        if let Some(graph) = result.entity_graph() {
+            let mut keep = HashSet::new();
+            let blocks = result
+                .node_blocks
+                .iter()
+                .map(|b| (b.entity_dim(), b.entity_tag))
+                .chain(
+                    result
+                        .element_blocks
+                        .iter()
+                        .map(|b| (b.entity_dim, b.entity_tag)),
+                );
+            for (block_dim, tag) in blocks {
+                keep.extend(graph.closure(block_dim, tag));
+            }
+            if let Some(partitioned) = &mut result.partitioned_entities {
+                partitioned.points.retain(|e| keep.contains(&(0, e.tag)));
+                partitioned.curves.retain(|e| keep.contains(&(1, e.tag)));
+                partitioned.surfaces.retain(|e| keep.contains(&(2, e.tag)));
+                partitioned.volumes.retain(|e| keep.contains(&(3, e.tag)));
+            } else if let Some(entities) = &mut result.entities {
+                entities.points.retain(|e| keep.contains(&(0, e.tag)));
+                entities.curves.retain(|e| keep.contains(&(1, e.tag)));
+                entities.surfaces.retain(|e| keep.contains(&(2, e.tag)));
+                entities.volumes.retain(|e| keep.contains(&(3, e.tag)));
+            }
+        }
+
+        let carried = carried_physical_tags(&result);
+        result.physical_names.retain(|pn| {
+            let pn_dim = pn.dimension as i32;
+            pn_dim <= dim && carried.contains(&(pn_dim, pn.tag.magnitude()))
+        });
+        Ok(result)
+    }
+
+    /// The line elements of the mesh, see [`Mesh::extract_dimension`]
+    pub fn extract_beams(&self) -> Result<Mesh> {
+        self.extract_dimension(1)
+    }
+
+    /// The surface elements of the mesh, see [`Mesh::extract_dimension`]
+    pub fn extract_shells(&self) -> Result<Mesh> {
+        self.extract_dimension(2)
+    }
+}
+
+/// `(dimension, magnitude)` of the physical tags carried by the entities and
+/// partitioned entities of `mesh`
+pub(crate) fn carried_physical_tags(mesh: &Mesh) -> HashSet<(i32, PhysicalTag)> {
+    let mut carried = HashSet::new();
+    let entities = mesh.effective_entities();
+    let tagged = (entities.points.iter().map(|e| (0, &e.physical_tags)))
+        .chain(entities.curves.iter().map(|e| (1, &e.physical_tags)))
+        .chain(entities.surfaces.iter().map(|e| (2, &e.physical_tags)))
+        .chain(entities.volumes.iter().map(|e| (3, &e.physical_tags)));
+    for (entity_dim, tags) in tagged {
+        carried.extend(tags.iter().map(|t| (entity_dim, t.magnitude())));
+    }
+    if let Some(partitioned) = &mesh.partitioned_entities {
+        let tagged = (partitioned.points.iter().map(|e| (0, &e.physical_tags)))
+            .chain(partitioned.curves.iter().map(|e| (1, &e.physical_tags)))
+            .chain(partitioned.surfaces.iter().map(|e| (2, &e.physical_tags)))
+            .chain(partitioned.volumes.iter().map(|e| (3, &e.physical_tags)));
+        for (entity_dim, tags) in tagged {
+            carried.extend(tags.iter().map(|t| (entity_dim, t.magnitude())));
+        }
+    }
+    carried
+}
+
+impl ElementSet {
+    /// All elements matching `filter`
+    pub fn from_filter(mesh: &Mesh, name: impl Into<String>, filter: &ElementFilter) -> Self {
+        Self::new(name, mesh.iter_elements(filter).map(|e| e.tag()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit square of two triangles in the physical surface 6, bounded by
+    /// three lines that hold its nodes
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $PhysicalNames\n1\n2 6 \"My surface\"\n$EndPhysicalNames\n\
+        $Entities\n0 3 1 0\n1 0 0 0 1 0 0 1 5 0\n2 1 0 0 1 1 0 1 5 0\n3 0 1 0 1 1 0 0 0\n\
+        1 0 0 0 1 1 0 1 6 0\n$EndEntities\n\
+        $Nodes\n3 4 1 4\n1 1 0 2\n1\n2\n0 0 0\n1 0 0\n1 2 0 1\n3\n1 1 0\n1 3 0 1\n4\n0 1 0\n\
+        $EndNodes\n$Elements\n4 5 1 5\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n\
+        2 1 2 2\n4 1 2 3\n5 1 3 4\n$EndElements\n";
+
+    #[test]
+    fn test_filter_by_type_and_group() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let triangles = ElementFilter::of_type(ElementType::Triangle3);
+        let surface = ElementFilter::physical_group(2, PhysicalTag(6));
+        assert_eq!(mesh.count_elements(&triangles), 2);
+        assert_eq!(mesh.count_elements(&surface), 2);
+        assert_eq!(mesh.count_elements(&!triangles), 3);
+        let missing = ElementFilter::physical_group(2, PhysicalTag(99));
+        assert_eq!(mesh.count_elements(&missing), 0);
+    }
+
+    #[test]
+    fn test_filter_within_box() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        // Only the centroid of triangle 4 lies below y = 0.5
+        let lower = ElementFilter::of_type(ElementType::Triangle3)
+            .and(ElementFilter::within_box([0.0; 3], [1.0, 0.5, 0.0]));
+        let upper = ElementFilter::of_type(ElementType::Triangle3)
+            .and(!ElementFilter::within_box([0.0; 3], [1.0, 0.5, 0.0]));
+        assert_eq!(mesh.count_elements(&lower), 1);
+        assert_eq!(mesh.count_elements(&upper), 1);
+        assert_eq!(mesh.count_elements(&lower.or(upper)), 2);
+    }
+
+    #[test]
+    fn test_extract_keeps_used_nodes() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let lower = ElementFilter::within_box([0.0; 3], [1.0, 0.5, 0.0])
+            .and(ElementFilter::of_type(ElementType::Triangle3));
+        let extracted = mesh.extract(&lower).unwrap();
+        assert_eq!(extracted.num_elements(), 1);
+        assert_eq!(extracted.num_nodes(), 3);
+    }
+
+    #[test]
+    fn test_extract_beams() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let beams = mesh.extract_beams().unwrap();
+        assert_eq!(beams.num_elements(), 3);
+        assert!(beams.element_blocks.iter().all(|b| b.entity_dim == 1));
+        let entities = beams.entities.as_ref().unwrap();
+        assert!(entities.surfaces.is_empty());
+        assert_eq!(entities.curves.len(), 3);
+        assert!(beams.physical_names.is_empty());
+    }
+
+    #[test]
+    fn test_extract_shells() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let shells = mesh.extract_shells().unwrap();
+        assert_eq!(shells.num_elements(), 2);
+        assert_eq!(shells.physical_names.len(), 1);
+        assert_eq!(shells.entities.as_ref().unwrap().surfaces.len(), 1);
+        assert!(shells.validate_all().is_valid());
+        assert_eq!(mesh.extract_dimension(3).unwrap().num_elements(), 0);
+    }
+}
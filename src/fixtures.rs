@@ -0,0 +1,93 @@
+//! Small meshes shared by the unit tests
+
+/// A single line element between two nodes
+#[cfg(feature = "cache")]
+pub(crate) const LINE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Nodes\n1 2 1 2\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+    $Elements\n1 1 1 1\n1 1 1 1\n1 1 2\n$EndElements\n";
+
+/// A triangle and one of its edges
+pub(crate) const TRIANGLE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Nodes\n1 3 1 3\n2 1 0 3\n1\n2\n3\n0 0 0\n1 0 0\n0 1 0\n$EndNodes\n\
+    $Elements\n2 2 1 2\n1 1 1 1\n1 1 2\n2 1 2 1\n2 1 2 3\n$EndElements\n";
+
+/// A single triangle
+pub(crate) const SINGLE_TRIANGLE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Nodes\n1 3 1 3\n2 1 0 3\n1\n2\n3\n0 0 0\n1 0 0\n0 1 0\n$EndNodes\n\
+    $Elements\n1 1 1 1\n2 1 2 1\n1 1 2 3\n$EndElements\n";
+
+/// A unit square of two triangles with a corner at the origin
+pub(crate) const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+    $Elements\n1 2 1 2\n2 1 2 2\n1 1 2 3\n2 1 3 4\n$EndElements\n";
+
+/// A unit square of two triangles with a line on its bottom edge
+pub(crate) const SQUARE_WITH_LINE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+    $Elements\n2 3 1 3\n1 1 1 1\n1 1 2\n2 1 2 2\n2 1 2 3\n3 1 3 4\n$EndElements\n";
+
+/// A unit square of two triangles in the physical surface 6, bounded by three
+/// curves of which the bottom and right ones form the physical curve 5, without
+/// `$PhysicalNames`
+pub(crate) const TAGGED_SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Entities\n0 3 1 0\n1 0 0 0 1 0 0 1 5 0\n2 1 0 0 1 1 0 1 5 0\n3 0 1 0 1 1 0 0 0\n\
+    1 0 0 0 1 1 0 1 6 0\n$EndEntities\n\
+    $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+    $Elements\n4 5 1 5\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n\
+    2 1 2 2\n4 1 2 3\n5 1 3 4\n$EndElements\n";
+
+/// [`TAGGED_SQUARE`] with the physical names "boundary" and "My surface"
+pub(crate) const NAMED_SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $PhysicalNames\n2\n1 5 \"boundary\"\n2 6 \"My surface\"\n$EndPhysicalNames\n\
+    $Entities\n0 3 1 0\n1 0 0 0 1 0 0 1 5 0\n2 1 0 0 1 1 0 1 5 0\n3 0 1 0 1 1 0 0 0\n\
+    1 0 0 0 1 1 0 1 6 0\n$EndEntities\n\
+    $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+    $Elements\n4 5 1 5\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n\
+    2 1 2 2\n4 1 2 3\n5 1 3 4\n$EndElements\n";
+
+/// [`NAMED_SQUARE`] with its nodes on the boundary curves
+pub(crate) const NAMED_SQUARE_ON_CURVES: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $PhysicalNames\n2\n1 5 \"boundary\"\n2 6 \"My surface\"\n$EndPhysicalNames\n\
+    $Entities\n0 3 1 0\n1 0 0 0 1 0 0 1 5 0\n2 1 0 0 1 1 0 1 5 0\n3 0 1 0 1 1 0 0 0\n\
+    1 0 0 0 1 1 0 1 6 0\n$EndEntities\n\
+    $Nodes\n3 4 1 4\n1 1 0 2\n1\n2\n0 0 0\n1 0 0\n1 2 0 1\n3\n1 1 0\n1 3 0 1\n4\n0 1 0\n\
+    $EndNodes\n\
+    $Elements\n4 5 1 5\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n\
+    2 1 2 2\n4 1 2 3\n5 1 3 4\n$EndElements\n";
+
+/// A unit square periodic in x and y: the top edge is the image of the
+/// bottom one and the right edge the image of the left one
+pub(crate) const PERIODIC: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Entities\n0 4 1 0\n1 0 0 0 1 0 0 0 0\n2 1 0 0 1 1 0 0 0\n3 0 1 0 1 1 0 0 0\n\
+    4 0 0 0 0 1 0 0 0\n1 0 0 0 1 1 0 0 0\n$EndEntities\n\
+    $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+    $Elements\n1 2 1 2\n2 1 2 2\n1 1 2 3\n2 1 3 4\n$EndElements\n\
+    $Periodic\n2\n1 3 1\n16 1 0 0 0 0 1 0 1 0 0 1 0 0 0 0 1\n2\n4 1\n3 2\n\
+    1 2 4\n16 1 0 0 1 0 1 0 0 0 0 1 0 0 0 0 1\n2\n2 1\n3 4\n$EndPeriodic\n";
+
+/// A unit cube of one hexahedron
+pub(crate) const HEXAHEDRON: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Nodes\n1 8 1 8\n3 1 0 8\n1\n2\n3\n4\n5\n6\n7\n8\n\
+    0 0 0\n1 0 0\n1 1 0\n0 1 0\n0 0 1\n1 0 1\n1 1 1\n0 1 1\n$EndNodes\n\
+    $Elements\n1 1 1 1\n3 1 5 1\n1 1 2 3 4 5 6 7 8\n$EndElements\n";
+
+/// The surface of the unit cube, two triangles per face
+pub(crate) const CUBE_SURFACE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Nodes\n1 8 1 8\n2 1 0 8\n1\n2\n3\n4\n5\n6\n7\n8\n\
+    0 0 0\n1 0 0\n1 1 0\n0 1 0\n0 0 1\n1 0 1\n1 1 1\n0 1 1\n$EndNodes\n\
+    $Elements\n1 12 1 12\n2 1 2 12\n1 1 4 3\n2 1 3 2\n3 5 6 7\n4 5 7 8\n5 1 2 6\n\
+    6 1 6 5\n7 4 8 7\n8 4 7 3\n9 1 5 8\n10 1 8 4\n11 2 3 7\n12 2 7 6\n$EndElements\n";
+
+/// A chain of four segments whose node tags jump back and forth
+pub(crate) const CHAIN: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Nodes\n1 5 1 5\n1 1 0 5\n1\n5\n2\n4\n3\n0 0 0\n1 0 0\n2 0 0\n3 0 0\n4 0 0\n$EndNodes\n\
+    $Elements\n1 4 1 4\n1 1 1 4\n1 1 5\n2 5 2\n3 2 4\n4 4 3\n$EndElements\n";
+
+/// Two triangles of a unit square sharing an edge, one per partition
+pub(crate) const PARTITIONED: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+    $Entities\n0 0 1 0\n1 0 0 0 1 1 0 0 0\n$EndEntities\n\
+    $PartitionedEntities\n2\n0\n0 0 2 0\n2 2 1 1 1 0 0 0 1 1 0 0 0\n\
+    3 2 1 1 2 0 0 0 1 1 0 0 0\n$EndPartitionedEntities\n\
+    $Nodes\n2 4 1 4\n2 2 0 3\n1\n2\n3\n0 0 0\n1 0 0\n1 1 0\n2 3 0 1\n4\n0 1 0\n\
+    $EndNodes\n\
+    $Elements\n2 2 1 2\n2 2 2 1\n1 1 2 3\n2 3 2 1\n2 1 3 4\n$EndElements\n";
@@ -0,0 +1,211 @@
+//! Fresh tags for generated nodes, elements, entities and physical groups
+
+use crate::types::{ElementTag, EntityTag, Mesh, NodeTag, PhysicalTag};
+use std::collections::BTreeMap;
+
+/// Hands out node, element, entity and physical tags that are not used yet
+///
+/// [`TagAllocator::for_mesh`] starts past every tag used by a mesh, so the new
+/// nodes and elements of a generator can be added to it without collisions.
+/// Entity and physical tags are only unique within their dimension, so they are
+/// allocated per dimension. Tags handed out by [`TagAllocator::map_node`] and
+/// [`TagAllocator::map_element`] are recorded against the tag they replace.
+///
+/// ```
+/// use gmsh_parser::generate::TagAllocator;
+/// use gmsh_parser::{EntityTag, NodeTag};
+///
+/// let mut tags = TagAllocator::new();
+/// let copy = tags.map_node(NodeTag(7));
+/// assert_eq!(copy, NodeTag(1));
+/// assert_eq!(tags.map_node(NodeTag(7)), copy);
+/// assert_eq!(tags.entity(2), EntityTag(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TagAllocator {
+    next_node: NodeTag,
+    next_element: ElementTag,
+    next_entity: [EntityTag; 4],
+    next_physical: [PhysicalTag; 4],
+    node_map: BTreeMap<NodeTag, NodeTag>,
+    element_map: BTreeMap<ElementTag, ElementTag>,
+}
+
+impl Default for TagAllocator {
+    fn default() -> Self {
+        Self {
+            next_node: NodeTag(1),
+            next_element: ElementTag(1),
+            next_entity: [EntityTag(1); 4],
+            next_physical: [PhysicalTag(1); 4],
+            node_map: BTreeMap::new(),
+            element_map: BTreeMap::new(),
+        }
+    }
+}
+
+impl TagAllocator {
+    /// An allocator for an empty mesh, handing out tags from 1
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An allocator handing out tags past those used by `mesh`
+    ///
+    /// Node and element blocks, compact element blocks, entities (synthesized
+    /// if the file has none), partition entities and physical names are taken
+    /// into account.
+    pub fn for_mesh(mesh: &Mesh) -> Self {
+        let mut allocator = Self::new();
+        for node in mesh.node_blocks.iter().flat_map(|b| &b.nodes) {
+            allocator.reserve_node(node.tag);
+        }
+        for element in mesh.element_blocks.iter().flat_map(|b| &b.elements) {
+            allocator.reserve_element(element.tag);
+        }
+        for &tag in mesh.compact_element_blocks.iter().flat_map(|b| &b.tags) {
+            allocator.reserve_element(tag);
+        }
+
+        let entities = mesh.effective_entities();
+        let mut reserve = |dim: i32, tag: EntityTag, physical_tags: &[PhysicalTag]| {
+            allocator.reserve_entity(dim, tag);
+            for &physical in physical_tags {
+                allocator.reserve_physical(dim, physical);
+            }
+        };
+        for e in &entities.points {
+            reserve(0, e.tag, &e.physical_tags);
+        }
+        for e in &entities.curves {
+            reserve(1, e.tag, &e.physical_tags);
+        }
+        for e in &entities.surfaces {
+            reserve(2, e.tag, &e.physical_tags);
+        }
+        for e in &entities.volumes {
+            reserve(3, e.tag, &e.physical_tags);
+        }
+        if let Some(partitioned) = &mesh.partitioned_entities {
+            for e in &partitioned.points {
+                reserve(0, e.tag, &e.physical_tags);
+            }
+            for e in &partitioned.curves {
+                reserve(1, e.tag, &e.physical_tags);
+            }
+            for e in &partitioned.surfaces {
+                reserve(2, e.tag, &e.physical_tags);
+            }
+            for e in &partitioned.volumes {
+                reserve(3, e.tag, &e.physical_tags);
+            }
+        }
+        for pn in &mesh.physical_names {
+            allocator.reserve_physical(pn.dimension as i32, pn.tag);
+        }
+        allocator
+    }
+
+    /// Make sure `tag` is never handed out
+    pub fn reserve_node(&mut self, tag: NodeTag) {
+        self.next_node = self.next_node.max(tag + 1);
+    }
+
+    /// Make sure `tag` is never handed out
+    pub fn reserve_element(&mut self, tag: ElementTag) {
+        self.next_element = self.next_element.max(tag + 1);
+    }
+
+    /// Make sure `tag` is never handed out for dimension `dim`
+    pub fn reserve_entity(&mut self, dim: i32, tag: EntityTag) {
+        let next = &mut self.next_entity[dim.clamp(0, 3) as usize];
+        *next = (*next).max(EntityTag(tag.0.abs()) + 1);
+    }
+
+    /// Make sure `tag` is never handed out for dimension `dim`
+    pub fn reserve_physical(&mut self, dim: i32, tag: PhysicalTag) {
+        let next = &mut self.next_physical[dim.clamp(0, 3) as usize];
+        *next = (*next).max(PhysicalTag(tag.0.abs()) + 1);
+    }
+
+    /// A new node tag
+    pub fn node(&mut self) -> NodeTag {
+        let tag = self.next_node;
+        self.next_node = tag + 1;
+        tag
+    }
+
+    /// A new element tag
+    pub fn element(&mut self) -> ElementTag {
+        let tag = self.next_element;
+        self.next_element = tag + 1;
+        tag
+    }
+
+    /// A new entity tag of dimension `dim`
+    pub fn entity(&mut self, dim: i32) -> EntityTag {
+        let next = &mut self.next_entity[dim.clamp(0, 3) as usize];
+        let tag = *next;
+        *next = tag + 1;
+        tag
+    }
+
+    /// A new physical tag of dimension `dim`
+    pub fn physical(&mut self, dim: i32) -> PhysicalTag {
+        let next = &mut self.next_physical[dim.clamp(0, 3) as usize];
+        let tag = *next;
+        *next = tag + 1;
+        tag
+    }
+
+    /// The new tag standing for node `source`, allocated on first use
+    pub fn map_node(&mut self, source: NodeTag) -> NodeTag {
+        if let Some(&tag) = self.node_map.get(&source) {
+            return tag;
+        }
+        let tag = self.node();
+        self.node_map.insert(source, tag);
+        tag
+    }
+
+    /// The new tag standing for element `source`, allocated on first use
+    pub fn map_element(&mut self, source: ElementTag) -> ElementTag {
+        if let Some(&tag) = self.element_map.get(&source) {
+            return tag;
+        }
+        let tag = self.element();
+        self.element_map.insert(source, tag);
+        tag
+    }
+
+    /// Source node tags and the tags allocated for them by [`TagAllocator::map_node`]
+    pub fn node_map(&self) -> &BTreeMap<NodeTag, NodeTag> {
+        &self.node_map
+    }
+
+    /// Source element tags and the tags allocated for them by
+    /// [`TagAllocator::map_element`]
+    pub fn element_map(&self) -> &BTreeMap<ElementTag, ElementTag> {
+        &self.element_map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::TAGGED_SQUARE;
+
+    #[test]
+    fn test_allocator_skips_used_tags() {
+        let mesh = crate::parse_msh(TAGGED_SQUARE).unwrap();
+        let mut tags = TagAllocator::for_mesh(&mesh);
+        assert_eq!(tags.node(), NodeTag(5));
+        assert_eq!(tags.map_element(ElementTag(1)), ElementTag(6));
+        assert_eq!(tags.entity(1), EntityTag(4));
+        assert_eq!(tags.entity(2), EntityTag(2));
+        assert_eq!(tags.entity(3), EntityTag(1));
+        assert_eq!(tags.physical(2), PhysicalTag(7));
+        assert_eq!(tags.physical(1), PhysicalTag(6));
+        assert_eq!(tags.element_map().len(), 1);
+    }
+}
@@ -0,0 +1,465 @@
+//! Extrusion of surface meshes into volume meshes
+
+use super::TagAllocator;
+use crate::error::{ParseError, Result};
+use crate::geometry::{bounds, node_coordinates};
+use crate::types::element::Element;
+use crate::types::{
+    ElementBlock, ElementBlockRef, ElementType, Entities, EntityDimension, EntityTag, Mesh,
+    MeshFormat, Node, NodeBlock, NodeTag, PhysicalName, PhysicalTag, SurfaceEntity, VolumeEntity,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Extrude the surface elements of a 2D mesh along `direction`
+///
+/// `direction` is the total extrusion vector, split into `layers` equal layers.
+/// Triangles become `Prism6` and quadrangles `Hexahedron8`; line elements on
+/// boundary curves become the `Quadrangle4` side walls.
+///
+/// Every surface entity produces one volume, keeps its elements as the bottom
+/// surface and gains a top surface. Physical groups are propagated as follows:
+/// - a surface group `N` becomes the volume group `N` (same tag), the bottom
+///   surface group `N_bottom` (same tag) and a new top surface group `N_top`
+/// - a curve group `M` becomes a new side surface group `M`
+///
+/// # Errors
+/// Returns an error if `layers` is zero, `direction` is the zero vector, the mesh
+/// has no surface elements, or contains element types other than `Triangle3`,
+/// `Quadrangle4` (surfaces) and `Line2` (curves).
+pub fn extrude(mesh: &Mesh, direction: [f64; 3], layers: usize) -> Result<Mesh> {
+    if layers == 0 {
+        return Err(ParseError::GeometryError(
+            "Extrusion requires at least one layer".to_string(),
+        ));
+    }
+    if direction.iter().all(|&d| d == 0.0) {
+        return Err(ParseError::GeometryError(
+            "Extrusion direction must not be the zero vector".to_string(),
+        ));
+    }
+
+    let surface_blocks: Vec<ElementBlockRef> = mesh
+        .element_block_refs()
+        .filter(|block| block.entity_dim() == 2)
+        .collect();
+    let curve_blocks: Vec<ElementBlockRef> = mesh
+        .element_block_refs()
+        .filter(|block| block.entity_dim() == 1)
+        .collect();
+
+    if surface_blocks.iter().all(|block| block.is_empty()) {
+        return Err(ParseError::GeometryError(
+            "Mesh has no surface elements to extrude".to_string(),
+        ));
+    }
+    for block in &surface_blocks {
+        if !matches!(
+            block.element_type(),
+            ElementType::Triangle3 | ElementType::Quadrangle4
+        ) {
+            return Err(unsupported(*block));
+        }
+    }
+    for block in &curve_blocks {
+        if block.element_type() != ElementType::Line2 {
+            return Err(unsupported(*block));
+        }
+    }
+
+    let coords = node_coordinates(mesh);
+    let max_node_tag = coords.keys().copied().max().unwrap_or_default();
+    let layer_tag = |tag: NodeTag, layer: usize| tag + layer as u64 * max_node_tag.0;
+    let layer_point = |tag: NodeTag, layer: usize| {
+        let p = coords[&tag];
+        let t = layer as f64 / layers as f64;
+        [
+            p[0] + t * direction[0],
+            p[1] + t * direction[1],
+            p[2] + t * direction[2],
+        ]
+    };
+
+    // Each node follows the first source surface that uses it
+    let mut owner: HashMap<NodeTag, EntityTag> = HashMap::new();
+    for block in &surface_blocks {
+        for element in block.elements() {
+            for node in element.nodes() {
+                if !coords.contains_key(node) {
+                    return Err(ParseError::GeometryError(format!(
+                        "Element {} references missing node {}",
+                        element.tag(),
+                        node
+                    )));
+                }
+                owner.entry(*node).or_insert(block.entity_tag());
+            }
+        }
+    }
+    for block in &curve_blocks {
+        for element in block.elements() {
+            if let Some(node) = element.nodes().iter().find(|n| !owner.contains_key(n)) {
+                return Err(ParseError::GeometryError(format!(
+                    "Line element {} uses node {} which is not part of any surface",
+                    element.tag(),
+                    node
+                )));
+            }
+        }
+    }
+
+    // Entity tags of the generated model
+    let source_surfaces: BTreeSet<EntityTag> =
+        surface_blocks.iter().map(|b| b.entity_tag()).collect();
+    let source_curves: BTreeSet<EntityTag> = curve_blocks.iter().map(|b| b.entity_tag()).collect();
+    let mut tags = TagAllocator::for_mesh(mesh);
+    let volume_tag: BTreeMap<EntityTag, EntityTag> = source_surfaces
+        .iter()
+        .map(|&s| (s, tags.entity(3)))
+        .collect();
+    let top_tag: BTreeMap<EntityTag, EntityTag> = source_surfaces
+        .iter()
+        .map(|&s| (s, tags.entity(2)))
+        .collect();
+    let side_tag: BTreeMap<EntityTag, EntityTag> =
+        source_curves.iter().map(|&c| (c, tags.entity(2))).collect();
+
+    // Physical groups of the source entities
+    let (surface_physicals, curve_physicals, bounding_curves) = source_entity_data(mesh);
+    let group_tags: BTreeSet<PhysicalTag> = surface_physicals.values().flatten().copied().collect();
+    let curve_group_tags: BTreeSet<PhysicalTag> =
+        curve_physicals.values().flatten().copied().collect();
+    let top_physical: BTreeMap<PhysicalTag, PhysicalTag> =
+        group_tags.iter().map(|&p| (p, tags.physical(2))).collect();
+    let side_physical: BTreeMap<PhysicalTag, PhysicalTag> = curve_group_tags
+        .iter()
+        .map(|&p| (p, tags.physical(2)))
+        .collect();
+
+    let mut result = Mesh::new(MeshFormat::default());
+    result.coordinate_system = mesh.coordinate_system.clone();
+
+    for pn in &mesh.physical_names {
+        match pn.dimension {
+            EntityDimension::Surface => {
+                result.physical_names.push(PhysicalName::new(
+                    EntityDimension::Volume,
+                    pn.tag,
+                    pn.name.clone(),
+                ));
+                result.physical_names.push(PhysicalName::new(
+                    EntityDimension::Surface,
+                    pn.tag,
+                    format!("{}_bottom", pn.name),
+                ));
+                if let Some(&top) = top_physical.get(&pn.tag) {
+                    result.physical_names.push(PhysicalName::new(
+                        EntityDimension::Surface,
+                        top,
+                        format!("{}_top", pn.name),
+                    ));
+                }
+            }
+            EntityDimension::Curve => {
+                if let Some(&side) = side_physical.get(&pn.tag) {
+                    result.physical_names.push(PhysicalName::new(
+                        EntityDimension::Surface,
+                        side,
+                        pn.name.clone(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Elements
+    // The result is a new mesh, so its elements are numbered from 1
+    let mut element_tags = TagAllocator::new();
+    let mut new_element = |nodes: Vec<NodeTag>| Element::new(element_tags.element(), nodes);
+
+    for block in &surface_blocks {
+        let s = block.entity_tag();
+        let volume_type = match block.element_type() {
+            ElementType::Triangle3 => ElementType::Prism6,
+            _ => ElementType::Hexahedron8,
+        };
+
+        let mut volume_elements = Vec::with_capacity(block.len() * layers);
+        for element in block.elements() {
+            let base = oriented_base(element.nodes(), &coords, direction);
+            for layer in 0..layers {
+                let nodes = base
+                    .iter()
+                    .map(|&n| layer_tag(n, layer))
+                    .chain(base.iter().map(|&n| layer_tag(n, layer + 1)))
+                    .collect();
+                volume_elements.push(new_element(nodes));
+            }
+        }
+        let bottom_elements = block
+            .elements()
+            .map(|e| new_element(e.nodes().to_vec()))
+            .collect();
+        let top_elements = block
+            .elements()
+            .map(|e| new_element(e.nodes().iter().map(|&n| layer_tag(n, layers)).collect()))
+            .collect();
+
+        result.element_blocks.push(ElementBlock::new(
+            3,
+            volume_tag[&s],
+            volume_type,
+            volume_elements,
+        ));
+        result.element_blocks.push(ElementBlock::new(
+            2,
+            s,
+            block.element_type(),
+            bottom_elements,
+        ));
+        result.element_blocks.push(ElementBlock::new(
+            2,
+            top_tag[&s],
+            block.element_type(),
+            top_elements,
+        ));
+    }
+
+    for block in &curve_blocks {
+        let mut side_elements = Vec::with_capacity(block.len() * layers);
+        for element in block.elements() {
+            let (a, b) = (element.nodes()[0], element.nodes()[1]);
+            for layer in 0..layers {
+                side_elements.push(new_element(vec![
+                    layer_tag(a, layer),
+                    layer_tag(b, layer),
+                    layer_tag(b, layer + 1),
+                    layer_tag(a, layer + 1),
+                ]));
+            }
+        }
+        result.element_blocks.push(ElementBlock::new(
+            2,
+            side_tag[&block.entity_tag()],
+            ElementType::Quadrangle4,
+            side_elements,
+        ));
+    }
+
+    // Nodes: bottom layer on the source surface, top layer on the top surface,
+    // intermediate layers inside the volume
+    let mut node_groups: BTreeMap<(i32, EntityTag), Vec<Node>> = BTreeMap::new();
+    let ordered_nodes = mesh
+        .node_blocks
+        .iter()
+        .flat_map(|block| block.nodes.iter())
+        .filter_map(|node| owner.get(&node.tag).map(|&s| (node.tag, s)));
+    for (tag, s) in ordered_nodes {
+        for layer in 0..=layers {
+            let entity = if layer == 0 {
+                (2, s)
+            } else if layer == layers {
+                (2, top_tag[&s])
+            } else {
+                (3, volume_tag[&s])
+            };
+            let [x, y, z] = layer_point(tag, layer);
+            node_groups.entry(entity).or_default().push(Node {
+                tag: layer_tag(tag, layer),
+                x,
+                y,
+                z,
+                parametric_coords: None,
+            });
+        }
+    }
+    for ((dim, tag), nodes) in node_groups {
+        result.node_blocks.push(NodeBlock {
+            entity_dim: EntityDimension::from_i32(dim).unwrap_or(EntityDimension::Volume),
+            entity_tag: tag,
+            parametric: false,
+            nodes,
+        });
+    }
+
+    // Entities, with bounding boxes computed from the generated elements
+    let new_coords = node_coordinates(&result);
+    let entity_bounds = |dim: i32, tag: EntityTag| {
+        bounds(
+            result
+                .element_blocks
+                .iter()
+                .filter(|b| b.entity_dim == dim && b.entity_tag == tag)
+                .flat_map(|b| b.elements.iter())
+                .flat_map(|e| e.nodes.iter())
+                .map(|n| new_coords[n]),
+        )
+        .unwrap_or(([0.0; 3], [0.0; 3]))
+    };
+    let surface_entity = |tag: EntityTag, physical_tags: Vec<PhysicalTag>| {
+        let (min, max) = entity_bounds(2, tag);
+        SurfaceEntity {
+            tag,
+            min_x: min[0],
+            min_y: min[1],
+            min_z: min[2],
+            max_x: max[0],
+            max_y: max[1],
+            max_z: max[2],
+            physical_tags,
+            bounding_curves: Vec::new(),
+        }
+    };
+
+    let mut entities = Entities::new();
+    for &s in &source_surfaces {
+        let physicals = surface_physicals.get(&s).cloned().unwrap_or_default();
+        let (min, max) = entity_bounds(3, volume_tag[&s]);
+
+        let mut bounding_surfaces = vec![-s.0, top_tag[&s].0];
+        if let Some(curves) = bounding_curves.get(&s) {
+            bounding_surfaces.extend(
+                curves
+                    .iter()
+                    .filter_map(|c| side_tag.get(&EntityTag(c.abs())))
+                    .map(|t| t.0),
+            );
+        }
+
+        entities.volumes.push(VolumeEntity {
+            tag: volume_tag[&s],
+            min_x: min[0],
+            min_y: min[1],
+            min_z: min[2],
+            max_x: max[0],
+            max_y: max[1],
+            max_z: max[2],
+            physical_tags: physicals.clone(),
+            bounding_surfaces,
+        });
+        entities.surfaces.push(surface_entity(s, physicals.clone()));
+        entities.surfaces.push(surface_entity(
+            top_tag[&s],
+            physicals.iter().map(|p| top_physical[p]).collect(),
+        ));
+    }
+    for &c in &source_curves {
+        let physicals = curve_physicals
+            .get(&c)
+            .map(|tags| tags.iter().map(|p| side_physical[p]).collect())
+            .unwrap_or_default();
+        entities
+            .surfaces
+            .push(surface_entity(side_tag[&c], physicals));
+    }
+    result.entities = Some(entities);
+
+    result.validate()?;
+    Ok(result)
+}
+
+type EntityPhysicals = HashMap<EntityTag, Vec<PhysicalTag>>;
+type EntityBoundaries = HashMap<EntityTag, Vec<i32>>;
+
+/// Physical tags of surfaces and curves, and bounding curves of surfaces
+fn source_entity_data(mesh: &Mesh) -> (EntityPhysicals, EntityPhysicals, EntityBoundaries) {
+    let mut surfaces = HashMap::new();
+    let mut curves = HashMap::new();
+    let mut bounding_curves = HashMap::new();
+
+    if let Some(entities) = &mesh.entities {
+        for surface in &entities.surfaces {
+            surfaces.insert(surface.tag, surface.physical_tags.clone());
+            bounding_curves.insert(surface.tag, surface.bounding_curves.clone());
+        }
+        for curve in &entities.curves {
+            curves.insert(curve.tag, curve.physical_tags.clone());
+        }
+    }
+
+    (surfaces, curves, bounding_curves)
+}
+
+/// Order the nodes of a triangle or quadrangle so that its normal points along `direction`
+fn oriented_base(
+    nodes: &[NodeTag],
+    coords: &HashMap<NodeTag, [f64; 3]>,
+    direction: [f64; 3],
+) -> Vec<NodeTag> {
+    let p: Vec<[f64; 3]> = nodes.iter().map(|n| coords[n]).collect();
+    let (u, v) = if p.len() == 3 {
+        (sub(p[1], p[0]), sub(p[2], p[0]))
+    } else {
+        (sub(p[2], p[0]), sub(p[3], p[1]))
+    };
+    let normal = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let dot = normal[0] * direction[0] + normal[1] * direction[1] + normal[2] * direction[2];
+
+    let mut ordered = nodes.to_vec();
+    if dot < 0.0 {
+        ordered[1..].reverse();
+    }
+    ordered
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn unsupported(block: ElementBlockRef) -> ParseError {
+    ParseError::GeometryError(format!(
+        "Cannot extrude {} elements (entity dim={}, tag={})",
+        block.element_type(),
+        block.entity_dim(),
+        block.entity_tag()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    /// A unit square of two triangles in the physical surface "My surface"
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $PhysicalNames\n1\n2 1 \"My surface\"\n$EndPhysicalNames\n\
+        $Entities\n0 0 1 0\n1 0 0 0 1 1 0 1 1 0\n$EndEntities\n\
+        $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+        $Elements\n1 2 1 2\n2 1 2 2\n1 1 2 3\n2 1 3 4\n$EndElements\n";
+
+    #[test]
+    fn test_extrude_square() {
+        let mesh = parse_msh(SQUARE).unwrap();
+        let extruded = extrude(&mesh, [0.0, 0.0, 0.2], 3).unwrap();
+
+        let prisms: usize = extruded
+            .element_blocks
+            .iter()
+            .filter(|b| b.element_type == ElementType::Prism6)
+            .map(|b| b.elements.len())
+            .sum();
+        assert_eq!(prisms, 2 * 3);
+
+        let names: Vec<(i32, &str)> = extruded
+            .physical_names
+            .iter()
+            .map(|pn| (pn.dimension as i32, pn.name.as_str()))
+            .collect();
+        assert!(names.contains(&(3, "My surface")));
+        assert!(names.contains(&(2, "My surface_bottom")));
+        assert!(names.contains(&(2, "My surface_top")));
+
+        let volume = &extruded.entities.as_ref().unwrap().volumes[0];
+        assert!((volume.max_z - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_extrude_rejects_zero_layers() {
+        let mesh = parse_msh(SQUARE).unwrap();
+        assert!(extrude(&mesh, [0.0, 0.0, 1.0], 0).is_err());
+    }
+}
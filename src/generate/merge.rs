@@ -0,0 +1,425 @@
+//! Merging of separately meshed parts into one mesh
+
+use super::replicate::{copy_entities, Offsets};
+use super::weld::PointGrid;
+use super::TagAllocator;
+use crate::error::Result;
+use crate::geometry::{bounds, Affine3, Tolerance};
+use crate::types::element::Element;
+use crate::types::{
+    Change, ElementBlock, ElementBlockCompact, ElementBlockRef, ElementType, Mesh, NodeTag,
+    PhysicalName, PhysicalTag,
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Options of [`Mesh::merge`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MergeOptions {
+    /// Fuse nodes of the other mesh with coincident nodes of this one
+    pub fuse_nodes: bool,
+    /// Largest distance between fused nodes, by default the absolute part of
+    /// the [`Tolerance`] derived from the size of both meshes
+    pub tolerance: Option<f64>,
+}
+
+impl MergeOptions {
+    pub fn fuse_nodes(mut self) -> Self {
+        self.fuse_nodes = true;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+}
+
+/// Append the post-processing views of `$source` to `$target`, remapping each
+/// entry with `$remap`
+///
+/// Views with the same name, time step and number of components are merged;
+/// the others are appended.
+macro_rules! merge_views {
+    ($target:expr, $source:expr, $remap:expr) => {
+        for view in $source {
+            let mut view = view.clone();
+            view.data.iter_mut().for_each($remap);
+            let same = $target.iter_mut().find(|v| {
+                v.string_tags.first() == view.string_tags.first()
+                    && v.integer_tags.get(..2) == view.integer_tags.get(..2)
+            });
+            match same {
+                Some(existing) => {
+                    existing.data.extend(view.data);
+                    if let Some(count) = existing.integer_tags.get_mut(2) {
+                        *count = existing.data.len() as i32;
+                    }
+                }
+                None => $target.push(view),
+            }
+        }
+    };
+}
+
+impl Mesh {
+    /// Build the mesh made of this mesh and `other`, e.g. the parts of an
+    /// assembly exported as separate files
+    ///
+    /// Node, element and entity tags of `other` are offset past those of this
+    /// mesh. Physical groups of `other` are matched to groups of this mesh with
+    /// the same dimension and name; the others keep their tag unless it is
+    /// taken, in which case they get a new one. Node and element sets and
+    /// post-processing views with the same name (and time step) are merged.
+    ///
+    /// With [`MergeOptions::fuse_nodes`], nodes of `other` within the tolerance
+    /// of a node of this mesh are replaced by it, and elements of `other` that
+    /// then duplicate an element of this mesh, e.g. faces on the interface, are
+    /// dropped. Nodes of `other` are never fused with each other.
+    ///
+    /// Partitions, ghost elements, parametrizations and the change log of
+    /// `other` are not merged, and the declared `$Nodes`/`$Elements` headers
+    /// are dropped. The merge is recorded in the [`Mesh::change_log`] of the
+    /// result.
+    ///
+    /// # Errors
+    /// Returns an error if the merged mesh fails [`Mesh::validate`].
+    pub fn merge(&self, other: &Mesh, options: &MergeOptions) -> Result<Mesh> {
+        let mut tags = TagAllocator::for_mesh(self);
+        let offsets = Offsets {
+            node: tags.node().0 - 1,
+            element: tags.element().0 - 1,
+            entity: [0, 1, 2, 3].map(|dim| tags.entity(dim).0 - 1),
+        };
+        let physical = physical_map(self, other, &mut tags);
+        let physical_tag = |dim: i32, tag: PhysicalTag| {
+            physical
+                .get(&(dim, tag.0.abs()))
+                .map_or(tag, |&new| PhysicalTag(tag.0.signum() * new.0))
+        };
+
+        let mut result = self.clone();
+        result.nodes_header = None;
+        result.elements_header = None;
+
+        // Nodes of `other` replaced by nodes of this mesh
+        let fused = if options.fuse_nodes {
+            fused_nodes(self, other, options.tolerance)
+        } else {
+            HashMap::new()
+        };
+        let node = |tag: NodeTag| fused.get(&tag).copied().unwrap_or(tag + offsets.node);
+
+        for block in &other.node_blocks {
+            let mut block = block.clone();
+            block.entity_tag = block.entity_tag + offsets.entity[block.entity_dim as usize];
+            block.nodes.retain(|n| !fused.contains_key(&n.tag));
+            for n in &mut block.nodes {
+                n.tag = n.tag + offsets.node;
+            }
+            if !block.nodes.is_empty() {
+                result.node_blocks.push(block);
+            }
+        }
+
+        let existing: HashSet<(ElementType, Vec<NodeTag>)> = if fused.is_empty() {
+            HashSet::new()
+        } else {
+            self.elements()
+                .map(|e| sorted_key(e.element_type(), e.nodes()))
+                .collect()
+        };
+        for block in other.element_block_refs() {
+            let entity_tag =
+                block.entity_tag() + offsets.entity[block.entity_dim().clamp(0, 3) as usize];
+            let elements: Vec<Element> = block
+                .elements()
+                .map(|e| {
+                    let nodes = e.nodes().iter().map(|&n| node(n)).collect();
+                    Element::new(e.tag() + offsets.element, nodes)
+                })
+                .filter(|e| !existing.contains(&sorted_key(block.element_type(), &e.nodes)))
+                .collect();
+            if elements.is_empty() {
+                continue;
+            }
+            let merged = ElementBlock::new(
+                block.entity_dim(),
+                entity_tag,
+                block.element_type(),
+                elements,
+            );
+            // Compact blocks of `other` stay compact, after the blocks so far
+            let position = result.element_blocks.len() + result.compact_element_blocks.len();
+            match ElementBlockCompact::from_block(&merged) {
+                Some(compact) if matches!(block, ElementBlockRef::Compact(_)) => {
+                    result.compact_element_blocks.push(ElementBlockCompact {
+                        position,
+                        ..compact
+                    })
+                }
+                _ => result.element_blocks.push(merged),
+            }
+        }
+
+        if self.entities.is_some() || other.entities.is_some() {
+            let mut entities = self.effective_entities().into_owned();
+            let first_new = [
+                entities.points.len(),
+                entities.curves.len(),
+                entities.surfaces.len(),
+                entities.volumes.len(),
+            ];
+            copy_entities(
+                &other.effective_entities(),
+                &mut entities,
+                &Affine3::identity(),
+                &offsets,
+            );
+            for e in &mut entities.points[first_new[0]..] {
+                e.physical_tags
+                    .iter_mut()
+                    .for_each(|t| *t = physical_tag(0, *t));
+            }
+            for e in &mut entities.curves[first_new[1]..] {
+                e.physical_tags
+                    .iter_mut()
+                    .for_each(|t| *t = physical_tag(1, *t));
+            }
+            for e in &mut entities.surfaces[first_new[2]..] {
+                e.physical_tags
+                    .iter_mut()
+                    .for_each(|t| *t = physical_tag(2, *t));
+            }
+            for e in &mut entities.volumes[first_new[3]..] {
+                e.physical_tags
+                    .iter_mut()
+                    .for_each(|t| *t = physical_tag(3, *t));
+            }
+            result.entities = Some(entities);
+        }
+        for pn in &other.physical_names {
+            let tag = physical_tag(pn.dimension as i32, pn.tag);
+            let known = result
+                .physical_names
+                .iter()
+                .any(|p| p.dimension == pn.dimension && p.tag == tag);
+            if !known {
+                result
+                    .physical_names
+                    .push(PhysicalName::new(pn.dimension, tag, pn.name.clone()));
+            }
+        }
+
+        for link in &other.periodic_links {
+            let mut link = link.clone();
+            let dim = link.entity_dim as usize;
+            link.entity_tag = link.entity_tag + offsets.entity[dim];
+            link.entity_tag_master = link.entity_tag_master + offsets.entity[dim];
+            for (slave, master) in &mut link.node_correspondences {
+                (*slave, *master) = (node(*slave), node(*master));
+            }
+            result.periodic_links.push(link);
+        }
+
+        merge_views!(result.node_data, &other.node_data, |(tag, _)| *tag =
+            node(*tag));
+        merge_views!(result.element_data, &other.element_data, |(tag, _)| {
+            *tag = *tag + offsets.element
+        });
+        merge_views!(
+            result.element_node_data,
+            &other.element_node_data,
+            |(tag, _, _)| *tag = *tag + offsets.element
+        );
+
+        for set in &other.node_sets {
+            let tags: BTreeSet<NodeTag> = set.tags.iter().map(|&t| node(t)).collect();
+            match result.node_sets.iter_mut().find(|s| s.name == set.name) {
+                Some(existing) => existing.tags.extend(tags),
+                None => result.node_sets.push(crate::NodeSet {
+                    tags,
+                    ..set.clone()
+                }),
+            }
+        }
+        for set in &other.element_sets {
+            let tags = set.tags.iter().map(|&t| t + offsets.element).collect();
+            match result.element_sets.iter_mut().find(|s| s.name == set.name) {
+                Some(existing) => existing.tags.extend(tags),
+                None => result.element_sets.push(crate::ElementSet {
+                    tags,
+                    ..set.clone()
+                }),
+            }
+        }
+
+        result.recount();
+        result.validate()?;
+        result.record_change(
+            Change::new("merge")
+                .with_count("nodes_added", result.num_nodes() - self.num_nodes())
+                .with_count("nodes_fused", fused.len())
+                .with_count(
+                    "elements_added",
+                    result.num_elements() - self.num_elements(),
+                ),
+        );
+        Ok(result)
+    }
+}
+
+/// New tags of the physical groups of `other`, keyed by dimension and tag
+fn physical_map(
+    mesh: &Mesh,
+    other: &Mesh,
+    tags: &mut TagAllocator,
+) -> HashMap<(i32, i32), PhysicalTag> {
+    let groups = |mesh: &Mesh| -> BTreeSet<(i32, i32)> {
+        let entities = mesh.effective_entities();
+        let entity_groups = entities
+            .points
+            .iter()
+            .map(|e| (0, &e.physical_tags))
+            .chain(entities.curves.iter().map(|e| (1, &e.physical_tags)))
+            .chain(entities.surfaces.iter().map(|e| (2, &e.physical_tags)))
+            .chain(entities.volumes.iter().map(|e| (3, &e.physical_tags)))
+            .flat_map(|(dim, tags)| tags.iter().map(move |t| (dim, t.0.abs())))
+            .collect::<Vec<_>>();
+        entity_groups
+            .into_iter()
+            .chain(
+                mesh.physical_names
+                    .iter()
+                    .map(|pn| (pn.dimension as i32, pn.tag.0.abs())),
+            )
+            .collect()
+    };
+    let name = |mesh: &Mesh, dim: i32, tag: i32| {
+        mesh.physical_names
+            .iter()
+            .find(|pn| pn.dimension as i32 == dim && pn.tag.0.abs() == tag)
+            .map(|pn| pn.name.clone())
+    };
+
+    let mut taken = groups(mesh);
+    let mut map = HashMap::new();
+    for (dim, tag) in groups(other) {
+        let named = name(other, dim, tag).and_then(|n| {
+            mesh.physical_names
+                .iter()
+                .find(|pn| pn.dimension as i32 == dim && pn.name == n)
+        });
+        let new = match named {
+            Some(pn) => pn.tag,
+            None if taken.insert((dim, tag)) => {
+                tags.reserve_physical(dim, PhysicalTag(tag));
+                PhysicalTag(tag)
+            }
+            None => {
+                let new = tags.physical(dim);
+                taken.insert((dim, new.0));
+                new
+            }
+        };
+        map.insert((dim, tag), new);
+    }
+    map
+}
+
+/// Nodes of `other` coinciding with a node of `mesh`, and that node
+fn fused_nodes(mesh: &Mesh, other: &Mesh, tolerance: Option<f64>) -> HashMap<NodeTag, NodeTag> {
+    let points = |mesh: &Mesh| -> Vec<(NodeTag, [f64; 3])> {
+        mesh.node_blocks
+            .iter()
+            .flat_map(|b| &b.nodes)
+            .map(|n| (n.tag, [n.x, n.y, n.z]))
+            .collect()
+    };
+    let (ours, theirs) = (points(mesh), points(other));
+    let tolerance = tolerance.unwrap_or_else(|| {
+        bounds(ours.iter().chain(&theirs).map(|(_, p)| *p))
+            .map_or(Tolerance::default().absolute, |(min, max)| {
+                Tolerance::from_bounds(min, max).absolute
+            })
+    });
+
+    let mut grid = PointGrid::new(tolerance);
+    for &(tag, p) in &ours {
+        grid.insert(tag, p, 0);
+    }
+    theirs
+        .into_iter()
+        .filter_map(|(tag, p)| Some((tag, grid.find_other_group(p, 1)?)))
+        .collect()
+}
+
+fn sorted_key(element_type: ElementType, nodes: &[NodeTag]) -> (ElementType, Vec<NodeTag>) {
+    let mut key = nodes.to_vec();
+    key.sort_unstable();
+    (element_type, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit square bounded by four curves, with the named physical surface 6
+    /// and the unnamed physical curve 5 on all sides but the top
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $PhysicalNames\n1\n2 6 \"My surface\"\n$EndPhysicalNames\n\
+        $Entities\n0 4 1 0\n1 0 0 0 1 0 0 1 5 0\n2 1 0 0 1 1 0 1 5 0\n3 0 1 0 1 1 0 0 0\n\
+        4 0 0 0 0 1 0 1 5 0\n1 0 0 0 1 1 0 1 6 0\n$EndEntities\n\
+        $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+        $Elements\n5 6 1 6\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n\
+        1 4 1 1\n4 4 1\n2 1 2 2\n5 1 2 3\n6 1 3 4\n$EndElements\n";
+
+    /// Two copies of [`SQUARE`], the second one shifted to the right
+    fn parts() -> (Mesh, Mesh) {
+        let part = crate::parse_msh(SQUARE).unwrap();
+        let mut neighbour = part.clone();
+        for n in neighbour.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+            n.x += 1.0;
+        }
+        if let Some(entities) = neighbour.entities.as_mut() {
+            entities.recompute_bounds(&neighbour.node_blocks);
+        }
+        (part, neighbour)
+    }
+
+    #[test]
+    fn test_merge_side_by_side_parts() {
+        let (part, neighbour) = parts();
+        let merged = part.merge(&neighbour, &MergeOptions::default()).unwrap();
+        assert_eq!(merged.num_nodes(), 8);
+        assert_eq!(merged.num_elements(), 12);
+        // Both parts share the named surface group; the unnamed curve group
+        // of the second part would collide and gets a new tag
+        assert_eq!(merged.physical_names.len(), part.physical_names.len());
+        let entities = merged.entities.as_ref().unwrap();
+        assert!(entities
+            .surfaces
+            .iter()
+            .all(|s| s.physical_tags == [PhysicalTag(6)]));
+        let curve_groups: BTreeSet<_> = entities
+            .curves
+            .iter()
+            .flat_map(|c| c.physical_tags.iter().copied())
+            .collect();
+        assert_eq!(
+            curve_groups,
+            BTreeSet::from([PhysicalTag(5), PhysicalTag(6)])
+        );
+    }
+
+    #[test]
+    fn test_merge_fuses_interface_nodes() {
+        let (part, neighbour) = parts();
+        let fused = part
+            .merge(&neighbour, &MergeOptions::default().fuse_nodes())
+            .unwrap();
+        // Nodes 2 and 3 are shared, and the left edge of the second part
+        // duplicates the right edge of the first
+        assert_eq!(fused.num_nodes(), 6);
+        assert_eq!(fused.num_elements(), 11);
+    }
+}
@@ -0,0 +1,18 @@
+//! Mesh generation from existing parsed meshes
+//!
+//! Generators never modify their input; they return a new, validated [`Mesh`](crate::Mesh).
+//! [`Mesh::merge`](crate::Mesh::merge) combines separately meshed parts the same way.
+//! New tags are handed out by a [`TagAllocator`], which code adding its own
+//! nodes, elements or entities to a mesh can use as well.
+
+mod allocator;
+mod extrude;
+mod merge;
+mod replicate;
+mod weld;
+
+pub use allocator::TagAllocator;
+pub use extrude::extrude;
+pub use merge::MergeOptions;
+pub use replicate::{mirror, rotate_pattern};
+pub(crate) use weld::PointGrid;
@@ -0,0 +1,523 @@
+//! Mirrored and rotational replication of meshes
+
+use super::weld::PointGrid;
+use crate::error::{ParseError, Result};
+use crate::geometry::{bounds, Affine3, Axis, Plane, Tolerance};
+use crate::types::element::Element;
+use crate::types::{
+    CurveEntity, ElementBlock, ElementTag, ElementType, Entities, EntityTag, Mesh, Node, NodeBlock,
+    NodeTag, PeriodicLink, PointEntity, SurfaceEntity, VolumeEntity,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Build the symmetric model made of `mesh` and its mirror image across `plane`
+///
+/// Nodes of the mirrored copy that coincide with original nodes (typically the
+/// nodes on the symmetry plane), within the [`Tolerance`] derived from the size
+/// of the result, are welded, and interface elements that end up
+/// duplicated are removed. Mirrored elements are renumbered so that their
+/// orientation stays positive.
+///
+/// The copy gets node, element and entity tags offset past the originals and
+/// shares the physical groups and the coordinate system of the original. Periodic links are duplicated with
+/// their affine transform mapped into the mirrored frame; links made interior by
+/// welding are dropped. Post-processing data, partitions, ghost elements and
+/// parametrizations are not replicated.
+///
+/// # Errors
+/// Returns an error if the plane normal is zero or the mesh contains element types
+/// whose mirrored node ordering is not known (only linear elements are supported).
+pub fn mirror(mesh: &Mesh, plane: &Plane) -> Result<Mesh> {
+    let reflection = Affine3::reflection(plane).ok_or_else(|| {
+        ParseError::GeometryError("Mirror plane normal must not be zero".to_string())
+    })?;
+    replicate(mesh, &[Affine3::identity(), reflection])
+}
+
+/// Build the cyclic pattern made of `n` copies of `mesh` rotated about `axis`
+///
+/// Copy `k` is rotated by `k * 2π / n`. Interfaces between neighbouring sectors
+/// are welded and handled as in [`mirror`].
+///
+/// # Errors
+/// Returns an error if `n` is zero or the axis direction is zero.
+pub fn rotate_pattern(mesh: &Mesh, axis: &Axis, n: usize) -> Result<Mesh> {
+    if n == 0 {
+        return Err(ParseError::GeometryError(
+            "Rotational pattern requires at least one copy".to_string(),
+        ));
+    }
+
+    let transforms = (0..n)
+        .map(|k| {
+            let angle = k as f64 * std::f64::consts::TAU / n as f64;
+            Affine3::rotation(axis, angle).ok_or_else(|| {
+                ParseError::GeometryError("Rotation axis direction must not be zero".to_string())
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    replicate(mesh, &transforms)
+}
+
+/// Tag offsets applied to copy `k`
+pub(super) struct Offsets {
+    pub(super) node: u64,
+    pub(super) element: u64,
+    pub(super) entity: [i32; 4],
+}
+
+/// Node or element tags of the original mesh, `first..first + len`
+///
+/// `first` is 1 unless the mesh uses tag 0, so copies of a mesh tagged from 1
+/// continue right after the previous copy and a tag 0 still gets its own slot.
+#[derive(Debug, Clone, Copy)]
+struct TagSpan {
+    first: u64,
+    len: u64,
+}
+
+impl TagSpan {
+    fn of(tags: impl Iterator<Item = u64>) -> Self {
+        let (min, max) = tags.fold((1, 0), |(min, max), tag| (min.min(tag), max.max(tag)));
+        Self {
+            first: min,
+            len: max.saturating_add(1).saturating_sub(min).max(1),
+        }
+    }
+
+    /// Offset added to the tags of copy `k`
+    fn offset(self, k: usize) -> u64 {
+        k as u64 * self.len
+    }
+
+    /// The copy a tag of the replicated mesh belongs to
+    fn copy_of(self, tag: u64) -> usize {
+        (tag.saturating_sub(self.first) / self.len) as usize
+    }
+}
+
+fn replicate(mesh: &Mesh, transforms: &[Affine3]) -> Result<Mesh> {
+    let node_span = TagSpan::of(
+        mesh.node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter())
+            .map(|n| n.tag.0),
+    );
+    let element_span = TagSpan::of(mesh.elements().map(|e| e.tag().0));
+    let max_entity_tags = max_entity_tags(mesh);
+
+    let mut result = Mesh::new(mesh.format.clone());
+    result.physical_names = mesh.physical_names.clone();
+    result.coordinate_system = mesh.coordinate_system.clone();
+    let mut entities = mesh.entities.as_ref().map(|_| Entities::new());
+
+    for (k, transform) in transforms.iter().enumerate() {
+        let offsets = Offsets {
+            node: node_span.offset(k),
+            element: element_span.offset(k),
+            entity: max_entity_tags.map(|max| k as i32 * max),
+        };
+        let reflect = transform.determinant() < 0.0;
+
+        for block in &mesh.node_blocks {
+            let dim = block.entity_dim as usize;
+            result.node_blocks.push(NodeBlock {
+                entity_dim: block.entity_dim,
+                entity_tag: block.entity_tag + offsets.entity[dim],
+                // Parametric coordinates refer to the original CAD entity
+                parametric: block.parametric && k == 0,
+                nodes: block
+                    .nodes
+                    .iter()
+                    .map(|node| {
+                        let [x, y, z] = transform.apply([node.x, node.y, node.z]);
+                        Node {
+                            tag: node.tag + offsets.node,
+                            x,
+                            y,
+                            z,
+                            parametric_coords: if k == 0 {
+                                node.parametric_coords.clone()
+                            } else {
+                                None
+                            },
+                        }
+                    })
+                    .collect(),
+            });
+        }
+
+        for block in mesh.element_block_refs() {
+            let order = if reflect {
+                Some(mirrored_order(block.element_type()).ok_or_else(|| {
+                    ParseError::GeometryError(format!(
+                        "Mirroring {} elements is not supported",
+                        block.element_type()
+                    ))
+                })?)
+            } else {
+                None
+            };
+            let dim = block.entity_dim().clamp(0, 3) as usize;
+
+            let elements = block
+                .elements()
+                .map(|element| {
+                    let nodes = element.nodes();
+                    let nodes: Vec<NodeTag> = match order {
+                        Some(order) => order.iter().map(|&i| nodes[i]).collect(),
+                        None => nodes.to_vec(),
+                    };
+                    Element::new(
+                        element.tag() + offsets.element,
+                        nodes.into_iter().map(|n| n + offsets.node).collect(),
+                    )
+                })
+                .collect();
+            result.element_blocks.push(ElementBlock::new(
+                block.entity_dim(),
+                block.entity_tag() + offsets.entity[dim],
+                block.element_type(),
+                elements,
+            ));
+        }
+
+        if let (Some(source), Some(target)) = (&mesh.entities, entities.as_mut()) {
+            copy_entities(source, target, transform, &offsets);
+        }
+
+        for link in &mesh.periodic_links {
+            let dim = link.entity_dim as usize;
+            // Express the slave <- master map in the frame of this copy
+            let affine_transform = match (
+                Affine3::from_gmsh(&link.affine_transform),
+                transform.inverse(),
+            ) {
+                (Some(affine), Some(inverse)) => inverse.then(&affine).then(transform).to_gmsh(),
+                _ => link.affine_transform.clone(),
+            };
+            result.periodic_links.push(PeriodicLink {
+                entity_dim: link.entity_dim,
+                entity_tag: link.entity_tag + offsets.entity[dim],
+                entity_tag_master: link.entity_tag_master + offsets.entity[dim],
+                affine_transform,
+                node_correspondences: link
+                    .node_correspondences
+                    .iter()
+                    .map(|&(slave, master)| (slave + offsets.node, master + offsets.node))
+                    .collect(),
+            });
+        }
+    }
+
+    result.entities = entities;
+    weld_copies(&mut result, node_span, element_span, max_entity_tags);
+    // Transformed boxes of rotated copies are loose; tighten them to the nodes
+    if let Some(entities) = result.entities.as_mut() {
+        entities.recompute_bounds(&result.node_blocks);
+    }
+    result.validate()?;
+    Ok(result)
+}
+
+/// Merge coincident nodes of different copies and drop the resulting duplicates
+fn weld_copies(
+    mesh: &mut Mesh,
+    node_span: TagSpan,
+    element_span: TagSpan,
+    max_entity_tags: [i32; 4],
+) {
+    let copy_of = |tag: NodeTag| node_span.copy_of(tag.0);
+    let copy_of_element = |tag: ElementTag| element_span.copy_of(tag.0);
+    let Some((min, max)) = bounds(
+        mesh.node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter())
+            .map(|n| [n.x, n.y, n.z]),
+    ) else {
+        return;
+    };
+
+    let mut grid = PointGrid::new(Tolerance::from_bounds(min, max).absolute);
+    let mut replacement: HashMap<NodeTag, NodeTag> = HashMap::new();
+    let mut welded: HashSet<NodeTag> = HashSet::new();
+
+    for block in &mut mesh.node_blocks {
+        block.nodes.retain(|node| {
+            let p = [node.x, node.y, node.z];
+            match grid.find_other_group(p, copy_of(node.tag)) {
+                Some(existing) => {
+                    replacement.insert(node.tag, existing);
+                    welded.insert(existing);
+                    false
+                }
+                None => {
+                    grid.insert(node.tag, p, copy_of(node.tag));
+                    true
+                }
+            }
+        });
+    }
+    mesh.node_blocks.retain(|b| !b.nodes.is_empty());
+
+    let remap = |tag: NodeTag| replacement.get(&tag).copied().unwrap_or(tag);
+
+    // Elements duplicated by welding (e.g. faces on the interface) are kept
+    // once; elements of the same copy sharing their nodes are distinct
+    let mut first_copy: HashMap<(ElementType, Vec<NodeTag>), usize> = HashMap::new();
+    for block in &mut mesh.element_blocks {
+        let element_type = block.element_type;
+        block.elements.retain_mut(|element| {
+            for node in &mut element.nodes {
+                *node = remap(*node);
+            }
+            let mut key = element.nodes.clone();
+            key.sort_unstable();
+            let copy = copy_of_element(element.tag);
+            *first_copy.entry((element_type, key)).or_insert(copy) == copy
+        });
+    }
+    mesh.element_blocks.retain(|b| !b.elements.is_empty());
+
+    // Periodic links lying on welded interfaces are now interior
+    mesh.periodic_links.retain_mut(|link| {
+        for pair in &mut link.node_correspondences {
+            *pair = (remap(pair.0), remap(pair.1));
+        }
+        link.node_correspondences
+            .retain(|(slave, master)| slave != master);
+        !link.node_correspondences.is_empty()
+            && !link
+                .node_correspondences
+                .iter()
+                .all(|(slave, _)| welded.contains(slave))
+    });
+
+    // Entity copies left without nodes or elements disappear
+    let used: HashSet<(i32, EntityTag)> = mesh
+        .node_blocks
+        .iter()
+        .map(|b| (b.entity_dim(), b.entity_tag))
+        .chain(
+            mesh.element_blocks
+                .iter()
+                .map(|b| (b.entity_dim, b.entity_tag)),
+        )
+        .collect();
+    if let Some(entities) = mesh.entities.as_mut() {
+        // Entities of the first copy are always kept, even when empty
+        let keep = |dim: usize, tag: EntityTag| {
+            tag.0 <= max_entity_tags[dim] || used.contains(&(dim as i32, tag))
+        };
+        entities.points.retain(|e| keep(0, e.tag));
+        entities.curves.retain(|e| keep(1, e.tag));
+        entities.surfaces.retain(|e| keep(2, e.tag));
+        entities.volumes.retain(|e| keep(3, e.tag));
+
+        let points: HashSet<i32> = entities.points.iter().map(|e| e.tag.0).collect();
+        let curves: HashSet<i32> = entities.curves.iter().map(|e| e.tag.0).collect();
+        let surfaces: HashSet<i32> = entities.surfaces.iter().map(|e| e.tag.0).collect();
+        for curve in &mut entities.curves {
+            curve.bounding_points.retain(|b| points.contains(&b.abs()));
+        }
+        for surface in &mut entities.surfaces {
+            surface
+                .bounding_curves
+                .retain(|b| curves.contains(&b.abs()));
+        }
+        for volume in &mut entities.volumes {
+            volume
+                .bounding_surfaces
+                .retain(|b| surfaces.contains(&b.abs()));
+        }
+    }
+}
+
+fn max_entity_tags(mesh: &Mesh) -> [i32; 4] {
+    let mut max = [0; 4];
+    if let Some(entities) = &mesh.entities {
+        max[0] = entities.points.iter().map(|e| e.tag.0).max().unwrap_or(0);
+        max[1] = entities.curves.iter().map(|e| e.tag.0).max().unwrap_or(0);
+        max[2] = entities.surfaces.iter().map(|e| e.tag.0).max().unwrap_or(0);
+        max[3] = entities.volumes.iter().map(|e| e.tag.0).max().unwrap_or(0);
+    }
+    for block in &mesh.node_blocks {
+        let dim = block.entity_dim as usize;
+        max[dim] = max[dim].max(block.entity_tag.0);
+    }
+    for block in mesh.element_block_refs() {
+        let dim = block.entity_dim().clamp(0, 3) as usize;
+        max[dim] = max[dim].max(block.entity_tag().0);
+    }
+    max
+}
+
+pub(super) fn copy_entities(
+    source: &Entities,
+    target: &mut Entities,
+    transform: &Affine3,
+    offsets: &Offsets,
+) {
+    let shift = |tags: &[i32], dim: usize| -> Vec<i32> {
+        tags.iter()
+            .map(|&t| t.signum() * (t.abs() + offsets.entity[dim]))
+            .collect()
+    };
+    let moved_box = |min: [f64; 3], max: [f64; 3]| {
+        let corners = (0..8).map(|i| {
+            transform.apply([
+                if i & 1 == 0 { min[0] } else { max[0] },
+                if i & 2 == 0 { min[1] } else { max[1] },
+                if i & 4 == 0 { min[2] } else { max[2] },
+            ])
+        });
+        bounds(corners).unwrap_or((min, max))
+    };
+
+    for p in &source.points {
+        let [x, y, z] = transform.apply([p.x, p.y, p.z]);
+        target.points.push(PointEntity {
+            tag: p.tag + offsets.entity[0],
+            x,
+            y,
+            z,
+            physical_tags: p.physical_tags.clone(),
+        });
+    }
+    for c in &source.curves {
+        let (min, max) = moved_box([c.min_x, c.min_y, c.min_z], [c.max_x, c.max_y, c.max_z]);
+        target.curves.push(CurveEntity {
+            tag: c.tag + offsets.entity[1],
+            min_x: min[0],
+            min_y: min[1],
+            min_z: min[2],
+            max_x: max[0],
+            max_y: max[1],
+            max_z: max[2],
+            physical_tags: c.physical_tags.clone(),
+            bounding_points: shift(&c.bounding_points, 0),
+        });
+    }
+    for s in &source.surfaces {
+        let (min, max) = moved_box([s.min_x, s.min_y, s.min_z], [s.max_x, s.max_y, s.max_z]);
+        target.surfaces.push(SurfaceEntity {
+            tag: s.tag + offsets.entity[2],
+            min_x: min[0],
+            min_y: min[1],
+            min_z: min[2],
+            max_x: max[0],
+            max_y: max[1],
+            max_z: max[2],
+            physical_tags: s.physical_tags.clone(),
+            bounding_curves: shift(&s.bounding_curves, 1),
+        });
+    }
+    for v in &source.volumes {
+        let (min, max) = moved_box([v.min_x, v.min_y, v.min_z], [v.max_x, v.max_y, v.max_z]);
+        target.volumes.push(VolumeEntity {
+            tag: v.tag + offsets.entity[3],
+            min_x: min[0],
+            min_y: min[1],
+            min_z: min[2],
+            max_x: max[0],
+            max_y: max[1],
+            max_z: max[2],
+            physical_tags: v.physical_tags.clone(),
+            bounding_surfaces: shift(&v.bounding_surfaces, 2),
+        });
+    }
+}
+
+/// Node permutation that restores positive orientation after a reflection
+fn mirrored_order(element_type: ElementType) -> Option<&'static [usize]> {
+    match element_type {
+        ElementType::Point => Some(&[0]),
+        ElementType::Line2 => Some(&[0, 1]),
+        ElementType::Triangle3 => Some(&[0, 2, 1]),
+        ElementType::Quadrangle4 => Some(&[0, 3, 2, 1]),
+        ElementType::Tetrahedron4 => Some(&[0, 2, 1, 3]),
+        ElementType::Hexahedron8 => Some(&[4, 5, 6, 7, 0, 1, 2, 3]),
+        ElementType::Prism6 => Some(&[3, 4, 5, 0, 1, 2]),
+        ElementType::Pyramid5 => Some(&[0, 3, 2, 1, 4]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SQUARE;
+    use crate::types::CoordinateSystem;
+
+    #[test]
+    fn test_mirror_welds_symmetry_plane() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let plane = Plane::new([1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let mirrored = mirror(&mesh, &plane).unwrap();
+        // Nodes 2 and 3 lie on the plane
+        assert_eq!(mirrored.num_nodes(), 6);
+        assert_eq!(mirrored.num_elements(), 4);
+    }
+
+    #[test]
+    fn test_mirror_with_zero_tags() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 4 0 3\n2 1 0 4\n0\n1\n2\n3\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+            $Elements\n1 2 0 1\n2 1 2 2\n0 0 1 2\n1 0 2 3\n$EndElements\n";
+        let mesh = crate::parse_msh(content).unwrap();
+        let plane = Plane::new([1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let mirrored = mirror(&mesh, &plane).unwrap();
+        assert_eq!(mirrored.num_nodes(), 6);
+        assert_eq!(mirrored.num_elements(), 4);
+    }
+
+    #[test]
+    fn test_mirror_keeps_positive_orientation() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let plane = Plane::new([1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let mirrored = mirror(&mesh, &plane).unwrap();
+        let coords = crate::geometry::node_coordinates(&mirrored);
+        for element in mirrored.element_blocks.iter().flat_map(|b| &b.elements) {
+            let [a, b, c] = [0, 1, 2].map(|i| coords[&element.nodes[i]]);
+            let area = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+            assert!(area > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_rotate_pattern_shares_axis_node() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let axis = Axis::new([0.0; 3], [0.0, 0.0, 1.0]);
+        let pattern = rotate_pattern(&mesh, &axis, 4).unwrap();
+        // The origin, four nodes on the x and y axes and four corners
+        assert_eq!(pattern.num_nodes(), 9);
+        assert_eq!(pattern.num_elements(), 8);
+        assert!(rotate_pattern(&mesh, &axis, 0).is_err());
+    }
+
+    #[test]
+    fn test_copies_keep_coordinate_system() {
+        let mut mesh = crate::parse_msh(SQUARE).unwrap();
+        mesh.coordinate_system = Some(CoordinateSystem::new("part 1"));
+        let plane = Plane::new([1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        let mirrored = mirror(&mesh, &plane).unwrap();
+        assert_eq!(mirrored.coordinate_system, mesh.coordinate_system);
+        let axis = Axis::new([0.0; 3], [0.0, 0.0, 1.0]);
+        let pattern = rotate_pattern(&mesh, &axis, 4).unwrap();
+        assert_eq!(pattern.coordinate_system, mesh.coordinate_system);
+    }
+
+    #[test]
+    fn test_weld_keeps_elements_sharing_nodes_within_a_copy() {
+        // Lines 1 and 2 lie on the mirror plane, on different curves
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 3\n1 1 0 3\n1\n2\n3\n0 0 0\n0 1 0\n1 0 0\n$EndNodes\n\
+            $Elements\n3 3 1 3\n1 1 1 1\n1 1 2\n1 2 1 1\n2 1 2\n1 3 1 1\n3 1 3\n\
+            $EndElements\n";
+        let mesh = crate::parse_msh(content).unwrap();
+        let plane = Plane::new([0.0; 3], [1.0, 0.0, 0.0]);
+        let mirrored = mirror(&mesh, &plane).unwrap();
+        assert_eq!(mirrored.num_nodes(), 4);
+        assert_eq!(mirrored.num_elements(), 4);
+    }
+}
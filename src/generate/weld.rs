@@ -0,0 +1,67 @@
+//! Detection of coincident nodes using a uniform hash grid
+
+use crate::types::NodeTag;
+use std::collections::HashMap;
+
+/// Node tag, position and group of an inserted point
+type GridPoint = (NodeTag, [f64; 3], usize);
+
+/// Spatial hash of points for tolerance-based coincidence queries
+pub(crate) struct PointGrid {
+    cell_size: f64,
+    tolerance: f64,
+    cells: HashMap<[i64; 3], Vec<GridPoint>>,
+}
+
+impl PointGrid {
+    /// Create a grid matching points closer than `tolerance`
+    pub(crate) fn new(tolerance: f64) -> Self {
+        let tolerance = tolerance.max(f64::MIN_POSITIVE);
+        Self {
+            cell_size: tolerance * 2.0,
+            tolerance,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, p: [f64; 3]) -> [i64; 3] {
+        [
+            (p[0] / self.cell_size).floor() as i64,
+            (p[1] / self.cell_size).floor() as i64,
+            (p[2] / self.cell_size).floor() as i64,
+        ]
+    }
+
+    /// Insert a point with its node tag and the group (copy, mesh, ...) it came from
+    pub(crate) fn insert(&mut self, tag: NodeTag, p: [f64; 3], group: usize) {
+        let cell = self.cell(p);
+        self.cells.entry(cell).or_default().push((tag, p, group));
+    }
+
+    /// Find a previously inserted point within tolerance that belongs to another group
+    pub(crate) fn find_other_group(&self, p: [f64; 3], group: usize) -> Option<NodeTag> {
+        let [cx, cy, cz] = self.cell(p);
+        let mut best: Option<(f64, NodeTag)> = None;
+
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(points) = self.cells.get(&[cx + dx, cy + dy, cz + dz]) else {
+                        continue;
+                    };
+                    for &(tag, q, other_group) in points {
+                        if other_group == group {
+                            continue;
+                        }
+                        let d = crate::geometry::distance(p, q);
+                        if d <= self.tolerance && best.is_none_or(|(best_d, _)| d < best_d) {
+                            best = Some((d, tag));
+                        }
+                    }
+                }
+            }
+        }
+
+        best.map(|(_, tag)| tag)
+    }
+}
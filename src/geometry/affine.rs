@@ -0,0 +1,278 @@
+//! Affine transformations of 3D points
+
+/// Affine transformation `p -> A p + t` stored as the top three rows of a 4x4 matrix
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct Affine3 {
+    /// Row-major `[A | t]`
+    pub matrix: [[f64; 4]; 3],
+}
+
+/// A plane given by a point on it and its normal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub point: [f64; 3],
+    pub normal: [f64; 3],
+}
+
+/// A line in space given by a point on it and its direction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Axis {
+    pub point: [f64; 3],
+    pub direction: [f64; 3],
+}
+
+impl Plane {
+    pub fn new(point: [f64; 3], normal: [f64; 3]) -> Self {
+        Self { point, normal }
+    }
+}
+
+impl Axis {
+    pub fn new(point: [f64; 3], direction: [f64; 3]) -> Self {
+        Self { point, direction }
+    }
+}
+
+impl Default for Affine3 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Affine3 {
+    pub fn identity() -> Self {
+        Self::from_linear(
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            [0.0; 3],
+        )
+    }
+
+    /// Build a transformation from its linear part and translation
+    pub fn from_linear(linear: [[f64; 3]; 3], translation: [f64; 3]) -> Self {
+        let mut matrix = [[0.0; 4]; 3];
+        for i in 0..3 {
+            matrix[i][..3].copy_from_slice(&linear[i]);
+            matrix[i][3] = translation[i];
+        }
+        Self { matrix }
+    }
+
+    pub fn translation(offset: [f64; 3]) -> Self {
+        Self::from_linear(Self::identity().linear(), offset)
+    }
+
+    /// Scaling about the origin
+    pub fn scaling(factors: [f64; 3]) -> Self {
+        Self::from_linear(
+            [
+                [factors[0], 0.0, 0.0],
+                [0.0, factors[1], 0.0],
+                [0.0, 0.0, factors[2]],
+            ],
+            [0.0; 3],
+        )
+    }
+
+    /// Rotation by `angle` radians about `axis` (right-hand rule)
+    ///
+    /// Returns `None` if the axis direction is the zero vector.
+    pub fn rotation(axis: &Axis, angle: f64) -> Option<Self> {
+        let [x, y, z] = normalize(axis.direction)?;
+        let (s, c) = angle.sin_cos();
+        let t = 1.0 - c;
+        let linear = [
+            [t * x * x + c, t * x * y - s * z, t * x * z + s * y],
+            [t * x * y + s * z, t * y * y + c, t * y * z - s * x],
+            [t * x * z - s * y, t * y * z + s * x, t * z * z + c],
+        ];
+        Some(Self::about_point(linear, axis.point))
+    }
+
+    /// Reflection across `plane`
+    ///
+    /// Returns `None` if the plane normal is the zero vector.
+    pub fn reflection(plane: &Plane) -> Option<Self> {
+        let n = normalize(plane.normal)?;
+        let mut linear = [[0.0; 3]; 3];
+        for (i, row) in linear.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                let delta = if i == j { 1.0 } else { 0.0 };
+                *value = delta - 2.0 * n[i] * n[j];
+            }
+        }
+        Some(Self::about_point(linear, plane.point))
+    }
+
+    /// Parse the 16-value row-major 4x4 matrix used by `$Periodic`
+    ///
+    /// Returns `None` if `values` does not hold exactly 16 entries.
+    pub fn from_gmsh(values: &[f64]) -> Option<Self> {
+        if values.len() != 16 {
+            return None;
+        }
+        let mut matrix = [[0.0; 4]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row.copy_from_slice(&values[i * 4..i * 4 + 4]);
+        }
+        Some(Self { matrix })
+    }
+
+    /// The 16-value row-major 4x4 matrix used by `$Periodic`
+    pub fn to_gmsh(&self) -> Vec<f64> {
+        let mut values: Vec<f64> = self.matrix.iter().flatten().copied().collect();
+        values.extend_from_slice(&[0.0, 0.0, 0.0, 1.0]);
+        values
+    }
+
+    /// Linear part `A`
+    pub fn linear(&self) -> [[f64; 3]; 3] {
+        let m = &self.matrix;
+        [
+            [m[0][0], m[0][1], m[0][2]],
+            [m[1][0], m[1][1], m[1][2]],
+            [m[2][0], m[2][1], m[2][2]],
+        ]
+    }
+
+    /// Translation part `t`
+    pub fn translation_part(&self) -> [f64; 3] {
+        [self.matrix[0][3], self.matrix[1][3], self.matrix[2][3]]
+    }
+
+    /// Transform a point
+    pub fn apply(&self, p: [f64; 3]) -> [f64; 3] {
+        let v = self.apply_vector(p);
+        let t = self.translation_part();
+        [v[0] + t[0], v[1] + t[1], v[2] + t[2]]
+    }
+
+    /// Transform a direction (ignores the translation)
+    pub fn apply_vector(&self, v: [f64; 3]) -> [f64; 3] {
+        let m = &self.matrix;
+        [
+            m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+            m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+            m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+        ]
+    }
+
+    /// Axis-aligned bounding box of the image of the box `(min, max)`
+    ///
+    /// Exact for translations, scalings and quarter turns; rotated boxes grow.
+    pub fn apply_box(&self, min: [f64; 3], max: [f64; 3]) -> ([f64; 3], [f64; 3]) {
+        let corners = (0..8).map(|c| {
+            let corner = [0, 1, 2].map(|k| if c >> k & 1 == 0 { min[k] } else { max[k] });
+            self.apply(corner)
+        });
+        super::bounds(corners).unwrap_or((min, max))
+    }
+
+    /// Composition applying `self` first, then `next`
+    pub fn then(&self, next: &Affine3) -> Affine3 {
+        let a = next.linear();
+        let b = self.linear();
+        let mut linear = [[0.0; 3]; 3];
+        for (i, row) in linear.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+            }
+        }
+        Self::from_linear(linear, next.apply(self.translation_part()))
+    }
+
+    /// Determinant of the linear part; negative for orientation-reversing maps
+    pub fn determinant(&self) -> f64 {
+        let m = self.linear();
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Inverse transformation, or `None` if the linear part is singular
+    pub fn inverse(&self) -> Option<Affine3> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let m = self.linear();
+        let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        let inv = [
+            [
+                cofactor(1, 2, 1, 2) / det,
+                -cofactor(0, 2, 1, 2) / det,
+                cofactor(0, 1, 1, 2) / det,
+            ],
+            [
+                -cofactor(1, 2, 0, 2) / det,
+                cofactor(0, 2, 0, 2) / det,
+                -cofactor(0, 1, 0, 2) / det,
+            ],
+            [
+                cofactor(1, 2, 0, 1) / det,
+                -cofactor(0, 2, 0, 1) / det,
+                cofactor(0, 1, 0, 1) / det,
+            ],
+        ];
+        let linear_inverse = Self::from_linear(inv, [0.0; 3]);
+        let t = linear_inverse.apply_vector(self.translation_part());
+        Some(Self::from_linear(inv, [-t[0], -t[1], -t[2]]))
+    }
+
+    /// Linear map applied about a fixed point instead of the origin
+    fn about_point(linear: [[f64; 3]; 3], point: [f64; 3]) -> Self {
+        let moved = Self::from_linear(linear, [0.0; 3]).apply_vector(point);
+        Self::from_linear(
+            linear,
+            [
+                point[0] - moved[0],
+                point[1] - moved[1],
+                point[2] - moved[2],
+            ],
+        )
+    }
+}
+
+fn normalize(v: [f64; 3]) -> Option<[f64; 3]> {
+    let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return None;
+    }
+    Some([v[0] / norm, v[1] / norm, v[2] / norm])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f64; 3], b: [f64; 3]) {
+        for i in 0..3 {
+            assert!((a[i] - b[i]).abs() < 1e-12, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_rotation_and_inverse() {
+        let axis = Axis::new([1.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+        let rotation = Affine3::rotation(&axis, std::f64::consts::FRAC_PI_2).unwrap();
+
+        assert_close(rotation.apply([2.0, 0.0, 0.0]), [1.0, 1.0, 0.0]);
+        let back = rotation.then(&rotation.inverse().unwrap());
+        assert_close(back.apply([3.0, -2.0, 5.0]), [3.0, -2.0, 5.0]);
+    }
+
+    #[test]
+    fn test_reflection() {
+        let plane = Plane::new([1.0, 0.0, 0.0], [2.0, 0.0, 0.0]);
+        let reflection = Affine3::reflection(&plane).unwrap();
+
+        assert_close(reflection.apply([0.0, 1.0, 2.0]), [2.0, 1.0, 2.0]);
+        assert!(reflection.determinant() < 0.0);
+    }
+}
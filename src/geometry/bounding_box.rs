@@ -0,0 +1,171 @@
+//! Axis-aligned bounding boxes of a mesh and of its parts
+
+use super::{bounds, node_coordinates};
+use crate::types::{EntityTag, Mesh, NodeTag, PhysicalTag};
+use std::collections::BTreeMap;
+
+/// Axis-aligned box `(min, max)`
+type BoundingBox = ([f64; 3], [f64; 3]);
+
+impl Mesh {
+    /// Bounding box `(min, max)` of all nodes, or `None` if the mesh has no nodes
+    pub fn bounding_box(&self) -> Option<([f64; 3], [f64; 3])> {
+        let nodes = self.node_blocks.iter().flat_map(|b| &b.nodes);
+        bounds(nodes.map(|n| [n.x, n.y, n.z]))
+    }
+
+    /// Bounding box of every entity with nodes or elements, by `(dimension, tag)`
+    ///
+    /// The box of an entity encloses the nodes of its node blocks and the nodes
+    /// of its elements, so it includes the boundary nodes that Gmsh stores on
+    /// lower-dimensional entities. Partition entities get their own boxes.
+    pub fn entity_bounding_boxes(&self) -> BTreeMap<(i32, EntityTag), ([f64; 3], [f64; 3])> {
+        let coords = node_coordinates(self);
+        let mut boxes = BTreeMap::new();
+        for block in &self.node_blocks {
+            let points = block.nodes.iter().map(|n| [n.x, n.y, n.z]);
+            extend(&mut boxes, (block.entity_dim(), block.entity_tag), points);
+        }
+
+        let points = |nodes: &[NodeTag]| -> Vec<[f64; 3]> {
+            nodes
+                .iter()
+                .filter_map(|n| coords.get(n).copied())
+                .collect()
+        };
+        for block in &self.element_blocks {
+            let nodes = block.elements.iter().flat_map(|e| points(&e.nodes));
+            extend(&mut boxes, (block.entity_dim, block.entity_tag), nodes);
+        }
+        for block in &self.compact_element_blocks {
+            let key = (block.entity_dim, block.entity_tag);
+            extend(&mut boxes, key, points(&block.connectivity));
+        }
+        boxes
+    }
+
+    /// Bounding box of every physical group, by `(dimension, tag)`
+    ///
+    /// The box of a group encloses the boxes of the entities carrying its
+    /// physical tag (see [`Mesh::entity_bounding_boxes`]), partition entities
+    /// included. Groups whose entities have no nodes are left out.
+    pub fn physical_group_bounding_boxes(
+        &self,
+    ) -> BTreeMap<(i32, PhysicalTag), ([f64; 3], [f64; 3])> {
+        let entity_boxes = self.entity_bounding_boxes();
+        let entities = self.effective_entities();
+        let mut tagged: Vec<(i32, EntityTag, &[PhysicalTag])> = Vec::new();
+        tagged.extend(
+            entities
+                .points
+                .iter()
+                .map(|e| (0, e.tag, &e.physical_tags[..])),
+        );
+        tagged.extend(
+            entities
+                .curves
+                .iter()
+                .map(|e| (1, e.tag, &e.physical_tags[..])),
+        );
+        tagged.extend(
+            entities
+                .surfaces
+                .iter()
+                .map(|e| (2, e.tag, &e.physical_tags[..])),
+        );
+        tagged.extend(
+            entities
+                .volumes
+                .iter()
+                .map(|e| (3, e.tag, &e.physical_tags[..])),
+        );
+        if let Some(partitioned) = &self.partitioned_entities {
+            tagged.extend(
+                partitioned
+                    .points
+                    .iter()
+                    .map(|e| (0, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                partitioned
+                    .curves
+                    .iter()
+                    .map(|e| (1, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                partitioned
+                    .surfaces
+                    .iter()
+                    .map(|e| (2, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                partitioned
+                    .volumes
+                    .iter()
+                    .map(|e| (3, e.tag, &e.physical_tags[..])),
+            );
+        }
+
+        let mut boxes = BTreeMap::new();
+        for (dim, entity, physical_tags) in tagged {
+            let Some(&(min, max)) = entity_boxes.get(&(dim, entity)) else {
+                continue;
+            };
+            for &physical in physical_tags {
+                extend(&mut boxes, (dim, physical.magnitude()), [min, max]);
+            }
+        }
+        boxes
+    }
+}
+
+/// Grow the box at `key` to enclose `points`
+fn extend<K: Ord>(
+    boxes: &mut BTreeMap<K, BoundingBox>,
+    key: K,
+    points: impl IntoIterator<Item = [f64; 3]>,
+) {
+    let mut points = points.into_iter().peekable();
+    if points.peek().is_none() {
+        return;
+    }
+    let current = boxes.get(&key).map(|&(min, max)| [min, max]);
+    if let Some(grown) = bounds(current.into_iter().flatten().chain(points)) {
+        boxes.insert(key, grown);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{EntityTag, PhysicalTag};
+
+    /// A 2 x 1 rectangle, with the physical curve 5 on its bottom and right edges
+    const RECTANGLE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Entities\n0 3 1 0\n1 0 0 0 2 0 0 1 5 0\n2 2 0 0 2 1 0 1 5 0\n3 0 1 0 2 1 0 0 0\n\
+        1 0 0 0 2 1 0 1 6 0\n$EndEntities\n\
+        $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n2 0 0\n2 1 0\n0 1 0\n$EndNodes\n\
+        $Elements\n4 5 1 5\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n\
+        2 1 2 2\n4 1 2 3\n5 1 3 4\n$EndElements\n";
+
+    #[test]
+    fn test_bounding_boxes() {
+        let mesh = crate::parse_msh(RECTANGLE).unwrap();
+        assert_eq!(mesh.bounding_box(), Some(([0.0; 3], [2.0, 1.0, 0.0])));
+
+        // Curve 2 is the edge x = 2, its nodes are on the end points
+        let entities = mesh.entity_bounding_boxes();
+        assert_eq!(
+            entities[&(1, EntityTag(2))],
+            ([2.0, 0.0, 0.0], [2.0, 1.0, 0.0])
+        );
+        assert_eq!(
+            entities[&(1, EntityTag(3))],
+            ([0.0, 1.0, 0.0], [2.0, 1.0, 0.0])
+        );
+
+        let groups = mesh.physical_group_bounding_boxes();
+        assert_eq!(groups[&(2, PhysicalTag(6))], mesh.bounding_box().unwrap());
+        assert_eq!(groups[&(1, PhysicalTag(5))], ([0.0; 3], [2.0, 1.0, 0.0]));
+        assert!(!groups.contains_key(&(1, PhysicalTag(6))));
+    }
+}
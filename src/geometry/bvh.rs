@@ -0,0 +1,113 @@
+//! Bounding volume hierarchy of axis-aligned boxes
+
+use super::bounds;
+
+/// Boxes per leaf; below this, testing every box beats descending further
+const LEAF_SIZE: usize = 4;
+
+/// Binary tree of axis-aligned boxes, split at the median of their centres
+/// along the longest axis
+pub(crate) struct Bvh {
+    boxes: Vec<([f64; 3], [f64; 3])>,
+    nodes: Vec<BvhNode>,
+    /// Box indices, each node covering a contiguous range
+    items: Vec<usize>,
+}
+
+struct BvhNode {
+    min: [f64; 3],
+    max: [f64; 3],
+    start: usize,
+    end: usize,
+    children: Option<(usize, usize)>,
+}
+
+impl Bvh {
+    pub(crate) fn new(boxes: &[([f64; 3], [f64; 3])]) -> Self {
+        let mut bvh = Self {
+            boxes: boxes.to_vec(),
+            nodes: Vec::new(),
+            items: (0..boxes.len()).collect(),
+        };
+        if !boxes.is_empty() {
+            bvh.build(boxes, 0, boxes.len());
+        }
+        bvh
+    }
+
+    fn build(&mut self, boxes: &[([f64; 3], [f64; 3])], start: usize, end: usize) -> usize {
+        let items = &mut self.items[start..end];
+        let (min, max) = bounds(items.iter().flat_map(|&i| [boxes[i].0, boxes[i].1]))
+            .expect("node ranges are never empty");
+        if items.len() > LEAF_SIZE {
+            let axis = (0..3)
+                .max_by(|&a, &b| (max[a] - min[a]).total_cmp(&(max[b] - min[b])))
+                .unwrap_or(0);
+            let centre = |i: usize| boxes[i].0[axis] + boxes[i].1[axis];
+            let middle = items.len() / 2;
+            items.select_nth_unstable_by(middle, |&a, &b| centre(a).total_cmp(&centre(b)));
+        }
+        let split = (end - start > LEAF_SIZE).then_some(start + (end - start) / 2);
+
+        let index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            start,
+            end,
+            children: None,
+        });
+        if let Some(middle) = split {
+            let left = self.build(boxes, start, middle);
+            let right = self.build(boxes, middle, end);
+            self.nodes[index].children = Some((left, right));
+        }
+        index
+    }
+
+    /// Indices of the boxes overlapping the box from `min` to `max`, touching
+    /// included
+    pub(crate) fn query(&self, min: [f64; 3], max: [f64; 3]) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = if self.nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if (0..3).any(|k| node.max[k] < min[k] || node.min[k] > max[k]) {
+                continue;
+            }
+            match node.children {
+                Some((left, right)) => stack.extend([left, right]),
+                None => found.extend(self.items[node.start..node.end].iter().filter(|&&i| {
+                    let (lo, hi) = self.boxes[i];
+                    (0..3).all(|k| hi[k] >= min[k] && lo[k] <= max[k])
+                })),
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_finds_overlapping_boxes() {
+        let boxes: Vec<_> = (0..20)
+            .map(|i| {
+                let x = i as f64;
+                ([x, 0.0, 0.0], [x + 1.0, 1.0, 1.0])
+            })
+            .collect();
+        let bvh = Bvh::new(&boxes);
+        let mut found = bvh.query([4.5, 0.5, 0.5], [6.0, 0.5, 0.5]);
+        found.sort_unstable();
+        assert_eq!(found, [4, 5, 6]);
+        assert!(bvh.query([0.0, 2.0, 0.0], [30.0, 3.0, 1.0]).is_empty());
+        assert!(Bvh::new(&[]).query([0.0; 3], [1.0; 3]).is_empty());
+    }
+}
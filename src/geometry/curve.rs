@@ -0,0 +1,358 @@
+//! Ordered traversal and arc-length parameterization of curve entities, and
+//! resampling of curve parametrizations
+
+use super::{distance, node_coordinates};
+use crate::error::{ParseError, Result};
+use crate::types::{
+    CurveParametrization, CurveParametrizationNode, ElementFamily, ElementType, EntityTag, Mesh,
+    NodeTag,
+};
+use std::collections::HashMap;
+
+/// The nodes of a curve entity in traversal order
+///
+/// For closed curves the first node is repeated at the end, so consecutive
+/// points always describe a segment of the curve.
+#[derive(Debug, Clone)]
+pub struct CurvePath {
+    /// Tag of the curve entity
+    pub curve_tag: EntityTag,
+    /// Node tags in traversal order
+    pub node_tags: Vec<NodeTag>,
+    /// Node coordinates, parallel to `node_tags`
+    pub points: Vec<[f64; 3]>,
+    /// Whether the curve forms a closed loop
+    pub closed: bool,
+}
+
+/// Cumulative arc length along a [`CurvePath`]
+#[derive(Debug, Clone)]
+pub struct ArclengthParam {
+    /// Arc length at each point of the curve (starts at 0.0)
+    pub s: Vec<f64>,
+    /// Point coordinates, parallel to `s`
+    pub points: Vec<[f64; 3]>,
+}
+
+impl ArclengthParam {
+    /// Total length of the curve
+    pub fn total_length(&self) -> f64 {
+        self.s.last().copied().unwrap_or(0.0)
+    }
+
+    /// Interpolate the position at arc length `s`
+    ///
+    /// Values outside `[0, total_length]` are clamped to the curve ends.
+    /// Returns `None` if the curve has no points.
+    pub fn point_at(&self, s: f64) -> Option<[f64; 3]> {
+        let first = *self.points.first()?;
+        if self.points.len() == 1 || s <= 0.0 {
+            return Some(first);
+        }
+        if s >= self.total_length() {
+            return self.points.last().copied();
+        }
+
+        // First index whose arc length is >= s; s > 0 guarantees i >= 1
+        let i = self.s.partition_point(|&value| value < s);
+        let (s0, s1) = (self.s[i - 1], self.s[i]);
+        let (p0, p1) = (self.points[i - 1], self.points[i]);
+        let t = if s1 > s0 { (s - s0) / (s1 - s0) } else { 0.0 };
+
+        Some([
+            p0[0] + t * (p1[0] - p0[0]),
+            p0[1] + t * (p1[1] - p0[1]),
+            p0[2] + t * (p1[2] - p0[2]),
+        ])
+    }
+}
+
+/// Compute the cumulative arc length at every node of a curve
+pub fn arclength_param(curve: &CurvePath) -> ArclengthParam {
+    let mut s = Vec::with_capacity(curve.points.len());
+    let mut total = 0.0;
+
+    for (i, point) in curve.points.iter().enumerate() {
+        if i > 0 {
+            total += distance(curve.points[i - 1], *point);
+        }
+        s.push(total);
+    }
+
+    ArclengthParam {
+        s,
+        points: curve.points.clone(),
+    }
+}
+
+/// How [`CurveParametrization::resample`] spaces its points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spacing {
+    /// Evenly spaced in the parameter `u`
+    Parameter,
+    /// Evenly spaced in arc length along the parametrization polyline
+    Arclength,
+}
+
+impl CurveParametrization {
+    /// `n` points evenly spaced between the ends of the parametrization
+    ///
+    /// Nodes are taken in increasing `u`. Coordinates and, for
+    /// [`Spacing::Arclength`], `u` are interpolated linearly between them.
+    /// Returns no points if `n` is zero or the parametrization has no nodes.
+    pub fn resample(&self, n: usize, spacing: Spacing) -> Vec<CurveParametrizationNode> {
+        let mut nodes: Vec<&CurveParametrizationNode> = self.nodes.iter().collect();
+        nodes.sort_by(|a, b| a.u.total_cmp(&b.u));
+        match nodes[..] {
+            [] => return Vec::new(),
+            [single] => return vec![single.clone(); n],
+            _ => {}
+        }
+
+        // Value of the spacing variable at each node
+        let mut key = Vec::with_capacity(nodes.len());
+        for (i, node) in nodes.iter().enumerate() {
+            key.push(match spacing {
+                Spacing::Parameter => node.u,
+                Spacing::Arclength if i == 0 => 0.0,
+                Spacing::Arclength => {
+                    let previous = nodes[i - 1];
+                    key[i - 1]
+                        + distance(
+                            [previous.x, previous.y, previous.z],
+                            [node.x, node.y, node.z],
+                        )
+                }
+            });
+        }
+
+        let (start, end) = (key[0], key[key.len() - 1]);
+        (0..n)
+            .map(|i| {
+                let target = if n == 1 {
+                    start
+                } else {
+                    start + (end - start) * i as f64 / (n - 1) as f64
+                };
+                let j = key.partition_point(|&k| k < target).clamp(1, key.len() - 1);
+                let (a, b) = (nodes[j - 1], nodes[j]);
+                let span = key[j] - key[j - 1];
+                let t = if span > 0.0 {
+                    (target - key[j - 1]) / span
+                } else {
+                    0.0
+                };
+                let lerp = |x: f64, y: f64| x + t * (y - x);
+                CurveParametrizationNode {
+                    x: lerp(a.x, b.x),
+                    y: lerp(a.y, b.y),
+                    z: lerp(a.z, b.z),
+                    u: lerp(a.u, b.u),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Order the line elements of a curve entity into a single path
+///
+/// High-order line elements contribute their interior nodes in order. The traversal
+/// follows element orientation where possible.
+///
+/// # Errors
+/// Returns an error if the curve has no line elements, branches, is made of several
+/// disconnected pieces, or references nodes missing from the mesh.
+pub fn trace_curve(mesh: &Mesh, curve_tag: EntityTag) -> Result<CurvePath> {
+    // Each segment is stored as [start, interior..., end]
+    let mut segments: Vec<Vec<NodeTag>> = Vec::new();
+    for block in mesh.element_block_refs() {
+        if block.entity_dim() != 1
+            || block.entity_tag() != curve_tag
+            || !is_line(block.element_type())
+        {
+            continue;
+        }
+        for element in block.elements() {
+            let mut ordered = Vec::with_capacity(element.nodes().len());
+            ordered.push(element.nodes()[0]);
+            ordered.extend_from_slice(&element.nodes()[2..]);
+            ordered.push(element.nodes()[1]);
+            segments.push(ordered);
+        }
+    }
+
+    if segments.is_empty() {
+        return Err(ParseError::GeometryError(format!(
+            "Curve {} has no line elements",
+            curve_tag
+        )));
+    }
+
+    // Endpoint node -> segments touching it
+    let mut incident: HashMap<NodeTag, Vec<usize>> = HashMap::new();
+    for (i, segment) in segments.iter().enumerate() {
+        incident.entry(segment[0]).or_default().push(i);
+        incident
+            .entry(segment[segment.len() - 1])
+            .or_default()
+            .push(i);
+    }
+
+    if let Some((node, _)) = incident.iter().find(|(_, segs)| segs.len() > 2) {
+        return Err(ParseError::GeometryError(format!(
+            "Curve {} branches at node {}",
+            curve_tag, node
+        )));
+    }
+
+    // Prefer starting at an open end that is also the start of its segment
+    let open_ends: Vec<NodeTag> = incident
+        .iter()
+        .filter(|(_, segs)| segs.len() == 1)
+        .map(|(&node, _)| node)
+        .collect();
+    let start = open_ends
+        .iter()
+        .copied()
+        .filter(|node| segments[incident[node][0]][0] == *node)
+        .min()
+        .or_else(|| open_ends.iter().copied().min())
+        .unwrap_or(segments[0][0]);
+
+    let mut visited = vec![false; segments.len()];
+    let mut node_tags = vec![start];
+    let mut current = start;
+
+    while let Some(&next) = incident[&current].iter().find(|&&i| !visited[i]) {
+        visited[next] = true;
+        let segment = &segments[next];
+        if segment[0] == current {
+            node_tags.extend_from_slice(&segment[1..]);
+        } else {
+            node_tags.extend(segment[..segment.len() - 1].iter().rev());
+        }
+        current = *node_tags.last().unwrap_or(&current);
+    }
+
+    if visited.iter().any(|v| !v) {
+        return Err(ParseError::GeometryError(format!(
+            "Curve {} is not a single connected path",
+            curve_tag
+        )));
+    }
+
+    let coords = node_coordinates(mesh);
+    let points = node_tags
+        .iter()
+        .map(|tag| {
+            coords.get(tag).copied().ok_or_else(|| {
+                ParseError::GeometryError(format!(
+                    "Curve {} references missing node {}",
+                    curve_tag, tag
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let closed = node_tags.len() > 2 && node_tags.first() == node_tags.last();
+
+    Ok(CurvePath {
+        curve_tag,
+        node_tags,
+        points,
+        closed,
+    })
+}
+
+/// Line element types whose first two nodes are the end vertices
+fn is_line(element_type: ElementType) -> bool {
+    element_type.family() == ElementFamily::Line
+        && element_type
+            .polynomial_order()
+            .is_some_and(|order| order >= 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    const TRIANGLE_LOOP: &str = r#"$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$Nodes
+1 3 1 3
+1 1 0 3
+1
+2
+3
+0 0 0
+3 0 0
+3 4 0
+$EndNodes
+$Elements
+1 3 1 3
+1 1 1 3
+1 1 2
+2 3 2
+3 3 1
+$EndElements
+"#;
+
+    #[test]
+    fn test_trace_closed_curve() {
+        let mesh = parse_msh(TRIANGLE_LOOP).unwrap();
+        let curve = trace_curve(&mesh, EntityTag(1)).unwrap();
+
+        assert!(curve.closed);
+        assert_eq!(curve.node_tags, vec![1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_arclength_param() {
+        let mesh = parse_msh(TRIANGLE_LOOP).unwrap();
+        let param = arclength_param(&trace_curve(&mesh, EntityTag(1)).unwrap());
+
+        assert_eq!(param.s, vec![0.0, 3.0, 7.0, 12.0]);
+        assert_eq!(param.total_length(), 12.0);
+        assert_eq!(param.point_at(5.0), Some([3.0, 2.0, 0.0]));
+        assert_eq!(param.point_at(100.0), Some([0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_trace_missing_curve() {
+        let mesh = parse_msh(TRIANGLE_LOOP).unwrap();
+        assert!(matches!(
+            trace_curve(&mesh, EntityTag(7)),
+            Err(ParseError::GeometryError(_))
+        ));
+    }
+
+    #[test]
+    fn test_resample_curve_parametrization() {
+        let node = |x: f64, u: f64| CurveParametrizationNode {
+            x,
+            y: 0.0,
+            z: 0.0,
+            u,
+        };
+        let curve = CurveParametrization {
+            curve_tag: EntityTag(1),
+            nodes: vec![node(3.0, 1.0), node(0.0, 0.0), node(1.0, 0.5)],
+        };
+
+        let by_u: Vec<f64> = curve
+            .resample(5, Spacing::Parameter)
+            .iter()
+            .map(|n| n.x)
+            .collect();
+        assert_eq!(by_u, [0.0, 0.5, 1.0, 2.0, 3.0]);
+
+        let by_length = curve.resample(4, Spacing::Arclength);
+        let x: Vec<f64> = by_length.iter().map(|n| n.x).collect();
+        let u: Vec<f64> = by_length.iter().map(|n| n.u).collect();
+        assert_eq!(x, [0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(u, [0.0, 0.5, 0.75, 1.0]);
+        assert!(curve.resample(0, Spacing::Arclength).is_empty());
+    }
+}
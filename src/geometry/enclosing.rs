@@ -0,0 +1,228 @@
+//! Bounding spheres and oriented bounding boxes of the nodes of a mesh
+
+use super::distance;
+use crate::types::Mesh;
+
+/// A sphere enclosing every node, see [`bounding_sphere`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingSphere {
+    pub center: [f64; 3],
+    pub radius: f64,
+}
+
+/// A box enclosing every node, aligned with the principal axes of the nodes,
+/// see [`oriented_bbox`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrientedBox {
+    pub center: [f64; 3],
+    /// Orthonormal axes of the box, from the largest to the smallest extent
+    pub axes: [[f64; 3]; 3],
+    /// Half of the size of the box along each axis
+    pub half_extents: [f64; 3],
+}
+
+impl OrientedBox {
+    pub fn volume(&self) -> f64 {
+        8.0 * self.half_extents.iter().product::<f64>()
+    }
+
+    /// The eight corners of the box
+    pub fn corners(&self) -> [[f64; 3]; 8] {
+        std::array::from_fn(|i| {
+            let mut corner = self.center;
+            for (k, axis) in self.axes.iter().enumerate() {
+                let sign = if i & (1 << k) == 0 { -1.0 } else { 1.0 };
+                for c in 0..3 {
+                    corner[c] += sign * self.half_extents[k] * axis[c];
+                }
+            }
+            corner
+        })
+    }
+}
+
+/// A sphere enclosing every node of `mesh`, or `None` if it has no nodes
+///
+/// Uses Ritter's algorithm: the sphere is usually a few percent larger than
+/// the smallest enclosing sphere, which is enough for framing a view or a
+/// broad-phase overlap test.
+pub fn bounding_sphere(mesh: &Mesh) -> Option<BoundingSphere> {
+    let points: Vec<[f64; 3]> = node_points(mesh).collect();
+    let first = *points.first()?;
+    let farthest = |from: [f64; 3]| {
+        points
+            .iter()
+            .copied()
+            .max_by(|a, b| distance(from, *a).total_cmp(&distance(from, *b)))
+            .unwrap_or(from)
+    };
+    let a = farthest(first);
+    let b = farthest(a);
+
+    let mut center = [0, 1, 2].map(|k| 0.5 * (a[k] + b[k]));
+    let mut radius = 0.5 * distance(a, b);
+    for &p in &points {
+        let d = distance(center, p);
+        if d > radius {
+            // Grow the sphere just enough to reach p, keeping the far side fixed
+            let grown = 0.5 * (radius + d);
+            let shift = (grown - radius) / d;
+            center = [0, 1, 2].map(|k| center[k] + shift * (p[k] - center[k]));
+            radius = grown;
+        }
+    }
+    Some(BoundingSphere { center, radius })
+}
+
+/// A box enclosing every node of `mesh`, aligned with the principal axes of
+/// the node cloud, or `None` if it has no nodes
+///
+/// The axes are the eigenvectors of the covariance matrix of the node
+/// coordinates. The box is tight along them but not necessarily the smallest
+/// oriented box; for nodes with no preferred direction (e.g. a cube meshed
+/// uniformly) the axes are those of the coordinate system.
+pub fn oriented_bbox(mesh: &Mesh) -> Option<OrientedBox> {
+    let points: Vec<[f64; 3]> = node_points(mesh).collect();
+    if points.is_empty() {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean = [0, 1, 2].map(|k| points.iter().map(|p| p[k]).sum::<f64>() / n);
+    let mut covariance = [[0.0; 3]; 3];
+    for p in &points {
+        let d = [0, 1, 2].map(|k| p[k] - mean[k]);
+        for (i, row) in covariance.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value += d[i] * d[j] / n;
+            }
+        }
+    }
+
+    let (values, vectors) = symmetric_eigen(covariance);
+    let mut order = [0, 1, 2];
+    order.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+    let mut axes = order.map(|i| [vectors[0][i], vectors[1][i], vectors[2][i]]);
+    // Keep the frame right-handed
+    axes[2] = cross(axes[0], axes[1]);
+
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for p in &points {
+        for (k, axis) in axes.iter().enumerate() {
+            let t = (0..3).map(|c| p[c] * axis[c]).sum::<f64>();
+            min[k] = min[k].min(t);
+            max[k] = max[k].max(t);
+        }
+    }
+    let middle = [0, 1, 2].map(|k| 0.5 * (min[k] + max[k]));
+    let center = [0, 1, 2].map(|c| (0..3).map(|k| middle[k] * axes[k][c]).sum());
+    Some(OrientedBox {
+        center,
+        axes,
+        half_extents: [0, 1, 2].map(|k| 0.5 * (max[k] - min[k])),
+    })
+}
+
+fn node_points(mesh: &Mesh) -> impl Iterator<Item = [f64; 3]> + '_ {
+    mesh.node_blocks
+        .iter()
+        .flat_map(|b| &b.nodes)
+        .map(|n| [n.x, n.y, n.z])
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Eigenvalues and eigenvectors (as columns) of a symmetric matrix, by cyclic
+/// Jacobi rotations
+fn symmetric_eigen(mut a: [[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    for _ in 0..50 {
+        let off = a[0][1].powi(2) + a[0][2].powi(2) + a[1][2].powi(2);
+        let scale = a[0][0].powi(2) + a[1][1].powi(2) + a[2][2].powi(2);
+        if off <= f64::EPSILON * f64::EPSILON * scale {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let t = if theta == 0.0 { 1.0 } else { t };
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+            // a <- Jᵀ a J, v <- v J with the rotation J in the (p, q) plane
+            for row in &mut a {
+                let (ap, aq) = (row[p], row[q]);
+                row[p] = c * ap - s * aq;
+                row[q] = s * ap + c * aq;
+            }
+            let (ap, aq) = (a[p], a[q]);
+            a[p] = [0, 1, 2].map(|k| c * ap[k] - s * aq[k]);
+            a[q] = [0, 1, 2].map(|k| s * ap[k] + c * aq[k]);
+            for row in &mut v {
+                let (vp, vq) = (row[p], row[q]);
+                row[p] = c * vp - s * vq;
+                row[q] = s * vp + c * vq;
+            }
+        }
+    }
+    ([a[0][0], a[1][1], a[2][2]], v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::HEXAHEDRON;
+    use crate::geometry::{Affine3, Axis};
+
+    /// A 1 x 3 rectangle of two triangles
+    const RECTANGLE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 3 0\n0 3 0\n$EndNodes\n\
+        $Elements\n1 2 1 2\n2 1 2 2\n1 1 2 3\n2 1 3 4\n$EndElements\n";
+
+    #[test]
+    fn test_bounding_sphere_encloses_cube() {
+        let mesh = crate::parse_msh(HEXAHEDRON).unwrap();
+        let sphere = bounding_sphere(&mesh).unwrap();
+        let half_diagonal = 3f64.sqrt() / 2.0;
+        assert!(sphere.radius >= half_diagonal - 1e-12);
+        assert!(sphere.radius <= 1.05 * half_diagonal);
+        assert!(node_points(&mesh).all(|p| distance(sphere.center, p) <= sphere.radius + 1e-12));
+    }
+
+    #[test]
+    fn test_oriented_bbox_follows_rotated_rectangle() {
+        let mut mesh = crate::parse_msh(RECTANGLE).unwrap();
+        let rotation = Affine3::rotation(&Axis::new([0.0; 3], [0.0, 0.0, 1.0]), 0.5).unwrap();
+        for node in mesh.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+            [node.x, node.y, node.z] = rotation.apply([node.x, node.y, node.z]);
+        }
+
+        let obb = oriented_bbox(&mesh).unwrap();
+        assert!((obb.half_extents[0] - 1.5).abs() < 1e-9, "{:?}", obb);
+        assert!((obb.half_extents[1] - 0.5).abs() < 1e-9, "{:?}", obb);
+        assert!(obb.half_extents[2] < 1e-9);
+        let long_axis = rotation.apply_vector([0.0, 1.0, 0.0]);
+        let alignment: f64 = (0..3).map(|k| obb.axes[0][k] * long_axis[k]).sum();
+        assert!((alignment.abs() - 1.0).abs() < 1e-9);
+        let inside = |p: [f64; 3]| {
+            (0..3).all(|k| {
+                let t: f64 = (0..3)
+                    .map(|c| (p[c] - obb.center[c]) * obb.axes[k][c])
+                    .sum();
+                t.abs() <= obb.half_extents[k] + 1e-12
+            })
+        };
+        assert!(node_points(&mesh).all(inside));
+        assert!(obb.corners().into_iter().all(inside));
+    }
+}
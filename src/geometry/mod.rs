@@ -0,0 +1,56 @@
+//! Geometric utilities built on top of a parsed mesh
+//!
+//! These helpers only use data already present in the MSH file (node coordinates and
+//! element connectivity); no CAD kernel is involved.
+
+mod affine;
+mod bounding_box;
+mod bvh;
+mod curve;
+mod enclosing;
+mod overlap;
+mod profile;
+mod projection;
+mod tolerance;
+mod transform;
+mod units;
+
+pub use affine::{Affine3, Axis, Plane};
+pub use curve::{arclength_param, trace_curve, ArclengthParam, CurvePath, Spacing};
+pub use enclosing::{bounding_sphere, oriented_bbox, BoundingSphere, OrientedBox};
+pub use overlap::{overlaps, OverlapReport};
+pub use profile::{profile, profile_with_field, ProfileStation};
+pub use projection::closest_point;
+pub use tolerance::Tolerance;
+pub use units::LengthUnit;
+
+use crate::types::{Mesh, NodeTag};
+use std::collections::HashMap;
+
+/// Collect the coordinates of every node in the mesh, keyed by node tag
+pub(crate) fn node_coordinates(mesh: &Mesh) -> HashMap<NodeTag, [f64; 3]> {
+    mesh.node_blocks
+        .iter()
+        .flat_map(|block| block.nodes.iter())
+        .map(|node| (node.tag, [node.x, node.y, node.z]))
+        .collect()
+}
+
+/// Euclidean distance between two points
+pub(crate) fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// Axis-aligned bounds `(min, max)` of a set of points, or `None` if it is empty
+pub(crate) fn bounds<I>(points: I) -> Option<([f64; 3], [f64; 3])>
+where
+    I: IntoIterator<Item = [f64; 3]>,
+{
+    points.into_iter().fold(None, |acc, p| match acc {
+        None => Some((p, p)),
+        Some((min, max)) => Some((
+            [min[0].min(p[0]), min[1].min(p[1]), min[2].min(p[2])],
+            [max[0].max(p[0]), max[1].max(p[1]), max[2].max(p[2])],
+        )),
+    })
+}
@@ -0,0 +1,285 @@
+//! Intersection and containment between the elements of two meshes
+
+use super::bvh::Bvh;
+use super::{bounds, node_coordinates, Tolerance};
+use crate::fields::simplex::simplices;
+use crate::types::{ElementTag, Mesh};
+use std::collections::BTreeSet;
+
+/// Overlapping elements of two meshes, see [`overlaps`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverlapReport {
+    /// Pairs of intersecting elements, of the first and of the second mesh
+    pub intersecting: Vec<(ElementTag, ElementTag)>,
+    /// Elements of the first mesh whose corner nodes all lie inside the second
+    pub first_inside_second: Vec<ElementTag>,
+    /// Elements of the second mesh whose corner nodes all lie inside the first
+    pub second_inside_first: Vec<ElementTag>,
+}
+
+impl OverlapReport {
+    /// Whether the meshes are disjoint or only touch
+    pub fn is_empty(&self) -> bool {
+        self.intersecting.is_empty()
+            && self.first_inside_second.is_empty()
+            && self.second_inside_first.is_empty()
+    }
+}
+
+/// Find the elements of `first` and `second` that intersect or lie inside the
+/// other mesh
+///
+/// Each mesh is represented by its elements of the highest dimension, split
+/// into triangles or tetrahedra through their corner nodes, so curved elements
+/// are treated as linear. Meshes without surface or volume elements have no
+/// region and overlap nothing. Elements that only touch, e.g. along the
+/// interface of two parts of an assembly, do not intersect; the tolerance is
+/// derived from the size of both meshes (see [`Tolerance::from_bounds`]).
+///
+/// Candidate pairs come from a bounding volume hierarchy of each mesh and are
+/// tested with the separating axis theorem.
+pub fn overlaps(first: &Mesh, second: &Mesh) -> OverlapReport {
+    let a = Region::new(first);
+    let b = Region::new(second);
+    let margin = bounds(
+        a.simplices
+            .iter()
+            .chain(&b.simplices)
+            .flat_map(|s| s.corners.iter().copied()),
+    )
+    .map_or_else(Tolerance::default, |(min, max)| {
+        Tolerance::from_bounds(min, max)
+    })
+    .absolute;
+
+    let mut intersecting = BTreeSet::new();
+    for s in &a.simplices {
+        for j in b.bvh.query(s.min, s.max) {
+            let t = &b.simplices[j];
+            if !separated(&s.corners, &t.corners, margin) {
+                intersecting.insert((s.element, t.element));
+            }
+        }
+    }
+
+    OverlapReport {
+        intersecting: intersecting.into_iter().collect(),
+        first_inside_second: a.inside(&b, margin),
+        second_inside_first: b.inside(&a, margin),
+    }
+}
+
+/// A triangle or tetrahedron of an element
+struct Simplex {
+    element: ElementTag,
+    corners: Vec<[f64; 3]>,
+    min: [f64; 3],
+    max: [f64; 3],
+}
+
+/// Simplices of the elements of the highest dimension of a mesh
+struct Region {
+    simplices: Vec<Simplex>,
+    bvh: Bvh,
+}
+
+impl Region {
+    fn new(mesh: &Mesh) -> Self {
+        let coords = node_coordinates(mesh);
+        let dim = mesh.element_block_refs().map(|b| b.entity_dim()).max();
+        let mut simplices_of_mesh = Vec::new();
+        for block in mesh
+            .element_block_refs()
+            .filter(|b| b.entity_dim() >= 2 && Some(b.entity_dim()) == dim)
+        {
+            let Some(split) = simplices(block.element_type()) else {
+                continue;
+            };
+            for element in block.elements() {
+                for simplex in split {
+                    let corners: Option<Vec<[f64; 3]>> = simplex
+                        .iter()
+                        .map(|&i| coords.get(element.nodes().get(i)?).copied())
+                        .collect();
+                    let Some(corners) = corners else {
+                        continue;
+                    };
+                    let Some((min, max)) = bounds(corners.iter().copied()) else {
+                        continue;
+                    };
+                    simplices_of_mesh.push(Simplex {
+                        element: element.tag(),
+                        corners,
+                        min,
+                        max,
+                    });
+                }
+            }
+        }
+        let boxes: Vec<_> = simplices_of_mesh.iter().map(|s| (s.min, s.max)).collect();
+        Self {
+            bvh: Bvh::new(&boxes),
+            simplices: simplices_of_mesh,
+        }
+    }
+
+    /// Whether `p` lies inside or on one of the simplices
+    fn contains(&self, p: [f64; 3], margin: f64) -> bool {
+        let min = p.map(|x| x - margin);
+        let max = p.map(|x| x + margin);
+        self.bvh
+            .query(min, max)
+            .into_iter()
+            .any(|i| !separated(&[p], &self.simplices[i].corners, -margin))
+    }
+
+    /// Elements of this region whose corners all lie inside `other`
+    fn inside(&self, other: &Region, margin: f64) -> Vec<ElementTag> {
+        let mut outside = BTreeSet::new();
+        let mut elements = BTreeSet::new();
+        for s in &self.simplices {
+            elements.insert(s.element);
+            if !outside.contains(&s.element)
+                && !s.corners.iter().all(|&p| other.contains(p, margin))
+            {
+                outside.insert(s.element);
+            }
+        }
+        elements.difference(&outside).copied().collect()
+    }
+}
+
+/// Whether the projections of two simplices (or of a point and a simplex)
+/// overlap by no more than `margin` along some axis
+///
+/// The candidate axes are the face normals of both simplices, which for a
+/// triangle include the in-plane normals of its edges, and the cross products
+/// of their edges.
+fn separated(a: &[[f64; 3]], b: &[[f64; 3]], margin: f64) -> bool {
+    let (edges_a, edges_b) = (edges(a), edges(b));
+    let crossed = edges_a
+        .iter()
+        .flat_map(|&u| edges_b.iter().map(move |&v| cross(u, v)));
+    face_normals(a)
+        .into_iter()
+        .chain(face_normals(b))
+        .chain(crossed)
+        .filter_map(|axis| {
+            let length = dot(axis, axis).sqrt();
+            (length > 0.0 && length.is_finite()).then(|| axis.map(|x| x / length))
+        })
+        .any(|axis| {
+            let (lo_a, hi_a) = project(a, axis);
+            let (lo_b, hi_b) = project(b, axis);
+            hi_a.min(hi_b) - lo_a.max(lo_b) <= margin
+        })
+}
+
+fn project(points: &[[f64; 3]], axis: [f64; 3]) -> (f64, f64) {
+    points
+        .iter()
+        .map(|&p| dot(p, axis))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), t| {
+            (lo.min(t), hi.max(t))
+        })
+}
+
+fn edges(points: &[[f64; 3]]) -> Vec<[f64; 3]> {
+    let mut edges = Vec::new();
+    for (i, &p) in points.iter().enumerate() {
+        for &q in &points[i + 1..] {
+            edges.push(sub(q, p));
+        }
+    }
+    edges
+}
+
+fn face_normals(points: &[[f64; 3]]) -> Vec<[f64; 3]> {
+    match *points {
+        [a, b, c] => {
+            let n = cross(sub(b, a), sub(c, a));
+            vec![
+                n,
+                cross(n, sub(b, a)),
+                cross(n, sub(c, b)),
+                cross(n, sub(a, c)),
+            ]
+        }
+        [a, b, c, d] => vec![
+            cross(sub(b, a), sub(c, a)),
+            cross(sub(b, a), sub(d, a)),
+            cross(sub(c, a), sub(d, a)),
+            cross(sub(c, b), sub(d, b)),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Affine3;
+
+    /// A 2 x 1 x 1 bar of two hexahedra, split at x = 1
+    const BAR: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n1 12 1 12\n3 1 0 12\n1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n\
+        0 0 0\n1 0 0\n1 1 0\n0 1 0\n0 0 1\n1 0 1\n1 1 1\n0 1 1\n\
+        2 0 0\n2 1 0\n2 0 1\n2 1 1\n$EndNodes\n\
+        $Elements\n1 2 1 2\n3 1 5 2\n1 1 2 3 4 5 6 7 8\n2 2 9 10 3 6 11 12 7\n$EndElements\n";
+
+    fn transformed(mesh: &Mesh, transform: &Affine3) -> Mesh {
+        let mut mesh = mesh.clone();
+        for node in mesh.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+            [node.x, node.y, node.z] = transform.apply([node.x, node.y, node.z]);
+        }
+        mesh
+    }
+
+    #[test]
+    fn test_touching_bars_do_not_overlap() {
+        let bar = crate::parse_msh(BAR).unwrap();
+        let touching = transformed(&bar, &Affine3::translation([2.0, 0.0, 0.0]));
+        assert!(overlaps(&bar, &touching).is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_bars() {
+        let bar = crate::parse_msh(BAR).unwrap();
+        let shifted = transformed(&bar, &Affine3::translation([1.5, 0.0, 0.0]));
+        let report = overlaps(&bar, &shifted);
+        // Only the second hexahedron reaches the shifted copy, and only its first
+        assert_eq!(report.intersecting, [(ElementTag(2), ElementTag(1))]);
+        assert!(report.first_inside_second.is_empty());
+        assert!(report.second_inside_first.is_empty());
+    }
+
+    #[test]
+    fn test_shrunk_copy_is_inside() {
+        let bar = crate::parse_msh(BAR).unwrap();
+        let shrink = Affine3::translation([-1.0, -0.5, -0.5])
+            .then(&Affine3::scaling([0.5; 3]))
+            .then(&Affine3::translation([1.0, 0.5, 0.5]));
+        let inner = transformed(&bar, &shrink);
+
+        let report = overlaps(&bar, &inner);
+        assert_eq!(report.second_inside_first.len(), 2);
+        assert!(report.first_inside_second.is_empty());
+    }
+}
@@ -0,0 +1,258 @@
+//! Cross-section profiles along an axis
+
+use super::{node_coordinates, Axis};
+use crate::error::{ParseError, Result};
+use crate::fields::simplex::simplices;
+use crate::types::{Mesh, NodeTag};
+use std::collections::HashMap;
+
+/// Cross section of a mesh at one station of a [`profile`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProfileStation {
+    /// Signed distance of the section plane from the axis point
+    pub position: f64,
+    /// Area of the section, or its length for a mesh without volume elements
+    pub area: f64,
+    /// Area-weighted mean of the field over the section, per component
+    ///
+    /// Only set by [`profile_with_field`], and `None` if the section is empty.
+    pub field: Option<Vec<f64>>,
+}
+
+/// Cross-sectional areas of `mesh` at `n_slices` stations along `axis`
+///
+/// The nodes span a range of positions along the axis; it is split into
+/// `n_slices` equal slices and the mesh is cut by the plane normal to the axis
+/// through the middle of each slice. Volume elements are cut into polygons
+/// whose areas are summed. A mesh without volume elements is cut into
+/// segments, so the "area" is the length of the section. Elements are split
+/// into simplices through their corner nodes, so curved elements are treated as
+/// linear.
+///
+/// # Errors
+/// Returns an error if `n_slices` is zero or the axis direction is zero.
+pub fn profile(mesh: &Mesh, axis: &Axis, n_slices: usize) -> Result<Vec<ProfileStation>> {
+    sections(mesh, axis, n_slices, None)
+}
+
+/// Like [`profile`], also averaging the node data view called `field` over each
+/// section
+///
+/// The view is taken at its last time step and interpolated linearly along the
+/// cut element edges. Nodes without data count as zero.
+///
+/// # Errors
+/// Returns an error if `n_slices` is zero, the axis direction is zero or the
+/// mesh has no node data view called `field`.
+pub fn profile_with_field(
+    mesh: &Mesh,
+    axis: &Axis,
+    n_slices: usize,
+    field: &str,
+) -> Result<Vec<ProfileStation>> {
+    let view = mesh
+        .field(field)
+        .ok_or_else(|| ParseError::NotFound(format!("View \"{}\"", field)))?;
+    let values: HashMap<NodeTag, &[f64]> = view
+        .data
+        .iter()
+        .map(|(tag, values)| (*tag, values.as_slice()))
+        .collect();
+    sections(mesh, axis, n_slices, Some(&values))
+}
+
+/// A point of a section with the field values interpolated there
+type SectionPoint = ([f64; 3], Vec<f64>);
+
+fn sections(
+    mesh: &Mesh,
+    axis: &Axis,
+    n_slices: usize,
+    values: Option<&HashMap<NodeTag, &[f64]>>,
+) -> Result<Vec<ProfileStation>> {
+    if n_slices == 0 {
+        return Err(ParseError::GeometryError(
+            "Profile requires at least one slice".to_string(),
+        ));
+    }
+    let length = axis.direction.iter().map(|d| d * d).sum::<f64>().sqrt();
+    if length == 0.0 {
+        return Err(ParseError::GeometryError(
+            "Profile axis direction must not be zero".to_string(),
+        ));
+    }
+    let direction = axis.direction.map(|d| d / length);
+    let position = |p: [f64; 3]| (0..3).map(|k| (p[k] - axis.point[k]) * direction[k]).sum();
+
+    let coords = node_coordinates(mesh);
+    let Some((start, end)) = coords.values().map(|&p| position(p)).fold(None, |acc, t| {
+        let (lo, hi) = acc.unwrap_or((t, t));
+        Some((f64::min(lo, t), f64::max(hi, t)))
+    }) else {
+        return Ok(Vec::new());
+    };
+    let width = (end - start) / n_slices as f64;
+    let components = values
+        .and_then(|v| v.values().next())
+        .map_or(0, |v| v.len());
+
+    // Corner points of the simplices of the highest dimension
+    let dim = mesh.element_block_refs().map(|b| b.entity_dim()).max();
+    let mut cells: Vec<Vec<SectionPoint>> = Vec::new();
+    for block in mesh
+        .element_block_refs()
+        .filter(|b| Some(b.entity_dim()) == dim)
+    {
+        let Some(split) = simplices(block.element_type()) else {
+            continue;
+        };
+        for element in block.elements() {
+            for simplex in split {
+                let corners: Option<Vec<SectionPoint>> = simplex
+                    .iter()
+                    .map(|&i| {
+                        let tag = element.nodes().get(i)?;
+                        let value = match values.and_then(|v| v.get(tag)) {
+                            Some(v) => v.to_vec(),
+                            None => vec![0.0; components],
+                        };
+                        Some((*coords.get(tag)?, value))
+                    })
+                    .collect();
+                cells.extend(corners);
+            }
+        }
+    }
+
+    let stations = (0..n_slices)
+        .map(|i| {
+            let t = start + (i as f64 + 0.5) * width;
+            let mut area = 0.0;
+            let mut sum = vec![0.0; components];
+            for corners in &cells {
+                let distances: Vec<f64> = corners.iter().map(|(p, _)| position(*p) - t).collect();
+                let section = cut(corners, &distances);
+                let measure = match section.as_slice() {
+                    [a, b] => super::distance(a.0, b.0),
+                    [a, b, c] => triangle_area(a.0, b.0, c.0),
+                    [a, b, c, d] => triangle_area(a.0, b.0, c.0) + triangle_area(a.0, c.0, d.0),
+                    _ => continue,
+                };
+                area += measure;
+                for (k, total) in sum.iter_mut().enumerate() {
+                    let mean =
+                        section.iter().map(|(_, v)| v[k]).sum::<f64>() / section.len() as f64;
+                    *total += measure * mean;
+                }
+            }
+            let field = (values.is_some() && area > 0.0)
+                .then(|| sum.into_iter().map(|s| s / area).collect());
+            ProfileStation {
+                position: t,
+                area,
+                field,
+            }
+        })
+        .collect();
+    Ok(stations)
+}
+
+/// Intersection of a simplex with the plane where `distances` vanish, as a
+/// segment, triangle or quadrangle with its vertices in cyclic order
+fn cut(corners: &[SectionPoint], distances: &[f64]) -> Vec<SectionPoint> {
+    let below: Vec<usize> = (0..corners.len())
+        .filter(|&i| distances[i] <= 0.0)
+        .collect();
+    let above: Vec<usize> = (0..corners.len()).filter(|&i| distances[i] > 0.0).collect();
+    let crossing = |i: usize, j: usize| {
+        let s = distances[i] / (distances[i] - distances[j]);
+        let (pi, vi) = &corners[i];
+        let (pj, vj) = &corners[j];
+        let p = [0, 1, 2].map(|k| pi[k] + s * (pj[k] - pi[k]));
+        let v = vi.iter().zip(vj).map(|(a, b)| a + s * (b - a)).collect();
+        (p, v)
+    };
+    match (below.as_slice(), above.as_slice()) {
+        (&[a, b], &[c, d]) => vec![
+            crossing(a, c),
+            crossing(a, d),
+            crossing(b, d),
+            crossing(b, c),
+        ],
+        (&[i], others) | (others, &[i]) if !others.is_empty() => {
+            others.iter().map(|&j| crossing(i, j)).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn triangle_area(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> f64 {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    0.5 * (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{HEXAHEDRON, SQUARE};
+    use crate::types::NodeData;
+
+    #[test]
+    fn test_profile_of_unit_cube() {
+        let mesh = crate::parse_msh(HEXAHEDRON).unwrap();
+        let axis = Axis::new([0.0; 3], [0.0, 0.0, 2.0]);
+        let stations = profile(&mesh, &axis, 4).unwrap();
+        assert_eq!(stations.len(), 4);
+        assert!((stations[0].position - 0.125).abs() < 1e-12);
+        for station in &stations {
+            assert!((station.area - 1.0).abs() < 1e-9, "{:?}", station);
+            assert!(station.field.is_none());
+        }
+    }
+
+    #[test]
+    fn test_profile_with_field() {
+        let mut mesh = crate::parse_msh(HEXAHEDRON).unwrap();
+        let data = mesh
+            .node_blocks
+            .iter()
+            .flat_map(|b| &b.nodes)
+            .map(|n| (n.tag, vec![n.z, 1.0]))
+            .collect();
+        mesh.node_data.push(NodeData {
+            string_tags: vec!["height".to_string()],
+            real_tags: vec![0.0],
+            integer_tags: vec![0, 2],
+            data,
+        });
+        let axis = Axis::new([0.0; 3], [0.0, 0.0, 2.0]);
+        let stations = profile_with_field(&mesh, &axis, 4, "height").unwrap();
+        let field = stations[1].field.as_ref().unwrap();
+        assert!((field[0] - 0.375).abs() < 1e-9 && (field[1] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_profile_errors() {
+        let mesh = crate::parse_msh(HEXAHEDRON).unwrap();
+        let axis = Axis::new([0.0; 3], [0.0, 0.0, 2.0]);
+        assert!(profile(&mesh, &axis, 0).is_err());
+        assert!(profile(&mesh, &Axis::new([0.0; 3], [0.0; 3]), 4).is_err());
+        assert!(profile_with_field(&mesh, &axis, 4, "missing").is_err());
+    }
+
+    #[test]
+    fn test_profile_of_surface_mesh_measures_lengths() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let stations = profile(&mesh, &Axis::new([0.0; 3], [0.0, 1.0, 0.0]), 3).unwrap();
+        for station in &stations {
+            assert!((station.area - 1.0).abs() < 1e-9, "{:?}", station);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Projection of points onto surface parametrizations
+
+use crate::error::{ParseError, Result};
+use crate::types::{EntityTag, Mesh, SurfaceParametrization};
+
+impl SurfaceParametrization {
+    /// Closest point of the parametrization triangulation to `p`
+    ///
+    /// Returns `(u, v, xyz)`, with `u` and `v` interpolated linearly inside the
+    /// closest triangle, or `None` if the parametrization has no valid triangle.
+    /// All triangles are visited, so the cost grows with their number.
+    pub fn closest_point(&self, p: [f64; 3]) -> Option<(f64, f64, [f64; 3])> {
+        let mut best: Option<(f64, f64, f64, [f64; 3])> = None;
+        for triangle in &self.triangles {
+            let corners = [
+                self.nodes.get(triangle.node_index1)?,
+                self.nodes.get(triangle.node_index2)?,
+                self.nodes.get(triangle.node_index3)?,
+            ];
+            let points = corners.map(|n| [n.x, n.y, n.z]);
+            let weights = closest_on_triangle(points, p);
+            let q = combine(&points, weights);
+            let d2 = (q[0] - p[0]).powi(2) + (q[1] - p[1]).powi(2) + (q[2] - p[2]).powi(2);
+            if best.is_some_and(|(best_d2, ..)| best_d2 <= d2) {
+                continue;
+            }
+            let u = (0..3).map(|i| weights[i] * corners[i].u).sum();
+            let v = (0..3).map(|i| weights[i] * corners[i].v).sum();
+            best = Some((d2, u, v, q));
+        }
+        best.map(|(_, u, v, q)| (u, v, q))
+    }
+}
+
+/// Snap `p` onto the parametrization of the surface entity `surface_tag`
+///
+/// Returns `(u, v, xyz)` for the closest point of the surface, e.g. to move
+/// nodes back onto the CAD surface after smoothing or perturbation. See
+/// [`SurfaceParametrization::closest_point`].
+///
+/// # Errors
+/// Returns an error if the mesh has no `$Parametrizations` section, has no
+/// parametrization for the surface, or if it has no triangles.
+pub fn closest_point(
+    mesh: &Mesh,
+    surface_tag: EntityTag,
+    p: [f64; 3],
+) -> Result<(f64, f64, [f64; 3])> {
+    let parametrizations = mesh
+        .parametrizations
+        .as_ref()
+        .ok_or_else(|| ParseError::MissingSection("Parametrizations".to_string()))?;
+    let surface = parametrizations
+        .surfaces
+        .iter()
+        .find(|s| s.surface_tag == surface_tag)
+        .ok_or_else(|| {
+            ParseError::NotFound(format!("Parametrization of surface {}", surface_tag))
+        })?;
+    surface.closest_point(p).ok_or_else(|| {
+        ParseError::GeometryError(format!(
+            "Parametrization of surface {} has no triangles",
+            surface_tag
+        ))
+    })
+}
+
+fn combine(points: &[[f64; 3]; 3], w: [f64; 3]) -> [f64; 3] {
+    let mut q = [0.0; 3];
+    for (point, weight) in points.iter().zip(w) {
+        for k in 0..3 {
+            q[k] += weight * point[k];
+        }
+    }
+    q
+}
+
+/// Barycentric weights of the point of triangle `[a, b, c]` closest to `p`
+///
+/// Follows the region tests of Ericson, Real-Time Collision Detection, 5.1.5.
+fn closest_on_triangle([a, b, c]: [[f64; 3]; 3], p: [f64; 3]) -> [f64; 3] {
+    let sub = |x: [f64; 3], y: [f64; 3]| [x[0] - y[0], x[1] - y[1], x[2] - y[2]];
+    let dot = |x: [f64; 3], y: [f64; 3]| x[0] * y[0] + x[1] * y[1] + x[2] * y[2];
+    let (ab, ac, ap) = (sub(b, a), sub(c, a), sub(p, a));
+
+    let (d1, d2) = (dot(ab, ap), dot(ac, ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+    let bp = sub(p, b);
+    let (d3, d4) = (dot(ab, bp), dot(ac, bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return [0.0, 1.0, 0.0];
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let t = d1 / (d1 - d3);
+        return [1.0 - t, t, 0.0];
+    }
+    let cp = sub(p, c);
+    let (d5, d6) = (dot(ab, cp), dot(ac, cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return [0.0, 0.0, 1.0];
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let t = d2 / (d2 - d6);
+        return [1.0 - t, 0.0, t];
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let t = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return [0.0, 1.0 - t, t];
+    }
+    let denom = va + vb + vc;
+    if denom == 0.0 {
+        // Degenerate triangle
+        return [1.0, 0.0, 0.0];
+    }
+    let (v, w) = (vb / denom, vc / denom);
+    [1.0 - v - w, v, w]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::distance;
+    use crate::parse_msh;
+
+    /// Surface 1 is the square [0, 2] x [0, 2] at z = 1, with u = x / 2 and
+    /// v = y / 2
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Parametrizations\n0 1\n1\n4 2\n\
+        0 0 1 0 0 0 0 0 0 0 0\n2 0 1 1 0 0 0 0 0 0 0\n\
+        2 2 1 1 1 0 0 0 0 0 0\n0 2 1 0 1 0 0 0 0 0 0\n\
+        0 1 2\n0 2 3\n$EndParametrizations\n";
+
+    #[test]
+    fn test_nodes_project_onto_themselves() {
+        let mesh = parse_msh(SQUARE).unwrap();
+        let surface = &mesh.parametrizations.as_ref().unwrap().surfaces[0];
+        for node in &surface.nodes {
+            let (u, v, q) = closest_point(&mesh, EntityTag(1), [node.x, node.y, node.z]).unwrap();
+            assert!(distance(q, [node.x, node.y, node.z]) < 1e-12);
+            assert!((u - node.u).abs() < 1e-12 && (v - node.v).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_projection_of_offset_point() {
+        let mesh = parse_msh(SQUARE).unwrap();
+        let (u, v, q) = closest_point(&mesh, EntityTag(1), [1.0, 0.5, 3.0]).unwrap();
+        assert_eq!(q, [1.0, 0.5, 1.0]);
+        assert_eq!((u, v), (0.5, 0.25));
+    }
+
+    #[test]
+    fn test_closest_on_triangle() {
+        let t = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        assert_eq!(closest_on_triangle(t, [0.25, 0.25, 3.0]), [0.5, 0.25, 0.25]);
+        assert_eq!(closest_on_triangle(t, [2.0, -1.0, 0.0]), [0.0, 1.0, 0.0]);
+        let w = closest_on_triangle(t, [1.0, 1.0, 0.0]);
+        assert!((w[1] - 0.5).abs() < 1e-12 && (w[2] - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_projection_needs_parametrizations() {
+        let mesh = parse_msh("$MeshFormat\n4.1 0 8\n$EndMeshFormat\n").unwrap();
+        assert!(matches!(
+            closest_point(&mesh, EntityTag(1), [0.0; 3]),
+            Err(ParseError::MissingSection(_))
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+//! Numeric tolerances for geometric comparisons
+
+use super::distance;
+use crate::types::Mesh;
+
+/// Tolerance for comparing coordinates and lengths
+///
+/// A length `d` is negligible next to a quantity of magnitude `scale` when
+/// `d <= max(absolute, relative * scale)`. The absolute part covers quantities
+/// close to zero, where a relative comparison fails. [`Mesh::tolerance`]
+/// derives it from the size of the mesh, which is what geometric operations use
+/// by default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tolerance {
+    /// Smallest distance that is never negligible
+    pub absolute: f64,
+    /// Fraction of the compared magnitude that is negligible
+    pub relative: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            absolute: 1e-12,
+            relative: 1e-9,
+        }
+    }
+}
+
+impl Tolerance {
+    pub fn new(absolute: f64, relative: f64) -> Self {
+        Self { absolute, relative }
+    }
+
+    /// Tolerance for a model spanning the box from `min` to `max`
+    ///
+    /// The absolute part is the default relative tolerance times the box
+    /// diagonal, or the default absolute tolerance for an empty or flat box.
+    pub fn from_bounds(min: [f64; 3], max: [f64; 3]) -> Self {
+        let default = Self::default();
+        let diagonal = distance(min, max);
+        if diagonal > 0.0 {
+            Self::new(diagonal * default.relative, default.relative)
+        } else {
+            default
+        }
+    }
+
+    pub fn with_absolute(mut self, absolute: f64) -> Self {
+        self.absolute = absolute;
+        self
+    }
+
+    pub fn with_relative(mut self, relative: f64) -> Self {
+        self.relative = relative;
+        self
+    }
+
+    /// Largest negligible difference between quantities of magnitude `scale`
+    pub fn slack(&self, scale: f64) -> f64 {
+        self.absolute.max(self.relative * scale.abs())
+    }
+
+    /// Whether `a` and `b` are equal within tolerance
+    pub fn approx_eq(&self, a: f64, b: f64) -> bool {
+        (a - b).abs() <= self.slack(a.abs().max(b.abs()))
+    }
+
+    /// Whether the points `a` and `b` are at most the absolute tolerance apart
+    pub fn coincident(&self, a: [f64; 3], b: [f64; 3]) -> bool {
+        distance(a, b) <= self.absolute
+    }
+}
+
+impl Mesh {
+    /// Default tolerance for geometric operations on this mesh
+    ///
+    /// Scaled by the diagonal of [`Mesh::bounding_box`] (see
+    /// [`Tolerance::from_bounds`]).
+    pub fn tolerance(&self) -> Tolerance {
+        match self.bounding_box() {
+            Some((min, max)) => Tolerance::from_bounds(min, max),
+            None => Tolerance::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tolerance_scales_with_mesh() {
+        // Two opposite corners of the unit cube
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 2 1 2\n0 1 0 2\n1\n2\n0 0 0\n1 1 1\n$EndNodes\n";
+        let mesh = crate::parse_msh(content).unwrap();
+        let tolerance = mesh.tolerance();
+        assert!((tolerance.absolute - 3f64.sqrt() * 1e-9).abs() < 1e-18);
+        assert!(tolerance.coincident([0.0; 3], [0.0, 0.0, 1e-10]));
+        assert!(!tolerance.coincident([0.0; 3], [0.0, 0.0, 1e-8]));
+
+        assert!(tolerance.approx_eq(1e6, 1e6 + 1e-4));
+        assert!(!tolerance.approx_eq(1.0, 1.0 + 1e-6));
+        assert_eq!(
+            Tolerance::from_bounds([1.0; 3], [1.0; 3]),
+            Tolerance::default()
+        );
+    }
+}
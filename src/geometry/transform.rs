@@ -0,0 +1,276 @@
+//! Rigid and affine repositioning of a whole mesh
+
+use super::{Affine3, Axis};
+use crate::error::{ParseError, Result};
+use crate::types::{Change, Mesh};
+
+impl Mesh {
+    /// Apply `transform` to every coordinate of the mesh
+    ///
+    /// Moves the nodes, the points and bounding boxes of the entities and
+    /// partitioned entities, and the nodes of the parametrizations, whose
+    /// curvature directions follow the linear part. Periodic transformations
+    /// are conjugated so that they still map masters onto slaves; they are
+    /// left as they are if `transform` is singular. Post-processing data is not
+    /// changed.
+    ///
+    /// Bounding boxes become the boxes of the images of the old boxes, which
+    /// grow under rotations other than quarter turns. Transformations with a
+    /// negative determinant, e.g. reflections, invert the elements (see
+    /// [`Mesh::validate_geometry`](crate::Mesh::validate_geometry)).
+    pub fn transform(&mut self, transform: &Affine3) {
+        self.apply_transform(transform);
+        self.record_change(
+            Change::new("transform")
+                .with_parameter("matrix", format!("{:?}", transform.matrix))
+                .with_count("nodes", self.num_nodes()),
+        );
+    }
+
+    /// Move the mesh by `offset`
+    pub fn translate(&mut self, offset: [f64; 3]) {
+        self.apply_transform(&Affine3::translation(offset));
+        self.record_change(
+            Change::new("translate")
+                .with_parameter("offset", format!("{:?}", offset))
+                .with_count("nodes", self.num_nodes()),
+        );
+    }
+
+    /// Scale the mesh about the origin by `factors` along each axis
+    pub fn scale(&mut self, factors: [f64; 3]) {
+        self.apply_transform(&Affine3::scaling(factors));
+        self.record_change(
+            Change::new("scale")
+                .with_parameter("factors", format!("{:?}", factors))
+                .with_count("nodes", self.num_nodes()),
+        );
+    }
+
+    /// Rotate the mesh by `angle` radians about `axis` (right-hand rule)
+    ///
+    /// # Errors
+    /// Returns [`ParseError::GeometryError`] if the axis direction is zero.
+    pub fn rotate(&mut self, axis: &Axis, angle: f64) -> Result<()> {
+        let rotation = Affine3::rotation(axis, angle).ok_or_else(|| {
+            ParseError::GeometryError("Rotation axis has a zero direction".to_string())
+        })?;
+        self.apply_transform(&rotation);
+        self.record_change(
+            Change::new("rotate")
+                .with_parameter("point", format!("{:?}", axis.point))
+                .with_parameter("direction", format!("{:?}", axis.direction))
+                .with_parameter("angle", angle)
+                .with_count("nodes", self.num_nodes()),
+        );
+        Ok(())
+    }
+
+    /// Move the mesh into the parent frame of its
+    /// [`coordinate_system`](Mesh::coordinate_system)
+    ///
+    /// Applies the transform of the coordinate system as [`Mesh::transform`]
+    /// does, then resets it to the identity so that the coordinate system still
+    /// describes the coordinates. Its name and unit are kept. Does nothing if
+    /// the mesh has no coordinate system or it coincides with its parent.
+    pub fn apply_coordinate_transform(&mut self) {
+        let Some(frame) = self.coordinate_system.as_mut().filter(|f| !f.is_identity()) else {
+            return;
+        };
+        let transform = std::mem::take(&mut frame.transform);
+        let name = frame.name.clone();
+        self.apply_transform(&transform);
+        self.record_change(
+            Change::new("apply_coordinate_transform")
+                .with_parameter("frame", name)
+                .with_parameter("matrix", format!("{:?}", transform.matrix))
+                .with_count("nodes", self.num_nodes()),
+        );
+    }
+
+    pub(super) fn apply_transform(&mut self, transform: &Affine3) {
+        let point = |x: &mut f64, y: &mut f64, z: &mut f64| {
+            [*x, *y, *z] = transform.apply([*x, *y, *z]);
+        };
+        let boxed = |min: [&mut f64; 3], max: [&mut f64; 3]| {
+            let (new_min, new_max) =
+                transform.apply_box([*min[0], *min[1], *min[2]], [*max[0], *max[1], *max[2]]);
+            for (value, new) in min.into_iter().zip(new_min) {
+                *value = new;
+            }
+            for (value, new) in max.into_iter().zip(new_max) {
+                *value = new;
+            }
+        };
+
+        for node in self.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+            point(&mut node.x, &mut node.y, &mut node.z);
+        }
+
+        if let Some(entities) = &mut self.entities {
+            for p in &mut entities.points {
+                point(&mut p.x, &mut p.y, &mut p.z);
+            }
+            for c in &mut entities.curves {
+                boxed(
+                    [&mut c.min_x, &mut c.min_y, &mut c.min_z],
+                    [&mut c.max_x, &mut c.max_y, &mut c.max_z],
+                );
+            }
+            for s in &mut entities.surfaces {
+                boxed(
+                    [&mut s.min_x, &mut s.min_y, &mut s.min_z],
+                    [&mut s.max_x, &mut s.max_y, &mut s.max_z],
+                );
+            }
+            for v in &mut entities.volumes {
+                boxed(
+                    [&mut v.min_x, &mut v.min_y, &mut v.min_z],
+                    [&mut v.max_x, &mut v.max_y, &mut v.max_z],
+                );
+            }
+        }
+
+        if let Some(entities) = &mut self.partitioned_entities {
+            for p in &mut entities.points {
+                point(&mut p.x, &mut p.y, &mut p.z);
+            }
+            for c in &mut entities.curves {
+                boxed(
+                    [&mut c.min_x, &mut c.min_y, &mut c.min_z],
+                    [&mut c.max_x, &mut c.max_y, &mut c.max_z],
+                );
+            }
+            for s in &mut entities.surfaces {
+                boxed(
+                    [&mut s.min_x, &mut s.min_y, &mut s.min_z],
+                    [&mut s.max_x, &mut s.max_y, &mut s.max_z],
+                );
+            }
+            for v in &mut entities.volumes {
+                boxed(
+                    [&mut v.min_x, &mut v.min_y, &mut v.min_z],
+                    [&mut v.max_x, &mut v.max_y, &mut v.max_z],
+                );
+            }
+        }
+
+        if let Some(parametrizations) = &mut self.parametrizations {
+            for node in parametrizations
+                .curves
+                .iter_mut()
+                .flat_map(|c| &mut c.nodes)
+            {
+                point(&mut node.x, &mut node.y, &mut node.z);
+            }
+            for node in parametrizations
+                .surfaces
+                .iter_mut()
+                .flat_map(|s| &mut s.nodes)
+            {
+                point(&mut node.x, &mut node.y, &mut node.z);
+                [node.curv_max_x, node.curv_max_y, node.curv_max_z] =
+                    transform.apply_vector([node.curv_max_x, node.curv_max_y, node.curv_max_z]);
+                [node.curv_min_x, node.curv_min_y, node.curv_min_z] =
+                    transform.apply_vector([node.curv_min_x, node.curv_min_y, node.curv_min_z]);
+            }
+        }
+
+        if let Some(inverse) = transform.inverse() {
+            for link in &mut self.periodic_links {
+                if let Some(periodic) = link.transform() {
+                    link.affine_transform = inverse.then(&periodic).then(transform).to_gmsh();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::PERIODIC;
+    use crate::geometry::{distance, node_coordinates, LengthUnit};
+    use crate::types::{CoordinateSystem, NodeTag};
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_rotation_moves_nodes() {
+        let mut mesh = crate::parse_msh(PERIODIC).unwrap();
+        let axis = Axis::new([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+        mesh.rotate(&axis, FRAC_PI_2).unwrap();
+        mesh.translate([10.0, 0.0, 0.0]);
+        let after = node_coordinates(&mesh);
+        assert!(distance(after[&NodeTag(2)], [10.0, 1.0, 0.0]) < 1e-12);
+        assert!(distance(after[&NodeTag(3)], [9.0, 1.0, 0.0]) < 1e-12);
+
+        let log: Vec<_> = mesh.change_log().iter().map(|c| &c.operation).collect();
+        assert_eq!(log, ["rotate", "translate"]);
+        assert!(mesh.rotate(&Axis::new([0.0; 3], [0.0; 3]), 1.0).is_err());
+    }
+
+    #[test]
+    fn test_rotation_keeps_periodic_links() {
+        let mut mesh = crate::parse_msh(PERIODIC).unwrap();
+        mesh.rotate(&Axis::new([1.0, 2.0, 3.0], [1.0, 1.0, 0.0]), 0.7)
+            .unwrap();
+        mesh.translate([10.0, 0.0, 0.0]);
+
+        let after = node_coordinates(&mesh);
+        for link in &mesh.periodic_links {
+            for (slave, master) in &link.node_correspondences {
+                let image = link.master_to_slave(after[master]).unwrap();
+                assert!(distance(image, after[slave]) < 1e-12);
+            }
+        }
+        assert!(mesh.check_periodic_links().is_empty());
+    }
+
+    #[test]
+    fn test_scale_updates_entity_boxes() {
+        let mut mesh = crate::parse_msh(PERIODIC).unwrap();
+        mesh.scale([2.0, -1.0, 1.0]);
+        let surface = &mesh.entities.as_ref().unwrap().surfaces[0];
+        assert_eq!((surface.min_x, surface.max_x), (0.0, 2.0));
+        assert_eq!((surface.min_y, surface.max_y), (-1.0, 0.0));
+    }
+
+    /// [`PERIODIC`] in millimetres, placed 100 mm along x in its parent frame
+    fn mesh_in_frame() -> Mesh {
+        let mut mesh = crate::parse_msh(PERIODIC).unwrap();
+        mesh.coordinate_system = Some(
+            CoordinateSystem::new("part 1")
+                .with_unit(LengthUnit::Millimetre)
+                .with_transform(Affine3::translation([100.0, 0.0, 0.0])),
+        );
+        mesh
+    }
+
+    #[test]
+    fn test_coordinate_system_round_trip() {
+        let mesh = mesh_in_frame();
+        let reparsed = crate::parse_msh(crate::writer::write_msh(&mesh).unwrap()).unwrap();
+        assert_eq!(reparsed.coordinate_system, mesh.coordinate_system);
+    }
+
+    #[test]
+    fn test_apply_coordinate_transform() {
+        // The parent coordinates do not depend on the unit of the mesh
+        let mut mesh = mesh_in_frame();
+        let mut in_metres = mesh.clone();
+        in_metres.convert_units(LengthUnit::Millimetre, LengthUnit::Metre);
+        for mesh in [&mut mesh, &mut in_metres] {
+            mesh.apply_coordinate_transform();
+            assert!(distance(node_coordinates(mesh)[&NodeTag(3)], [101.0, 1.0, 0.0]) < 1e-9);
+            assert!(mesh.coordinate_system.as_ref().unwrap().is_identity());
+        }
+        assert_eq!(
+            in_metres.coordinate_system.unwrap().unit,
+            Some(LengthUnit::Metre)
+        );
+        assert_eq!(
+            mesh.change_log().last().unwrap().parameters["frame"],
+            "part 1"
+        );
+    }
+}
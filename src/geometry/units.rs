@@ -0,0 +1,146 @@
+//! Conversion of a mesh between length units
+
+use super::Affine3;
+use crate::types::{Change, Mesh};
+use std::fmt;
+
+/// Unit of length of the coordinates of a mesh
+///
+/// MSH files do not record their unit; CAD exports are commonly in
+/// millimetres while solvers often expect metres.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub enum LengthUnit {
+    Micrometre,
+    Millimetre,
+    Centimetre,
+    Metre,
+    Kilometre,
+    Inch,
+    Foot,
+}
+
+impl LengthUnit {
+    /// Length of one unit in metres
+    pub const fn metres(self) -> f64 {
+        match self {
+            Self::Micrometre => 1e-6,
+            Self::Millimetre => 1e-3,
+            Self::Centimetre => 1e-2,
+            Self::Metre => 1.0,
+            Self::Kilometre => 1e3,
+            Self::Inch => 0.0254,
+            Self::Foot => 0.3048,
+        }
+    }
+
+    /// Factor turning a length in `self` into a length in `to`
+    pub fn factor_to(self, to: LengthUnit) -> f64 {
+        if self == to {
+            1.0
+        } else {
+            self.metres() / to.metres()
+        }
+    }
+
+    /// Usual symbol of the unit, e.g. `"mm"`
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Micrometre => "um",
+            Self::Millimetre => "mm",
+            Self::Centimetre => "cm",
+            Self::Metre => "m",
+            Self::Kilometre => "km",
+            Self::Inch => "in",
+            Self::Foot => "ft",
+        }
+    }
+
+    /// The unit whose [`symbol`](Self::symbol) is `symbol`
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        [
+            Self::Micrometre,
+            Self::Millimetre,
+            Self::Centimetre,
+            Self::Metre,
+            Self::Kilometre,
+            Self::Inch,
+            Self::Foot,
+        ]
+        .into_iter()
+        .find(|unit| unit.symbol() == symbol)
+    }
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl Mesh {
+    /// Convert the coordinates of the mesh from `from` to `to`
+    ///
+    /// Scales everything [`Mesh::scale`] does, about the origin, and records
+    /// the conversion in the [change log](Mesh::change_log) so that the unit of
+    /// the mesh can be traced back. The unit of the
+    /// [coordinate system](Mesh::coordinate_system), if any, becomes `to` and
+    /// its transform is adjusted so that the mesh keeps its place in the parent
+    /// frame.
+    pub fn convert_units(&mut self, from: LengthUnit, to: LengthUnit) {
+        let factor = from.factor_to(to);
+        self.apply_transform(&Affine3::scaling([factor; 3]));
+        if let Some(frame) = &mut self.coordinate_system {
+            frame.unit = Some(to);
+            frame.transform = Affine3::scaling([1.0 / factor; 3]).then(&frame.transform);
+        }
+        self.record_change(
+            Change::new("convert_units")
+                .with_parameter("from", from)
+                .with_parameter("to", to)
+                .with_parameter("factor", factor)
+                .with_count("nodes", self.num_nodes()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1 x 3 rectangle of two triangles
+    const RECTANGLE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Entities\n0 0 1 0\n1 0 0 0 1 3 0 0 0\n$EndEntities\n\
+        $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 3 0\n0 3 0\n$EndNodes\n\
+        $Elements\n1 2 1 2\n2 1 2 2\n1 1 2 3\n2 1 3 4\n$EndElements\n";
+
+    #[test]
+    fn test_millimetres_to_metres() {
+        let mut mesh = crate::parse_msh(RECTANGLE).unwrap();
+        mesh.convert_units(LengthUnit::Millimetre, LengthUnit::Metre);
+        let node = &mesh.node_blocks[0].nodes[2];
+        assert!((node.x - 1e-3).abs() < 1e-15 && (node.y - 3e-3).abs() < 1e-15);
+        let surface = &mesh.entities.as_ref().unwrap().surfaces[0];
+        assert!((surface.max_y - 3e-3).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_unit_conversion_is_logged() {
+        let mut mesh = crate::parse_msh(RECTANGLE).unwrap();
+        mesh.convert_units(LengthUnit::Millimetre, LengthUnit::Metre);
+        let change = &mesh.change_log()[0];
+        assert_eq!(change.operation, "convert_units");
+        assert_eq!(change.parameters["from"], "mm");
+        assert_eq!(change.parameters["to"], "m");
+    }
+
+    #[test]
+    fn test_unit_factors() {
+        assert_eq!(LengthUnit::Inch.factor_to(LengthUnit::Millimetre), 25.4);
+        assert_eq!(LengthUnit::Metre.factor_to(LengthUnit::Millimetre), 1e3);
+    }
+}
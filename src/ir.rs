@@ -0,0 +1,512 @@
+//! Format-agnostic representation of an unstructured mesh
+//!
+//! [`UnstructuredMesh`] follows the data model of meshio: points, cells
+//! grouped by type and indexing the points from 0, and named data attached to
+//! the points and cells. Converters to other formats can be written against it
+//! and reach MSH through the conversions with [`Mesh`]:
+//!
+//! ```no_run
+//! use gmsh_parser::ir::UnstructuredMesh;
+//! use gmsh_parser::{parse_msh_file, Mesh};
+//!
+//! let ir = UnstructuredMesh::from(parse_msh_file("model.msh").unwrap());
+//! for block in &ir.cells_by_type {
+//!     println!("{:?}: {} cells", block.cell_type, block.len());
+//! }
+//! let mesh = Mesh::try_from(ir).unwrap();
+//! ```
+//!
+//! As in meshio, the entity and physical tag of each cell are kept in the cell
+//! data [`GEOMETRICAL`] and [`PHYSICAL`], and the physical names in
+//! [`UnstructuredMesh::field_data`].
+
+use crate::error::{ParseError, Result};
+use crate::types::element::{Element, ElementBlock};
+use crate::types::{
+    CoordinateSystem, ElementData, ElementTag, ElementType, Entities, EntityDimension, EntityTag,
+    Mesh, MeshFormat, Node, NodeBlock, NodeData, NodeTag, PhysicalName, PhysicalTag,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Cell data holding the entity tag of each cell
+pub const GEOMETRICAL: &str = "gmsh:geometrical";
+/// Cell data holding the physical tag of each cell, 0 for none
+pub const PHYSICAL: &str = "gmsh:physical";
+
+/// A mesh as points, cells and data arrays, see the [module docs](self)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnstructuredMesh {
+    pub points: Vec<[f64; 3]>,
+    /// One block per cell type, in order of first appearance
+    pub cells_by_type: Vec<CellBlock>,
+    /// Values at the points, by name
+    pub point_data: BTreeMap<String, DataArray>,
+    /// Values at the cells, by name, one array per block of `cells_by_type`
+    pub cell_data: BTreeMap<String, Vec<DataArray>>,
+    /// Physical groups by name, as `(dimension, tag)`
+    pub field_data: BTreeMap<String, (i32, PhysicalTag)>,
+    /// Frame of the points, see [`Mesh::coordinate_system`]
+    pub coordinate_system: Option<CoordinateSystem>,
+}
+
+impl UnstructuredMesh {
+    pub fn num_cells(&self) -> usize {
+        self.cells_by_type.iter().map(CellBlock::len).sum()
+    }
+}
+
+/// Cells of one type, as point indices back to back
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellBlock {
+    pub cell_type: ElementType,
+    pub connectivity: Vec<usize>,
+    /// Start of each cell in `connectivity`, plus the end of the last one
+    pub offsets: Vec<usize>,
+}
+
+impl CellBlock {
+    pub fn new(cell_type: ElementType) -> Self {
+        Self {
+            cell_type,
+            connectivity: Vec::new(),
+            offsets: vec![0],
+        }
+    }
+
+    /// Append a cell made of the points `cell`
+    pub fn push(&mut self, cell: &[usize]) {
+        self.connectivity.extend_from_slice(cell);
+        self.offsets.push(self.connectivity.len());
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Point indices of each cell
+    pub fn cells(&self) -> impl Iterator<Item = &[usize]> + '_ {
+        self.offsets
+            .windows(2)
+            .map(|w| &self.connectivity[w[0]..w[1]])
+    }
+}
+
+/// Values with `num_components` components per point or cell
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataArray {
+    pub num_components: usize,
+    pub values: Vec<f64>,
+}
+
+impl DataArray {
+    pub fn new(num_components: usize, values: Vec<f64>) -> Self {
+        Self {
+            num_components,
+            values,
+        }
+    }
+
+    /// Number of points or cells with values
+    pub fn len(&self) -> usize {
+        self.values.len() / self.num_components.max(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Components of point or cell `index`
+    pub fn get(&self, index: usize) -> Option<&[f64]> {
+        let n = self.num_components;
+        self.values.get(index * n..(index + 1) * n)
+    }
+}
+
+impl From<Mesh> for UnstructuredMesh {
+    fn from(mesh: Mesh) -> Self {
+        Self::from(&mesh)
+    }
+}
+
+impl From<&Mesh> for UnstructuredMesh {
+    /// Points in node order and cells in element order
+    ///
+    /// The last time step of each `$NodeData` and `$ElementData` view becomes
+    /// point and cell data, `NaN` where the view has no value. Elements using
+    /// missing nodes, which [`Mesh::validate`] rejects, are left out.
+    fn from(mesh: &Mesh) -> Self {
+        let mut ir = Self {
+            coordinate_system: mesh.coordinate_system.clone(),
+            ..Self::default()
+        };
+        let mut point_of = HashMap::new();
+        for node in mesh.node_blocks.iter().flat_map(|b| &b.nodes) {
+            point_of.insert(node.tag, ir.points.len());
+            ir.points.push([node.x, node.y, node.z]);
+        }
+
+        let physical_of = entity_physical_tags(mesh);
+        let mut block_of: HashMap<ElementType, usize> = HashMap::new();
+        let mut cell_of: HashMap<ElementTag, (usize, usize)> = HashMap::new();
+        let mut geometrical: Vec<Vec<f64>> = Vec::new();
+        let mut physical: Vec<Vec<f64>> = Vec::new();
+        let mut cell = Vec::new();
+        let elements = mesh.elements().map(|e| {
+            let block = (e.entity_dim(), e.entity_tag(), e.element_type());
+            (block, e.tag(), e.nodes())
+        });
+        for ((dim, entity, element_type), tag, nodes) in elements {
+            cell.clear();
+            cell.extend(nodes.iter().map_while(|n| point_of.get(n).copied()));
+            if cell.len() < nodes.len() {
+                continue;
+            }
+            let b = *block_of.entry(element_type).or_insert_with(|| {
+                ir.cells_by_type.push(CellBlock::new(element_type));
+                geometrical.push(Vec::new());
+                physical.push(Vec::new());
+                ir.cells_by_type.len() - 1
+            });
+            cell_of.insert(tag, (b, ir.cells_by_type[b].len()));
+            ir.cells_by_type[b].push(&cell);
+            geometrical[b].push(entity.0 as f64);
+            let group = physical_of.get(&(dim, entity)).map_or(0, |p| p.0);
+            physical[b].push(group as f64);
+        }
+        if !ir.cells_by_type.is_empty() {
+            let arrays = |values: Vec<Vec<f64>>| values.into_iter().map(|v| DataArray::new(1, v));
+            ir.cell_data
+                .insert(GEOMETRICAL.to_string(), arrays(geometrical).collect());
+            ir.cell_data
+                .insert(PHYSICAL.to_string(), arrays(physical).collect());
+        }
+
+        for view in mesh.node_views() {
+            let records = view.last();
+            let Some(n) = num_components(records.iter().map(|r| (r.num_components(), &r.data)))
+            else {
+                continue;
+            };
+            let mut values = vec![f64::NAN; n * ir.points.len()];
+            for (tag, value) in records.iter().flat_map(|r| &r.data) {
+                if let Some(&i) = point_of.get(tag).filter(|_| value.len() == n) {
+                    values[i * n..(i + 1) * n].copy_from_slice(value);
+                }
+            }
+            ir.point_data
+                .insert(view.name.to_string(), DataArray::new(n, values));
+        }
+
+        for view in mesh.element_views() {
+            let records = view.last();
+            let Some(n) = num_components(records.iter().map(|r| (r.num_components(), &r.data)))
+            else {
+                continue;
+            };
+            let mut arrays: Vec<_> = ir
+                .cells_by_type
+                .iter()
+                .map(|block| DataArray::new(n, vec![f64::NAN; n * block.len()]))
+                .collect();
+            for (tag, value) in records.iter().flat_map(|r| &r.data) {
+                if let Some(&(b, i)) = cell_of.get(tag).filter(|_| value.len() == n) {
+                    arrays[b].values[i * n..(i + 1) * n].copy_from_slice(value);
+                }
+            }
+            ir.cell_data.insert(view.name.to_string(), arrays);
+        }
+
+        for name in &mesh.physical_names {
+            ir.field_data.insert(
+                name.name.clone(),
+                (name.dimension as i32, name.tag.magnitude()),
+            );
+        }
+        ir
+    }
+}
+
+/// First physical tag of every entity carrying one, by `(dimension, tag)`
+fn entity_physical_tags(mesh: &Mesh) -> HashMap<(i32, EntityTag), PhysicalTag> {
+    let entities = mesh.effective_entities();
+    let tagged = (entities.points.iter().map(|e| (0, e.tag, &e.physical_tags)))
+        .chain(entities.curves.iter().map(|e| (1, e.tag, &e.physical_tags)))
+        .chain(
+            entities
+                .surfaces
+                .iter()
+                .map(|e| (2, e.tag, &e.physical_tags)),
+        )
+        .chain(
+            entities
+                .volumes
+                .iter()
+                .map(|e| (3, e.tag, &e.physical_tags)),
+        );
+    tagged
+        .filter_map(|(dim, tag, physical)| Some(((dim, tag), physical.first()?.magnitude())))
+        .collect()
+}
+
+/// Number of components of a view, from its header or its first value
+fn num_components<'a, T: 'a>(
+    mut records: impl Iterator<Item = (Option<usize>, &'a Vec<(T, Vec<f64>)>)>,
+) -> Option<usize> {
+    let (declared, data) = records.next()?;
+    declared
+        .or_else(|| data.first().map(|(_, v)| v.len()))
+        .filter(|&n| n > 0)
+}
+
+impl TryFrom<UnstructuredMesh> for Mesh {
+    type Error = ParseError;
+
+    fn try_from(ir: UnstructuredMesh) -> Result<Self> {
+        Self::try_from(&ir)
+    }
+}
+
+impl TryFrom<&UnstructuredMesh> for Mesh {
+    type Error = ParseError;
+
+    /// A mesh with node `i + 1` for point `i` and elements numbered from 1 in
+    /// cell order
+    ///
+    /// Cells are put on the entity given by [`GEOMETRICAL`], or else by
+    /// [`PHYSICAL`], or else on entity 1 of their dimension. With [`PHYSICAL`],
+    /// entities are created to carry the physical tags. All points go in one
+    /// node block, on the first entity of the highest dimension. Other cell
+    /// data and the point data become one-step `$ElementData` and `$NodeData`
+    /// views.
+    ///
+    /// # Errors
+    /// Returns [`ParseError::MeshValidationError`] if a cell uses a missing
+    /// point, a data array does not match the points or cells, or a physical
+    /// group has an invalid dimension.
+    fn try_from(ir: &UnstructuredMesh) -> Result<Self> {
+        let invalid = |message: String| ParseError::MeshValidationError(message);
+        let num_points = ir.points.len();
+        for (b, block) in ir.cells_by_type.iter().enumerate() {
+            if let Some(&point) = block.connectivity.iter().find(|&&p| p >= num_points) {
+                return Err(invalid(format!(
+                    "Cell block {} uses point {}, but there are {} points",
+                    b, point, num_points
+                )));
+            }
+        }
+        for (name, arrays) in &ir.cell_data {
+            let sizes = arrays.iter().map(DataArray::len);
+            if !sizes.eq(ir.cells_by_type.iter().map(CellBlock::len)) {
+                return Err(invalid(format!(
+                    "Cell data \"{}\" does not match the cell blocks",
+                    name
+                )));
+            }
+        }
+        for (name, array) in &ir.point_data {
+            if array.len() != num_points {
+                return Err(invalid(format!(
+                    "Point data \"{}\" has {} values for {} points",
+                    name,
+                    array.len(),
+                    num_points
+                )));
+            }
+        }
+
+        let mut mesh = Mesh::new(MeshFormat::default());
+        mesh.coordinate_system = ir.coordinate_system.clone();
+        let entity_data = ir
+            .cell_data
+            .get(GEOMETRICAL)
+            .or_else(|| ir.cell_data.get(PHYSICAL));
+        let physical_data = ir.cell_data.get(PHYSICAL);
+        let mut physical_tags: BTreeMap<(i32, EntityTag), Vec<PhysicalTag>> = BTreeMap::new();
+        let mut tag_of = Vec::with_capacity(ir.cells_by_type.len());
+        let mut next_tag = 1;
+        for (b, block) in ir.cells_by_type.iter().enumerate() {
+            let dim = block.cell_type.dimension();
+            let mut groups: Vec<(EntityTag, Vec<Element>)> = Vec::new();
+            let mut tags = Vec::with_capacity(block.len());
+            for (i, cell) in block.cells().enumerate() {
+                let value = |data: Option<&Vec<DataArray>>| {
+                    data.and_then(|arrays| arrays[b].get(i)?.first().copied())
+                };
+                let entity = EntityTag(value(entity_data).map_or(1, |v| v as i32));
+                let nodes = cell.iter().map(|&p| NodeTag(p as u64 + 1)).collect();
+                let element = Element::new(ElementTag(next_tag), nodes);
+                match groups.iter_mut().find(|(tag, _)| *tag == entity) {
+                    Some((_, elements)) => elements.push(element),
+                    None => groups.push((entity, vec![element])),
+                }
+                if let Some(physical) = value(physical_data).map(|v| PhysicalTag(v as i32)) {
+                    let carried = physical_tags.entry((dim, entity)).or_default();
+                    if physical.0 != 0 && !carried.contains(&physical) {
+                        carried.push(physical);
+                    }
+                }
+                tags.push(ElementTag(next_tag));
+                next_tag += 1;
+            }
+            tag_of.push(tags);
+            for (entity, elements) in groups {
+                mesh.element_blocks
+                    .push(ElementBlock::new(dim, entity, block.cell_type, elements));
+            }
+        }
+
+        let max_dim = mesh.element_blocks.iter().map(|b| b.entity_dim).max();
+        let host = mesh
+            .element_blocks
+            .iter()
+            .find(|b| Some(b.entity_dim) == max_dim)
+            .map_or((0, EntityTag(1)), |b| (b.entity_dim, b.entity_tag));
+        if num_points > 0 {
+            mesh.node_blocks.push(NodeBlock {
+                entity_dim: EntityDimension::from_i32(host.0).unwrap_or(EntityDimension::Point),
+                entity_tag: host.1,
+                parametric: false,
+                nodes: ir
+                    .points
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| Node {
+                        tag: NodeTag(i as u64 + 1),
+                        x: p[0],
+                        y: p[1],
+                        z: p[2],
+                        parametric_coords: None,
+                    })
+                    .collect(),
+            });
+        }
+
+        if physical_data.is_some() {
+            let mut entities = Entities::synthesize(&mesh.node_blocks, mesh.element_block_refs());
+            let mut carried =
+                |dim: i32, tag: EntityTag| physical_tags.remove(&(dim, tag)).unwrap_or_default();
+            for e in &mut entities.points {
+                e.physical_tags = carried(0, e.tag);
+            }
+            for e in &mut entities.curves {
+                e.physical_tags = carried(1, e.tag);
+            }
+            for e in &mut entities.surfaces {
+                e.physical_tags = carried(2, e.tag);
+            }
+            for e in &mut entities.volumes {
+                e.physical_tags = carried(3, e.tag);
+            }
+            mesh.entities = Some(entities);
+        }
+
+        for (name, &(dim, tag)) in &ir.field_data {
+            let dimension = EntityDimension::from_i32(dim).ok_or_else(|| {
+                invalid(format!(
+                    "Physical group \"{}\" has invalid dimension {}",
+                    name, dim
+                ))
+            })?;
+            mesh.physical_names
+                .push(PhysicalName::new(dimension, tag, name.clone()));
+        }
+
+        for (name, array) in &ir.point_data {
+            mesh.node_data.push(NodeData {
+                string_tags: vec![name.clone()],
+                real_tags: vec![0.0],
+                integer_tags: vec![0, array.num_components as i32, num_points as i32],
+                data: (0..num_points)
+                    .filter_map(|i| Some((NodeTag(i as u64 + 1), array.get(i)?.to_vec())))
+                    .collect(),
+            });
+        }
+        for (name, arrays) in &ir.cell_data {
+            if name == GEOMETRICAL || name == PHYSICAL {
+                continue;
+            }
+            let num_components = arrays.first().map_or(1, |a| a.num_components);
+            let data: Vec<_> = arrays
+                .iter()
+                .zip(&tag_of)
+                .flat_map(|(array, tags)| {
+                    tags.iter()
+                        .enumerate()
+                        .filter_map(|(i, &tag)| Some((tag, array.get(i)?.to_vec())))
+                })
+                .collect();
+            mesh.element_data.push(ElementData {
+                string_tags: vec![name.clone()],
+                real_tags: vec![0.0],
+                integer_tags: vec![0, num_components as i32, data.len() as i32],
+                data,
+            });
+        }
+
+        mesh.validate()?;
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::NAMED_SQUARE;
+
+    #[test]
+    fn test_mesh_to_cells() {
+        let mesh = crate::parse_msh(NAMED_SQUARE).unwrap();
+        let ir = UnstructuredMesh::from(&mesh);
+        assert_eq!(ir.points.len(), 4);
+        assert_eq!(ir.num_cells(), 5);
+        assert_eq!(ir.field_data.len(), 2);
+        let triangles = ir
+            .cells_by_type
+            .iter()
+            .find(|b| b.cell_type == ElementType::Triangle3)
+            .unwrap();
+        let cells: Vec<_> = triangles.cells().collect();
+        assert_eq!(cells, [[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_cells_and_groups() {
+        let mut mesh = crate::parse_msh(NAMED_SQUARE).unwrap();
+        mesh.coordinate_system = Some(CoordinateSystem::new("part 1"));
+        let ir = UnstructuredMesh::from(&mesh);
+        let back = Mesh::try_from(&ir).unwrap();
+        assert_eq!(back.coordinate_system, mesh.coordinate_system);
+        assert_eq!(back.num_nodes(), 4);
+        assert_eq!(back.num_elements(), 5);
+        assert_eq!(back.physical_group("boundary").unwrap().num_elements(), 2);
+        assert_eq!(back.physical_group("My surface").unwrap().num_elements(), 2);
+        assert_eq!(UnstructuredMesh::from(back), ir);
+    }
+
+    #[test]
+    fn test_invalid_cells_are_rejected() {
+        let mut ir = UnstructuredMesh {
+            points: vec![[0.0; 3], [1.0, 0.0, 0.0]],
+            ..Default::default()
+        };
+        let mut block = CellBlock::new(ElementType::Line2);
+        block.push(&[0, 1]);
+        ir.cells_by_type.push(block);
+        ir.point_data
+            .insert("T".to_string(), DataArray::new(1, vec![1.0, 2.0]));
+        let mesh = Mesh::try_from(&ir).unwrap();
+        assert_eq!(mesh.node_data[0].data[1].1, [2.0]);
+        assert_eq!(mesh.element_blocks[0].entity_tag, EntityTag(1));
+        assert!(mesh.entities.is_none());
+
+        ir.cells_by_type[0].push(&[1, 2]);
+        assert!(Mesh::try_from(&ir).is_err());
+    }
+}
@@ -17,17 +17,55 @@
 //! - `$Parametrizations` - Parametric curve and surface definitions
 //! - `$NodeData`, `$ElementData`, `$ElementNodeData` - Post-processing data
 //! - `$InterpolationScheme` - Custom interpolation schemes
+//! - `$NodeSets`, `$ElementSets` - Named node and element sets (crate-defined, see [`sets`])
+//! - `$CoordinateSystem` - Frame and unit of the coordinates (crate-defined, see
+//!   [`CoordinateSystem`])
 //!
 //! ## Unknown Sections
 //!
 //! When the parser encounters unknown sections (not listed above), it will:
-//! - Skip the section content
+//! - Keep the section lines in `mesh.raw_sections` (see [`RawSection`])
 //! - Add a warning to `mesh.warnings`
 //! - Continue parsing the rest of the file
 //!
 //! This allows the parser to handle MSH files that contain sections not yet supported
 //! or custom sections added by specific Gmsh versions.
 //!
+//! [`ParserOptions`] (see [`parse_msh_with`]) can reject unknown sections
+//! instead, skip post-processing and parametrization sections, or record
+//! inconsistent `$Nodes`/`$Elements` headers as warnings rather than errors.
+//! Its lenient mode also recovers from missing `$End` markers, unknown element
+//! types and extra tokens, returning the mesh that could be read with located
+//! warnings. With compact storage, element blocks are parsed into flat arrays
+//! ([`ElementBlockCompact`]) to save memory on large meshes.
+//!
+//! ## Writing
+//!
+//! [`writer::write_msh`] and [`writer::write_msh_file`] write a mesh back as
+//! ASCII MSH 4.1, so files can be parsed, modified and saved. Unknown sections
+//! are written back unchanged after the known ones.
+//!
+//! ## Tags
+//!
+//! Node, element, entity and physical tags have their own types ([`NodeTag`],
+//! [`ElementTag`], [`EntityTag`], [`PhysicalTag`]) so they cannot be mixed up.
+//! They convert to and from their integer values and compare equal to them.
+//!
+//! ## Optional Features
+//!
+//! - `fancy` (default) - colored, terminal-aware rendering of diagnostics; disable default
+//!   features to build for `wasm32-unknown-unknown`, and parse with [`parse_msh_bytes`]
+//! - `serde` - `Serialize`/`Deserialize` for all mesh types
+//! - `cache` - binary cache and content-addressed store of parsed meshes (see [`cache`], [`repo`]),
+//!   and named selections saved next to mesh files (see [`selections`])
+//! - `rkyv` - memory-mapped, zero-copy mesh archives (see [`archive`])
+//! - `ndarray` - sampling of element data onto structured grids (see [`fields::rasterize`]),
+//!   and matrix views of exported coordinates (see [`export::MeshArrays`])
+//! - `gzip`, `zstd` - transparent decompression of `.msh.gz` and `.msh.zst` files by
+//!   [`parse_msh_file`] and the other path-based functions, and compression of them by
+//!   [`registry::write_any`]
+//! - `tokio` - `parse_msh_file_async`, reading files without blocking an async runtime
+//!
 //! ## Example
 //!
 //! ### Quick Summary
@@ -81,14 +119,54 @@
 //! }
 //! ```
 
+#[cfg(feature = "rkyv")]
+pub mod archive;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod compat;
 pub mod error;
+pub mod export;
+pub mod fields;
+pub mod filter;
+#[cfg(test)]
+mod fixtures;
+pub mod generate;
+pub mod geometry;
+#[cfg(feature = "cache")]
+mod hash;
+pub mod ir;
+pub mod lint;
+pub mod materials;
 pub mod parser;
+pub mod physical;
+pub mod quality;
+pub mod registry;
+#[cfg(feature = "cache")]
+pub mod repo;
+pub mod roundtrip;
+#[cfg(feature = "cache")]
+pub mod selections;
+pub mod sets;
+pub mod topology;
 pub mod types;
+pub mod views;
+pub mod writer;
 
 // Re-export main types and functions
-pub use error::{ParseError, ParseWarning, Result};
-pub use parser::{parse_msh, parse_msh_file};
+pub use error::{Diagnostics, ParseError, ParseWarning, Result, Severity, WarningKind};
+pub use parser::{
+    parse_elements_into, parse_elements_only, parse_msh, parse_msh_bytes, parse_msh_bytes_with,
+    parse_msh_file, parse_msh_file_into, parse_msh_file_with, parse_msh_reader, parse_msh_with,
+    parse_nodes_into, parse_nodes_only, probe_msh_file, BlockFilter, ElementSink, MshInfo,
+    NodeSink, ParseProgress, ParserOptions, SectionFilter,
+};
+#[cfg(feature = "tokio")]
+pub use parser::{parse_msh_file_async, parse_msh_file_with_async};
+pub use sets::{ElementSet, NodeSet};
 pub use types::{
-    CurveEntity, ElementBlock, ElementTopology, ElementType, Entities, EntityDimension, FileType,
-    Mesh, MeshFormat, NodeBlock, PhysicalName, PointEntity, SurfaceEntity, Version, VolumeEntity,
+    Change, CoordinateSystem, CurveEntity, ElementBlock, ElementBlockCompact, ElementFamily,
+    ElementTag, ElementTopology, ElementType, Entities, EntityDimension, EntityGraph, EntityTag,
+    FileType, Indexing, Mesh, MeshFormat, MeshSummary, NodeBlock, NodeTag, Orientation,
+    PhysicalName, PhysicalTag, PointEntity, RawSection, Renumbering, SectionHeader, SurfaceEntity,
+    Version, VolumeEntity,
 };
@@ -0,0 +1,375 @@
+//! Checks for meshes that parse fine but are likely to give wrong results
+//!
+//! [`check_node_ordering`] catches higher-order elements written with a node
+//! ordering other than Gmsh's, e.g. by a converter from another format:
+//!
+//! ```no_run
+//! use gmsh_parser::lint::check_node_ordering;
+//! use gmsh_parser::parse_msh_file;
+//!
+//! let mesh = parse_msh_file("model.msh").unwrap();
+//! for issue in check_node_ordering(&mesh) {
+//!     println!(
+//!         "element {}: node {} is {:.3} away from the middle of {}-{}",
+//!         issue.element, issue.node, issue.distance, issue.edge[0], issue.edge[1]
+//!     );
+//! }
+//! ```
+//!
+//! [`check_entity_nodes`] catches elements using nodes that belong to
+//! unrelated entities, a sign of a corrupted or badly merged file.
+//!
+//! [`check_tied_interface`] checks that shells or beams meant to be tied to
+//! solids share their nodes, rather than lying on top of them unconnected.
+
+use crate::error::Result;
+use crate::generate::PointGrid;
+use crate::geometry::{distance, node_coordinates};
+use crate::topology::corner_edges;
+use crate::types::{ElementTag, ElementType, EntityTag, Mesh, NodeTag, PhysicalTag};
+use std::collections::{BTreeSet, HashMap};
+
+/// Largest distance between a mid-side node and the middle of its edge, as a
+/// fraction of the edge length, accepted by [`check_node_ordering`]
+///
+/// Mid-side nodes of curved elements are moved onto the geometry, so they are
+/// rarely exactly in the middle. A misplaced node is typically half an edge
+/// length or more away.
+pub const NODE_ORDERING_TOLERANCE: f64 = 0.25;
+
+/// A mid-side node that does not lie near the middle of its edge
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeOrderingIssue {
+    /// Element the node belongs to
+    pub element: ElementTag,
+    /// The misplaced mid-side node
+    pub node: NodeTag,
+    /// Corner nodes of the edge the node should lie on, in Gmsh's ordering
+    pub edge: [NodeTag; 2],
+    /// Distance between the node and the middle of the edge
+    pub distance: f64,
+}
+
+/// Check that the mid-side nodes of second-order elements lie near the
+/// middle of their edges
+///
+/// Every mid-side node is compared with the middle of the edge that Gmsh's
+/// node ordering puts it on. A node further away than
+/// [`NODE_ORDERING_TOLERANCE`] times the edge length is reported, so an
+/// element with a different ordering convention gives one issue per displaced
+/// node. Elements of other orders and nodes without coordinates are skipped.
+pub fn check_node_ordering(mesh: &Mesh) -> Vec<NodeOrderingIssue> {
+    let coords = node_coordinates(mesh);
+    let mut issues = Vec::new();
+    for block in mesh.element_block_refs() {
+        let Some(edges) = mid_side_edges(block.element_type()) else {
+            continue;
+        };
+        let first_mid_side = block.element_type().fixed_node_count().unwrap_or_default()
+            - edges.len()
+            - interior_nodes(block.element_type());
+        for element in block.elements() {
+            for (i, &[a, b]) in edges.iter().enumerate() {
+                let tags = (
+                    element.nodes().get(a),
+                    element.nodes().get(b),
+                    element.nodes().get(first_mid_side + i),
+                );
+                let (Some(&a), Some(&b), Some(&node)) = tags else {
+                    continue;
+                };
+                let (Some(pa), Some(pb), Some(p)) =
+                    (coords.get(&a), coords.get(&b), coords.get(&node))
+                else {
+                    continue;
+                };
+                let middle = [0, 1, 2].map(|k| 0.5 * (pa[k] + pb[k]));
+                let offset = distance(*p, middle);
+                if offset > NODE_ORDERING_TOLERANCE * distance(*pa, *pb) {
+                    issues.push(NodeOrderingIssue {
+                        element: element.tag(),
+                        node,
+                        edge: [a, b],
+                        distance: offset,
+                    });
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// A node used by an element but stored on an entity outside the closure of
+/// the element's entity
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntityNodeIssue {
+    pub element: ElementTag,
+    pub node: NodeTag,
+    /// `(dim, tag)` of the element block
+    pub element_entity: (i32, EntityTag),
+    /// `(dim, tag)` of the node block holding the node
+    pub node_entity: (i32, EntityTag),
+}
+
+/// Check that elements only use nodes of their entity or of its boundary
+///
+/// Gmsh stores each node on the entity of lowest dimension it lies on, so the
+/// nodes of an element belong to the element's entity or to the entities
+/// bounding it, recursively (see [`EntityGraph::closure`]). Nodes of
+/// embedded points and curves are reported too, since embedded entities are
+/// not in the bounding lists.
+///
+/// Meshes without entities have nothing to check against; nodes missing from
+/// the node blocks are left to [`Mesh::validate`].
+///
+/// [`EntityGraph::closure`]: crate::types::EntityGraph::closure
+pub fn check_entity_nodes(mesh: &Mesh) -> Vec<EntityNodeIssue> {
+    let Some(graph) = mesh.entity_graph() else {
+        return Vec::new();
+    };
+    let node_entities: HashMap<NodeTag, (i32, EntityTag)> = mesh
+        .node_blocks
+        .iter()
+        .flat_map(|b| {
+            b.nodes
+                .iter()
+                .map(|n| (n.tag, (b.entity_dim(), b.entity_tag())))
+        })
+        .collect();
+
+    let mut issues = Vec::new();
+    for block in mesh.element_block_refs() {
+        let closure = graph.closure(block.entity_dim(), block.entity_tag());
+        for element in block.elements() {
+            for node in element.nodes() {
+                let Some(&node_entity) = node_entities.get(node) else {
+                    continue;
+                };
+                if !closure.contains(&node_entity) {
+                    issues.push(EntityNodeIssue {
+                        element: element.tag(),
+                        node: *node,
+                        element_entity: (block.entity_dim(), block.entity_tag()),
+                        node_entity,
+                    });
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// Why a node of an interface element is not tied to the host elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterfaceNodeIssueKind {
+    /// A host node lies at the same position but is a different node, so the
+    /// interface is not connected to the host
+    Unshared { coincident: NodeTag },
+    /// No host node lies at the position of the node
+    Unmatched,
+}
+
+/// A node of an interface element that no host element uses, see
+/// [`check_tied_interface`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterfaceNodeIssue {
+    pub node: NodeTag,
+    /// First interface element using the node, in file order
+    pub element: ElementTag,
+    pub kind: InterfaceNodeIssueKind,
+}
+
+/// Check that the elements of the physical group `interface` are tied to
+/// those of the physical group `host`
+///
+/// Shells or beams meant to be tied to solids, e.g. a skin or reinforcement
+/// embedded in a volume, must share their nodes with the solid elements;
+/// otherwise the solver sees two disconnected parts. Each node of the
+/// interface elements that no host element uses is reported once, as
+/// [`Unshared`](InterfaceNodeIssueKind::Unshared) if a host node lies at the
+/// same position within [`Mesh::tolerance`], which usually means the parts were
+/// meshed separately and never merged, or as
+/// [`Unmatched`](InterfaceNodeIssueKind::Unmatched) otherwise.
+///
+/// Groups are given as `(dimension, tag)`.
+///
+/// # Errors
+/// Returns [`ParseError::NotFound`](crate::ParseError::NotFound) if either
+/// physical group has no entity.
+pub fn check_tied_interface(
+    mesh: &Mesh,
+    interface: (i32, PhysicalTag),
+    host: (i32, PhysicalTag),
+) -> Result<Vec<InterfaceNodeIssue>> {
+    let interface = mesh.physical_group(interface)?;
+    let host = mesh.physical_group(host)?;
+    let coords = node_coordinates(mesh);
+
+    let mut grid = PointGrid::new(mesh.tolerance().absolute);
+    for &tag in &host.node_tags {
+        if let Some(&p) = coords.get(&tag) {
+            grid.insert(tag, p, 0);
+        }
+    }
+
+    let mut issues = Vec::new();
+    let mut seen = BTreeSet::new();
+    for element in interface.elements() {
+        for &node in element.nodes() {
+            if host.node_tags.contains(&node) || !seen.insert(node) {
+                continue;
+            }
+            let coincident = coords.get(&node).and_then(|&p| grid.find_other_group(p, 1));
+            issues.push(InterfaceNodeIssue {
+                node,
+                element: element.tag(),
+                kind: match coincident {
+                    Some(coincident) => InterfaceNodeIssueKind::Unshared { coincident },
+                    None => InterfaceNodeIssueKind::Unmatched,
+                },
+            });
+        }
+    }
+    Ok(issues)
+}
+
+/// Corner nodes of the edges carrying the mid-side nodes of second-order
+/// elements, in the order of the mid-side nodes
+fn mid_side_edges(element_type: ElementType) -> Option<&'static [[usize; 2]]> {
+    (element_type.polynomial_order() == Some(2)).then(|| corner_edges(element_type.family()))
+}
+
+/// Number of face and volume nodes following the mid-side nodes
+fn interior_nodes(element_type: ElementType) -> usize {
+    use ElementType::*;
+    match element_type {
+        Quadrangle9 => 1,
+        Hexahedron27 => 7,
+        Prism18 => 3,
+        Pyramid14 => 1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    fn triangle6(nodes: &str) -> Mesh {
+        let content = format!(
+            "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+             $Nodes\n1 6 1 6\n2 1 0 6\n1\n2\n3\n4\n5\n6\n\
+             0 0 0\n1 0 0\n0 1 0\n0.5 0 0\n0.5 0.5 0\n0 0.5 0\n$EndNodes\n\
+             $Elements\n1 1 1 1\n2 1 9 1\n1 {}\n$EndElements\n",
+            nodes
+        );
+        parse_msh(content).unwrap()
+    }
+
+    #[test]
+    fn test_gmsh_ordering_passes() {
+        assert!(check_node_ordering(&triangle6("1 2 3 4 5 6")).is_empty());
+    }
+
+    #[test]
+    fn test_rotated_mid_side_nodes_are_reported() {
+        // Mid-side nodes listed starting from the edge (1, 2), as in some other formats
+        let issues = check_node_ordering(&triangle6("1 2 3 5 6 4"));
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].element, ElementTag(1));
+        assert_eq!(issues[0].node, NodeTag(5));
+        assert_eq!(issues[0].edge, [NodeTag(1), NodeTag(2)]);
+        assert!((issues[0].distance - 0.5).abs() < 1e-12);
+    }
+
+    /// A unit square of four triangles around a centre node, with a node on
+    /// each corner point and the edges meshed on their own curves
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Entities\n4 4 1 0\n1 0 0 0 0\n2 1 0 0 0\n3 1 1 0 0\n4 0 1 0 0\n\
+        1 0 0 0 1 0 0 0 2 1 -2\n2 1 0 0 1 1 0 0 2 2 -3\n3 0 1 0 1 1 0 0 2 3 -4\n\
+        4 0 0 0 0 1 0 0 2 4 -1\n1 0 0 0 1 1 0 0 4 1 2 3 4\n$EndEntities\n\
+        $Nodes\n5 5 1 5\n0 1 0 1\n1\n0 0 0\n0 2 0 1\n2\n1 0 0\n0 3 0 1\n3\n1 1 0\n\
+        0 4 0 1\n4\n0 1 0\n2 1 0 1\n5\n0.5 0.5 0\n$EndNodes\n\
+        $Elements\n5 8 1 8\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n1 4 1 1\n4 4 1\n\
+        2 1 2 4\n5 1 2 5\n6 2 3 5\n7 3 4 5\n8 4 1 5\n$EndElements\n";
+
+    #[test]
+    fn test_nodes_inside_entity_closure() {
+        assert!(check_entity_nodes(&parse_msh(SQUARE).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_nodes_outside_entity_closure() {
+        // Make the bottom edge use the centre node of the surface
+        let mut mesh = parse_msh(SQUARE).unwrap();
+        mesh.element_blocks[0].elements[0].nodes[0] = NodeTag(5);
+        let issues = check_entity_nodes(&mesh);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].element, ElementTag(1));
+        assert_eq!(issues[0].node, NodeTag(5));
+        assert_eq!(issues[0].node_entity, (2, EntityTag(1)));
+    }
+
+    /// A unit cube of one hexahedron, the physical volume 7, with its face
+    /// x = 0 meshed as the physical surface 1
+    const CUBE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Entities\n0 0 1 1\n1 0 0 0 0 1 1 1 1 0\n1 0 0 0 1 1 1 1 7 0\n$EndEntities\n\
+        $Nodes\n1 8 1 8\n3 1 0 8\n1\n2\n3\n4\n5\n6\n7\n8\n\
+        0 0 0\n1 0 0\n1 1 0\n0 1 0\n0 0 1\n1 0 1\n1 1 1\n0 1 1\n$EndNodes\n\
+        $Elements\n2 2 1 2\n2 1 3 1\n1 1 4 8 5\n3 1 5 1\n2 1 2 3 4 5 6 7 8\n$EndElements\n";
+
+    #[test]
+    fn test_tied_interface() {
+        let mesh = parse_msh(CUBE).unwrap();
+        let (left, solid) = ((2, PhysicalTag(1)), (3, PhysicalTag(7)));
+        assert!(check_tied_interface(&mesh, left, solid).unwrap().is_empty());
+        assert!(check_tied_interface(&mesh, left, (3, PhysicalTag(99))).is_err());
+    }
+
+    #[test]
+    fn test_untied_interface_nodes() {
+        let mut mesh = parse_msh(CUBE).unwrap();
+        let (left, solid) = ((2, PhysicalTag(1)), (3, PhysicalTag(7)));
+
+        // Give a face of the left side its own copy of a node, then move it
+        let group = mesh.physical_group(left).unwrap();
+        let element = group.elements().next().unwrap();
+        let (tag, original) = (element.tag(), element.nodes()[0]);
+        let mut copy = mesh
+            .node_blocks
+            .iter()
+            .flat_map(|b| &b.nodes)
+            .find(|n| n.tag == original)
+            .unwrap()
+            .clone();
+        copy.tag = NodeTag(1_000_000);
+        mesh.node_blocks[0].nodes.push(copy);
+        let face = mesh
+            .element_blocks
+            .iter_mut()
+            .flat_map(|b| &mut b.elements)
+            .find(|e| e.tag == tag)
+            .unwrap();
+        face.nodes[0] = NodeTag(1_000_000);
+
+        let issues = check_tied_interface(&mesh, left, solid).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].node, NodeTag(1_000_000));
+        assert_eq!(issues[0].element, tag);
+        assert_eq!(
+            issues[0].kind,
+            InterfaceNodeIssueKind::Unshared {
+                coincident: original
+            }
+        );
+
+        mesh.node_blocks[0].nodes.last_mut().unwrap().x += 0.5;
+        let issues = check_tied_interface(&mesh, left, solid).unwrap();
+        assert_eq!(issues[0].kind, InterfaceNodeIssueKind::Unmatched);
+    }
+}
@@ -0,0 +1,226 @@
+//! Material assignment by physical group
+//!
+//! Solvers usually give each volume physical group a material. A
+//! [`MaterialTable`] records these assignments and checks them against a mesh:
+//!
+//! ```no_run
+//! use gmsh_parser::materials::{Material, MaterialTable};
+//! use gmsh_parser::parse_msh_file;
+//!
+//! let mesh = parse_msh_file("model.msh").unwrap();
+//! let table = MaterialTable::new()
+//!     .assign("steel part", Material::new("steel", 1).with_property("E", 210e9))
+//!     .assign("core", Material::new("copper", 2).with_property("E", 117e9));
+//! // One material id per volume element, in file order
+//! let ids = table.material_ids(&mesh).unwrap();
+//! ```
+//!
+//! Materials are assigned to the elements of the highest dimension in the mesh:
+//! volumes in 3D meshes, surfaces in 2D ones.
+
+use crate::error::{ParseError, Result};
+use crate::physical::PhysicalGroupId;
+use crate::types::{ElementTag, EntityTag, Mesh, PhysicalTag};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+/// A user-defined material
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Material {
+    pub name: String,
+    /// Identifier written to solver input, e.g. in [`MaterialTable::material_ids`]
+    pub id: i32,
+    /// Free-form properties, e.g. `E = 210e9`
+    pub properties: BTreeMap<String, String>,
+}
+
+impl Material {
+    pub fn new(name: impl Into<String>, id: i32) -> Self {
+        Self {
+            name: name.into(),
+            id,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_property(mut self, key: impl Into<String>, value: impl fmt::Display) -> Self {
+        self.properties.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// The property `key` parsed as `T`, or `None` if it is missing or does not
+    /// parse
+    pub fn property<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.properties.get(key)?.parse().ok()
+    }
+}
+
+/// Physical group given by name or by `(dimension, tag)`, owned
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MaterialGroup {
+    Name(String),
+    Tag(i32, PhysicalTag),
+}
+
+impl MaterialGroup {
+    fn id(&self) -> PhysicalGroupId<'_> {
+        match self {
+            Self::Name(name) => PhysicalGroupId::Name(name),
+            &Self::Tag(dim, tag) => PhysicalGroupId::Tag(dim, tag),
+        }
+    }
+}
+
+impl fmt::Display for MaterialGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "\"{}\"", name),
+            Self::Tag(dim, tag) => write!(f, "(dim={}, tag={})", dim, tag),
+        }
+    }
+}
+
+/// Materials of physical groups, in assignment order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaterialTable {
+    pub assignments: Vec<(MaterialGroup, Material)>,
+}
+
+impl MaterialTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give `material` to the physical group `group`, a name or a
+    /// `(dimension, tag)` pair
+    pub fn assign<'a>(mut self, group: impl Into<PhysicalGroupId<'a>>, material: Material) -> Self {
+        let group = match group.into() {
+            PhysicalGroupId::Name(name) => MaterialGroup::Name(name.to_string()),
+            PhysicalGroupId::Tag(dim, tag) => MaterialGroup::Tag(dim, tag),
+        };
+        self.assignments.push((group, material));
+        self
+    }
+
+    /// Check that every element of the highest dimension gets exactly one
+    /// material
+    ///
+    /// # Errors
+    /// Returns [`ParseError::NotFound`] if a group is not in the mesh, and
+    /// [`ParseError::MeshValidationError`] if a group has a lower dimension or
+    /// an element has no material or several.
+    pub fn validate(&self, mesh: &Mesh) -> Result<()> {
+        self.element_materials(mesh).map(|_| ())
+    }
+
+    /// Material of every element of the highest dimension, in file order
+    ///
+    /// # Errors
+    /// See [`MaterialTable::validate`].
+    pub fn element_materials(&self, mesh: &Mesh) -> Result<Vec<(ElementTag, &Material)>> {
+        let dim = mesh_dimension(mesh);
+        let mut by_entity: HashMap<EntityTag, (&MaterialGroup, &Material)> = HashMap::new();
+        for (group, material) in &self.assignments {
+            let physical = mesh.physical_group(group.id())?;
+            if physical.dim != dim {
+                return Err(ParseError::MeshValidationError(format!(
+                    "Material group {} has dimension {}, elements to cover have dimension {}",
+                    group, physical.dim, dim
+                )));
+            }
+            for &entity in &physical.entities {
+                if let Some((other, _)) = by_entity.insert(entity, (group, material)) {
+                    return Err(ParseError::MeshValidationError(format!(
+                        "Elements of entity {} get a material from both {} and {}",
+                        entity, other, group
+                    )));
+                }
+            }
+        }
+
+        let mut result = Vec::with_capacity(mesh.num_elements());
+        for block in mesh.element_block_refs() {
+            if block.entity_dim() != dim {
+                continue;
+            }
+            let Some(&(_, material)) = by_entity.get(&block.entity_tag()) else {
+                return Err(ParseError::MeshValidationError(format!(
+                    "Elements of entity {} (dim={}) have no material",
+                    block.entity_tag(),
+                    dim
+                )));
+            };
+            result.extend(block.elements().map(|e| (e.tag(), material)));
+        }
+        Ok(result)
+    }
+
+    /// Material id of every element of the highest dimension, in file order
+    ///
+    /// # Errors
+    /// See [`MaterialTable::validate`].
+    pub fn material_ids(&self, mesh: &Mesh) -> Result<Vec<i32>> {
+        Ok(self
+            .element_materials(mesh)?
+            .into_iter()
+            .map(|(_, material)| material.id)
+            .collect())
+    }
+}
+
+/// Highest dimension of the element blocks
+fn mesh_dimension(mesh: &Mesh) -> i32 {
+    mesh.element_block_refs()
+        .map(|b| b.entity_dim())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit square of two triangles, with the physical curve 5 and the
+    /// physical surface 6 "My surface"
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $PhysicalNames\n1\n2 6 \"My surface\"\n$EndPhysicalNames\n\
+        $Entities\n0 3 1 0\n1 0 0 0 1 0 0 1 5 0\n2 1 0 0 1 1 0 1 5 0\n3 0 1 0 1 1 0 0 0\n\
+        1 0 0 0 1 1 0 1 6 0\n$EndEntities\n\
+        $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+        $Elements\n4 5 1 5\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n\
+        2 1 2 2\n4 1 2 3\n5 1 3 4\n$EndElements\n";
+
+    #[test]
+    fn test_material_properties() {
+        let steel = Material::new("steel", 7).with_property("E", 210e9);
+        assert_eq!(steel.property::<f64>("E"), Some(210e9));
+        assert_eq!(steel.property::<f64>("nu"), None);
+    }
+
+    #[test]
+    fn test_material_assignment() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let table = MaterialTable::new().assign("My surface", Material::new("steel", 7));
+        assert_eq!(table.material_ids(&mesh).unwrap(), [7, 7]);
+    }
+
+    #[test]
+    fn test_invalid_material_tables() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let steel = Material::new("steel", 7);
+        let twice = MaterialTable::new()
+            .assign("My surface", steel.clone())
+            .assign((2, PhysicalTag(6)), steel.clone());
+        assert!(matches!(
+            twice.validate(&mesh),
+            Err(ParseError::MeshValidationError(_))
+        ));
+        let boundary = MaterialTable::new().assign((1, PhysicalTag(5)), steel);
+        assert!(boundary.validate(&mesh).is_err());
+        assert!(MaterialTable::new().validate(&mesh).is_err());
+    }
+}
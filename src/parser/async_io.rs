@@ -0,0 +1,60 @@
+//! Parsing without blocking an async runtime (`tokio` feature)
+
+use super::{parse_msh_internal, ParserOptions, SourceFile};
+use crate::error::Result;
+use crate::types::Mesh;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Parse a MSH file from a given path without blocking the calling task
+///
+/// The file is read with Tokio's async I/O and parsed on the blocking thread
+/// pool, so large files do not hold up the executor's worker threads. Compressed
+/// files are handled as by [`parse_msh_file`](crate::parse_msh_file).
+///
+/// # Errors
+/// Returns an error if the file cannot be read or is not a valid MSH file.
+pub async fn parse_msh_file_async<P: AsRef<Path>>(path: P) -> Result<Mesh> {
+    parse_msh_file_with_async(path, &ParserOptions::default()).await
+}
+
+/// Parse a MSH file from a given path using `options` without blocking the
+/// calling task, see [`parse_msh_file_async`]
+pub async fn parse_msh_file_with_async<P: AsRef<Path>>(
+    path: P,
+    options: &ParserOptions,
+) -> Result<Mesh> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).await?;
+
+    let options = options.clone();
+    tokio::task::spawn_blocking(move || {
+        let reader = SourceFile::from_bytes(bytes)?.to_line_reader();
+        parse_msh_internal(&mut reader.with_options(options))
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SINGLE_TRIANGLE;
+
+    #[tokio::test]
+    async fn test_async_parse() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("triangle.msh");
+        std::fs::write(&path, SINGLE_TRIANGLE).unwrap();
+        let mesh = parse_msh_file_async(&path).await.unwrap();
+        assert_eq!((mesh.num_nodes(), mesh.num_elements()), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn test_async_parse_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.msh");
+        assert!(parse_msh_file_async(&path).await.is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Transparent decompression of compressed MSH files
+//!
+//! Compressed inputs are recognized by their magic bytes rather than by their
+//! extension, so `.msh.gz`, `.msh.zst` and misnamed files all work. Each format
+//! needs its feature (`gzip` or `zstd`); without it a clear error is returned
+//! instead of an invalid UTF-8 one.
+
+use std::io;
+
+pub(crate) const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+pub(crate) const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// The decompressed content of gzip or zstd data, or `None` if `bytes` is
+/// not compressed
+pub(crate) fn decompress(bytes: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        gunzip(bytes).map(Some)
+    } else if bytes.starts_with(&ZSTD_MAGIC) {
+        unzstd(bytes).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gunzip(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gunzip(_bytes: &[u8]) -> io::Result<Vec<u8>> {
+    Err(missing_feature("gzip"))
+}
+
+#[cfg(feature = "zstd")]
+fn unzstd(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::stream::decode_all(bytes)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn unzstd(_bytes: &[u8]) -> io::Result<Vec<u8>> {
+    Err(missing_feature("zstd"))
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn missing_feature(feature: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "{} compressed input, enable the `{}` feature of gmsh-parser to read it",
+            feature, feature
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SINGLE_TRIANGLE;
+
+    #[test]
+    fn test_plain_input_is_unchanged() {
+        assert_eq!(decompress(SINGLE_TRIANGLE.as_bytes()).unwrap(), None);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_file_is_parsed() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(SINGLE_TRIANGLE.as_bytes()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let compressed = dir.path().join("triangle.msh.gz");
+        std::fs::write(&compressed, encoder.finish().unwrap()).unwrap();
+
+        let mesh = crate::parse_msh_file(&compressed).unwrap();
+        assert_eq!((mesh.num_nodes(), mesh.num_elements()), (3, 1));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_file_is_parsed() {
+        let compressed = zstd::stream::encode_all(SINGLE_TRIANGLE.as_bytes(), 0).unwrap();
+        assert_eq!(
+            decompress(&compressed).unwrap(),
+            Some(SINGLE_TRIANGLE.as_bytes().to_vec())
+        );
+    }
+}
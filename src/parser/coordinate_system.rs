@@ -0,0 +1,74 @@
+//! Parser for the crate-defined $CoordinateSystem section
+//!
+//! The section holds the quoted name of the frame, its length unit and the
+//! three rows of its transform, see [`CoordinateSystem`].
+
+use crate::error::Result;
+use crate::geometry::Affine3;
+use crate::types::{CoordinateSystem, Mesh};
+
+use super::LineReader;
+
+pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
+    let token_line = reader.read_token_line()?;
+    let name = token_line.iter().parse_quoted_string_to_line_end()?;
+
+    let token_line = reader.read_token_line()?;
+    let mut iter = token_line.iter();
+    let unit = iter.parse_length_unit("unit")?;
+    iter.expect_no_more()?;
+
+    let mut transform = Affine3::identity();
+    for (i, row) in transform.matrix.iter_mut().enumerate() {
+        let token_line = reader.read_token_line()?;
+        let mut iter = token_line.iter();
+        let values = iter.parse_floats(4, &format!("transform[{}]", i))?;
+        iter.expect_no_more()?;
+        row.copy_from_slice(&values);
+    }
+
+    reader.read_end_marker("CoordinateSystem")?;
+
+    mesh.coordinate_system = Some(CoordinateSystem {
+        name,
+        unit,
+        transform,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use crate::geometry::LengthUnit;
+
+    #[test]
+    fn test_parse_coordinate_system() {
+        let data = r#""left wing"
+mm
+1 0 0 100
+0 1 0 0
+0 0 1 -2.5
+$EndCoordinateSystem
+"#;
+
+        let mut reader = LineReader::new(SourceFile::new(data.into()));
+        let mut mesh = Mesh::dummy();
+        parse(&mut reader, &mut mesh).unwrap();
+
+        let frame = mesh.coordinate_system.unwrap();
+        assert_eq!(frame.name, "left wing");
+        assert_eq!(frame.unit, Some(LengthUnit::Millimetre));
+        assert_eq!(frame.transform.translation_part(), [100.0, 0.0, -2.5]);
+
+        let data = "\"a\"\nfurlong\n";
+        let mut reader = LineReader::new(SourceFile::new(data.into()));
+        match parse(&mut reader, &mut Mesh::dummy()) {
+            Err(crate::ParseError::InvalidData { message, .. }) => {
+                assert!(message.starts_with("unit must be a length unit"))
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+}
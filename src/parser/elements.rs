@@ -1,11 +1,13 @@
-use super::{LineReader, TokenLine};
-use crate::error::{ParseError, Result};
+use super::{capacity_hint, parse_section_header, ElementBlockHeader, LineReader, TokenLine};
+use crate::error::{ParseError, ParseWarning, Result, WarningKind};
 use crate::parser::token::TokenIter;
-use crate::types::element::{Element, ElementBlock};
-use crate::types::{ElementType, Mesh};
+use crate::types::element::{Element, ElementBlock, ElementBlockCompact};
+use crate::types::{ElementTag, ElementType, EntityTag, Mesh, NodeTag};
+use std::collections::HashSet;
 
 pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     let header_line = reader.read_token_line()?;
+    let header = parse_section_header(&mut header_line.iter(), "Element")?;
     let mut iter = header_line.iter();
 
     let num_entity_blocks = iter.parse_usize("numEntityBlocks")?;
@@ -13,70 +15,189 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     // Parse metadata and validate later (after parsing all blocks)
     let metadata_iter = iter;
 
+    // Entities whose blocks are kept, if filtered
+    let kept = reader
+        .options()
+        .block_filter
+        .as_ref()
+        .map(|filter| filter.selected(mesh));
+
     // Parse each entity block
+    let mut skipped = false;
     for _ in 0..num_entity_blocks {
-        let block = parse_element_block(reader)?;
-        mesh.element_blocks.push(block);
+        skipped |= !parse_element_block(reader, mesh, kept.as_ref())?;
+        reader.block_parsed()?;
     }
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("Elements")?;
+    reader.read_end_marker("Elements")?;
+    mesh.elements_header = Some(header);
+
+    // The header describes the whole section, not the kept blocks
+    if skipped {
+        return Ok(());
+    }
 
     // Validate parsed elements against metadata
-    validate_elements_metadata(&mesh.element_blocks, metadata_iter)?;
+    match validate_elements_metadata(mesh, metadata_iter) {
+        // With metadata validation disabled, mismatches become warnings
+        Err(ParseError::InvalidData { message, span, .. })
+            if !reader.options().validate_metadata || reader.is_lenient() =>
+        {
+            let warning = ParseWarning::at(message, span);
+            mesh.warnings
+                .push(warning.with_kind(WarningKind::MetadataMismatch));
+        }
+        result => result?,
+    }
 
     Ok(())
 }
 
-fn parse_element_block(reader: &mut LineReader) -> Result<ElementBlock> {
+/// Parse an element block into `mesh`, or skip it if its entity is not in
+/// `kept`, returning whether it was kept
+///
+/// In lenient mode, blocks of unknown types are skipped as well but count as
+/// kept, since they are reported as warnings.
+fn parse_element_block(
+    reader: &mut LineReader,
+    mesh: &mut Mesh,
+    kept: Option<&HashSet<(i32, EntityTag)>>,
+) -> Result<bool> {
+    let token_line = reader.read_token_line()?;
+    let header = match block_header(&token_line) {
+        Err(ParseError::InvalidElementType {
+            element_type, span, ..
+        }) if reader.is_lenient() => {
+            // The number of lines to skip follows the element type
+            let mut iter = token_line.iter();
+            iter.nth(2);
+            let num_elements = iter.parse_usize("numElementsInBlock")?;
+            for _ in 0..num_elements {
+                reader.read_token_line()?;
+            }
+            reader.warn(
+                ParseWarning::at(
+                    format!(
+                        "Skipped {} elements of unknown type {}",
+                        num_elements, element_type
+                    ),
+                    span,
+                )
+                .with_kind(WarningKind::UnknownElementType),
+            );
+            return Ok(true);
+        }
+        result => result?,
+    };
+    if kept.is_some_and(|kept| !kept.contains(&(header.entity_dim, header.entity_tag))) {
+        reader.skip_lines(header.num_elements)?;
+        return Ok(false);
+    }
+
+    let compact =
+        ElementBlockCompact::new(header.entity_dim, header.entity_tag, header.element_type);
+    if let Some(mut block) = compact.filter(|_| reader.options().compact_storage) {
+        block.tags.reserve(capacity_hint(header.num_elements));
+        block.connectivity.reserve(capacity_hint(
+            header.num_elements.saturating_mul(block.stride),
+        ));
+        for _ in 0..header.num_elements {
+            let tag = read_element(reader, header.element_type, &mut block.connectivity)?;
+            block.tags.push(tag);
+        }
+        block.position = mesh.element_blocks.len() + mesh.compact_element_blocks.len();
+        mesh.compact_element_blocks.push(block);
+        return Ok(true);
+    }
+
+    let mut elements = Vec::with_capacity(capacity_hint(header.num_elements));
+    for _ in 0..header.num_elements {
+        elements.push(parse_element(reader, header.element_type)?);
+    }
+
+    mesh.element_blocks.push(ElementBlock::new(
+        header.entity_dim,
+        header.entity_tag,
+        header.element_type,
+        elements,
+    ));
+    Ok(true)
+}
+
+/// Parse the `entityDim entityTag elementType numElementsInBlock` line of a block
+pub(crate) fn parse_block_header(reader: &mut LineReader) -> Result<ElementBlockHeader> {
     let token_line = reader.read_token_line()?;
+    block_header(&token_line)
+}
+
+fn block_header(token_line: &TokenLine) -> Result<ElementBlockHeader> {
     let mut iter = token_line.iter();
 
     let entity_dim = iter.parse_int("entityDim")?;
-    let entity_tag = iter.parse_int("entityTag")?;
+    let entity_tag = iter.parse_entity_tag("entityTag")?;
     let element_type = iter.parse_element_type("elementType")?;
-    let num_elements_in_block = iter.parse_usize("numElementsInBlock")?;
+    let num_elements = iter.parse_usize("numElementsInBlock")?;
     iter.expect_no_more()?;
 
-    let mut elements = Vec::with_capacity(num_elements_in_block);
+    Ok(ElementBlockHeader {
+        entity_dim,
+        entity_tag,
+        element_type,
+        num_elements,
+    })
+}
 
-    // Get the expected node count for this element type
-    let fixed_count = element_type.fixed_node_count();
+/// Parse the line of a single element of type `element_type`
+pub(crate) fn parse_element(reader: &mut LineReader, element_type: ElementType) -> Result<Element> {
+    let mut nodes = Vec::new();
+    let tag = read_element(reader, element_type, &mut nodes)?;
+    Ok(Element::new(tag, nodes))
+}
 
-    for _ in 0..num_elements_in_block {
-        let token_line = reader.read_token_line()?;
-        let mut iter = token_line.iter();
+/// Parse the line of a single element, appending its nodes to `nodes`
+pub(crate) fn read_element(
+    reader: &mut LineReader,
+    element_type: ElementType,
+    nodes: &mut Vec<NodeTag>,
+) -> Result<ElementTag> {
+    let token_line = reader.read_token_line()?;
+    let mut iter = token_line.iter();
 
-        let tag = iter.parse_usize("elementTag")?;
-        let nodes = parse_element_nodes(&mut iter, &token_line, tag, element_type, fixed_count)?;
+    let tag = iter.parse_element_tag("elementTag")?;
+    let fixed_count = element_type.fixed_node_count();
+    parse_element_nodes(
+        &mut iter,
+        &token_line,
+        tag,
+        element_type,
+        fixed_count,
+        nodes,
+    )?;
 
-        elements.push(Element::new(tag, nodes));
+    if let Some(source_map) = reader.source_map_mut() {
+        source_map.record_element(tag, token_line.span());
     }
 
-    Ok(ElementBlock::new(
-        entity_dim,
-        entity_tag,
-        element_type,
-        elements,
-    ))
+    Ok(tag)
 }
 
 /// Parse nodes for a single element with improved error messages
 fn parse_element_nodes(
     iter: &mut TokenIter,
     token_line: &TokenLine,
-    tag: usize,
+    tag: ElementTag,
     element_type: ElementType,
     fixed_count: Option<usize>,
-) -> Result<Vec<usize>> {
-    let mut nodes = Vec::new();
+    nodes: &mut Vec<NodeTag>,
+) -> Result<()> {
+    let first = nodes.len();
 
     match fixed_count {
         Some(count) => {
             // Fixed number of nodes
             nodes.reserve(count);
             for i in 0..count {
-                match iter.parse_usize("nodeTag") {
+                match iter.parse_node_tag("nodeTag") {
                     Ok(node) => nodes.push(node),
                     Err(ParseError::UnexpectedEndOfLine { .. }) => {
                         return Err(token_line.invalid_format(format!(
@@ -92,42 +213,42 @@ fn parse_element_nodes(
         None => {
             // Variable number of nodes (Polygon, Polyhedron, etc.)
             while iter.has_next() {
-                nodes.push(iter.parse_usize("nodeTag")?);
+                nodes.push(iter.parse_node_tag("nodeTag")?);
             }
 
             // Validate that at least one node is present
-            if nodes.is_empty() {
+            if nodes.len() == first {
                 return Err(token_line
                     .invalid_format(format!("Element {} ({:?}) has no nodes", tag, element_type)));
             }
         }
     }
 
-    Ok(nodes)
+    Ok(())
 }
 
 /// Validate parsed elements against metadata from the header
-fn validate_elements_metadata(
-    element_blocks: &[ElementBlock],
-    mut metadata_iter: TokenIter,
-) -> Result<()> {
+fn validate_elements_metadata(mesh: &Mesh, mut metadata_iter: TokenIter) -> Result<()> {
     // Parse metadata
     let num_elements_token = metadata_iter.peek_token()?;
     let expected_num_elements = metadata_iter.parse_usize("numElements")?;
 
     let min_element_tag_token = metadata_iter.peek_token()?;
-    let expected_min_element_tag = metadata_iter.parse_usize("minElementTag")?;
+    let expected_min_element_tag = metadata_iter.parse_element_tag("minElementTag")?;
 
     let max_element_tag_token = metadata_iter.peek_token()?;
-    let expected_max_element_tag = metadata_iter.parse_usize("maxElementTag")?;
+    let expected_max_element_tag = metadata_iter.parse_element_tag("maxElementTag")?;
 
     metadata_iter.expect_no_more()?;
 
+    let tags = || {
+        let expanded = mesh.element_blocks.iter().flat_map(|b| &b.elements);
+        let compact = mesh.compact_element_blocks.iter().flat_map(|b| &b.tags);
+        expanded.map(|e| e.tag).chain(compact.copied())
+    };
+
     // Count total elements
-    let actual_num_elements: usize = element_blocks
-        .iter()
-        .map(|block| block.elements.len())
-        .sum();
+    let actual_num_elements = tags().count();
 
     if actual_num_elements != expected_num_elements {
         return Err(ParseError::InvalidData {
@@ -141,20 +262,18 @@ fn validate_elements_metadata(
     }
 
     // Find min and max element tags
-    let mut actual_min_tag = usize::MAX;
-    let mut actual_max_tag = usize::MIN;
+    let mut actual_min_tag = ElementTag(u64::MAX);
+    let mut actual_max_tag = ElementTag(u64::MIN);
 
-    for block in element_blocks {
-        for element in &block.elements {
-            actual_min_tag = actual_min_tag.min(element.tag);
-            actual_max_tag = actual_max_tag.max(element.tag);
-        }
+    for tag in tags() {
+        actual_min_tag = actual_min_tag.min(tag);
+        actual_max_tag = actual_max_tag.max(tag);
     }
 
     // Handle case with no elements
     if actual_num_elements == 0 {
-        actual_min_tag = 0;
-        actual_max_tag = 0;
+        actual_min_tag = ElementTag(0);
+        actual_max_tag = ElementTag(0);
     }
 
     if actual_min_tag != expected_min_element_tag {
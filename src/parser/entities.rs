@@ -19,8 +19,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
             "Duplicate $Entities section found - this section should only appear once",
         ));
     }
-    mesh.entities = Some(Entities::new());
-    let entities = mesh.entities.as_mut().unwrap();
+    let entities = mesh.entities.insert(Entities::new());
 
     // Parse points
     for _ in 0..num_points {
@@ -46,8 +45,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         entities.volumes.push(volume);
     }
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("Entities")?;
+    reader.read_end_marker("Entities")?;
 
     Ok(())
 }
@@ -56,13 +54,13 @@ fn parse_point_entity(reader: &mut LineReader) -> Result<PointEntity> {
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
-    let tag = iter.parse_int("tag")?;
+    let tag = iter.parse_entity_tag("tag")?;
     let x = iter.parse_float("x")?;
     let y = iter.parse_float("y")?;
     let z = iter.parse_float("z")?;
 
     let num_physical_tags = iter.parse_usize("numPhysicalTags")?;
-    let physical_tags = iter.parse_ints(num_physical_tags, "physicalTag")?;
+    let physical_tags = iter.parse_physical_tags(num_physical_tags, "physicalTag")?;
 
     iter.expect_no_more()?;
 
@@ -79,7 +77,7 @@ fn parse_curve_entity(reader: &mut LineReader) -> Result<CurveEntity> {
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
-    let tag = iter.parse_int("tag")?;
+    let tag = iter.parse_entity_tag("tag")?;
     let min_x = iter.parse_float("minX")?;
     let min_y = iter.parse_float("minY")?;
     let min_z = iter.parse_float("minZ")?;
@@ -88,7 +86,7 @@ fn parse_curve_entity(reader: &mut LineReader) -> Result<CurveEntity> {
     let max_z = iter.parse_float("maxZ")?;
 
     let num_physical_tags = iter.parse_usize("numPhysicalTags")?;
-    let physical_tags: Vec<i32> = iter.parse_ints(num_physical_tags, "physicalTag")?;
+    let physical_tags = iter.parse_physical_tags(num_physical_tags, "physicalTag")?;
 
     let num_bounding_points = iter.parse_usize("numBoundingPoints")?;
     let bounding_points: Vec<i32> = iter.parse_ints(num_bounding_points, "boundingPoint")?;
@@ -112,7 +110,7 @@ fn parse_surface_entity(reader: &mut LineReader) -> Result<SurfaceEntity> {
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
-    let tag = iter.parse_int("tag")?;
+    let tag = iter.parse_entity_tag("tag")?;
     let min_x = iter.parse_float("minX")?;
     let min_y = iter.parse_float("minY")?;
     let min_z = iter.parse_float("minZ")?;
@@ -121,7 +119,7 @@ fn parse_surface_entity(reader: &mut LineReader) -> Result<SurfaceEntity> {
     let max_z = iter.parse_float("maxZ")?;
 
     let num_physical_tags = iter.parse_usize("numPhysicalTags")?;
-    let physical_tags: Vec<i32> = iter.parse_ints(num_physical_tags, "physicalTag")?;
+    let physical_tags = iter.parse_physical_tags(num_physical_tags, "physicalTag")?;
 
     let num_bounding_curves = iter.parse_usize("numBoundingCurves")?;
     let bounding_curves: Vec<i32> = iter.parse_ints(num_bounding_curves, "boundingCurve")?;
@@ -145,7 +143,7 @@ fn parse_volume_entity(reader: &mut LineReader) -> Result<VolumeEntity> {
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
-    let tag = iter.parse_int("tag")?;
+    let tag = iter.parse_entity_tag("tag")?;
     let min_x = iter.parse_float("minX")?;
     let min_y = iter.parse_float("minY")?;
     let min_z = iter.parse_float("minZ")?;
@@ -154,7 +152,7 @@ fn parse_volume_entity(reader: &mut LineReader) -> Result<VolumeEntity> {
     let max_z = iter.parse_float("maxZ")?;
 
     let num_physical_tags = iter.parse_usize("numPhysicalTags")?;
-    let physical_tags: Vec<i32> = iter.parse_ints(num_physical_tags, "physicalTag")?;
+    let physical_tags = iter.parse_physical_tags(num_physical_tags, "physicalTag")?;
 
     let num_bounding_surfaces = iter.parse_usize("numBoundingSurfaces")?;
     let bounding_surfaces: Vec<i32> = iter.parse_ints(num_bounding_surfaces, "boundingSurface")?;
@@ -17,7 +17,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let token_line = reader.read_token_line()?;
         let mut iter = token_line.iter();
 
-        let element_tag = iter.parse_usize("elementTag")?;
+        let element_tag = iter.parse_element_tag("elementTag")?;
         let partition_tag = iter.parse_int("partitionTag")?;
 
         let num_ghost_partitions = iter.parse_usize("numGhostPartitions")?;
@@ -32,8 +32,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         });
     }
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("GhostElements")?;
+    reader.read_end_marker("GhostElements")?;
 
     Ok(())
 }
@@ -0,0 +1,547 @@
+//! Two-pass parsing with bounded memory
+
+use super::{
+    elements, mesh_format, nodes, parse_section, parse_section_header, post_processing,
+    ElementBlockHeader, LineReader, NodeBlockHeader, SourceFile,
+};
+use crate::error::{ParseError, Result};
+use crate::types::element::ElementBlock;
+use crate::types::{ElementData, ElementNodeData, ElementTag, Mesh, NodeBlock, NodeData, NodeTag};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+/// A block of the `$Nodes` or `$Elements` section found by the scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedBlock<H> {
+    pub header: H,
+    /// Byte offset of the line following the block header
+    pub offset: u64,
+}
+
+/// A post-processing section found by the scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedSection {
+    /// Section name without the leading `$`, e.g. `"NodeData"`
+    pub name: String,
+    /// Byte offset of the line following the section start
+    pub offset: u64,
+}
+
+/// A MSH file whose node and element blocks are parsed on demand
+///
+/// The file is first scanned, recording where each node and element
+/// block and each post-processing section starts, without storing any node,
+/// element or data value. Blocks are then parsed on demand, either into the usual [`NodeBlock`] and [`ElementBlock`] or into
+/// buffers the caller allocated from the section headers:
+///
+/// ```no_run
+/// use gmsh_parser::parser::IndexedFile;
+/// use gmsh_parser::NodeTag;
+///
+/// let mut file = IndexedFile::open("huge.msh").unwrap();
+/// let total = file.metadata().nodes_header.as_ref().map_or(0, |h| h.count);
+/// let mut tags = vec![NodeTag(0); total];
+/// let mut coordinates = vec![0.0; 3 * total];
+/// let mut start = 0;
+/// for i in 0..file.node_blocks().len() {
+///     let end = start + file.node_blocks()[i].header.num_nodes;
+///     file.read_nodes_into(i, &mut tags[start..end], &mut coordinates[3 * start..3 * end])
+///         .unwrap();
+///     start = end;
+/// }
+/// ```
+///
+/// Memory use is bounded by the other sections, kept parsed in
+/// [`IndexedFile::metadata`], plus the text of the largest block or
+/// post-processing section, read again from the file for each request, plus
+/// whatever the caller keeps.
+pub struct IndexedFile<R> {
+    reader: R,
+    metadata: Mesh,
+    node_blocks: Vec<IndexedBlock<NodeBlockHeader>>,
+    element_blocks: Vec<IndexedBlock<ElementBlockHeader>>,
+    data_sections: Vec<IndexedSection>,
+}
+
+impl IndexedFile<BufReader<File>> {
+    /// Scan the file at `path`
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or a section is malformed.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: BufRead + Seek> IndexedFile<R> {
+    /// Scan `reader` from its current position to the end
+    ///
+    /// Every section other than `$Nodes`, `$Elements`, `$NodeData`,
+    /// `$ElementData` and `$ElementNodeData` is parsed into
+    /// [`IndexedFile::metadata`]. Block headers are checked, block contents are
+    /// only counted; the mesh is not validated.
+    ///
+    /// # Errors
+    /// Returns an error if reading fails or a section is malformed.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let start = reader.stream_position()?;
+        let mut scanner = Scanner {
+            reader: &mut reader,
+            offset: start,
+            line: String::new(),
+        };
+        let mut metadata_text = String::new();
+        let mut nodes_header = None;
+        let mut elements_header = None;
+        let mut node_blocks = Vec::new();
+        let mut element_blocks = Vec::new();
+        let mut data_sections = Vec::new();
+
+        while scanner.read_line()? {
+            match scanner.line.trim() {
+                "$Nodes" => {
+                    let mut header_line = scanner.next_line_reader()?;
+                    let token_line = header_line.read_token_line()?;
+                    let header = parse_section_header::<NodeTag>(&mut token_line.iter(), "Node")?;
+                    for _ in 0..header.num_entity_blocks {
+                        let header = nodes::parse_block_header(&mut scanner.next_line_reader()?)?;
+                        let offset = scanner.offset;
+                        // Tags, then coordinates
+                        scanner.skip_lines(header.num_nodes.saturating_mul(2))?;
+                        node_blocks.push(IndexedBlock { header, offset });
+                    }
+                    scanner.next_line_reader()?.read_end_marker("Nodes")?;
+                    nodes_header = Some(header);
+                }
+                "$Elements" => {
+                    let mut header_line = scanner.next_line_reader()?;
+                    let token_line = header_line.read_token_line()?;
+                    let header =
+                        parse_section_header::<ElementTag>(&mut token_line.iter(), "Element")?;
+                    for _ in 0..header.num_entity_blocks {
+                        let header =
+                            elements::parse_block_header(&mut scanner.next_line_reader()?)?;
+                        let offset = scanner.offset;
+                        scanner.skip_lines(header.num_elements)?;
+                        element_blocks.push(IndexedBlock { header, offset });
+                    }
+                    scanner.next_line_reader()?.read_end_marker("Elements")?;
+                    elements_header = Some(header);
+                }
+                section @ ("$NodeData" | "$ElementData" | "$ElementNodeData") => {
+                    let name = section[1..].to_string();
+                    let offset = scanner.offset;
+                    scanner.skip_section(&name)?;
+                    data_sections.push(IndexedSection { name, offset });
+                }
+                _ => {
+                    metadata_text.push_str(scanner.line.trim_end_matches(['\r', '\n']));
+                    metadata_text.push('\n');
+                }
+            }
+        }
+
+        let mut metadata = parse_metadata(SourceFile::new(metadata_text).to_line_reader())?;
+        metadata.nodes_header = nodes_header;
+        metadata.elements_header = elements_header;
+        Ok(Self {
+            reader,
+            metadata,
+            node_blocks,
+            element_blocks,
+            data_sections,
+        })
+    }
+
+    /// Everything in the file except nodes, elements and post-processing data
+    pub fn metadata(&self) -> &Mesh {
+        &self.metadata
+    }
+
+    pub fn node_blocks(&self) -> &[IndexedBlock<NodeBlockHeader>] {
+        &self.node_blocks
+    }
+
+    pub fn element_blocks(&self) -> &[IndexedBlock<ElementBlockHeader>] {
+        &self.element_blocks
+    }
+
+    /// The `$NodeData`, `$ElementData` and `$ElementNodeData` sections, in
+    /// file order
+    pub fn data_sections(&self) -> &[IndexedSection] {
+        &self.data_sections
+    }
+
+    /// Parse the data section `index`, which must be a `$NodeData` section
+    ///
+    /// # Errors
+    /// Returns [`ParseError::NotFound`] if there is no such `$NodeData`
+    /// section, or an error if reading fails or the section is malformed.
+    pub fn read_node_data(&mut self, index: usize) -> Result<NodeData> {
+        let mut mesh = self.read_data_section(index, "NodeData")?;
+        Ok(mesh.node_data.remove(0))
+    }
+
+    /// Parse the data section `index`, which must be an `$ElementData` section
+    ///
+    /// # Errors
+    /// As [`IndexedFile::read_node_data`].
+    pub fn read_element_data(&mut self, index: usize) -> Result<ElementData> {
+        let mut mesh = self.read_data_section(index, "ElementData")?;
+        Ok(mesh.element_data.remove(0))
+    }
+
+    /// Parse the data section `index`, which must be an `$ElementNodeData`
+    /// section
+    ///
+    /// # Errors
+    /// As [`IndexedFile::read_node_data`].
+    pub fn read_element_node_data(&mut self, index: usize) -> Result<ElementNodeData> {
+        let mut mesh = self.read_data_section(index, "ElementNodeData")?;
+        Ok(mesh.element_node_data.remove(0))
+    }
+
+    /// A mesh holding only the view of the data section `index`
+    fn read_data_section(&mut self, index: usize, name: &str) -> Result<Mesh> {
+        let section = self
+            .data_sections
+            .get(index)
+            .filter(|s| s.name == name)
+            .ok_or_else(|| ParseError::NotFound(format!("{} section {}", name, index)))?
+            .clone();
+
+        self.reader.seek(SeekFrom::Start(section.offset))?;
+        let mut scanner = Scanner {
+            reader: &mut self.reader,
+            offset: section.offset,
+            line: String::new(),
+        };
+        let mut chunk = String::new();
+        loop {
+            scanner.next_nonempty()?;
+            chunk.push_str(scanner.line.trim_end_matches(['\r', '\n']));
+            chunk.push('\n');
+            if scanner.line.trim().strip_prefix("$End") == Some(name) {
+                break;
+            }
+        }
+
+        let mut reader = SourceFile::new(chunk).to_line_reader();
+        let mut mesh = Mesh::new(self.metadata.format.clone());
+        match name {
+            "NodeData" => post_processing::parse_node_data(&mut reader, &mut mesh)?,
+            "ElementData" => post_processing::parse_element_data(&mut reader, &mut mesh)?,
+            _ => post_processing::parse_element_node_data(&mut reader, &mut mesh)?,
+        }
+        Ok(mesh)
+    }
+
+    /// Parse the node block `index`
+    ///
+    /// # Errors
+    /// Returns [`ParseError::NotFound`] if there is no such block, or an error
+    /// if reading fails or the block is malformed.
+    pub fn read_node_block(&mut self, index: usize) -> Result<NodeBlock> {
+        let block = block(&self.node_blocks, index, "Node")?.clone();
+        let mut reader = self.read_lines(block.offset, 2 * block.header.num_nodes)?;
+        let tags = nodes::parse_block_tags(&mut reader, &block.header)?;
+        let nodes = tags
+            .into_iter()
+            .map(|tag| nodes::parse_node(&mut reader, &block.header, tag))
+            .collect::<Result<_>>()?;
+        Ok(NodeBlock {
+            entity_dim: block.header.entity_dim,
+            entity_tag: block.header.entity_tag,
+            parametric: block.header.parametric,
+            nodes,
+        })
+    }
+
+    /// Parse the node block `index` into `tags` and `coordinates`, interleaved
+    /// as `x y z` per node
+    ///
+    /// Parametric coordinates are not kept.
+    ///
+    /// # Errors
+    /// As [`IndexedFile::read_node_block`], and if the buffers do not hold
+    /// exactly the nodes of the block.
+    pub fn read_nodes_into(
+        &mut self,
+        index: usize,
+        tags: &mut [NodeTag],
+        coordinates: &mut [f64],
+    ) -> Result<()> {
+        let block = block(&self.node_blocks, index, "Node")?.clone();
+        let n = block.header.num_nodes;
+        check_len("tags", tags.len(), n)?;
+        check_len("coordinates", coordinates.len(), 3 * n)?;
+
+        let mut reader = self.read_lines(block.offset, 2 * n)?;
+        let parsed = nodes::parse_block_tags(&mut reader, &block.header)?;
+        for (i, tag) in parsed.into_iter().enumerate() {
+            let node = nodes::parse_node(&mut reader, &block.header, tag)?;
+            tags[i] = tag;
+            coordinates[3 * i..3 * i + 3].copy_from_slice(&[node.x, node.y, node.z]);
+        }
+        Ok(())
+    }
+
+    /// Parse the element block `index`
+    ///
+    /// # Errors
+    /// Returns [`ParseError::NotFound`] if there is no such block, or an error
+    /// if reading fails or the block is malformed.
+    pub fn read_element_block(&mut self, index: usize) -> Result<ElementBlock> {
+        let block = block(&self.element_blocks, index, "Element")?.clone();
+        let header = &block.header;
+        let mut reader = self.read_lines(block.offset, header.num_elements)?;
+        let elements = (0..header.num_elements)
+            .map(|_| elements::parse_element(&mut reader, header.element_type))
+            .collect::<Result<_>>()?;
+        Ok(ElementBlock::new(
+            header.entity_dim,
+            header.entity_tag,
+            header.element_type,
+            elements,
+        ))
+    }
+
+    /// Parse the element block `index` into `tags` and `connectivity`, the
+    /// nodes of each element following each other
+    ///
+    /// # Errors
+    /// As [`IndexedFile::read_element_block`], if the element type has no fixed
+    /// node count, and if the buffers do not hold exactly the elements of the
+    /// block.
+    pub fn read_elements_into(
+        &mut self,
+        index: usize,
+        tags: &mut [ElementTag],
+        connectivity: &mut [NodeTag],
+    ) -> Result<()> {
+        let block = block(&self.element_blocks, index, "Element")?.clone();
+        let header = &block.header;
+        let stride = header.element_type.fixed_node_count().ok_or_else(|| {
+            invalid_input(format!(
+                "{:?} elements have no fixed node count",
+                header.element_type
+            ))
+        })?;
+        check_len("tags", tags.len(), header.num_elements)?;
+        check_len(
+            "connectivity",
+            connectivity.len(),
+            header.num_elements * stride,
+        )?;
+
+        let mut reader = self.read_lines(block.offset, header.num_elements)?;
+        let mut nodes = Vec::with_capacity(stride);
+        for (i, tag) in tags.iter_mut().enumerate() {
+            nodes.clear();
+            *tag = elements::read_element(&mut reader, header.element_type, &mut nodes)?;
+            connectivity[i * stride..(i + 1) * stride].copy_from_slice(&nodes);
+        }
+        Ok(())
+    }
+
+    /// The `count` non-empty lines from `offset`, ready to be parsed
+    fn read_lines(&mut self, offset: u64, count: usize) -> Result<LineReader> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut scanner = Scanner {
+            reader: &mut self.reader,
+            offset,
+            line: String::new(),
+        };
+        let mut chunk = String::new();
+        let mut read = 0;
+        while read < count {
+            scanner.next_nonempty()?;
+            chunk.push_str(&scanner.line);
+            if !chunk.ends_with('\n') {
+                chunk.push('\n');
+            }
+            read += 1;
+        }
+        Ok(SourceFile::new(chunk).to_line_reader())
+    }
+}
+
+/// Line-by-line reader keeping track of the byte offset
+struct Scanner<'a, R> {
+    reader: &'a mut R,
+    /// Offset of the next line
+    offset: u64,
+    /// Last line read, terminator included
+    line: String,
+}
+
+impl<R: BufRead> Scanner<'_, R> {
+    /// Read the next line, or return `false` at the end of the input
+    fn read_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        let read = self.reader.read_line(&mut self.line)?;
+        self.offset += read as u64;
+        Ok(read > 0)
+    }
+
+    fn next_nonempty(&mut self) -> Result<()> {
+        loop {
+            if !self.read_line()? {
+                return Err(ParseError::UnexpectedEof);
+            }
+            if !self.line.trim().is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// The next non-empty line, ready to be parsed
+    fn next_line_reader(&mut self) -> Result<LineReader> {
+        self.next_nonempty()?;
+        Ok(SourceFile::new(self.line.clone()).to_line_reader())
+    }
+
+    fn skip_lines(&mut self, count: usize) -> Result<()> {
+        for _ in 0..count {
+            self.next_nonempty()?;
+        }
+        Ok(())
+    }
+
+    /// Skip the lines up to and including `$End{name}`
+    fn skip_section(&mut self, name: &str) -> Result<()> {
+        loop {
+            self.next_nonempty()?;
+            if self.line.trim().strip_prefix("$End") == Some(name) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Parse `$MeshFormat` and the sections following it, without validating
+fn parse_metadata(mut reader: LineReader) -> Result<Mesh> {
+    let mut mesh = mesh_format::parse_mesh(&mut reader)?;
+    loop {
+        let token_line = match reader.read_token_line() {
+            Ok(line) => line,
+            Err(ParseError::UnexpectedEof) => break,
+            Err(e) => return Err(e),
+        };
+        let first_token = token_line.iter().peek_token()?;
+        parse_section(&mut reader, first_token, &mut mesh)?;
+    }
+    mesh.warnings.extend(reader.take_warnings());
+    Ok(mesh)
+}
+
+fn block<'a, H>(
+    blocks: &'a [IndexedBlock<H>],
+    index: usize,
+    kind: &str,
+) -> Result<&'a IndexedBlock<H>> {
+    blocks
+        .get(index)
+        .ok_or_else(|| ParseError::NotFound(format!("{} block {}", kind, index)))
+}
+
+fn check_len(name: &str, len: usize, expected: usize) -> Result<()> {
+    if len == expected {
+        Ok(())
+    } else {
+        Err(invalid_input(format!(
+            "`{}` holds {} values, the block needs {}",
+            name, len, expected
+        )))
+    }
+}
+
+fn invalid_input(message: String) -> ParseError {
+    io::Error::new(io::ErrorKind::InvalidInput, message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::NAMED_SQUARE_ON_CURVES;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_open_scans_blocks_and_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("square.msh");
+        std::fs::write(&path, NAMED_SQUARE_ON_CURVES).unwrap();
+        let file = IndexedFile::open(&path).unwrap();
+        assert_eq!(file.node_blocks().len(), 3);
+        assert_eq!(file.element_blocks().len(), 4);
+        assert_eq!(file.metadata().physical_names.len(), 2);
+        assert_eq!(file.metadata().num_nodes(), 0);
+    }
+
+    #[test]
+    fn test_read_blocks() {
+        let mut file = IndexedFile::new(Cursor::new(NAMED_SQUARE_ON_CURVES)).unwrap();
+        let block = file.read_element_block(3).unwrap();
+        let elements: Vec<_> = block
+            .elements
+            .iter()
+            .map(|e| (e.tag.0, &e.nodes[..]))
+            .collect();
+        assert_eq!(
+            elements,
+            [
+                (4, &[NodeTag(1), NodeTag(2), NodeTag(3)][..]),
+                (5, &[NodeTag(1), NodeTag(3), NodeTag(4)][..]),
+            ]
+        );
+        let nodes = file.read_node_block(1).unwrap().nodes;
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(
+            (nodes[0].tag, [nodes[0].x, nodes[0].y]),
+            (NodeTag(3), [1.0, 1.0])
+        );
+        assert!(file.read_node_block(3).is_err());
+    }
+
+    #[test]
+    fn test_read_into_buffers() {
+        let mut file = IndexedFile::new(Cursor::new(NAMED_SQUARE_ON_CURVES)).unwrap();
+        let mut tags = vec![NodeTag(0); 2];
+        let mut coordinates = vec![0.0; 6];
+        file.read_nodes_into(0, &mut tags, &mut coordinates)
+            .unwrap();
+        assert_eq!(tags, [NodeTag(1), NodeTag(2)]);
+        assert_eq!(coordinates, [0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        assert!(file.read_nodes_into(0, &mut tags, &mut []).is_err());
+
+        let mut tags = vec![ElementTag(0); 2];
+        let mut connectivity = vec![NodeTag(0); 6];
+        file.read_elements_into(3, &mut tags, &mut connectivity)
+            .unwrap();
+        assert_eq!(tags, [ElementTag(4), ElementTag(5)]);
+        let expected = [1, 2, 3, 1, 3, 4].map(NodeTag);
+        assert_eq!(connectivity, expected);
+    }
+
+    #[test]
+    fn test_data_sections_are_read_on_demand() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $NodeData\n1\n\"T\"\n1\n0.0\n3\n0\n1\n2\n1 10\n2 20\n$EndNodeData\n\
+            $ElementData\n1\n\"P\"\n1\n0.0\n3\n0\n1\n1\n1 5\n$EndElementData\n";
+        let mut file = IndexedFile::new(std::io::Cursor::new(content)).unwrap();
+        assert!(file.metadata().node_data.is_empty());
+        let names: Vec<_> = file.data_sections().iter().map(|s| &s.name[..]).collect();
+        assert_eq!(names, ["NodeData", "ElementData"]);
+
+        let view = file.read_element_data(1).unwrap();
+        assert_eq!(view.data, [(ElementTag(1), vec![5.0])]);
+        let view = file.read_node_data(0).unwrap();
+        assert_eq!(view.string_tags, ["T"]);
+        assert_eq!(view.data[1], (NodeTag(2), vec![20.0]));
+        assert!(matches!(
+            file.read_node_data(1),
+            Err(ParseError::NotFound(_))
+        ));
+    }
+}
@@ -3,16 +3,20 @@
 use crate::error::Result;
 use crate::types::{ElementTopologyInterpolation, InterpolationMatrix, InterpolationScheme, Mesh};
 
-use super::LineReader;
+use super::{capacity_hint, LineReader};
 
 pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
-    // Read scheme name (all tokens on the line combined)
+    // Read scheme name (all tokens on the line combined), quoted as Gmsh writes it
     let token_line = reader.read_token_line()?;
     let name = token_line
         .iter()
-        .map(|t| t.value.as_str())
+        .map(|t| t.value)
         .collect::<Vec<_>>()
         .join(" ");
+    let name = match name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+        Some(unquoted) => unquoted.to_string(),
+        None => name,
+    };
 
     // Read number of element topologies
     let token_line = reader.read_token_line()?;
@@ -20,7 +24,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
 
     let num_element_topologies = iter.parse_usize("numElementTopologies")?;
 
-    let mut topologies = Vec::with_capacity(num_element_topologies);
+    let mut topologies = Vec::with_capacity(capacity_hint(num_element_topologies));
 
     for _ in 0..num_element_topologies {
         // Read element topology ID
@@ -37,7 +41,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let num_interpolation_matrices = iter.parse_usize("numInterpolationMatrices")?;
         iter.expect_no_more()?;
 
-        let mut matrices = Vec::with_capacity(num_interpolation_matrices);
+        let mut matrices = Vec::with_capacity(capacity_hint(num_interpolation_matrices));
 
         for _ in 0..num_interpolation_matrices {
             // Read matrix dimensions and values (all on the same line)
@@ -48,7 +52,9 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
             let num_columns = iter.parse_usize("numColumns")?;
 
             // Read matrix values (row by row)
-            let total_values = num_rows * num_columns;
+            let total_values = num_rows
+                .checked_mul(num_columns)
+                .ok_or_else(|| token_line.invalid_format("Matrix size overflows"))?;
             let values = iter.parse_floats(total_values, "matrixValue")?;
             iter.expect_no_more()?;
 
@@ -68,8 +74,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     mesh.interpolation_schemes
         .push(InterpolationScheme { name, topologies });
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("InterpolationScheme")?;
+    reader.read_end_marker("InterpolationScheme")?;
 
     Ok(())
 }
@@ -1,10 +1,30 @@
 use super::LineReader;
-use crate::error::{ParseError, Result};
-use crate::types::{FileType, MeshFormat};
+use crate::error::{ParseError, ParseWarning, Result};
+use crate::types::{FileType, Mesh, MeshFormat};
+
+/// Values of `data_size` written by Gmsh and single-precision writers
+///
+/// ASCII files do not depend on it, so other values only produce a warning.
+pub const SUPPORTED_DATA_SIZES: [i32; 2] = [4, 8];
 
 /// Parse $MeshFormat section and return MeshFormat
 /// This function expects the reader to be positioned at the line containing "$MeshFormat"
 pub fn parse(reader: &mut LineReader) -> Result<MeshFormat> {
+    parse_with_warning(reader).map(|(format, _)| format)
+}
+
+/// Parse $MeshFormat section and return an empty mesh with that format
+///
+/// An unusual data size is reported in [`Mesh::warnings`] in every mode, not
+/// only in lenient mode, as it does not stop an ASCII file from being read.
+pub fn parse_mesh(reader: &mut LineReader) -> Result<Mesh> {
+    let (format, warning) = parse_with_warning(reader)?;
+    let mut mesh = Mesh::new(format);
+    mesh.warnings.extend(warning);
+    Ok(mesh)
+}
+
+fn parse_with_warning(reader: &mut LineReader) -> Result<(MeshFormat, Option<ParseWarning>)> {
     // Verify $MeshFormat header
     let token_line = reader.read_token_line()?;
     token_line.expect_section_start("MeshFormat")?;
@@ -15,6 +35,7 @@ pub fn parse(reader: &mut LineReader) -> Result<MeshFormat> {
 
     let version = iter.parse_version()?;
     let file_type = iter.parse_file_type("file_type")?;
+    let data_size_token = iter.peek_token()?;
     let data_size = iter.parse_int("data_size")?;
 
     iter.expect_no_more()?;
@@ -33,11 +54,19 @@ pub fn parse(reader: &mut LineReader) -> Result<MeshFormat> {
         return Err(ParseError::UnsupportedFileType { file_type });
     }
 
+    // Single-precision writers use 4, everything else 8; ASCII values are
+    // read as text whatever the declared size
+    let warning = (!SUPPORTED_DATA_SIZES.contains(&data_size)).then(|| {
+        ParseWarning::at(
+            format!("Unusual data size {} (expected 4 or 8)", data_size),
+            data_size_token.span,
+        )
+    });
+
     // Verify $EndMeshFormat
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("MeshFormat")?;
+    reader.read_end_marker("MeshFormat")?;
 
-    Ok(MeshFormat::new(version, file_type, data_size))
+    Ok((MeshFormat::new(version, file_type, data_size), warning))
 }
 
 #[cfg(test)]
@@ -82,4 +111,17 @@ mod tests {
             Err(ParseError::UnsupportedFileType { .. })
         ));
     }
+
+    #[test]
+    fn test_data_size() {
+        let data = "$MeshFormat\n4.1 0 4\n$EndMeshFormat\n";
+        let mut reader = LineReader::new(SourceFile::new(data.into()));
+        assert_eq!(parse(&mut reader).unwrap().data_size, 4);
+
+        let data = "$MeshFormat\n4.1 0 2\n$EndMeshFormat\n";
+        let mesh = crate::parse_msh(data).unwrap();
+        assert_eq!(mesh.format.data_size, 2);
+        assert_eq!(mesh.warnings.len(), 1);
+        assert!(mesh.warnings[0].message.starts_with("Unusual data size 2"));
+    }
 }
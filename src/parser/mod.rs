@@ -1,8 +1,25 @@
+// Malformed input must surface as a ParseError, never abort the host application
+#![cfg_attr(
+    not(test),
+    deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)
+)]
+
 // Core parsing infrastructure
+#[cfg(feature = "tokio")]
+mod async_io;
+pub(crate) mod compression;
+mod indexed;
+mod options;
+mod probe;
+mod progress;
 mod reader;
+mod sink;
+pub mod source_map;
+mod streaming;
 mod token;
 
 // Section-specific parsers
+pub mod coordinate_system;
 pub mod elements;
 pub mod entities;
 pub mod ghost_elements;
@@ -14,17 +31,33 @@ pub mod partitioned_entities;
 pub mod periodic;
 pub mod physical_names;
 pub mod post_processing;
+pub mod sets;
 
 // Re-exports for public API
+#[cfg(feature = "tokio")]
+pub use async_io::{parse_msh_file_async, parse_msh_file_with_async};
+pub use indexed::{IndexedBlock, IndexedFile, IndexedSection};
+pub use options::{BlockFilter, ParserOptions, SectionFilter};
+pub use probe::{probe_msh_file, probe_msh_reader, MshInfo};
+pub use progress::{CancellationToken, ParseProgress, ProgressCallback};
 pub use reader::{LineReader, SourceFile};
-pub use token::{Span, Token, TokenLine};
+pub use sink::{parse_elements_into, parse_msh_file_into, parse_nodes_into, ElementSink, NodeSink};
+pub use source_map::{Located, SourceMap, Spanned, WithSpans};
+pub use streaming::{ElementBlockHeader, Event, NodeBlockHeader, StreamingParser};
+pub use token::{Span, Token, TokenLine, TokenRef};
 
+use std::collections::HashSet;
+use std::io::Read;
 use std::path::Path;
 
-use crate::error::{ParseError, ParseWarning, Result};
-use crate::types::Mesh;
+use crate::error::{ParseError, ParseWarning, Result, WarningKind};
+use crate::types::{Mesh, RawSection, SectionHeader};
+use token::TokenIter;
 
 /// Parse a MSH file from a given path
+///
+/// Gzip and zstd compressed files, e.g. `.msh.gz`, are decompressed when the
+/// `gzip` or `zstd` feature is enabled.
 pub fn parse_msh_file<P: AsRef<Path>>(path: P) -> Result<Mesh> {
     let mut line_reader = SourceFile::from_path(&path)?.to_line_reader();
     parse_msh_internal(&mut line_reader)
@@ -36,11 +69,123 @@ pub fn parse_msh(content: impl AsRef<str>) -> Result<Mesh> {
     parse_msh_internal(&mut line_reader)
 }
 
+/// Parse a MSH file from a given path using `options`
+pub fn parse_msh_file_with<P: AsRef<Path>>(path: P, options: &ParserOptions) -> Result<Mesh> {
+    let reader = SourceFile::from_path(&path)?.to_line_reader();
+    parse_msh_internal(&mut reader.with_options(options.clone()))
+}
+
+/// Parse MSH data from a string content using `options`
+pub fn parse_msh_with(content: impl AsRef<str>, options: &ParserOptions) -> Result<Mesh> {
+    let reader = SourceFile::new(content.as_ref().to_string()).to_line_reader();
+    parse_msh_internal(&mut reader.with_options(options.clone()))
+}
+
+/// Parse MSH data from any reader, e.g. stdin, a socket or a decompressor
+///
+/// The whole input is read before parsing starts.
+///
+/// # Errors
+/// Returns an error if reading fails, the input is not valid UTF-8, or it is
+/// not a valid MSH file.
+pub fn parse_msh_reader<R: Read>(mut reader: R) -> Result<Mesh> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    let mut line_reader = SourceFile::new(content).to_line_reader();
+    parse_msh_internal(&mut line_reader)
+}
+
+/// Parse MSH data from bytes, e.g. a file uploaded in a browser
+///
+/// Gzip and zstd data is decompressed as by [`parse_msh_file`]. Nothing is
+/// read from the filesystem, so this works on `wasm32-unknown-unknown`.
+///
+/// # Errors
+/// Returns an error if the data is not valid UTF-8 (see
+/// [`ParserOptions::lossy_utf8`]) or not a valid MSH file.
+pub fn parse_msh_bytes(bytes: &[u8]) -> Result<Mesh> {
+    parse_msh_bytes_with(bytes, &ParserOptions::default())
+}
+
+/// Parse MSH data from bytes using `options`, see [`parse_msh_bytes`]
+pub fn parse_msh_bytes_with(bytes: &[u8], options: &ParserOptions) -> Result<Mesh> {
+    let decompressed = compression::decompress(bytes)?;
+    let bytes = decompressed.as_deref().unwrap_or(bytes);
+    let (content, invalid_at) = match std::str::from_utf8(bytes) {
+        Ok(content) => (content.to_string(), None),
+        Err(e) if options.lossy_utf8 => (
+            String::from_utf8_lossy(bytes).into_owned(),
+            Some(e.valid_up_to()),
+        ),
+        Err(e) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid UTF-8 at byte {}", e.valid_up_to()),
+            )
+            .into())
+        }
+    };
+    let reader = SourceFile::new(content).to_line_reader();
+    let mut mesh = parse_msh_internal(&mut reader.with_options(options.clone()))?;
+    if let Some(offset) = invalid_at {
+        mesh.warnings.insert(
+            0,
+            ParseWarning::new(format!(
+                "Replaced invalid UTF-8, first found at byte {}",
+                offset
+            ))
+            .with_kind(WarningKind::InvalidEncoding),
+        );
+        if let Some(max_warnings) = options.max_warnings {
+            mesh.warnings.truncate(max_warnings);
+        }
+    }
+    Ok(mesh)
+}
+
+/// Parse a MSH file, recording where each node and element was defined
+///
+/// See [`SourceMap`] for reporting diagnostics against the file.
+pub fn parse_msh_file_with_source_map<P: AsRef<Path>>(path: P) -> Result<(Mesh, SourceMap)> {
+    let line_reader = SourceFile::from_path(&path)?.to_line_reader();
+    parse_with_source_map(line_reader)
+}
+
+/// Parse MSH data from a string, recording where each node and element was defined
+pub fn parse_msh_with_source_map(content: impl AsRef<str>) -> Result<(Mesh, SourceMap)> {
+    let line_reader = SourceFile::new(content.as_ref().to_string()).to_line_reader();
+    parse_with_source_map(line_reader)
+}
+
+fn parse_with_source_map(line_reader: LineReader) -> Result<(Mesh, SourceMap)> {
+    let mut line_reader = line_reader.with_source_map();
+    let mesh = parse_msh_internal(&mut line_reader)?;
+    Ok((mesh, line_reader.take_source_map().unwrap_or_default()))
+}
+
+/// Parse only `$MeshFormat` and the `$Nodes` section of a MSH file
+///
+/// Shorthand for [`parse_msh_file_with`] and a [`SectionFilter`] of `$Nodes`,
+/// for tools needing only the node coordinates; see
+/// [`ParserOptions::sections`].
+pub fn parse_nodes_only<P: AsRef<Path>>(path: P) -> Result<Mesh> {
+    let sections = SectionFilter::default().section("Nodes");
+    parse_msh_file_with(path, &ParserOptions::default().sections(sections))
+}
+
+/// Parse only `$MeshFormat` and the `$Elements` section of a MSH file
+///
+/// The counterpart of [`parse_nodes_only`] for tools needing only the
+/// connectivity.
+pub fn parse_elements_only<P: AsRef<Path>>(path: P) -> Result<Mesh> {
+    let sections = SectionFilter::default().section("Elements");
+    parse_msh_file_with(path, &ParserOptions::default().sections(sections))
+}
+
 /// Internal parsing function that works with a LineReader
 fn parse_msh_internal(line_reader: &mut LineReader) -> Result<Mesh> {
     // Parse $MeshFormat section first (required)
-    let format = mesh_format::parse(line_reader)?;
-    let mut mesh = Mesh::new(format);
+    let mut mesh = mesh_format::parse_mesh(line_reader)?;
 
     // Parse remaining sections
     loop {
@@ -51,84 +196,441 @@ fn parse_msh_internal(line_reader: &mut LineReader) -> Result<Mesh> {
         };
 
         let first_token = token_line.iter().peek_token()?;
+        line_reader.begin_section(first_token.value);
+        line_reader.check_cancelled()?;
+        parse_section(line_reader, first_token, &mut mesh)?;
+        mesh.warnings.extend(line_reader.take_warnings());
+    }
+    line_reader.finish_progress();
 
-        match first_token.value.as_str() {
-            "$MeshFormat" => {
-                return Err(ParseError::InvalidData {
-                    message: "$MeshFormat section appears more than once".to_string(),
-                    span: first_token.span.to_source_span(),
-                    msh_content: first_token.source.clone(),
-                });
-            }
-            "$PhysicalNames" => {
-                physical_names::parse(line_reader, &mut mesh)?;
-            }
-            "$Entities" => {
-                entities::parse(line_reader, &mut mesh)?;
-            }
-            "$PartitionedEntities" => {
-                partitioned_entities::parse(line_reader, &mut mesh)?;
-            }
-            "$Nodes" => {
-                nodes::parse(line_reader, &mut mesh)?;
-            }
-            "$Elements" => {
-                elements::parse(line_reader, &mut mesh)?;
-            }
-            "$Periodic" => {
-                periodic::parse(line_reader, &mut mesh)?;
-            }
-            "$GhostElements" => {
-                ghost_elements::parse(line_reader, &mut mesh)?;
-            }
-            "$Parametrizations" => {
-                parametrizations::parse(line_reader, &mut mesh)?;
-            }
-            "$NodeData" => {
-                post_processing::parse_node_data(line_reader, &mut mesh)?;
-            }
-            "$ElementData" => {
-                post_processing::parse_element_data(line_reader, &mut mesh)?;
-            }
-            "$ElementNodeData" => {
-                post_processing::parse_element_node_data(line_reader, &mut mesh)?;
-            }
-            "$InterpolationScheme" => {
-                interpolation_scheme::parse(line_reader, &mut mesh)?;
-            }
-            _ if first_token.value.starts_with('$') && !first_token.value.starts_with("$End") => {
-                // Unknown section - skip it and add warning
-                let warning = ParseWarning::new(format!("Skipping unknown section: {}", first_token.value));
-                mesh.warnings.push(warning);
-                skip_section(line_reader, &first_token.value)?;
-            }
-            _ => {
-                // Unexpected content outside of sections - add warning
-                let warning = ParseWarning::new(format!(
+    if let Some(max_warnings) = line_reader.options().max_warnings {
+        mesh.warnings.truncate(max_warnings);
+    }
+    if line_reader.options().block_filter.is_some() {
+        restrict_sets(&mut mesh);
+    }
+    mesh.recount();
+
+    // Validate mesh consistency, unless sections were left out
+    if line_reader.options().sections.is_none() {
+        mesh.validate()?;
+    }
+
+    Ok(mesh)
+}
+
+/// Drop the members of node and element sets whose blocks were skipped by a
+/// [`BlockFilter`]
+fn restrict_sets(mesh: &mut Mesh) {
+    let nodes: HashSet<_> = mesh
+        .node_blocks
+        .iter()
+        .flat_map(|b| b.nodes.iter().map(|n| n.tag))
+        .collect();
+    let elements: HashSet<_> = mesh
+        .element_blocks
+        .iter()
+        .flat_map(|b| b.elements.iter().map(|e| e.tag))
+        .chain(
+            mesh.compact_element_blocks
+                .iter()
+                .flat_map(|b| b.tags.iter().copied()),
+        )
+        .collect();
+    for set in &mut mesh.node_sets {
+        set.tags.retain(|tag| nodes.contains(tag));
+    }
+    for set in &mut mesh.element_sets {
+        set.tags.retain(|tag| elements.contains(tag));
+    }
+}
+
+/// Parse the section starting with `first_token` into `mesh`
+///
+/// Called with the reader positioned right after the section header line.
+fn parse_section(
+    line_reader: &mut LineReader,
+    first_token: TokenRef,
+    mesh: &mut Mesh,
+) -> Result<()> {
+    let options = line_reader.options();
+    let skipped = match first_token.value {
+        "$Parametrizations" => options.skip_parametrizations,
+        "$NodeData" | "$ElementData" | "$ElementNodeData" | "$InterpolationScheme" => {
+            options.skip_post_processing_sections
+        }
+        _ => false,
+    };
+    let name = first_token.value;
+    let filtered = name.starts_with('$')
+        && !name.starts_with("$End")
+        && options
+            .sections
+            .as_ref()
+            .is_some_and(|sections| !sections.contains(name));
+    if skipped || filtered {
+        return skip_section(line_reader, first_token.value);
+    }
+
+    match first_token.value {
+        "$MeshFormat" => {
+            return Err(ParseError::InvalidData {
+                message: "$MeshFormat section appears more than once".to_string(),
+                span: first_token.span.to_source_span(),
+                msh_content: first_token.source.clone(),
+            });
+        }
+        "$PhysicalNames" => {
+            physical_names::parse(line_reader, mesh)?;
+        }
+        "$Entities" => {
+            entities::parse(line_reader, mesh)?;
+        }
+        "$PartitionedEntities" => {
+            partitioned_entities::parse(line_reader, mesh)?;
+        }
+        "$Nodes" => {
+            nodes::parse(line_reader, mesh)?;
+        }
+        "$Elements" => {
+            elements::parse(line_reader, mesh)?;
+        }
+        "$Periodic" => {
+            periodic::parse(line_reader, mesh)?;
+        }
+        "$GhostElements" => {
+            ghost_elements::parse(line_reader, mesh)?;
+        }
+        "$Parametrizations" => {
+            parametrizations::parse(line_reader, mesh)?;
+        }
+        "$NodeData" => {
+            post_processing::parse_node_data(line_reader, mesh)?;
+        }
+        "$ElementData" => {
+            post_processing::parse_element_data(line_reader, mesh)?;
+        }
+        "$ElementNodeData" => {
+            post_processing::parse_element_node_data(line_reader, mesh)?;
+        }
+        "$InterpolationScheme" => {
+            interpolation_scheme::parse(line_reader, mesh)?;
+        }
+        "$NodeSets" => {
+            sets::parse_node_sets(line_reader, mesh)?;
+        }
+        "$ElementSets" => {
+            sets::parse_element_sets(line_reader, mesh)?;
+        }
+        "$CoordinateSystem" => {
+            coordinate_system::parse(line_reader, mesh)?;
+        }
+        _ if line_reader.options().strict_mode => {
+            let message = if first_token.value.starts_with('$') {
+                format!("Unknown section: {}", first_token.value)
+            } else {
+                format!(
                     "Unexpected content outside of sections: {}",
                     first_token.value
-                ));
-                mesh.warnings.push(warning);
-            }
+                )
+            };
+            return Err(ParseError::InvalidData {
+                message,
+                span: first_token.span.to_source_span(),
+                msh_content: first_token.source.clone(),
+            });
+        }
+        _ if first_token.value.starts_with('$') && !first_token.value.starts_with("$End") => {
+            // Unknown section - keep its text and add a warning
+            let name = &first_token.value[1..];
+            let (lines, end) = line_reader.read_raw_lines(name)?;
+            let span = Span::new(first_token.span.offset, end - first_token.span.offset);
+            mesh.warnings.push(
+                ParseWarning::at(
+                    format!("Unknown section kept as raw text: {}", first_token.value),
+                    first_token.span,
+                )
+                .with_kind(WarningKind::UnknownSection),
+            );
+            mesh.raw_sections.push(RawSection {
+                name: name.to_string(),
+                span,
+                lines,
+            });
+        }
+        _ => {
+            // Unexpected content outside of sections - add warning
+            let warning = ParseWarning::new(format!(
+                "Unexpected content outside of sections: {}",
+                first_token.value
+            ));
+            mesh.warnings
+                .push(warning.with_kind(WarningKind::UnexpectedContent));
         }
     }
+    Ok(())
+}
 
-    // Validate mesh consistency
-    mesh.validate()?;
+/// Largest number of items reserved up front from a count read from the file
+///
+/// Counts in headers are untrusted: a corrupted `numNodesInBlock` must end in a
+/// parse error once the data runs out, not in an allocation failure.
+const MAX_PREALLOCATION: usize = 1 << 16;
 
-    Ok(mesh)
+/// Capacity to reserve for `count` items announced by the file
+pub(crate) fn capacity_hint(count: usize) -> usize {
+    count.min(MAX_PREALLOCATION)
+}
+
+/// Parse the `numEntityBlocks numItems minTag maxTag` line of `$Nodes` or
+/// `$Elements`, where `item` is `Node` or `Element`
+///
+/// Extra tokens are left for the caller to report.
+fn parse_section_header<T: From<u64>>(
+    iter: &mut TokenIter,
+    item: &str,
+) -> Result<SectionHeader<T>> {
+    Ok(SectionHeader {
+        num_entity_blocks: iter.parse_usize("numEntityBlocks")?,
+        count: iter.parse_usize(&format!("num{}s", item))?,
+        min_tag: T::from(iter.parse_unsigned(&format!("min{}Tag", item))?),
+        max_tag: T::from(iter.parse_unsigned(&format!("max{}Tag", item))?),
+    })
 }
 
-/// Skip an unknown section
+/// Skip a section without tokenizing it
 fn skip_section(reader: &mut LineReader, section_name: &str) -> Result<()> {
-    let end_marker = format!("$End{}", &section_name[1..]);
+    reader.skip_raw_section(&section_name[1..])
+}
 
-    loop {
-        let token_line = reader.read_token_line()?;
-        let first_token = token_line.iter().peek_token()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::NAMED_SQUARE_ON_CURVES;
 
-        if first_token.value == end_marker {
-            return Ok(());
+    #[test]
+    fn test_parse_from_reader() {
+        let mesh =
+            parse_msh_reader(std::io::BufReader::new(NAMED_SQUARE_ON_CURVES.as_bytes())).unwrap();
+        assert_eq!(mesh.num_nodes(), 4);
+        assert_eq!(mesh.num_elements(), 5);
+    }
+
+    #[test]
+    fn test_reader_rejects_invalid_utf8() {
+        let invalid_utf8: &[u8] = b"$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\xff\n";
+        assert!(matches!(
+            parse_msh_reader(invalid_utf8),
+            Err(ParseError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parser_options() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 2\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+            $Custom\n1\n$EndCustom\n$Other\n$EndOther\n";
+        assert!(parse_msh(content).is_err());
+
+        let lenient = ParserOptions::default().validate_metadata(false);
+        let mesh = parse_msh_with(content, &lenient).unwrap();
+        assert_eq!(mesh.node_blocks[0].nodes.len(), 2);
+        assert_eq!(mesh.warnings.len(), 3);
+        assert!(mesh.warnings[0].message.contains("Node count mismatch"));
+
+        let mesh = parse_msh_with(content, &lenient.clone().max_warnings(1)).unwrap();
+        assert_eq!(mesh.warnings.len(), 1);
+        assert!(matches!(
+            parse_msh_with(content, &lenient.strict()),
+            Err(ParseError::InvalidData { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lenient_recovery() {
+        // Wrong node count, extra token, missing $EndNodes and an unknown element type
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 2\n0 1 0 2\n1\n2\n0 0 0 7\n1 0 0\n\
+            $Elements\n2 2 1 2\n0 1 999 1\n1 1\n0 1 15 1\n2 2\n$EndElements\n";
+        assert!(parse_msh(content).is_err());
+
+        let mesh = parse_msh_with(content, &ParserOptions::default().lenient()).unwrap();
+        assert_eq!(mesh.node_blocks[0].nodes.len(), 2);
+        assert_eq!(mesh.element_blocks.len(), 1);
+        let messages: Vec<&str> = mesh.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(mesh.warnings.len(), 5, "{:?}", messages);
+        assert!(mesh.warnings.iter().all(|w| w.span.is_some()));
+
+        let extra = mesh
+            .warnings
+            .iter()
+            .find(|w| w.message.contains("unexpected token"));
+        let span = extra.and_then(|w| w.span).unwrap();
+        assert_eq!(&content[span.offset..span.offset + span.len], "7");
+    }
+
+    #[test]
+    fn test_compact_storage() {
+        let options = ParserOptions::default().compact_storage();
+        let expanded = parse_msh(NAMED_SQUARE_ON_CURVES).unwrap();
+        let compact = parse_msh_with(NAMED_SQUARE_ON_CURVES, &options).unwrap();
+        assert!(compact.element_blocks.is_empty());
+        assert_eq!(
+            compact.compact_element_blocks.len(),
+            expanded.element_blocks.len()
+        );
+
+        let written = crate::writer::write_msh(&compact).unwrap();
+        let mut reparsed = parse_msh_with(written, &options).unwrap();
+        reparsed.expand_storage();
+        for (a, b) in reparsed.element_blocks.iter().zip(&expanded.element_blocks) {
+            assert_eq!(a.elements.len(), b.elements.len());
+            assert_eq!(
+                a.elements.last().unwrap().nodes,
+                b.elements.last().unwrap().nodes
+            );
         }
     }
+
+    #[test]
+    fn test_unknown_sections_are_kept() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Comments\nmade by hand\n\n  indented line\n$EndComments\n\
+            $Nodes\n1 1 1 1\n0 1 0 1\n1\n0 0 0\n$EndNodes\n";
+        let mesh = parse_msh(content).unwrap();
+        assert_eq!(mesh.raw_sections.len(), 1);
+        let section = &mesh.raw_sections[0];
+        assert_eq!(section.name, "Comments");
+        assert_eq!(section.lines, ["made by hand", "", "  indented line"]);
+        let start = content.find("$Comments").unwrap();
+        let end = content.find("$Nodes").unwrap() - 1;
+        assert_eq!(section.span, Span::new(start, end - start));
+        assert_eq!(mesh.num_nodes(), 1);
+        assert!(mesh.warnings[0].message.contains("$Comments"));
+
+        let written = crate::writer::write_msh(&mesh).unwrap();
+        assert!(written.ends_with("$Comments\nmade by hand\n\n  indented line\n$EndComments\n"));
+        assert_eq!(
+            parse_msh(&written).unwrap().raw_sections[0].lines,
+            section.lines
+        );
+    }
+
+    #[test]
+    fn test_parse_nodes_or_elements_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("square.msh");
+        std::fs::write(&path, NAMED_SQUARE_ON_CURVES).unwrap();
+
+        let nodes = parse_nodes_only(&path).unwrap();
+        assert_eq!(nodes.num_nodes(), 4);
+        assert_eq!(nodes.num_elements(), 0);
+        assert!(nodes.entities.is_none() && nodes.physical_names.is_empty());
+
+        let elements = parse_elements_only(&path).unwrap();
+        assert_eq!(elements.num_elements(), 5);
+        assert_eq!(elements.num_nodes(), 0);
+        assert!(elements.entities.is_none());
+    }
+
+    #[test]
+    fn test_skip_sections() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Parametrizations\n1 0\n1\n2\n0 0 0 0\n1 0 0 1\n$EndParametrizations\n\
+            $Nodes\n1 2 1 2\n1 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+            $NodeData\n1\n\"T\"\n1\n0.0\n3\n0\n1\n2\n1 0.5\n2 1.5\n$EndNodeData\n";
+        let full = parse_msh(content).unwrap();
+        assert!(full.parametrizations.is_some());
+        assert_eq!(full.node_data.len(), 1);
+
+        let options = ParserOptions::default()
+            .skip_post_processing_sections()
+            .skip_parametrizations();
+        let skipped = parse_msh_with(content, &options).unwrap();
+        assert!(skipped.parametrizations.is_none());
+        assert!(skipped.node_data.is_empty());
+        assert_eq!(skipped.num_nodes(), 2);
+    }
+
+    #[test]
+    fn test_block_filter() {
+        use crate::types::PhysicalTag;
+
+        let options = ParserOptions::default()
+            .block_filter(BlockFilter::default().physical_group(1, PhysicalTag(5)));
+        let mesh = parse_msh_with(NAMED_SQUARE_ON_CURVES, &options).unwrap();
+
+        // The bottom and right curves, with the nodes they use
+        assert_eq!(mesh.num_elements(), 2);
+        assert!(mesh.element_blocks.iter().all(|b| b.entity_dim == 1));
+        let kept: HashSet<_> = mesh
+            .node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter().map(|n| n.tag.0))
+            .collect();
+        assert_eq!(kept, HashSet::from([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_block_filter_without_entities() {
+        use crate::types::EntityTag;
+
+        // The nodes are on the curves, which are unknown without $Entities
+        let start = NAMED_SQUARE_ON_CURVES.find("$Entities").unwrap();
+        let end = NAMED_SQUARE_ON_CURVES.find("$Nodes").unwrap();
+        let content = NAMED_SQUARE_ON_CURVES.replace(&NAMED_SQUARE_ON_CURVES[start..end], "");
+        let options =
+            ParserOptions::default().block_filter(BlockFilter::default().entity(2, EntityTag(1)));
+        let mesh = parse_msh_with(&content, &options).unwrap();
+        assert_eq!(mesh.num_elements(), 2);
+        assert_eq!(mesh.num_nodes(), 4);
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        let mesh = parse_msh_bytes(NAMED_SQUARE_ON_CURVES.as_bytes()).unwrap();
+        assert_eq!((mesh.num_nodes(), mesh.num_elements()), (4, 5));
+    }
+
+    #[test]
+    fn test_parse_latin1_bytes() {
+        // Latin-1 encoded physical name
+        let mut latin1 = NAMED_SQUARE_ON_CURVES.as_bytes().to_vec();
+        let at = latin1.windows(2).position(|w| w == b"My").unwrap();
+        latin1[at + 1] = 0xff;
+        assert!(parse_msh_bytes(&latin1).is_err());
+        let mesh = parse_msh_bytes_with(&latin1, &ParserOptions::default().lossy_utf8()).unwrap();
+        assert_eq!(mesh.physical_names[1].name, "M\u{fffd} surface");
+        assert!(mesh.warnings[0]
+            .message
+            .contains(&format!("byte {}", at + 1)));
+
+        let options = ParserOptions::default().lossy_utf8().max_warnings(0);
+        let mesh = parse_msh_bytes_with(&latin1, &options).unwrap();
+        assert!(mesh.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_section_filter() {
+        let sections: SectionFilter = ["$Nodes", "PhysicalNames"].into_iter().collect();
+        assert!(sections.contains("MeshFormat") && !sections.contains("$Elements"));
+        let mesh = parse_msh_with(
+            NAMED_SQUARE_ON_CURVES,
+            &ParserOptions::default().sections(sections),
+        )
+        .unwrap();
+
+        assert_eq!(mesh.num_nodes(), 4);
+        assert_eq!(mesh.physical_names.len(), 2);
+        assert_eq!(mesh.num_elements(), 0);
+        assert!(mesh.entities.is_none());
+    }
+
+    #[test]
+    fn test_section_filter_skips_unknown_sections() {
+        let content = format!("{}$Custom\nabc\n$EndCustom\n", NAMED_SQUARE_ON_CURVES);
+        let options = ParserOptions::default().sections(SectionFilter::default());
+        let mesh = parse_msh_with(content, &options).unwrap();
+        assert!(mesh.raw_sections.is_empty() && mesh.warnings.is_empty());
+    }
 }
@@ -1,10 +1,11 @@
-use super::LineReader;
-use crate::error::{ParseError, Result};
+use super::{capacity_hint, parse_section_header, LineReader, NodeBlockHeader};
+use crate::error::{ParseError, ParseWarning, Result, WarningKind};
 use crate::parser::token::TokenIter;
-use crate::types::{Mesh, Node, NodeBlock};
+use crate::types::{Mesh, Node, NodeBlock, NodeTag};
 
 pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     let token_line = reader.read_token_line()?;
+    let header = parse_section_header(&mut token_line.iter(), "Node")?;
     let mut iter = token_line.iter();
 
     let num_entity_blocks = iter.parse_usize("numEntityBlocks")?;
@@ -12,84 +13,151 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     // Parse metadata and validate later (after parsing all blocks)
     let metadata_iter = iter;
 
+    // Entities whose blocks are kept, if filtered
+    let kept = reader
+        .options()
+        .block_filter
+        .as_ref()
+        .and_then(|filter| filter.selected_closure(mesh));
+
     // Parse each entity block
+    let mut skipped = false;
     for _ in 0..num_entity_blocks {
-        let block = parse_node_block(reader)?;
+        let header = parse_block_header(reader)?;
+        if let Some(kept) = &kept {
+            let key = (header.entity_dim as i32, header.entity_tag);
+            if !kept.contains(&key) {
+                // Tags, then coordinates
+                reader.skip_lines(header.num_nodes.saturating_mul(2))?;
+                reader.block_parsed()?;
+                skipped = true;
+                continue;
+            }
+        }
+        let block = parse_node_block(reader, header)?;
         mesh.node_blocks.push(block);
+        reader.block_parsed()?;
     }
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("Nodes")?;
+    reader.read_end_marker("Nodes")?;
+    mesh.nodes_header = Some(header);
+
+    // The header describes the whole section, not the kept blocks
+    if skipped {
+        return Ok(());
+    }
 
     // Validate parsed nodes against metadata
-    validate_nodes_metadata(&mesh.node_blocks, metadata_iter)?;
+    match validate_nodes_metadata(&mesh.node_blocks, metadata_iter) {
+        // With metadata validation disabled, mismatches become warnings
+        Err(ParseError::InvalidData { message, span, .. })
+            if !reader.options().validate_metadata || reader.is_lenient() =>
+        {
+            let warning = ParseWarning::at(message, span);
+            mesh.warnings
+                .push(warning.with_kind(WarningKind::MetadataMismatch));
+        }
+        result => result?,
+    }
 
     Ok(())
 }
 
-fn parse_node_block(reader: &mut LineReader) -> Result<NodeBlock> {
+fn parse_node_block(reader: &mut LineReader, header: NodeBlockHeader) -> Result<NodeBlock> {
+    let node_tags = parse_block_tags(reader, &header)?;
+
+    // Read all coordinates and create the unified Node struct
+    let mut nodes = Vec::with_capacity(capacity_hint(header.num_nodes));
+    for tag in node_tags.into_iter() {
+        nodes.push(parse_node(reader, &header, tag)?);
+    }
+
+    Ok(NodeBlock {
+        entity_dim: header.entity_dim,
+        entity_tag: header.entity_tag,
+        parametric: header.parametric,
+        nodes,
+    })
+}
+
+/// Parse the `entityDim entityTag parametric numNodesInBlock` line of a block
+pub(crate) fn parse_block_header(reader: &mut LineReader) -> Result<NodeBlockHeader> {
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
     let entity_dim = iter.parse_entity_dimension("entityDim")?;
-    let entity_tag = iter.parse_int("entityTag")?;
-    let is_parametric = iter.parse_bool("parametric")?;
-    let num_nodes_in_block = iter.parse_usize("numNodesInBlock")?;
+    let entity_tag = iter.parse_entity_tag("entityTag")?;
+    let parametric = iter.parse_bool("parametric")?;
+    let num_nodes = iter.parse_usize("numNodesInBlock")?;
 
     iter.expect_no_more()?;
 
-    // Read all node tags
-    let mut node_tags = Vec::with_capacity(num_nodes_in_block);
-    for _ in 0..num_nodes_in_block {
+    Ok(NodeBlockHeader {
+        entity_dim,
+        entity_tag,
+        parametric,
+        num_nodes,
+    })
+}
+
+/// Read the node tags of a block, which precede its coordinates
+pub(crate) fn parse_block_tags(
+    reader: &mut LineReader,
+    header: &NodeBlockHeader,
+) -> Result<Vec<NodeTag>> {
+    let mut node_tags = Vec::with_capacity(capacity_hint(header.num_nodes));
+    for _ in 0..header.num_nodes {
         let token_line = reader.read_token_line()?;
         let mut iter = token_line.iter();
-        let tag = iter.parse_usize("nodeTag")?;
+        let tag = iter.parse_node_tag("nodeTag")?;
         iter.expect_no_more()?;
         node_tags.push(tag);
     }
+    Ok(node_tags)
+}
 
-    // Read all coordinates and create the unified Node struct
-    let mut nodes = Vec::with_capacity(num_nodes_in_block);
-    for tag in node_tags.into_iter() {
-        let token_line = reader.read_token_line()?;
-        let mut iter = token_line.iter();
-        let x = iter.parse_float("x")?;
-        let y = iter.parse_float("y")?;
-        let z = iter.parse_float("z")?;
-
-        let parametric_coords = if is_parametric {
-            let mut p_coords = Vec::new();
-            if entity_dim as i32 >= 1 {
-                p_coords.push(iter.parse_float("u")?);
-            }
-            if entity_dim as i32 >= 2 {
-                p_coords.push(iter.parse_float("v")?);
-            }
-            if entity_dim as i32 == 3 {
-                // Only Volume entities have 'w' coordinate
-                p_coords.push(iter.parse_float("w")?);
-            }
-            Some(p_coords)
-        } else {
-            None
-        };
+/// Parse the coordinate line of the node `tag` of a block
+pub(crate) fn parse_node(
+    reader: &mut LineReader,
+    header: &NodeBlockHeader,
+    tag: NodeTag,
+) -> Result<Node> {
+    let token_line = reader.read_token_line()?;
+    let mut iter = token_line.iter();
+    let x = iter.parse_float("x")?;
+    let y = iter.parse_float("y")?;
+    let z = iter.parse_float("z")?;
+
+    let entity_dim = header.entity_dim;
+    let parametric_coords = if header.parametric {
+        let mut p_coords = Vec::new();
+        if entity_dim as i32 >= 1 {
+            p_coords.push(iter.parse_float("u")?);
+        }
+        if entity_dim as i32 >= 2 {
+            p_coords.push(iter.parse_float("v")?);
+        }
+        if entity_dim as i32 == 3 {
+            // Only Volume entities have 'w' coordinate
+            p_coords.push(iter.parse_float("w")?);
+        }
+        Some(p_coords)
+    } else {
+        None
+    };
 
-        iter.expect_no_more()?;
+    iter.expect_no_more()?;
 
-        nodes.push(Node {
-            tag,
-            x,
-            y,
-            z,
-            parametric_coords,
-        });
+    if let Some(source_map) = reader.source_map_mut() {
+        source_map.record_node(tag, token_line.span());
     }
 
-    Ok(NodeBlock {
-        entity_dim,
-        entity_tag,
-        parametric: is_parametric,
-        nodes,
+    Ok(Node {
+        tag,
+        x,
+        y,
+        z,
+        parametric_coords,
     })
 }
 
@@ -100,17 +168,17 @@ fn validate_nodes_metadata(node_blocks: &[NodeBlock], mut metadata_iter: TokenIt
     let expected_num_nodes = metadata_iter.parse_usize("numNodes")?;
 
     let min_node_tag_token = metadata_iter.peek_token()?;
-    let expected_min_node_tag = metadata_iter.parse_usize("minNodeTag")?;
+    let expected_min_node_tag = metadata_iter.parse_node_tag("minNodeTag")?;
 
     let max_node_tag_token = metadata_iter.peek_token()?;
-    let expected_max_node_tag = metadata_iter.parse_usize("maxNodeTag")?;
+    let expected_max_node_tag = metadata_iter.parse_node_tag("maxNodeTag")?;
 
     metadata_iter.expect_no_more()?;
 
     // Calculate actual stats
     let mut actual_num_nodes = 0;
-    let mut actual_min_tag = usize::MAX;
-    let mut actual_max_tag = usize::MIN;
+    let mut actual_min_tag = NodeTag(u64::MAX);
+    let mut actual_max_tag = NodeTag(u64::MIN);
 
     for block in node_blocks {
         actual_num_nodes += block.num_nodes();
@@ -0,0 +1,347 @@
+//! Options controlling how MSH files are parsed
+
+use super::progress::{CancellationToken, ParseProgress, ProgressCallback};
+use crate::types::{EntityTag, Mesh, PhysicalTag};
+use std::collections::{BTreeSet, HashSet};
+
+/// Options for [`parse_msh_with`](super::parse_msh_with) and
+/// [`parse_msh_file_with`](super::parse_msh_file_with)
+///
+/// The default options parse every section and reject inconsistent headers,
+/// like [`parse_msh`](super::parse_msh):
+///
+/// ```no_run
+/// use gmsh_parser::{parse_msh_file_with, ParserOptions};
+///
+/// let options = ParserOptions::default()
+///     .skip_post_processing_sections()
+///     .validate_metadata(false)
+///     .max_warnings(10);
+/// let mesh = parse_msh_file_with("model.msh", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Skip `$NodeData`, `$ElementData`, `$ElementNodeData` and `$InterpolationScheme`
+    pub skip_post_processing_sections: bool,
+    /// Skip `$Parametrizations`
+    pub skip_parametrizations: bool,
+    /// Reject `$Nodes` and `$Elements` sections whose header counts and tag
+    /// ranges disagree with their content; when `false`, the mismatch is
+    /// recorded as a warning instead
+    pub validate_metadata: bool,
+    /// Keep at most this many warnings in [`Mesh::warnings`](crate::Mesh::warnings)
+    pub max_warnings: Option<usize>,
+    /// Reject unknown sections and content outside of sections instead of
+    /// recording a warning
+    pub strict_mode: bool,
+    /// Recover from common problems in the file instead of failing
+    ///
+    /// Mismatched header counts, missing `$End` markers before the next
+    /// section or the end of the file, blocks of unknown element types and
+    /// extra tokens at the end of lines are recorded as warnings with their
+    /// location, and parsing continues with what could be read.
+    pub lenient: bool,
+    /// Store element blocks as flat arrays in
+    /// [`Mesh::compact_element_blocks`](crate::Mesh::compact_element_blocks)
+    /// instead of one allocation per element
+    ///
+    /// Blocks of element types without a fixed node count are stored as usual.
+    pub compact_storage: bool,
+    /// Only keep the node and element blocks of these entities, skipping the
+    /// lines of the others without storing them
+    pub block_filter: Option<BlockFilter>,
+    /// Only parse these sections, skipping the lines of the others without
+    /// tokenizing them
+    pub sections: Option<SectionFilter>,
+    /// Replace invalid UTF-8 in the input of
+    /// [`parse_msh_bytes_with`](super::parse_msh_bytes_with) with U+FFFD and
+    /// record a warning, instead of failing
+    pub lossy_utf8: bool,
+    /// Called at the start of each section and about every megabyte read
+    pub progress: Option<ProgressCallback>,
+    /// Stop parsing with [`ParseError::Cancelled`](crate::ParseError::Cancelled)
+    /// once this token is cancelled
+    pub cancellation: Option<CancellationToken>,
+}
+
+/// Entities whose blocks are kept, see [`ParserOptions::block_filter`]
+///
+/// An entity is selected if it is listed, or if it carries one of the listed
+/// physical groups (matched on magnitude) in `$Entities` or
+/// `$PartitionedEntities`. Element blocks of selected entities are kept. Node
+/// blocks are kept for the selected entities and their boundaries, down to
+/// points (see [`EntityGraph::closure`](crate::EntityGraph::closure)), which
+/// is where Gmsh stores the nodes of their elements. Without `$Entities` the
+/// boundaries are unknown, so every node block is kept.
+///
+/// ```no_run
+/// use gmsh_parser::{parse_msh_file_with, BlockFilter, ParserOptions, PhysicalTag};
+///
+/// let filter = BlockFilter::default().physical_group(3, PhysicalTag(2));
+/// let options = ParserOptions::default().block_filter(filter);
+/// let region = parse_msh_file_with("huge.msh", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockFilter {
+    /// Entities by `(dimension, tag)`
+    pub entities: BTreeSet<(i32, EntityTag)>,
+    /// Physical groups by `(dimension, tag)`
+    pub physical_groups: BTreeSet<(i32, PhysicalTag)>,
+}
+
+impl BlockFilter {
+    pub fn entity(mut self, dim: i32, tag: EntityTag) -> Self {
+        self.entities.insert((dim, tag));
+        self
+    }
+
+    pub fn physical_group(mut self, dim: i32, tag: PhysicalTag) -> Self {
+        self.physical_groups.insert((dim, tag.magnitude()));
+        self
+    }
+
+    /// Entities of `mesh` whose element blocks are kept
+    pub(crate) fn selected(&self, mesh: &Mesh) -> HashSet<(i32, EntityTag)> {
+        let mut selected: HashSet<_> = self.entities.iter().copied().collect();
+        let mut tagged: Vec<(i32, EntityTag, &[PhysicalTag])> = Vec::new();
+        if let Some(entities) = &mesh.entities {
+            tagged.extend(
+                entities
+                    .points
+                    .iter()
+                    .map(|e| (0, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                entities
+                    .curves
+                    .iter()
+                    .map(|e| (1, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                entities
+                    .surfaces
+                    .iter()
+                    .map(|e| (2, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                entities
+                    .volumes
+                    .iter()
+                    .map(|e| (3, e.tag, &e.physical_tags[..])),
+            );
+        }
+        if let Some(entities) = &mesh.partitioned_entities {
+            tagged.extend(
+                entities
+                    .points
+                    .iter()
+                    .map(|e| (0, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                entities
+                    .curves
+                    .iter()
+                    .map(|e| (1, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                entities
+                    .surfaces
+                    .iter()
+                    .map(|e| (2, e.tag, &e.physical_tags[..])),
+            );
+            tagged.extend(
+                entities
+                    .volumes
+                    .iter()
+                    .map(|e| (3, e.tag, &e.physical_tags[..])),
+            );
+        }
+        for (dim, tag, physical_tags) in tagged {
+            if physical_tags
+                .iter()
+                .any(|p| self.physical_groups.contains(&(dim, p.magnitude())))
+            {
+                selected.insert((dim, tag));
+            }
+        }
+        selected
+    }
+
+    /// Entities of `mesh` whose node blocks are kept: the selected entities
+    /// and their boundaries, or `None` to keep them all when there is no
+    /// entity graph
+    pub(crate) fn selected_closure(&self, mesh: &Mesh) -> Option<HashSet<(i32, EntityTag)>> {
+        let graph = mesh.entity_graph()?;
+        let closure = self
+            .selected(mesh)
+            .into_iter()
+            .flat_map(|(dim, tag)| graph.closure(dim, tag))
+            .collect();
+        Some(closure)
+    }
+}
+
+/// Sections to parse, see [`ParserOptions::sections`]
+///
+/// Sections are named with or without their leading `$`, e.g. `"Nodes"` or
+/// `"$PhysicalNames"`. `$MeshFormat` is always parsed.
+///
+/// ```no_run
+/// use gmsh_parser::{parse_msh_file_with, ParserOptions, SectionFilter};
+///
+/// let sections = SectionFilter::default().section("PhysicalNames");
+/// let options = ParserOptions::default().sections(sections);
+/// let mesh = parse_msh_file_with("huge.msh", &options).unwrap();
+/// for group in &mesh.physical_names {
+///     println!("{}", group.name);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectionFilter {
+    /// Section names without the leading `$`
+    pub sections: BTreeSet<String>,
+}
+
+impl SectionFilter {
+    pub fn section(mut self, name: &str) -> Self {
+        self.sections
+            .insert(name.strip_prefix('$').unwrap_or(name).to_string());
+        self
+    }
+
+    /// Whether the section `name`, with or without its leading `$`, is parsed
+    pub fn contains(&self, name: &str) -> bool {
+        name == "$MeshFormat"
+            || name == "MeshFormat"
+            || self
+                .sections
+                .contains(name.strip_prefix('$').unwrap_or(name))
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for SectionFilter {
+    fn from_iter<I: IntoIterator<Item = S>>(names: I) -> Self {
+        names.into_iter().fold(Self::default(), |filter, name| {
+            filter.section(name.as_ref())
+        })
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            skip_post_processing_sections: false,
+            skip_parametrizations: false,
+            validate_metadata: true,
+            max_warnings: None,
+            strict_mode: false,
+            lenient: false,
+            compact_storage: false,
+            block_filter: None,
+            sections: None,
+            lossy_utf8: false,
+            progress: None,
+            cancellation: None,
+        }
+    }
+}
+
+impl ParserOptions {
+    pub fn skip_post_processing_sections(mut self) -> Self {
+        self.skip_post_processing_sections = true;
+        self
+    }
+
+    pub fn skip_parametrizations(mut self) -> Self {
+        self.skip_parametrizations = true;
+        self
+    }
+
+    pub fn validate_metadata(mut self, validate: bool) -> Self {
+        self.validate_metadata = validate;
+        self
+    }
+
+    pub fn max_warnings(mut self, max_warnings: usize) -> Self {
+        self.max_warnings = Some(max_warnings);
+        self
+    }
+
+    pub fn strict(mut self) -> Self {
+        self.strict_mode = true;
+        self
+    }
+
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    pub fn compact_storage(mut self) -> Self {
+        self.compact_storage = true;
+        self
+    }
+
+    /// Call `callback` as parsing progresses, e.g. to update a progress bar
+    ///
+    /// ```no_run
+    /// use gmsh_parser::{parse_msh_file_with, ParserOptions};
+    ///
+    /// let options = ParserOptions::default().progress(|p| {
+    ///     eprint!("\r{:5.1}% {}", 100.0 * p.fraction(), p.section.unwrap_or_default());
+    /// });
+    /// let mesh = parse_msh_file_with("large.msh", &options).unwrap();
+    /// ```
+    pub fn progress(mut self, callback: impl FnMut(ParseProgress) + Send + 'static) -> Self {
+        self.progress = Some(ProgressCallback::new(callback));
+        self
+    }
+
+    /// Check `token` between sections and blocks, so that another thread can
+    /// abort the parse
+    ///
+    /// ```no_run
+    /// use gmsh_parser::parser::CancellationToken;
+    /// use gmsh_parser::{parse_msh_file_with, ParserOptions};
+    ///
+    /// let token = CancellationToken::new();
+    /// let options = ParserOptions::default().cancellation(token.clone());
+    /// let parse = std::thread::spawn(move || parse_msh_file_with("large.msh", &options));
+    /// token.cancel();
+    /// // `Err(ParseError::Cancelled)`, unless parsing was already over
+    /// let result = parse.join().unwrap();
+    /// ```
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn lossy_utf8(mut self) -> Self {
+        self.lossy_utf8 = true;
+        self
+    }
+
+    /// Only keep the node and element blocks selected by `filter`
+    ///
+    /// The other blocks are skipped line by line, so loading one region of a
+    /// large file needs little time and memory. Header counts are not checked
+    /// against the kept blocks, and node and element sets are restricted to
+    /// the kept nodes and elements. Nodes of embedded points and curves are
+    /// not on the boundary of their host, so those entities must be selected
+    /// too.
+    pub fn block_filter(mut self, filter: BlockFilter) -> Self {
+        self.block_filter = Some(filter);
+        self
+    }
+
+    /// Only parse the sections listed in `filter`
+    ///
+    /// The other sections, unknown ones included, are skipped line by line
+    /// without being tokenized, which makes e.g. reading the physical names
+    /// or the nodes of a large file much faster. The mesh is not validated,
+    /// as the sections it would be checked against may be missing.
+    pub fn sections(mut self, filter: SectionFilter) -> Self {
+        self.sections = Some(filter);
+        self
+    }
+}
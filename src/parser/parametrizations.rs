@@ -6,7 +6,7 @@ use crate::types::{
     Parametrizations, SurfaceParametrization, SurfaceParametrizationNode,
 };
 
-use super::LineReader;
+use super::{capacity_hint, LineReader};
 
 pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     let mut parametrizations = Parametrizations::default();
@@ -25,7 +25,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         // curveTag (on its own line)
         let curve_tag_line = reader.read_token_line()?;
         let mut iter = curve_tag_line.iter();
-        let curve_tag = iter.parse_int("curveTag")?;
+        let curve_tag = iter.parse_entity_tag("curveTag")?;
         iter.expect_no_more()?;
 
         // numNodes (on next line)
@@ -34,7 +34,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let num_nodes = iter.parse_usize("numNodes")?;
         iter.expect_no_more()?;
 
-        let mut nodes = Vec::with_capacity(num_nodes);
+        let mut nodes = Vec::with_capacity(capacity_hint(num_nodes));
         for _ in 0..num_nodes {
             // nodeX nodeY nodeZ nodeU
             let node_line = reader.read_token_line()?;
@@ -59,7 +59,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         // surfaceTag (on its own line)
         let surface_tag_line = reader.read_token_line()?;
         let mut iter = surface_tag_line.iter();
-        let surface_tag = iter.parse_int("surfaceTag")?;
+        let surface_tag = iter.parse_entity_tag("surfaceTag")?;
         iter.expect_no_more()?;
 
         // numNodes numTriangles (on next line)
@@ -69,7 +69,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let num_triangles = iter.parse_usize("numTriangles")?;
         iter.expect_no_more()?;
 
-        let mut nodes = Vec::with_capacity(num_nodes);
+        let mut nodes = Vec::with_capacity(capacity_hint(num_nodes));
         for _ in 0..num_nodes {
             // nodeX nodeY nodeZ nodeU nodeV curvMaxX curvMaxY curvMaxZ curvMinX curvMinY curvMinZ
             let node_line = reader.read_token_line()?;
@@ -91,7 +91,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
             iter.expect_no_more()?;
         }
 
-        let mut triangles = Vec::with_capacity(num_triangles);
+        let mut triangles = Vec::with_capacity(capacity_hint(num_triangles));
         for _ in 0..num_triangles {
             // nodeIndex1 nodeIndex2 nodeIndex3
             let triangle_line = reader.read_token_line()?;
@@ -114,8 +114,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
 
     mesh.parametrizations = Some(parametrizations);
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("Parametrizations")?;
+    reader.read_end_marker("Parametrizations")?;
 
     Ok(())
 }
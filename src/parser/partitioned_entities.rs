@@ -29,7 +29,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let mut iter = token_line.iter();
 
         partitioned.ghost_entities.push(GhostEntity {
-            tag: iter.parse_int("ghostEntityTag")?,
+            tag: iter.parse_entity_tag("ghostEntityTag")?,
             partition: iter.parse_int("ghostEntityPartition")?,
         });
         iter.expect_no_more()?;
@@ -69,8 +69,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
 
     mesh.partitioned_entities = Some(partitioned);
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("PartitionedEntities")?;
+    reader.read_end_marker("PartitionedEntities")?;
 
     Ok(())
 }
@@ -80,9 +79,9 @@ fn parse_partitioned_point(reader: &mut LineReader) -> Result<PartitionedPoint>
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
-    let tag = iter.parse_int("tag")?;
+    let tag = iter.parse_entity_tag("tag")?;
     let parent_dim = iter.parse_entity_dimension("parent_dim")?;
-    let parent_tag = iter.parse_int("parent_tag")?;
+    let parent_tag = iter.parse_entity_tag("parent_tag")?;
     let num_partitions = iter.parse_usize("numPartitions")?;
 
     let partition_tags = iter.parse_ints(num_partitions, "partitionTag")?;
@@ -92,7 +91,7 @@ fn parse_partitioned_point(reader: &mut LineReader) -> Result<PartitionedPoint>
     let z = iter.parse_float("z")?;
     let num_physical_tags = iter.parse_usize("numPhysicalTags")?;
 
-    let physical_tags = iter.parse_ints(num_physical_tags, "physicalTag")?;
+    let physical_tags = iter.parse_physical_tags(num_physical_tags, "physicalTag")?;
     iter.expect_no_more()?;
 
     Ok(PartitionedPoint {
@@ -112,9 +111,9 @@ fn parse_partitioned_curve(reader: &mut LineReader) -> Result<PartitionedCurve>
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
-    let tag = iter.parse_int("tag")?;
+    let tag = iter.parse_entity_tag("tag")?;
     let parent_dim = iter.parse_entity_dimension("parent_dim")?;
-    let parent_tag = iter.parse_int("parent_tag")?;
+    let parent_tag = iter.parse_entity_tag("parent_tag")?;
     let num_partitions = iter.parse_usize("numPartitions")?;
 
     let partition_tags = iter.parse_ints(num_partitions, "partitionTag")?;
@@ -127,7 +126,7 @@ fn parse_partitioned_curve(reader: &mut LineReader) -> Result<PartitionedCurve>
     let max_z = iter.parse_float("maxZ")?;
     let num_physical_tags = iter.parse_usize("numPhysicalTags")?;
 
-    let physical_tags = iter.parse_ints(num_physical_tags, "physicalTag")?;
+    let physical_tags = iter.parse_physical_tags(num_physical_tags, "physicalTag")?;
 
     let num_bounding_points = iter.parse_usize("numBoundingPoints")?;
     let bounding_points = iter.parse_ints(num_bounding_points, "boundingPoint")?;
@@ -154,9 +153,9 @@ fn parse_partitioned_surface(reader: &mut LineReader) -> Result<PartitionedSurfa
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
-    let tag = iter.parse_int("tag")?;
+    let tag = iter.parse_entity_tag("tag")?;
     let parent_dim = iter.parse_entity_dimension("parent_dim")?;
-    let parent_tag = iter.parse_int("parent_tag")?;
+    let parent_tag = iter.parse_entity_tag("parent_tag")?;
     let num_partitions = iter.parse_usize("numPartitions")?;
 
     let partition_tags = iter.parse_ints(num_partitions, "partitionTag")?;
@@ -169,7 +168,7 @@ fn parse_partitioned_surface(reader: &mut LineReader) -> Result<PartitionedSurfa
     let max_z = iter.parse_float("maxZ")?;
     let num_physical_tags = iter.parse_usize("numPhysicalTags")?;
 
-    let physical_tags = iter.parse_ints(num_physical_tags, "physicalTag")?;
+    let physical_tags = iter.parse_physical_tags(num_physical_tags, "physicalTag")?;
 
     let num_bounding_curves = iter.parse_usize("numBoundingCurves")?;
     let bounding_curves = iter.parse_ints(num_bounding_curves, "boundingCurve")?;
@@ -196,9 +195,9 @@ fn parse_partitioned_volume(reader: &mut LineReader) -> Result<PartitionedVolume
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
 
-    let tag = iter.parse_int("tag")?;
+    let tag = iter.parse_entity_tag("tag")?;
     let parent_dim = iter.parse_entity_dimension("parent_dim")?;
-    let parent_tag = iter.parse_int("parent_tag")?;
+    let parent_tag = iter.parse_entity_tag("parent_tag")?;
     let num_partitions = iter.parse_usize("numPartitions")?;
 
     let partition_tags = iter.parse_ints(num_partitions, "partitionTag")?;
@@ -211,7 +210,7 @@ fn parse_partitioned_volume(reader: &mut LineReader) -> Result<PartitionedVolume
     let max_z = iter.parse_float("maxZ")?;
     let num_physical_tags = iter.parse_usize("numPhysicalTags")?;
 
-    let physical_tags = iter.parse_ints(num_physical_tags, "physicalTag")?;
+    let physical_tags = iter.parse_physical_tags(num_physical_tags, "physicalTag")?;
 
     let num_bounding_surfaces = iter.parse_usize("numBoundingSurfaces")?;
     let bounding_surfaces = iter.parse_ints(num_bounding_surfaces, "boundingSurface")?;
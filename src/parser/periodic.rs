@@ -3,7 +3,7 @@
 use crate::error::Result;
 use crate::types::{Mesh, PeriodicLink};
 
-use super::LineReader;
+use super::{capacity_hint, LineReader};
 
 pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     // Read number of periodic links
@@ -18,8 +18,8 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let mut iter = token_line.iter();
 
         let entity_dim = iter.parse_entity_dimension("entityDim")?;
-        let entity_tag = iter.parse_int("entityTag")?;
-        let entity_tag_master = iter.parse_int("entityTagMaster")?;
+        let entity_tag = iter.parse_entity_tag("entityTag")?;
+        let entity_tag_master = iter.parse_entity_tag("entityTagMaster")?;
         iter.expect_no_more()?;
 
         // Read affine transform
@@ -36,13 +36,13 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let num_corresponding_nodes = iter.parse_usize("numCorrespondingNodes")?;
         iter.expect_no_more()?;
 
-        let mut node_correspondences = Vec::with_capacity(num_corresponding_nodes);
+        let mut node_correspondences = Vec::with_capacity(capacity_hint(num_corresponding_nodes));
         for _ in 0..num_corresponding_nodes {
             let token_line = reader.read_token_line()?;
             let mut iter = token_line.iter();
 
-            let node_tag = iter.parse_usize("nodeTag")?;
-            let node_tag_master = iter.parse_usize("nodeTagMaster")?;
+            let node_tag = iter.parse_node_tag("nodeTag")?;
+            let node_tag_master = iter.parse_node_tag("nodeTagMaster")?;
             iter.expect_no_more()?;
 
             node_correspondences.push((node_tag, node_tag_master));
@@ -57,8 +57,7 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         });
     }
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("Periodic")?;
+    reader.read_end_marker("Periodic")?;
 
     Ok(())
 }
@@ -68,6 +67,7 @@ mod tests {
     use super::super::super::*;
     use super::super::*;
     use super::*;
+    use crate::types::NodeTag;
 
     #[test]
     fn test_parse_periodic() {
@@ -93,7 +93,7 @@ $EndPeriodic
         assert_eq!(link.entity_tag_master, 2);
         assert_eq!(link.affine_transform.len(), 0);
         assert_eq!(link.node_correspondences.len(), 2);
-        assert_eq!(link.node_correspondences[0], (1, 2));
-        assert_eq!(link.node_correspondences[1], (3, 4));
+        assert_eq!(link.node_correspondences[0], (NodeTag(1), NodeTag(2)));
+        assert_eq!(link.node_correspondences[1], (NodeTag(3), NodeTag(4)));
     }
 }
@@ -13,15 +13,14 @@ pub fn parse(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let mut iter = token_line.iter();
 
         let dimension = iter.parse_entity_dimension("PhysicalNames")?;
-        let tag = iter.parse_int("tag")?;
+        let tag = iter.parse_physical_tag("tag")?;
         let name = iter.parse_quoted_string_to_line_end()?;
 
         mesh.physical_names
             .push(PhysicalName::new(dimension, tag, name));
     }
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("PhysicalNames")?;
+    reader.read_end_marker("PhysicalNames")?;
 
     Ok(())
 }
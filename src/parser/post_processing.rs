@@ -5,26 +5,31 @@ use crate::types::{ElementData, ElementNodeData, Mesh, NodeData};
 
 use super::LineReader;
 
-/// Parse $NodeData section
-pub fn parse_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
-    let mut node_data = NodeData {
-        string_tags: Vec::new(),
-        real_tags: Vec::new(),
-        integer_tags: Vec::new(),
-        data: Vec::new(),
-    };
+/// Tags opening a post-processing section
+struct ViewTags {
+    string_tags: Vec<String>,
+    real_tags: Vec<f64>,
+    integer_tags: Vec<i32>,
+    num_components: usize,
+    num_entities: usize,
+}
 
+/// Parse the string, real and integer tags of a post-processing section
+///
+/// The integer tags must start with the time step, a positive number of
+/// components and the number of entities with data.
+fn parse_tags(reader: &mut LineReader) -> Result<ViewTags> {
     // Read string tags
     let token_line = reader.read_token_line()?;
     let mut iter = token_line.iter();
     let num_string_tags = iter.parse_usize("numStringTags")?;
     iter.expect_no_more()?;
 
+    let mut string_tags = Vec::with_capacity(super::capacity_hint(num_string_tags));
     for _ in 0..num_string_tags {
         let token_line = reader.read_token_line()?;
         let mut iter = token_line.iter();
-        let tag = iter.parse_quoted_string_to_line_end()?;
-        node_data.string_tags.push(tag);
+        string_tags.push(iter.parse_quoted_string_to_line_end()?);
     }
 
     // Read real tags
@@ -33,12 +38,12 @@ pub fn parse_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     let num_real_tags = iter.parse_usize("numRealTags")?;
     iter.expect_no_more()?;
 
+    let mut real_tags = Vec::with_capacity(super::capacity_hint(num_real_tags));
     for _ in 0..num_real_tags {
         let token_line = reader.read_token_line()?;
         let mut iter = token_line.iter();
-        let tag = iter.parse_float("realTag")?;
+        real_tags.push(iter.parse_float("realTag")?);
         iter.expect_no_more()?;
-        node_data.real_tags.push(tag);
     }
 
     // Read integer tags
@@ -46,25 +51,55 @@ pub fn parse_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
     let mut iter = token_line.iter();
     let num_integer_tags = iter.parse_usize("numIntegerTags")?;
     iter.expect_no_more()?;
+    if num_integer_tags < 3 {
+        return Err(token_line.invalid_format(format!(
+            "Expected at least 3 integer tags (time step, components, entities), found {}",
+            num_integer_tags
+        )));
+    }
 
-    for _ in 0..num_integer_tags {
+    let mut integer_tags = Vec::with_capacity(super::capacity_hint(num_integer_tags));
+    for i in 0..num_integer_tags {
         let token_line = reader.read_token_line()?;
         let mut iter = token_line.iter();
         let tag = iter.parse_int("integerTag")?;
         iter.expect_no_more()?;
-        node_data.integer_tags.push(tag);
+        match i {
+            1 if tag < 1 => {
+                return Err(token_line.invalid_format(format!(
+                    "Number of components must be positive, found {}",
+                    tag
+                )))
+            }
+            2 if tag < 0 => {
+                return Err(token_line.invalid_format(format!(
+                    "Number of entities must not be negative, found {}",
+                    tag
+                )))
+            }
+            _ => {}
+        }
+        integer_tags.push(tag);
     }
 
-    // Get number of components and entities from integer tags
-    let num_components = if node_data.integer_tags.len() >= 2 {
-        node_data.integer_tags[1] as usize
-    } else {
-        1
-    };
-    let num_entities = if node_data.integer_tags.len() >= 3 {
-        node_data.integer_tags[2] as usize
-    } else {
-        0
+    Ok(ViewTags {
+        num_components: integer_tags[1] as usize,
+        num_entities: integer_tags[2] as usize,
+        string_tags,
+        real_tags,
+        integer_tags,
+    })
+}
+
+/// Parse $NodeData section
+pub fn parse_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
+    let tags = parse_tags(reader)?;
+    let (num_components, num_entities) = (tags.num_components, tags.num_entities);
+    let mut node_data = NodeData {
+        string_tags: tags.string_tags,
+        real_tags: tags.real_tags,
+        integer_tags: tags.integer_tags,
+        data: Vec::with_capacity(super::capacity_hint(num_entities)),
     };
 
     // Read data
@@ -72,7 +107,7 @@ pub fn parse_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
         let token_line = reader.read_token_line()?;
         let mut iter = token_line.iter();
 
-        let node_tag = iter.parse_usize("nodeTag")?;
+        let node_tag = iter.parse_node_tag("nodeTag")?;
         let values = iter.parse_floats(num_components, "value")?;
         iter.expect_no_more()?;
 
@@ -81,71 +116,19 @@ pub fn parse_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
 
     mesh.node_data.push(node_data);
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("NodeData")?;
+    reader.read_end_marker("NodeData")?;
     Ok(())
 }
 
 /// Parse $ElementData section
 pub fn parse_element_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
+    let tags = parse_tags(reader)?;
+    let (num_components, num_entities) = (tags.num_components, tags.num_entities);
     let mut element_data = ElementData {
-        string_tags: Vec::new(),
-        real_tags: Vec::new(),
-        integer_tags: Vec::new(),
-        data: Vec::new(),
-    };
-
-    // Read string tags
-    let token_line = reader.read_token_line()?;
-    let mut iter = token_line.iter();
-    let num_string_tags = iter.parse_usize("numStringTags")?;
-    iter.expect_no_more()?;
-
-    for _ in 0..num_string_tags {
-        let token_line = reader.read_token_line()?;
-        let mut iter = token_line.iter();
-        let tag = iter.parse_quoted_string_to_line_end()?;
-        element_data.string_tags.push(tag);
-    }
-
-    // Read real tags
-    let token_line = reader.read_token_line()?;
-    let mut iter = token_line.iter();
-    let num_real_tags = iter.parse_usize("numRealTags")?;
-    iter.expect_no_more()?;
-
-    for _ in 0..num_real_tags {
-        let token_line = reader.read_token_line()?;
-        let mut iter = token_line.iter();
-        let tag = iter.parse_float("realTag")?;
-        iter.expect_no_more()?;
-        element_data.real_tags.push(tag);
-    }
-
-    // Read integer tags
-    let token_line = reader.read_token_line()?;
-    let mut iter = token_line.iter();
-    let num_integer_tags = iter.parse_usize("numIntegerTags")?;
-    iter.expect_no_more()?;
-
-    for _ in 0..num_integer_tags {
-        let token_line = reader.read_token_line()?;
-        let mut iter = token_line.iter();
-        let tag = iter.parse_int("integerTag")?;
-        iter.expect_no_more()?;
-        element_data.integer_tags.push(tag);
-    }
-
-    // Get number of components and entities from integer tags
-    let num_components = if element_data.integer_tags.len() >= 2 {
-        element_data.integer_tags[1] as usize
-    } else {
-        1
-    };
-    let num_entities = if element_data.integer_tags.len() >= 3 {
-        element_data.integer_tags[2] as usize
-    } else {
-        0
+        string_tags: tags.string_tags,
+        real_tags: tags.real_tags,
+        integer_tags: tags.integer_tags,
+        data: Vec::with_capacity(super::capacity_hint(num_entities)),
     };
 
     // Read data
@@ -153,7 +136,7 @@ pub fn parse_element_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()
         let token_line = reader.read_token_line()?;
         let mut iter = token_line.iter();
 
-        let element_tag = iter.parse_usize("elementTag")?;
+        let element_tag = iter.parse_element_tag("elementTag")?;
         let values = iter.parse_floats(num_components, "value")?;
         iter.expect_no_more()?;
 
@@ -162,71 +145,19 @@ pub fn parse_element_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()
 
     mesh.element_data.push(element_data);
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("ElementData")?;
+    reader.read_end_marker("ElementData")?;
     Ok(())
 }
 
 /// Parse $ElementNodeData section
 pub fn parse_element_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
+    let tags = parse_tags(reader)?;
+    let (num_components, num_entities) = (tags.num_components, tags.num_entities);
     let mut element_node_data = ElementNodeData {
-        string_tags: Vec::new(),
-        real_tags: Vec::new(),
-        integer_tags: Vec::new(),
-        data: Vec::new(),
-    };
-
-    // Read string tags
-    let token_line = reader.read_token_line()?;
-    let mut iter = token_line.iter();
-    let num_string_tags = iter.parse_usize("numStringTags")?;
-    iter.expect_no_more()?;
-
-    for _ in 0..num_string_tags {
-        let token_line = reader.read_token_line()?;
-        let mut iter = token_line.iter();
-        let tag = iter.parse_quoted_string_to_line_end()?;
-        element_node_data.string_tags.push(tag);
-    }
-
-    // Read real tags
-    let token_line = reader.read_token_line()?;
-    let mut iter = token_line.iter();
-    let num_real_tags = iter.parse_usize("numRealTags")?;
-    iter.expect_no_more()?;
-
-    for _ in 0..num_real_tags {
-        let token_line = reader.read_token_line()?;
-        let mut iter = token_line.iter();
-        let tag = iter.parse_float("realTag")?;
-        iter.expect_no_more()?;
-        element_node_data.real_tags.push(tag);
-    }
-
-    // Read integer tags
-    let token_line = reader.read_token_line()?;
-    let mut iter = token_line.iter();
-    let num_integer_tags = iter.parse_usize("numIntegerTags")?;
-    iter.expect_no_more()?;
-
-    for _ in 0..num_integer_tags {
-        let token_line = reader.read_token_line()?;
-        let mut iter = token_line.iter();
-        let tag = iter.parse_int("integerTag")?;
-        iter.expect_no_more()?;
-        element_node_data.integer_tags.push(tag);
-    }
-
-    // Get number of components and entities from integer tags
-    let num_components = if element_node_data.integer_tags.len() >= 2 {
-        element_node_data.integer_tags[1] as usize
-    } else {
-        1
-    };
-    let num_entities = if element_node_data.integer_tags.len() >= 3 {
-        element_node_data.integer_tags[2] as usize
-    } else {
-        0
+        string_tags: tags.string_tags,
+        real_tags: tags.real_tags,
+        integer_tags: tags.integer_tags,
+        data: Vec::with_capacity(super::capacity_hint(num_entities)),
     };
 
     // Read data
@@ -234,10 +165,12 @@ pub fn parse_element_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Resu
         let token_line = reader.read_token_line()?;
         let mut iter = token_line.iter();
 
-        let element_tag = iter.parse_usize("elementTag")?;
+        let element_tag = iter.parse_element_tag("elementTag")?;
         let num_nodes_per_element = iter.parse_usize("numNodesPerElement")?;
 
-        let total_values = num_components * num_nodes_per_element;
+        let total_values = num_components
+            .checked_mul(num_nodes_per_element)
+            .ok_or_else(|| token_line.invalid_format("Number of values overflows"))?;
         let values = iter.parse_floats(total_values, "value")?;
         iter.expect_no_more()?;
 
@@ -248,7 +181,44 @@ pub fn parse_element_node_data(reader: &mut LineReader, mesh: &mut Mesh) -> Resu
 
     mesh.element_node_data.push(element_node_data);
 
-    let token_line = reader.read_token_line()?;
-    token_line.expect_end_marker("ElementNodeData")?;
+    reader.read_end_marker("ElementNodeData")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ParseError;
+    use crate::parse_msh;
+
+    const VIEW: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n1 2 1 2\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+        $ElementData\n2\n\"v\"\n\"scheme\"\n1\n0.5\n4\n3\n3\n1\n2\n1 1 0 0\n$EndElementData\n";
+
+    #[test]
+    fn test_integer_tag_accessors() {
+        let content = VIEW
+            .replace("$ElementData", "$NodeData")
+            .replace("$EndElementData", "$EndNodeData");
+        let mesh = parse_msh(content).unwrap();
+        let view = &mesh.node_data[0];
+        assert_eq!(view.name(), Some("v"));
+        assert_eq!(view.interpolation_scheme(), Some("scheme"));
+        assert_eq!(view.time(), Some(0.5));
+        assert_eq!(view.time_step(), Some(3));
+        assert_eq!(view.num_components(), Some(3));
+        assert_eq!(view.num_entities(), Some(1));
+        assert_eq!(view.partition(), Some(2));
+
+        // Too few integer tags, no components, negative entity count
+        for bad in [
+            VIEW.replace("4\n3\n3\n1\n2\n", "2\n3\n3\n"),
+            VIEW.replace("4\n3\n3\n1\n2\n", "4\n3\n0\n1\n2\n"),
+            VIEW.replace("4\n3\n3\n1\n2\n", "4\n3\n3\n-1\n2\n"),
+        ] {
+            assert!(matches!(
+                parse_msh(bad),
+                Err(ParseError::InvalidFormat { .. })
+            ));
+        }
+    }
+}
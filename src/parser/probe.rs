@@ -0,0 +1,270 @@
+//! Summary of a MSH file read from its section headers
+
+use super::token::TokenIter;
+use super::{mesh_format, parse_section_header, physical_names, SourceFile};
+use crate::error::{ParseError, Result};
+use crate::types::{ElementTag, Mesh, MeshFormat, NodeTag, PhysicalName, SectionHeader};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// What [`probe_msh_file`] found in a MSH file
+#[derive(Debug, Clone)]
+pub struct MshInfo {
+    pub format: MeshFormat,
+    /// Sections in file order, named without the leading `$`, `MeshFormat`
+    /// included
+    pub sections: Vec<String>,
+    pub nodes_header: Option<SectionHeader<NodeTag>>,
+    pub elements_header: Option<SectionHeader<ElementTag>>,
+    /// Numbers of points, curves, surfaces and volumes in `$Entities`
+    pub num_entities: Option<[usize; 4]>,
+    /// Number of partitions in `$PartitionedEntities`
+    pub num_partitions: Option<usize>,
+    pub physical_names: Vec<PhysicalName>,
+    /// Number of bytes read, the size of the file for [`probe_msh_file`]
+    pub size: u64,
+}
+
+impl MshInfo {
+    /// Whether the section `name`, with or without its leading `$`, is present
+    pub fn has_section(&self, name: &str) -> bool {
+        let name = name.strip_prefix('$').unwrap_or(name);
+        self.sections.iter().any(|s| s == name)
+    }
+
+    /// Number of nodes announced by the `$Nodes` header
+    pub fn num_nodes(&self) -> usize {
+        self.nodes_header.as_ref().map_or(0, |h| h.count)
+    }
+
+    /// Number of elements announced by the `$Elements` header
+    pub fn num_elements(&self) -> usize {
+        self.elements_header.as_ref().map_or(0, |h| h.count)
+    }
+}
+
+/// Read the section headers of a MSH file without parsing its data
+///
+/// Only `$MeshFormat`, `$PhysicalNames` and the first line of `$Nodes`,
+/// `$Elements`, `$Entities` and `$PartitionedEntities` are parsed; the other
+/// lines are read one at a time and dropped, so memory use does not depend on
+/// the size of the file. Counts are those announced by the headers, which are
+/// not checked against the data.
+///
+/// ```no_run
+/// use gmsh_parser::probe_msh_file;
+///
+/// let info = probe_msh_file("huge.msh").unwrap();
+/// println!("{} nodes, {} elements", info.num_nodes(), info.num_elements());
+/// for group in &info.physical_names {
+///     println!("{} {}", group.tag, group.name);
+/// }
+/// ```
+///
+/// # Errors
+/// Returns an error if the file cannot be read, does not start with a valid
+/// `$MeshFormat` section, or a parsed header is malformed. Compressed files
+/// are not supported.
+pub fn probe_msh_file<P: AsRef<Path>>(path: P) -> Result<MshInfo> {
+    probe_msh_reader(BufReader::new(File::open(path)?))
+}
+
+/// Read the section headers of MSH data from `reader`, see [`probe_msh_file`]
+pub fn probe_msh_reader<R: BufRead>(reader: R) -> Result<MshInfo> {
+    let mut lines = Lines {
+        reader,
+        line: String::new(),
+        size: 0,
+    };
+
+    // `$MeshFormat` must come first
+    let mut text = lines.next_nonempty()?.to_string();
+    if text.trim() == "$MeshFormat" {
+        text.push_str(&lines.section_text("MeshFormat")?);
+    }
+    let format = mesh_format::parse(&mut SourceFile::new(text).to_line_reader())?;
+
+    let mut info = MshInfo {
+        format,
+        sections: vec!["MeshFormat".to_string()],
+        nodes_header: None,
+        elements_header: None,
+        num_entities: None,
+        num_partitions: None,
+        physical_names: Vec::new(),
+        size: 0,
+    };
+    while lines.next()? {
+        let Some(name) = lines.section_name() else {
+            continue;
+        };
+        info.sections.push(name.clone());
+        match name.as_str() {
+            "Nodes" => {
+                let line = lines.next_nonempty()?.to_string();
+                info.nodes_header = Some(parse_first_line(line, |iter| {
+                    parse_section_header(iter, "Node")
+                })?);
+            }
+            "Elements" => {
+                let line = lines.next_nonempty()?.to_string();
+                info.elements_header = Some(parse_first_line(line, |iter| {
+                    parse_section_header(iter, "Element")
+                })?);
+            }
+            "Entities" => {
+                let line = lines.next_nonempty()?.to_string();
+                info.num_entities = Some(parse_first_line(line, |iter| {
+                    Ok([
+                        iter.parse_usize("numPoints")?,
+                        iter.parse_usize("numCurves")?,
+                        iter.parse_usize("numSurfaces")?,
+                        iter.parse_usize("numVolumes")?,
+                    ])
+                })?);
+            }
+            "PartitionedEntities" => {
+                let line = lines.next_nonempty()?.to_string();
+                info.num_partitions = Some(parse_first_line(line, |iter| {
+                    iter.parse_usize("numPartitions")
+                })?);
+            }
+            "PhysicalNames" => {
+                let text = lines.section_text(&name)?;
+                let mut mesh = Mesh::new(info.format.clone());
+                physical_names::parse(&mut SourceFile::new(text).to_line_reader(), &mut mesh)?;
+                info.physical_names = mesh.physical_names;
+                continue;
+            }
+            _ => {}
+        }
+        lines.skip_to_end(&name)?;
+    }
+    info.size = lines.size;
+    Ok(info)
+}
+
+/// Parse the tokens of `line` with `parse`, which may leave some unread
+fn parse_first_line<T>(line: String, parse: impl FnOnce(&mut TokenIter) -> Result<T>) -> Result<T> {
+    let token_line = SourceFile::new(line).to_line_reader().read_token_line()?;
+    parse(&mut token_line.iter())
+}
+
+/// Lines of the input, read one at a time
+struct Lines<R> {
+    reader: R,
+    /// Last line read, terminator included
+    line: String,
+    /// Number of bytes read so far
+    size: u64,
+}
+
+impl<R: BufRead> Lines<R> {
+    /// Read the next line, or return `false` at the end of the input
+    fn next(&mut self) -> Result<bool> {
+        self.line.clear();
+        let read = self.reader.read_line(&mut self.line)?;
+        self.size += read as u64;
+        Ok(read > 0)
+    }
+
+    fn next_nonempty(&mut self) -> Result<&str> {
+        loop {
+            if !self.next()? {
+                return Err(ParseError::UnexpectedEof);
+            }
+            if !self.line.trim().is_empty() {
+                return Ok(&self.line);
+            }
+        }
+    }
+
+    /// Name of the section started by the last line, if it starts one
+    fn section_name(&self) -> Option<String> {
+        let first = self.line.split_whitespace().next()?;
+        let name = first.strip_prefix('$')?;
+        (!name.starts_with("End")).then(|| name.to_string())
+    }
+
+    /// Lines up to `$End<name>`, end marker included
+    fn section_text(&mut self, name: &str) -> Result<String> {
+        let mut text = String::new();
+        loop {
+            text.push_str(self.next_nonempty()?);
+            if self.is_end_of(name) {
+                return Ok(text);
+            }
+        }
+    }
+
+    fn skip_to_end(&mut self, name: &str) -> Result<()> {
+        loop {
+            if !self.next()? {
+                return Err(ParseError::UnexpectedEof);
+            }
+            if self.is_end_of(name) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn is_end_of(&self, name: &str) -> bool {
+        self.line
+            .trim()
+            .strip_prefix("$End")
+            .is_some_and(|end| end == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::NAMED_SQUARE_ON_CURVES;
+
+    #[test]
+    fn test_probe_reads_headers() {
+        let info = probe_msh_reader(NAMED_SQUARE_ON_CURVES.as_bytes()).unwrap();
+        assert_eq!(info.format.data_size, 8);
+        assert_eq!((info.num_nodes(), info.num_elements()), (4, 5));
+        assert_eq!(
+            info.nodes_header,
+            crate::parse_msh(NAMED_SQUARE_ON_CURVES)
+                .unwrap()
+                .nodes_header
+        );
+        assert_eq!(info.num_entities, Some([0, 3, 1, 0]));
+        let names: Vec<_> = info.physical_names.iter().map(|n| &n.name[..]).collect();
+        assert_eq!(names, ["boundary", "My surface"]);
+        assert_eq!(
+            info.sections,
+            [
+                "MeshFormat",
+                "PhysicalNames",
+                "Entities",
+                "Nodes",
+                "Elements"
+            ]
+        );
+        assert!(info.has_section("$Entities") && !info.has_section("Periodic"));
+        assert_eq!(info.size, NAMED_SQUARE_ON_CURVES.len() as u64);
+    }
+
+    #[test]
+    fn test_probe_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("square.msh");
+        std::fs::write(&path, NAMED_SQUARE_ON_CURVES).unwrap();
+        let info = probe_msh_file(&path).unwrap();
+        assert_eq!(info.num_nodes(), 4);
+        assert_eq!(info.size, std::fs::metadata(&path).unwrap().len());
+    }
+
+    #[test]
+    fn test_probe_rejects_truncated_section() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n1 2 1 2\n";
+        let info = probe_msh_reader(content.as_bytes());
+        assert!(matches!(info, Err(ParseError::UnexpectedEof)));
+        assert!(probe_msh_reader("$Nodes\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! Progress reports and cancellation of long parses
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Bytes read between two reports in the middle of a section
+const REPORT_INTERVAL: usize = 1 << 20;
+
+/// How far a parse has got, see [`ParserOptions::progress`](super::ParserOptions::progress)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseProgress {
+    /// Bytes of the input consumed so far
+    pub bytes_read: usize,
+    /// Size of the input in bytes, after decompression
+    pub total_bytes: usize,
+    /// Section being parsed, without the leading `$`, if any
+    pub section: Option<String>,
+    /// Node and element blocks parsed so far, skipped ones included
+    pub blocks_parsed: usize,
+}
+
+impl ParseProgress {
+    /// Fraction of the input consumed, between 0 and 1
+    pub fn fraction(&self) -> f64 {
+        if self.total_bytes == 0 {
+            1.0
+        } else {
+            self.bytes_read as f64 / self.total_bytes as f64
+        }
+    }
+}
+
+/// Function receiving [`ParseProgress`] reports
+///
+/// Clones share the same function. Two callbacks are equal if they are clones
+/// of each other.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<Mutex<dyn FnMut(ParseProgress) + Send>>);
+
+impl ProgressCallback {
+    pub fn new(callback: impl FnMut(ParseProgress) + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(callback)))
+    }
+
+    fn call(&self, progress: ParseProgress) {
+        if let Ok(mut callback) = self.0.lock() {
+            callback(progress);
+        }
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback(..)")
+    }
+}
+
+impl PartialEq for ProgressCallback {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ProgressCallback {}
+
+/// Flag stopping a parse from another thread, see
+/// [`ParserOptions::cancellation`](super::ParserOptions::cancellation)
+///
+/// Clones share the same flag. The parser checks it between sections and
+/// between node and element blocks, and returns
+/// [`ParseError::Cancelled`](crate::ParseError::Cancelled) once it is set.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the parses using this token to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancellationToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
+
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+/// State of the reports of one parse
+pub(crate) struct ProgressTracker {
+    callback: ProgressCallback,
+    total_bytes: usize,
+    section: Option<String>,
+    blocks_parsed: usize,
+    next_report: usize,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(callback: ProgressCallback, total_bytes: usize) -> Self {
+        Self {
+            callback,
+            total_bytes,
+            section: None,
+            blocks_parsed: 0,
+            next_report: 0,
+        }
+    }
+
+    /// Report the start of `section`, given with or without its `$`
+    pub(crate) fn begin_section(&mut self, section: &str, offset: usize) {
+        self.section = Some(section.trim_start_matches('$').to_string());
+        self.report(offset);
+    }
+
+    pub(crate) fn block_parsed(&mut self, offset: usize) {
+        self.blocks_parsed += 1;
+        self.advance(offset);
+    }
+
+    /// Report if enough of the input was read since the last report
+    pub(crate) fn advance(&mut self, offset: usize) {
+        if offset >= self.next_report {
+            self.report(offset);
+        }
+    }
+
+    /// Report the end of the input
+    pub(crate) fn finish(&mut self) {
+        self.section = None;
+        self.report(self.total_bytes);
+    }
+
+    fn report(&mut self, offset: usize) {
+        self.next_report = offset.saturating_add(REPORT_INTERVAL);
+        self.callback.call(ParseProgress {
+            bytes_read: offset,
+            total_bytes: self.total_bytes,
+            section: self.section.clone(),
+            blocks_parsed: self.blocks_parsed,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CancellationToken;
+    use crate::fixtures::SQUARE_WITH_LINE;
+    use crate::{parse_msh_with, ParseError, ParserOptions};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_progress_reports() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let options = ParserOptions::default().progress(move |p| sink.lock().unwrap().push(p));
+        parse_msh_with(SQUARE_WITH_LINE, &options).unwrap();
+
+        let reports = reports.lock().unwrap();
+        let last = reports.last().unwrap();
+        assert_eq!(last.fraction(), 1.0);
+        assert_eq!(last.section, None);
+        assert_eq!(last.blocks_parsed, 3);
+        assert!(reports
+            .iter()
+            .any(|p| p.section.as_deref() == Some("Nodes")));
+        assert!(reports
+            .windows(2)
+            .all(|w| w[0].bytes_read <= w[1].bytes_read));
+    }
+
+    #[test]
+    fn test_options_with_progress_compare_equal() {
+        let options = ParserOptions::default().progress(|_| {});
+        assert_eq!(options, options.clone());
+        assert_ne!(options, ParserOptions::default());
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let token = CancellationToken::new();
+        let options = ParserOptions::default().cancellation(token.clone());
+        assert!(parse_msh_with(SQUARE_WITH_LINE, &options).is_ok());
+
+        // Cancel from the progress callback once the nodes are reached
+        let canceller = token.clone();
+        let options = options.progress(move |p| {
+            if p.section.as_deref() == Some("Nodes") {
+                canceller.cancel();
+            }
+        });
+        let result = parse_msh_with(SQUARE_WITH_LINE, &options);
+        assert!(matches!(result, Err(ParseError::Cancelled)));
+        assert!(token.is_cancelled());
+    }
+}
@@ -1,6 +1,9 @@
-use super::token::{Span, Token, TokenLine};
-use crate::error::{ParseError, Result};
-use std::io::{BufRead, BufReader, Cursor};
+use super::options::ParserOptions;
+use super::progress::ProgressTracker;
+use super::source_map::SourceMap;
+use super::token::{Span, TokenLine, WarningSink};
+use crate::error::{ParseError, ParseWarning, Result, WarningKind};
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -18,8 +21,20 @@ impl SourceFile {
         }
     }
 
+    /// Read the file at `path`, decompressing gzip and zstd data
     pub fn from_path<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
-        let content = std::fs::read_to_string(&path)?;
+        Self::from_bytes(std::fs::read(&path)?)
+    }
+
+    /// The content of a file read as bytes, decompressing gzip and zstd data
+    pub fn from_bytes(bytes: Vec<u8>) -> std::io::Result<Self> {
+        let bytes = super::compression::decompress(&bytes)?.unwrap_or(bytes);
+        let content = String::from_utf8(bytes).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })?;
         Ok(Self::new(content))
     }
 
@@ -31,64 +46,242 @@ impl SourceFile {
 
 /// Line reader that tracks positions and generates tokens
 pub struct LineReader {
-    lines: std::io::Lines<BufReader<Cursor<Vec<u8>>>>,
     source: Arc<String>,
     current_offset: usize,
+    source_map: Option<SourceMap>,
+    options: ParserOptions,
+    /// Recovered problems, only collected in lenient mode
+    warnings: Option<WarningSink>,
+    progress: Option<ProgressTracker>,
 }
 
 impl LineReader {
     pub fn new(source: SourceFile) -> Self {
-        let bytes = source.content.as_bytes().to_vec();
-        let cursor = Cursor::new(bytes);
-        let reader = BufReader::new(cursor);
-
         Self {
-            lines: reader.lines(),
             source: source.content,
             current_offset: 0,
+            source_map: None,
+            options: ParserOptions::default(),
+            warnings: None,
+            progress: None,
+        }
+    }
+
+    /// Record the spans of nodes and elements while reading
+    pub fn with_source_map(mut self) -> Self {
+        self.source_map = Some(SourceMap::new(Arc::clone(&self.source)));
+        self
+    }
+
+    /// The source map being recorded, if enabled
+    pub(crate) fn source_map_mut(&mut self) -> Option<&mut SourceMap> {
+        self.source_map.as_mut()
+    }
+
+    /// Parse with `options` instead of the defaults
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.warnings = options.lenient.then(WarningSink::default);
+        self.progress = options
+            .progress
+            .clone()
+            .map(|callback| ProgressTracker::new(callback, self.source.len()));
+        self.options = options;
+        self
+    }
+
+    /// The options the sections are parsed with
+    pub(crate) fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// Whether recoverable problems are recorded as warnings instead of errors
+    pub(crate) fn is_lenient(&self) -> bool {
+        self.warnings.is_some()
+    }
+
+    /// Record a recovered problem, in lenient mode
+    pub(crate) fn warn(&self, warning: ParseWarning) {
+        if let Some(Ok(mut warnings)) = self.warnings.as_ref().map(|w| w.lock()) {
+            warnings.push(warning);
+        }
+    }
+
+    /// Take the warnings recorded so far
+    pub(crate) fn take_warnings(&mut self) -> Vec<ParseWarning> {
+        match self.warnings.as_ref().map(|w| w.lock()) {
+            Some(Ok(mut warnings)) => std::mem::take(&mut *warnings),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Report the start of a section to the progress callback, if any
+    pub(crate) fn begin_section(&mut self, section: &str) {
+        if let Some(progress) = &mut self.progress {
+            progress.begin_section(section, self.current_offset);
         }
     }
 
-    fn next_line(&mut self) -> Result<String> {
-        let line = self
-            .lines
-            .next()
-            .ok_or(ParseError::UnexpectedEof)?
-            .expect("I/O error cannot occur when reading from Cursor");
-        self.current_offset += line.len() + 1;
-        Ok(line)
+    /// Count a node or element block for the progress callback, if any, and
+    /// check for cancellation
+    pub(crate) fn block_parsed(&mut self) -> Result<()> {
+        if let Some(progress) = &mut self.progress {
+            progress.block_parsed(self.current_offset);
+        }
+        self.check_cancelled()
+    }
+
+    /// Fail with [`ParseError::Cancelled`] if the parse was cancelled
+    pub(crate) fn check_cancelled(&self) -> Result<()> {
+        match &self.options.cancellation {
+            Some(token) if token.is_cancelled() => Err(ParseError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Report the end of the input to the progress callback, if any
+    pub(crate) fn finish_progress(&mut self) {
+        if let Some(progress) = &mut self.progress {
+            progress.finish();
+        }
+    }
+
+    /// Take the recorded source map, leaving recording disabled
+    pub fn take_source_map(&mut self) -> Option<SourceMap> {
+        self.source_map.take()
+    }
+
+    /// Byte range of the next line, without its line terminator
+    fn next_line(&mut self) -> Result<Range<usize>> {
+        let start = self.current_offset;
+        let rest = self.source.get(start..).unwrap_or_default();
+        if rest.is_empty() {
+            return Err(ParseError::UnexpectedEof);
+        }
+
+        let (len, advance) = match rest.find('\n') {
+            Some(newline) => (newline, newline + 1),
+            None => (rest.len(), rest.len()),
+        };
+        self.current_offset += advance;
+        if let Some(progress) = &mut self.progress {
+            progress.advance(self.current_offset);
+        }
+        Ok(start..start + len)
     }
 
     /// Read the next non-empty line and tokenize it
     pub fn read_token_line(&mut self) -> Result<TokenLine> {
         loop {
-            let line_start_offset = self.current_offset;
-            let line = self.next_line()?;
+            let range = self.next_line()?;
+            let line_start_offset = range.start;
+            let line = &self.source[range];
+
+            // Tokenize the line; `\r` of CRLF files counts as whitespace
+            let tokens: Vec<Span> = line
+                .split_whitespace()
+                .map(|word| {
+                    // Position of this word in the original line
+                    let word_start = word.as_ptr() as usize - line.as_ptr() as usize;
+                    Span::new(line_start_offset + word_start, word.len())
+                })
+                .collect();
 
-            if line.trim().is_empty() {
+            if tokens.is_empty() {
                 continue;
             }
+            return TokenLine::from_spans(Arc::clone(&self.source), tokens)
+                .map(|line| line.with_warnings(self.warnings.clone()));
+        }
+    }
 
-            // Tokenize the line
-            let mut tokens = Vec::new();
-            let mut current_pos = 0;
+    /// Skip the next `count` non-empty lines without tokenizing them
+    pub(crate) fn skip_lines(&mut self, count: usize) -> Result<()> {
+        let mut skipped = 0;
+        while skipped < count {
+            let range = self.next_line()?;
+            if !self.source[range].trim().is_empty() {
+                skipped += 1;
+            }
+        }
+        Ok(())
+    }
 
-            for word in line.split_whitespace() {
-                // Find the position of this word in the original line
-                let word_start = line[current_pos..].find(word).unwrap() + current_pos;
-                let byte_offset = line_start_offset + word_start;
+    /// Read the `$End<section_name>` line closing a section
+    ///
+    /// In lenient mode, a marker missing before the next section or the end
+    /// of the file is recorded as a warning and the next section is left to
+    /// be read.
+    pub(crate) fn read_end_marker(&mut self, section_name: &str) -> Result<()> {
+        let line_start = self.current_offset;
+        let token_line = match self.read_token_line() {
+            Err(ParseError::UnexpectedEof) if self.is_lenient() => {
+                let message = format!("Missing $End{} at end of file", section_name);
+                self.warn(ParseWarning::new(message).with_kind(WarningKind::MissingEndMarker));
+                return Ok(());
+            }
+            result => result?,
+        };
 
-                let token = Token::new(
-                    word.to_string(),
-                    Span::new(byte_offset, word.len()),
-                    Arc::clone(&self.source),
-                );
+        match token_line.expect_end_marker(section_name) {
+            Err(ParseError::ExpectedEndOfSection { found, span, .. })
+                if self.is_lenient() && found.starts_with('$') && !found.starts_with("$End") =>
+            {
+                let message = format!("Missing $End{} before {}", section_name, found);
+                let warning = ParseWarning::at(message, span);
+                self.warn(warning.with_kind(WarningKind::MissingEndMarker));
+                self.current_offset = line_start;
+                Ok(())
+            }
+            result => result,
+        }
+    }
 
-                tokens.push(token);
-                current_pos = word_start + word.len();
+    /// Read the lines up to `$End<section_name>` as they are, returning them
+    /// with the offset right after the end marker
+    pub(crate) fn read_raw_lines(&mut self, section_name: &str) -> Result<(Vec<String>, usize)> {
+        let mut lines = Vec::new();
+        let end = self.scan_to_end_marker(section_name, |line| lines.push(line.to_string()))?;
+        Ok((lines, end))
+    }
+
+    /// Skip the lines up to `$End<section_name>` without tokenizing them
+    pub(crate) fn skip_raw_section(&mut self, section_name: &str) -> Result<()> {
+        self.scan_to_end_marker(section_name, |_| {}).map(|_| ())
+    }
+
+    /// Name of the next section, `$` included, skipping any content outside
+    /// of sections, or `None` at the end of the file
+    ///
+    /// Only looks at the first character of each line.
+    pub(crate) fn next_section_name(&mut self) -> Result<Option<String>> {
+        loop {
+            let range = match self.next_line() {
+                Ok(range) => range,
+                Err(ParseError::UnexpectedEof) => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            let line = self.source[range].trim();
+            if line.starts_with('$') {
+                return Ok(line.split_whitespace().next().map(str::to_string));
             }
+        }
+    }
 
-            return Ok(TokenLine::new(tokens));
+    /// Call `on_line` with each line up to `$End<section_name>`, line
+    /// terminator removed, and return the offset right after the end marker
+    fn scan_to_end_marker(
+        &mut self,
+        section_name: &str,
+        mut on_line: impl FnMut(&str),
+    ) -> Result<usize> {
+        let end_marker = format!("$End{}", section_name);
+        loop {
+            let range = self.next_line()?;
+            let line = self.source[range.clone()].trim_end_matches('\r');
+            if line.trim() == end_marker {
+                return Ok(range.end);
+            }
+            on_line(line);
         }
     }
 }
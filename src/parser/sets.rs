@@ -0,0 +1,98 @@
+//! Parser for the crate-defined $NodeSets and $ElementSets sections
+//!
+//! Each set is written as a header line `setTag numTags "name"` (a set tag of 0
+//! means the set has no tag) followed by its tags, spread over as many lines as
+//! needed.
+
+use crate::error::Result;
+use crate::sets::{ElementSet, NodeSet};
+use crate::types::{ElementTag, Mesh, NodeTag};
+
+use super::LineReader;
+
+pub fn parse_node_sets(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
+    for (name, tag, tags) in parse_sets(reader, "NodeSets")? {
+        let set = NodeSet::new(name, tags.into_iter().map(NodeTag));
+        mesh.node_sets.push(match tag {
+            0 => set,
+            tag => set.with_tag(tag),
+        });
+    }
+    Ok(())
+}
+
+pub fn parse_element_sets(reader: &mut LineReader, mesh: &mut Mesh) -> Result<()> {
+    for (name, tag, tags) in parse_sets(reader, "ElementSets")? {
+        let set = ElementSet::new(name, tags.into_iter().map(ElementTag));
+        mesh.element_sets.push(match tag {
+            0 => set,
+            tag => set.with_tag(tag),
+        });
+    }
+    Ok(())
+}
+
+fn parse_sets(reader: &mut LineReader, section: &str) -> Result<Vec<(String, i32, Vec<u64>)>> {
+    let token_line = reader.read_token_line()?;
+    let mut iter = token_line.iter();
+    let num_sets = iter.parse_usize("numSets")?;
+    iter.expect_no_more()?;
+
+    let mut sets = Vec::new();
+    for _ in 0..num_sets {
+        let token_line = reader.read_token_line()?;
+        let mut iter = token_line.iter();
+        let set_tag = iter.parse_int("setTag")?;
+        let num_tags = iter.parse_usize("numTags")?;
+        let name = iter.parse_quoted_string_to_line_end()?;
+
+        let mut tags = Vec::new();
+        while tags.len() < num_tags {
+            let token_line = reader.read_token_line()?;
+            let mut iter = token_line.iter();
+            while iter.has_next() && tags.len() < num_tags {
+                tags.push(iter.parse_unsigned("tag")?);
+            }
+            iter.expect_no_more()?;
+        }
+
+        sets.push((name, set_tag, tags));
+    }
+
+    reader.read_end_marker(section)?;
+
+    Ok(sets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_parse_node_sets() {
+        let data = r#"2
+3 4 "inlet nodes"
+1 2
+5 8
+0 0 "empty"
+$EndNodeSets
+"#;
+
+        let source_file = SourceFile::new(data.into());
+        let mut reader = LineReader::new(source_file);
+        let mut mesh = Mesh::dummy();
+
+        parse_node_sets(&mut reader, &mut mesh).unwrap();
+
+        assert_eq!(mesh.node_sets.len(), 2);
+        assert_eq!(mesh.node_sets[0].name, "inlet nodes");
+        assert_eq!(mesh.node_sets[0].tag, Some(3));
+        assert_eq!(
+            mesh.node_sets[0].iter().collect::<Vec<_>>(),
+            vec![1, 2, 5, 8]
+        );
+        assert_eq!(mesh.node_sets[1].tag, None);
+        assert!(mesh.node_sets[1].is_empty());
+    }
+}
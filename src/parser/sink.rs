@@ -0,0 +1,258 @@
+use super::{
+    elements, mesh_format, nodes, parse_section_header, skip_section, ElementBlockHeader,
+    LineReader, NodeBlockHeader, SourceFile,
+};
+use crate::error::{ParseError, ParseWarning, Result, WarningKind};
+use crate::types::{ElementTag, Mesh, NodeTag, SectionHeader};
+use std::path::Path;
+
+/// Receiver of the nodes of a `$Nodes` section, see [`parse_nodes_into`]
+///
+/// Lets a solver write coordinates straight into its own arrays instead of
+/// going through the node blocks of a [`Mesh`].
+pub trait NodeSink {
+    /// Called once before any node with the section header, e.g. to allocate
+    /// storage for `header.count` nodes
+    fn begin_section(&mut self, header: &SectionHeader<NodeTag>) {
+        let _ = header;
+    }
+
+    /// Called at the start of each entity block
+    fn begin_block(&mut self, header: &NodeBlockHeader) {
+        let _ = header;
+    }
+
+    /// Called for every node in file order; `parametric` is empty unless the
+    /// block is parametric
+    fn node(&mut self, tag: NodeTag, coordinates: [f64; 3], parametric: &[f64]);
+}
+
+/// Receiver of the elements of an `$Elements` section, see
+/// [`parse_elements_into`]
+pub trait ElementSink {
+    /// Called once before any element with the section header
+    fn begin_section(&mut self, header: &SectionHeader<ElementTag>) {
+        let _ = header;
+    }
+
+    /// Called at the start of each entity block, whose header gives the
+    /// element type of the elements that follow
+    fn begin_block(&mut self, header: &ElementBlockHeader) {
+        let _ = header;
+    }
+
+    /// Called for every element in file order with its node tags
+    fn element(&mut self, tag: ElementTag, nodes: &[NodeTag]);
+}
+
+/// Parse the `$Nodes` section of a MSH file into `sink`
+///
+/// Like [`parse_nodes_only`](super::parse_nodes_only), other sections are
+/// skipped, but no node is stored: the returned mesh only holds the format and
+/// [`Mesh::nodes_header`], along with any warnings.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or the section is malformed,
+/// in which case `sink` may have received part of the nodes.
+pub fn parse_nodes_into<P: AsRef<Path>>(path: P, sink: &mut impl NodeSink) -> Result<Mesh> {
+    let mut line_reader = SourceFile::from_path(&path)?.to_line_reader();
+    parse_into(&mut line_reader, Some(sink), None)
+}
+
+/// Parse the `$Elements` section of a MSH file into `sink`
+///
+/// The counterpart of [`parse_nodes_into`] for the connectivity.
+pub fn parse_elements_into<P: AsRef<Path>>(path: P, sink: &mut impl ElementSink) -> Result<Mesh> {
+    let mut line_reader = SourceFile::from_path(&path)?.to_line_reader();
+    parse_into(&mut line_reader, None, Some(sink))
+}
+
+/// Parse the `$Nodes` and `$Elements` sections of a MSH file into their sinks
+/// in a single pass, see [`parse_nodes_into`]
+pub fn parse_msh_file_into<P: AsRef<Path>>(
+    path: P,
+    nodes: &mut impl NodeSink,
+    elements: &mut impl ElementSink,
+) -> Result<Mesh> {
+    let mut line_reader = SourceFile::from_path(&path)?.to_line_reader();
+    parse_into(&mut line_reader, Some(nodes), Some(elements))
+}
+
+fn parse_into(
+    line_reader: &mut LineReader,
+    mut node_sink: Option<&mut dyn NodeSink>,
+    mut element_sink: Option<&mut dyn ElementSink>,
+) -> Result<Mesh> {
+    let mut mesh = mesh_format::parse_mesh(line_reader)?;
+    while let Some(name) = line_reader.next_section_name()? {
+        match (name.as_str(), &mut node_sink, &mut element_sink) {
+            ("$Nodes", Some(sink), _) => stream_nodes(line_reader, &mut mesh, *sink)?,
+            ("$Elements", _, Some(sink)) => stream_elements(line_reader, &mut mesh, *sink)?,
+            _ if name.starts_with("$End") => {}
+            _ => skip_section(line_reader, &name)?,
+        }
+        mesh.warnings.extend(line_reader.take_warnings());
+    }
+    Ok(mesh)
+}
+
+fn stream_nodes(reader: &mut LineReader, mesh: &mut Mesh, sink: &mut dyn NodeSink) -> Result<()> {
+    let header_line = reader.read_token_line()?;
+    let header = parse_section_header(&mut header_line.iter(), "Node")?;
+    sink.begin_section(&header);
+
+    let mut count = 0;
+    for _ in 0..header.num_entity_blocks {
+        let block = nodes::parse_block_header(reader)?;
+        sink.begin_block(&block);
+        for tag in nodes::parse_block_tags(reader, &block)? {
+            let node = nodes::parse_node(reader, &block, tag)?;
+            let parametric = node.parametric_coords.as_deref().unwrap_or_default();
+            sink.node(tag, [node.x, node.y, node.z], parametric);
+            count += 1;
+        }
+        reader.block_parsed()?;
+    }
+    reader.read_end_marker("Nodes")?;
+
+    check_count(reader, mesh, &header_line, header.count, count, "Node")?;
+    mesh.nodes_header = Some(header);
+    Ok(())
+}
+
+fn stream_elements(
+    reader: &mut LineReader,
+    mesh: &mut Mesh,
+    sink: &mut dyn ElementSink,
+) -> Result<()> {
+    let header_line = reader.read_token_line()?;
+    let header = parse_section_header(&mut header_line.iter(), "Element")?;
+    sink.begin_section(&header);
+
+    let mut count = 0;
+    let mut nodes = Vec::new();
+    for _ in 0..header.num_entity_blocks {
+        let block = elements::parse_block_header(reader)?;
+        sink.begin_block(&block);
+        for _ in 0..block.num_elements {
+            nodes.clear();
+            let tag = elements::read_element(reader, block.element_type, &mut nodes)?;
+            sink.element(tag, &nodes);
+        }
+        count += block.num_elements;
+        reader.block_parsed()?;
+    }
+    reader.read_end_marker("Elements")?;
+
+    check_count(reader, mesh, &header_line, header.count, count, "Element")?;
+    mesh.elements_header = Some(header);
+    Ok(())
+}
+
+/// Compare the number of items of a section with its header, as the full
+/// parser does; only the count is checked since the tags are not kept
+fn check_count(
+    reader: &LineReader,
+    mesh: &mut Mesh,
+    header_line: &super::TokenLine,
+    expected: usize,
+    actual: usize,
+    item: &str,
+) -> Result<()> {
+    if expected == actual {
+        return Ok(());
+    }
+    let mut iter = header_line.iter();
+    iter.parse_usize("numEntityBlocks")?;
+    let token = iter.peek_token()?;
+    let message = format!(
+        "{} count mismatch: header declares {}, but {} were parsed",
+        item, expected, actual
+    );
+    if !reader.options().validate_metadata || reader.is_lenient() {
+        let warning = ParseWarning::at(message, token.span);
+        mesh.warnings
+            .push(warning.with_kind(WarningKind::MetadataMismatch));
+        return Ok(());
+    }
+    Err(ParseError::InvalidData {
+        message,
+        span: token.span.to_source_span(),
+        msh_content: token.source.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SQUARE_WITH_LINE;
+
+    #[derive(Default)]
+    struct Arrays {
+        coordinates: Vec<f64>,
+        tags: Vec<NodeTag>,
+        connectivity: Vec<NodeTag>,
+        offsets: Vec<usize>,
+        blocks: usize,
+    }
+
+    impl NodeSink for Arrays {
+        fn begin_section(&mut self, header: &SectionHeader<NodeTag>) {
+            self.coordinates.reserve(3 * header.count);
+        }
+
+        fn node(&mut self, tag: NodeTag, coordinates: [f64; 3], _: &[f64]) {
+            self.tags.push(tag);
+            self.coordinates.extend(coordinates);
+        }
+    }
+
+    impl ElementSink for Arrays {
+        fn begin_block(&mut self, _: &ElementBlockHeader) {
+            self.blocks += 1;
+        }
+
+        fn element(&mut self, _: ElementTag, nodes: &[NodeTag]) {
+            self.connectivity.extend_from_slice(nodes);
+            self.offsets.push(self.connectivity.len());
+        }
+    }
+
+    #[test]
+    fn test_sinks_receive_nodes_and_elements() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("square.msh");
+        std::fs::write(&path, SQUARE_WITH_LINE).unwrap();
+        let mut nodes = Arrays::default();
+        let mut elements = Arrays::default();
+        let partial = parse_msh_file_into(&path, &mut nodes, &mut elements).unwrap();
+
+        assert_eq!(nodes.tags, [1, 2, 3, 4].map(NodeTag));
+        let expected = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        assert_eq!(nodes.coordinates, expected);
+        assert_eq!(elements.connectivity, [1, 2, 1, 2, 3, 1, 3, 4].map(NodeTag));
+        assert_eq!(elements.offsets, [2, 5, 8]);
+        assert_eq!(elements.blocks, 2);
+
+        // The headers are kept, the blocks went to the sinks
+        let mesh = crate::parse_msh(SQUARE_WITH_LINE).unwrap();
+        assert!(partial.node_blocks.is_empty() && partial.element_blocks.is_empty());
+        assert_eq!(partial.nodes_header, mesh.nodes_header);
+        assert_eq!(partial.elements_header, mesh.elements_header);
+    }
+
+    #[test]
+    fn test_sink_reports_count_mismatch() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+                       $Nodes\n1 2 1 2\n0 1 0 1\n1\n0 0 0\n$EndNodes\n";
+        let mut reader = SourceFile::new(content.to_string()).to_line_reader();
+        let mut sink = Arrays::default();
+        match parse_into(&mut reader, Some(&mut sink), None) {
+            Err(ParseError::InvalidData { message, .. }) => {
+                assert!(message.starts_with("Node count mismatch"))
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert_eq!(sink.tags, [NodeTag(1)]);
+    }
+}
@@ -0,0 +1,194 @@
+//! Locations of parsed nodes and elements in the source file
+//!
+//! A [`SourceMap`] is recorded on request by [`parse_msh_with_source_map`] and
+//! [`parse_msh_file_with_source_map`]. It lets checks written on top of the
+//! parsed mesh report problems as miette diagnostics pointing at the offending
+//! line, exactly like the parser's own errors:
+//!
+//! ```no_run
+//! use gmsh_parser::parser::{parse_msh_file_with_source_map, WithSpans};
+//!
+//! let (mesh, source_map) = parse_msh_file_with_source_map("model.msh").unwrap();
+//! let nodes = mesh.node_blocks.iter().flat_map(|b| &b.nodes);
+//! for (node, span) in nodes.with_spans(&source_map) {
+//!     if node.z < 0.0 {
+//!         if let Some(span) = span {
+//!             let error = source_map.invalid_data(span, "node below the ground plane");
+//!             println!("{:?}", miette::Report::new(error));
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Spans refer to the file as it was parsed; they are not updated when the mesh
+//! is modified afterwards.
+//!
+//! [`parse_msh_with_source_map`]: super::parse_msh_with_source_map
+//! [`parse_msh_file_with_source_map`]: super::parse_msh_file_with_source_map
+
+use super::Span;
+use crate::error::{Diagnostics, ParseError};
+use crate::types::element::Element;
+use crate::types::{ElementRef, ElementTag, Node, NodeTag};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Spans of the node and element lines of a parsed file
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    source: Arc<String>,
+    nodes: HashMap<NodeTag, Span>,
+    elements: HashMap<ElementTag, Span>,
+}
+
+impl SourceMap {
+    pub fn new(source: Arc<String>) -> Self {
+        Self {
+            source,
+            nodes: HashMap::new(),
+            elements: HashMap::new(),
+        }
+    }
+
+    /// Full content of the parsed file
+    pub fn source(&self) -> &Arc<String> {
+        &self.source
+    }
+
+    /// Span of the coordinate line of a node
+    pub fn node_span(&self, tag: NodeTag) -> Option<Span> {
+        self.nodes.get(&tag).copied()
+    }
+
+    /// Span of the line defining an element
+    pub fn element_span(&self, tag: ElementTag) -> Option<Span> {
+        self.elements.get(&tag).copied()
+    }
+
+    pub(crate) fn record_node(&mut self, tag: NodeTag, span: Span) {
+        self.nodes.insert(tag, span);
+    }
+
+    pub(crate) fn record_element(&mut self, tag: ElementTag, span: Span) {
+        self.elements.insert(tag, span);
+    }
+
+    /// An error labelling `span` of the parsed file with `message`, see [`Diagnostics::error_at`]
+    pub fn invalid_data(&self, span: Span, message: impl Into<String>) -> ParseError {
+        Diagnostics::error_at(span, message, Arc::clone(&self.source))
+    }
+}
+
+/// Parsed items whose originating line can be looked up in a [`SourceMap`]
+pub trait Located {
+    fn span_in(&self, source_map: &SourceMap) -> Option<Span>;
+}
+
+impl Located for &Node {
+    fn span_in(&self, source_map: &SourceMap) -> Option<Span> {
+        source_map.node_span(self.tag)
+    }
+}
+
+impl Located for &Element {
+    fn span_in(&self, source_map: &SourceMap) -> Option<Span> {
+        source_map.element_span(self.tag)
+    }
+}
+
+impl Located for ElementRef<'_> {
+    fn span_in(&self, source_map: &SourceMap) -> Option<Span> {
+        source_map.element_span(self.tag())
+    }
+}
+
+/// Iterator adapter pairing each item with its span, see [`WithSpans::with_spans`]
+pub struct Spanned<'m, I> {
+    inner: I,
+    source_map: &'m SourceMap,
+}
+
+impl<I> Iterator for Spanned<'_, I>
+where
+    I: Iterator,
+    I::Item: Located,
+{
+    type Item = (I::Item, Option<Span>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        let span = item.span_in(self.source_map);
+        Some((item, span))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Extension for iterators over nodes and elements
+pub trait WithSpans: Iterator + Sized
+where
+    Self::Item: Located,
+{
+    /// Pair every item with the span of its line, `None` if it was not parsed from the file
+    fn with_spans(self, source_map: &SourceMap) -> Spanned<'_, Self> {
+        Spanned {
+            inner: self,
+            source_map,
+        }
+    }
+}
+
+impl<I> WithSpans for I
+where
+    I: Iterator,
+    I::Item: Located,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filter::ElementFilter;
+    use crate::parser::{parse_msh_with_source_map, WithSpans};
+
+    const MESH: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n1 3 1 3\n2 1 0 3\n1\n2\n3\n0 0 0\n1 0 0\n0.5 1 0\n$EndNodes\n\
+        $Elements\n2 2 1 2\n1 1 1 1\n1 1 2\n2 1 2 1\n2 1 2 3\n$EndElements\n";
+
+    #[test]
+    fn test_spans_point_at_lines() {
+        let (mesh, source_map) = parse_msh_with_source_map(MESH).unwrap();
+
+        let nodes = mesh.node_blocks.iter().flat_map(|b| &b.nodes);
+        for (node, span) in nodes.with_spans(&source_map) {
+            let span = span.unwrap();
+            let line = &MESH[span.offset..span.offset + span.len];
+            let x: f64 = line.split_whitespace().next().unwrap().parse().unwrap();
+            assert_eq!(x, node.x);
+        }
+
+        let all = ElementFilter::All;
+        for (element, span) in mesh.iter_elements(&all).with_spans(&source_map) {
+            let span = span.unwrap();
+            let line = &MESH[span.offset..span.offset + span.len];
+            let tag = line.split_whitespace().next().unwrap();
+            assert_eq!(tag, element.tag().to_string());
+        }
+    }
+
+    #[test]
+    fn test_invalid_data_renders_in_source() {
+        let (mesh, source_map) = parse_msh_with_source_map(MESH).unwrap();
+
+        let element = &mesh.element_blocks[0].elements[0];
+        let span = source_map.element_span(element.tag).unwrap();
+        let error = source_map.invalid_data(span, "element without boundary condition");
+
+        let mut rendered = String::new();
+        miette::GraphicalReportHandler::new()
+            .render_report(&mut rendered, &error)
+            .unwrap();
+        assert!(rendered.contains("element without boundary condition"));
+    }
+}
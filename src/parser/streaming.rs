@@ -0,0 +1,351 @@
+//! Event-based parsing of large meshes
+//!
+//! [`StreamingParser`] walks a file section by section and yields nodes and
+//! elements one at a time instead of collecting them into a [`Mesh`], so that
+//! consumers can build their own data structures without paying for the
+//! per-element allocations of a complete mesh:
+//!
+//! ```no_run
+//! use gmsh_parser::parser::{Event, StreamingParser};
+//!
+//! let mut parser = StreamingParser::from_path("large.msh").unwrap();
+//! let mut tetrahedra = 0;
+//! for event in &mut parser {
+//!     if let Event::Element(element) = event.unwrap() {
+//!         tetrahedra += usize::from(element.nodes.len() == 4);
+//!     }
+//! }
+//! let groups = parser.metadata().physical_names.len();
+//! println!("{} tetrahedra, {} physical groups", tetrahedra, groups);
+//! ```
+//!
+//! All other sections are small and parsed as usual into [`StreamingParser::metadata`].
+
+use super::{elements, mesh_format, nodes, parse_section, LineReader, SourceFile, Token};
+use crate::error::{ParseError, Result};
+use crate::types::element::Element;
+use crate::types::{ElementType, EntityDimension, EntityTag, Mesh, Node, NodeTag};
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Header line of a block in the `$Nodes` section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeBlockHeader {
+    pub entity_dim: EntityDimension,
+    pub entity_tag: EntityTag,
+    pub parametric: bool,
+    pub num_nodes: usize,
+}
+
+/// Header line of a block in the `$Elements` section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementBlockHeader {
+    pub entity_dim: i32,
+    pub entity_tag: EntityTag,
+    pub element_type: ElementType,
+    pub num_elements: usize,
+}
+
+/// What the [`StreamingParser`] found next in the file
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// Start of a section, named without the leading `$`
+    SectionStart(String),
+    NodeBlockHeader(NodeBlockHeader),
+    /// A node of the block announced by the last [`Event::NodeBlockHeader`]
+    Node(Node),
+    ElementBlockHeader(ElementBlockHeader),
+    /// An element of the block announced by the last [`Event::ElementBlockHeader`]
+    Element(Element),
+    /// End of a section, named without the leading `$`
+    SectionEnd(String),
+}
+
+/// Iterator over the [`Event`]s of a MSH file
+///
+/// `$MeshFormat` is read when the parser is created. The events then follow the
+/// file: `$Nodes` and `$Elements` yield a [`Event::SectionStart`], one event per
+/// block header, node and element, and a [`Event::SectionEnd`]. Every other
+/// section is parsed into [`StreamingParser::metadata`] and yields only its
+/// start and end events.
+///
+/// Counts in the section headers are checked as the data goes by. Checks that
+/// need the whole mesh, such as elements referencing undefined nodes, are not
+/// made. The iterator stops after the first error.
+pub struct StreamingParser {
+    reader: LineReader,
+    metadata: Mesh,
+    state: State,
+    pending: VecDeque<Event>,
+}
+
+enum State {
+    Sections,
+    Nodes(BlockProgress<NodeCursor>),
+    Elements(BlockProgress<ElementCursor>),
+    Done,
+}
+
+/// Position inside the `$Nodes` or `$Elements` section
+struct BlockProgress<C> {
+    blocks_left: usize,
+    block: Option<C>,
+    /// `numNodes`/`numElements` token of the section header
+    declared_token: Token,
+    declared: usize,
+    parsed: usize,
+}
+
+struct NodeCursor {
+    header: NodeBlockHeader,
+    tags: std::vec::IntoIter<NodeTag>,
+}
+
+struct ElementCursor {
+    element_type: ElementType,
+    remaining: usize,
+}
+
+impl StreamingParser {
+    /// Start parsing `content`, reading its `$MeshFormat` section
+    ///
+    /// # Errors
+    /// Returns an error if the content does not start with a supported `$MeshFormat`.
+    pub fn new(content: impl AsRef<str>) -> Result<Self> {
+        Self::from_source(SourceFile::new(content.as_ref().to_string()))
+    }
+
+    /// Start parsing the file at `path`, reading its `$MeshFormat` section
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or does not start with a
+    /// supported `$MeshFormat`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_source(SourceFile::from_path(path)?)
+    }
+
+    fn from_source(source: SourceFile) -> Result<Self> {
+        let mut reader = source.to_line_reader();
+        let metadata = mesh_format::parse_mesh(&mut reader)?;
+        Ok(Self {
+            reader,
+            metadata,
+            state: State::Sections,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Everything parsed so far except nodes and elements
+    pub fn metadata(&self) -> &Mesh {
+        &self.metadata
+    }
+
+    /// Consume the parser, returning everything parsed except nodes and elements
+    pub fn into_metadata(self) -> Mesh {
+        self.metadata
+    }
+
+    fn advance(&mut self) -> Result<Option<Event>> {
+        if let Some(event) = self.pending.pop_front() {
+            return Ok(Some(event));
+        }
+        match &mut self.state {
+            State::Sections => self.next_section(),
+            State::Nodes(progress) => {
+                let reader = &mut self.reader;
+                if let Some(cursor) = &mut progress.block {
+                    if let Some(tag) = cursor.tags.next() {
+                        progress.parsed += 1;
+                        return Ok(Some(Event::Node(nodes::parse_node(
+                            reader,
+                            &cursor.header,
+                            tag,
+                        )?)));
+                    }
+                    progress.block = None;
+                }
+                if progress.blocks_left == 0 {
+                    return self.end_section("Nodes");
+                }
+                progress.blocks_left -= 1;
+                let header = nodes::parse_block_header(reader)?;
+                let tags = nodes::parse_block_tags(reader, &header)?;
+                progress.block = Some(NodeCursor {
+                    header: header.clone(),
+                    tags: tags.into_iter(),
+                });
+                Ok(Some(Event::NodeBlockHeader(header)))
+            }
+            State::Elements(progress) => {
+                let reader = &mut self.reader;
+                if let Some(cursor) = &mut progress.block {
+                    if cursor.remaining > 0 {
+                        cursor.remaining -= 1;
+                        progress.parsed += 1;
+                        let element = elements::parse_element(reader, cursor.element_type)?;
+                        return Ok(Some(Event::Element(element)));
+                    }
+                    progress.block = None;
+                }
+                if progress.blocks_left == 0 {
+                    return self.end_section("Elements");
+                }
+                progress.blocks_left -= 1;
+                let header = elements::parse_block_header(reader)?;
+                progress.block = Some(ElementCursor {
+                    element_type: header.element_type,
+                    remaining: header.num_elements,
+                });
+                Ok(Some(Event::ElementBlockHeader(header)))
+            }
+            State::Done => Ok(None),
+        }
+    }
+
+    /// Read the next section header and start streaming or parsing it
+    fn next_section(&mut self) -> Result<Option<Event>> {
+        loop {
+            let token_line = match self.reader.read_token_line() {
+                Ok(line) => line,
+                Err(ParseError::UnexpectedEof) => {
+                    self.state = State::Done;
+                    return Ok(None);
+                }
+                Err(e) => return Err(e),
+            };
+            let first_token = token_line.iter().peek_token()?;
+
+            let name = match first_token.value.strip_prefix('$') {
+                Some(name) if !name.starts_with("End") => name.to_string(),
+                _ => {
+                    // Content outside of sections, recorded as a warning
+                    parse_section(&mut self.reader, first_token, &mut self.metadata)?;
+                    continue;
+                }
+            };
+
+            match name.as_str() {
+                "Nodes" => self.state = State::Nodes(self.read_section_header("numNodes")?),
+                "Elements" => {
+                    self.state = State::Elements(self.read_section_header("numElements")?);
+                }
+                _ => {
+                    parse_section(&mut self.reader, first_token, &mut self.metadata)?;
+                    self.pending.push_back(Event::SectionEnd(name.clone()));
+                }
+            }
+            return Ok(Some(Event::SectionStart(name)));
+        }
+    }
+
+    /// Read the `numEntityBlocks numItems minTag maxTag` line of `$Nodes` or `$Elements`
+    fn read_section_header<C>(&mut self, count_name: &str) -> Result<BlockProgress<C>> {
+        let token_line = self.reader.read_token_line()?;
+        let mut iter = token_line.iter();
+        let blocks_left = iter.parse_usize("numEntityBlocks")?;
+        let declared_token = iter.peek_token()?.to_token();
+        let declared = iter.parse_usize(count_name)?;
+        iter.parse_unsigned("minTag")?;
+        iter.parse_unsigned("maxTag")?;
+        iter.expect_no_more()?;
+
+        Ok(BlockProgress {
+            blocks_left,
+            block: None,
+            declared_token,
+            declared,
+            parsed: 0,
+        })
+    }
+
+    /// Check the end marker and the declared count of the current section
+    fn end_section(&mut self, name: &str) -> Result<Option<Event>> {
+        let (declared_token, declared, parsed) = match &self.state {
+            State::Nodes(p) => (p.declared_token.clone(), p.declared, p.parsed),
+            State::Elements(p) => (p.declared_token.clone(), p.declared, p.parsed),
+            State::Sections | State::Done => return Ok(None),
+        };
+
+        self.reader.read_end_marker(name)?;
+        if declared != parsed {
+            return Err(ParseError::InvalidData {
+                message: format!(
+                    "{} count mismatch: header declares {}, but {} were parsed",
+                    &name[..name.len() - 1],
+                    declared,
+                    parsed
+                ),
+                span: declared_token.span.to_source_span(),
+                msh_content: declared_token.source,
+            });
+        }
+
+        self.state = State::Sections;
+        Ok(Some(Event::SectionEnd(name.to_string())))
+    }
+}
+
+impl Iterator for StreamingParser {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.advance() {
+            Ok(event) => event.map(Ok),
+            Err(e) => {
+                self.state = State::Done;
+                self.pending.clear();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for StreamingParser {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    #[test]
+    fn test_events_match_parsed_mesh() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $PhysicalNames\n1\n2 1 \"surface\"\n$EndPhysicalNames\n\
+            $Entities\n0 0 1 0\n1 0 0 0 1 1 0 1 1 0\n$EndEntities\n\
+            $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+            $Elements\n1 2 1 2\n2 1 2 2\n1 1 2 3\n2 1 3 4\n$EndElements\n";
+        let mesh = parse_msh(content).unwrap();
+
+        let mut parser = StreamingParser::new(content).unwrap();
+        let mut node_tags = Vec::new();
+        let mut element_tags = Vec::new();
+        let mut sections = Vec::new();
+        for event in &mut parser {
+            match event.unwrap() {
+                Event::Node(node) => node_tags.push(node.tag),
+                Event::Element(element) => element_tags.push(element.tag),
+                Event::SectionStart(name) => sections.push(name),
+                _ => {}
+            }
+        }
+
+        let nodes = mesh.node_blocks.iter().flat_map(|b| &b.nodes);
+        assert_eq!(node_tags, nodes.map(|n| n.tag).collect::<Vec<_>>());
+        let elements = mesh.element_blocks.iter().flat_map(|b| &b.elements);
+        assert_eq!(element_tags, elements.map(|e| e.tag).collect::<Vec<_>>());
+        assert_eq!(sections, ["PhysicalNames", "Entities", "Nodes", "Elements"]);
+        assert_eq!(parser.metadata().physical_names.len(), 1);
+    }
+
+    #[test]
+    fn test_stops_after_count_mismatch() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 2\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n";
+        let events: Vec<_> = StreamingParser::new(content).unwrap().collect();
+        assert!(matches!(
+            events.last(),
+            Some(Err(ParseError::InvalidData { .. }))
+        ));
+        assert_eq!(events.iter().filter(|e| e.is_ok()).count(), 4);
+    }
+}
@@ -1,5 +1,6 @@
+use crate::error::ParseWarning;
 use miette::SourceSpan;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 mod token_iter;
 mod token_line;
@@ -8,8 +9,16 @@ mod token_parser;
 pub use token_iter::TokenIter;
 pub use token_line::TokenLine;
 
+/// Warnings recorded while reading lines in lenient mode
+pub(crate) type WarningSink = Arc<Mutex<Vec<ParseWarning>>>;
+
 /// Represents a location in the source file
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Span {
     /// Byte offset from the start of the file
     pub offset: usize,
@@ -28,6 +37,18 @@ impl Span {
     }
 }
 
+impl From<SourceSpan> for Span {
+    fn from(span: SourceSpan) -> Self {
+        Self::new(span.offset(), span.len())
+    }
+}
+
+impl From<Span> for SourceSpan {
+    fn from(span: Span) -> Self {
+        span.to_source_span()
+    }
+}
+
 /// A token with position information
 #[derive(Debug, Clone)]
 pub struct Token {
@@ -48,3 +69,24 @@ impl Token {
         }
     }
 }
+
+/// A token borrowed from the source of the line it was read from
+///
+/// Lines are tokenized into spans only; the value is a slice of the source, so
+/// reading a line does not copy its tokens.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenRef<'a> {
+    /// The string value of the token
+    pub value: &'a str,
+    /// Location in the source file
+    pub span: Span,
+    /// Reference to the full source file content (for error reporting)
+    pub source: &'a Arc<String>,
+}
+
+impl TokenRef<'_> {
+    /// Copy this token out of its line
+    pub fn to_token(&self) -> Token {
+        Token::new(self.value.to_string(), self.span, Arc::clone(self.source))
+    }
+}
@@ -1,38 +1,65 @@
-use super::Token;
-use crate::error::{ParseError, Result};
+use super::{Span, TokenRef, WarningSink};
+use crate::error::{ParseError, ParseWarning, Result, WarningKind};
+use std::sync::Arc;
 
 /// Iterator over tokens in a TokenLine with parsing methods
 pub struct TokenIter<'a> {
-    pub(super) tokens: &'a [Token],
+    pub(super) tokens: &'a [Span],
     pub(super) index: usize,
+    source: &'a Arc<String>,
+    warnings: Option<&'a WarningSink>,
 }
 
 impl<'a> TokenIter<'a> {
-    pub(super) fn new(tokens: &'a [Token]) -> Self {
-        Self { tokens, index: 0 }
+    pub(super) fn new(
+        tokens: &'a [Span],
+        source: &'a Arc<String>,
+        warnings: Option<&'a WarningSink>,
+    ) -> Self {
+        Self {
+            tokens,
+            index: 0,
+            source,
+            warnings,
+        }
+    }
+
+    /// Token at `span` in the source
+    fn token(&self, span: Span) -> TokenRef<'a> {
+        let source: &'a Arc<String> = self.source;
+        TokenRef {
+            value: source
+                .get(span.offset..span.offset + span.len)
+                .unwrap_or_default(),
+            span,
+            source,
+        }
     }
 
     /// Peek at the current token without advancing
-    pub fn peek_token(&self) -> Result<&'a Token> {
-        self.tokens.get(self.index).ok_or_else(|| {
-            // Point to the end of the line (after the last token)
-            // TokenLine guarantees at least one token, so unwrap is safe
-            let last_token = self
-                .tokens
-                .last()
-                .expect("TokenLine should never be empty (guaranteed by TokenLine::new)");
-            let end_offset = last_token.span.offset + last_token.span.len;
+    pub fn peek_token(&self) -> Result<TokenRef<'a>> {
+        match self.tokens.get(self.index) {
+            Some(&span) => Ok(self.token(span)),
+            None => {
+                // Point to the end of the line (after the last token)
+                let Some(last) = self.tokens.last() else {
+                    return Err(ParseError::EmptyLine);
+                };
+                let end_offset = last.offset + last.len;
+                // Keep the label inside the source when the file ends right here
+                let len = usize::from(end_offset < self.source.len());
 
-            ParseError::UnexpectedEndOfLine {
-                expected: "more data".to_string(),
-                span: (end_offset, 1).into(),
-                msh_content: last_token.source.clone(),
+                Err(ParseError::UnexpectedEndOfLine {
+                    expected: "more data".to_string(),
+                    span: (end_offset, len).into(),
+                    msh_content: self.source.clone(),
+                })
             }
-        })
+        }
     }
 
     /// Internal helper: get the next token and advance the index
-    pub(super) fn next_token(&mut self) -> Result<&'a Token> {
+    pub(super) fn next_token(&mut self) -> Result<TokenRef<'a>> {
         let token = self.peek_token()?;
         self.index += 1;
         Ok(token)
@@ -53,10 +80,32 @@ impl<'a> TokenIter<'a> {
     /// This is useful for validating that a line has exactly the expected number of tokens
     /// after parsing all required values.
     ///
+    /// In lenient mode the remaining tokens are skipped and recorded as a
+    /// warning instead.
+    ///
     /// # Errors
     /// Returns an error if there are remaining tokens
     pub fn expect_no_more(&mut self) -> Result<()> {
         if let Some(token) = self.next() {
+            if let Some(warnings) = self.warnings {
+                // Cover everything from the first extra token to the end of the line
+                let end = self.tokens.last().map_or(token.span.len, |last| {
+                    last.offset + last.len - token.span.offset
+                });
+                let warning = ParseWarning::at(
+                    format!(
+                        "Ignoring {} unexpected token(s) at end of line",
+                        self.remaining() + 1
+                    ),
+                    Span::new(token.span.offset, end),
+                )
+                .with_kind(WarningKind::ExtraTokens);
+                if let Ok(mut warnings) = warnings.lock() {
+                    warnings.push(warning);
+                }
+                self.index = self.tokens.len();
+                return Ok(());
+            }
             return Err(ParseError::UnexpectedExtraData {
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
@@ -69,16 +118,12 @@ impl<'a> TokenIter<'a> {
 /// Implement standard Iterator trait for TokenIter
 /// This allows using standard iterator methods like map, filter, collect, etc.
 impl<'a> Iterator for TokenIter<'a> {
-    type Item = &'a Token;
+    type Item = TokenRef<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.tokens.len() {
-            let token = &self.tokens[self.index];
-            self.index += 1;
-            Some(token)
-        } else {
-            None
-        }
+        let span = *self.tokens.get(self.index)?;
+        self.index += 1;
+        Some(self.token(span))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
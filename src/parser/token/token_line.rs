@@ -1,18 +1,57 @@
-use super::{Token, TokenIter};
+use super::{Span, Token, TokenIter, WarningSink};
 use crate::error::{ParseError, Result};
 use miette::SourceSpan;
+use std::sync::Arc;
 
 /// A line of tokens with metadata
 #[derive(Debug)]
 pub struct TokenLine {
-    /// The tokens in this line (private - use iter() for access)
-    tokens: Vec<Token>,
+    /// Locations of the tokens in `source` (private - use iter() for access)
+    tokens: Vec<Span>,
+    /// Span from the start of the first token to the end of the last one
+    span: SourceSpan,
+    /// Full source content (for error reporting)
+    source: Arc<String>,
+    /// Where recoverable problems go instead of errors in lenient mode
+    warnings: Option<WarningSink>,
 }
 
 impl TokenLine {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        assert!(!tokens.is_empty(), "TokenLine must have at least one token");
-        Self { tokens }
+    /// Create a line from its tokens
+    ///
+    /// # Errors
+    /// Returns [`ParseError::EmptyLine`] if `tokens` is empty.
+    pub fn new(tokens: Vec<Token>) -> Result<Self> {
+        let Some(first) = tokens.first() else {
+            return Err(ParseError::EmptyLine);
+        };
+        let source = Arc::clone(&first.source);
+        Self::from_spans(source, tokens.iter().map(|token| token.span).collect())
+    }
+
+    /// Create a line from the locations of its tokens in `source`
+    ///
+    /// # Errors
+    /// Returns [`ParseError::EmptyLine`] if `tokens` is empty.
+    pub(crate) fn from_spans(source: Arc<String>, tokens: Vec<Span>) -> Result<Self> {
+        let (Some(first), Some(last)) = (tokens.first(), tokens.last()) else {
+            return Err(ParseError::EmptyLine);
+        };
+        let start = first.offset;
+        let end = last.offset + last.len;
+        let span = (start, end - start).into();
+        Ok(Self {
+            tokens,
+            span,
+            source,
+            warnings: None,
+        })
+    }
+
+    /// Record extra tokens at the end of the line as warnings in `warnings`
+    pub(crate) fn with_warnings(mut self, warnings: Option<WarningSink>) -> Self {
+        self.warnings = warnings;
+        self
     }
 
     /// Get the number of tokens
@@ -26,24 +65,18 @@ impl TokenLine {
         ParseError::InvalidFormat {
             message: message.into(),
             span: self.line_span(),
-            msh_content: self.tokens[0].source.clone(),
+            msh_content: self.source.clone(),
         }
     }
 
     /// Get the span for the entire line
     pub fn line_span(&self) -> SourceSpan {
-        let first = self
-            .tokens
-            .first()
-            .expect("TokenLine should never be empty (guaranteed by TokenLine::new)");
-        let last = self
-            .tokens
-            .last()
-            .expect("TokenLine should never be empty (guaranteed by TokenLine::new)");
+        self.span
+    }
 
-        let start = first.span.offset;
-        let end = last.span.offset + last.span.len;
-        (start, end - start).into()
+    /// Location of the entire line
+    pub fn span(&self) -> Span {
+        Span::new(self.span.offset(), self.span.len())
     }
 
     /// Expect a specific section start marker (e.g., "$MeshFormat")
@@ -74,7 +107,7 @@ impl TokenLine {
         if token.value != expected {
             return Err(ParseError::ExpectedEndOfSection {
                 expected,
-                found: token.value.clone(),
+                found: token.value.to_string(),
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
             });
@@ -87,6 +120,6 @@ impl TokenLine {
 
     /// Create an iterator over the tokens in this line
     pub fn iter(&self) -> TokenIter<'_> {
-        TokenIter::new(&self.tokens)
+        TokenIter::new(&self.tokens, &self.source, self.warnings.as_ref())
     }
 }
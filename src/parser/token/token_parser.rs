@@ -1,5 +1,6 @@
 use super::TokenIter;
 use crate::error::{ParseError, Result};
+use crate::types::{ElementTag, EntityTag, NodeTag, PhysicalTag};
 
 /// Parsing methods for TokenIter
 impl<'a> TokenIter<'a> {
@@ -11,7 +12,7 @@ impl<'a> TokenIter<'a> {
             .parse()
             .map_err(|parse_error| ParseError::ParseIntError {
                 field: field.to_string(),
-                value: token.value.clone(),
+                value: token.value.to_string(),
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
                 cause: parse_error,
@@ -26,7 +27,42 @@ impl<'a> TokenIter<'a> {
             .parse()
             .map_err(|parse_error| ParseError::ParseIntError {
                 field: field.to_string(),
-                value: token.value.clone(),
+                value: token.value.to_string(),
+                span: token.span.to_source_span(),
+                msh_content: token.source.clone(),
+                cause: parse_error,
+            })
+    }
+
+    /// Parse the next token as a node tag and advance
+    pub fn parse_node_tag(&mut self, field: &str) -> Result<NodeTag> {
+        self.parse_unsigned(field).map(NodeTag)
+    }
+
+    /// Parse the next token as an element tag and advance
+    pub fn parse_element_tag(&mut self, field: &str) -> Result<ElementTag> {
+        self.parse_unsigned(field).map(ElementTag)
+    }
+
+    /// Parse the next token as an entity tag and advance
+    pub fn parse_entity_tag(&mut self, field: &str) -> Result<EntityTag> {
+        self.parse_int(field).map(EntityTag)
+    }
+
+    /// Parse the next token as a physical tag and advance
+    pub fn parse_physical_tag(&mut self, field: &str) -> Result<PhysicalTag> {
+        self.parse_int(field).map(PhysicalTag)
+    }
+
+    /// Parse the next token as a u64 and advance
+    pub fn parse_unsigned(&mut self, field: &str) -> Result<u64> {
+        let token = self.next_token()?;
+        token
+            .value
+            .parse()
+            .map_err(|parse_error| ParseError::ParseIntError {
+                field: field.to_string(),
+                value: token.value.to_string(),
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
                 cause: parse_error,
@@ -41,7 +77,7 @@ impl<'a> TokenIter<'a> {
             .parse()
             .map_err(|parse_error| ParseError::ParseFloatError {
                 field: field.to_string(),
-                value: token.value.clone(),
+                value: token.value.to_string(),
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
                 cause: parse_error,
@@ -51,7 +87,7 @@ impl<'a> TokenIter<'a> {
     /// Parse the next token as a boolean (0 or 1) and advance
     pub fn parse_bool(&mut self, field: &str) -> Result<bool> {
         let token = self.next_token()?;
-        match token.value.as_str() {
+        match token.value {
             "0" => Ok(false),
             "1" => Ok(true),
             _ => Err(ParseError::InvalidData {
@@ -68,7 +104,7 @@ impl<'a> TokenIter<'a> {
         let parts: Vec<&str> = token.value.split('.').collect();
 
         let make_error = || ParseError::InvalidVersionFormat {
-            version: token.value.clone(),
+            version: token.value.to_string(),
             span: token.span.to_source_span(),
             msh_content: token.source.clone(),
         };
@@ -80,7 +116,7 @@ impl<'a> TokenIter<'a> {
         let major = parts[0].parse::<u32>().map_err(|_| make_error())?;
         let minor = parts[1].parse::<u32>().map_err(|_| make_error())?;
 
-        Ok(crate::types::Version::new(major, minor, token.clone()))
+        Ok(crate::types::Version::new(major, minor, token.to_token()))
     }
 
     /// Parse the next token as an EntityDimension and advance
@@ -91,7 +127,7 @@ impl<'a> TokenIter<'a> {
             .parse()
             .map_err(|parse_error| ParseError::ParseIntError {
                 field: field.to_string(),
-                value: token.value.clone(),
+                value: token.value.to_string(),
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
                 cause: parse_error,
@@ -113,7 +149,7 @@ impl<'a> TokenIter<'a> {
             .parse()
             .map_err(|parse_error| ParseError::ParseIntError {
                 field: field.to_string(),
-                value: token.value.clone(),
+                value: token.value.to_string(),
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
                 cause: parse_error,
@@ -133,7 +169,7 @@ impl<'a> TokenIter<'a> {
             .parse()
             .map_err(|parse_error| ParseError::ParseIntError {
                 field: field.to_string(),
-                value: token.value.clone(),
+                value: token.value.to_string(),
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
                 cause: parse_error,
@@ -155,7 +191,7 @@ impl<'a> TokenIter<'a> {
             .parse()
             .map_err(|parse_error| ParseError::ParseIntError {
                 field: field.to_string(),
-                value: token.value.clone(),
+                value: token.value.to_string(),
                 span: token.span.to_source_span(),
                 msh_content: token.source.clone(),
                 cause: parse_error,
@@ -172,6 +208,28 @@ impl<'a> TokenIter<'a> {
         }
     }
 
+    /// Parse the next token as a length unit symbol, `-` for none, and advance
+    pub fn parse_length_unit(
+        &mut self,
+        field: &str,
+    ) -> Result<Option<crate::geometry::LengthUnit>> {
+        let token = self.next_token()?;
+        if token.value == "-" {
+            return Ok(None);
+        }
+        match crate::geometry::LengthUnit::from_symbol(token.value) {
+            Some(unit) => Ok(Some(unit)),
+            None => Err(ParseError::InvalidData {
+                message: format!(
+                    "{} must be a length unit such as mm or m, or -, found '{}'",
+                    field, token.value
+                ),
+                span: token.span.to_source_span(),
+                msh_content: token.source.clone(),
+            }),
+        }
+    }
+
     /// Parse a quoted string from the current position to the end of the line
     ///
     /// Collects all remaining tokens on this line and expects them to form a quoted string.
@@ -186,11 +244,11 @@ impl<'a> TokenIter<'a> {
     /// - The content doesn't end with a quote
     pub fn parse_quoted_string_to_line_end(&mut self) -> Result<String> {
         let start_token = self.peek_token()?;
-        let start_span = start_token.span.clone();
+        let start_span = start_token.span;
         let source = start_token.source.clone();
 
         // Collect all remaining tokens on this line using standard iterator methods
-        let parts: Vec<&str> = self.map(|token| token.value.as_str()).collect();
+        let parts: Vec<&str> = self.map(|token| token.value).collect();
         let combined = parts.join(" ");
         let trimmed = combined.trim();
 
@@ -230,4 +288,15 @@ impl<'a> TokenIter<'a> {
             .map(|i| self.parse_int(&format!("{}[{}]", field_prefix, i)))
             .collect()
     }
+
+    /// Parse multiple physical tags starting from the current position
+    pub fn parse_physical_tags(
+        &mut self,
+        count: usize,
+        field_prefix: &str,
+    ) -> Result<Vec<PhysicalTag>> {
+        (0..count)
+            .map(|i| self.parse_physical_tag(&format!("{}[{}]", field_prefix, i)))
+            .collect()
+    }
 }
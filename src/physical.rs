@@ -0,0 +1,260 @@
+//! Physical group queries
+//!
+//! [`Mesh::physical_group`] resolves a physical group, given by name or by
+//! dimension and tag, to the entities, element blocks and nodes it covers:
+//!
+//! ```no_run
+//! use gmsh_parser::{parse_msh_file, PhysicalTag};
+//!
+//! let mesh = parse_msh_file("model.msh").unwrap();
+//! let inlet = mesh.physical_group("inlet").unwrap();
+//! println!("{} elements, {} nodes", inlet.num_elements(), inlet.node_tags.len());
+//! let body = mesh.physical_group((3, PhysicalTag(1))).unwrap();
+//! for element in body.elements() {
+//!     println!("{} {:?}", element.tag(), element.nodes());
+//! }
+//! ```
+
+use crate::error::{ParseError, Result};
+use crate::sets::physical_entities;
+use crate::types::{
+    ElementBlockRef, ElementRef, EntityTag, Mesh, NodeTag, Orientation, PhysicalTag,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Designation of a physical group, by name or by `(dimension, tag)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhysicalGroupId<'a> {
+    Name(&'a str),
+    Tag(i32, PhysicalTag),
+}
+
+impl<'a> From<&'a str> for PhysicalGroupId<'a> {
+    fn from(name: &'a str) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl From<(i32, PhysicalTag)> for PhysicalGroupId<'_> {
+    fn from((dim, tag): (i32, PhysicalTag)) -> Self {
+        Self::Tag(dim, tag)
+    }
+}
+
+/// The part of a mesh belonging to a physical group, see [`Mesh::physical_group`]
+#[derive(Debug, Clone)]
+pub struct PhysicalGroup<'a> {
+    pub dim: i32,
+    pub tag: PhysicalTag,
+    /// Name from `$PhysicalNames`, if any
+    pub name: Option<&'a str>,
+    /// Entities of dimension `dim` carrying the physical tag, including
+    /// partition entities
+    pub entities: BTreeSet<EntityTag>,
+    /// Element blocks of these entities, compact ones included
+    pub element_blocks: Vec<ElementBlockRef<'a>>,
+    /// Nodes used by the elements of the group
+    pub node_tags: BTreeSet<NodeTag>,
+}
+
+impl<'a> PhysicalGroup<'a> {
+    /// Elements of the group, in file order
+    pub fn elements(&self) -> impl Iterator<Item = ElementRef<'a>> + '_ {
+        self.element_blocks
+            .iter()
+            .flat_map(|block| block.elements())
+    }
+
+    /// Number of elements in the group
+    pub fn num_elements(&self) -> usize {
+        self.element_blocks.iter().map(|b| b.len()).sum()
+    }
+}
+
+impl Mesh {
+    /// The physical group designated by `id`, a name or a `(dimension, tag)` pair
+    ///
+    /// Entities match on the magnitude of their physical tags, whatever their
+    /// orientation sign (see [`Mesh::physical_tag_orientations`]).
+    ///
+    /// # Errors
+    /// Returns [`ParseError::NotFound`] if no physical group has this name, or
+    /// if no entity carries the physical tag.
+    pub fn physical_group<'a, 'b>(
+        &'a self,
+        id: impl Into<PhysicalGroupId<'b>>,
+    ) -> Result<PhysicalGroup<'a>> {
+        let (dim, tag) = match id.into() {
+            PhysicalGroupId::Tag(dim, tag) => (dim, tag.magnitude()),
+            PhysicalGroupId::Name(name) => self
+                .physical_names
+                .iter()
+                .find(|pn| pn.name == name)
+                .map(|pn| (pn.dimension as i32, pn.tag))
+                .ok_or_else(|| ParseError::NotFound(format!("Physical group \"{}\"", name)))?,
+        };
+        let entities: BTreeSet<EntityTag> =
+            physical_entities(self, dim, tag)?.into_iter().collect();
+        let name = self
+            .physical_names
+            .iter()
+            .find(|pn| pn.dimension as i32 == dim && pn.tag.matches(tag))
+            .map(|pn| pn.name.as_str());
+        let element_blocks: Vec<ElementBlockRef> = self
+            .element_block_refs()
+            .filter(|b| b.entity_dim() == dim && entities.contains(&b.entity_tag()))
+            .collect();
+        let node_tags = element_blocks
+            .iter()
+            .flat_map(|b| b.elements().flat_map(|e| e.nodes().iter().copied()))
+            .collect();
+
+        Ok(PhysicalGroup {
+            dim,
+            tag,
+            name,
+            entities,
+            element_blocks,
+            node_tags,
+        })
+    }
+
+    /// Orientation with which each entity of dimension `dim` carries the
+    /// physical tag `tag`, partition entities included
+    ///
+    /// Some exports write negative physical tags on entities whose orientation
+    /// is reversed with respect to the group. `tag` is matched on magnitude;
+    /// entities without the tag are left out.
+    pub fn physical_tag_orientations(
+        &self,
+        dim: i32,
+        tag: PhysicalTag,
+    ) -> BTreeMap<EntityTag, Orientation> {
+        let entities = self.effective_entities();
+        let mut tagged: Vec<(EntityTag, &[PhysicalTag])> = match dim {
+            0 => entities
+                .points
+                .iter()
+                .map(|e| (e.tag, &e.physical_tags[..]))
+                .collect(),
+            1 => entities
+                .curves
+                .iter()
+                .map(|e| (e.tag, &e.physical_tags[..]))
+                .collect(),
+            2 => entities
+                .surfaces
+                .iter()
+                .map(|e| (e.tag, &e.physical_tags[..]))
+                .collect(),
+            3 => entities
+                .volumes
+                .iter()
+                .map(|e| (e.tag, &e.physical_tags[..]))
+                .collect(),
+            _ => Vec::new(),
+        };
+        if let Some(partitioned) = &self.partitioned_entities {
+            match dim {
+                0 => tagged.extend(
+                    partitioned
+                        .points
+                        .iter()
+                        .map(|e| (e.tag, &e.physical_tags[..])),
+                ),
+                1 => tagged.extend(
+                    partitioned
+                        .curves
+                        .iter()
+                        .map(|e| (e.tag, &e.physical_tags[..])),
+                ),
+                2 => tagged.extend(
+                    partitioned
+                        .surfaces
+                        .iter()
+                        .map(|e| (e.tag, &e.physical_tags[..])),
+                ),
+                3 => tagged.extend(
+                    partitioned
+                        .volumes
+                        .iter()
+                        .map(|e| (e.tag, &e.physical_tags[..])),
+                ),
+                _ => {}
+            }
+        }
+        tagged
+            .into_iter()
+            .filter_map(|(entity, physical_tags)| {
+                let found = physical_tags.iter().find(|p| p.matches(tag))?;
+                Some((entity, found.orientation()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::NAMED_SQUARE_ON_CURVES;
+    use crate::sets::{ElementSet, NodeSet};
+
+    #[test]
+    fn test_physical_group_by_name() {
+        let mesh = crate::parse_msh(NAMED_SQUARE_ON_CURVES).unwrap();
+        let surface = mesh.physical_group("My surface").unwrap();
+        assert_eq!((surface.dim, surface.tag), (2, PhysicalTag(6)));
+        assert_eq!(surface.name, Some("My surface"));
+        assert!(surface.entities.iter().eq([&EntityTag(1)]));
+        assert_eq!(surface.num_elements(), 2);
+        assert_eq!(surface.node_tags.len(), 4);
+        assert!(mesh.physical_group("missing").is_err());
+    }
+
+    #[test]
+    fn test_physical_group_by_tag() {
+        let mesh = crate::parse_msh(NAMED_SQUARE_ON_CURVES).unwrap();
+        let boundary = mesh.physical_group((1, PhysicalTag(5))).unwrap();
+        assert_eq!(boundary.name, Some("boundary"));
+        let set = ElementSet::from_physical_group(&mesh, 1, PhysicalTag(5)).unwrap();
+        assert_eq!(boundary.num_elements(), set.len());
+        assert!(boundary.elements().all(|e| set.contains(e.tag())));
+        assert_eq!(boundary.node_tags, set.node_set(&mesh).tags);
+        assert!(mesh.physical_group((2, PhysicalTag(99))).is_err());
+    }
+
+    #[test]
+    fn test_physical_group_of_partitioned_mesh() {
+        // Gmsh repeats the group of surface 1 on its partitions 2 and 3
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $PhysicalNames\n1\n2 1 \"Left\"\n$EndPhysicalNames\n\
+            $Entities\n0 0 1 0\n1 0 0 0 1 1 0 1 1 0\n$EndEntities\n\
+            $PartitionedEntities\n2\n0\n0 0 2 0\n2 2 1 1 1 0 0 0 1 1 0 1 1 0\n\
+            3 2 1 1 2 0 0 0 1 1 0 1 1 0\n$EndPartitionedEntities\n\
+            $Nodes\n2 4 1 4\n2 2 0 3\n1\n2\n3\n0 0 0\n1 0 0\n1 1 0\n2 3 0 1\n4\n0 1 0\n\
+            $EndNodes\n\
+            $Elements\n2 2 1 2\n2 2 2 1\n1 1 2 3\n2 3 2 1\n2 1 3 4\n$EndElements\n";
+        let mesh = crate::parse_msh(content).unwrap();
+        let left = mesh.physical_group("Left").unwrap();
+        assert_eq!(left.num_elements(), 2);
+        let nodes = NodeSet::from_predicate(&mesh, "all", |_| true);
+        assert_eq!(left.node_tags, nodes.tags);
+    }
+
+    #[test]
+    fn test_negative_physical_tags_match_on_magnitude() {
+        let content = NAMED_SQUARE_ON_CURVES.replace("1 0 0 0 1 1 0 1 6 0", "1 0 0 0 1 1 0 1 -6 0");
+        let mesh = crate::parse_msh(content).unwrap();
+        let surface = EntityTag(1);
+        let group = mesh.physical_group((2, PhysicalTag(6))).unwrap();
+        assert_eq!(group.name, Some("My surface"));
+        assert!(group.entities.contains(&surface));
+        assert_eq!(
+            mesh.physical_tag_orientations(2, PhysicalTag(6))[&surface],
+            Orientation::Reversed
+        );
+        assert!(mesh
+            .physical_group_bounding_boxes()
+            .contains_key(&(2, PhysicalTag(6))));
+    }
+}
@@ -0,0 +1,586 @@
+//! Shape quality of the elements of a mesh
+//!
+//! [`Mesh::element_quality`] measures every element and
+//! [`Mesh::quality_report`] summarizes the elements of the highest dimension,
+//! e.g. to catch inverted tetrahedra right after parsing:
+//!
+//! ```no_run
+//! use gmsh_parser::parse_msh_file;
+//!
+//! let mesh = parse_msh_file("model.msh").unwrap();
+//! let report = mesh.quality_report();
+//! println!(
+//!     "scaled Jacobian in [{:.3}, {:.3}], {} inverted elements",
+//!     report.scaled_jacobian.min,
+//!     report.scaled_jacobian.max,
+//!     report.inverted.len()
+//! );
+//! ```
+//!
+//! [`Mesh::validate_geometry`] lists every inverted or degenerate element.
+//!
+//! Metrics are computed from the corner nodes, so higher-order elements are
+//! measured like their linear counterparts.
+
+use crate::geometry::{distance, node_coordinates};
+use crate::topology::{corner_count, corner_edges, corner_faces};
+use crate::types::{ElementFamily, ElementTag, ElementType, Mesh, NodeTag};
+use std::f64::consts::SQRT_2;
+
+/// Number of bins of the histograms of a [`QualityReport`]
+pub const HISTOGRAM_BINS: usize = 10;
+
+/// Largest scaled Jacobian, in absolute value, of the elements that
+/// [`Mesh::validate_geometry`] reports as degenerate
+pub const DEGENERACY_TOLERANCE: f64 = 1e-9;
+
+/// Shape metrics of one element, see [`element_quality`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ElementQuality {
+    pub tag: ElementTag,
+    pub element_type: ElementType,
+    /// Length, area or volume, negative for inverted elements
+    pub size: f64,
+    /// Longest edge over shortest edge, 1 for ideal shapes
+    pub aspect_ratio: f64,
+    /// Smallest determinant of the edges at a corner, scaled to 1 for ideal
+    /// shapes; zero or negative for degenerate or inverted elements
+    pub scaled_jacobian: f64,
+    /// Smallest angle between two faces, in degrees, for volume elements
+    pub min_dihedral_angle: Option<f64>,
+    /// Largest equiangular skewness of the faces, from 0 for ideal shapes to 1
+    /// for degenerate ones
+    pub skewness: f64,
+}
+
+impl ElementQuality {
+    /// Whether the element is inverted or degenerate
+    pub fn is_inverted(&self) -> bool {
+        self.scaled_jacobian <= 0.0
+    }
+}
+
+/// Measure an element of type `element_type` whose nodes are at `points`
+///
+/// Volume elements are inverted when their corners are not in Gmsh's
+/// ordering. Surface elements have no inside: those lying in a plane
+/// `z = const`, as in 2D meshes, are oriented by the `z` axis, others by their
+/// own normal, so that only folded or non-convex ones come out inverted.
+///
+/// Returns `None` for points, polygons and polyhedra, and if there are fewer
+/// points than corners.
+pub fn element_quality(
+    tag: ElementTag,
+    element_type: ElementType,
+    points: &[[f64; 3]],
+) -> Option<ElementQuality> {
+    let family = element_type.family();
+    let n = corner_count(family);
+    if matches!(family, ElementFamily::Point | ElementFamily::Other) || points.len() < n {
+        return None;
+    }
+    let corners = &points[..n];
+
+    let lengths = corner_edges(family)
+        .iter()
+        .map(|&[a, b]| distance(corners[a], corners[b]));
+    let (shortest, longest) = lengths.fold((f64::INFINITY, 0.0_f64), |(min, max), l| {
+        (min.min(l), max.max(l))
+    });
+    let aspect_ratio = if shortest > 0.0 {
+        longest / shortest
+    } else {
+        f64::INFINITY
+    };
+
+    let mut quality = ElementQuality {
+        tag,
+        element_type,
+        size: 0.0,
+        aspect_ratio,
+        scaled_jacobian: 0.0,
+        min_dihedral_angle: None,
+        skewness: 0.0,
+    };
+    match element_type.dimension() {
+        1 => {
+            quality.size = longest;
+            quality.scaled_jacobian = if longest > 0.0 { 1.0 } else { 0.0 };
+        }
+        2 => {
+            let normal = newell_normal(corners);
+            let reference = if corners.iter().all(|p| p[2] == corners[0][2]) {
+                [0.0, 0.0, 1.0]
+            } else {
+                scale(normal, 1.0 / norm(normal))
+            };
+            quality.size = 0.5 * dot(normal, reference);
+            let ideal = if n == 3 { 2.0 / 3f64.sqrt() } else { 1.0 };
+            quality.scaled_jacobian = (0..n)
+                .map(|i| {
+                    let p = corners[i];
+                    let next = sub(corners[(i + 1) % n], p);
+                    let prev = sub(corners[(i + n - 1) % n], p);
+                    let lengths = norm(next) * norm(prev);
+                    if lengths > 0.0 {
+                        ideal * dot(cross(next, prev), reference) / lengths
+                    } else {
+                        0.0
+                    }
+                })
+                .fold(f64::INFINITY, f64::min)
+                .clamp(-1.0, 1.0);
+            quality.skewness = skewness(corners);
+        }
+        _ => {
+            let faces = corner_faces(family);
+            let centre = scale(
+                corners.iter().fold([0.0; 3], |acc, &p| add(acc, p)),
+                1.0 / n as f64,
+            );
+            quality.size = faces
+                .iter()
+                .flat_map(|face| (1..face.len() - 1).map(move |k| [face[0], face[k], face[k + 1]]))
+                .map(|[a, b, c]| {
+                    let [a, b, c] = [a, b, c].map(|i| sub(corners[i], centre));
+                    dot(a, cross(b, c)) / 6.0
+                })
+                .sum();
+
+            let (frames, ideal) = corner_frames(family);
+            quality.scaled_jacobian = frames
+                .iter()
+                .map(|&[c, a, b, d]| {
+                    let [a, b, d] = [a, b, d].map(|i| sub(corners[i], corners[c]));
+                    let lengths = norm(a) * norm(b) * norm(d);
+                    if lengths > 0.0 {
+                        ideal * dot(a, cross(b, d)) / lengths
+                    } else {
+                        0.0
+                    }
+                })
+                .fold(f64::INFINITY, f64::min)
+                .clamp(-1.0, 1.0);
+
+            let normals: Vec<[f64; 3]> = faces
+                .iter()
+                .map(|face| newell_normal(&face.iter().map(|&i| corners[i]).collect::<Vec<_>>()))
+                .collect();
+            quality.min_dihedral_angle = corner_edges(family)
+                .iter()
+                .filter_map(|edge| {
+                    let mut sharing = faces
+                        .iter()
+                        .zip(&normals)
+                        .filter(|(face, _)| edge.iter().all(|c| face.contains(c)))
+                        .map(|(_, &normal)| normal);
+                    let (first, second) = (sharing.next()?, sharing.next()?);
+                    let lengths = norm(first) * norm(second);
+                    let cos = if lengths > 0.0 {
+                        (dot(first, second) / lengths).clamp(-1.0, 1.0)
+                    } else {
+                        1.0
+                    };
+                    Some(180.0 - cos.acos().to_degrees())
+                })
+                .reduce(f64::min);
+            quality.skewness = faces
+                .iter()
+                .map(|face| skewness(&face.iter().map(|&i| corners[i]).collect::<Vec<_>>()))
+                .fold(0.0, f64::max);
+        }
+    }
+    Some(quality)
+}
+
+/// Corners with three edges forming a right-handed frame in valid elements,
+/// as `[corner, a, b, c]`, and the factor scaling the determinant of the
+/// frame to 1 for ideal shapes
+fn corner_frames(family: ElementFamily) -> (&'static [[usize; 4]], f64) {
+    match family {
+        ElementFamily::Tetrahedron => (
+            &[[0, 1, 2, 3], [1, 2, 0, 3], [2, 0, 1, 3], [3, 0, 2, 1]],
+            SQRT_2,
+        ),
+        ElementFamily::Hexahedron => (
+            &[
+                [0, 1, 3, 4],
+                [1, 2, 0, 5],
+                [2, 3, 1, 6],
+                [3, 0, 2, 7],
+                [4, 7, 5, 0],
+                [5, 4, 6, 1],
+                [6, 5, 7, 2],
+                [7, 6, 4, 3],
+            ],
+            1.0,
+        ),
+        ElementFamily::Prism => (
+            &[
+                [0, 1, 2, 3],
+                [1, 2, 0, 4],
+                [2, 0, 1, 5],
+                [3, 5, 4, 0],
+                [4, 3, 5, 1],
+                [5, 4, 3, 2],
+            ],
+            2.0 / 3f64.sqrt(),
+        ),
+        // The apex has four edges and no frame of its own
+        ElementFamily::Pyramid => (
+            &[[0, 1, 3, 4], [1, 2, 0, 4], [2, 3, 1, 4], [3, 0, 2, 4]],
+            SQRT_2,
+        ),
+        _ => (&[], 1.0),
+    }
+}
+
+/// Equiangular skewness of a polygon: how far its angles are from those of
+/// the regular polygon with as many corners
+fn skewness(polygon: &[[f64; 3]]) -> f64 {
+    let n = polygon.len();
+    let ideal = 180.0 * (n as f64 - 2.0) / n as f64;
+    let (min, max) = (0..n)
+        .map(|i| {
+            let p = polygon[i];
+            let next = sub(polygon[(i + 1) % n], p);
+            let prev = sub(polygon[(i + n - 1) % n], p);
+            let lengths = norm(next) * norm(prev);
+            if lengths > 0.0 {
+                (dot(next, prev) / lengths)
+                    .clamp(-1.0, 1.0)
+                    .acos()
+                    .to_degrees()
+            } else {
+                0.0
+            }
+        })
+        .fold((f64::INFINITY, 0.0_f64), |(min, max), angle| {
+            (min.min(angle), max.max(angle))
+        });
+    ((max - ideal) / (180.0 - ideal))
+        .max((ideal - min) / ideal)
+        .clamp(0.0, 1.0)
+}
+
+/// Normal of a polygon with the length of twice its area, by Newell's method
+fn newell_normal(polygon: &[[f64; 3]]) -> [f64; 3] {
+    let n = polygon.len();
+    (0..n).fold([0.0; 3], |acc, i| {
+        add(acc, cross(polygon[i], polygon[(i + 1) % n]))
+    })
+}
+
+/// What is wrong with the shape of an element, see [`Mesh::validate_geometry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GeometryIssueKind {
+    /// Negative size, or a corner whose edges are left-handed
+    Inverted,
+    /// Zero length, area or volume at a corner, e.g. coincident nodes
+    Degenerate,
+}
+
+/// An element with an invalid shape
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeometryIssue {
+    pub element: ElementTag,
+    pub element_type: ElementType,
+    pub kind: GeometryIssueKind,
+    /// Signed length, area or volume of the element
+    pub size: f64,
+}
+
+/// Summary of one metric over many elements
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetricSummary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Number of values in each of [`HISTOGRAM_BINS`] equal bins between
+    /// `min` and `max`
+    pub histogram: Vec<usize>,
+}
+
+impl MetricSummary {
+    /// Summarize the finite `values`; infinite ones come from degenerate
+    /// elements and are left out
+    pub fn from_values(values: impl IntoIterator<Item = f64>) -> Self {
+        let values: Vec<f64> = values.into_iter().filter(|v| v.is_finite()).collect();
+        if values.is_empty() {
+            return Self::default();
+        }
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mut histogram = vec![0; HISTOGRAM_BINS];
+        for &v in &values {
+            let bin = if max > min {
+                ((v - min) / (max - min) * HISTOGRAM_BINS as f64) as usize
+            } else {
+                0
+            };
+            histogram[bin.min(HISTOGRAM_BINS - 1)] += 1;
+        }
+        Self {
+            min,
+            max,
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            histogram,
+        }
+    }
+
+    /// Range of values counted in bin `i` of the histogram
+    pub fn bin_range(&self, i: usize) -> (f64, f64) {
+        let width = (self.max - self.min) / HISTOGRAM_BINS as f64;
+        (
+            self.min + width * i as f64,
+            self.min + width * (i + 1) as f64,
+        )
+    }
+}
+
+/// Quality of the elements of the highest dimension of a mesh, see
+/// [`Mesh::quality_report`]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualityReport {
+    /// Dimension of the measured elements
+    pub dimension: i32,
+    /// Number of measured elements
+    pub count: usize,
+    /// Inverted or degenerate elements, see [`ElementQuality::is_inverted`]
+    pub inverted: Vec<ElementTag>,
+    pub size: MetricSummary,
+    pub aspect_ratio: MetricSummary,
+    pub scaled_jacobian: MetricSummary,
+    /// Only for volume elements
+    pub min_dihedral_angle: Option<MetricSummary>,
+    pub skewness: MetricSummary,
+}
+
+impl QualityReport {
+    /// Summarize `qualities`, whatever their dimension
+    pub fn from_qualities(qualities: &[ElementQuality]) -> Self {
+        let dimension = qualities
+            .iter()
+            .map(|q| q.element_type.dimension())
+            .max()
+            .unwrap_or(0);
+        let dihedral: Vec<f64> = qualities
+            .iter()
+            .filter_map(|q| q.min_dihedral_angle)
+            .collect();
+        Self {
+            dimension,
+            count: qualities.len(),
+            inverted: qualities
+                .iter()
+                .filter(|q| q.is_inverted())
+                .map(|q| q.tag)
+                .collect(),
+            size: MetricSummary::from_values(qualities.iter().map(|q| q.size)),
+            aspect_ratio: MetricSummary::from_values(qualities.iter().map(|q| q.aspect_ratio)),
+            scaled_jacobian: MetricSummary::from_values(
+                qualities.iter().map(|q| q.scaled_jacobian),
+            ),
+            min_dihedral_angle: (!dihedral.is_empty())
+                .then(|| MetricSummary::from_values(dihedral)),
+            skewness: MetricSummary::from_values(qualities.iter().map(|q| q.skewness)),
+        }
+    }
+}
+
+impl Mesh {
+    /// Quality of every element that [`element_quality`] can measure, in
+    /// file order
+    ///
+    /// Elements using nodes missing from the node blocks are skipped.
+    pub fn element_quality(&self) -> Vec<ElementQuality> {
+        let coords = node_coordinates(self);
+        let elements = self
+            .elements()
+            .map(|e| (e.tag(), e.element_type(), e.nodes()));
+        elements
+            .filter_map(|(tag, element_type, nodes)| {
+                let points = nodes
+                    .iter()
+                    .map(|node: &NodeTag| coords.get(node).copied())
+                    .collect::<Option<Vec<_>>>()?;
+                element_quality(tag, element_type, &points)
+            })
+            .collect()
+    }
+
+    /// Summary of the quality of the elements of the highest dimension
+    pub fn quality_report(&self) -> QualityReport {
+        let qualities = self.element_quality();
+        let dimension = qualities
+            .iter()
+            .map(|q| q.element_type.dimension())
+            .max()
+            .unwrap_or(0);
+        let highest: Vec<ElementQuality> = qualities
+            .into_iter()
+            .filter(|q| q.element_type.dimension() == dimension)
+            .collect();
+        QualityReport::from_qualities(&highest)
+    }
+
+    /// Find the inverted and degenerate elements
+    ///
+    /// Signed lengths, areas and volumes come from [`element_quality`], which
+    /// also gives the orientation convention of surface elements. An element
+    /// is degenerate when its scaled Jacobian is within
+    /// [`DEGENERACY_TOLERANCE`] of zero. Unlike [`Mesh::validate`], every
+    /// problem is returned, in file order.
+    pub fn validate_geometry(&self) -> Vec<GeometryIssue> {
+        self.element_quality()
+            .into_iter()
+            .filter_map(|q| {
+                let kind = if q.scaled_jacobian.abs() <= DEGENERACY_TOLERANCE {
+                    GeometryIssueKind::Degenerate
+                } else if q.size < 0.0 || q.is_inverted() {
+                    GeometryIssueKind::Inverted
+                } else {
+                    return None;
+                };
+                Some(GeometryIssue {
+                    element: q.tag,
+                    element_type: q.element_type,
+                    kind,
+                    size: q.size,
+                })
+            })
+            .collect()
+    }
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f64; 3], s: f64) -> [f64; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(a: [f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::ReferenceElement;
+
+    #[test]
+    fn test_reference_elements_are_valid() {
+        for element_type in [
+            ElementType::Triangle3,
+            ElementType::Quadrangle4,
+            ElementType::Tetrahedron4,
+            ElementType::Hexahedron8,
+            ElementType::Prism6,
+            ElementType::Pyramid5,
+            ElementType::Hexahedron27,
+        ] {
+            let reference = ReferenceElement::new(element_type).unwrap();
+            let quality = element_quality(ElementTag(1), element_type, &reference.nodes).unwrap();
+            assert!(quality.size > 0.0, "{}", element_type);
+            assert!(!quality.is_inverted(), "{}", element_type);
+        }
+
+        let cube = ReferenceElement::new(ElementType::Hexahedron8).unwrap();
+        let quality =
+            element_quality(ElementTag(1), ElementType::Hexahedron8, &cube.nodes).unwrap();
+        assert!((quality.size - 8.0).abs() < 1e-12);
+        assert!((quality.scaled_jacobian - 1.0).abs() < 1e-12);
+        assert!((quality.min_dihedral_angle.unwrap() - 90.0).abs() < 1e-9);
+        assert!(quality.skewness.abs() < 1e-12);
+        assert_eq!(quality.aspect_ratio, 1.0);
+
+        // Regular tetrahedron
+        let s = 1.0 / SQRT_2;
+        let regular = [
+            [1.0, 0.0, -s],
+            [-1.0, 0.0, -s],
+            [0.0, 1.0, s],
+            [0.0, -1.0, s],
+        ];
+        let quality = element_quality(ElementTag(1), ElementType::Tetrahedron4, &regular).unwrap();
+        assert!((quality.scaled_jacobian.abs() - 1.0).abs() < 1e-12);
+        let dihedral = quality.min_dihedral_angle.unwrap();
+        assert!((dihedral - (1.0_f64 / 3.0).acos().to_degrees()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_inverted_elements() {
+        let tet = [
+            [0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ];
+        let quality = element_quality(ElementTag(3), ElementType::Tetrahedron4, &tet).unwrap();
+        assert!(quality.is_inverted());
+        assert!((quality.size + 1.0 / 6.0).abs() < 1e-12);
+
+        let clockwise = [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]];
+        let quality = element_quality(ElementTag(4), ElementType::Triangle3, &clockwise).unwrap();
+        assert!(quality.is_inverted());
+        let report = QualityReport::from_qualities(&[quality]);
+        assert_eq!(report.inverted, [ElementTag(4)]);
+        assert_eq!(report.min_dihedral_angle, None);
+    }
+
+    #[test]
+    fn test_validate_geometry() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 5 1 5\n3 1 0 5\n1\n2\n3\n4\n5\n\
+            0 0 0\n1 0 0\n0 1 0\n0 0 1\n1 1 0\n$EndNodes\n\
+            $Elements\n1 3 1 3\n3 1 4 3\n1 1 2 3 4\n2 1 3 2 4\n3 1 2 3 5\n$EndElements\n";
+        let mesh = crate::parse_msh(content).unwrap();
+        let issues = mesh.validate_geometry();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].element, ElementTag(2));
+        assert_eq!(issues[0].kind, GeometryIssueKind::Inverted);
+        assert!(issues[0].size < 0.0);
+        assert_eq!(issues[1].element, ElementTag(3));
+        assert_eq!(issues[1].kind, GeometryIssueKind::Degenerate);
+    }
+
+    #[test]
+    fn test_quality_report() {
+        // A unit square of two triangles, with a line on its bottom edge
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+            $Elements\n2 3 1 3\n1 1 1 1\n1 1 2\n2 1 2 2\n2 1 2 3\n3 1 3 4\n$EndElements\n";
+        let mesh = crate::parse_msh(content).unwrap();
+        let report = mesh.quality_report();
+        assert_eq!(report.dimension, 2);
+        assert_eq!(report.count, 2);
+        assert!(report.inverted.is_empty());
+        assert_eq!(report.size.histogram.iter().sum::<usize>(), 2);
+        assert!((report.size.min - 0.5).abs() < 1e-12 && (report.size.max - 0.5).abs() < 1e-12);
+        assert!(report.skewness.max < 1.0);
+        assert!(report.aspect_ratio.min >= 1.0);
+    }
+}
@@ -0,0 +1,471 @@
+//! Reading and writing meshes by file format
+//!
+//! A [`FormatRegistry`] maps file extensions and leading bytes to
+//! [`FileFormat`]s, so that [`read_any`] and [`write_any`] pick the parser or
+//! writer from the path alone:
+//!
+//! ```no_run
+//! use gmsh_parser::registry::{read_any, write_any};
+//!
+//! let mesh = read_any("model.msh.gz").unwrap();
+//! write_any("model.vtu", &mesh).unwrap();
+//! ```
+//!
+//! Applications add their own formats by implementing [`FileFormat`] and
+//! registering it; formats registered last take precedence, so a built-in
+//! format can also be replaced.
+
+use crate::error::{ParseError, Result};
+use crate::export::{ExportFormat, StlOptions};
+use crate::parser::compression::{GZIP_MAGIC, ZSTD_MAGIC};
+use crate::parser::parse_msh_bytes;
+use crate::types::Mesh;
+use crate::writer::{write_msh_to, WriteOptions};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Number of leading bytes passed to [`FileFormat::sniff`]
+pub const SNIFF_LEN: usize = 512;
+
+/// A mesh file format known to a [`FormatRegistry`]
+///
+/// Only [`name`](Self::name) and [`extensions`](Self::extensions) are
+/// required; a format that neither reads nor writes is only useful for
+/// identification.
+pub trait FileFormat: Send + Sync {
+    /// Short name, e.g. `"msh"`
+    fn name(&self) -> &str;
+
+    /// File extensions without the leading dot, e.g. `["msh", "msh.gz"]`,
+    /// matched case-insensitively
+    fn extensions(&self) -> &[&str];
+
+    /// Whether `head`, the first [`SNIFF_LEN`] bytes of a file at most, looks
+    /// like this format
+    fn sniff(&self, head: &[u8]) -> bool {
+        let _ = head;
+        false
+    }
+
+    fn can_read(&self) -> bool {
+        false
+    }
+
+    fn can_write(&self) -> bool {
+        false
+    }
+
+    /// Parse the content of a file, called only if [`can_read`](Self::can_read)
+    fn read(&self, bytes: &[u8]) -> Result<Mesh> {
+        let _ = bytes;
+        Err(unsupported(self.name(), "reader"))
+    }
+
+    /// Write `mesh`, called only if [`can_write`](Self::can_write)
+    fn write(&self, mesh: &Mesh, writer: &mut dyn Write) -> Result<()> {
+        let _ = (mesh, writer);
+        Err(unsupported(self.name(), "writer"))
+    }
+}
+
+fn unsupported(name: &str, what: &str) -> ParseError {
+    ParseError::NotFound(format!("{} for {} files", what, name))
+}
+
+/// ASCII MSH 4.1, read and written
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Msh;
+
+impl FileFormat for Msh {
+    fn name(&self) -> &str {
+        "msh"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["msh"]
+    }
+
+    fn sniff(&self, head: &[u8]) -> bool {
+        head.trim_ascii_start().starts_with(b"$MeshFormat")
+    }
+
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    fn can_write(&self) -> bool {
+        true
+    }
+
+    fn read(&self, bytes: &[u8]) -> Result<Mesh> {
+        parse_msh_bytes(bytes)
+    }
+
+    fn write(&self, mesh: &Mesh, mut writer: &mut dyn Write) -> Result<()> {
+        write_msh_to(mesh, &WriteOptions::default(), &mut writer)
+    }
+}
+
+/// Gzip compressed [`Msh`], read and written with the `gzip` feature
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MshGz;
+
+impl FileFormat for MshGz {
+    fn name(&self) -> &str {
+        "msh.gz"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["msh.gz"]
+    }
+
+    fn sniff(&self, head: &[u8]) -> bool {
+        head.starts_with(&GZIP_MAGIC)
+    }
+
+    /// Always true, so that reading without the feature reports it missing
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    fn can_write(&self) -> bool {
+        cfg!(feature = "gzip")
+    }
+
+    fn read(&self, bytes: &[u8]) -> Result<Mesh> {
+        parse_msh_bytes(bytes)
+    }
+
+    #[cfg(feature = "gzip")]
+    fn write(&self, mesh: &Mesh, writer: &mut dyn Write) -> Result<()> {
+        let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+        Msh.write(mesh, &mut encoder)?;
+        encoder.finish()?;
+        Ok(())
+    }
+}
+
+/// Zstd compressed [`Msh`], read and written with the `zstd` feature
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MshZst;
+
+impl FileFormat for MshZst {
+    fn name(&self) -> &str {
+        "msh.zst"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["msh.zst"]
+    }
+
+    fn sniff(&self, head: &[u8]) -> bool {
+        head.starts_with(&ZSTD_MAGIC)
+    }
+
+    /// Always true, so that reading without the feature reports it missing
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    fn can_write(&self) -> bool {
+        cfg!(feature = "zstd")
+    }
+
+    fn read(&self, bytes: &[u8]) -> Result<Mesh> {
+        parse_msh_bytes(bytes)
+    }
+
+    #[cfg(feature = "zstd")]
+    fn write(&self, mesh: &Mesh, writer: &mut dyn Write) -> Result<()> {
+        let mut encoder = zstd::stream::write::Encoder::new(writer, 0)?;
+        Msh.write(mesh, &mut encoder)?;
+        encoder.finish()?;
+        Ok(())
+    }
+}
+
+/// VTK XML unstructured grid, written with [`Mesh::export`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Vtu;
+
+impl FileFormat for Vtu {
+    fn name(&self) -> &str {
+        "vtu"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["vtu"]
+    }
+
+    fn sniff(&self, head: &[u8]) -> bool {
+        head.windows(8).any(|w| w == b"<VTKFile")
+    }
+
+    fn can_write(&self) -> bool {
+        true
+    }
+
+    fn write(&self, mesh: &Mesh, mut writer: &mut dyn Write) -> Result<()> {
+        mesh.export()
+            .format(ExportFormat::Vtu)
+            .to_writer(&mut writer)
+    }
+}
+
+/// Legacy VTK unstructured grid, written with [`Mesh::export`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Vtk;
+
+impl FileFormat for Vtk {
+    fn name(&self) -> &str {
+        "vtk"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["vtk"]
+    }
+
+    fn sniff(&self, head: &[u8]) -> bool {
+        head.starts_with(b"# vtk DataFile")
+    }
+
+    fn can_write(&self) -> bool {
+        true
+    }
+
+    fn write(&self, mesh: &Mesh, mut writer: &mut dyn Write) -> Result<()> {
+        mesh.export()
+            .format(ExportFormat::Vtk)
+            .to_writer(&mut writer)
+    }
+}
+
+/// Binary STL of the surface elements, written with [`Mesh::export_stl`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stl;
+
+impl FileFormat for Stl {
+    fn name(&self) -> &str {
+        "stl"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["stl"]
+    }
+
+    fn can_write(&self) -> bool {
+        true
+    }
+
+    fn write(&self, mesh: &Mesh, mut writer: &mut dyn Write) -> Result<()> {
+        mesh.export_stl(&mut writer, &StlOptions::default())
+    }
+}
+
+/// File formats by extension and content
+pub struct FormatRegistry {
+    /// In registration order, searched from the end
+    formats: Vec<Box<dyn FileFormat>>,
+}
+
+impl Default for FormatRegistry {
+    /// The built-in formats: [`Msh`], [`MshGz`], [`MshZst`], [`Vtu`], [`Vtk`]
+    /// and [`Stl`]
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(Msh);
+        registry.register(MshGz);
+        registry.register(MshZst);
+        registry.register(Vtu);
+        registry.register(Vtk);
+        registry.register(Stl);
+        registry
+    }
+}
+
+impl FormatRegistry {
+    /// A registry without any format, see [`FormatRegistry::default`] for
+    /// the built-in ones
+    pub fn new() -> Self {
+        Self {
+            formats: Vec::new(),
+        }
+    }
+
+    /// Add `format`, which takes precedence over those already registered
+    pub fn register(&mut self, format: impl FileFormat + 'static) -> &mut Self {
+        self.formats.push(Box::new(format));
+        self
+    }
+
+    /// Registered formats, most recent first
+    pub fn formats(&self) -> impl Iterator<Item = &dyn FileFormat> {
+        self.formats.iter().rev().map(|f| f.as_ref())
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&dyn FileFormat> {
+        self.formats().find(|f| f.name() == name)
+    }
+
+    /// Format whose longest extension matches the file name of `path`
+    pub fn by_extension(&self, path: impl AsRef<Path>) -> Option<&dyn FileFormat> {
+        let file_name = path.as_ref().file_name()?.to_str()?.to_ascii_lowercase();
+        let matched = |ext: &str| {
+            file_name
+                .strip_suffix(&ext.to_ascii_lowercase())
+                .is_some_and(|stem| stem.ends_with('.'))
+        };
+        let mut best: Option<(usize, &dyn FileFormat)> = None;
+        for format in self.formats() {
+            let extensions = format.extensions().iter();
+            let len = extensions.filter(|e| matched(e)).map(|e| e.len()).max();
+            if let Some(len) = len.filter(|&len| best.is_none_or(|(best, _)| len > best)) {
+                best = Some((len, format));
+            }
+        }
+        best.map(|(_, format)| format)
+    }
+
+    /// First readable format recognizing the leading bytes of a file
+    pub fn by_content(&self, bytes: &[u8]) -> Option<&dyn FileFormat> {
+        let head = &bytes[..bytes.len().min(SNIFF_LEN)];
+        self.formats().find(|f| f.can_read() && f.sniff(head))
+    }
+
+    /// Read the mesh at `path` with the format of its extension, or else the
+    /// format recognizing its content
+    ///
+    /// # Errors
+    /// Returns [`ParseError::NotFound`] if no readable format matches, or the
+    /// error of the file system or the format.
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Mesh> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let format = self
+            .by_extension(path)
+            .filter(|f| f.can_read())
+            .or_else(|| self.by_content(&bytes))
+            .ok_or_else(|| ParseError::NotFound(format!("Reader for {}", path.display())))?;
+        format.read(&bytes)
+    }
+
+    /// Write `mesh` to `path` with the format of its extension
+    ///
+    /// # Errors
+    /// Returns [`ParseError::NotFound`] if no writable format has the
+    /// extension, or the error of the file system or the format.
+    pub fn write(&self, path: impl AsRef<Path>, mesh: &Mesh) -> Result<()> {
+        let path = path.as_ref();
+        let format = self
+            .by_extension(path)
+            .filter(|f| f.can_write())
+            .ok_or_else(|| ParseError::NotFound(format!("Writer for {}", path.display())))?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        format.write(mesh, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Read the mesh at `path` with the built-in formats, see
+/// [`FormatRegistry::read`]
+pub fn read_any(path: impl AsRef<Path>) -> Result<Mesh> {
+    FormatRegistry::default().read(path)
+}
+
+/// Write `mesh` to `path` with the built-in format of its extension, see
+/// [`FormatRegistry::write`]
+pub fn write_any(path: impl AsRef<Path>, mesh: &Mesh) -> Result<()> {
+    FormatRegistry::default().write(path, mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::TRIANGLE;
+
+    /// One node coordinate per line
+    struct Nodes;
+
+    impl FileFormat for Nodes {
+        fn name(&self) -> &str {
+            "nodes"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["xyz", "msh"]
+        }
+
+        fn can_write(&self) -> bool {
+            true
+        }
+
+        fn write(&self, mesh: &Mesh, writer: &mut dyn Write) -> Result<()> {
+            for node in mesh.node_blocks.iter().flat_map(|b| &b.nodes) {
+                writeln!(writer, "{} {} {}", node.x, node.y, node.z)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lookup_by_extension_and_content() {
+        let registry = FormatRegistry::default();
+        assert_eq!(registry.by_extension("a/b.MSH").unwrap().name(), "msh");
+        assert_eq!(registry.by_extension("b.msh.gz").unwrap().name(), "msh.gz");
+        assert_eq!(registry.by_extension("b.vtu").unwrap().name(), "vtu");
+        assert!(registry.by_extension("b.gz").is_none());
+        assert!(registry.by_extension("msh").is_none());
+
+        let content = b"\n$MeshFormat\n4.1 0 8\n$EndMeshFormat\n";
+        assert_eq!(registry.by_content(content).unwrap().name(), "msh");
+        assert_eq!(registry.by_content(&ZSTD_MAGIC).unwrap().name(), "msh.zst");
+        assert!(registry.by_content(b"# vtk DataFile").is_none());
+    }
+
+    #[test]
+    fn test_read_and_write_any() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("triangle.msh");
+        std::fs::write(&path, TRIANGLE).unwrap();
+        let mesh = read_any(&path).unwrap();
+        assert_eq!(mesh.num_nodes(), 3);
+
+        // Unknown extension, recognized by content
+        let renamed = dir.path().join("triangle.mesh");
+        std::fs::write(&renamed, TRIANGLE).unwrap();
+        assert_eq!(read_any(&renamed).unwrap().num_elements(), 2);
+
+        write_any(dir.path().join("triangle.vtk"), &mesh).unwrap();
+        let vtk = std::fs::read(dir.path().join("triangle.vtk")).unwrap();
+        assert!(Vtk.sniff(&vtk));
+        assert!(matches!(
+            write_any(dir.path().join("triangle.txt"), &mesh),
+            Err(ParseError::NotFound(_))
+        ));
+
+        // A registered format overrides the built-in one
+        let mut registry = FormatRegistry::default();
+        registry.register(Nodes);
+        registry.write(&path, &mesh).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text, "0 0 0\n1 0 0\n0 1 0\n");
+    }
+
+    #[test]
+    fn test_write_compressed_msh() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.msh.gz");
+        let mesh = crate::parse_msh("$MeshFormat\n4.1 0 8\n$EndMeshFormat\n").unwrap();
+        let written = write_any(&path, &mesh);
+
+        if cfg!(feature = "gzip") {
+            written.unwrap();
+            assert!(std::fs::read(&path).unwrap().starts_with(&GZIP_MAGIC));
+            assert_eq!(read_any(&path).unwrap().format.data_size, 8);
+        } else {
+            assert!(matches!(written, Err(ParseError::NotFound(_))));
+        }
+    }
+}
@@ -0,0 +1,417 @@
+//! Content-addressed store of parsed meshes
+//!
+//! A [`MeshRepo`] keeps meshes in a directory, keyed by their [`Fingerprint`]
+//! (a hash of the parsed content, so the same mesh parsed from differently
+//! formatted files is stored once). Each entry carries [`EntryMetadata`] used for
+//! lookups and garbage collection.
+//!
+//! Enabled by the `cache` feature.
+
+use crate::error::{ParseError, Result, WarningKind};
+use crate::hash::Fnv1a;
+use crate::sets::{ElementSet, NodeSet};
+use crate::types::{
+    CoordinateSystem, ElementBlock, ElementBlockCompact, ElementData, ElementNodeData, ElementTag,
+    Entities, GhostElement, InterpolationScheme, Mesh, MeshFormat, NodeBlock, NodeData, NodeTag,
+    Parametrizations, PartitionedEntities, PeriodicLink, PhysicalName, SectionHeader,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// Hash identifying the content of a mesh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fingerprint(pub u64);
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(Fingerprint)
+    }
+}
+
+impl Mesh {
+    /// Fingerprint of the parsed content of this mesh
+    ///
+    /// Parse warnings are part of the content, by kind and message; source text
+    /// layout (whitespace, number formatting, and so the file locations held by
+    /// warnings and raw sections) is not, nor is the [change log](Mesh::change_log).
+    pub fn fingerprint(&self) -> Fingerprint {
+        let mut hasher = Fnv1a::default();
+        // Writing into the hasher cannot fail
+        let _ = bincode::serialize_into(&mut hasher, &Content::of(self));
+        Fingerprint(hasher.finish())
+    }
+}
+
+/// The fields of a mesh hashed by [`Mesh::fingerprint`]
+#[derive(serde::Serialize)]
+struct Content<'a> {
+    format: &'a MeshFormat,
+    physical_names: &'a [PhysicalName],
+    entities: &'a Option<Entities>,
+    partitioned_entities: &'a Option<PartitionedEntities>,
+    node_blocks: &'a [NodeBlock],
+    element_blocks: &'a [ElementBlock],
+    compact_element_blocks: &'a [ElementBlockCompact],
+    nodes_header: &'a Option<SectionHeader<NodeTag>>,
+    elements_header: &'a Option<SectionHeader<ElementTag>>,
+    periodic_links: &'a [PeriodicLink],
+    ghost_elements: &'a [GhostElement],
+    parametrizations: &'a Option<Parametrizations>,
+    node_data: &'a [NodeData],
+    element_data: &'a [ElementData],
+    element_node_data: &'a [ElementNodeData],
+    interpolation_schemes: &'a [InterpolationScheme],
+    node_sets: &'a [NodeSet],
+    element_sets: &'a [ElementSet],
+    coordinate_system: &'a Option<CoordinateSystem>,
+    /// Name and lines of each raw section
+    raw_sections: Vec<(&'a str, &'a [String])>,
+    /// Kind and message of each warning
+    warnings: Vec<(WarningKind, &'a str)>,
+}
+
+impl<'a> Content<'a> {
+    fn of(mesh: &'a Mesh) -> Self {
+        // Destructured so that a new field of `Mesh` has to be placed here
+        let Mesh {
+            format,
+            physical_names,
+            entities,
+            partitioned_entities,
+            node_blocks,
+            element_blocks,
+            compact_element_blocks,
+            nodes_header,
+            elements_header,
+            periodic_links,
+            ghost_elements,
+            parametrizations,
+            node_data,
+            element_data,
+            element_node_data,
+            interpolation_schemes,
+            node_sets,
+            element_sets,
+            coordinate_system,
+            raw_sections,
+            warnings,
+            change_log: _,
+            counts: _,
+        } = mesh;
+        Self {
+            format,
+            physical_names,
+            entities,
+            partitioned_entities,
+            node_blocks,
+            element_blocks,
+            compact_element_blocks,
+            nodes_header,
+            elements_header,
+            periodic_links,
+            ghost_elements,
+            parametrizations,
+            node_data,
+            element_data,
+            element_node_data,
+            interpolation_schemes,
+            node_sets,
+            element_sets,
+            coordinate_system,
+            raw_sections: raw_sections
+                .iter()
+                .map(|section| (section.name.as_str(), section.lines.as_slice()))
+                .collect(),
+            warnings: warnings
+                .iter()
+                .map(|warning| (warning.kind, warning.message.as_str()))
+                .collect(),
+        }
+    }
+}
+
+/// Information stored next to each mesh in a [`MeshRepo`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryMetadata {
+    pub fingerprint: Fingerprint,
+    /// File the mesh was parsed from, if known
+    pub source_path: Option<PathBuf>,
+    /// Free-form description of how the mesh was produced (parse options, tool versions, ...)
+    pub options: BTreeMap<String, String>,
+    pub created: SystemTime,
+    pub last_accessed: SystemTime,
+}
+
+/// A directory of meshes keyed by fingerprint
+#[derive(Debug, Clone)]
+pub struct MeshRepo {
+    root: PathBuf,
+}
+
+fn repo_error(error: impl fmt::Display) -> ParseError {
+    ParseError::CacheError(error.to_string())
+}
+
+impl MeshRepo {
+    /// Open the repository at `root`, creating the directory if needed
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        std::fs::create_dir_all(&root)?;
+        Ok(Self {
+            root: root.as_ref().to_path_buf(),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn mesh_path(&self, fingerprint: Fingerprint) -> PathBuf {
+        self.root.join(format!("{}.mesh", fingerprint))
+    }
+
+    fn metadata_path(&self, fingerprint: Fingerprint) -> PathBuf {
+        self.root.join(format!("{}.meta", fingerprint))
+    }
+
+    fn write_metadata(&self, metadata: &EntryMetadata) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(self.metadata_path(metadata.fingerprint))?);
+        bincode::serialize_into(&mut writer, metadata).map_err(repo_error)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Store `mesh` and return its fingerprint
+    ///
+    /// If the mesh is already present only its metadata is updated: the source
+    /// path and options are replaced when given, and the access time refreshed.
+    pub fn insert(
+        &self,
+        mesh: &Mesh,
+        source_path: Option<&Path>,
+        options: BTreeMap<String, String>,
+    ) -> Result<Fingerprint> {
+        let fingerprint = mesh.fingerprint();
+        let now = SystemTime::now();
+
+        let metadata = match self.metadata(fingerprint)? {
+            Some(mut existing) if self.mesh_path(fingerprint).exists() => {
+                if let Some(path) = source_path {
+                    existing.source_path = Some(path.to_path_buf());
+                }
+                if !options.is_empty() {
+                    existing.options = options;
+                }
+                existing.last_accessed = now;
+                existing
+            }
+            _ => {
+                let mut writer = BufWriter::new(File::create(self.mesh_path(fingerprint))?);
+                bincode::serialize_into(&mut writer, mesh).map_err(repo_error)?;
+                writer.flush()?;
+                EntryMetadata {
+                    fingerprint,
+                    source_path: source_path.map(Path::to_path_buf),
+                    options,
+                    created: now,
+                    last_accessed: now,
+                }
+            }
+        };
+
+        self.write_metadata(&metadata)?;
+        Ok(fingerprint)
+    }
+
+    /// Parse `path` and store the result
+    pub fn insert_file(&self, path: impl AsRef<Path>) -> Result<Fingerprint> {
+        let mesh = crate::parse_msh_file(&path)?;
+        self.insert(&mesh, Some(path.as_ref()), BTreeMap::new())
+    }
+
+    pub fn contains(&self, fingerprint: Fingerprint) -> bool {
+        self.mesh_path(fingerprint).exists()
+    }
+
+    /// Load the mesh with `fingerprint`, refreshing its access time
+    pub fn get(&self, fingerprint: Fingerprint) -> Result<Option<Mesh>> {
+        let file = match File::open(self.mesh_path(fingerprint)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mesh = bincode::deserialize_from(BufReader::new(file)).map_err(repo_error)?;
+
+        if let Some(mut metadata) = self.metadata(fingerprint)? {
+            metadata.last_accessed = SystemTime::now();
+            self.write_metadata(&metadata)?;
+        }
+        Ok(Some(mesh))
+    }
+
+    pub fn metadata(&self, fingerprint: Fingerprint) -> Result<Option<EntryMetadata>> {
+        match File::open(self.metadata_path(fingerprint)) {
+            Ok(file) => bincode::deserialize_from(BufReader::new(file))
+                .map(Some)
+                .map_err(repo_error),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Metadata of all entries, sorted by fingerprint
+    pub fn entries(&self) -> Result<Vec<EntryMetadata>> {
+        let mut entries = Vec::new();
+        for fingerprint in self.fingerprints()? {
+            if let Some(metadata) = self.metadata(fingerprint)? {
+                entries.push(metadata);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Entries parsed from `path`
+    pub fn find_by_source(&self, path: impl AsRef<Path>) -> Result<Vec<EntryMetadata>> {
+        let path = path.as_ref();
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|e| e.source_path.as_deref() == Some(path))
+            .collect())
+    }
+
+    /// Remove an entry; returns whether it existed
+    pub fn remove(&self, fingerprint: Fingerprint) -> Result<bool> {
+        let mut existed = false;
+        for path in [self.mesh_path(fingerprint), self.metadata_path(fingerprint)] {
+            match std::fs::remove_file(path) {
+                Ok(()) => existed = true,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(existed)
+    }
+
+    /// Remove entries not accessed within `max_age`, as well as incomplete entries
+    ///
+    /// Returns the fingerprints that were removed.
+    pub fn gc(&self, max_age: Duration) -> Result<Vec<Fingerprint>> {
+        let now = SystemTime::now();
+        let mut removed = Vec::new();
+
+        for fingerprint in self.fingerprints()? {
+            let stale = match self.metadata(fingerprint)? {
+                Some(metadata) if self.contains(fingerprint) => now
+                    .duration_since(metadata.last_accessed)
+                    .is_ok_and(|age| age > max_age),
+                _ => true,
+            };
+            if stale {
+                self.remove(fingerprint)?;
+                removed.push(fingerprint);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Fingerprints with a mesh or metadata file, sorted
+    fn fingerprints(&self) -> Result<Vec<Fingerprint>> {
+        let mut fingerprints = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            let is_entry = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("mesh") | Some("meta")
+            );
+            let fingerprint = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse().ok());
+            if let (true, Some(fingerprint)) = (is_entry, fingerprint) {
+                fingerprints.push(fingerprint);
+            }
+        }
+        fingerprints.sort_unstable();
+        fingerprints.dedup();
+        Ok(fingerprints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::LINE;
+
+    #[test]
+    fn test_repo_insert_and_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = MeshRepo::open(dir.path().join("repo")).unwrap();
+        let source = dir.path().join("mesh.msh");
+        std::fs::write(&source, LINE).unwrap();
+
+        let fingerprint = repo.insert_file(&source).unwrap();
+        assert_eq!(repo.insert_file(&source).unwrap(), fingerprint);
+        assert_eq!(repo.entries().unwrap().len(), 1);
+        assert_eq!(repo.find_by_source(&source).unwrap().len(), 1);
+
+        let mesh = repo.get(fingerprint).unwrap().unwrap();
+        assert_eq!(mesh.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_layout() {
+        // The header declares 3 nodes, which is reported with its location
+        let mesh = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 2\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+            $Elements\n1 1 1 1\n1 1 1 1\n1 1 2\n$EndElements\n";
+        let shifted = mesh.replacen("$Nodes", "\n$Nodes", 1);
+        let options = crate::ParserOptions::default().validate_metadata(false);
+        let a = crate::parse_msh_with(mesh, &options).unwrap();
+        let b = crate::parse_msh_with(&shifted, &options).unwrap();
+        assert!(!a.warnings.is_empty());
+        assert_ne!(a.warnings[0].span, b.warnings[0].span);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        let mut logged = a.clone();
+        logged.record_change(crate::types::Change::new("noop"));
+        assert_eq!(logged.fingerprint(), a.fingerprint());
+    }
+
+    #[test]
+    fn test_repo_gc() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = MeshRepo::open(dir.path()).unwrap();
+        let mesh = crate::parse_msh(LINE).unwrap();
+        let fingerprint = repo.insert(&mesh, None, BTreeMap::new()).unwrap();
+
+        let mut other = mesh.clone();
+        other.node_blocks[0].nodes[1].x = 2.0;
+        let options = BTreeMap::from([("variant".to_string(), "b".to_string())]);
+        let other_fingerprint = repo.insert(&other, None, options).unwrap();
+        assert_ne!(other_fingerprint, fingerprint);
+
+        assert!(repo.gc(Duration::from_secs(3600)).unwrap().is_empty());
+        std::fs::remove_file(repo.mesh_path(other_fingerprint)).unwrap();
+        assert_eq!(
+            repo.gc(Duration::from_secs(3600)).unwrap(),
+            vec![other_fingerprint]
+        );
+        assert_eq!(repo.gc(Duration::ZERO).unwrap(), vec![fingerprint]);
+        assert!(repo.get(fingerprint).unwrap().is_none());
+    }
+}
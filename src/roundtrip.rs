@@ -0,0 +1,541 @@
+//! Round-trip checks of the writer, for regression tests
+//!
+//! [`assert_lossless`] parses a MSH file, writes it with the crate writer,
+//! parses the output and panics if the two meshes differ, so a corpus of
+//! meshes can be checked with a few lines in a test:
+//!
+//! ```no_run
+//! use gmsh_parser::roundtrip::assert_lossless;
+//!
+//! #[test]
+//! fn corpus_round_trips() {
+//!     for entry in std::fs::read_dir("tests/meshes").unwrap() {
+//!         assert_lossless(entry.unwrap().path());
+//!     }
+//! }
+//! ```
+//!
+//! Reals are compared exactly by default, as the writer prints them in their
+//! shortest round-trip form; give a [`Tolerance`] when writing with fewer
+//! digits (see [`RoundTripOptions`]).
+
+use crate::error::Result;
+use crate::geometry::Tolerance;
+use crate::parser::{parse_msh, parse_msh_file};
+use crate::types::{
+    CoordinateSystem, ElementTag, ElementType, Entities, EntityTag, Mesh, NodeBlock, NodeTag,
+    Parametrizations, PhysicalTag,
+};
+use crate::writer::{write_msh_to, WriteOptions};
+use std::fmt::Debug;
+use std::path::Path;
+
+/// Number of differences listed in the panic message of [`assert_lossless`]
+const MAX_REPORTED: usize = 20;
+
+/// Options of [`check_lossless`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTripOptions {
+    /// Tolerance on reals: coordinates, parametric coordinates and data
+    pub tolerance: Tolerance,
+    /// Options of the writer under test
+    pub write: WriteOptions,
+}
+
+impl Default for RoundTripOptions {
+    /// Exact comparison of the output of the default writer
+    fn default() -> Self {
+        Self {
+            tolerance: Tolerance::new(0.0, 0.0),
+            write: WriteOptions::default(),
+        }
+    }
+}
+
+impl RoundTripOptions {
+    pub fn with_tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_write_options(mut self, write: WriteOptions) -> Self {
+        self.write = write;
+        self
+    }
+}
+
+/// Parse `path`, write it back, parse the output and check that nothing was
+/// lost, panicking with the differences otherwise
+///
+/// # Panics
+/// If `path` or the written mesh cannot be parsed, or the meshes differ.
+#[track_caller]
+pub fn assert_lossless(path: impl AsRef<Path>) {
+    assert_lossless_with(path, &RoundTripOptions::default());
+}
+
+/// [`assert_lossless`] with `options`
+///
+/// # Panics
+/// If `path` or the written mesh cannot be parsed, or the meshes differ.
+#[track_caller]
+pub fn assert_lossless_with(path: impl AsRef<Path>, options: &RoundTripOptions) {
+    let path = path.as_ref();
+    let differences = check_lossless(path, options)
+        .unwrap_or_else(|e| panic!("{}: round trip failed: {:?}", path.display(), e));
+    if differences.is_empty() {
+        return;
+    }
+    let mut message = format!(
+        "{}: {} differences after a round trip",
+        path.display(),
+        differences.len()
+    );
+    for difference in differences.iter().take(MAX_REPORTED) {
+        message.push_str("\n  ");
+        message.push_str(difference);
+    }
+    if differences.len() > MAX_REPORTED {
+        message.push_str("\n  ...");
+    }
+    panic!("{}", message);
+}
+
+/// Parse `path`, write it back with `options.write`, parse the output and
+/// return the differences between the two meshes, see [`compare_meshes`]
+///
+/// # Errors
+/// Returns an error if `path` or the written mesh cannot be parsed.
+pub fn check_lossless(path: impl AsRef<Path>, options: &RoundTripOptions) -> Result<Vec<String>> {
+    let original = parse_msh_file(path)?;
+    let mut written = Vec::new();
+    write_msh_to(&original, &options.write, &mut written)?;
+    let reparsed = parse_msh(String::from_utf8_lossy(&written))?;
+    Ok(compare_meshes(&original, &reparsed, &options.tolerance))
+}
+
+/// Differences between the content of `a` and `b`, one line each, reals
+/// compared within `tolerance`
+///
+/// Everything the writer writes is compared: physical names, entities with
+/// their physical groups, bounding entities and coordinates or boxes,
+/// partitioned and ghost entities, node and element blocks, periodic links,
+/// parametrizations, interpolation schemes, post-processing views, sets, the
+/// coordinate system and unrecognized sections. Element blocks are compared in
+/// order, compact ones after the others. The format, warnings, change log and
+/// declared section headers are not compared.
+pub fn compare_meshes(a: &Mesh, b: &Mesh, tolerance: &Tolerance) -> Vec<String> {
+    let mut diff = Differences {
+        tolerance,
+        found: Vec::new(),
+    };
+
+    let names = |mesh: &Mesh| -> Vec<_> {
+        let names = mesh.physical_names.iter();
+        names
+            .map(|p| (p.dimension as i32, p.tag, p.name.clone()))
+            .collect()
+    };
+    diff.eq("physical names", names(a), names(b));
+    if diff.eq("entities", a.entities.is_some(), b.entities.is_some()) {
+        let entities = |mesh: &Mesh| {
+            mesh.entities
+                .as_ref()
+                .map(entity_records)
+                .unwrap_or_default()
+        };
+        diff.records("entity", entities(a), entities(b));
+    }
+    diff.eq(
+        "partitioned entities",
+        &a.partitioned_entities,
+        &b.partitioned_entities,
+    );
+    diff.eq("ghost elements", &a.ghost_elements, &b.ghost_elements);
+
+    if diff.eq("node blocks", a.node_blocks.len(), b.node_blocks.len()) {
+        for (i, (block_a, block_b)) in a.node_blocks.iter().zip(&b.node_blocks).enumerate() {
+            let context = format!("node block {}", i);
+            let header = |b: &NodeBlock| (b.entity_dim(), b.entity_tag, b.parametric);
+            diff.eq(&context, header(block_a), header(block_b));
+            if !diff.eq(&context, block_a.nodes.len(), block_b.nodes.len()) {
+                continue;
+            }
+            for (node_a, node_b) in block_a.nodes.iter().zip(&block_b.nodes) {
+                let context = format!("node {}", node_a.tag);
+                diff.eq(&context, node_a.tag, node_b.tag);
+                diff.reals(
+                    &context,
+                    &[node_a.x, node_a.y, node_a.z],
+                    &[node_b.x, node_b.y, node_b.z],
+                );
+                let uvw_a = node_a.parametric_coords.as_deref().unwrap_or_default();
+                let uvw_b = node_b.parametric_coords.as_deref().unwrap_or_default();
+                diff.reals(&format!("{} parametric coordinates", context), uvw_a, uvw_b);
+            }
+        }
+    }
+
+    let blocks_a = element_blocks(a);
+    let blocks_b = element_blocks(b);
+    if diff.eq("element blocks", blocks_a.len(), blocks_b.len()) {
+        for (i, (block_a, block_b)) in blocks_a.iter().zip(&blocks_b).enumerate() {
+            let context = format!("element block {}", i);
+            diff.eq(&context, block_a.0, block_b.0);
+            diff.eq(&context, &block_a.1, &block_b.1);
+        }
+    }
+
+    let links = |mesh: &Mesh| -> Vec<_> {
+        let links = mesh.periodic_links.iter();
+        links
+            .map(|l| {
+                (
+                    l.entity_dim as i32,
+                    l.entity_tag,
+                    l.entity_tag_master,
+                    l.node_correspondences.clone(),
+                )
+            })
+            .collect()
+    };
+    if diff.eq("periodic links", links(a), links(b)) {
+        for (link_a, link_b) in a.periodic_links.iter().zip(&b.periodic_links) {
+            let context = format!("periodic link {}", link_a.entity_tag);
+            diff.reals(&context, &link_a.affine_transform, &link_b.affine_transform);
+        }
+    }
+
+    let parametrizations = |mesh: &Mesh| {
+        let parametrizations = mesh.parametrizations.as_ref();
+        parametrizations
+            .map(parametrization_records)
+            .unwrap_or_default()
+    };
+    diff.eq(
+        "parametrizations",
+        a.parametrizations.is_some(),
+        b.parametrizations.is_some(),
+    );
+    diff.records("parametrization", parametrizations(a), parametrizations(b));
+
+    let schemes = |mesh: &Mesh| -> Vec<_> {
+        let schemes = mesh.interpolation_schemes.iter();
+        schemes
+            .map(|s| {
+                let matrices = s.topologies.iter().flat_map(|t| &t.matrices);
+                let shapes = s.topologies.iter().map(|t| {
+                    let shapes = t.matrices.iter().map(|m| (m.num_rows, m.num_columns));
+                    (t.element_topology, shapes.collect::<Vec<_>>())
+                });
+                let values = matrices.flat_map(|m| m.values.iter().copied());
+                (
+                    (s.name.clone(), shapes.collect::<Vec<_>>()),
+                    values.collect(),
+                )
+            })
+            .collect()
+    };
+    diff.records("interpolation scheme", schemes(a), schemes(b));
+
+    let views_a = views(a);
+    let views_b = views(b);
+    if diff.eq("views", views_a.len(), views_b.len()) {
+        for (i, (view_a, view_b)) in views_a.iter().zip(&views_b).enumerate() {
+            let context = format!("view {}", i);
+            let tags = |view: &View| (view.string_tags.to_vec(), view.integer_tags.to_vec());
+            diff.eq(&context, tags(view_a), tags(view_b));
+            let real_tags = format!("{} real tags", context);
+            diff.reals(&real_tags, view_a.real_tags, view_b.real_tags);
+            let keys = |view: &View| view.values.iter().map(|v| v.0).collect::<Vec<_>>();
+            if diff.eq(&context, keys(view_a), keys(view_b)) {
+                for ((key, values_a), (_, values_b)) in view_a.values.iter().zip(&view_b.values) {
+                    diff.reals(&format!("{} entity {}", context, key.0), values_a, values_b);
+                }
+            }
+        }
+    }
+
+    let sets = |mesh: &Mesh| -> Vec<(String, Option<i32>, Vec<i64>)> {
+        let nodes = mesh.node_sets.iter().map(|s| {
+            let tags = s.tags.iter().map(|t| t.0 as i64).collect();
+            (s.name.clone(), s.tag, tags)
+        });
+        let elements = mesh.element_sets.iter().map(|s| {
+            let tags = s.tags.iter().map(|t| t.0 as i64).collect();
+            (s.name.clone(), s.tag, tags)
+        });
+        nodes.chain(elements).collect()
+    };
+    diff.eq("sets", sets(a), sets(b));
+    let frame = |mesh: &Mesh| {
+        let frame = mesh.coordinate_system.as_ref();
+        frame.map(|f| (f.name.clone(), f.unit))
+    };
+    if diff.eq("coordinate system", frame(a), frame(b)) {
+        if let (Some(frame_a), Some(frame_b)) = (&a.coordinate_system, &b.coordinate_system) {
+            let matrix = |f: &CoordinateSystem| f.transform.matrix.concat();
+            diff.reals("coordinate transform", &matrix(frame_a), &matrix(frame_b));
+        }
+    }
+    let raw = |mesh: &Mesh| -> Vec<_> {
+        let sections = mesh.raw_sections.iter();
+        sections
+            .map(|s| (s.name.clone(), s.lines.clone()))
+            .collect()
+    };
+    diff.eq("unrecognized sections", raw(a), raw(b));
+
+    diff.found
+}
+
+/// Tags and values of a post-processing view, values by tag and, for
+/// element node data, node index
+struct View<'a> {
+    string_tags: &'a [String],
+    integer_tags: &'a [i32],
+    real_tags: &'a [f64],
+    values: Vec<((i64, usize), &'a [f64])>,
+}
+
+/// Post-processing views, node data first
+fn views(mesh: &Mesh) -> Vec<View<'_>> {
+    let nodes = mesh.node_data.iter().map(|v| View {
+        string_tags: &v.string_tags,
+        integer_tags: &v.integer_tags,
+        real_tags: &v.real_tags,
+        values: v
+            .data
+            .iter()
+            .map(|(t, x)| ((t.0 as i64, 0), &x[..]))
+            .collect(),
+    });
+    let elements = mesh.element_data.iter().map(|v| View {
+        string_tags: &v.string_tags,
+        integer_tags: &v.integer_tags,
+        real_tags: &v.real_tags,
+        values: v
+            .data
+            .iter()
+            .map(|(t, x)| ((t.0 as i64, 0), &x[..]))
+            .collect(),
+    });
+    let element_nodes = mesh.element_node_data.iter().map(|v| View {
+        string_tags: &v.string_tags,
+        integer_tags: &v.integer_tags,
+        real_tags: &v.real_tags,
+        values: v
+            .data
+            .iter()
+            .map(|(t, n, x)| ((t.0 as i64, *n), &x[..]))
+            .collect(),
+    });
+    nodes.chain(elements).chain(element_nodes).collect()
+}
+
+/// `(dim, tag, physical groups, bounding entities)` and coordinates or box
+/// of each entity
+type EntityRecord = ((i32, EntityTag, Vec<PhysicalTag>, Vec<i32>), Vec<f64>);
+
+/// Entities by dimension
+fn entity_records(entities: &Entities) -> Vec<EntityRecord> {
+    let points = entities.points.iter().map(|e| {
+        let key = (0, e.tag, e.physical_tags.clone(), Vec::new());
+        (key, vec![e.x, e.y, e.z])
+    });
+    let curves = entities.curves.iter().map(|e| {
+        let key = (1, e.tag, e.physical_tags.clone(), e.bounding_points.clone());
+        (
+            key,
+            vec![e.min_x, e.min_y, e.min_z, e.max_x, e.max_y, e.max_z],
+        )
+    });
+    let surfaces = entities.surfaces.iter().map(|e| {
+        let key = (2, e.tag, e.physical_tags.clone(), e.bounding_curves.clone());
+        (
+            key,
+            vec![e.min_x, e.min_y, e.min_z, e.max_x, e.max_y, e.max_z],
+        )
+    });
+    let volumes = entities.volumes.iter().map(|e| {
+        let key = (
+            3,
+            e.tag,
+            e.physical_tags.clone(),
+            e.bounding_surfaces.clone(),
+        );
+        (
+            key,
+            vec![e.min_x, e.min_y, e.min_z, e.max_x, e.max_y, e.max_z],
+        )
+    });
+    points
+        .chain(curves)
+        .chain(surfaces)
+        .chain(volumes)
+        .collect()
+}
+
+/// `(dim, tag, triangles)` and node values of each parametrization
+type ParametrizationRecord = ((i32, EntityTag, Vec<[usize; 3]>), Vec<f64>);
+
+/// Curve parametrizations, then surface ones
+fn parametrization_records(parametrizations: &Parametrizations) -> Vec<ParametrizationRecord> {
+    let curves = parametrizations.curves.iter().map(|p| {
+        let nodes = p.nodes.iter().flat_map(|n| [n.x, n.y, n.z, n.u]);
+        ((1, p.curve_tag, Vec::new()), nodes.collect())
+    });
+    let surfaces = parametrizations.surfaces.iter().map(|p| {
+        let triangles = p
+            .triangles
+            .iter()
+            .map(|t| [t.node_index1, t.node_index2, t.node_index3]);
+        let nodes = p.nodes.iter().flat_map(|n| {
+            [
+                n.x,
+                n.y,
+                n.z,
+                n.u,
+                n.v,
+                n.curv_max_x,
+                n.curv_max_y,
+                n.curv_max_z,
+                n.curv_min_x,
+                n.curv_min_y,
+                n.curv_min_z,
+            ]
+        });
+        ((2, p.surface_tag, triangles.collect()), nodes.collect())
+    });
+    curves.chain(surfaces).collect()
+}
+
+/// Header and elements of every element block, compact ones included, in
+/// file order
+#[allow(clippy::type_complexity)]
+fn element_blocks(
+    mesh: &Mesh,
+) -> Vec<((i32, EntityTag, ElementType), Vec<(ElementTag, &[NodeTag])>)> {
+    mesh.element_block_refs()
+        .map(|b| {
+            let elements = b.elements().map(|e| (e.tag(), e.nodes()));
+            (
+                (b.entity_dim(), b.entity_tag(), b.element_type()),
+                elements.collect(),
+            )
+        })
+        .collect()
+}
+
+struct Differences<'a> {
+    tolerance: &'a Tolerance,
+    found: Vec<String>,
+}
+
+impl Differences<'_> {
+    /// Record a difference unless `a == b`, and return whether they are equal
+    fn eq<T: PartialEq + Debug>(&mut self, context: &str, a: T, b: T) -> bool {
+        if a == b {
+            return true;
+        }
+        let (a, b) = (format!("{:?}", a), format!("{:?}", b));
+        // Long values are cut, the context locates them
+        let cut = |s: String| match s.char_indices().nth(80) {
+            Some((i, _)) => format!("{}...", &s[..i]),
+            None => s,
+        };
+        self.found
+            .push(format!("{}: {} != {}", context, cut(a), cut(b)));
+        false
+    }
+
+    /// Compare the keys of `a` and `b` exactly, then, if they are equal, the
+    /// reals of each record within tolerance
+    fn records<K: PartialEq + Debug>(
+        &mut self,
+        context: &str,
+        a: Vec<(K, Vec<f64>)>,
+        b: Vec<(K, Vec<f64>)>,
+    ) {
+        let keys_a: Vec<_> = a.iter().map(|r| &r.0).collect();
+        let keys_b: Vec<_> = b.iter().map(|r| &r.0).collect();
+        if self.eq(context, keys_a, keys_b) {
+            for (i, (record_a, record_b)) in a.iter().zip(&b).enumerate() {
+                self.reals(&format!("{} {}", context, i), &record_a.1, &record_b.1);
+            }
+        }
+    }
+
+    /// Record a difference unless `a` and `b` have the same length and equal
+    /// values within tolerance
+    fn reals(&mut self, context: &str, a: &[f64], b: &[f64]) {
+        let equal = a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(x, y)| x == y || self.tolerance.approx_eq(*x, *y));
+        if !equal {
+            self.found.push(format!("{}: {:?} != {:?}", context, a, b));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::RealPrecision;
+
+    const MESH: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $PhysicalNames\n1\n1 1 \"edge\"\n$EndPhysicalNames\n\
+        $Entities\n2 1 0 0\n1 0 0 0 0\n2 1 0 0 0\n1 0 0 0 1 0 0 1 1 2 1 -2\n$EndEntities\n\
+        $Nodes\n1 2 1 2\n1 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+        $Elements\n1 1 1 1\n1 1 1 1\n1 1 2\n$EndElements\n";
+
+    #[test]
+    fn test_written_mesh_compares_equal() {
+        let mesh = parse_msh(MESH).unwrap();
+        let reparsed = parse_msh(crate::writer::write_msh(&mesh).unwrap()).unwrap();
+        assert!(compare_meshes(&mesh, &reparsed, &Tolerance::default()).is_empty());
+    }
+
+    #[test]
+    fn test_differences_are_reported() {
+        let mesh = parse_msh(MESH).unwrap();
+        let mut moved = mesh.clone();
+        moved.node_blocks[0].nodes[0].x += 1e-6;
+        moved.physical_names.pop();
+        let differences = compare_meshes(&mesh, &moved, &Tolerance::default());
+        assert_eq!(differences.len(), 2, "{:?}", differences);
+        assert!(differences[1].starts_with("node "));
+        let loose = Tolerance::default().with_absolute(1e-3);
+        assert_eq!(compare_meshes(&mesh, &moved, &loose).len(), 1);
+    }
+
+    #[test]
+    fn test_entity_boxes_are_compared() {
+        let mesh = parse_msh(MESH).unwrap();
+        let mut moved = mesh.clone();
+        moved.entities.as_mut().unwrap().curves[0].max_x = 2.0;
+        let differences = compare_meshes(&mesh, &moved, &Tolerance::default());
+        assert_eq!(differences.len(), 1, "{:?}", differences);
+        assert!(differences[0].starts_with("entity 2: "));
+    }
+
+    #[test]
+    fn test_precision_needs_a_tolerance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mesh.msh");
+        std::fs::write(
+            &path,
+            MESH.replace("1 0 0\n$EndNodes", "0.123456 0 0\n$EndNodes"),
+        )
+        .unwrap();
+        assert_lossless(&path);
+
+        let options = RoundTripOptions::default().with_write_options(
+            WriteOptions::default().with_precision(RealPrecision::Significant(3)),
+        );
+        assert!(!check_lossless(&path, &options).unwrap().is_empty());
+        let options = options.with_tolerance(Tolerance::new(0.0, 1e-2));
+        assert_lossless_with(&path, &options);
+    }
+}
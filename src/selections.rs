@@ -0,0 +1,403 @@
+//! Named selections persisted next to a mesh file
+//!
+//! Node and element sets built interactively are lost when a mesh file is
+//! regenerated, since Gmsh does not write them. [`save`] stores them, with
+//! free-form annotations, in a small TOML sidecar file keyed by the
+//! [`Fingerprint`] of the mesh. [`load_for`] restores them only if the mesh is
+//! unchanged, e.g. when the same geometry was meshed again with the same
+//! settings.
+//!
+//! The fingerprint is taken with the node and element sets and the change log
+//! of the mesh left out, so applying selections does not change it.
+//!
+//! Enabled by the `cache` feature.
+
+use crate::error::{ParseError, Result};
+use crate::repo::Fingerprint;
+use crate::sets::{ElementSet, NodeSet};
+use crate::types::{Change, ElementTag, Mesh, NodeTag};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Node and element sets of a mesh, with annotations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selections {
+    /// Fingerprint of the mesh without its sets, see [`mesh_fingerprint`]
+    pub fingerprint: Fingerprint,
+    pub node_sets: Vec<NodeSet>,
+    pub element_sets: Vec<ElementSet>,
+    /// Free-form notes, e.g. who made the selections and why
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl Selections {
+    /// The node and element sets of `mesh`, without annotations
+    pub fn from_mesh(mesh: &Mesh) -> Self {
+        Self {
+            fingerprint: mesh_fingerprint(mesh),
+            node_sets: mesh.node_sets.clone(),
+            element_sets: mesh.element_sets.clone(),
+            annotations: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_annotation(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.annotations.insert(key.into(), value.into());
+        self
+    }
+
+    /// Whether the selections were made on `mesh`
+    pub fn matches(&self, mesh: &Mesh) -> bool {
+        self.fingerprint == mesh_fingerprint(mesh)
+    }
+
+    /// Add the sets to `mesh`, replacing sets of the same name
+    ///
+    /// Recorded in the [`Mesh::change_log`].
+    pub fn apply(&self, mesh: &mut Mesh) {
+        for set in &self.node_sets {
+            mesh.node_sets.retain(|s| s.name != set.name);
+            mesh.node_sets.push(set.clone());
+        }
+        for set in &self.element_sets {
+            mesh.element_sets.retain(|s| s.name != set.name);
+            mesh.element_sets.push(set.clone());
+        }
+        mesh.record_change(
+            Change::new("apply_selections")
+                .with_parameter("fingerprint", self.fingerprint)
+                .with_count("node_sets", self.node_sets.len())
+                .with_count("element_sets", self.element_sets.len()),
+        );
+    }
+}
+
+/// Fingerprint of `mesh` with its node and element sets and its change log
+/// left out
+pub fn mesh_fingerprint(mesh: &Mesh) -> Fingerprint {
+    if mesh.node_sets.is_empty() && mesh.element_sets.is_empty() && mesh.change_log.is_empty() {
+        return mesh.fingerprint();
+    }
+    let mut bare = mesh.clone();
+    bare.node_sets.clear();
+    bare.element_sets.clear();
+    bare.change_log.clear();
+    bare.fingerprint()
+}
+
+/// Conventional sidecar path of a mesh file: `part.msh` gives
+/// `part.msh.selections.toml`
+pub fn sidecar_path(mesh_path: impl AsRef<Path>) -> PathBuf {
+    let mut path = mesh_path.as_ref().as_os_str().to_owned();
+    path.push(".selections.toml");
+    PathBuf::from(path)
+}
+
+/// Write `selections` to `path`
+pub fn save(path: impl AsRef<Path>, selections: &Selections) -> Result<()> {
+    std::fs::write(path, format_selections(selections))?;
+    Ok(())
+}
+
+/// Read the selections stored in `path`
+///
+/// # Errors
+/// Returns an error if the file cannot be read or is not a selections file.
+pub fn load(path: impl AsRef<Path>) -> Result<Selections> {
+    parse_selections(&std::fs::read_to_string(path)?)
+}
+
+/// Read the selections stored in `path` if they were made on `mesh`
+///
+/// Returns `None` if the file does not exist or belongs to another mesh.
+///
+/// # Errors
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load_for(path: impl AsRef<Path>, mesh: &Mesh) -> Result<Option<Selections>> {
+    let selections = match load(path) {
+        Ok(selections) => selections,
+        Err(ParseError::IoError(e)) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    Ok(selections.matches(mesh).then_some(selections))
+}
+
+fn format_selections(selections: &Selections) -> String {
+    let mut out = String::from("# Named selections, see gmsh_parser::selections\n");
+    // Writing into a String cannot fail
+    let _ = writeln!(out, "fingerprint = \"{}\"", selections.fingerprint);
+    if !selections.annotations.is_empty() {
+        out.push_str("\n[annotations]\n");
+        for (key, value) in &selections.annotations {
+            let _ = writeln!(out, "{} = {}", quote(key), quote(value));
+        }
+    }
+    let sets = selections
+        .node_sets
+        .iter()
+        .map(|s| {
+            (
+                "node_sets",
+                &s.name,
+                s.tag,
+                s.tags.iter().map(|t| t.0).collect::<Vec<u64>>(),
+            )
+        })
+        .chain(selections.element_sets.iter().map(|s| {
+            (
+                "element_sets",
+                &s.name,
+                s.tag,
+                s.tags.iter().map(|t| t.0).collect::<Vec<u64>>(),
+            )
+        }));
+    for (table, name, tag, tags) in sets {
+        let _ = writeln!(out, "\n[[{}]]\nname = {}", table, quote(name));
+        if let Some(tag) = tag {
+            let _ = writeln!(out, "tag = {}", tag);
+        }
+        let list: Vec<String> = tags.iter().map(u64::to_string).collect();
+        let _ = writeln!(out, "tags = [{}]", list.join(", "));
+    }
+    out
+}
+
+/// A TOML basic string
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04X}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The table the following keys belong to
+enum Table {
+    Top,
+    Annotations,
+    NodeSet,
+    ElementSet,
+}
+
+/// Parse the subset of TOML written by [`save`]
+fn parse_selections(text: &str) -> Result<Selections> {
+    let mut fingerprint = None;
+    let mut selections = Selections {
+        fingerprint: Fingerprint(0),
+        node_sets: Vec::new(),
+        element_sets: Vec::new(),
+        annotations: BTreeMap::new(),
+    };
+    let mut table = Table::Top;
+
+    for (number, line) in text.lines().enumerate() {
+        let error = |message: &str| {
+            ParseError::CacheError(format!("Selections file, line {}: {}", number + 1, message))
+        };
+        let line = line.trim();
+        match line {
+            "" => continue,
+            _ if line.starts_with('#') => continue,
+            "[annotations]" => table = Table::Annotations,
+            "[[node_sets]]" => {
+                selections.node_sets.push(NodeSet::default());
+                table = Table::NodeSet;
+            }
+            "[[element_sets]]" => {
+                selections.element_sets.push(ElementSet::default());
+                table = Table::ElementSet;
+            }
+            _ if line.starts_with('[') => return Err(error("unknown table")),
+            _ => {
+                let (key, rest) = if line.starts_with('"') {
+                    parse_string(line).ok_or_else(|| error("invalid key"))?
+                } else {
+                    let end = line.find('=').ok_or_else(|| error("expected `=`"))?;
+                    (line[..end].trim().to_string(), &line[end..])
+                };
+                let value = rest
+                    .trim_start()
+                    .strip_prefix('=')
+                    .ok_or_else(|| error("expected `=`"))?
+                    .trim();
+                let string = || match parse_string(value) {
+                    Some((s, rest)) if rest.trim().is_empty() => Ok(s),
+                    _ => Err(error("expected a string")),
+                };
+                let tag = || {
+                    value
+                        .parse::<i32>()
+                        .map_err(|_| error("expected an integer"))
+                };
+                let tags = || {
+                    let list = value
+                        .strip_prefix('[')
+                        .and_then(|v| v.strip_suffix(']'))
+                        .ok_or_else(|| error("expected a list of tags"))?;
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(|t| t.parse::<u64>().map_err(|_| error("invalid tag")))
+                        .collect::<Result<Vec<u64>>>()
+                };
+                match (&table, key.as_str()) {
+                    (Table::Top, "fingerprint") => {
+                        let parsed = string()?
+                            .parse()
+                            .map_err(|_| error("invalid fingerprint"))?;
+                        fingerprint = Some(parsed);
+                    }
+                    (Table::Annotations, _) => {
+                        selections.annotations.insert(key, string()?);
+                    }
+                    (Table::NodeSet, _) => {
+                        let set = selections
+                            .node_sets
+                            .last_mut()
+                            .expect("set tables start with a new set");
+                        match key.as_str() {
+                            "name" => set.name = string()?,
+                            "tag" => set.tag = Some(tag()?),
+                            "tags" => set.tags = tags()?.into_iter().map(NodeTag).collect(),
+                            _ => return Err(error("unknown key")),
+                        }
+                    }
+                    (Table::ElementSet, _) => {
+                        let set = selections
+                            .element_sets
+                            .last_mut()
+                            .expect("set tables start with a new set");
+                        match key.as_str() {
+                            "name" => set.name = string()?,
+                            "tag" => set.tag = Some(tag()?),
+                            "tags" => set.tags = tags()?.into_iter().map(ElementTag).collect(),
+                            _ => return Err(error("unknown key")),
+                        }
+                    }
+                    _ => return Err(error("unknown key")),
+                }
+            }
+        }
+    }
+
+    selections.fingerprint = fingerprint
+        .ok_or_else(|| ParseError::CacheError("Selections file has no fingerprint".to_string()))?;
+    Ok(selections)
+}
+
+/// Parse a basic string at the start of `s`, returning it and the rest of `s`
+fn parse_string(s: &str) -> Option<(String, &str)> {
+    let mut chars = s.strip_prefix('"')?.char_indices();
+    let mut out = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((out, &s[i + 2..])),
+            '\\' => match chars.next()?.1 {
+                '"' => out.push('"'),
+                '\\' => out.push('\\'),
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                'r' => out.push('\r'),
+                'u' => {
+                    let hex: String = (0..4)
+                        .filter_map(|_| chars.next().map(|(_, c)| c))
+                        .collect();
+                    out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                _ => return None,
+            },
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::TAGGED_SQUARE;
+    use crate::types::PhysicalTag;
+
+    /// [`TAGGED_SQUARE`] with a node set and an element set
+    fn mesh_with_sets() -> Mesh {
+        let mut mesh = crate::parse_msh(TAGGED_SQUARE).unwrap();
+        mesh.node_sets
+            .push(NodeSet::from_physical_group(&mesh, 1, PhysicalTag(5)).unwrap());
+        mesh.element_sets.push(
+            ElementSet::new("picked \"by hand\"", [ElementTag(3), ElementTag(5)]).with_tag(7),
+        );
+        mesh
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        let path = sidecar_path("meshes/part.msh");
+        assert_eq!(path, Path::new("meshes/part.msh.selections.toml"));
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_sets() {
+        let mesh = crate::parse_msh(TAGGED_SQUARE).unwrap();
+        assert_eq!(mesh_fingerprint(&mesh_with_sets()), mesh_fingerprint(&mesh));
+    }
+
+    #[test]
+    fn test_save_and_load_selections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("square.msh.selections.toml");
+        let selections = Selections::from_mesh(&mesh_with_sets())
+            .with_annotation("note", "inlet = left edge\nchecked")
+            .with_annotation("author", "ana");
+        save(&path, &selections).unwrap();
+        assert_eq!(load(&path).unwrap(), selections);
+    }
+
+    #[test]
+    fn test_selections_survive_regenerated_mesh() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("square.msh.selections.toml");
+        let mesh = mesh_with_sets();
+        save(&path, &Selections::from_mesh(&mesh)).unwrap();
+
+        let mut regenerated = crate::parse_msh(TAGGED_SQUARE).unwrap();
+        let loaded = load_for(&path, &regenerated).unwrap().unwrap();
+        loaded.apply(&mut regenerated);
+        assert_eq!(regenerated.node_sets, mesh.node_sets);
+        assert_eq!(regenerated.element_sets, mesh.element_sets);
+    }
+
+    #[test]
+    fn test_selections_of_another_mesh_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("square.msh.selections.toml");
+        save(&path, &Selections::from_mesh(&mesh_with_sets())).unwrap();
+
+        let moved =
+            crate::parse_msh(TAGGED_SQUARE.replace("1 1 0\n0 1 0", "1 2 0\n0 1 0")).unwrap();
+        assert!(load_for(&path, &moved).unwrap().is_none());
+        assert!(load_for(dir.path().join("missing.toml"), &moved)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_malformed_selections_file() {
+        assert!(parse_selections("[[node_sets]]\nname = \"a\"\n").is_err());
+        assert!(parse_selections("fingerprint = \"00000000000000ff\"\nsize = 3\n").is_err());
+        let parsed = parse_selections("fingerprint = \"00000000000000ff\"\n").unwrap();
+        assert_eq!(parsed.fingerprint, Fingerprint(255));
+    }
+}
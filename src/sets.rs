@@ -0,0 +1,408 @@
+//! Named node and element sets
+//!
+//! Sets are the usual currency for assigning boundary conditions and material
+//! properties in solvers. They can be built from physical groups, predicates or
+//! explicit tag lists and combined with set algebra.
+//!
+//! Sets survive a round trip through MSH files in the crate-defined `$NodeSets`
+//! and `$ElementSets` sections, produced by [`format_node_sets`] and
+//! [`format_element_sets`]. Gmsh itself skips these sections.
+
+use crate::error::{ParseError, Result};
+use crate::types::{ElementRef, ElementTag, EntityTag, Mesh, Node, NodeTag, PhysicalTag};
+use crate::writer::check_quotable;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// A named set of node tags
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct NodeSet {
+    pub name: String,
+    /// Optional numeric identifier, e.g. the physical tag the set was built from
+    pub tag: Option<i32>,
+    pub tags: BTreeSet<NodeTag>,
+}
+
+/// A named set of element tags
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct ElementSet {
+    pub name: String,
+    /// Optional numeric identifier, e.g. the physical tag the set was built from
+    pub tag: Option<i32>,
+    pub tags: BTreeSet<ElementTag>,
+}
+
+macro_rules! impl_tag_set {
+    ($set:ident, $tag:ty) => {
+        impl $set {
+            /// Create a set from an explicit list of tags
+            pub fn new(name: impl Into<String>, tags: impl IntoIterator<Item = $tag>) -> Self {
+                Self {
+                    name: name.into(),
+                    tag: None,
+                    tags: tags.into_iter().collect(),
+                }
+            }
+
+            /// Attach a numeric identifier to the set
+            pub fn with_tag(mut self, tag: i32) -> Self {
+                self.tag = Some(tag);
+                self
+            }
+
+            /// Rename the set
+            pub fn with_name(mut self, name: impl Into<String>) -> Self {
+                self.name = name.into();
+                self
+            }
+
+            pub fn len(&self) -> usize {
+                self.tags.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.tags.is_empty()
+            }
+
+            pub fn contains(&self, tag: $tag) -> bool {
+                self.tags.contains(&tag)
+            }
+
+            /// Tags in ascending order
+            pub fn iter(&self) -> impl Iterator<Item = $tag> + '_ {
+                self.tags.iter().copied()
+            }
+
+            /// Tags in either set; the result keeps the name of `self` and no tag
+            pub fn union(&self, other: &Self) -> Self {
+                Self::new(self.name.clone(), self.tags.union(&other.tags).copied())
+            }
+
+            /// Tags in both sets; the result keeps the name of `self` and no tag
+            pub fn intersection(&self, other: &Self) -> Self {
+                Self::new(
+                    self.name.clone(),
+                    self.tags.intersection(&other.tags).copied(),
+                )
+            }
+
+            /// Tags in `self` but not in `other`; the result keeps the name of `self` and no tag
+            pub fn difference(&self, other: &Self) -> Self {
+                Self::new(
+                    self.name.clone(),
+                    self.tags.difference(&other.tags).copied(),
+                )
+            }
+        }
+    };
+}
+
+impl_tag_set!(NodeSet, NodeTag);
+impl_tag_set!(ElementSet, ElementTag);
+
+impl NodeSet {
+    /// All nodes of the elements belonging to the physical group `(dim, tag)`
+    ///
+    /// The set is named after the physical name of the group, if any.
+    ///
+    /// # Errors
+    /// Returns an error if no entity carries the physical tag.
+    pub fn from_physical_group(mesh: &Mesh, dim: i32, tag: PhysicalTag) -> Result<Self> {
+        Ok(ElementSet::from_physical_group(mesh, dim, tag)?.node_set(mesh))
+    }
+
+    /// All nodes of the elements belonging to the physical group called `name`
+    ///
+    /// # Errors
+    /// Returns an error if no physical group has this name.
+    pub fn from_physical_name(mesh: &Mesh, name: &str) -> Result<Self> {
+        Ok(ElementSet::from_physical_name(mesh, name)?.node_set(mesh))
+    }
+
+    /// All nodes for which `predicate` returns `true`
+    pub fn from_predicate<F>(mesh: &Mesh, name: impl Into<String>, mut predicate: F) -> Self
+    where
+        F: FnMut(&Node) -> bool,
+    {
+        let tags = mesh
+            .node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter())
+            .filter(|n| predicate(n))
+            .map(|n| n.tag);
+        Self::new(name, tags)
+    }
+}
+
+impl ElementSet {
+    /// All elements of the entities belonging to the physical group `(dim, tag)`
+    ///
+    /// The set is named after the physical name of the group, if any.
+    ///
+    /// # Errors
+    /// Returns an error if no entity carries the physical tag.
+    pub fn from_physical_group(mesh: &Mesh, dim: i32, tag: PhysicalTag) -> Result<Self> {
+        let entities = physical_entities(mesh, dim, tag)?;
+        let name = mesh
+            .physical_names
+            .iter()
+            .find(|pn| pn.dimension as i32 == dim && pn.tag.matches(tag))
+            .map(|pn| pn.name.clone())
+            .unwrap_or_else(|| tag.to_string());
+
+        let tags = mesh
+            .element_block_refs()
+            .filter(|b| b.entity_dim() == dim && entities.contains(&b.entity_tag()))
+            .flat_map(|b| b.elements().map(|e| e.tag()));
+        Ok(Self::new(name, tags).with_tag(tag.magnitude().0))
+    }
+
+    /// All elements of the physical group called `name`
+    ///
+    /// # Errors
+    /// Returns an error if no physical group has this name.
+    pub fn from_physical_name(mesh: &Mesh, name: &str) -> Result<Self> {
+        let pn = mesh
+            .physical_names
+            .iter()
+            .find(|pn| pn.name == name)
+            .ok_or_else(|| ParseError::NotFound(format!("Physical group \"{}\"", name)))?;
+        Self::from_physical_group(mesh, pn.dimension as i32, pn.tag)
+    }
+
+    /// All elements for which `predicate` returns `true`
+    pub fn from_predicate<F>(mesh: &Mesh, name: impl Into<String>, mut predicate: F) -> Self
+    where
+        F: FnMut(ElementRef<'_>) -> bool,
+    {
+        let tags = mesh.elements().filter(|&e| predicate(e)).map(|e| e.tag());
+        Self::new(name, tags)
+    }
+
+    /// Nodes used by the elements of this set, with the same name and tag
+    pub fn node_set(&self, mesh: &Mesh) -> NodeSet {
+        let tags = mesh
+            .elements()
+            .filter(|e| self.contains(e.tag()))
+            .flat_map(|e| e.nodes().iter().copied());
+        NodeSet {
+            name: self.name.clone(),
+            tag: self.tag,
+            tags: tags.collect(),
+        }
+    }
+}
+
+/// Format node sets as a `$NodeSets` section
+///
+/// # Errors
+/// Returns an error if a set name holds a line break.
+pub fn format_node_sets(sets: &[NodeSet]) -> Result<String> {
+    let mut out = Vec::new();
+    write_node_sets(&mut out, sets)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Format element sets as an `$ElementSets` section
+///
+/// # Errors
+/// Returns an error if a set name holds a line break.
+pub fn format_element_sets(sets: &[ElementSet]) -> Result<String> {
+    let mut out = Vec::new();
+    write_element_sets(&mut out, sets)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+pub(crate) fn write_node_sets<W: Write>(out: &mut W, sets: &[NodeSet]) -> io::Result<()> {
+    write_sets(
+        out,
+        "NodeSets",
+        sets.iter().map(|s| (s.name.as_str(), s.tag, &s.tags)),
+    )
+}
+
+pub(crate) fn write_element_sets<W: Write>(out: &mut W, sets: &[ElementSet]) -> io::Result<()> {
+    write_sets(
+        out,
+        "ElementSets",
+        sets.iter().map(|s| (s.name.as_str(), s.tag, &s.tags)),
+    )
+}
+
+/// Tags written per line inside a set
+const TAGS_PER_LINE: usize = 10;
+
+fn write_sets<'a, W: Write, T: Display + 'a>(
+    out: &mut W,
+    section: &str,
+    sets: impl ExactSizeIterator<Item = (&'a str, Option<i32>, &'a BTreeSet<T>)>,
+) -> io::Result<()> {
+    writeln!(out, "${}\n{}", section, sets.len())?;
+    for (name, tag, tags) in sets {
+        check_quotable(name)?;
+        writeln!(out, "{} {} \"{}\"", tag.unwrap_or(0), tags.len(), name)?;
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        for line in tags.chunks(TAGS_PER_LINE) {
+            writeln!(out, "{}", line.join(" "))?;
+        }
+    }
+    writeln!(out, "$End{}", section)
+}
+
+/// Tags of the entities of dimension `dim` carrying the physical tag `tag`,
+/// with either sign
+///
+/// Partition entities carrying the tag are included, so that the elements of
+/// partitioned meshes are found.
+pub(crate) fn physical_entities(
+    mesh: &Mesh,
+    dim: i32,
+    tag: PhysicalTag,
+) -> Result<HashSet<EntityTag>> {
+    let entities = mesh.effective_entities();
+
+    let mut found: HashSet<EntityTag> = match dim {
+        0 => entities
+            .points
+            .iter()
+            .filter(|e| e.physical_tags.iter().any(|p| p.matches(tag)))
+            .map(|e| e.tag)
+            .collect(),
+        1 => entities
+            .curves
+            .iter()
+            .filter(|e| e.physical_tags.iter().any(|p| p.matches(tag)))
+            .map(|e| e.tag)
+            .collect(),
+        2 => entities
+            .surfaces
+            .iter()
+            .filter(|e| e.physical_tags.iter().any(|p| p.matches(tag)))
+            .map(|e| e.tag)
+            .collect(),
+        3 => entities
+            .volumes
+            .iter()
+            .filter(|e| e.physical_tags.iter().any(|p| p.matches(tag)))
+            .map(|e| e.tag)
+            .collect(),
+        _ => HashSet::new(),
+    };
+    if let Some(partitioned) = &mesh.partitioned_entities {
+        match dim {
+            0 => found.extend(
+                partitioned
+                    .points
+                    .iter()
+                    .filter(|e| e.physical_tags.iter().any(|p| p.matches(tag)))
+                    .map(|e| e.tag),
+            ),
+            1 => found.extend(
+                partitioned
+                    .curves
+                    .iter()
+                    .filter(|e| e.physical_tags.iter().any(|p| p.matches(tag)))
+                    .map(|e| e.tag),
+            ),
+            2 => found.extend(
+                partitioned
+                    .surfaces
+                    .iter()
+                    .filter(|e| e.physical_tags.iter().any(|p| p.matches(tag)))
+                    .map(|e| e.tag),
+            ),
+            3 => found.extend(
+                partitioned
+                    .volumes
+                    .iter()
+                    .filter(|e| e.physical_tags.iter().any(|p| p.matches(tag)))
+                    .map(|e| e.tag),
+            ),
+            _ => {}
+        }
+    }
+
+    if found.is_empty() {
+        return Err(ParseError::NotFound(format!(
+            "Physical group ({}, {})",
+            dim, tag
+        )));
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::NAMED_SQUARE;
+
+    fn nodes(tags: &[u64]) -> BTreeSet<NodeTag> {
+        tags.iter().copied().map(NodeTag).collect()
+    }
+
+    #[test]
+    fn test_element_set_from_physical_name() {
+        let mesh = crate::parse_msh(NAMED_SQUARE).unwrap();
+        let surface = ElementSet::from_physical_name(&mesh, "My surface").unwrap();
+        assert_eq!(surface.name, "My surface");
+        assert_eq!(surface.tag, Some(6));
+        assert_eq!(surface.node_set(&mesh).tags, nodes(&[1, 2, 3, 4]));
+        assert!(ElementSet::from_physical_name(&mesh, "missing").is_err());
+    }
+
+    #[test]
+    fn test_node_set_from_physical_group() {
+        let mesh = crate::parse_msh(NAMED_SQUARE).unwrap();
+        let boundary = NodeSet::from_physical_group(&mesh, 1, PhysicalTag(5)).unwrap();
+        assert_eq!(boundary.name, "boundary");
+        assert_eq!(boundary.tags, nodes(&[1, 2, 3]));
+        assert!(NodeSet::from_physical_group(&mesh, 2, PhysicalTag(99)).is_err());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let mesh = crate::parse_msh(NAMED_SQUARE).unwrap();
+        let boundary = NodeSet::from_physical_group(&mesh, 1, PhysicalTag(5)).unwrap();
+        let bottom = NodeSet::from_predicate(&mesh, "bottom", |n| n.y == 0.0);
+        let top = NodeSet::from_predicate(&mesh, "top", |n| n.y == 1.0);
+        assert_eq!(boundary.intersection(&bottom).tags, nodes(&[1, 2]));
+        assert_eq!(top.difference(&boundary).tags, nodes(&[4]));
+        assert_eq!(boundary.union(&top).tags, nodes(&[1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_sets_round_trip() {
+        let mesh = crate::parse_msh(NAMED_SQUARE).unwrap();
+        let nodes = NodeSet::from_physical_group(&mesh, 1, PhysicalTag(5)).unwrap();
+        let elements = ElementSet::new("triangles", [ElementTag(4), ElementTag(5)]).with_tag(7);
+        let content = format!(
+            "{}{}{}",
+            NAMED_SQUARE,
+            format_node_sets(std::slice::from_ref(&nodes)).unwrap(),
+            format_element_sets(std::slice::from_ref(&elements)).unwrap()
+        );
+
+        let reparsed = crate::parse_msh(&content).unwrap();
+        assert_eq!(reparsed.node_sets, vec![nodes]);
+        assert_eq!(reparsed.element_sets, vec![elements]);
+    }
+
+    #[test]
+    fn test_unquotable_set_names_are_rejected() {
+        let set = NodeSet::new("inlet\r", [NodeTag(1)]);
+        assert!(format_node_sets(&[set]).is_err());
+        let set = ElementSet::new("inlet\nwall", [ElementTag(1)]);
+        assert!(format_element_sets(&[set]).is_err());
+    }
+}
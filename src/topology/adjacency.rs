@@ -0,0 +1,256 @@
+//! Node-to-element and element-to-element adjacency in compressed sparse row form
+
+use super::dual::{facet_key, facets};
+use crate::types::{ElementRef, ElementTag, Indexing, Mesh, NodeTag};
+use std::collections::HashMap;
+
+/// Lists of indices stored back to back: list `i` is
+/// `targets[offsets[i]..offsets[i + 1]]`
+///
+/// With [`Indexing::OneBased`], list numbers, offsets and targets all start
+/// at 1, as in Fortran solvers and METIS with `numflag = 1`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Csr {
+    pub offsets: Vec<usize>,
+    pub targets: Vec<usize>,
+    pub indexing: Indexing,
+}
+
+impl Csr {
+    /// Gather the lists from `(source, target)` pairs, with `len` sources
+    ///
+    /// Each list is sorted and free of duplicates.
+    fn from_pairs(len: usize, pairs: &[(usize, usize)]) -> Self {
+        let mut offsets = vec![0; len + 1];
+        for &(source, _) in pairs {
+            offsets[source + 1] += 1;
+        }
+        for i in 0..len {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut targets = vec![0; pairs.len()];
+        let mut next = offsets.clone();
+        for &(source, target) in pairs {
+            targets[next[source]] = target;
+            next[source] += 1;
+        }
+
+        // Sort and deduplicate every list in place, compacting as we go
+        let mut compact = Self {
+            offsets: vec![0; len + 1],
+            targets: Vec::with_capacity(targets.len()),
+            indexing: Indexing::ZeroBased,
+        };
+        for i in 0..len {
+            let list = &mut targets[offsets[i]..offsets[i + 1]];
+            list.sort_unstable();
+            let start = compact.targets.len();
+            for &target in list.iter() {
+                if compact.targets.len() == start || compact.targets.last() != Some(&target) {
+                    compact.targets.push(target);
+                }
+            }
+            compact.offsets[i + 1] = compact.targets.len();
+        }
+        compact
+    }
+
+    /// Number of lists
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// List `i`, numbered in the base of the arrays
+    ///
+    /// # Panics
+    /// Panics if there is no list `i`.
+    pub fn get(&self, i: usize) -> &[usize] {
+        let base = self.indexing.index(0);
+        let i = i.wrapping_sub(base);
+        &self.targets[self.offsets[i] - base..self.offsets[i + 1] - base]
+    }
+
+    /// The same lists with offsets and targets in the base `indexing`
+    pub fn with_indexing(mut self, indexing: Indexing) -> Self {
+        self.indexing.rebase(&mut self.offsets, indexing);
+        self.indexing.rebase(&mut self.targets, indexing);
+        self.indexing = indexing;
+        self
+    }
+}
+
+/// Elements around each node and neighbours of each element, see
+/// [`Mesh::build_adjacency`]
+///
+/// Nodes and elements are referred to by index: node `i` is `nodes[i]` and
+/// element `j` is `elements[j]`, shifted by one with [`Indexing::OneBased`]
+/// (see [`Adjacency::with_indexing`]).
+#[derive(Debug, Clone)]
+pub struct Adjacency {
+    /// Node tags in increasing order
+    pub nodes: Vec<NodeTag>,
+    /// Element tags in file order
+    pub elements: Vec<ElementTag>,
+    /// Elements using each node
+    pub node_to_element: Csr,
+    /// Elements of the same dimension sharing a facet with each element
+    pub element_to_element: Csr,
+    pub indexing: Indexing,
+    element_index: HashMap<ElementTag, usize>,
+}
+
+impl Adjacency {
+    /// The same adjacency with node and element indices in the base
+    /// `indexing`
+    pub fn with_indexing(mut self, indexing: Indexing) -> Self {
+        self.node_to_element = self.node_to_element.with_indexing(indexing);
+        self.element_to_element = self.element_to_element.with_indexing(indexing);
+        self.indexing = indexing;
+        self
+    }
+
+    /// Index of the node `tag`
+    pub fn node_index(&self, tag: NodeTag) -> Option<usize> {
+        let position = self.nodes.binary_search(&tag).ok()?;
+        Some(self.indexing.index(position))
+    }
+
+    /// Index of the element `tag`
+    pub fn element_index(&self, tag: ElementTag) -> Option<usize> {
+        let position = self.element_index.get(&tag)?;
+        Some(self.indexing.index(*position))
+    }
+
+    /// Tag of the node with index `i`
+    pub fn node_tag(&self, i: usize) -> Option<NodeTag> {
+        self.nodes.get(self.indexing.position(i)?).copied()
+    }
+
+    /// Tag of the element with index `j`
+    pub fn element_tag(&self, j: usize) -> Option<ElementTag> {
+        self.elements.get(self.indexing.position(j)?).copied()
+    }
+
+    /// Indices of the elements using node `i`
+    pub fn elements_of_node(&self, i: usize) -> &[usize] {
+        self.node_to_element.get(i)
+    }
+
+    /// Indices of the elements sharing a facet with element `i`
+    pub fn neighbors(&self, i: usize) -> &[usize] {
+        self.element_to_element.get(i)
+    }
+}
+
+/// Elements of the same dimension sharing a facet, by position in `elements`
+///
+/// Elements of types without a facet table have no neighbours.
+pub(crate) fn facet_neighbors(elements: &[ElementRef<'_>]) -> Csr {
+    let mut sharing: HashMap<(i32, Vec<NodeTag>), Vec<usize>> = HashMap::new();
+    for (i, element) in elements.iter().enumerate() {
+        let Some(facets) = facets(element.element_type()) else {
+            continue;
+        };
+        for facet in facets {
+            let key = facet_key(element.nodes(), facet);
+            sharing
+                .entry((element.entity_dim(), key))
+                .or_default()
+                .push(i);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for group in sharing.values() {
+        for &a in group {
+            pairs.extend(group.iter().filter(|&&b| b != a).map(|&b| (a, b)));
+        }
+    }
+    Csr::from_pairs(elements.len(), &pairs)
+}
+
+impl Mesh {
+    /// Build the node-to-element and element-to-element adjacency of the mesh
+    ///
+    /// Elements are neighbours when they have the same dimension and share a
+    /// facet, as in the [`topology`](crate::topology) module. Nodes referenced
+    /// by elements but missing from the node blocks are included, and so are
+    /// the elements of compact blocks.
+    pub fn build_adjacency(&self) -> Adjacency {
+        let elements: Vec<ElementRef<'_>> = self.elements().collect();
+
+        let mut nodes: Vec<NodeTag> = self
+            .node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter().map(|n| n.tag))
+            .chain(elements.iter().flat_map(|e| e.nodes().iter().copied()))
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+
+        let mut pairs = Vec::new();
+        for (j, element) in elements.iter().enumerate() {
+            for node in element.nodes() {
+                if let Ok(i) = nodes.binary_search(node) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+
+        Adjacency {
+            node_to_element: Csr::from_pairs(nodes.len(), &pairs),
+            element_to_element: facet_neighbors(&elements),
+            indexing: Indexing::ZeroBased,
+            element_index: elements
+                .iter()
+                .enumerate()
+                .map(|(j, e)| (e.tag(), j))
+                .collect(),
+            elements: elements.iter().map(|e| e.tag()).collect(),
+            nodes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    #[test]
+    fn test_adjacency_of_two_triangles() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+            $Elements\n2 3 1 3\n2 1 2 2\n1 1 2 3\n2 1 3 4\n1 1 1 1\n3 1 2\n$EndElements\n";
+        let mesh = parse_msh(content).unwrap();
+        let adjacency = mesh.build_adjacency();
+
+        assert_eq!(adjacency.nodes.len(), 4);
+        let node = adjacency.node_index(NodeTag(1)).unwrap();
+        assert_eq!(adjacency.elements_of_node(node), [0, 1, 2]);
+        let node = adjacency.node_index(NodeTag(2)).unwrap();
+        assert_eq!(adjacency.elements_of_node(node), [0, 2]);
+
+        // Triangles share the edge 1-3; the line has no other line to connect to
+        let first = adjacency.element_index(ElementTag(1)).unwrap();
+        assert_eq!(adjacency.neighbors(first), [1]);
+        let line = adjacency.element_index(ElementTag(3)).unwrap();
+        assert!(adjacency.neighbors(line).is_empty());
+        assert_eq!(adjacency.element_to_element.offsets, [0, 1, 2, 2]);
+
+        let fortran = adjacency.with_indexing(Indexing::OneBased);
+        assert_eq!(fortran.element_to_element.offsets, [1, 2, 3, 3]);
+        let first = fortran.element_index(ElementTag(1)).unwrap();
+        assert_eq!(first, 1);
+        assert_eq!(fortran.neighbors(first), [2]);
+        assert_eq!(fortran.element_tag(2), Some(ElementTag(2)));
+        let node = fortran.node_index(NodeTag(1)).unwrap();
+        assert_eq!(fortran.elements_of_node(node), [1, 2, 3]);
+        assert_eq!(fortran.node_tag(node), Some(NodeTag(1)));
+        assert_eq!(fortran.node_tag(0), None);
+    }
+}
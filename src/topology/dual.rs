@@ -0,0 +1,95 @@
+//! Dual graph of a mesh: elements connected through shared facets
+
+use super::adjacency::{facet_neighbors, Csr};
+use crate::types::{ElementRef, ElementTag, ElementType, Mesh, NodeTag};
+use std::collections::HashMap;
+
+/// Facets of an element, as indices of their corner nodes in Gmsh ordering
+///
+/// Volume faces are listed with their normal pointing out of the element.
+pub(crate) fn facets(element_type: ElementType) -> Option<&'static [&'static [usize]]> {
+    use ElementType::*;
+    let facets: &'static [&'static [usize]] = match element_type {
+        Line2 | Line3 | Line4 | Line5 | Line6 => &[&[0], &[1]],
+        Triangle3 | Triangle6 | Triangle9 | Triangle10 => &[&[0, 1], &[1, 2], &[2, 0]],
+        Quadrangle4 | Quadrangle8 | Quadrangle9 => &[&[0, 1], &[1, 2], &[2, 3], &[3, 0]],
+        Tetrahedron4 | Tetrahedron10 => &[&[0, 2, 1], &[0, 1, 3], &[0, 3, 2], &[3, 1, 2]],
+        Hexahedron8 | Hexahedron20 | Hexahedron27 => &[
+            &[0, 3, 2, 1],
+            &[0, 1, 5, 4],
+            &[0, 4, 7, 3],
+            &[1, 2, 6, 5],
+            &[2, 3, 7, 6],
+            &[4, 5, 6, 7],
+        ],
+        Prism6 | Prism15 | Prism18 => &[
+            &[0, 2, 1],
+            &[3, 4, 5],
+            &[0, 1, 4, 3],
+            &[0, 3, 5, 2],
+            &[1, 2, 5, 4],
+        ],
+        Pyramid5 | Pyramid13 | Pyramid14 => &[
+            &[0, 3, 2, 1],
+            &[0, 1, 4],
+            &[0, 4, 3],
+            &[1, 2, 4],
+            &[2, 3, 4],
+        ],
+        _ => return None,
+    };
+    Some(facets)
+}
+
+/// Sorted node tags of a facet, identical for every element sharing it
+pub(crate) fn facet_key(nodes: &[NodeTag], facet: &[usize]) -> Vec<NodeTag> {
+    let mut key: Vec<NodeTag> = facet.iter().map(|&i| nodes[i]).collect();
+    key.sort_unstable();
+    key
+}
+
+/// Elements of a mesh and their neighbours across facets
+pub(crate) struct DualGraph<'a> {
+    elements: Vec<ElementRef<'a>>,
+    index: HashMap<ElementTag, usize>,
+    neighbors: Csr,
+}
+
+impl<'a> DualGraph<'a> {
+    /// Connect the elements of the same dimension that share a facet
+    ///
+    /// Elements of types without a facet table (points, polygons, high-order
+    /// types) are part of the graph but have no neighbours.
+    pub(crate) fn new(mesh: &'a Mesh) -> Self {
+        let elements: Vec<ElementRef<'a>> = mesh.elements().collect();
+        let index = elements
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.tag(), i))
+            .collect();
+
+        let neighbors = facet_neighbors(&elements);
+
+        Self {
+            elements,
+            index,
+            neighbors,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub(crate) fn element(&self, i: usize) -> ElementRef<'a> {
+        self.elements[i]
+    }
+
+    pub(crate) fn index_of(&self, tag: ElementTag) -> Option<usize> {
+        self.index.get(&tag).copied()
+    }
+
+    pub(crate) fn neighbors(&self, i: usize) -> &[usize] {
+        self.neighbors.get(i)
+    }
+}
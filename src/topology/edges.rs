@@ -0,0 +1,161 @@
+//! Feature edges of surface meshes
+
+use super::dual::facets;
+use crate::geometry::node_coordinates;
+use crate::types::{ElementTag, Mesh, NodeTag};
+use std::collections::{BTreeMap, HashMap};
+
+/// An edge of the surface mesh singled out by [`feature_edges`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureEdge {
+    /// End nodes, smallest tag first
+    pub nodes: [NodeTag; 2],
+    /// Surface elements sharing the edge
+    pub faces: Vec<ElementTag>,
+    /// Angle in radians between the normals of the two faces, or `None` for
+    /// boundary and non-manifold edges
+    pub angle: Option<f64>,
+}
+
+/// Edges of the surface elements where the mesh folds by more than `angle_threshold`
+///
+/// The angle between the normals of the two elements sharing an edge is
+/// compared with `angle_threshold`, in radians. Normals are oriented
+/// consistently across the edge first, so that the result does not depend on
+/// the orientation of the surface entities. Edges with a single face (open
+/// boundaries) or more than two faces (non-manifold junctions) are always
+/// reported. Only elements in blocks of dimension 2 are considered, using
+/// their corner nodes.
+///
+/// Edges are returned sorted by their node tags.
+pub fn feature_edges(mesh: &Mesh, angle_threshold: f64) -> Vec<FeatureEdge> {
+    let surface = SurfaceEdges::new(mesh);
+    surface
+        .edges
+        .iter()
+        .filter_map(|(&nodes, faces)| {
+            let angle = surface.angle(faces);
+            if angle.is_some_and(|a| a <= angle_threshold) {
+                return None;
+            }
+            Some(FeatureEdge {
+                nodes,
+                faces: faces.iter().map(|&(f, _)| surface.faces[f]).collect(),
+                angle,
+            })
+        })
+        .collect()
+}
+
+/// Surface elements of a mesh and the edges between them
+pub(super) struct SurfaceEdges {
+    /// Tags of the elements in blocks of dimension 2
+    pub(super) faces: Vec<ElementTag>,
+    normals: Vec<[f64; 3]>,
+    /// Faces of each edge, with the edge's direction as seen from the face
+    pub(super) edges: BTreeMap<[NodeTag; 2], Vec<(usize, bool)>>,
+}
+
+impl SurfaceEdges {
+    pub(super) fn new(mesh: &Mesh) -> Self {
+        let coords = node_coordinates(mesh);
+        let mut surface = Self {
+            faces: Vec::new(),
+            normals: Vec::new(),
+            edges: BTreeMap::new(),
+        };
+
+        for block in mesh.element_block_refs().filter(|b| b.entity_dim() == 2) {
+            let Some(facets) = facets(block.element_type()) else {
+                continue;
+            };
+            for element in block.elements() {
+                let face = surface.faces.len();
+                let corners: Vec<NodeTag> = facets.iter().map(|f| element.nodes()[f[0]]).collect();
+                surface.normals.push(normal(&corners, &coords));
+                surface.faces.push(element.tag());
+                for facet in facets.iter() {
+                    let (a, b) = (element.nodes()[facet[0]], element.nodes()[facet[1]]);
+                    let key = if a < b { [a, b] } else { [b, a] };
+                    surface.edges.entry(key).or_default().push((face, a < b));
+                }
+            }
+        }
+        surface
+    }
+
+    /// Angle between the normals of the faces of an edge shared by exactly two faces
+    pub(super) fn angle(&self, faces: &[(usize, bool)]) -> Option<f64> {
+        let [(f, forward_f), (g, forward_g)] = faces[..] else {
+            return None;
+        };
+        let (n, mut m) = (self.normals[f], self.normals[g]);
+        // Consistently oriented faces run along a shared edge in opposite directions
+        if forward_f == forward_g {
+            m = [-m[0], -m[1], -m[2]];
+        }
+        let cos = n[0] * m[0] + n[1] * m[1] + n[2] * m[2];
+        Some(cos.clamp(-1.0, 1.0).acos())
+    }
+}
+
+/// Unit normal of a planar polygon (Newell's method), zero if it is degenerate
+fn normal(corners: &[NodeTag], coords: &HashMap<NodeTag, [f64; 3]>) -> [f64; 3] {
+    let mut n = [0.0; 3];
+    for (i, tag) in corners.iter().enumerate() {
+        let p = coords.get(tag).copied().unwrap_or_default();
+        let q = coords
+            .get(&corners[(i + 1) % corners.len()])
+            .copied()
+            .unwrap_or_default();
+        n[0] += (p[1] - q[1]) * (p[2] + q[2]);
+        n[1] += (p[2] - q[2]) * (p[0] + q[0]);
+        n[2] += (p[0] - q[0]) * (p[1] + q[1]);
+    }
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length == 0.0 {
+        return n;
+    }
+    [n[0] / length, n[1] / length, n[2] / length]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::CUBE_SURFACE;
+    use crate::geometry::distance;
+    use crate::parse_msh;
+
+    #[test]
+    fn test_feature_edges_of_cube() {
+        let mesh = parse_msh(CUBE_SURFACE).unwrap();
+        let coords = node_coordinates(&mesh);
+        let edges = feature_edges(&mesh, 30f64.to_radians());
+
+        // The twelve edges of the cube, not the face diagonals
+        assert_eq!(edges.len(), 12);
+        for edge in &edges {
+            assert_eq!(
+                distance(coords[&edge.nodes[0]], coords[&edge.nodes[1]]),
+                1.0
+            );
+            assert_eq!(edge.faces.len(), 2);
+            let angle = edge.angle.unwrap();
+            assert!((angle - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_open_surface_reports_boundary() {
+        // Two triangles of the unit square
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+            $Elements\n1 2 1 2\n2 1 2 2\n1 1 2 3\n2 1 3 4\n$EndElements\n";
+        let edges = feature_edges(&parse_msh(content).unwrap(), 0.1);
+        assert_eq!(edges.len(), 4);
+        assert!(edges
+            .iter()
+            .all(|e| e.faces.len() == 1 && e.angle.is_none()));
+    }
+}
@@ -0,0 +1,54 @@
+//! Connectivity between the elements of a mesh
+//!
+//! Elements are neighbours in the dual graph when they have the same dimension
+//! and share a facet: a corner node for lines, an edge for surface elements and
+//! a face for volume elements. Only corner nodes are compared, so higher-order
+//! elements connect like their linear counterparts.
+//!
+//! [`grow_region`] selects a connected region around a seed element:
+//!
+//! ```no_run
+//! use gmsh_parser::topology::grow_region;
+//! use gmsh_parser::{parse_msh_file, ElementTag, EntityTag};
+//!
+//! let mesh = parse_msh_file("model.msh").unwrap();
+//! let region = grow_region(&mesh, ElementTag(1), |e| e.entity_tag() == EntityTag(1)).unwrap();
+//! println!("{} elements", region.len());
+//! ```
+//!
+//! [`feature_edges`] finds the edges where a surface mesh folds sharply, e.g.
+//! to draw them, and [`segment_surfaces`] splits a surface along them into
+//! patches, e.g. to recover the surfaces of a mesh that lost its physical groups.
+//! [`identify_volumes`] does the same for volume elements, split along the
+//! surface elements of the mesh.
+//!
+//! [`Mesh::build_adjacency`](crate::Mesh::build_adjacency) gives the node-to-element
+//! and element-to-element adjacency as [`Csr`] arrays, for solvers and partitioners.
+//!
+//! [`ReferenceElement`] gives the local node coordinates of an element type and
+//! the nodes of its edges and faces, in Gmsh ordering.
+
+mod adjacency;
+mod dual;
+mod edges;
+mod patches;
+mod reference;
+mod region;
+mod volumes;
+
+pub use adjacency::{Adjacency, Csr};
+pub use edges::{feature_edges, FeatureEdge};
+pub use patches::segment_surfaces;
+pub use reference::ReferenceElement;
+pub(crate) use reference::{corner_count, corner_edges, corner_faces};
+pub use region::grow_region;
+pub use volumes::identify_volumes;
+
+/// Representative of `i` in a union-find forest, with path halving
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
@@ -0,0 +1,165 @@
+//! Segmentation of surface meshes into patches
+
+use super::edges::SurfaceEdges;
+use super::root;
+use crate::error::Result;
+use crate::generate::TagAllocator;
+use crate::geometry::{bounds, node_coordinates};
+use crate::sets::ElementSet;
+use crate::types::{
+    Change, ElementBlock, ElementTag, EntityDimension, EntityTag, Mesh, PhysicalName, PhysicalTag,
+    SurfaceEntity,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Split the surface elements into patches bounded by feature edges
+///
+/// Two surface elements belong to the same patch when they are connected
+/// through edges shared by exactly two elements whose normals differ by at
+/// most `angle_threshold` radians (see [`feature_edges`](super::feature_edges)).
+/// Open boundaries and non-manifold edges also separate patches.
+///
+/// Patches are ordered by their smallest element tag and named `patch_1`,
+/// `patch_2`, ..., with the same number as their tag. Pass them to
+/// [`Mesh::add_surface_patches`] to turn them into physical groups.
+pub fn segment_surfaces(mesh: &Mesh, angle_threshold: f64) -> Vec<ElementSet> {
+    let surface = SurfaceEdges::new(mesh);
+    let mut parent: Vec<usize> = (0..surface.faces.len()).collect();
+    for faces in surface.edges.values() {
+        if surface.angle(faces).is_some_and(|a| a <= angle_threshold) {
+            let (a, b) = (root(&mut parent, faces[0].0), root(&mut parent, faces[1].0));
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<ElementTag>> = HashMap::new();
+    for face in 0..surface.faces.len() {
+        let r = root(&mut parent, face);
+        groups.entry(r).or_default().push(surface.faces[face]);
+    }
+    let mut patches: Vec<Vec<ElementTag>> = groups.into_values().collect();
+    patches.sort_by_key(|tags| tags.iter().min().copied());
+    patches
+        .into_iter()
+        .enumerate()
+        .map(|(i, tags)| ElementSet::new(format!("patch_{}", i + 1), tags).with_tag(i as i32 + 1))
+        .collect()
+}
+
+impl Mesh {
+    /// Make each patch a new surface entity with its own physical group
+    ///
+    /// The surface elements of every patch are moved to blocks on a new
+    /// surface entity, tagged after the existing ones, which gets a new physical
+    /// group named after the patch. Element tags of other dimensions are
+    /// ignored. Nodes stay on their entities. Returns the physical tags of the
+    /// new groups, in the order of `patches`.
+    ///
+    /// If the mesh has no `$Entities` section, the entities are synthesized
+    /// first (see [`Mesh::effective_entities`]). Compact element blocks are
+    /// expanded first (see [`Mesh::expand_storage`]).
+    pub fn add_surface_patches(&mut self, patches: &[ElementSet]) -> Result<Vec<PhysicalTag>> {
+        self.expand_storage();
+        let mut entities = self.effective_entities().into_owned();
+        let mut tags = TagAllocator::for_mesh(self);
+        let mut patch_of: HashMap<ElementTag, EntityTag> = HashMap::new();
+        let mut new_entities = Vec::with_capacity(patches.len());
+        for patch in patches {
+            let (entity, physical) = (tags.entity(2), tags.physical(2));
+            patch_of.extend(patch.iter().map(|tag| (tag, entity)));
+            self.physical_names.push(PhysicalName::new(
+                EntityDimension::Surface,
+                physical,
+                patch.name.clone(),
+            ));
+            new_entities.push((entity, physical));
+        }
+
+        // Move the elements, one block per patch and element type
+        let mut moved: BTreeMap<(EntityTag, i32), ElementBlock> = BTreeMap::new();
+        for block in self.element_blocks.iter_mut().filter(|b| b.entity_dim == 2) {
+            for element in std::mem::take(&mut block.elements) {
+                match patch_of.get(&element.tag) {
+                    Some(&entity) => moved
+                        .entry((entity, block.element_type.to_i32()))
+                        .or_insert_with(|| {
+                            ElementBlock::new(2, entity, block.element_type, Vec::new())
+                        })
+                        .elements
+                        .push(element),
+                    None => block.elements.push(element),
+                }
+            }
+        }
+        self.element_blocks.retain(|b| !b.elements.is_empty());
+
+        let coords = node_coordinates(self);
+        for &(entity, physical) in &new_entities {
+            let nodes = moved
+                .values()
+                .filter(|b| b.entity_tag == entity)
+                .flat_map(|b| b.elements.iter().flat_map(|e| e.nodes.iter()));
+            let (min, max) = bounds(nodes.filter_map(|n| coords.get(n).copied()))
+                .unwrap_or(([0.0; 3], [0.0; 3]));
+            entities.surfaces.push(SurfaceEntity {
+                tag: entity,
+                min_x: min[0],
+                min_y: min[1],
+                min_z: min[2],
+                max_x: max[0],
+                max_y: max[1],
+                max_z: max[2],
+                physical_tags: vec![physical],
+                bounding_curves: Vec::new(),
+            });
+        }
+        let elements_moved = moved.values().map(|b| b.elements.len()).sum();
+        self.element_blocks.extend(moved.into_values());
+        self.entities = Some(entities);
+        self.validate()?;
+        self.record_change(
+            Change::new("add_surface_patches")
+                .with_count("patches", patches.len())
+                .with_count("elements_moved", elements_moved),
+        );
+        Ok(new_entities.into_iter().map(|(_, p)| p).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::CUBE_SURFACE;
+    use crate::parse_msh;
+    use crate::writer::write_msh;
+
+    #[test]
+    fn test_segment_cube_into_faces() {
+        let mesh = parse_msh(CUBE_SURFACE).unwrap();
+        let patches = segment_surfaces(&mesh, 30f64.to_radians());
+        assert_eq!(patches.len(), 6);
+
+        // Each patch holds the two triangles of one face
+        let faces: Vec<Vec<u64>> = patches
+            .iter()
+            .map(|p| p.tags.iter().map(|t| t.0).collect())
+            .collect();
+        assert_eq!(faces, [[1, 2], [3, 4], [5, 6], [7, 8], [9, 10], [11, 12]]);
+        assert_eq!(patches[0].name, "patch_1");
+        assert_eq!(patches[5].tag, Some(6));
+    }
+
+    #[test]
+    fn test_add_surface_patches() {
+        let mut mesh = parse_msh(CUBE_SURFACE).unwrap();
+        let patches = segment_surfaces(&mesh, 30f64.to_radians());
+        let groups = mesh.add_surface_patches(&patches).unwrap();
+        assert_eq!(groups.len(), 6);
+
+        let reparsed = parse_msh(write_msh(&mesh).unwrap()).unwrap();
+        for patch in &patches {
+            let set = ElementSet::from_physical_name(&reparsed, &patch.name).unwrap();
+            assert_eq!(set.tags, patch.tags);
+        }
+    }
+}
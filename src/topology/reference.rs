@@ -0,0 +1,295 @@
+//! Reference elements: local node coordinates and edge and face tables in
+//! Gmsh ordering
+
+use crate::types::{ElementFamily, ElementType};
+
+/// Local coordinates and sub-entities of an element type, see
+/// [`ReferenceElement::new`]
+///
+/// Node indices refer to the nodes of an element in Gmsh ordering: corners
+/// first, then the nodes inside the edges, the faces and the volume.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceElement {
+    pub element_type: ElementType,
+    /// Position of every node on the reference element
+    pub nodes: Vec<[f64; 3]>,
+    /// Nodes of every edge: its two corners, then the nodes inside it
+    pub edges: Vec<Vec<usize>>,
+    /// Nodes of every face: its corners, then the nodes inside its edges and
+    /// inside the face
+    ///
+    /// Faces of volume elements have their normal pointing out of the element.
+    /// A surface element is its own single face; lines and points have none.
+    pub faces: Vec<Vec<usize>>,
+}
+
+impl ReferenceElement {
+    /// The reference element of `element_type`
+    ///
+    /// The reference shapes are Gmsh's: `[-1, 1]` for lines, `[-1, 1]²` for
+    /// quadrangles, `[-1, 1]³` for hexahedra, the unit simplex for triangles
+    /// and tetrahedra, a unit triangle extruded over `[-1, 1]` for prisms, and
+    /// a pyramid on `[-1, 1]²` with its apex at `z = 1`.
+    ///
+    /// Returns `None` for element types other than points, lines of any order
+    /// and the first- and second-order surface and volume elements.
+    pub fn new(element_type: ElementType) -> Option<Self> {
+        let family = element_type.family();
+        let order = element_type.polynomial_order()?;
+        let corners = corners(family)?;
+        let corner_edges = corner_edges(family);
+        let corner_faces = corner_faces(family);
+
+        let mut element = Self {
+            element_type,
+            nodes: corners.to_vec(),
+            edges: corner_edges.iter().map(|edge| edge.to_vec()).collect(),
+            faces: Vec::new(),
+        };
+        match (family, order) {
+            (ElementFamily::Point, 0) => {}
+            (ElementFamily::Line, 1..) => {
+                for k in 1..order {
+                    let x = -1.0 + 2.0 * f64::from(k) / f64::from(order);
+                    element.edges[0].push(element.nodes.len());
+                    element.nodes.push([x, 0.0, 0.0]);
+                }
+            }
+            (_, 1) => element.faces = corner_faces.iter().map(|face| face.to_vec()).collect(),
+            (_, 2) => {
+                for (i, &[a, b]) in corner_edges.iter().enumerate() {
+                    element.edges[i].push(element.nodes.len());
+                    element.nodes.push(middle(&[corners[a], corners[b]]));
+                }
+                for face in corner_faces {
+                    let mut nodes = face.to_vec();
+                    for (k, &a) in face.iter().enumerate() {
+                        let b = face[(k + 1) % face.len()];
+                        let edge = corner_edges
+                            .iter()
+                            .position(|e| *e == [a, b] || *e == [b, a])?;
+                        nodes.push(corners.len() + edge);
+                    }
+                    element.faces.push(nodes);
+                }
+                let fixed = element_type.fixed_node_count()?;
+                // Complete quadrangle faces get a centre node, in face order
+                for face in &mut element.faces {
+                    let corners_of_face = &face[..face.len() / 2];
+                    if element.nodes.len() < fixed && corners_of_face.len() == 4 {
+                        let points: Vec<[f64; 3]> =
+                            corners_of_face.iter().map(|&i| corners[i]).collect();
+                        face.push(element.nodes.len());
+                        element.nodes.push(middle(&points));
+                    }
+                }
+                // Hexahedron27 ends with the centre of the volume
+                if element.nodes.len() < fixed {
+                    element.nodes.push(middle(corners));
+                }
+            }
+            _ => return None,
+        }
+        (element.nodes.len() == element_type.fixed_node_count()?).then_some(element)
+    }
+
+    /// Number of corner nodes
+    pub fn num_corners(&self) -> usize {
+        corner_count(self.element_type.family())
+    }
+}
+
+/// Number of corner nodes of the elements of `family`
+pub(crate) fn corner_count(family: ElementFamily) -> usize {
+    corners(family).map_or(0, <[_]>::len)
+}
+
+/// Corners of the reference shape of `family`, in Gmsh ordering
+fn corners(family: ElementFamily) -> Option<&'static [[f64; 3]]> {
+    let corners: &'static [[f64; 3]] = match family {
+        ElementFamily::Point => &[[0.0, 0.0, 0.0]],
+        ElementFamily::Line => &[[-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+        ElementFamily::Triangle => &[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        ElementFamily::Quadrangle => &[
+            [-1.0, -1.0, 0.0],
+            [1.0, -1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [-1.0, 1.0, 0.0],
+        ],
+        ElementFamily::Tetrahedron => &[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ],
+        ElementFamily::Hexahedron => &[
+            [-1.0, -1.0, -1.0],
+            [1.0, -1.0, -1.0],
+            [1.0, 1.0, -1.0],
+            [-1.0, 1.0, -1.0],
+            [-1.0, -1.0, 1.0],
+            [1.0, -1.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [-1.0, 1.0, 1.0],
+        ],
+        ElementFamily::Prism => &[
+            [0.0, 0.0, -1.0],
+            [1.0, 0.0, -1.0],
+            [0.0, 1.0, -1.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ],
+        ElementFamily::Pyramid => &[
+            [-1.0, -1.0, 0.0],
+            [1.0, -1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [-1.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ],
+        ElementFamily::Other => return None,
+    };
+    Some(corners)
+}
+
+/// Edges of `family` as pairs of corners, in the order Gmsh numbers the
+/// nodes inside them
+pub(crate) fn corner_edges(family: ElementFamily) -> &'static [[usize; 2]] {
+    match family {
+        ElementFamily::Point | ElementFamily::Other => &[],
+        ElementFamily::Line => &[[0, 1]],
+        ElementFamily::Triangle => &[[0, 1], [1, 2], [2, 0]],
+        ElementFamily::Quadrangle => &[[0, 1], [1, 2], [2, 3], [3, 0]],
+        ElementFamily::Tetrahedron => &[[0, 1], [1, 2], [2, 0], [3, 0], [3, 2], [3, 1]],
+        ElementFamily::Hexahedron => &[
+            [0, 1],
+            [0, 3],
+            [0, 4],
+            [1, 2],
+            [1, 5],
+            [2, 3],
+            [2, 6],
+            [3, 7],
+            [4, 5],
+            [4, 7],
+            [5, 6],
+            [6, 7],
+        ],
+        ElementFamily::Prism => &[
+            [0, 1],
+            [0, 2],
+            [0, 3],
+            [1, 2],
+            [1, 4],
+            [2, 5],
+            [3, 4],
+            [3, 5],
+            [4, 5],
+        ],
+        ElementFamily::Pyramid => &[
+            [0, 1],
+            [0, 3],
+            [0, 4],
+            [1, 2],
+            [1, 4],
+            [2, 3],
+            [2, 4],
+            [3, 4],
+        ],
+    }
+}
+
+/// Faces of `family` as cycles of corners, outward for volume elements
+pub(crate) fn corner_faces(family: ElementFamily) -> &'static [&'static [usize]] {
+    match family {
+        ElementFamily::Point | ElementFamily::Line | ElementFamily::Other => &[],
+        ElementFamily::Triangle => &[&[0, 1, 2]],
+        ElementFamily::Quadrangle => &[&[0, 1, 2, 3]],
+        ElementFamily::Tetrahedron => &[&[0, 2, 1], &[0, 1, 3], &[0, 3, 2], &[3, 1, 2]],
+        ElementFamily::Hexahedron => &[
+            &[0, 3, 2, 1],
+            &[0, 1, 5, 4],
+            &[0, 4, 7, 3],
+            &[1, 2, 6, 5],
+            &[2, 3, 7, 6],
+            &[4, 5, 6, 7],
+        ],
+        ElementFamily::Prism => &[
+            &[0, 2, 1],
+            &[3, 4, 5],
+            &[0, 1, 4, 3],
+            &[0, 3, 5, 2],
+            &[1, 2, 5, 4],
+        ],
+        ElementFamily::Pyramid => &[
+            &[0, 3, 2, 1],
+            &[0, 1, 4],
+            &[0, 4, 3],
+            &[1, 2, 4],
+            &[2, 3, 4],
+        ],
+    }
+}
+
+/// Centroid of `points`
+fn middle(points: &[[f64; 3]]) -> [f64; 3] {
+    let n = points.len() as f64;
+    [0, 1, 2].map(|k| points.iter().map(|p| p[k]).sum::<f64>() / n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_order_reference_elements() {
+        let hex = ReferenceElement::new(ElementType::Hexahedron27).unwrap();
+        assert_eq!(hex.nodes.len(), 27);
+        assert_eq!(hex.nodes[8], [0.0, -1.0, -1.0]);
+        assert_eq!(hex.nodes[20], [0.0, 0.0, -1.0]);
+        assert_eq!(hex.nodes[21], [0.0, -1.0, 0.0]);
+        assert_eq!(hex.nodes[26], [0.0, 0.0, 0.0]);
+        assert_eq!(hex.faces[0], [0, 3, 2, 1, 9, 13, 11, 8, 20]);
+
+        let prism = ReferenceElement::new(ElementType::Prism18).unwrap();
+        assert_eq!(prism.nodes[15], [0.5, 0.0, 0.0]);
+        assert_eq!(prism.faces[0].len(), 6);
+        assert_eq!(prism.faces[2].len(), 9);
+
+        assert_eq!(
+            ReferenceElement::new(ElementType::Quadrangle8)
+                .unwrap()
+                .faces[0],
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        );
+        let line = ReferenceElement::new(ElementType::Line4).unwrap();
+        assert_eq!(line.edges[0], [0, 1, 2, 3]);
+        assert!((line.nodes[2][0] + 1.0 / 3.0).abs() < 1e-12);
+        assert!(ReferenceElement::new(ElementType::Tetrahedron20).is_none());
+    }
+
+    #[test]
+    fn test_faces_point_outwards() {
+        for element_type in [
+            ElementType::Tetrahedron4,
+            ElementType::Hexahedron8,
+            ElementType::Prism6,
+            ElementType::Pyramid5,
+        ] {
+            let element = ReferenceElement::new(element_type).unwrap();
+            let centre = middle(&element.nodes);
+            for face in &element.faces {
+                let [a, b, c] = [0, 1, 2].map(|k| element.nodes[face[k]]);
+                let u = [0, 1, 2].map(|k| b[k] - a[k]);
+                let v = [0, 1, 2].map(|k| c[k] - a[k]);
+                let normal = [
+                    u[1] * v[2] - u[2] * v[1],
+                    u[2] * v[0] - u[0] * v[2],
+                    u[0] * v[1] - u[1] * v[0],
+                ];
+                let outward: f64 = (0..3).map(|k| normal[k] * (a[k] - centre[k])).sum();
+                assert!(outward > 0.0, "{} face {:?}", element_type, face);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Region growing over the dual graph
+
+use super::dual::DualGraph;
+use crate::error::{ParseError, Result};
+use crate::sets::ElementSet;
+use crate::types::{ElementRef, ElementTag, Mesh};
+use std::collections::VecDeque;
+
+/// Connected region of elements around `seed` for which `predicate` holds
+///
+/// The region grows breadth-first from the seed to neighbouring elements of
+/// the same dimension, adding every neighbour accepted by `predicate`, e.g.
+/// elements of the same physical group, with a quality below a threshold or a
+/// field value above an iso-value. The seed itself is always part of the
+/// region. The returned set is named `"region"`.
+///
+/// # Errors
+/// Returns [`ParseError::NotFound`] if no element has the tag `seed`.
+pub fn grow_region<F>(mesh: &Mesh, seed: ElementTag, mut predicate: F) -> Result<ElementSet>
+where
+    F: FnMut(ElementRef<'_>) -> bool,
+{
+    let graph = DualGraph::new(mesh);
+    let start = graph
+        .index_of(seed)
+        .ok_or_else(|| ParseError::NotFound(format!("Element {}", seed)))?;
+
+    let mut visited = vec![false; graph.len()];
+    visited[start] = true;
+    let mut region = vec![seed];
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        for &next in graph.neighbors(current) {
+            if visited[next] {
+                continue;
+            }
+            visited[next] = true;
+            let element = graph.element(next);
+            if predicate(element) {
+                region.push(element.tag());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    Ok(ElementSet::new("region", region))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::node_coordinates;
+    use crate::parse_msh;
+
+    /// The rectangle [0, 3] x [0, 1] split into three squares of two triangles
+    const STRIP: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n1 8 1 8\n2 1 0 8\n1\n2\n3\n4\n5\n6\n7\n8\n\
+        0 0 0\n1 0 0\n2 0 0\n3 0 0\n0 1 0\n1 1 0\n2 1 0\n3 1 0\n$EndNodes\n\
+        $Elements\n1 6 1 6\n2 1 2 6\n1 1 2 6\n2 1 6 5\n3 2 3 7\n4 2 7 6\n5 3 4 8\n6 3 8 7\n\
+        $EndElements\n";
+
+    #[test]
+    fn test_grow_region_covers_connected_surface() {
+        let mesh = parse_msh(STRIP).unwrap();
+        let region = grow_region(&mesh, ElementTag(3), |_| true).unwrap();
+        assert_eq!(region.len(), 6);
+        assert!(matches!(
+            grow_region(&mesh, ElementTag(7), |_| true),
+            Err(ParseError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_grow_region_stops_at_predicate() {
+        let mesh = parse_msh(STRIP).unwrap();
+        let coords = node_coordinates(&mesh);
+        let left = |e: ElementRef<'_>| e.nodes().iter().all(|n| coords[n][0] <= 1.0);
+        let region = grow_region(&mesh, ElementTag(2), left).unwrap();
+        assert_eq!(region.len(), 2);
+        assert!(region.contains(ElementTag(1)));
+    }
+}
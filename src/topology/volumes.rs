@@ -0,0 +1,104 @@
+//! Volume regions separated by surface elements
+
+use super::dual::{facet_key, facets};
+use super::root;
+use crate::sets::ElementSet;
+use crate::types::{ElementTag, Mesh, NodeTag};
+use std::collections::{HashMap, HashSet};
+
+/// Group the volume elements into regions separated by surface elements
+///
+/// Two volume elements belong to the same region when they are connected
+/// through faces that are shared by exactly two volume elements and not
+/// covered by a surface element of the mesh. Internal surfaces kept in the
+/// mesh, typically because they belong to a physical group, therefore split
+/// the volume into regions, which is useful for files that lack 3D physical
+/// groups. Only elements in blocks of dimension 3 and 2 are considered, using
+/// their corner nodes.
+///
+/// Regions are ordered by their smallest element tag and named `region_1`,
+/// `region_2`, ..., with the same number as their tag.
+pub fn identify_volumes(mesh: &Mesh) -> Vec<ElementSet> {
+    let surfaces: HashSet<Vec<NodeTag>> = mesh
+        .element_block_refs()
+        .filter(|b| b.entity_dim() == 2)
+        .filter_map(|b| Some((b, facets(b.element_type())?)))
+        .flat_map(|(block, facets)| {
+            let corners: Vec<usize> = facets.iter().map(|f| f[0]).collect();
+            block
+                .elements()
+                .map(move |e| facet_key(e.nodes(), &corners))
+        })
+        .collect();
+
+    let mut volumes = Vec::new();
+    let mut faces: HashMap<Vec<NodeTag>, Vec<usize>> = HashMap::new();
+    for block in mesh.element_block_refs().filter(|b| b.entity_dim() == 3) {
+        let facets = facets(block.element_type()).unwrap_or_default();
+        for element in block.elements() {
+            for facet in facets {
+                let key = facet_key(element.nodes(), facet);
+                faces.entry(key).or_default().push(volumes.len());
+            }
+            volumes.push(element.tag());
+        }
+    }
+
+    let mut parent: Vec<usize> = (0..volumes.len()).collect();
+    for (key, elements) in &faces {
+        if let [a, b] = elements[..] {
+            if !surfaces.contains(key) {
+                let (a, b) = (root(&mut parent, a), root(&mut parent, b));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<ElementTag>> = HashMap::new();
+    for (i, &tag) in volumes.iter().enumerate() {
+        groups.entry(root(&mut parent, i)).or_default().push(tag);
+    }
+    let mut regions: Vec<Vec<ElementTag>> = groups.into_values().collect();
+    regions.sort_by_key(|tags| tags.iter().min().copied());
+    regions
+        .into_iter()
+        .enumerate()
+        .map(|(i, tags)| ElementSet::new(format!("region_{}", i + 1), tags).with_tag(i as i32 + 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    #[test]
+    fn test_internal_surface_splits_volume() {
+        // Two tetrahedra sharing the face 1-2-3
+        let tetrahedra = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 5 1 5\n3 1 0 5\n1\n2\n3\n4\n5\n\
+            0 0 0\n1 0 0\n0 1 0\n0 0 1\n0 0 -1\n$EndNodes\n";
+        let joined = format!(
+            "{}$Elements\n1 2 1 2\n3 1 4 2\n1 1 2 3 4\n2 1 3 2 5\n$EndElements\n",
+            tetrahedra
+        );
+        let split = format!(
+            "{}$Elements\n2 3 1 3\n2 1 2 1\n3 1 2 3\n3 1 4 2\n1 1 2 3 4\n2 1 3 2 5\n$EndElements\n",
+            tetrahedra
+        );
+
+        assert_eq!(identify_volumes(&parse_msh(joined).unwrap()).len(), 1);
+        let regions = identify_volumes(&parse_msh(split).unwrap());
+        assert_eq!(regions.len(), 2);
+        assert!(regions[0].contains(ElementTag(1)));
+        assert_eq!(regions[1].name, "region_2");
+    }
+
+    #[test]
+    fn test_surface_mesh_has_no_volume() {
+        let triangle = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 3\n2 1 0 3\n1\n2\n3\n0 0 0\n1 0 0\n0 1 0\n$EndNodes\n\
+            $Elements\n1 1 1 1\n2 1 2 1\n1 1 2 3\n$EndElements\n";
+        assert!(identify_volumes(&parse_msh(triangle).unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+//! Journal of the operations that modified a mesh after parsing
+
+use super::{ElementTag, Mesh, NodeTag};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// One operation recorded in [`Mesh::change_log`]
+///
+/// Tag maps only list the tags that changed, from old to new.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct Change {
+    /// Name of the operation, usually the method that made the change
+    pub operation: String,
+    /// Arguments of the operation, formatted
+    pub parameters: BTreeMap<String, String>,
+    /// Numbers of nodes, elements, ... affected
+    pub counts: BTreeMap<String, usize>,
+    pub node_map: Vec<(NodeTag, NodeTag)>,
+    pub element_map: Vec<(ElementTag, ElementTag)>,
+}
+
+impl Change {
+    pub fn new(operation: impl Into<String>) -> Self {
+        Self {
+            operation: operation.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_parameter(mut self, key: impl Into<String>, value: impl fmt::Display) -> Self {
+        self.parameters.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn with_count(mut self, key: impl Into<String>, count: usize) -> Self {
+        self.counts.insert(key.into(), count);
+        self
+    }
+
+    /// Record the node tags that changed in `map`
+    pub fn with_node_map<'a>(
+        mut self,
+        map: impl IntoIterator<Item = (&'a NodeTag, &'a NodeTag)>,
+    ) -> Self {
+        self.node_map = map
+            .into_iter()
+            .filter(|(old, new)| old != new)
+            .map(|(&old, &new)| (old, new))
+            .collect();
+        self
+    }
+
+    /// Record the element tags that changed in `map`
+    pub fn with_element_map<'a>(
+        mut self,
+        map: impl IntoIterator<Item = (&'a ElementTag, &'a ElementTag)>,
+    ) -> Self {
+        self.element_map = map
+            .into_iter()
+            .filter(|(old, new)| old != new)
+            .map(|(&old, &new)| (old, new))
+            .collect();
+        self
+    }
+}
+
+/// `operation(key=value, ...): N key, ...`
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parameters: Vec<String> = self
+            .parameters
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        write!(f, "{}({})", self.operation, parameters.join(", "))?;
+        let mut counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(key, count)| format!("{} {}", count, key))
+            .collect();
+        if !self.node_map.is_empty() {
+            counts.push(format!("{} node tags changed", self.node_map.len()));
+        }
+        if !self.element_map.is_empty() {
+            counts.push(format!("{} element tags changed", self.element_map.len()));
+        }
+        if !counts.is_empty() {
+            write!(f, ": {}", counts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Mesh {
+    /// Operations that modified the mesh since it was parsed, oldest first
+    ///
+    /// Mutating methods of the crate, such as [`Mesh::renumber_contiguous`] or
+    /// [`Mesh::quantize_coordinates`], record what they changed, so pipelines
+    /// can audit how a mesh was altered before writing it back. The log is not
+    /// written to MSH files.
+    pub fn change_log(&self) -> &[Change] {
+        &self.change_log
+    }
+
+    /// Append `change` to the [`Mesh::change_log`], e.g. from code editing
+    /// the mesh fields directly
+    pub fn record_change(&mut self, change: Change) {
+        self.change_log.push(change);
+    }
+
+    pub fn clear_change_log(&mut self) {
+        self.change_log.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::CHAIN;
+
+    #[test]
+    fn test_mutations_are_logged() {
+        let mut mesh = crate::parse_msh(CHAIN).unwrap();
+        assert!(mesh.change_log().is_empty());
+
+        mesh.quantize_coordinates(2);
+        mesh.renumber_rcm();
+        let log = mesh.change_log();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].operation, "quantize_coordinates");
+        assert_eq!(log[0].parameters["significant_digits"], "2");
+        assert_eq!(log[1].operation, "renumber_rcm");
+        assert!(!log[1].node_map.is_empty());
+        assert!(log[1].to_string().starts_with("renumber_rcm(): "));
+    }
+
+    #[test]
+    fn test_recorded_change_display() {
+        let mut mesh = crate::parse_msh(CHAIN).unwrap();
+        mesh.record_change(
+            Change::new("smooth")
+                .with_parameter("iterations", 3)
+                .with_count("nodes_moved", 7),
+        );
+        assert_eq!(
+            mesh.change_log()[0].to_string(),
+            "smooth(iterations=3): 7 nodes_moved"
+        );
+        mesh.clear_change_log();
+        assert!(mesh.change_log().is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+//! Frame of the coordinates of a mesh
+
+use crate::geometry::{Affine3, LengthUnit};
+
+/// Frame the node coordinates are expressed in, stored in the crate-defined
+/// `$CoordinateSystem` section
+///
+/// MSH files carry bare coordinates; when parts meshed separately are
+/// assembled, this records which frame and unit each one uses and where the
+/// frame sits in the assembly. The section reads
+///
+/// ```text
+/// $CoordinateSystem
+/// "name"
+/// unit
+/// a11 a12 a13 t1
+/// a21 a22 a23 t2
+/// a31 a32 a33 t3
+/// $EndCoordinateSystem
+/// ```
+///
+/// where `unit` is a [`LengthUnit::symbol`], or `-` if unknown, and the last
+/// three lines are the rows of [`transform`](Self::transform).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct CoordinateSystem {
+    /// Name of the frame, e.g. the part or datum it is attached to
+    pub name: String,
+    /// Unit of the coordinates, if known
+    pub unit: Option<LengthUnit>,
+    /// Placement of the frame in its parent: maps the coordinates of the mesh
+    /// to parent coordinates, see
+    /// [`Mesh::apply_coordinate_transform`](crate::Mesh::apply_coordinate_transform)
+    pub transform: Affine3,
+}
+
+impl CoordinateSystem {
+    /// A frame named `name`, of unknown unit, coinciding with its parent
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            unit: None,
+            transform: Affine3::identity(),
+        }
+    }
+
+    pub fn with_unit(mut self, unit: LengthUnit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Affine3) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Whether the frame coincides with its parent
+    pub fn is_identity(&self) -> bool {
+        self.transform == Affine3::identity()
+    }
+}
@@ -0,0 +1,297 @@
+use super::{Element, ElementBlock, ElementType};
+use crate::error::{ParseError, Result};
+use crate::types::{ElementTag, EntityTag, Indexing, Mesh, NodeTag};
+use std::collections::HashMap;
+
+/// Element block stored as flat arrays
+///
+/// Holds the same data as an [`ElementBlock`] without one allocation per
+/// element: the nodes of element `i` are `connectivity[i * stride..(i + 1) * stride]`.
+/// Only element types with a fixed number of nodes can be stored this way.
+///
+/// Parsed with [`ParserOptions::compact_storage`](crate::ParserOptions::compact_storage)
+/// into [`Mesh::compact_element_blocks`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct ElementBlockCompact {
+    pub entity_dim: i32,
+    pub entity_tag: EntityTag,
+    pub element_type: ElementType,
+    /// Number of nodes per element
+    pub stride: usize,
+    /// Tag of each element
+    pub tags: Vec<ElementTag>,
+    /// Nodes of all elements, `stride` per element
+    pub connectivity: Vec<NodeTag>,
+    /// Index of the block among all element blocks of the mesh, expanded or
+    /// compact, so that the file order can be restored
+    pub position: usize,
+}
+
+impl ElementBlockCompact {
+    /// An empty block, or `None` if `element_type` has no fixed node count
+    pub fn new(entity_dim: i32, entity_tag: EntityTag, element_type: ElementType) -> Option<Self> {
+        Some(Self {
+            entity_dim,
+            entity_tag,
+            element_type,
+            stride: element_type.fixed_node_count()?,
+            tags: Vec::new(),
+            connectivity: Vec::new(),
+            position: 0,
+        })
+    }
+
+    /// Store `block` compactly, or `None` if its element type has no fixed
+    /// node count or an element has the wrong number of nodes
+    ///
+    /// The block gets position 0; set [`position`](Self::position) to place it
+    /// among the other blocks of a mesh.
+    pub fn from_block(block: &ElementBlock) -> Option<Self> {
+        let mut compact = Self::new(block.entity_dim, block.entity_tag, block.element_type)?;
+        compact.tags.reserve(block.elements.len());
+        compact
+            .connectivity
+            .reserve(block.elements.len() * compact.stride);
+        for element in &block.elements {
+            if element.nodes.len() != compact.stride {
+                return None;
+            }
+            compact.tags.push(element.tag);
+            compact.connectivity.extend_from_slice(&element.nodes);
+        }
+        Some(compact)
+    }
+
+    /// The same block with one [`Element`] per element
+    pub fn to_block(&self) -> ElementBlock {
+        let elements = self
+            .iter()
+            .map(|(tag, nodes)| Element::new(tag, nodes.to_vec()))
+            .collect();
+        ElementBlock::new(
+            self.entity_dim,
+            self.entity_tag,
+            self.element_type,
+            elements,
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Nodes of the element at `index`
+    pub fn nodes(&self, index: usize) -> Option<&[NodeTag]> {
+        let start = index.checked_mul(self.stride)?;
+        self.connectivity.get(start..start + self.stride)
+    }
+
+    /// Tags and nodes of the elements, in order
+    pub fn iter(&self) -> impl Iterator<Item = (ElementTag, &[NodeTag])> + '_ {
+        // Every fixed node count is positive, but `chunks` panics on zero
+        let chunks = self.connectivity.chunks(self.stride.max(1));
+        self.tags.iter().copied().zip(chunks)
+    }
+
+    /// The connectivity as indices into `node_tags`, in the base `indexing`
+    ///
+    /// `node_tags` is the node numbering of the caller, e.g.
+    /// [`MeshArrays::node_tags`](crate::export::MeshArrays::node_tags).
+    ///
+    /// # Errors
+    /// Returns [`ParseError::MeshValidationError`] if an element uses a node
+    /// missing from `node_tags`.
+    pub fn indexed_connectivity(
+        &self,
+        node_tags: &[NodeTag],
+        indexing: Indexing,
+    ) -> Result<Vec<usize>> {
+        let position: HashMap<NodeTag, usize> = node_tags
+            .iter()
+            .enumerate()
+            .map(|(i, &tag)| (tag, i))
+            .collect();
+        self.iter()
+            .flat_map(|(tag, nodes)| nodes.iter().map(move |node| (tag, node)))
+            .map(|(tag, node)| match position.get(node) {
+                Some(&i) => Ok(indexing.index(i)),
+                None => Err(ParseError::MeshValidationError(format!(
+                    "Element {} references missing node {}",
+                    tag, node
+                ))),
+            })
+            .collect()
+    }
+}
+
+/// An element block of either storage
+pub(crate) enum Stored<E, C> {
+    Expanded(E),
+    Compact(C),
+}
+
+/// Merge expanded and compact blocks back into file order
+///
+/// Compact blocks go to their [`position`](ElementBlockCompact::position), and
+/// expanded blocks fill the remaining places in order. Positions past the end
+/// place their blocks last.
+pub(crate) fn in_file_order<E, C>(
+    expanded: impl IntoIterator<Item = E>,
+    compact: impl IntoIterator<Item = C>,
+    position: impl Fn(&C) -> usize,
+) -> Vec<Stored<E, C>> {
+    let mut compact: Vec<C> = compact.into_iter().collect();
+    compact.sort_by_key(|block| position(block));
+    let mut compact = compact.into_iter().peekable();
+    let mut expanded = expanded.into_iter();
+    let mut blocks = Vec::new();
+    loop {
+        if let Some(block) = compact.next_if(|block| position(block) <= blocks.len()) {
+            blocks.push(Stored::Compact(block));
+        } else if let Some(block) = expanded.next() {
+            blocks.push(Stored::Expanded(block));
+        } else {
+            blocks.extend(compact.map(Stored::Compact));
+            return blocks;
+        }
+    }
+}
+
+impl Mesh {
+    /// Move every element block that can be stored compactly to
+    /// [`Mesh::compact_element_blocks`]
+    ///
+    /// Blocks of element types without a fixed node count stay in
+    /// [`Mesh::element_blocks`]. Each compact block records its position, so
+    /// the blocks keep their order in [`Mesh::element_block_refs`] and in
+    /// written files.
+    pub fn compact_storage(&mut self) {
+        let blocks = in_file_order(
+            std::mem::take(&mut self.element_blocks),
+            std::mem::take(&mut self.compact_element_blocks),
+            |block| block.position,
+        );
+        for (position, block) in blocks.into_iter().enumerate() {
+            let compact = match block {
+                Stored::Expanded(block) => match ElementBlockCompact::from_block(&block) {
+                    Some(compact) => compact,
+                    None => {
+                        self.element_blocks.push(block);
+                        continue;
+                    }
+                },
+                Stored::Compact(compact) => compact,
+            };
+            self.compact_element_blocks.push(ElementBlockCompact {
+                position,
+                ..compact
+            });
+        }
+    }
+
+    /// Move the compact element blocks back to [`Mesh::element_blocks`], each
+    /// at its original position
+    pub fn expand_storage(&mut self) {
+        let blocks = in_file_order(
+            std::mem::take(&mut self.element_blocks),
+            std::mem::take(&mut self.compact_element_blocks),
+            |block| block.position,
+        );
+        self.element_blocks = blocks
+            .into_iter()
+            .map(|block| match block {
+                Stored::Expanded(block) => block,
+                Stored::Compact(compact) => compact.to_block(),
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::TRIANGLE;
+
+    #[test]
+    fn test_compact_round_trip() {
+        let mut mesh = crate::parse_msh(TRIANGLE).unwrap();
+        let blocks = mesh.element_blocks.clone();
+        mesh.compact_storage();
+        assert!(mesh.element_blocks.is_empty());
+        assert_eq!(mesh.compact_element_blocks.len(), blocks.len());
+        mesh.validate().unwrap();
+
+        let compact = &mesh.compact_element_blocks[0];
+        assert_eq!(compact.len(), blocks[0].elements.len());
+        assert_eq!(
+            compact.nodes(0),
+            Some(blocks[0].elements[0].nodes.as_slice())
+        );
+        assert_eq!(compact.nodes(compact.len()), None);
+
+        mesh.expand_storage();
+        for (expanded, original) in mesh.element_blocks.iter().zip(&blocks) {
+            let tags = |b: &ElementBlock| -> Vec<_> { b.elements.iter().map(|e| e.tag).collect() };
+            assert_eq!(tags(expanded), tags(original));
+        }
+    }
+
+    #[test]
+    fn test_storage_keeps_block_order() {
+        // A polygon block, which cannot be compact, between two compact ones
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 3\n2 1 0 3\n1\n2\n3\n0 0 0\n1 0 0\n0 1 0\n$EndNodes\n\
+            $Elements\n3 3 1 3\n1 1 1 1\n1 1 2\n2 1 34 1\n2 1 2 3\n2 1 2 1\n3 1 2 3\n\
+            $EndElements\n";
+        let mut mesh = crate::parse_msh(content).unwrap();
+        let written = crate::writer::write_msh(&mesh).unwrap();
+        let order = |mesh: &Mesh| -> Vec<_> {
+            mesh.element_block_refs()
+                .map(|b| b.element_type())
+                .collect()
+        };
+        let types = order(&mesh);
+
+        mesh.compact_storage();
+        assert_eq!(mesh.element_blocks.len(), 1);
+        assert_eq!(mesh.compact_element_blocks.len(), 2);
+        assert_eq!(order(&mesh), types);
+        assert_eq!(crate::writer::write_msh(&mesh).unwrap(), written);
+
+        mesh.expand_storage();
+        assert_eq!(order(&mesh), types);
+        assert_eq!(crate::writer::write_msh(&mesh).unwrap(), written);
+    }
+
+    #[test]
+    fn test_indexed_connectivity_matches_arrays() {
+        use crate::export::ArrayOptions;
+
+        let mut mesh = crate::parse_msh(TRIANGLE).unwrap();
+        mesh.compact_storage();
+        let options = ArrayOptions::default().with_indexing(Indexing::OneBased);
+        let arrays = mesh.to_arrays(&options).unwrap();
+        let mut connectivity = Vec::new();
+        for block in &mesh.compact_element_blocks {
+            let indices = block
+                .indexed_connectivity(&arrays.node_tags, Indexing::OneBased)
+                .unwrap();
+            assert_eq!(indices.len(), block.connectivity.len());
+            connectivity.extend(indices);
+        }
+        assert_eq!(connectivity, [1, 2, 1, 2, 3]);
+        assert_eq!(connectivity, arrays.connectivity);
+        assert!(mesh.compact_element_blocks[0]
+            .indexed_connectivity(&[], Indexing::ZeroBased)
+            .is_err());
+    }
+}
@@ -1,18 +1,28 @@
+mod compact;
 pub mod type_enum;
-pub use type_enum::ElementType;
+pub use compact::ElementBlockCompact;
+pub(crate) use compact::{in_file_order, Stored};
+pub use type_enum::{ElementFamily, ElementType};
+
+use crate::types::{ElementTag, EntityTag, NodeTag};
 
 /// Element structure definition
 ///
 /// Simplified to a single generic structure used for all element types.
 /// The node count is validated at runtime during parsing.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Element {
-    pub tag: usize,
-    pub nodes: Vec<usize>,
+    pub tag: ElementTag,
+    pub nodes: Vec<NodeTag>,
 }
 
 impl Element {
-    pub fn new(tag: usize, nodes: Vec<usize>) -> Self {
+    pub fn new(tag: ElementTag, nodes: Vec<NodeTag>) -> Self {
         Self { tag, nodes }
     }
 }
@@ -21,9 +31,14 @@ impl Element {
 ///
 /// Represents a block of elements sharing the same type, dimension, and entity tag.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct ElementBlock {
     pub entity_dim: i32,
-    pub entity_tag: i32,
+    pub entity_tag: EntityTag,
     pub element_type: ElementType,
     pub elements: Vec<Element>,
 }
@@ -31,7 +46,7 @@ pub struct ElementBlock {
 impl ElementBlock {
     pub fn new(
         entity_dim: i32,
-        entity_tag: i32,
+        entity_tag: EntityTag,
         element_type: ElementType,
         elements: Vec<Element>,
     ) -> Self {
@@ -43,3 +58,122 @@ impl ElementBlock {
         }
     }
 }
+
+/// An element block of a mesh, stored in [`Mesh::element_blocks`] or in
+/// [`Mesh::compact_element_blocks`], see [`Mesh::element_block_refs`]
+///
+/// [`Mesh::element_blocks`]: crate::Mesh::element_blocks
+/// [`Mesh::compact_element_blocks`]: crate::Mesh::compact_element_blocks
+/// [`Mesh::element_block_refs`]: crate::Mesh::element_block_refs
+#[derive(Debug, Clone, Copy)]
+pub enum ElementBlockRef<'a> {
+    Expanded(&'a ElementBlock),
+    Compact(&'a ElementBlockCompact),
+}
+
+impl<'a> ElementBlockRef<'a> {
+    pub fn entity_dim(self) -> i32 {
+        match self {
+            Self::Expanded(block) => block.entity_dim,
+            Self::Compact(block) => block.entity_dim,
+        }
+    }
+
+    pub fn entity_tag(self) -> EntityTag {
+        match self {
+            Self::Expanded(block) => block.entity_tag,
+            Self::Compact(block) => block.entity_tag,
+        }
+    }
+
+    pub fn element_type(self) -> ElementType {
+        match self {
+            Self::Expanded(block) => block.element_type,
+            Self::Compact(block) => block.element_type,
+        }
+    }
+
+    /// Number of elements in the block
+    pub fn len(self) -> usize {
+        match self {
+            Self::Expanded(block) => block.elements.len(),
+            Self::Compact(block) => block.len(),
+        }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// The elements of the block, in order
+    pub fn elements(self) -> impl Iterator<Item = ElementRef<'a>> + 'a {
+        let (expanded, compact) = match self {
+            Self::Expanded(block) => (Some(block.elements.iter()), None),
+            Self::Compact(block) => (None, Some(block.iter())),
+        };
+        let expanded = expanded
+            .into_iter()
+            .flatten()
+            .map(|e| (e.tag, e.nodes.as_slice()));
+        expanded
+            .chain(compact.into_iter().flatten())
+            .map(move |(tag, nodes)| ElementRef {
+                block: self,
+                tag,
+                nodes,
+            })
+    }
+
+    /// A copy of the block with one [`Element`] per element
+    pub fn to_block(self) -> ElementBlock {
+        match self {
+            Self::Expanded(block) => block.clone(),
+            Self::Compact(block) => block.to_block(),
+        }
+    }
+}
+
+/// An element together with the block it belongs to
+///
+/// The element may be stored in an expanded or a compact block.
+#[derive(Debug, Clone, Copy)]
+pub struct ElementRef<'a> {
+    block: ElementBlockRef<'a>,
+    tag: ElementTag,
+    nodes: &'a [NodeTag],
+}
+
+impl<'a> ElementRef<'a> {
+    pub fn new(block: &'a ElementBlock, element: &'a Element) -> Self {
+        Self {
+            block: ElementBlockRef::Expanded(block),
+            tag: element.tag,
+            nodes: &element.nodes,
+        }
+    }
+
+    /// The block holding the element
+    pub fn block(&self) -> ElementBlockRef<'a> {
+        self.block
+    }
+
+    pub fn tag(&self) -> ElementTag {
+        self.tag
+    }
+
+    pub fn element_type(&self) -> ElementType {
+        self.block.element_type()
+    }
+
+    pub fn entity_dim(&self) -> i32 {
+        self.block.entity_dim()
+    }
+
+    pub fn entity_tag(&self) -> EntityTag {
+        self.block.entity_tag()
+    }
+
+    pub fn nodes(&self) -> &'a [NodeTag] {
+        self.nodes
+    }
+}
@@ -3,8 +3,12 @@
 //! This enum is used during parsing to identify element types by their ID.
 //! The actual element data is stored in individual typed structs and ElementBlock variants.
 
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub enum ElementType {
     // Basic elements (1-15)
     Line2,         // ID 1, 2 nodes
@@ -341,6 +345,149 @@ impl ElementType {
         }
     }
 
+    /// Gmsh element type ID, the inverse of [`ElementType::from_i32`]
+    pub fn to_i32(self) -> i32 {
+        match self {
+            ElementType::Line2 => 1,
+            ElementType::Triangle3 => 2,
+            ElementType::Quadrangle4 => 3,
+            ElementType::Tetrahedron4 => 4,
+            ElementType::Hexahedron8 => 5,
+            ElementType::Prism6 => 6,
+            ElementType::Pyramid5 => 7,
+            ElementType::Line3 => 8,
+            ElementType::Triangle6 => 9,
+            ElementType::Quadrangle9 => 10,
+            ElementType::Tetrahedron10 => 11,
+            ElementType::Hexahedron27 => 12,
+            ElementType::Prism18 => 13,
+            ElementType::Pyramid14 => 14,
+            ElementType::Point => 15,
+            ElementType::Quadrangle8 => 16,
+            ElementType::Hexahedron20 => 17,
+            ElementType::Prism15 => 18,
+            ElementType::Pyramid13 => 19,
+            ElementType::Triangle9 => 20,
+            ElementType::Triangle10 => 21,
+            ElementType::Triangle12 => 22,
+            ElementType::Triangle15 => 23,
+            ElementType::Triangle15I => 24,
+            ElementType::Triangle21 => 25,
+            ElementType::Line4 => 26,
+            ElementType::Line5 => 27,
+            ElementType::Line6 => 28,
+            ElementType::Tetrahedron20 => 29,
+            ElementType::Tetrahedron35 => 30,
+            ElementType::Tetrahedron56 => 31,
+            ElementType::Tetrahedron22 => 32,
+            ElementType::Tetrahedron28 => 33,
+            ElementType::Polygon => 34,
+            ElementType::Polyhedron => 35,
+            ElementType::Quadrangle16 => 36,
+            ElementType::Quadrangle25 => 37,
+            ElementType::Quadrangle36 => 38,
+            ElementType::Quadrangle12 => 39,
+            ElementType::Quadrangle16I => 40,
+            ElementType::Quadrangle20 => 41,
+            ElementType::Triangle28 => 42,
+            ElementType::Triangle36 => 43,
+            ElementType::Triangle45 => 44,
+            ElementType::Triangle55 => 45,
+            ElementType::Triangle66 => 46,
+            ElementType::Quadrangle49 => 47,
+            ElementType::Quadrangle64 => 48,
+            ElementType::Quadrangle81 => 49,
+            ElementType::Quadrangle100 => 50,
+            ElementType::Quadrangle121 => 51,
+            ElementType::Triangle18 => 52,
+            ElementType::Triangle21I => 53,
+            ElementType::Triangle24 => 54,
+            ElementType::Triangle27 => 55,
+            ElementType::Triangle30 => 56,
+            ElementType::Quadrangle24 => 57,
+            ElementType::Quadrangle28 => 58,
+            ElementType::Quadrangle32 => 59,
+            ElementType::Quadrangle36I => 60,
+            ElementType::Quadrangle40 => 61,
+            ElementType::Line7 => 62,
+            ElementType::Line8 => 63,
+            ElementType::Line9 => 64,
+            ElementType::Line10 => 65,
+            ElementType::Line11 => 66,
+            ElementType::LineB => 67,
+            ElementType::TriangleB => 68,
+            ElementType::PolygonB => 69,
+            ElementType::LineC => 70,
+            ElementType::Tetrahedron84 => 71,
+            ElementType::Tetrahedron120 => 72,
+            ElementType::Tetrahedron165 => 73,
+            ElementType::Tetrahedron220 => 74,
+            ElementType::Tetrahedron286 => 75,
+            ElementType::Tetrahedron34 => 79,
+            ElementType::Tetrahedron40 => 80,
+            ElementType::Tetrahedron46 => 81,
+            ElementType::Tetrahedron52 => 82,
+            ElementType::Tetrahedron58 => 83,
+            ElementType::Line1 => 84,
+            ElementType::Triangle1 => 85,
+            ElementType::Quadrangle1 => 86,
+            ElementType::Tetrahedron1 => 87,
+            ElementType::Hexahedron1 => 88,
+            ElementType::Prism1 => 89,
+            ElementType::Prism40 => 90,
+            ElementType::Prism75 => 91,
+            ElementType::Hexahedron64 => 92,
+            ElementType::Hexahedron125 => 93,
+            ElementType::Hexahedron216 => 94,
+            ElementType::Hexahedron343 => 95,
+            ElementType::Hexahedron512 => 96,
+            ElementType::Hexahedron729 => 97,
+            ElementType::Hexahedron1000 => 98,
+            ElementType::Hexahedron32 => 99,
+            ElementType::Hexahedron44 => 100,
+            ElementType::Hexahedron56 => 101,
+            ElementType::Hexahedron68 => 102,
+            ElementType::Hexahedron80 => 103,
+            ElementType::Hexahedron92 => 104,
+            ElementType::Hexahedron104 => 105,
+            ElementType::Prism126 => 106,
+            ElementType::Prism196 => 107,
+            ElementType::Prism288 => 108,
+            ElementType::Prism405 => 109,
+            ElementType::Prism550 => 110,
+            ElementType::Prism24 => 111,
+            ElementType::Prism33 => 112,
+            ElementType::Prism42 => 113,
+            ElementType::Prism51 => 114,
+            ElementType::Prism60 => 115,
+            ElementType::Prism69 => 116,
+            ElementType::Prism78 => 117,
+            ElementType::Pyramid30 => 118,
+            ElementType::Pyramid55 => 119,
+            ElementType::Pyramid91 => 120,
+            ElementType::Pyramid140 => 121,
+            ElementType::Pyramid204 => 122,
+            ElementType::Pyramid285 => 123,
+            ElementType::Pyramid385 => 124,
+            ElementType::Pyramid21 => 125,
+            ElementType::Pyramid29 => 126,
+            ElementType::Pyramid37 => 127,
+            ElementType::Pyramid45 => 128,
+            ElementType::Pyramid53 => 129,
+            ElementType::Pyramid61 => 130,
+            ElementType::Pyramid69 => 131,
+            ElementType::Pyramid1 => 132,
+            ElementType::PointSub => 133,
+            ElementType::LineSub => 134,
+            ElementType::TriangleSub => 135,
+            ElementType::TetrahedronSub => 136,
+            ElementType::Tetrahedron16 => 137,
+            ElementType::TriangleMini => 138,
+            ElementType::TetrahedronMini => 139,
+            ElementType::TriHedron4 => 140,
+        }
+    }
+
     /// Get the fixed node count for this element type, or None if variable
     pub fn fixed_node_count(&self) -> Option<usize> {
         match self {
@@ -484,3 +631,251 @@ impl ElementType {
         }
     }
 }
+
+/// Reference shape of an element type, see [`ElementType::family`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub enum ElementFamily {
+    Point,
+    Line,
+    Triangle,
+    Quadrangle,
+    Tetrahedron,
+    Hexahedron,
+    Prism,
+    Pyramid,
+    /// Polygons, polyhedra and trihedra
+    Other,
+}
+
+impl ElementType {
+    /// Reference shape of the element
+    ///
+    /// Bézier, sub-element and "mini" variants belong to the family of their
+    /// shape.
+    pub fn family(&self) -> ElementFamily {
+        self.classification().0
+    }
+
+    /// Topological dimension: 0 for points, 1 for lines, 2 for surface
+    /// elements and 3 for volume elements
+    pub fn dimension(&self) -> i32 {
+        match self.family() {
+            ElementFamily::Point => 0,
+            ElementFamily::Line => 1,
+            ElementFamily::Triangle | ElementFamily::Quadrangle => 2,
+            ElementFamily::Tetrahedron
+            | ElementFamily::Hexahedron
+            | ElementFamily::Prism
+            | ElementFamily::Pyramid => 3,
+            ElementFamily::Other => match self {
+                ElementType::Polygon | ElementType::PolygonB => 2,
+                _ => 3,
+            },
+        }
+    }
+
+    /// Polynomial order of the shape functions, 0 for single-node elements
+    ///
+    /// Returns `None` for element types whose node count is not fixed.
+    pub fn polynomial_order(&self) -> Option<u32> {
+        self.classification().1
+    }
+
+    /// Whether the element is incomplete, i.e. lacks the face or volume nodes
+    /// of the complete Lagrange element of the same order (e.g. `Quadrangle8`)
+    pub fn is_serendipity(&self) -> bool {
+        self.classification().2
+    }
+
+    /// Family, polynomial order and serendipity of the element type
+    fn classification(&self) -> (ElementFamily, Option<u32>, bool) {
+        use ElementFamily as F;
+        use ElementType::*;
+        match self {
+            Point => (F::Point, Some(0), false),
+            PointSub => (F::Point, None, false),
+
+            Line1 => (F::Line, Some(0), false),
+            Line2 => (F::Line, Some(1), false),
+            Line3 => (F::Line, Some(2), false),
+            Line4 => (F::Line, Some(3), false),
+            Line5 => (F::Line, Some(4), false),
+            Line6 => (F::Line, Some(5), false),
+            Line7 => (F::Line, Some(6), false),
+            Line8 => (F::Line, Some(7), false),
+            Line9 => (F::Line, Some(8), false),
+            Line10 => (F::Line, Some(9), false),
+            Line11 => (F::Line, Some(10), false),
+            LineB | LineC | LineSub => (F::Line, None, false),
+
+            Triangle1 => (F::Triangle, Some(0), false),
+            Triangle3 => (F::Triangle, Some(1), false),
+            Triangle6 => (F::Triangle, Some(2), false),
+            Triangle10 => (F::Triangle, Some(3), false),
+            Triangle15 => (F::Triangle, Some(4), false),
+            Triangle21 => (F::Triangle, Some(5), false),
+            Triangle28 => (F::Triangle, Some(6), false),
+            Triangle36 => (F::Triangle, Some(7), false),
+            Triangle45 => (F::Triangle, Some(8), false),
+            Triangle55 => (F::Triangle, Some(9), false),
+            Triangle66 => (F::Triangle, Some(10), false),
+            Triangle9 => (F::Triangle, Some(3), true),
+            Triangle12 => (F::Triangle, Some(4), true),
+            Triangle15I => (F::Triangle, Some(5), true),
+            Triangle18 => (F::Triangle, Some(6), true),
+            Triangle21I => (F::Triangle, Some(7), true),
+            Triangle24 => (F::Triangle, Some(8), true),
+            Triangle27 => (F::Triangle, Some(9), true),
+            Triangle30 => (F::Triangle, Some(10), true),
+            TriangleB | TriangleSub | TriangleMini => (F::Triangle, None, false),
+
+            Quadrangle1 => (F::Quadrangle, Some(0), false),
+            Quadrangle4 => (F::Quadrangle, Some(1), false),
+            Quadrangle9 => (F::Quadrangle, Some(2), false),
+            Quadrangle16 => (F::Quadrangle, Some(3), false),
+            Quadrangle25 => (F::Quadrangle, Some(4), false),
+            Quadrangle36 => (F::Quadrangle, Some(5), false),
+            Quadrangle49 => (F::Quadrangle, Some(6), false),
+            Quadrangle64 => (F::Quadrangle, Some(7), false),
+            Quadrangle81 => (F::Quadrangle, Some(8), false),
+            Quadrangle100 => (F::Quadrangle, Some(9), false),
+            Quadrangle121 => (F::Quadrangle, Some(10), false),
+            Quadrangle8 => (F::Quadrangle, Some(2), true),
+            Quadrangle12 => (F::Quadrangle, Some(3), true),
+            Quadrangle16I => (F::Quadrangle, Some(4), true),
+            Quadrangle20 => (F::Quadrangle, Some(5), true),
+            Quadrangle24 => (F::Quadrangle, Some(6), true),
+            Quadrangle28 => (F::Quadrangle, Some(7), true),
+            Quadrangle32 => (F::Quadrangle, Some(8), true),
+            Quadrangle36I => (F::Quadrangle, Some(9), true),
+            Quadrangle40 => (F::Quadrangle, Some(10), true),
+
+            Tetrahedron1 => (F::Tetrahedron, Some(0), false),
+            Tetrahedron4 => (F::Tetrahedron, Some(1), false),
+            Tetrahedron10 => (F::Tetrahedron, Some(2), false),
+            Tetrahedron20 => (F::Tetrahedron, Some(3), false),
+            Tetrahedron35 => (F::Tetrahedron, Some(4), false),
+            Tetrahedron56 => (F::Tetrahedron, Some(5), false),
+            Tetrahedron84 => (F::Tetrahedron, Some(6), false),
+            Tetrahedron120 => (F::Tetrahedron, Some(7), false),
+            Tetrahedron165 => (F::Tetrahedron, Some(8), false),
+            Tetrahedron220 => (F::Tetrahedron, Some(9), false),
+            Tetrahedron286 => (F::Tetrahedron, Some(10), false),
+            Tetrahedron16 => (F::Tetrahedron, Some(3), true),
+            Tetrahedron22 => (F::Tetrahedron, Some(4), true),
+            Tetrahedron28 => (F::Tetrahedron, Some(5), true),
+            Tetrahedron34 => (F::Tetrahedron, Some(6), true),
+            Tetrahedron40 => (F::Tetrahedron, Some(7), true),
+            Tetrahedron46 => (F::Tetrahedron, Some(8), true),
+            Tetrahedron52 => (F::Tetrahedron, Some(9), true),
+            Tetrahedron58 => (F::Tetrahedron, Some(10), true),
+            TetrahedronSub | TetrahedronMini => (F::Tetrahedron, None, false),
+
+            Hexahedron1 => (F::Hexahedron, Some(0), false),
+            Hexahedron8 => (F::Hexahedron, Some(1), false),
+            Hexahedron27 => (F::Hexahedron, Some(2), false),
+            Hexahedron64 => (F::Hexahedron, Some(3), false),
+            Hexahedron125 => (F::Hexahedron, Some(4), false),
+            Hexahedron216 => (F::Hexahedron, Some(5), false),
+            Hexahedron343 => (F::Hexahedron, Some(6), false),
+            Hexahedron512 => (F::Hexahedron, Some(7), false),
+            Hexahedron729 => (F::Hexahedron, Some(8), false),
+            Hexahedron1000 => (F::Hexahedron, Some(9), false),
+            Hexahedron20 => (F::Hexahedron, Some(2), true),
+            Hexahedron32 => (F::Hexahedron, Some(3), true),
+            Hexahedron44 => (F::Hexahedron, Some(4), true),
+            Hexahedron56 => (F::Hexahedron, Some(5), true),
+            Hexahedron68 => (F::Hexahedron, Some(6), true),
+            Hexahedron80 => (F::Hexahedron, Some(7), true),
+            Hexahedron92 => (F::Hexahedron, Some(8), true),
+            Hexahedron104 => (F::Hexahedron, Some(9), true),
+
+            Prism1 => (F::Prism, Some(0), false),
+            Prism6 => (F::Prism, Some(1), false),
+            Prism18 => (F::Prism, Some(2), false),
+            Prism40 => (F::Prism, Some(3), false),
+            Prism75 => (F::Prism, Some(4), false),
+            Prism126 => (F::Prism, Some(5), false),
+            Prism196 => (F::Prism, Some(6), false),
+            Prism288 => (F::Prism, Some(7), false),
+            Prism405 => (F::Prism, Some(8), false),
+            Prism550 => (F::Prism, Some(9), false),
+            Prism15 => (F::Prism, Some(2), true),
+            Prism24 => (F::Prism, Some(3), true),
+            Prism33 => (F::Prism, Some(4), true),
+            Prism42 => (F::Prism, Some(5), true),
+            Prism51 => (F::Prism, Some(6), true),
+            Prism60 => (F::Prism, Some(7), true),
+            Prism69 => (F::Prism, Some(8), true),
+            Prism78 => (F::Prism, Some(9), true),
+
+            Pyramid1 => (F::Pyramid, Some(0), false),
+            Pyramid5 => (F::Pyramid, Some(1), false),
+            Pyramid14 => (F::Pyramid, Some(2), false),
+            Pyramid30 => (F::Pyramid, Some(3), false),
+            Pyramid55 => (F::Pyramid, Some(4), false),
+            Pyramid91 => (F::Pyramid, Some(5), false),
+            Pyramid140 => (F::Pyramid, Some(6), false),
+            Pyramid204 => (F::Pyramid, Some(7), false),
+            Pyramid285 => (F::Pyramid, Some(8), false),
+            Pyramid385 => (F::Pyramid, Some(9), false),
+            Pyramid13 => (F::Pyramid, Some(2), true),
+            Pyramid21 => (F::Pyramid, Some(3), true),
+            Pyramid29 => (F::Pyramid, Some(4), true),
+            Pyramid37 => (F::Pyramid, Some(5), true),
+            Pyramid45 => (F::Pyramid, Some(6), true),
+            Pyramid53 => (F::Pyramid, Some(7), true),
+            Pyramid61 => (F::Pyramid, Some(8), true),
+            Pyramid69 => (F::Pyramid, Some(9), true),
+
+            Polygon | PolygonB | Polyhedron => (F::Other, None, false),
+            TriHedron4 => (F::Other, Some(1), false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classification_matches_node_counts() {
+        assert_eq!(ElementType::Quadrangle8.family(), ElementFamily::Quadrangle);
+        assert_eq!(ElementType::Quadrangle8.polynomial_order(), Some(2));
+        assert!(ElementType::Quadrangle8.is_serendipity());
+        assert!(!ElementType::Hexahedron27.is_serendipity());
+        assert_eq!(ElementType::Polygon.dimension(), 2);
+        assert_eq!(ElementType::Polygon.polynomial_order(), None);
+
+        // Node counts of complete and serendipity elements follow from the order
+        for id in 1..=140 {
+            let Some(t) = ElementType::from_i32(id) else {
+                continue;
+            };
+            let (Some(nodes), Some(p)) = (t.fixed_node_count(), t.polynomial_order()) else {
+                continue;
+            };
+            let p = p as usize;
+            let expected = match (t.family(), t.is_serendipity()) {
+                (_, _) if p == 0 => 1,
+                (ElementFamily::Point, _) => 1,
+                (ElementFamily::Line, _) => p + 1,
+                (ElementFamily::Triangle, false) => (p + 1) * (p + 2) / 2,
+                (ElementFamily::Triangle, true) => 3 * p,
+                (ElementFamily::Quadrangle, false) => (p + 1) * (p + 1),
+                (ElementFamily::Quadrangle, true) => 4 * p,
+                (ElementFamily::Tetrahedron, false) => (p + 1) * (p + 2) * (p + 3) / 6,
+                (ElementFamily::Hexahedron, false) => (p + 1).pow(3),
+                (ElementFamily::Prism, false) => (p + 1) * (p + 1) * (p + 2) / 2,
+                (ElementFamily::Pyramid, false) => (p + 1) * (p + 2) * (2 * p + 3) / 6,
+                _ => nodes,
+            };
+            assert_eq!(nodes, expected, "{}", t);
+        }
+    }
+}
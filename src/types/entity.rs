@@ -1,4 +1,16 @@
+use super::{ElementBlockRef, EntityTag, NodeBlock, NodeTag, PhysicalTag};
+use crate::geometry::bounds;
+use std::collections::HashMap;
+
+/// Minimum and maximum corners of an axis-aligned box
+type BoundingBox = ([f64; 3], [f64; 3]);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 #[repr(i32)]
 pub enum EntityDimension {
     Point = 0,
@@ -31,54 +43,79 @@ impl std::fmt::Display for EntityDimension {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PointEntity {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub x: f64,
     pub y: f64,
     pub z: f64,
-    pub physical_tags: Vec<i32>,
+    pub physical_tags: Vec<PhysicalTag>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct CurveEntity {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub min_x: f64,
     pub min_y: f64,
     pub min_z: f64,
     pub max_x: f64,
     pub max_y: f64,
     pub max_z: f64,
-    pub physical_tags: Vec<i32>,
-    pub bounding_points: Vec<i32>,  // Sign encodes orientation
+    pub physical_tags: Vec<PhysicalTag>,
+    pub bounding_points: Vec<i32>, // Sign encodes orientation
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct SurfaceEntity {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub min_x: f64,
     pub min_y: f64,
     pub min_z: f64,
     pub max_x: f64,
     pub max_y: f64,
     pub max_z: f64,
-    pub physical_tags: Vec<i32>,
-    pub bounding_curves: Vec<i32>,  // Sign encodes orientation
+    pub physical_tags: Vec<PhysicalTag>,
+    pub bounding_curves: Vec<i32>, // Sign encodes orientation
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct VolumeEntity {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub min_x: f64,
     pub min_y: f64,
     pub min_z: f64,
     pub max_x: f64,
     pub max_y: f64,
     pub max_z: f64,
-    pub physical_tags: Vec<i32>,
-    pub bounding_surfaces: Vec<i32>,  // Sign encodes orientation
+    pub physical_tags: Vec<PhysicalTag>,
+    pub bounding_surfaces: Vec<i32>, // Sign encodes orientation
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Entities {
     pub points: Vec<PointEntity>,
     pub curves: Vec<CurveEntity>,
@@ -90,4 +127,220 @@ impl Entities {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Bounding box `(min, max)` of the entity `(dim, tag)`, as given in the
+    /// file; points have an empty box at their coordinates
+    pub fn bounding_box(&self, dim: i32, tag: EntityTag) -> Option<BoundingBox> {
+        match dim {
+            0 => self
+                .points
+                .iter()
+                .find(|p| p.tag == tag)
+                .map(|p| ([p.x, p.y, p.z], [p.x, p.y, p.z])),
+            1 => self
+                .curves
+                .iter()
+                .find(|c| c.tag == tag)
+                .map(|c| ([c.min_x, c.min_y, c.min_z], [c.max_x, c.max_y, c.max_z])),
+            2 => self
+                .surfaces
+                .iter()
+                .find(|s| s.tag == tag)
+                .map(|s| ([s.min_x, s.min_y, s.min_z], [s.max_x, s.max_y, s.max_z])),
+            3 => self
+                .volumes
+                .iter()
+                .find(|v| v.tag == tag)
+                .map(|v| ([v.min_x, v.min_y, v.min_z], [v.max_x, v.max_y, v.max_z])),
+            _ => None,
+        }
+    }
+
+    /// Implicit entities for a mesh without an `$Entities` section
+    ///
+    /// One entity is created per `(dim, tag)` referenced by a node or element
+    /// block. Synthesized entities have no physical tags and no bounding
+    /// entities; their bounding boxes enclose the nodes of their node blocks and
+    /// elements, and point entities take the coordinates of their first node.
+    pub fn synthesize<'a>(
+        node_blocks: &[NodeBlock],
+        element_blocks: impl IntoIterator<Item = ElementBlockRef<'a>>,
+    ) -> Self {
+        let coordinates: HashMap<NodeTag, [f64; 3]> = node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter())
+            .map(|n| (n.tag, [n.x, n.y, n.z]))
+            .collect();
+
+        // (dim, tag) in order of first appearance, with the points of each entity
+        let mut order = Vec::new();
+        let mut points: HashMap<(i32, EntityTag), Vec<[f64; 3]>> = HashMap::new();
+        let mut add = |key: (i32, EntityTag), new: &mut dyn Iterator<Item = [f64; 3]>| {
+            points
+                .entry(key)
+                .or_insert_with(|| {
+                    order.push(key);
+                    Vec::new()
+                })
+                .extend(new);
+        };
+
+        for block in node_blocks {
+            let key = (block.entity_dim(), block.entity_tag);
+            add(key, &mut block.nodes.iter().map(|n| [n.x, n.y, n.z]));
+        }
+        for block in element_blocks {
+            let key = (block.entity_dim(), block.entity_tag());
+            add(
+                key,
+                &mut block
+                    .elements()
+                    .flat_map(|e| e.nodes())
+                    .filter_map(|n| coordinates.get(n).copied()),
+            );
+        }
+
+        let mut entities = Entities::new();
+        for key in order {
+            let (min, max) = bounds(points[&key].iter().copied()).unwrap_or(([0.0; 3], [0.0; 3]));
+            let (dim, tag) = key;
+            match dim {
+                0 => entities.points.push(PointEntity {
+                    tag,
+                    x: min[0],
+                    y: min[1],
+                    z: min[2],
+                    physical_tags: Vec::new(),
+                }),
+                1 => entities.curves.push(CurveEntity {
+                    tag,
+                    min_x: min[0],
+                    min_y: min[1],
+                    min_z: min[2],
+                    max_x: max[0],
+                    max_y: max[1],
+                    max_z: max[2],
+                    physical_tags: Vec::new(),
+                    bounding_points: Vec::new(),
+                }),
+                2 => entities.surfaces.push(SurfaceEntity {
+                    tag,
+                    min_x: min[0],
+                    min_y: min[1],
+                    min_z: min[2],
+                    max_x: max[0],
+                    max_y: max[1],
+                    max_z: max[2],
+                    physical_tags: Vec::new(),
+                    bounding_curves: Vec::new(),
+                }),
+                3 => entities.volumes.push(VolumeEntity {
+                    tag,
+                    min_x: min[0],
+                    min_y: min[1],
+                    min_z: min[2],
+                    max_x: max[0],
+                    max_y: max[1],
+                    max_z: max[2],
+                    physical_tags: Vec::new(),
+                    bounding_surfaces: Vec::new(),
+                }),
+                _ => {}
+            }
+        }
+        entities
+    }
+
+    /// Recompute point coordinates and bounding boxes from the current nodes
+    ///
+    /// The box of an entity encloses the nodes of its own node blocks and the
+    /// boxes of its bounding entities, since Gmsh stores boundary nodes on the
+    /// lower-dimensional entities. Entities without nodes or bounding entities
+    /// with known boxes are left unchanged.
+    pub fn recompute_bounds(&mut self, node_blocks: &[NodeBlock]) {
+        let mut own: HashMap<(i32, EntityTag), Vec<[f64; 3]>> = HashMap::new();
+        for block in node_blocks {
+            own.entry((block.entity_dim(), block.entity_tag))
+                .or_default()
+                .extend(block.nodes.iter().map(|n| [n.x, n.y, n.z]));
+        }
+
+        // Boxes of the entities of the previous dimension, by tag
+        let mut lower: HashMap<EntityTag, BoundingBox> = HashMap::new();
+        let mut boxed =
+            |dim: i32, tag: EntityTag, boundary: &[i32], lower: &HashMap<EntityTag, _>| {
+                let corners = boundary
+                    .iter()
+                    .filter_map(|b| lower.get(&EntityTag(b.abs())))
+                    .flat_map(|&(min, max): &BoundingBox| [min, max]);
+                let nodes = own.remove(&(dim, tag)).unwrap_or_default();
+                bounds(nodes.into_iter().chain(corners))
+            };
+
+        let mut current = HashMap::new();
+        for p in &mut self.points {
+            if let Some((min, _)) = boxed(0, p.tag, &[], &lower) {
+                [p.x, p.y, p.z] = min;
+            }
+            current.insert(p.tag, ([p.x, p.y, p.z], [p.x, p.y, p.z]));
+        }
+
+        lower = std::mem::take(&mut current);
+        for c in &mut self.curves {
+            if let Some((min, max)) = boxed(1, c.tag, &c.bounding_points, &lower) {
+                [c.min_x, c.min_y, c.min_z] = min;
+                [c.max_x, c.max_y, c.max_z] = max;
+            }
+            current.insert(
+                c.tag,
+                ([c.min_x, c.min_y, c.min_z], [c.max_x, c.max_y, c.max_z]),
+            );
+        }
+
+        lower = std::mem::take(&mut current);
+        for s in &mut self.surfaces {
+            if let Some((min, max)) = boxed(2, s.tag, &s.bounding_curves, &lower) {
+                [s.min_x, s.min_y, s.min_z] = min;
+                [s.max_x, s.max_y, s.max_z] = max;
+            }
+            current.insert(
+                s.tag,
+                ([s.min_x, s.min_y, s.min_z], [s.max_x, s.max_y, s.max_z]),
+            );
+        }
+
+        lower = std::mem::take(&mut current);
+        for v in &mut self.volumes {
+            if let Some((min, max)) = boxed(3, v.tag, &v.bounding_surfaces, &lower) {
+                [v.min_x, v.min_y, v.min_z] = min;
+                [v.max_x, v.max_y, v.max_z] = max;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_recompute_bounds_after_scaling() {
+        // A curve from point 1 to point 2 with one interior node
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Entities\n2 1 0 0\n1 0 0 0 0\n2 1 1 0 0\n1 0 0 0 1 1 0 0 2 1 -2\n$EndEntities\n\
+            $Nodes\n3 3 1 3\n0 1 0 1\n1\n0 0 0\n0 2 0 1\n2\n1 1 0\n1 1 0 1\n3\n0.5 0.5 0\n\
+            $EndNodes\n";
+        let mut mesh = crate::parse_msh(content).unwrap();
+        for node in mesh.node_blocks.iter_mut().flat_map(|b| b.nodes.iter_mut()) {
+            node.x *= 2.0;
+            node.y *= 3.0;
+        }
+
+        let mut entities = mesh.entities.take().unwrap();
+        entities.recompute_bounds(&mesh.node_blocks);
+
+        let curve = &entities.curves[0];
+        assert_eq!([curve.min_x, curve.min_y], [0.0, 0.0]);
+        assert_eq!([curve.max_x, curve.max_y], [2.0, 3.0]);
+        let point = &entities.points[1];
+        assert_eq!([point.x, point.y], [2.0, 3.0]);
+    }
 }
@@ -0,0 +1,176 @@
+//! Boundary relations between the entities of a model
+
+use super::{Entities, EntityTag, Mesh, Orientation, PartitionedEntities};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Entities of a model with their bounding entities, see
+/// [`Mesh::entity_graph`]
+///
+/// Entities are identified by `(dimension, tag)`. Only the bounding lists of
+/// `$Entities` are known: points and curves embedded in a surface, or surfaces
+/// embedded in a volume, are not part of its boundary.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityGraph {
+    /// Signed bounding tags of every entity, as in the file
+    boundaries: BTreeMap<(i32, EntityTag), Vec<i32>>,
+}
+
+impl EntityGraph {
+    pub fn new(entities: &Entities) -> Self {
+        let mut graph = Self::default();
+        for p in &entities.points {
+            graph.boundaries.insert((0, p.tag), Vec::new());
+        }
+        for c in &entities.curves {
+            graph
+                .boundaries
+                .insert((1, c.tag), c.bounding_points.clone());
+        }
+        for s in &entities.surfaces {
+            graph
+                .boundaries
+                .insert((2, s.tag), s.bounding_curves.clone());
+        }
+        for v in &entities.volumes {
+            graph
+                .boundaries
+                .insert((3, v.tag), v.bounding_surfaces.clone());
+        }
+        graph
+    }
+
+    /// The graph of the entities of a partitioned mesh
+    pub fn from_partitioned(entities: &PartitionedEntities) -> Self {
+        let mut graph = Self::default();
+        for p in &entities.points {
+            graph.boundaries.insert((0, p.tag), Vec::new());
+        }
+        for c in &entities.curves {
+            graph
+                .boundaries
+                .insert((1, c.tag), c.bounding_points.clone());
+        }
+        for s in &entities.surfaces {
+            graph
+                .boundaries
+                .insert((2, s.tag), s.bounding_curves.clone());
+        }
+        for v in &entities.volumes {
+            graph
+                .boundaries
+                .insert((3, v.tag), v.bounding_surfaces.clone());
+        }
+        graph
+    }
+
+    pub fn contains(&self, dim: i32, tag: EntityTag) -> bool {
+        self.boundaries.contains_key(&(dim, tag))
+    }
+
+    /// All entities, by dimension then tag
+    pub fn entities(&self) -> impl Iterator<Item = (i32, EntityTag)> + '_ {
+        self.boundaries.keys().copied()
+    }
+
+    /// Entities bounding `(dim, tag)`, of dimension `dim - 1`, without their
+    /// orientation
+    pub fn boundary(&self, dim: i32, tag: EntityTag) -> Vec<(i32, EntityTag)> {
+        self.boundaries
+            .get(&(dim, tag))
+            .into_iter()
+            .flatten()
+            .map(|b| (dim - 1, EntityTag(b.abs())))
+            .collect()
+    }
+
+    /// Entities bounding `(dim, tag)`, of dimension `dim - 1`, in file order
+    /// with the orientation given by the sign of their tag
+    ///
+    /// For a surface, a reversed curve is run from its end point to its start
+    /// point when going around the surface; for a volume, a reversed surface
+    /// has its normal pointing into the volume rather than out of it. A curve
+    /// lists its start point as forward and its end point as reversed. Empty
+    /// for points and unknown entities.
+    pub fn oriented_boundary(&self, dim: i32, tag: EntityTag) -> Vec<(EntityTag, Orientation)> {
+        self.boundaries
+            .get(&(dim, tag))
+            .into_iter()
+            .flatten()
+            .map(|&b| (EntityTag(b.abs()), Orientation::of(b)))
+            .collect()
+    }
+
+    /// `(dim, tag)` with its boundary, the boundary of its boundary, and so on
+    /// down to points
+    pub fn closure(&self, dim: i32, tag: EntityTag) -> BTreeSet<(i32, EntityTag)> {
+        let mut closure = BTreeSet::new();
+        let mut pending = vec![(dim, tag)];
+        while let Some(entity) = pending.pop() {
+            if closure.insert(entity) {
+                pending.extend(self.boundary(entity.0, entity.1));
+            }
+        }
+        closure
+    }
+}
+
+impl Mesh {
+    /// Boundary relations of the entities of the mesh, or `None` without an
+    /// `$Entities` section
+    ///
+    /// Partitioned meshes give the graph of their partitioned entities, which
+    /// the node and element blocks refer to.
+    pub fn entity_graph(&self) -> Option<EntityGraph> {
+        match (&self.partitioned_entities, &self.entities) {
+            (Some(partitioned), _) => Some(EntityGraph::from_partitioned(partitioned)),
+            (None, Some(entities)) => Some(EntityGraph::new(entities)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit square bounded by the curves 1 to 4, counterclockwise from the
+    /// bottom; the top and left curves run against the loop
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Entities\n4 4 1 0\n1 0 0 0 0\n2 1 0 0 0\n3 1 1 0 0\n4 0 1 0 0\n\
+        1 0 0 0 1 0 0 0 2 1 -2\n2 1 0 0 1 1 0 0 2 2 -3\n3 0 1 0 1 1 0 0 2 4 -3\n\
+        4 0 0 0 0 1 0 0 2 1 -4\n1 0 0 0 1 1 0 0 4 1 2 -3 -4\n$EndEntities\n";
+
+    #[test]
+    fn test_closure_of_surface() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let graph = mesh.entity_graph().unwrap();
+        let closure = graph.closure(2, EntityTag(1));
+        let count = |d: i32| closure.iter().filter(|(dim, _)| *dim == d).count();
+        assert_eq!((count(2), count(1), count(0)), (1, 4, 4));
+        assert!(closure.contains(&(2, EntityTag(1))));
+        assert!(graph.boundary(0, EntityTag(1)).is_empty());
+    }
+
+    #[test]
+    fn test_oriented_boundary_of_surface() {
+        let mesh = crate::parse_msh(SQUARE).unwrap();
+        let graph = mesh.entity_graph().unwrap();
+        assert_eq!(
+            graph.oriented_boundary(2, EntityTag(1)),
+            [
+                (EntityTag(1), Orientation::Forward),
+                (EntityTag(2), Orientation::Forward),
+                (EntityTag(3), Orientation::Reversed),
+                (EntityTag(4), Orientation::Reversed),
+            ]
+        );
+        assert_eq!(
+            graph.oriented_boundary(1, EntityTag(3)),
+            [
+                (EntityTag(4), Orientation::Forward),
+                (EntityTag(3), Orientation::Reversed),
+            ]
+        );
+        assert!(graph.oriented_boundary(2, EntityTag(9)).is_empty());
+    }
+}
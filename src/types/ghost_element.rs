@@ -1,14 +1,144 @@
 //! Ghost elements section types
 //!
 //! Defines ghost elements for parallel processing.
+//! [`Mesh::compute_ghost_elements`] builds them for a partitioned mesh.
+
+use crate::error::{ParseError, Result};
+use crate::types::{ElementRef, ElementTag, EntityTag, Mesh, NodeTag};
+use std::collections::{BTreeSet, HashMap};
 
 /// Ghost element information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct GhostElement {
     /// Element tag
-    pub element_tag: usize,
+    pub element_tag: ElementTag,
     /// Partition tag
     pub partition_tag: i32,
     /// Ghost partition tags
     pub ghost_partition_tags: Vec<i32>,
 }
+
+impl GhostElement {
+    pub fn new(
+        element_tag: ElementTag,
+        partition_tag: i32,
+        ghost_partition_tags: Vec<i32>,
+    ) -> Self {
+        Self {
+            element_tag,
+            partition_tag,
+            ghost_partition_tags,
+        }
+    }
+}
+
+impl Mesh {
+    /// Ghost elements of a partitioned mesh, as Gmsh computes them
+    ///
+    /// Only elements of the highest dimension take part. An element owned by
+    /// partition `p` is a ghost of every other partition owning an element it
+    /// shares a node with. The partition owning an element is the first
+    /// partition of the partitioned entity of its block. Ghost elements are
+    /// sorted by partition, then element tag.
+    ///
+    /// # Errors
+    /// Returns an error if the mesh has no `$PartitionedEntities` section.
+    pub fn compute_ghost_elements(&self) -> Result<Vec<GhostElement>> {
+        let partitioned = self
+            .partitioned_entities
+            .as_ref()
+            .ok_or_else(|| ParseError::MissingSection("PartitionedEntities".to_string()))?;
+        let mut owner: HashMap<(i32, EntityTag), i32> = HashMap::new();
+        let mut add = |dim: i32, tag: EntityTag, partitions: &[i32]| {
+            if let Some(&partition) = partitions.first() {
+                owner.insert((dim, tag), partition);
+            }
+        };
+        for e in &partitioned.points {
+            add(0, e.tag, &e.partition_tags);
+        }
+        for e in &partitioned.curves {
+            add(1, e.tag, &e.partition_tags);
+        }
+        for e in &partitioned.surfaces {
+            add(2, e.tag, &e.partition_tags);
+        }
+        for e in &partitioned.volumes {
+            add(3, e.tag, &e.partition_tags);
+        }
+
+        let dim = self.element_block_refs().map(|b| b.entity_dim()).max();
+        let elements: Vec<(i32, ElementRef<'_>)> = self
+            .element_block_refs()
+            .filter(|b| Some(b.entity_dim()) == dim)
+            .filter_map(|b| {
+                let &partition = owner.get(&(b.entity_dim(), b.entity_tag()))?;
+                Some(b.elements().map(move |e| (partition, e)))
+            })
+            .flatten()
+            .collect();
+
+        // Partitions owning an element around each node
+        let mut node_partitions: HashMap<NodeTag, BTreeSet<i32>> = HashMap::new();
+        for &(partition, element) in &elements {
+            for &node in element.nodes() {
+                node_partitions.entry(node).or_default().insert(partition);
+            }
+        }
+
+        let mut ghosts: Vec<GhostElement> = elements
+            .into_iter()
+            .filter_map(|(partition, element)| {
+                let others: BTreeSet<i32> = element
+                    .nodes()
+                    .iter()
+                    .flat_map(|n| &node_partitions[n])
+                    .copied()
+                    .filter(|&p| p != partition)
+                    .collect();
+                (!others.is_empty()).then(|| {
+                    GhostElement::new(element.tag(), partition, others.into_iter().collect())
+                })
+            })
+            .collect();
+        ghosts.sort_by_key(|g| (g.partition_tag, g.element_tag));
+        Ok(ghosts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::PARTITIONED;
+    use crate::parse_msh;
+    use crate::writer::write_msh;
+
+    #[test]
+    fn test_compute_ghost_elements() {
+        let mesh = parse_msh(PARTITIONED).unwrap();
+        let computed: Vec<_> = mesh
+            .compute_ghost_elements()
+            .unwrap()
+            .into_iter()
+            .map(|g| (g.partition_tag, g.element_tag.0, g.ghost_partition_tags))
+            .collect();
+        assert_eq!(computed, [(1, 1, vec![2]), (2, 2, vec![1])]);
+    }
+
+    #[test]
+    fn test_write_computed_ghost_elements() {
+        let mut mesh = parse_msh(PARTITIONED).unwrap();
+        assert!(mesh.ghost_elements.is_empty());
+        mesh.ghost_elements = mesh.compute_ghost_elements().unwrap();
+
+        let reparsed = parse_msh(write_msh(&mesh).unwrap()).unwrap();
+        assert_eq!(reparsed.ghost_elements.len(), 2);
+        let first = &reparsed.ghost_elements[0];
+        assert_eq!(first.partition_tag, 1);
+        assert_eq!(first.ghost_partition_tags, vec![2]);
+    }
+}
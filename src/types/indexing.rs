@@ -0,0 +1,44 @@
+//! Base of the indices given to solvers
+
+/// First value of the indices referring to nodes and elements
+///
+/// Connectivity arrays ([`MeshArrays`](crate::export::MeshArrays),
+/// [`Csr`](crate::topology::Csr),
+/// [`ElementBlockCompact::indexed_connectivity`](super::ElementBlockCompact::indexed_connectivity))
+/// are built in the base the caller asks for, so that Fortran codes get
+/// 1-based indices without shifting every array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Indexing {
+    /// C-style indices starting at 0
+    #[default]
+    ZeroBased,
+    /// Fortran-style indices starting at 1
+    OneBased,
+}
+
+impl Indexing {
+    /// Index of the item at position `i`
+    pub fn index(self, i: usize) -> usize {
+        match self {
+            Indexing::ZeroBased => i,
+            Indexing::OneBased => i + 1,
+        }
+    }
+
+    /// Position of the item with index `index`, or `None` if it is out of range
+    pub fn position(self, index: usize) -> Option<usize> {
+        match self {
+            Indexing::ZeroBased => Some(index),
+            Indexing::OneBased => index.checked_sub(1),
+        }
+    }
+
+    /// Shift `indices` from this base to `to`
+    pub(crate) fn rebase(self, indices: &mut [usize], to: Indexing) {
+        match (self, to) {
+            (Indexing::ZeroBased, Indexing::OneBased) => indices.iter_mut().for_each(|i| *i += 1),
+            (Indexing::OneBased, Indexing::ZeroBased) => indices.iter_mut().for_each(|i| *i -= 1),
+            _ => {}
+        }
+    }
+}
@@ -1,9 +1,16 @@
 //! Interpolation scheme section types
 //!
-//! Defines interpolation schemes for post-processing views.
+//! Defines interpolation schemes for post-processing views. Schemes built with
+//! [`InterpolationScheme::new`] and [`InterpolationScheme::with_topology`] are
+//! written with the rest of the mesh.
 
 /// Element topology types for interpolation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 #[repr(i32)]
 pub enum ElementTopology {
     Points = 1,
@@ -44,6 +51,11 @@ impl ElementTopology {
 
 /// Interpolation matrix
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct InterpolationMatrix {
     pub num_rows: usize,
     pub num_columns: usize,
@@ -52,6 +64,11 @@ pub struct InterpolationMatrix {
 
 /// Element topology interpolation
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct ElementTopologyInterpolation {
     /// Element topology type
     pub element_topology: ElementTopology,
@@ -61,9 +78,102 @@ pub struct ElementTopologyInterpolation {
 
 /// Complete interpolation scheme
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct InterpolationScheme {
     /// Name of the interpolation scheme
     pub name: String,
     /// Interpolations for different element topologies
     pub topologies: Vec<ElementTopologyInterpolation>,
 }
+
+impl InterpolationMatrix {
+    /// A `num_rows` x `num_columns` matrix from its values, row by row, or
+    /// `None` if there are not `num_rows * num_columns` values
+    pub fn new(num_rows: usize, num_columns: usize, values: Vec<f64>) -> Option<Self> {
+        (num_rows.checked_mul(num_columns)? == values.len()).then_some(Self {
+            num_rows,
+            num_columns,
+            values,
+        })
+    }
+
+    /// The value at `row`, `column`
+    pub fn get(&self, row: usize, column: usize) -> Option<f64> {
+        if row >= self.num_rows || column >= self.num_columns {
+            return None;
+        }
+        self.values.get(row * self.num_columns + column).copied()
+    }
+}
+
+impl InterpolationScheme {
+    /// A scheme without topologies
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            topologies: Vec::new(),
+        }
+    }
+
+    /// Add the matrices used for elements of `topology`
+    ///
+    /// Gmsh expects the coefficient and monomial exponent matrices of the
+    /// values, followed by those of the geometry if it is interpolated
+    /// differently.
+    pub fn with_topology(
+        mut self,
+        element_topology: ElementTopology,
+        matrices: Vec<InterpolationMatrix>,
+    ) -> Self {
+        self.topologies.push(ElementTopologyInterpolation {
+            element_topology,
+            matrices,
+        });
+        self
+    }
+
+    /// The matrices used for elements of `topology`, if any
+    pub fn matrices(&self, topology: ElementTopology) -> Option<&[InterpolationMatrix]> {
+        self.topologies
+            .iter()
+            .find(|t| t.element_topology == topology)
+            .map(|t| t.matrices.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+    use crate::types::{Mesh, MeshFormat};
+    use crate::writer::write_msh;
+
+    #[test]
+    fn test_write_interpolation_scheme() {
+        // Linear interpolation on the reference line: 1 - u, u
+        let coefficients = InterpolationMatrix::new(2, 2, vec![1.0, -1.0, 0.0, 1.0]).unwrap();
+        let exponents = InterpolationMatrix::new(2, 1, vec![0.0, 1.0]).unwrap();
+        assert!(InterpolationMatrix::new(2, 2, vec![1.0]).is_none());
+        assert_eq!(coefficients.get(0, 1), Some(-1.0));
+        assert_eq!(coefficients.get(2, 0), None);
+
+        let mut mesh = Mesh::new(MeshFormat::default());
+        mesh.interpolation_schemes.push(
+            InterpolationScheme::new("my linear lines")
+                .with_topology(ElementTopology::Lines, vec![coefficients, exponents]),
+        );
+        let written = write_msh(&mesh).unwrap();
+        assert!(written.contains("$InterpolationScheme\n\"my linear lines\"\n1\n2\n2\n"));
+
+        let reparsed = parse_msh(&written).unwrap();
+        let scheme = &reparsed.interpolation_schemes[0];
+        assert_eq!(scheme.name, "my linear lines");
+        let matrices = scheme.matrices(ElementTopology::Lines).unwrap();
+        assert_eq!(matrices[0].values, [1.0, -1.0, 0.0, 1.0]);
+        assert!(scheme.matrices(ElementTopology::Triangles).is_none());
+    }
+}
@@ -0,0 +1,162 @@
+//! Lookup of nodes and elements by tag
+
+use super::element::{in_file_order, Stored};
+use super::{ElementBlockRef, ElementRef, ElementTag, Mesh, Node, NodeTag};
+use std::collections::HashMap;
+
+/// Position of a node in [`Mesh::node_blocks`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeRef {
+    /// Index of the block in [`Mesh::node_blocks`]
+    pub block: usize,
+    /// Index of the node in the block
+    pub index: usize,
+}
+
+/// Nodes of a mesh indexed by tag, see [`Mesh::node_index`]
+#[derive(Debug, Clone)]
+pub struct NodeIndex<'a> {
+    mesh: &'a Mesh,
+    positions: HashMap<NodeTag, NodeRef>,
+}
+
+impl<'a> NodeIndex<'a> {
+    /// Position of the node `tag` in the node blocks
+    pub fn locate(&self, tag: NodeTag) -> Option<NodeRef> {
+        self.positions.get(&tag).copied()
+    }
+
+    /// The node `tag`
+    pub fn get(&self, tag: NodeTag) -> Option<&'a Node> {
+        let position = self.locate(tag)?;
+        Some(&self.mesh.node_blocks[position.block].nodes[position.index])
+    }
+
+    /// Coordinates of the node `tag`
+    pub fn coordinates(&self, tag: NodeTag) -> Option<[f64; 3]> {
+        self.get(tag).map(|node| [node.x, node.y, node.z])
+    }
+
+    /// Number of indexed nodes
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+impl Mesh {
+    /// Index of all nodes by tag, for constant-time lookups
+    ///
+    /// The index borrows the mesh, so it has to be rebuilt after the node
+    /// blocks change. If a tag appears several times, the last node wins.
+    pub fn node_index(&self) -> NodeIndex<'_> {
+        let positions = self
+            .node_blocks
+            .iter()
+            .enumerate()
+            .flat_map(|(block, b)| {
+                b.nodes
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, node)| (node.tag, NodeRef { block, index }))
+            })
+            .collect();
+        NodeIndex {
+            mesh: self,
+            positions,
+        }
+    }
+
+    /// Coordinates of the node `tag`
+    ///
+    /// Scans the node blocks; use [`Mesh::node_index`] for repeated lookups.
+    pub fn get_node(&self, tag: NodeTag) -> Option<[f64; 3]> {
+        self.node_blocks
+            .iter()
+            .flat_map(|block| &block.nodes)
+            .find(|node| node.tag == tag)
+            .map(|node| [node.x, node.y, node.z])
+    }
+
+    /// All element blocks, expanded or compact, in file order
+    pub fn element_block_refs(&self) -> impl Iterator<Item = ElementBlockRef<'_>> + '_ {
+        in_file_order(
+            &self.element_blocks,
+            &self.compact_element_blocks,
+            |block| block.position,
+        )
+        .into_iter()
+        .map(|block| match block {
+            Stored::Expanded(block) => ElementBlockRef::Expanded(block),
+            Stored::Compact(block) => ElementBlockRef::Compact(block),
+        })
+    }
+
+    /// All elements with their blocks, compact blocks included, in file order
+    pub fn elements(&self) -> impl Iterator<Item = ElementRef<'_>> + '_ {
+        self.element_block_refs().flat_map(|block| block.elements())
+    }
+
+    /// The element `tag`, with its block
+    ///
+    /// Scans the element blocks, compact ones included.
+    pub fn get_element(&self, tag: ElementTag) -> Option<ElementRef<'_>> {
+        self.elements().find(|element| element.tag() == tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+    use crate::types::{ElementType, EntityTag};
+
+    /// A triangle whose nodes are split over two blocks, and one of its edges
+    const TRIANGLE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n2 3 1 3\n1 1 0 2\n1\n2\n0 0 0\n1 0 0\n2 1 0 1\n3\n0 1 0\n$EndNodes\n\
+        $Elements\n2 2 1 2\n1 1 1 1\n1 1 2\n2 1 2 1\n2 1 2 3\n$EndElements\n";
+
+    #[test]
+    fn test_node_index() {
+        let mesh = parse_msh(TRIANGLE).unwrap();
+        let index = mesh.node_index();
+        assert_eq!(index.len(), 3);
+        assert_eq!(
+            index.locate(NodeTag(3)),
+            Some(NodeRef { block: 1, index: 0 })
+        );
+        assert_eq!(index.coordinates(NodeTag(3)), Some([0.0, 1.0, 0.0]));
+        assert_eq!(mesh.get_node(NodeTag(2)), Some([1.0, 0.0, 0.0]));
+        assert!(index.get(NodeTag(4)).is_none());
+        assert!(mesh.get_node(NodeTag(4)).is_none());
+    }
+
+    #[test]
+    fn test_elements_and_get_element() {
+        let mesh = parse_msh(TRIANGLE).unwrap();
+        let tags: Vec<ElementTag> = mesh.elements().map(|e| e.tag()).collect();
+        assert_eq!(tags, [ElementTag(1), ElementTag(2)]);
+
+        let triangle = mesh.get_element(ElementTag(2)).unwrap();
+        assert_eq!(triangle.nodes(), [NodeTag(1), NodeTag(2), NodeTag(3)]);
+        assert_eq!(triangle.element_type(), ElementType::Triangle3);
+        assert_eq!(triangle.entity_tag(), EntityTag(1));
+        assert!(mesh.get_element(ElementTag(3)).is_none());
+    }
+
+    #[test]
+    fn test_elements_of_compact_blocks() {
+        let mut mesh = parse_msh(TRIANGLE).unwrap();
+        mesh.compact_storage();
+        let tags: Vec<ElementTag> = mesh.elements().map(|e| e.tag()).collect();
+        assert_eq!(tags, [ElementTag(1), ElementTag(2)]);
+
+        let triangle = mesh.get_element(ElementTag(2)).unwrap();
+        assert_eq!(triangle.nodes(), [NodeTag(1), NodeTag(2), NodeTag(3)]);
+        assert_eq!(triangle.element_type(), ElementType::Triangle3);
+        assert!(matches!(triangle.block(), ElementBlockRef::Compact(_)));
+    }
+}
@@ -1,14 +1,23 @@
 //! Mesh structure - pure parsing result
 
 use super::{
-    ElementBlock, ElementData, ElementNodeData, Entities, GhostElement, InterpolationScheme,
-    MeshFormat, NodeBlock, NodeData, Parametrizations, PartitionedEntities, PeriodicLink,
-    PhysicalName,
+    Change, CoordinateSystem, ElementBlock, ElementBlockCompact, ElementData, ElementNodeData,
+    ElementTag, ElementType, Entities, GhostElement, InterpolationScheme, MeshFormat, NodeBlock,
+    NodeData, NodeTag, Parametrizations, PartitionedEntities, PeriodicLink, PhysicalName,
+    RawSection, SectionHeader,
 };
-use crate::error::{ParseError, ParseWarning};
-use std::collections::HashSet;
+use crate::error::ParseWarning;
+use crate::sets::{ElementSet, NodeSet};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Mesh {
     pub format: MeshFormat,
     pub physical_names: Vec<PhysicalName>,
@@ -16,6 +25,12 @@ pub struct Mesh {
     pub partitioned_entities: Option<PartitionedEntities>,
     pub node_blocks: Vec<NodeBlock>,
     pub element_blocks: Vec<ElementBlock>,
+    /// Element blocks stored as flat arrays, see [`ParserOptions::compact_storage`](crate::ParserOptions::compact_storage)
+    pub compact_element_blocks: Vec<ElementBlockCompact>,
+    /// Header of the `$Nodes` section as declared in the file
+    pub nodes_header: Option<SectionHeader<NodeTag>>,
+    /// Header of the `$Elements` section as declared in the file
+    pub elements_header: Option<SectionHeader<ElementTag>>,
     pub periodic_links: Vec<PeriodicLink>,
     pub ghost_elements: Vec<GhostElement>,
     pub parametrizations: Option<Parametrizations>,
@@ -23,7 +38,39 @@ pub struct Mesh {
     pub element_data: Vec<ElementData>,
     pub element_node_data: Vec<ElementNodeData>,
     pub interpolation_schemes: Vec<InterpolationScheme>,
+    /// Sets from the crate-defined `$NodeSets` section
+    pub node_sets: Vec<NodeSet>,
+    /// Sets from the crate-defined `$ElementSets` section
+    pub element_sets: Vec<ElementSet>,
+    /// Frame from the crate-defined `$CoordinateSystem` section
+    pub coordinate_system: Option<CoordinateSystem>,
+    /// Unrecognized sections, in file order
+    pub raw_sections: Vec<RawSection>,
     pub warnings: Vec<ParseWarning>,
+    /// See [`Mesh::change_log`]
+    pub(crate) change_log: Vec<Change>,
+    /// See [`Mesh::recount`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) counts: OnceLock<Counts>,
+}
+
+/// Node and element counts cached by [`Mesh::num_nodes`] and [`Mesh::num_elements`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Counts {
+    nodes: usize,
+    elements: usize,
+}
+
+impl Counts {
+    fn of(mesh: &Mesh) -> Self {
+        let expanded = mesh.element_blocks.iter().map(|block| block.elements.len());
+        let compact = mesh.compact_element_blocks.iter().map(|block| block.len());
+        Self {
+            nodes: mesh.node_blocks.iter().map(|block| block.nodes.len()).sum(),
+            elements: expanded.chain(compact).sum(),
+        }
+    }
 }
 
 impl Mesh {
@@ -35,6 +82,9 @@ impl Mesh {
             partitioned_entities: None,
             node_blocks: Vec::new(),
             element_blocks: Vec::new(),
+            compact_element_blocks: Vec::new(),
+            nodes_header: None,
+            elements_header: None,
             periodic_links: Vec::new(),
             ghost_elements: Vec::new(),
             parametrizations: None,
@@ -42,53 +92,22 @@ impl Mesh {
             element_data: Vec::new(),
             element_node_data: Vec::new(),
             interpolation_schemes: Vec::new(),
+            node_sets: Vec::new(),
+            element_sets: Vec::new(),
+            coordinate_system: None,
+            raw_sections: Vec::new(),
             warnings: Vec::new(),
+            change_log: Vec::new(),
+            counts: OnceLock::new(),
         }
     }
 
     /// Print a summary of the mesh contents
+    ///
+    /// Prints the [`Mesh::summary`], then the other sections present, the
+    /// change log and the warnings.
     pub fn print_summary(&self) {
-        // Format information
-        println!("Format:");
-        println!("  Version: {}", self.format.version);
-        println!("  File Type: {}", self.format.file_type);
-        println!("  Data Size: {}", self.format.data_size);
-
-        // Physical names
-        println!("\nPhysical Groups: {}", self.physical_names.len());
-        if !self.physical_names.is_empty() {
-            for pn in &self.physical_names {
-                println!(
-                    "  [dim={}, tag={}]: \"{}\"",
-                    pn.dimension as i32, pn.tag, pn.name
-                );
-            }
-        }
-
-        // Entities
-        if let Some(entities) = &self.entities {
-            println!("\nEntities:");
-            println!("  Points: {}", entities.points.len());
-            println!("  Curves: {}", entities.curves.len());
-            println!("  Surfaces: {}", entities.surfaces.len());
-            println!("  Volumes: {}", entities.volumes.len());
-        }
-
-        // Nodes
-        let total_nodes: usize = self.node_blocks.iter().map(|block| block.nodes.len()).sum();
-        println!("\nNodes:");
-        println!("  Node blocks: {}", self.node_blocks.len());
-        println!("  Total nodes: {}", total_nodes);
-
-        // Elements
-        let total_elements: usize = self
-            .element_blocks
-            .iter()
-            .map(|block| block.elements.len())
-            .sum();
-        println!("\nElements:");
-        println!("  Element blocks: {}", self.element_blocks.len());
-        println!("  Total elements: {}", total_elements);
+        print!("{}", self.summary());
 
         // Other data
         if !self.periodic_links.is_empty() {
@@ -113,6 +132,30 @@ impl Mesh {
             );
         }
 
+        if !self.node_sets.is_empty() {
+            println!("\nNode Sets: {}", self.node_sets.len());
+        }
+        if !self.element_sets.is_empty() {
+            println!("\nElement Sets: {}", self.element_sets.len());
+        }
+        if let Some(frame) = &self.coordinate_system {
+            let unit = frame
+                .unit
+                .map_or("unknown unit".to_string(), |u| u.to_string());
+            println!("\nCoordinate System: \"{}\" ({})", frame.name, unit);
+        }
+        if !self.raw_sections.is_empty() {
+            let names: Vec<&str> = self.raw_sections.iter().map(|s| s.name.as_str()).collect();
+            println!("\nRaw Sections: {}", names.join(", "));
+        }
+
+        if !self.change_log.is_empty() {
+            println!("\nChanges since parsing: {}", self.change_log.len());
+            for change in &self.change_log {
+                println!("  - {}", change);
+            }
+        }
+
         // Warnings
         if !self.warnings.is_empty() {
             println!("\nWarnings: {}", self.warnings.len());
@@ -122,202 +165,127 @@ impl Mesh {
         }
     }
 
-    /// Validate mesh consistency
+    /// Number of nodes in all node blocks
     ///
-    /// Checks for:
-    /// - Duplicate node tags
-    /// - Duplicate element tags
-    /// - Duplicate entity tags (if entities section is present)
-    /// - Elements referencing missing nodes
-    /// - Nodes referencing missing entities (if entities section is present)
-    /// - Elements referencing missing entities (if entities section is present)
-    pub fn validate(&self) -> crate::error::Result<()> {
-        let entity_tags = self.validate_and_collect_entity_tags()?;
-        let node_tags = self.validate_nodes(&entity_tags)?;
-        self.validate_elements(&entity_tags, &node_tags)?;
-        Ok(())
+    /// Counted once, at parse time or on first use, and kept up to date by the
+    /// methods that add or remove nodes. Call [`Mesh::recount`] after editing
+    /// [`Mesh::node_blocks`] directly.
+    pub fn num_nodes(&self) -> usize {
+        self.counts().nodes
     }
 
-    /// Validate and collect entity tags (dim, tag)
-    fn validate_and_collect_entity_tags(&self) -> crate::error::Result<HashSet<(i32, i32)>> {
-        // Collect entity tags: (dim, tag)
-        let mut entity_tags = HashSet::new();
-
-        if let Some(entities) = &self.entities {
-            for p in &entities.points {
-                if !entity_tags.insert((0, p.tag)) {
-                    return Err(ParseError::MeshValidationError(format!(
-                        "Duplicate point entity tag: {}",
-                        p.tag
-                    )));
-                }
-            }
-            for c in &entities.curves {
-                if !entity_tags.insert((1, c.tag)) {
-                    return Err(ParseError::MeshValidationError(format!(
-                        "Duplicate curve entity tag: {}",
-                        c.tag
-                    )));
-                }
-            }
-            for s in &entities.surfaces {
-                if !entity_tags.insert((2, s.tag)) {
-                    return Err(ParseError::MeshValidationError(format!(
-                        "Duplicate surface entity tag: {}",
-                        s.tag
-                    )));
-                }
-            }
-            for v in &entities.volumes {
-                if !entity_tags.insert((3, v.tag)) {
-                    return Err(ParseError::MeshValidationError(format!(
-                        "Duplicate volume entity tag: {}",
-                        v.tag
-                    )));
-                }
-            }
-        }
-
-        // Also include entities defined in the partitioned entities section.
-        if let Some(partitioned) = &self.partitioned_entities {
-            for p in &partitioned.points {
-                entity_tags.insert((0, p.tag));
-            }
-            for c in &partitioned.curves {
-                entity_tags.insert((1, c.tag));
-            }
-            for s in &partitioned.surfaces {
-                entity_tags.insert((2, s.tag));
-            }
-            for v in &partitioned.volumes {
-                entity_tags.insert((3, v.tag));
-            }
-        }
+    /// Number of elements in all element blocks, compact ones included
+    ///
+    /// Cached like [`Mesh::num_nodes`].
+    pub fn num_elements(&self) -> usize {
+        self.counts().elements
+    }
 
-        Ok(entity_tags)
+    /// Count the nodes and elements again after the blocks were edited directly
+    pub fn recount(&mut self) {
+        self.counts = OnceLock::from(Counts::of(self));
     }
 
-    /// Validate nodes (duplicate tags, entity references)
-    fn validate_nodes(
-        &self,
-        entity_tags: &HashSet<(i32, i32)>,
-    ) -> crate::error::Result<HashSet<usize>> {
-        let mut node_tags = HashSet::new();
-        let mut duplicate_node_tag = None;
-        let has_entity_info = self.entities.is_some() || self.partitioned_entities.is_some();
-
-        for block in &self.node_blocks {
-            // Check entity reference
-            // Only check if we have any entity info at all (either regular or partitioned)
-            if has_entity_info && !entity_tags.contains(&(block.entity_dim(), block.entity_tag())) {
-                return Err(ParseError::MeshValidationError(format!(
-                    "Node block references missing entity: dim={}, tag={}",
-                    block.entity_dim(),
-                    block.entity_tag()
-                )));
-            }
+    fn counts(&self) -> Counts {
+        *self.counts.get_or_init(|| Counts::of(self))
+    }
 
-            for node in &block.nodes {
-                if duplicate_node_tag.is_none() && !node_tags.insert(node.tag) {
-                    duplicate_node_tag = Some(node.tag);
-                }
-            }
+    /// Number of elements of each type, compact blocks included
+    pub fn element_type_histogram(&self) -> HashMap<ElementType, usize> {
+        let expanded = self
+            .element_blocks
+            .iter()
+            .map(|block| (block.element_type, block.elements.len()));
+        let compact = self
+            .compact_element_blocks
+            .iter()
+            .map(|block| (block.element_type, block.len()));
+        let mut histogram = HashMap::new();
+        for (element_type, count) in expanded.chain(compact) {
+            *histogram.entry(element_type).or_insert(0) += count;
+        }
+        histogram
+    }
 
-            if let Some(tag) = duplicate_node_tag {
-                return Err(ParseError::MeshValidationError(format!(
-                    "Duplicate node tag: {}",
-                    tag
-                )));
-            }
+    /// The entities of the mesh, synthesized from the node and element blocks
+    /// if the file has no `$Entities` section (see [`Entities::synthesize`])
+    pub fn effective_entities(&self) -> Cow<'_, Entities> {
+        match &self.entities {
+            Some(entities) => Cow::Borrowed(entities),
+            None => Cow::Owned(Entities::synthesize(
+                &self.node_blocks,
+                self.element_block_refs(),
+            )),
         }
+    }
 
-        Ok(node_tags)
+    /// Node data view called `name`, at its last time step in file order
+    pub fn field(&self, name: &str) -> Option<&NodeData> {
+        self.node_data.iter().rev().find(|d| d.name() == Some(name))
     }
 
-    /// Validate elements (duplicate tags, entity references, node references)
-    fn validate_elements(
-        &self,
-        entity_tags: &HashSet<(i32, i32)>,
-        node_tags: &HashSet<usize>,
-    ) -> crate::error::Result<()> {
-        let mut element_tags = HashSet::new();
-        let has_entity_info = self.entities.is_some() || self.partitioned_entities.is_some();
-
-        for block in &self.element_blocks {
-            // Check entity reference
-            if has_entity_info && !entity_tags.contains(&(block.entity_dim, block.entity_tag)) {
-                return Err(ParseError::MeshValidationError(format!(
-                    "Element block references missing entity: dim={}, tag={}",
-                    block.entity_dim, block.entity_tag
-                )));
-            }
+    /// All time steps of the node data view called `name`, in file order
+    pub fn field_steps<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a NodeData> + 'a {
+        self.node_data
+            .iter()
+            .filter(move |d| d.name() == Some(name))
+    }
 
-            for element in &block.elements {
-                if !element_tags.insert(element.tag) {
-                    return Err(ParseError::MeshValidationError(format!(
-                        "Duplicate element tag: {}",
-                        element.tag
-                    )));
-                }
-
-                // Check node references
-                for node_tag in &element.nodes {
-                    if !node_tags.contains(node_tag) {
-                        return Err(ParseError::MeshValidationError(format!(
-                            "Element {} references missing node {}",
-                            element.tag, node_tag
-                        )));
-                    }
-                }
-            }
+    /// Validate mesh consistency
+    ///
+    /// Checks for:
+    /// - Duplicate node tags
+    /// - Duplicate element tags
+    /// - Duplicate entity tags (if entities section is present)
+    /// - Elements referencing missing nodes
+    /// - Nodes referencing missing entities (if entities section is present)
+    /// - Elements referencing missing entities (if entities section is present)
+    /// - Node and element sets referencing missing nodes or elements
+    ///
+    /// Stops at the first problem; [`Mesh::validate_all`] lists them all.
+    /// Element shapes are not checked; see
+    /// [`Mesh::validate_geometry`](crate::Mesh::validate_geometry).
+    pub fn validate(&self) -> crate::error::Result<()> {
+        match self.validation_report(false).first_error() {
+            Some(error) => Err(error),
+            None => Ok(()),
         }
-
-        Ok(())
     }
 
     /// Create a dummy Mesh for testing purposes
     #[cfg(test)]
     pub fn dummy() -> Self {
-        use crate::parser::{SourceFile, Span, Token};
-
-        // Simulating: "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n"
-        // "4.1" starts at offset 12 (after "$MeshFormat\n")
-        let source_content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n";
-        let source_file = SourceFile::new(source_content.into());
-        let token = Token::new(
-            "4.1".to_string(),
-            Span::new(12, 3), // offset=12, len=3
-            source_file.content,
-        );
-        let version = super::Version::new(4, 1, token);
-        let format = MeshFormat::new(version, super::FileType::Ascii, 8);
-        Self::new(format)
+        Self::new(MeshFormat::default())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::TRIANGLE;
     use crate::types::element::Element;
-    use crate::types::{ElementBlock, ElementType, EntityDimension, Node, NodeBlock, PointEntity};
+    use crate::types::{
+        ElementBlock, ElementType, EntityDimension, EntityTag, Node, NodeBlock, PhysicalTag,
+        PointEntity,
+    };
 
     #[test]
     fn test_validate_duplicate_node_tag() {
         let mut mesh = Mesh::dummy();
         mesh.node_blocks.push(NodeBlock {
             entity_dim: EntityDimension::Point,
-            entity_tag: 1,
+            entity_tag: EntityTag(1),
             parametric: false,
             nodes: vec![
                 Node {
-                    tag: 1,
+                    tag: NodeTag(1),
                     x: 0.0,
                     y: 0.0,
                     z: 0.0,
                     parametric_coords: None,
                 },
                 Node {
-                    tag: 1, // Duplicate tag
+                    tag: NodeTag(1), // Duplicate tag
                     x: 1.0,
                     y: 0.0,
                     z: 0.0,
@@ -340,10 +308,10 @@ mod tests {
         // Add valid nodes
         mesh.node_blocks.push(NodeBlock {
             entity_dim: EntityDimension::Point,
-            entity_tag: 1,
+            entity_tag: EntityTag(1),
             parametric: false,
             nodes: vec![Node {
-                tag: 1,
+                tag: NodeTag(1),
                 x: 0.0,
                 y: 0.0,
                 z: 0.0,
@@ -354,16 +322,16 @@ mod tests {
         // Add duplicate elements
         mesh.element_blocks.push(ElementBlock {
             entity_dim: 0,
-            entity_tag: 1,
+            entity_tag: EntityTag(1),
             element_type: ElementType::Point,
             elements: vec![
                 Element {
-                    tag: 1,
-                    nodes: vec![1],
+                    tag: ElementTag(1),
+                    nodes: vec![NodeTag(1)],
                 },
                 Element {
-                    tag: 1, // Duplicate tag
-                    nodes: vec![1],
+                    tag: ElementTag(1), // Duplicate tag
+                    nodes: vec![NodeTag(1)],
                 },
             ],
         });
@@ -382,10 +350,10 @@ mod tests {
         // Add valid nodes
         mesh.node_blocks.push(NodeBlock {
             entity_dim: EntityDimension::Point,
-            entity_tag: 1,
+            entity_tag: EntityTag(1),
             parametric: false,
             nodes: vec![Node {
-                tag: 1,
+                tag: NodeTag(1),
                 x: 0.0,
                 y: 0.0,
                 z: 0.0,
@@ -396,11 +364,11 @@ mod tests {
         // Add element referencing missing node
         mesh.element_blocks.push(ElementBlock {
             entity_dim: 0,
-            entity_tag: 1,
+            entity_tag: EntityTag(1),
             element_type: ElementType::Point,
             elements: vec![Element {
-                tag: 1,
-                nodes: vec![2], // Missing node 2
+                tag: ElementTag(1),
+                nodes: vec![NodeTag(2)], // Missing node 2
             }],
         });
 
@@ -418,7 +386,7 @@ mod tests {
         // Define entities
         let mut entities = Entities::new();
         entities.points.push(PointEntity {
-            tag: 1,
+            tag: EntityTag(1),
             x: 0.0,
             y: 0.0,
             z: 0.0,
@@ -429,10 +397,10 @@ mod tests {
         // Add node block referencing missing entity
         mesh.node_blocks.push(NodeBlock {
             entity_dim: EntityDimension::Point,
-            entity_tag: 2, // Missing entity 2
+            entity_tag: EntityTag(2), // Missing entity 2
             parametric: false,
             nodes: vec![Node {
-                tag: 1,
+                tag: NodeTag(1),
                 x: 0.0,
                 y: 0.0,
                 z: 0.0,
@@ -454,7 +422,7 @@ mod tests {
         // Define entities
         let mut entities = Entities::new();
         entities.points.push(PointEntity {
-            tag: 1,
+            tag: EntityTag(1),
             x: 0.0,
             y: 0.0,
             z: 0.0,
@@ -465,10 +433,10 @@ mod tests {
         // Add valid nodes
         mesh.node_blocks.push(NodeBlock {
             entity_dim: EntityDimension::Point,
-            entity_tag: 1,
+            entity_tag: EntityTag(1),
             parametric: false,
             nodes: vec![Node {
-                tag: 1,
+                tag: NodeTag(1),
                 x: 0.0,
                 y: 0.0,
                 z: 0.0,
@@ -479,15 +447,72 @@ mod tests {
         // Add valid elements
         mesh.element_blocks.push(ElementBlock {
             entity_dim: 0,
-            entity_tag: 1,
+            entity_tag: EntityTag(1),
             element_type: ElementType::Point,
             elements: vec![Element {
-                tag: 1,
-                nodes: vec![1],
+                tag: ElementTag(1),
+                nodes: vec![NodeTag(1)],
             }],
         });
 
         let result = mesh.validate();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_effective_entities_without_entities_section() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+$Nodes\n1 3 1 3\n2 1 0 3\n1\n2\n3\n0 0 0\n2 0 0\n0 1 0\n$EndNodes\n\
+$Elements\n2 2 1 2\n2 1 2 1\n1 1 2 3\n1 5 1 1\n2 1 2\n$EndElements\n";
+        let mesh = crate::parse_msh(content).unwrap();
+        assert!(mesh.entities.is_none());
+
+        let entities = mesh.effective_entities();
+        assert_eq!(entities.surfaces.len(), 1);
+        let surface = &entities.surfaces[0];
+        assert_eq!(
+            (surface.min_x, surface.max_x, surface.max_y),
+            (0.0, 2.0, 1.0)
+        );
+        let curve = &entities.curves[0];
+        assert_eq!(
+            (curve.tag, curve.max_x, curve.max_y),
+            (EntityTag(5), 2.0, 0.0)
+        );
+
+        // Group queries report unknown groups instead of a missing section
+        let err = crate::ElementSet::from_physical_group(&mesh, 2, PhysicalTag(1)).unwrap_err();
+        assert!(matches!(err, crate::ParseError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_num_nodes_and_elements() {
+        let mut mesh = crate::parse_msh(TRIANGLE).unwrap();
+        assert!(mesh.counts.get().is_some(), "counted at parse time");
+        assert_eq!(mesh.num_nodes(), 3);
+        assert_eq!(mesh.num_elements(), 2);
+
+        mesh.compact_storage();
+        assert_eq!(mesh.num_elements(), 2);
+        assert_eq!(Mesh::dummy().num_nodes(), 0);
+
+        // Direct edits are picked up by recount
+        mesh.node_blocks.clear();
+        assert_eq!(mesh.num_nodes(), 3);
+        mesh.recount();
+        assert_eq!(mesh.num_nodes(), 0);
+        assert_eq!(mesh.num_elements(), 2);
+    }
+
+    #[test]
+    fn test_element_type_histogram() {
+        let mut mesh = crate::parse_msh(TRIANGLE).unwrap();
+        let histogram = mesh.element_type_histogram();
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(histogram[&ElementType::Line2], 1);
+        assert_eq!(histogram[&ElementType::Triangle3], 1);
+
+        mesh.compact_storage();
+        assert_eq!(mesh.element_type_histogram(), histogram);
+    }
 }
@@ -1,7 +1,13 @@
-use crate::parser::Token;
+use crate::parser::{Span, Token};
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub enum FileType {
     Ascii,
     Binary,
@@ -26,9 +32,14 @@ impl fmt::Display for FileType {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Version {
     pub major: u32,
     pub minor: u32,
+    #[cfg_attr(feature = "rkyv", rkyv(with = crate::archive::TokenAsValue))]
     pub token: Token,
 }
 
@@ -41,12 +52,44 @@ impl Version {
         }
     }
 
+    /// Version whose token points into a synthesized `$MeshFormat` header
+    ///
+    /// Used for meshes that were not parsed from text, so that diagnostics
+    /// referring to the version token still have source code to render.
+    pub fn synthesized(major: u32, minor: u32) -> Self {
+        let token = synthesized_token(&format!("{}.{}", major, minor));
+        Self::new(major, minor, token)
+    }
+
     /// Check if this version is supported (only MSH 4.1 is supported)
     pub fn is_supported(&self) -> bool {
         self.major == 4 && self.minor == 1
     }
 }
 
+/// Version token pointing into a synthesized `$MeshFormat` header
+pub(crate) fn synthesized_token(value: &str) -> Token {
+    let source = Arc::new(format!("$MeshFormat\n{} 0 8\n$EndMeshFormat\n", value));
+    let span = Span::new("$MeshFormat\n".len(), value.len());
+    Token::new(value.to_string(), span, source)
+}
+
+/// Versions serialize as `(major, minor)`; the source token is not preserved.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.major, self.minor).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Version {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (major, minor) = <(u32, u32)>::deserialize(deserializer)?;
+        Ok(Self::synthesized(major, minor))
+    }
+}
+
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}.{}", self.major, self.minor)
@@ -54,6 +97,11 @@ impl fmt::Display for Version {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct MeshFormat {
     pub version: Version,
     pub file_type: FileType,
@@ -69,3 +117,10 @@ impl MeshFormat {
         }
     }
 }
+
+impl Default for MeshFormat {
+    /// MSH 4.1 ASCII with 8-byte data size, for meshes built in memory
+    fn default() -> Self {
+        Self::new(Version::synthesized(4, 1), FileType::Ascii, 8)
+    }
+}
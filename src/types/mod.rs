@@ -1,28 +1,61 @@
+pub mod change_log;
+pub mod coordinate_system;
+pub mod element;
+pub mod entity;
+pub mod entity_graph;
+pub mod ghost_element;
+pub mod indexing;
+pub mod interpolation_scheme;
+pub mod lookup;
 pub mod mesh;
 pub mod mesh_format;
-pub mod entity;
 pub mod node;
-pub mod element;
-pub mod physical_name;
-pub mod periodic;
-pub mod ghost_element;
-pub mod partitioned_entity;
 pub mod parametrization;
+pub mod partitioned_entity;
+pub mod periodic;
+pub mod physical_name;
 pub mod post_processing;
-pub mod interpolation_scheme;
+pub mod raw_section;
+pub mod renumber;
+pub mod section_header;
+pub mod summary;
+pub mod tag_stats;
+pub mod tags;
+pub mod validation;
 
+pub use change_log::Change;
+pub use coordinate_system::CoordinateSystem;
+pub use element::{
+    ElementBlock, ElementBlockCompact, ElementBlockRef, ElementFamily, ElementRef, ElementType,
+};
+pub use entity::{
+    CurveEntity, Entities, EntityDimension, PointEntity, SurfaceEntity, VolumeEntity,
+};
+pub use entity_graph::EntityGraph;
+pub use ghost_element::GhostElement;
+pub use indexing::Indexing;
+pub use interpolation_scheme::{
+    ElementTopology, ElementTopologyInterpolation, InterpolationMatrix, InterpolationScheme,
+};
+pub use lookup::{NodeIndex, NodeRef};
 pub use mesh::Mesh;
-pub use mesh_format::{MeshFormat, Version, FileType};
-pub use entity::{Entities, PointEntity, CurveEntity, SurfaceEntity, VolumeEntity, EntityDimension};
+pub use mesh_format::{FileType, MeshFormat, Version};
 pub use node::{Node, NodeBlock};
-pub use element::{ElementBlock, ElementType};
-pub use physical_name::PhysicalName;
-pub use periodic::PeriodicLink;
-pub use ghost_element::GhostElement;
-pub use partitioned_entity::{PartitionedEntities, PartitionedPoint, PartitionedCurve, PartitionedSurface, PartitionedVolume, GhostEntity};
 pub use parametrization::{
-    Parametrizations, CurveParametrization, SurfaceParametrization,
-    CurveParametrizationNode, SurfaceParametrizationNode, ParametrizationTriangle
+    CurveParametrization, CurveParametrizationNode, ParametrizationTriangle, Parametrizations,
+    SurfaceParametrization, SurfaceParametrizationNode,
 };
-pub use post_processing::{NodeData, ElementData, ElementNodeData};
-pub use interpolation_scheme::{InterpolationScheme, ElementTopologyInterpolation, InterpolationMatrix, ElementTopology};
+pub use partitioned_entity::{
+    GhostEntity, PartitionedCurve, PartitionedEntities, PartitionedPoint, PartitionedSurface,
+    PartitionedVolume,
+};
+pub use periodic::{PeriodicIssue, PeriodicIssueKind, PeriodicLink, PeriodicRole};
+pub use physical_name::PhysicalName;
+pub use post_processing::{ElementData, ElementNodeData, NodeData};
+pub use raw_section::RawSection;
+pub use renumber::Renumbering;
+pub use section_header::SectionHeader;
+pub use summary::{DimensionSummary, MeshSummary, PhysicalGroupSummary};
+pub use tag_stats::{TagGapReport, TagGaps};
+pub use tags::{ElementTag, EntityTag, NodeTag, Orientation, PhysicalTag};
+pub use validation::ValidationReport;
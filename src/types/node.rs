@@ -1,11 +1,16 @@
-use crate::types::EntityDimension;
+use crate::types::{EntityDimension, EntityTag, NodeTag};
 
 /// Unified NodeBlock structure.
 /// Corresponds to each entity block in the $Nodes section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct NodeBlock {
     pub entity_dim: EntityDimension,
-    pub entity_tag: i32,
+    pub entity_tag: EntityTag,
     pub parametric: bool, // Parametric flag parsed from the block header (0 or 1)
     pub nodes: Vec<Node>,
 }
@@ -23,7 +28,7 @@ impl NodeBlock {
     }
 
     /// Returns the tag of the entity this block belongs to.
-    pub fn entity_tag(&self) -> i32 {
+    pub fn entity_tag(&self) -> EntityTag {
         self.entity_tag
     }
 }
@@ -31,8 +36,13 @@ impl NodeBlock {
 /// Unified Node structure.
 /// Uses the same type regardless of dimension or parametric status.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Node {
-    pub tag: usize,
+    pub tag: NodeTag,
     pub x: f64,
     pub y: f64,
     pub z: f64,
@@ -2,8 +2,15 @@
 //!
 //! Defines parametrizations for curves and surfaces.
 
+use crate::types::EntityTag;
+
 /// Node parametrization for curves
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct CurveParametrizationNode {
     pub x: f64,
     pub y: f64,
@@ -13,13 +20,23 @@ pub struct CurveParametrizationNode {
 
 /// Curve parametrization
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct CurveParametrization {
-    pub curve_tag: i32,
+    pub curve_tag: EntityTag,
     pub nodes: Vec<CurveParametrizationNode>,
 }
 
 /// Node parametrization for surfaces
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct SurfaceParametrizationNode {
     pub x: f64,
     pub y: f64,
@@ -36,6 +53,11 @@ pub struct SurfaceParametrizationNode {
 
 /// Triangle for surface parametrization
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct ParametrizationTriangle {
     pub node_index1: usize,
     pub node_index2: usize,
@@ -44,14 +66,24 @@ pub struct ParametrizationTriangle {
 
 /// Surface parametrization
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct SurfaceParametrization {
-    pub surface_tag: i32,
+    pub surface_tag: EntityTag,
     pub nodes: Vec<SurfaceParametrizationNode>,
     pub triangles: Vec<ParametrizationTriangle>,
 }
 
 /// Complete parametrizations information
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Parametrizations {
     pub curves: Vec<CurveParametrization>,
     pub surfaces: Vec<SurfaceParametrization>,
@@ -2,34 +2,49 @@
 //!
 //! Defines partitioned entities for parallel mesh processing.
 
-use crate::types::EntityDimension;
+use crate::types::{EntityDimension, EntityTag, PhysicalTag};
 
 /// Ghost entity information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct GhostEntity {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub partition: i32,
 }
 
 /// Partitioned point entity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PartitionedPoint {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub parent_dim: EntityDimension,
-    pub parent_tag: i32,
+    pub parent_tag: EntityTag,
     pub partition_tags: Vec<i32>,
     pub x: f64,
     pub y: f64,
     pub z: f64,
-    pub physical_tags: Vec<i32>,
+    pub physical_tags: Vec<PhysicalTag>,
 }
 
 /// Partitioned curve entity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PartitionedCurve {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub parent_dim: EntityDimension,
-    pub parent_tag: i32,
+    pub parent_tag: EntityTag,
     pub partition_tags: Vec<i32>,
     pub min_x: f64,
     pub min_y: f64,
@@ -37,16 +52,21 @@ pub struct PartitionedCurve {
     pub max_x: f64,
     pub max_y: f64,
     pub max_z: f64,
-    pub physical_tags: Vec<i32>,
+    pub physical_tags: Vec<PhysicalTag>,
     pub bounding_points: Vec<i32>,
 }
 
 /// Partitioned surface entity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PartitionedSurface {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub parent_dim: EntityDimension,
-    pub parent_tag: i32,
+    pub parent_tag: EntityTag,
     pub partition_tags: Vec<i32>,
     pub min_x: f64,
     pub min_y: f64,
@@ -54,16 +74,21 @@ pub struct PartitionedSurface {
     pub max_x: f64,
     pub max_y: f64,
     pub max_z: f64,
-    pub physical_tags: Vec<i32>,
+    pub physical_tags: Vec<PhysicalTag>,
     pub bounding_curves: Vec<i32>,
 }
 
 /// Partitioned volume entity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PartitionedVolume {
-    pub tag: i32,
+    pub tag: EntityTag,
     pub parent_dim: EntityDimension,
-    pub parent_tag: i32,
+    pub parent_tag: EntityTag,
     pub partition_tags: Vec<i32>,
     pub min_x: f64,
     pub min_y: f64,
@@ -71,12 +96,17 @@ pub struct PartitionedVolume {
     pub max_x: f64,
     pub max_y: f64,
     pub max_z: f64,
-    pub physical_tags: Vec<i32>,
+    pub physical_tags: Vec<PhysicalTag>,
     pub bounding_surfaces: Vec<i32>,
 }
 
 /// Complete partitioned entities information
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PartitionedEntities {
     pub num_partitions: usize,
     pub ghost_entities: Vec<GhostEntity>,
@@ -2,19 +2,272 @@
 //!
 //! Defines periodicity relations between entities and their corresponding nodes.
 
-use crate::types::EntityDimension;
+use crate::error::{ParseError, Result};
+use crate::geometry::{distance, Affine3};
+use crate::types::{EntityDimension, EntityTag, Mesh, NodeTag};
+use std::collections::{HashMap, HashSet};
 
 /// Periodic link between two entities
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PeriodicLink {
     /// Dimension of the entity
     pub entity_dim: EntityDimension,
     /// Tag of the slave entity
-    pub entity_tag: i32,
+    pub entity_tag: EntityTag,
     /// Tag of the master entity
-    pub entity_tag_master: i32,
+    pub entity_tag_master: EntityTag,
     /// Affine transformation matrix (can be empty if not provided)
     pub affine_transform: Vec<f64>,
     /// Node correspondences: (slave_node_tag, master_node_tag)
-    pub node_correspondences: Vec<(usize, usize)>,
+    pub node_correspondences: Vec<(NodeTag, NodeTag)>,
+}
+
+impl PeriodicLink {
+    /// The affine transformation of the link, if the file provides one
+    ///
+    /// Gmsh writes the transformation mapping points of the master entity onto
+    /// the slave entity.
+    pub fn transform(&self) -> Option<Affine3> {
+        Affine3::from_gmsh(&self.affine_transform)
+    }
+
+    /// Image on the slave entity of a point of the master entity
+    pub fn master_to_slave(&self, point: [f64; 3]) -> Option<[f64; 3]> {
+        self.transform().map(|t| t.apply(point))
+    }
+
+    /// Image on the master entity of a point of the slave entity
+    ///
+    /// Returns `None` if there is no transformation or it is singular.
+    pub fn slave_to_master(&self, point: [f64; 3]) -> Option<[f64; 3]> {
+        self.transform()?.inverse().map(|t| t.apply(point))
+    }
+}
+
+/// Which entity of a periodic link a [`PeriodicIssue`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PeriodicRole {
+    Slave,
+    Master,
+}
+
+/// What is wrong with a periodic link
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PeriodicIssueKind {
+    /// No entity of the link's dimension has the tag of the slave or master
+    MissingEntity(PeriodicRole),
+    /// The box of one entity, mapped by the transformation (or its inverse
+    /// for the slave), does not enclose the box of the other
+    BoundsMismatch {
+        /// Entity whose box sticks out
+        role: PeriodicRole,
+        /// Bounding box of the image of the other entity's box
+        expected: ([f64; 3], [f64; 3]),
+        /// Bounding box of the entity
+        found: ([f64; 3], [f64; 3]),
+        /// Largest distance the box sticks out, along any axis
+        excess: f64,
+    },
+}
+
+/// A periodic link inconsistent with `$Entities`, see
+/// [`Mesh::check_periodic_links`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeriodicIssue {
+    /// Index of the link in [`Mesh::periodic_links`]
+    pub link: usize,
+    pub entity_dim: EntityDimension,
+    pub slave: EntityTag,
+    pub master: EntityTag,
+    pub kind: PeriodicIssueKind,
+}
+
+impl Mesh {
+    /// Check the periodic links against the entities of the mesh
+    ///
+    /// Both entities of a link must exist with the link's dimension. When the
+    /// link has a transformation, the image of the master's bounding box must
+    /// enclose the slave's box, and the image of the slave's box under the
+    /// inverse must enclose the master's, within [`Mesh::tolerance`] of the
+    /// size of the boxes. Images of rotated boxes are axis-aligned again, so
+    /// the check only catches links that cannot match, not loose fits.
+    ///
+    /// Every problem is returned; a mesh without `$Entities` has nothing to
+    /// check against.
+    pub fn check_periodic_links(&self) -> Vec<PeriodicIssue> {
+        let Some(entities) = &self.entities else {
+            return Vec::new();
+        };
+        let tolerance = self.tolerance();
+        let mut issues = Vec::new();
+        for (i, link) in self.periodic_links.iter().enumerate() {
+            let dim = link.entity_dim as i32;
+            let issue = |kind| PeriodicIssue {
+                link: i,
+                entity_dim: link.entity_dim,
+                slave: link.entity_tag,
+                master: link.entity_tag_master,
+                kind,
+            };
+            let slave = entities.bounding_box(dim, link.entity_tag);
+            let master = entities.bounding_box(dim, link.entity_tag_master);
+            if slave.is_none() {
+                issues.push(issue(PeriodicIssueKind::MissingEntity(PeriodicRole::Slave)));
+            }
+            if master.is_none() {
+                issues.push(issue(PeriodicIssueKind::MissingEntity(
+                    PeriodicRole::Master,
+                )));
+            }
+            let (Some(slave), Some(master), Some(transform)) = (slave, master, link.transform())
+            else {
+                continue;
+            };
+            let mut checks = vec![(
+                PeriodicRole::Slave,
+                transform.apply_box(master.0, master.1),
+                slave,
+            )];
+            if let Some(inverse) = transform.inverse() {
+                checks.push((
+                    PeriodicRole::Master,
+                    inverse.apply_box(slave.0, slave.1),
+                    master,
+                ));
+            }
+            for (role, expected, found) in checks {
+                let excess = (0..3)
+                    .map(|k| (expected.0[k] - found.0[k]).max(found.1[k] - expected.1[k]))
+                    .fold(0.0, f64::max);
+                let size = distance(found.0, found.1);
+                if excess > tolerance.slack(size) {
+                    issues.push(issue(PeriodicIssueKind::BoundsMismatch {
+                        role,
+                        expected,
+                        found,
+                        excess,
+                    }));
+                }
+            }
+        }
+        issues
+    }
+
+    /// Master node of every periodic slave node, from all [`Mesh::periodic_links`]
+    ///
+    /// Chains are followed to their end: a node that is the slave of a node
+    /// which is itself a slave, as happens on edges and corners of meshes
+    /// periodic in several directions, maps to the final master. A slave listed
+    /// with different masters follows the first link listing it.
+    ///
+    /// # Errors
+    /// Returns [`ParseError::MeshValidationError`] if a link references a node
+    /// missing from the node blocks.
+    pub fn periodic_node_map(&self) -> Result<HashMap<NodeTag, NodeTag>> {
+        let node_tags: HashSet<NodeTag> = self
+            .node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter().map(|n| n.tag))
+            .collect();
+        let mut map = HashMap::new();
+        for link in &self.periodic_links {
+            for &(slave, master) in &link.node_correspondences {
+                for tag in [slave, master] {
+                    if !node_tags.contains(&tag) {
+                        return Err(ParseError::MeshValidationError(format!(
+                            "Periodic link of entity {} (dim={}) references missing node {}",
+                            link.entity_tag, link.entity_dim as i32, tag
+                        )));
+                    }
+                }
+                map.entry(slave).or_insert(master);
+            }
+        }
+
+        let direct = map.clone();
+        for master in map.values_mut() {
+            // Bounded to stop on cyclic links
+            for _ in 0..direct.len() {
+                match direct.get(master) {
+                    Some(&next) if next != *master => *master = next,
+                    _ => break,
+                }
+            }
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::PERIODIC;
+    use crate::geometry::node_coordinates;
+
+    #[test]
+    fn test_periodic_node_map() {
+        let mesh = crate::parse_msh(PERIODIC).unwrap();
+        let map = mesh.periodic_node_map().unwrap();
+        // Corner 3 is the image of corner 2, itself the image of corner 1
+        let expected = [(2, 1), (3, 1), (4, 1)].map(|(s, m)| (NodeTag(s), NodeTag(m)));
+        assert_eq!(map, HashMap::from(expected));
+    }
+
+    #[test]
+    fn test_periodic_link_transforms() {
+        let mesh = crate::parse_msh(PERIODIC).unwrap();
+        let coordinates = node_coordinates(&mesh);
+        for link in &mesh.periodic_links {
+            for (slave, master) in &link.node_correspondences {
+                let image = link.master_to_slave(coordinates[master]).unwrap();
+                assert!(distance(image, coordinates[slave]) < 1e-12);
+                let back = link.slave_to_master(coordinates[slave]).unwrap();
+                assert!(distance(back, coordinates[master]) < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_periodic_node_map_missing_node() {
+        let mut mesh = crate::parse_msh(PERIODIC).unwrap();
+        mesh.periodic_links[0].node_correspondences[0].1 = NodeTag(u64::MAX);
+        assert!(matches!(
+            mesh.periodic_node_map(),
+            Err(ParseError::MeshValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_consistent_periodic_links() {
+        let mesh = crate::parse_msh(PERIODIC).unwrap();
+        assert!(mesh.check_periodic_links().is_empty());
+    }
+
+    #[test]
+    fn test_periodic_links_against_entities() {
+        let mut mesh = crate::parse_msh(PERIODIC).unwrap();
+        let link = &mut mesh.periodic_links[0];
+        // Shift the transformation far away from the slave
+        link.affine_transform[3] += 100.0;
+        let master = link.entity_tag_master;
+        mesh.periodic_links[1].entity_tag_master = EntityTag(i32::MAX);
+
+        let issues = mesh.check_periodic_links();
+        assert!(issues.iter().any(|issue| issue.link == 0
+            && issue.master == master
+            && matches!(
+                issue.kind,
+                PeriodicIssueKind::BoundsMismatch { excess, .. } if excess > 99.0
+            )));
+        assert!(issues.iter().any(|issue| issue.link == 1
+            && issue.kind == PeriodicIssueKind::MissingEntity(PeriodicRole::Master)));
+    }
 }
@@ -1,16 +1,21 @@
 //! Physical group name representation
 
-use crate::types::EntityDimension;
+use crate::types::{EntityDimension, PhysicalTag};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct PhysicalName {
     pub dimension: EntityDimension,
-    pub tag: i32,
+    pub tag: PhysicalTag,
     pub name: String,
 }
 
 impl PhysicalName {
-    pub fn new(dimension: EntityDimension, tag: i32, name: String) -> Self {
+    pub fn new(dimension: EntityDimension, tag: PhysicalTag, name: String) -> Self {
         Self {
             dimension,
             tag,
@@ -2,8 +2,15 @@
 //!
 //! Defines types for NodeData, ElementData, and ElementNodeData sections.
 
+use crate::types::{ElementTag, NodeTag};
+
 /// Post-processing view data associated with nodes
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct NodeData {
     /// View name and interpolation scheme name
     pub string_tags: Vec<String>,
@@ -12,11 +19,16 @@ pub struct NodeData {
     /// Time step, num components, num entities, partition index
     pub integer_tags: Vec<i32>,
     /// Data: (node_tag, values)
-    pub data: Vec<(usize, Vec<f64>)>,
+    pub data: Vec<(NodeTag, Vec<f64>)>,
 }
 
 /// Post-processing view data associated with elements
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct ElementData {
     /// View name and interpolation scheme name
     pub string_tags: Vec<String>,
@@ -25,11 +37,16 @@ pub struct ElementData {
     /// Time step, num components, num entities, partition index
     pub integer_tags: Vec<i32>,
     /// Data: (element_tag, values)
-    pub data: Vec<(usize, Vec<f64>)>,
+    pub data: Vec<(ElementTag, Vec<f64>)>,
 }
 
 /// Post-processing view data associated with element nodes
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct ElementNodeData {
     /// View name and interpolation scheme name
     pub string_tags: Vec<String>,
@@ -38,5 +55,54 @@ pub struct ElementNodeData {
     /// Time step, num components, num entities, partition index
     pub integer_tags: Vec<i32>,
     /// Data: (element_tag, num_nodes_per_element, values)
-    pub data: Vec<(usize, usize, Vec<f64>)>,
+    pub data: Vec<(ElementTag, usize, Vec<f64>)>,
+}
+
+/// Accessors giving the meaning of the string, real and integer tags
+///
+/// The parser checks that the integer tags hold at least the time step, the
+/// number of components and the number of entities.
+macro_rules! impl_tag_accessors {
+    ($($data:ty),*) => {
+        $(
+            impl $data {
+                /// View name (first string tag)
+                pub fn name(&self) -> Option<&str> {
+                    self.string_tags.first().map(String::as_str)
+                }
+
+                /// Name of the interpolation scheme (second string tag)
+                pub fn interpolation_scheme(&self) -> Option<&str> {
+                    self.string_tags.get(1).map(String::as_str)
+                }
+
+                /// Time value (first real tag)
+                pub fn time(&self) -> Option<f64> {
+                    self.real_tags.first().copied()
+                }
+
+                /// Time step (first integer tag)
+                pub fn time_step(&self) -> Option<i32> {
+                    self.integer_tags.first().copied()
+                }
+
+                /// Number of field components, e.g. 1, 3 or 9 (second integer tag)
+                pub fn num_components(&self) -> Option<usize> {
+                    self.integer_tags.get(1).and_then(|&n| usize::try_from(n).ok())
+                }
+
+                /// Number of entities with data (third integer tag)
+                pub fn num_entities(&self) -> Option<usize> {
+                    self.integer_tags.get(2).and_then(|&n| usize::try_from(n).ok())
+                }
+
+                /// Partition index (fourth integer tag), for partitioned data
+                pub fn partition(&self) -> Option<i32> {
+                    self.integer_tags.get(3).copied()
+                }
+            }
+        )*
+    };
 }
+
+impl_tag_accessors!(NodeData, ElementData, ElementNodeData);
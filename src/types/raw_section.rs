@@ -0,0 +1,39 @@
+//! Sections the parser does not know, kept as text
+
+use crate::parser::Span;
+
+/// An unrecognized section such as `$Comments`, stored line by line
+///
+/// Custom sections written by other tools end up in [`Mesh::raw_sections`](super::Mesh::raw_sections)
+/// so they can be post-processed, and the writer outputs them unchanged after
+/// the known sections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct RawSection {
+    /// Section name without the leading `$`, e.g. `Comments`
+    pub name: String,
+    /// Location of the section in the file, from `$Name` to `$EndName`
+    pub span: Span,
+    /// Lines between the section markers, blank ones included, without line
+    /// terminators
+    pub lines: Vec<String>,
+}
+
+impl RawSection {
+    pub fn new(name: impl Into<String>, lines: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            span: Span::new(0, 0),
+            lines,
+        }
+    }
+
+    /// The section content as one string, lines separated by `\n`
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+}
@@ -0,0 +1,331 @@
+//! Renumbering of node and element tags
+
+use super::{Change, ElementTag, Mesh, NodeTag};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// Old tags and the new tags replacing them, see [`Mesh::renumber_contiguous`]
+///
+/// Tags missing from the maps are left unchanged. A renumbering can be rebuilt
+/// from the [`Mesh::change_log`] with [`Renumbering::from_change`] to move data
+/// stored apart from the mesh, e.g. views of a result file written for the
+/// original numbering, to the new tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Renumbering {
+    pub nodes: BTreeMap<NodeTag, NodeTag>,
+    pub elements: BTreeMap<ElementTag, ElementTag>,
+}
+
+impl Renumbering {
+    /// The tag maps recorded in `change`
+    pub fn from_change(change: &Change) -> Self {
+        Self {
+            nodes: change.node_map.iter().copied().collect(),
+            elements: change.element_map.iter().copied().collect(),
+        }
+    }
+
+    /// The renumbering going back to the old tags
+    pub fn inverse(&self) -> Self {
+        Self {
+            nodes: self.nodes.iter().map(|(&old, &new)| (new, old)).collect(),
+            elements: self
+                .elements
+                .iter()
+                .map(|(&old, &new)| (new, old))
+                .collect(),
+        }
+    }
+
+    /// New tag of node `tag`
+    pub fn node(&self, tag: NodeTag) -> NodeTag {
+        self.nodes.get(&tag).copied().unwrap_or(tag)
+    }
+
+    /// New tag of element `tag`
+    pub fn element(&self, tag: ElementTag) -> ElementTag {
+        self.elements.get(&tag).copied().unwrap_or(tag)
+    }
+
+    /// Move the post-processing views and periodic links of `mesh` to the new
+    /// tags, leaving its nodes and elements alone
+    ///
+    /// [`Mesh::renumber_contiguous`] and [`Mesh::renumber_rcm`] already do this
+    /// for the mesh they renumber.
+    pub fn remap_data(&self, mesh: &mut Mesh) {
+        let node = |tag: &mut NodeTag| *tag = self.node(*tag);
+        let element = |tag: &mut ElementTag| *tag = self.element(*tag);
+        for link in &mut mesh.periodic_links {
+            for (slave, master) in &mut link.node_correspondences {
+                node(slave);
+                node(master);
+            }
+        }
+        for data in &mut mesh.node_data {
+            data.data.iter_mut().for_each(|(tag, _)| node(tag));
+        }
+        for data in &mut mesh.element_data {
+            data.data.iter_mut().for_each(|(tag, _)| element(tag));
+        }
+        for data in &mut mesh.element_node_data {
+            data.data.iter_mut().for_each(|(tag, _, _)| element(tag));
+        }
+    }
+}
+
+impl Mesh {
+    /// Renumber nodes and elements to `1..=N` in file order
+    ///
+    /// Element connectivity, periodic links, ghost elements, post-processing
+    /// data and node and element sets are updated to the new tags. Nodes used
+    /// by elements but missing from the node blocks are numbered after the
+    /// others. The declared `$Nodes` and `$Elements` headers no longer describe
+    /// the mesh and are dropped. The tags that changed are recorded in the
+    /// [`Mesh::change_log`].
+    pub fn renumber_contiguous(&mut self) -> Renumbering {
+        let nodes = self.node_order();
+        self.renumber(&nodes, "renumber_contiguous")
+    }
+
+    /// Renumber nodes in reverse Cuthill–McKee order and elements to `1..=N` in
+    /// file order
+    ///
+    /// Nodes sharing an element get close tags, which reduces the bandwidth of
+    /// matrices assembled with node tags as indices. Each connected part of the
+    /// mesh is traversed breadth-first from one of its nodes of lowest degree.
+    /// References are updated as in [`Mesh::renumber_contiguous`].
+    pub fn renumber_rcm(&mut self) -> Renumbering {
+        let nodes = self.node_order();
+        let index: HashMap<NodeTag, usize> =
+            nodes.iter().enumerate().map(|(i, &tag)| (tag, i)).collect();
+        let mut neighbors = vec![Vec::new(); nodes.len()];
+        for element in self.connectivity() {
+            for a in element {
+                for b in element {
+                    if a != b {
+                        neighbors[index[a]].push(index[b]);
+                    }
+                }
+            }
+        }
+        for list in &mut neighbors {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        let mut by_degree: Vec<usize> = (0..nodes.len()).collect();
+        by_degree.sort_by_key(|&i| neighbors[i].len());
+        let mut visited = vec![false; nodes.len()];
+        let mut order = Vec::with_capacity(nodes.len());
+        let mut queue = VecDeque::new();
+        for start in by_degree {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            queue.push_back(start);
+            while let Some(i) = queue.pop_front() {
+                order.push(nodes[i]);
+                let mut next: Vec<usize> = neighbors[i]
+                    .iter()
+                    .copied()
+                    .filter(|&j| !visited[j])
+                    .collect();
+                next.sort_by_key(|&j| neighbors[j].len());
+                for j in next {
+                    visited[j] = true;
+                    queue.push_back(j);
+                }
+            }
+        }
+        order.reverse();
+        self.renumber(&order, "renumber_rcm")
+    }
+
+    /// Node tags in file order, then those only used by elements
+    fn node_order(&self) -> Vec<NodeTag> {
+        let mut seen = HashSet::new();
+        self.node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter().map(|n| n.tag))
+            .chain(self.connectivity().flatten().copied())
+            .filter(|&tag| seen.insert(tag))
+            .collect()
+    }
+
+    /// Nodes of every element, expanded and compact
+    fn connectivity(&self) -> impl Iterator<Item = &[NodeTag]> + '_ {
+        self.element_blocks
+            .iter()
+            .flat_map(|b| b.elements.iter().map(|e| e.nodes.as_slice()))
+            .chain(
+                self.compact_element_blocks
+                    .iter()
+                    .flat_map(|b| b.connectivity.chunks(b.stride.max(1))),
+            )
+    }
+
+    /// Give node `nodes[i]` the tag `i + 1` and number elements in file order,
+    /// logging the change as `operation`
+    fn renumber(&mut self, nodes: &[NodeTag], operation: &str) -> Renumbering {
+        let renumbering = Renumbering {
+            nodes: (1..)
+                .zip(nodes)
+                .map(|(new, &old)| (old, NodeTag(new)))
+                .collect(),
+            elements: (1..)
+                .zip(
+                    self.element_blocks
+                        .iter()
+                        .flat_map(|b| b.elements.iter().map(|e| e.tag))
+                        .chain(
+                            self.compact_element_blocks
+                                .iter()
+                                .flat_map(|b| b.tags.iter().copied()),
+                        ),
+                )
+                .map(|(new, old)| (old, ElementTag(new)))
+                .collect(),
+        };
+        let node = |tag: &mut NodeTag| *tag = renumbering.node(*tag);
+        let element = |tag: &mut ElementTag| *tag = renumbering.element(*tag);
+
+        for n in self.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+            node(&mut n.tag);
+        }
+        for e in self.element_blocks.iter_mut().flat_map(|b| &mut b.elements) {
+            element(&mut e.tag);
+            e.nodes.iter_mut().for_each(node);
+        }
+        for block in &mut self.compact_element_blocks {
+            block.tags.iter_mut().for_each(element);
+            block.connectivity.iter_mut().for_each(node);
+        }
+        for ghost in &mut self.ghost_elements {
+            element(&mut ghost.element_tag);
+        }
+        renumbering.remap_data(self);
+        for set in &mut self.node_sets {
+            set.tags = set.tags.iter().map(|&t| renumbering.node(t)).collect();
+        }
+        for set in &mut self.element_sets {
+            set.tags = set.tags.iter().map(|&t| renumbering.element(t)).collect();
+        }
+        self.nodes_header = None;
+        self.elements_header = None;
+        self.record_change(
+            Change::new(operation)
+                .with_node_map(&renumbering.nodes)
+                .with_element_map(&renumbering.elements),
+        );
+        renumbering
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::CHAIN;
+    use crate::types::NodeData;
+
+    /// Two triangles with gaps between the node and element tags
+    const GAPS: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n1 4 3 12\n2 1 0 4\n3\n6\n9\n12\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+        $Elements\n1 2 7 9\n2 1 2 2\n7 3 6 9\n9 3 9 12\n$EndElements\n";
+
+    /// The triangles of [`GAPS`] in partitions 1 and 2, ghosts of each other
+    const PARTITIONED: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Entities\n0 0 1 0\n1 0 0 0 1 1 0 0 0\n$EndEntities\n\
+        $PartitionedEntities\n2\n0\n0 0 2 0\n2 2 1 1 1 0 0 0 1 1 0 0 0\n\
+        3 2 1 1 2 0 0 0 1 1 0 0 0\n$EndPartitionedEntities\n\
+        $Nodes\n2 4 3 12\n2 2 0 3\n3\n6\n9\n0 0 0\n1 0 0\n1 1 0\n2 3 0 1\n12\n0 1 0\n\
+        $EndNodes\n\
+        $Elements\n2 2 7 9\n2 2 2 1\n7 3 6 9\n2 3 2 1\n9 3 9 12\n$EndElements\n\
+        $GhostElements\n2\n7 1 1 2\n9 2 1 1\n$EndGhostElements\n";
+
+    /// Largest difference between the tags of two nodes of an element
+    fn bandwidth(mesh: &Mesh) -> u64 {
+        mesh.connectivity()
+            .flat_map(|nodes| {
+                let (min, max) = (nodes.iter().min(), nodes.iter().max());
+                min.zip(max).map(|(a, b)| b.0 - a.0)
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_renumber_contiguous() {
+        let mut mesh = crate::parse_msh(GAPS).unwrap();
+        assert!(mesh.tag_gaps().needs_renumbering());
+
+        let renumbering = mesh.renumber_contiguous();
+        assert!(!mesh.tag_gaps().needs_renumbering());
+        assert_eq!(renumbering.nodes[&NodeTag(12)], NodeTag(4));
+        assert_eq!(renumbering.elements[&ElementTag(9)], ElementTag(2));
+        let triangles = &mesh.element_blocks[0].elements;
+        assert_eq!(triangles[1].nodes, [NodeTag(1), NodeTag(3), NodeTag(4)]);
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    fn test_renumber_contiguous_updates_ghost_elements() {
+        let mut mesh = crate::parse_msh(PARTITIONED).unwrap();
+        mesh.renumber_contiguous();
+        mesh.validate().unwrap();
+        let ghosts: Vec<_> = mesh
+            .ghost_elements
+            .iter()
+            .map(|g| (g.element_tag.0, g.partition_tag))
+            .collect();
+        assert_eq!(ghosts, [(1, 1), (2, 2)]);
+        assert_eq!(mesh.compute_ghost_elements().unwrap(), mesh.ghost_elements);
+    }
+
+    #[test]
+    fn test_views_follow_renumbering() {
+        let mut mesh = crate::parse_msh(CHAIN).unwrap();
+        // Node view holding the x coordinate of each node
+        let x: Vec<(NodeTag, Vec<f64>)> = mesh
+            .node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter().map(|n| (n.tag, vec![n.x])))
+            .collect();
+        mesh.node_data.push(NodeData {
+            string_tags: vec!["x".to_string()],
+            real_tags: vec![0.0],
+            integer_tags: vec![0, 1, x.len() as i32],
+            data: x,
+        });
+        let mut results = Mesh::new(mesh.format.clone());
+        results.node_data = mesh.node_data.clone();
+
+        mesh.renumber_rcm();
+        let coordinates: HashMap<NodeTag, f64> = mesh
+            .node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter().map(|n| (n.tag, n.x)))
+            .collect();
+        assert!(mesh.node_data[0]
+            .data
+            .iter()
+            .all(|(tag, values)| coordinates[tag] == values[0]));
+
+        let renumbering = Renumbering::from_change(&mesh.change_log()[0]);
+        renumbering.remap_data(&mut results);
+        assert_eq!(results.node_data[0].data, mesh.node_data[0].data);
+        renumbering.inverse().remap_data(&mut results);
+        assert_ne!(results.node_data[0].data, mesh.node_data[0].data);
+    }
+
+    #[test]
+    fn test_renumber_rcm_reduces_bandwidth() {
+        let mut mesh = crate::parse_msh(CHAIN).unwrap();
+        assert_eq!(bandwidth(&mesh), 4);
+        let renumbering = mesh.renumber_rcm();
+        assert_eq!(renumbering.nodes.len(), mesh.num_nodes());
+        assert!(!mesh.tag_gaps().needs_renumbering());
+        mesh.validate().unwrap();
+        assert_eq!(bandwidth(&mesh), 1);
+    }
+}
@@ -0,0 +1,89 @@
+//! Header lines of the `$Nodes` and `$Elements` sections
+
+use super::{ElementTag, Mesh, NodeTag};
+
+/// The `numEntityBlocks numItems minTag maxTag` line opening a `$Nodes` or
+/// `$Elements` section
+///
+/// The parser keeps the header as declared in the file in
+/// [`Mesh::nodes_header`] and [`Mesh::elements_header`], even when it does not
+/// match the data (see [`ParserOptions::validate_metadata`](crate::ParserOptions::validate_metadata)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct SectionHeader<T> {
+    pub num_entity_blocks: usize,
+    /// Number of nodes or elements
+    pub count: usize,
+    pub min_tag: T,
+    pub max_tag: T,
+}
+
+impl<T: Copy + Ord + Default> SectionHeader<T> {
+    /// Header describing `num_entity_blocks` blocks holding `tags`
+    ///
+    /// The tag range is `0..=0` when there are no tags, as Gmsh writes it.
+    pub fn from_tags(num_entity_blocks: usize, tags: impl IntoIterator<Item = T>) -> Self {
+        let mut header = Self {
+            num_entity_blocks,
+            count: 0,
+            min_tag: T::default(),
+            max_tag: T::default(),
+        };
+        for tag in tags {
+            if header.count == 0 {
+                (header.min_tag, header.max_tag) = (tag, tag);
+            }
+            header.min_tag = header.min_tag.min(tag);
+            header.max_tag = header.max_tag.max(tag);
+            header.count += 1;
+        }
+        header
+    }
+}
+
+impl Mesh {
+    /// The `$Nodes` header matching the node blocks
+    pub fn computed_nodes_header(&self) -> SectionHeader<NodeTag> {
+        let tags = self
+            .node_blocks
+            .iter()
+            .flat_map(|b| &b.nodes)
+            .map(|n| n.tag);
+        SectionHeader::from_tags(self.node_blocks.len(), tags)
+    }
+
+    /// The `$Elements` header matching the element blocks, compact ones included
+    pub fn computed_elements_header(&self) -> SectionHeader<ElementTag> {
+        let expanded = self.element_blocks.iter().flat_map(|b| &b.elements);
+        let compact = self.compact_element_blocks.iter().flat_map(|b| &b.tags);
+        let tags = expanded.map(|e| e.tag).chain(compact.copied());
+        let num_blocks = self.element_blocks.len() + self.compact_element_blocks.len();
+        SectionHeader::from_tags(num_blocks, tags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_msh, parse_msh_with, ParserOptions};
+
+    #[test]
+    fn test_declared_headers() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 5\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n\
+            $Elements\n1 1 1 1\n1 1 1 1\n1 1 2\n$EndElements\n";
+        assert!(parse_msh(content).is_err());
+
+        let options = ParserOptions::default().validate_metadata(false);
+        let mesh = parse_msh_with(content, &options).unwrap();
+        let declared = mesh.nodes_header.unwrap();
+        let computed = mesh.computed_nodes_header();
+        assert_eq!((declared.count, declared.max_tag), (3, NodeTag(5)));
+        assert_eq!((computed.count, computed.max_tag), (2, NodeTag(2)));
+        assert_eq!(mesh.elements_header, Some(mesh.computed_elements_header()));
+    }
+}
@@ -0,0 +1,285 @@
+//! Counts and extent of a mesh, for logging and reports
+
+use super::{ElementType, EntityTag, Mesh, MeshFormat, PhysicalTag};
+use crate::filter::carried_physical_tags;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Overview of a mesh, see [`Mesh::summary`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeshSummary {
+    pub format: MeshFormat,
+    pub num_nodes: usize,
+    pub num_node_blocks: usize,
+    pub num_elements: usize,
+    /// Element blocks, compact ones included
+    pub num_element_blocks: usize,
+    /// Number of elements of each type, in ascending order of Gmsh type code
+    pub elements_by_type: Vec<(ElementType, usize)>,
+    /// Breakdown by dimension, points first
+    pub dimensions: [DimensionSummary; 4],
+    /// Physical groups by dimension then tag
+    pub physical_groups: Vec<PhysicalGroupSummary>,
+    /// Bounding box `(min, max)` of the nodes
+    pub bounding_box: Option<([f64; 3], [f64; 3])>,
+    pub num_warnings: usize,
+}
+
+/// Entities, nodes and elements of one dimension
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DimensionSummary {
+    /// Entities of `$Entities`, or synthesized from the blocks without it
+    pub entities: usize,
+    /// Nodes classified on entities of this dimension
+    pub nodes: usize,
+    pub elements: usize,
+}
+
+/// Size of one physical group
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhysicalGroupSummary {
+    pub dim: i32,
+    pub tag: PhysicalTag,
+    /// Name from `$PhysicalNames`, if any
+    pub name: Option<String>,
+    /// Entities carrying the tag, partition entities included
+    pub entities: usize,
+    pub elements: usize,
+}
+
+impl Mesh {
+    /// Counts by type, dimension and physical group, and the bounding box
+    ///
+    /// The [`Display`](fmt::Display) of the result is what
+    /// [`Mesh::print_summary`] prints first; serialize it with the `serde`
+    /// feature to log it in a structured form.
+    pub fn summary(&self) -> MeshSummary {
+        let mut dimensions = [DimensionSummary::default(); 4];
+        let entities = self.effective_entities();
+        dimensions[0].entities = entities.points.len();
+        dimensions[1].entities = entities.curves.len();
+        dimensions[2].entities = entities.surfaces.len();
+        dimensions[3].entities = entities.volumes.len();
+        for block in &self.node_blocks {
+            if let Some(d) = dimensions.get_mut(block.entity_dim() as usize) {
+                d.nodes += block.nodes.len();
+            }
+        }
+
+        // Physical groups by entity, on magnitude
+        let mut groups_of: HashMap<(i32, EntityTag), Vec<PhysicalTag>> = HashMap::new();
+        let mut tagged = |dim: i32, tag: EntityTag, physical: &[PhysicalTag]| {
+            let groups = groups_of.entry((dim, tag)).or_default();
+            for p in physical.iter().map(|p| p.magnitude()) {
+                if !groups.contains(&p) {
+                    groups.push(p);
+                }
+            }
+        };
+        for e in &entities.points {
+            tagged(0, e.tag, &e.physical_tags);
+        }
+        for e in &entities.curves {
+            tagged(1, e.tag, &e.physical_tags);
+        }
+        for e in &entities.surfaces {
+            tagged(2, e.tag, &e.physical_tags);
+        }
+        for e in &entities.volumes {
+            tagged(3, e.tag, &e.physical_tags);
+        }
+        if let Some(partitioned) = &self.partitioned_entities {
+            for e in &partitioned.points {
+                tagged(0, e.tag, &e.physical_tags);
+            }
+            for e in &partitioned.curves {
+                tagged(1, e.tag, &e.physical_tags);
+            }
+            for e in &partitioned.surfaces {
+                tagged(2, e.tag, &e.physical_tags);
+            }
+            for e in &partitioned.volumes {
+                tagged(3, e.tag, &e.physical_tags);
+            }
+        }
+
+        let mut groups: BTreeMap<(i32, PhysicalTag), PhysicalGroupSummary> =
+            carried_physical_tags(self)
+                .into_iter()
+                .chain(
+                    self.physical_names
+                        .iter()
+                        .map(|p| (p.dimension as i32, p.tag.magnitude())),
+                )
+                .map(|(dim, tag)| {
+                    let name = self
+                        .physical_names
+                        .iter()
+                        .find(|p| p.dimension as i32 == dim && p.tag.matches(tag))
+                        .map(|p| p.name.clone());
+                    let summary = PhysicalGroupSummary {
+                        dim,
+                        tag,
+                        name,
+                        entities: 0,
+                        elements: 0,
+                    };
+                    ((dim, tag), summary)
+                })
+                .collect();
+        for (&(dim, _), physical) in &groups_of {
+            for &tag in physical {
+                if let Some(group) = groups.get_mut(&(dim, tag)) {
+                    group.entities += 1;
+                }
+            }
+        }
+
+        let blocks = self
+            .element_blocks
+            .iter()
+            .map(|b| (b.entity_dim, b.entity_tag, b.elements.len()))
+            .chain(
+                self.compact_element_blocks
+                    .iter()
+                    .map(|b| (b.entity_dim, b.entity_tag, b.len())),
+            );
+        for (dim, tag, count) in blocks {
+            if let Some(d) = dimensions.get_mut(dim as usize) {
+                d.elements += count;
+            }
+            for &physical in groups_of.get(&(dim, tag)).into_iter().flatten() {
+                if let Some(group) = groups.get_mut(&(dim, physical)) {
+                    group.elements += count;
+                }
+            }
+        }
+
+        let mut elements_by_type: Vec<_> = self.element_type_histogram().into_iter().collect();
+        elements_by_type.sort_by_key(|(element_type, _)| element_type.to_i32());
+
+        MeshSummary {
+            format: self.format.clone(),
+            num_nodes: self.num_nodes(),
+            num_node_blocks: self.node_blocks.len(),
+            num_elements: self.num_elements(),
+            num_element_blocks: self.element_blocks.len() + self.compact_element_blocks.len(),
+            elements_by_type,
+            dimensions,
+            physical_groups: groups.into_values().collect(),
+            bounding_box: self.bounding_box(),
+            num_warnings: self.warnings.len(),
+        }
+    }
+}
+
+impl fmt::Display for MeshSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Format:")?;
+        writeln!(f, "  Version: {}", self.format.version)?;
+        writeln!(f, "  File Type: {}", self.format.file_type)?;
+        writeln!(f, "  Data Size: {}", self.format.data_size)?;
+
+        writeln!(f, "\nPhysical Groups: {}", self.physical_groups.len())?;
+        for group in &self.physical_groups {
+            let name = group.name.as_deref().unwrap_or("");
+            writeln!(
+                f,
+                "  [dim={}, tag={}]: \"{}\" ({} entities, {} elements)",
+                group.dim, group.tag, name, group.entities, group.elements
+            )?;
+        }
+
+        writeln!(f, "\nEntities, nodes and elements by dimension:")?;
+        for (name, d) in ["Points", "Curves", "Surfaces", "Volumes"]
+            .iter()
+            .zip(&self.dimensions)
+        {
+            writeln!(
+                f,
+                "  {}: {} entities, {} nodes, {} elements",
+                name, d.entities, d.nodes, d.elements
+            )?;
+        }
+
+        writeln!(f, "\nNodes:")?;
+        writeln!(f, "  Node blocks: {}", self.num_node_blocks)?;
+        writeln!(f, "  Total nodes: {}", self.num_nodes)?;
+        if let Some((min, max)) = self.bounding_box {
+            writeln!(f, "  Bounding box: {:?} to {:?}", min, max)?;
+        }
+
+        writeln!(f, "\nElements:")?;
+        writeln!(f, "  Element blocks: {}", self.num_element_blocks)?;
+        writeln!(f, "  Total elements: {}", self.num_elements)?;
+        for (element_type, count) in &self.elements_by_type {
+            writeln!(f, "  {}: {}", element_type, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    /// A unit square of two triangles, with the physical curve 5 "boundary"
+    /// on two of its three curves and the physical surface 6
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $PhysicalNames\n1\n1 5 \"boundary\"\n$EndPhysicalNames\n\
+        $Entities\n0 3 1 0\n1 0 0 0 1 0 0 1 5 0\n2 1 0 0 1 1 0 1 5 0\n3 0 1 0 1 1 0 0 0\n\
+        1 0 0 0 1 1 0 1 6 0\n$EndEntities\n\
+        $Nodes\n1 4 1 4\n2 1 0 4\n1\n2\n3\n4\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n$EndNodes\n\
+        $Elements\n4 5 1 5\n1 1 1 1\n1 1 2\n1 2 1 1\n2 2 3\n1 3 1 1\n3 3 4\n\
+        2 1 2 2\n4 1 2 3\n5 1 3 4\n$EndElements\n";
+
+    #[test]
+    fn test_summary_counts() {
+        let summary = parse_msh(SQUARE).unwrap().summary();
+        assert_eq!(summary.num_nodes, 4);
+        assert_eq!(summary.num_elements, 5);
+        assert_eq!(summary.num_element_blocks, 4);
+        assert_eq!(
+            summary.elements_by_type,
+            [(ElementType::Line2, 3), (ElementType::Triangle3, 2)]
+        );
+        let dimension = |entities, nodes, elements| DimensionSummary {
+            entities,
+            nodes,
+            elements,
+        };
+        assert_eq!(summary.dimensions[0], dimension(0, 0, 0));
+        assert_eq!(summary.dimensions[1], dimension(3, 0, 3));
+        assert_eq!(summary.dimensions[2], dimension(1, 4, 2));
+        assert_eq!(summary.bounding_box, Some(([0.0; 3], [1.0, 1.0, 0.0])));
+    }
+
+    #[test]
+    fn test_summary_physical_groups() {
+        let summary = parse_msh(SQUARE).unwrap().summary();
+        let group = |dim, tag, name: Option<&str>, entities, elements| PhysicalGroupSummary {
+            dim,
+            tag: PhysicalTag(tag),
+            name: name.map(String::from),
+            entities,
+            elements,
+        };
+        assert_eq!(
+            summary.physical_groups,
+            [group(1, 5, Some("boundary"), 2, 2), group(2, 6, None, 1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_summary_display() {
+        let text = parse_msh(SQUARE).unwrap().summary().to_string();
+        assert!(text.contains("[dim=1, tag=5]: \"boundary\" (2 entities, 2 elements)"));
+        assert!(text.contains("  Curves: 3 entities, 0 nodes, 3 elements"));
+        assert!(text.contains("Total nodes: 4"));
+        assert!(text.contains("Triangle3: 2"));
+    }
+}
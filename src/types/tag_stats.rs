@@ -0,0 +1,132 @@
+//! Tag range and contiguity statistics
+
+use super::{ElementTag, Mesh, NodeTag};
+use std::ops::RangeInclusive;
+
+/// Contiguity statistics of a set of tags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TagGaps {
+    /// Number of distinct tags
+    pub count: usize,
+    /// Smallest and largest tag, if any
+    pub range: Option<RangeInclusive<u64>>,
+    /// Tags inside the range that are not used
+    pub missing: u64,
+    /// Number of maximal runs of missing tags
+    pub gaps: usize,
+    /// Length of the longest run of missing tags
+    pub largest_gap: u64,
+}
+
+impl TagGaps {
+    /// Compute statistics from tags in any order (duplicates are ignored)
+    pub fn from_tags(tags: impl IntoIterator<Item = u64>) -> Self {
+        let mut tags: Vec<u64> = tags.into_iter().collect();
+        tags.sort_unstable();
+        tags.dedup();
+
+        let mut stats = TagGaps {
+            count: tags.len(),
+            range: tags.first().zip(tags.last()).map(|(&min, &max)| min..=max),
+            ..Default::default()
+        };
+        for pair in tags.windows(2) {
+            let gap = pair[1] - pair[0] - 1;
+            if gap > 0 {
+                stats.missing += gap;
+                stats.gaps += 1;
+                stats.largest_gap = stats.largest_gap.max(gap);
+            }
+        }
+        stats
+    }
+
+    /// Whether the tags are exactly `1..=count`, as solvers requiring dense indices expect
+    pub fn is_dense(&self) -> bool {
+        match &self.range {
+            Some(range) => *range.start() == 1 && self.missing == 0,
+            None => true,
+        }
+    }
+}
+
+/// Contiguity of node and element tags, see [`Mesh::tag_gaps`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TagGapReport {
+    pub nodes: TagGaps,
+    pub elements: TagGaps,
+}
+
+impl TagGapReport {
+    /// Whether nodes or elements need renumbering to be dense
+    pub fn needs_renumbering(&self) -> bool {
+        !self.nodes.is_dense() || !self.elements.is_dense()
+    }
+}
+
+impl Mesh {
+    /// Smallest and largest node tag, or `None` if the mesh has no nodes
+    pub fn node_tag_range(&self) -> Option<RangeInclusive<NodeTag>> {
+        min_max(
+            self.node_blocks
+                .iter()
+                .flat_map(|b| b.nodes.iter().map(|n| n.tag)),
+        )
+    }
+
+    /// Smallest and largest element tag, or `None` if the mesh has no elements
+    pub fn element_tag_range(&self) -> Option<RangeInclusive<ElementTag>> {
+        min_max(self.elements().map(|e| e.tag()))
+    }
+
+    /// Contiguity statistics of node and element tags
+    pub fn tag_gaps(&self) -> TagGapReport {
+        TagGapReport {
+            nodes: TagGaps::from_tags(
+                self.node_blocks
+                    .iter()
+                    .flat_map(|b| b.nodes.iter().map(|n| n.tag.0)),
+            ),
+            elements: TagGaps::from_tags(self.elements().map(|e| e.tag().0)),
+        }
+    }
+}
+
+fn min_max<T: Ord + Copy>(tags: impl Iterator<Item = T>) -> Option<RangeInclusive<T>> {
+    tags.fold(None, |acc, tag| match acc {
+        None => Some(tag..=tag),
+        Some(range) => Some((*range.start()).min(tag)..=(*range.end()).max(tag)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_gaps() {
+        let gaps = TagGaps::from_tags([7, 1, 2, 3, 10, 3]);
+        assert_eq!(gaps.count, 5);
+        assert_eq!(gaps.range, Some(1..=10));
+        assert_eq!(gaps.missing, 5);
+        assert_eq!(gaps.gaps, 2);
+        assert_eq!(gaps.largest_gap, 3);
+        assert!(!gaps.is_dense());
+        assert!(TagGaps::from_tags(1..=4).is_dense());
+    }
+
+    #[test]
+    fn test_mesh_tag_gaps() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 4\n0 1 0 3\n1\n2\n4\n0 0 0\n1 0 0\n2 0 0\n$EndNodes\n\
+            $Elements\n1 2 3 7\n1 1 1 2\n3 1 2\n7 2 4\n$EndElements\n";
+        let report = crate::parse_msh(content).unwrap().tag_gaps();
+        assert_eq!(report.nodes.range, Some(1..=4));
+        assert_eq!(report.nodes.missing, 1);
+        assert_eq!(report.elements.range, Some(3..=7));
+        assert_eq!(report.elements.gaps, 1);
+        assert_eq!(report.elements.largest_gap, 3);
+    }
+}
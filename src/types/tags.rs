@@ -0,0 +1,180 @@
+//! Strongly typed tags
+//!
+//! Node, element, entity and physical tags are distinct newtypes so that one
+//! kind of tag cannot be passed where another is expected. Each converts to and
+//! from its underlying integer and compares equal to it, so literals keep
+//! working in comparisons: `node.tag == 5`.
+//!
+//! Signed entity references in bounding lists (`bounding_curves`, ...) stay
+//! plain `i32`, as their sign encodes orientation. Physical tags of entities
+//! may be negative for the same reason in some exports; [`PhysicalTag`] splits
+//! them into a [`magnitude`](PhysicalTag::magnitude) identifying the group and
+//! an [`Orientation`].
+
+use std::fmt;
+use std::ops::Add;
+
+macro_rules! tag_type {
+    ($(#[$doc:meta])* $name:ident($int:ty)) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        #[cfg_attr(
+            feature = "serde",
+            derive(serde::Serialize, serde::Deserialize),
+            serde(transparent)
+        )]
+        #[cfg_attr(
+            feature = "rkyv",
+            derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+            rkyv(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash))
+        )]
+        pub struct $name(pub $int);
+
+        impl $name {
+            pub const fn new(value: $int) -> Self {
+                Self(value)
+            }
+
+            /// The underlying integer
+            pub const fn get(self) -> $int {
+                self.0
+            }
+        }
+
+        impl From<$int> for $name {
+            fn from(value: $int) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for $int {
+            fn from(tag: $name) -> Self {
+                tag.0
+            }
+        }
+
+        impl PartialEq<$int> for $name {
+            fn eq(&self, other: &$int) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<$name> for $int {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.0
+            }
+        }
+
+        /// Offset a tag, e.g. when renumbering or merging meshes
+        impl Add<$int> for $name {
+            type Output = Self;
+
+            fn add(self, offset: $int) -> Self {
+                Self(self.0 + offset)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+tag_type!(
+    /// Tag of a node
+    NodeTag(u64)
+);
+tag_type!(
+    /// Tag of an element
+    ElementTag(u64)
+);
+tag_type!(
+    /// Tag of a geometrical entity, unique within its dimension
+    EntityTag(i32)
+);
+tag_type!(
+    /// Tag of a physical group, unique within its dimension
+    PhysicalTag(i32)
+);
+
+/// Orientation given by the sign of a signed tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    /// Positive tag
+    #[default]
+    Forward,
+    /// Negative tag
+    Reversed,
+}
+
+impl Orientation {
+    /// The orientation of a signed tag, zero counting as positive
+    pub const fn of(signed: i32) -> Self {
+        if signed < 0 {
+            Self::Reversed
+        } else {
+            Self::Forward
+        }
+    }
+
+    /// `1` or `-1`
+    pub const fn sign(self) -> i32 {
+        match self {
+            Self::Forward => 1,
+            Self::Reversed => -1,
+        }
+    }
+
+    pub const fn is_reversed(self) -> bool {
+        matches!(self, Self::Reversed)
+    }
+}
+
+impl PhysicalTag {
+    /// The tag of the physical group, without the orientation sign
+    ///
+    /// `i32::MIN` has no positive counterpart and saturates to `i32::MAX`.
+    pub const fn magnitude(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+
+    /// The orientation given by the sign of the tag
+    pub const fn orientation(self) -> Orientation {
+        Orientation::of(self.0)
+    }
+
+    /// Whether both tags designate the same physical group, whatever their
+    /// signs
+    pub const fn matches(self, other: PhysicalTag) -> bool {
+        self.0.unsigned_abs() == other.0.unsigned_abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_conversions() {
+        let tag = NodeTag::from(7);
+        assert_eq!(tag, 7);
+        assert_eq!(u64::from(tag), 7);
+        assert_eq!(tag.to_string(), "7");
+        assert_eq!(vec![EntityTag(1), EntityTag(-2)], vec![1, -2]);
+        assert!(ElementTag(3) < ElementTag(10));
+    }
+
+    #[test]
+    fn test_physical_tag_sign() {
+        let tag = PhysicalTag(-4);
+        assert_eq!(tag.magnitude(), 4);
+        assert_eq!(tag.orientation(), Orientation::Reversed);
+        assert_eq!(PhysicalTag(4).orientation().sign(), 1);
+        assert!(tag.matches(PhysicalTag(4)));
+        assert!(!tag.matches(PhysicalTag(-5)));
+        assert_eq!(PhysicalTag(i32::MIN).magnitude(), i32::MAX);
+    }
+}
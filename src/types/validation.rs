@@ -0,0 +1,250 @@
+//! Consistency checks collecting every problem of a mesh
+
+use super::{ElementTag, EntityTag, Mesh, NodeTag};
+use crate::error::ParseError;
+use std::collections::HashSet;
+
+/// Every consistency problem of a mesh, by category, see
+/// [`Mesh::validate_all`]
+///
+/// Each list holds the offending tags in file order, once per occurrence.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    /// Entities of `$Entities` sharing a `(dim, tag)` with an earlier one
+    pub duplicate_entity_tags: Vec<(i32, EntityTag)>,
+    /// Node blocks on an entity missing from `$Entities`, by `(dim, tag)`
+    pub node_blocks_missing_entity: Vec<(i32, EntityTag)>,
+    /// Nodes sharing a tag with an earlier one
+    pub duplicate_node_tags: Vec<NodeTag>,
+    /// Element blocks on an entity missing from `$Entities`, by `(dim, tag)`
+    pub element_blocks_missing_entity: Vec<(i32, EntityTag)>,
+    /// Elements sharing a tag with an earlier one
+    pub duplicate_element_tags: Vec<ElementTag>,
+    /// Elements using a node missing from the node blocks
+    pub missing_nodes: Vec<(ElementTag, NodeTag)>,
+    /// Node sets, by name, listing a missing node
+    pub node_sets_missing_node: Vec<(String, NodeTag)>,
+    /// Element sets, by name, listing a missing element
+    pub element_sets_missing_element: Vec<(String, ElementTag)>,
+    /// Nodes used by no element; allowed, e.g. for point clouds, but often left
+    /// over from editing
+    pub orphan_nodes: Vec<NodeTag>,
+}
+
+impl ValidationReport {
+    /// Whether [`Mesh::validate`] accepts the mesh; orphan nodes are allowed
+    pub fn is_valid(&self) -> bool {
+        self.first_error().is_none()
+    }
+
+    /// Number of problems, orphan nodes included
+    pub fn num_issues(&self) -> usize {
+        self.duplicate_entity_tags.len()
+            + self.node_blocks_missing_entity.len()
+            + self.duplicate_node_tags.len()
+            + self.element_blocks_missing_entity.len()
+            + self.duplicate_element_tags.len()
+            + self.missing_nodes.len()
+            + self.node_sets_missing_node.len()
+            + self.element_sets_missing_element.len()
+            + self.orphan_nodes.len()
+    }
+
+    /// The error [`Mesh::validate`] returns: the first problem of the first
+    /// non-empty category, in the order of the fields
+    pub fn first_error(&self) -> Option<ParseError> {
+        let message = if let Some((dim, tag)) = self.duplicate_entity_tags.first() {
+            let kind = ["point", "curve", "surface", "volume"]
+                .get(*dim as usize)
+                .unwrap_or(&"entity");
+            format!("Duplicate {} entity tag: {}", kind, tag)
+        } else if let Some((dim, tag)) = self.node_blocks_missing_entity.first() {
+            format!(
+                "Node block references missing entity: dim={}, tag={}",
+                dim, tag
+            )
+        } else if let Some(tag) = self.duplicate_node_tags.first() {
+            format!("Duplicate node tag: {}", tag)
+        } else if let Some((dim, tag)) = self.element_blocks_missing_entity.first() {
+            format!(
+                "Element block references missing entity: dim={}, tag={}",
+                dim, tag
+            )
+        } else if let Some(tag) = self.duplicate_element_tags.first() {
+            format!("Duplicate element tag: {}", tag)
+        } else if let Some((element, node)) = self.missing_nodes.first() {
+            format!("Element {} references missing node {}", element, node)
+        } else if let Some((name, tag)) = self.node_sets_missing_node.first() {
+            format!("Node set \"{}\" references missing node {}", name, tag)
+        } else if let Some((name, tag)) = self.element_sets_missing_element.first() {
+            format!(
+                "Element set \"{}\" references missing element {}",
+                name, tag
+            )
+        } else {
+            return None;
+        };
+        Some(ParseError::MeshValidationError(message))
+    }
+}
+
+impl Mesh {
+    /// Check the consistency of the mesh and return every problem found
+    ///
+    /// Runs the checks of [`Mesh::validate`] without stopping at the first
+    /// problem, and also lists orphan nodes, so that a file can be fixed in one
+    /// pass.
+    pub fn validate_all(&self) -> ValidationReport {
+        self.validation_report(true)
+    }
+
+    /// The problems found by [`Mesh::validate_all`], orphan nodes only if
+    /// `orphans` is set since they take an extra pass over the elements
+    pub(crate) fn validation_report(&self, orphans: bool) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        let mut entity_tags = HashSet::new();
+        if let Some(entities) = &self.entities {
+            let tags = entities
+                .points
+                .iter()
+                .map(|p| (0, p.tag))
+                .chain(entities.curves.iter().map(|c| (1, c.tag)))
+                .chain(entities.surfaces.iter().map(|s| (2, s.tag)))
+                .chain(entities.volumes.iter().map(|v| (3, v.tag)));
+            for key in tags {
+                if !entity_tags.insert(key) {
+                    report.duplicate_entity_tags.push(key);
+                }
+            }
+        }
+        // Also include entities defined in the partitioned entities section.
+        if let Some(partitioned) = &self.partitioned_entities {
+            entity_tags.extend(partitioned.points.iter().map(|p| (0, p.tag)));
+            entity_tags.extend(partitioned.curves.iter().map(|c| (1, c.tag)));
+            entity_tags.extend(partitioned.surfaces.iter().map(|s| (2, s.tag)));
+            entity_tags.extend(partitioned.volumes.iter().map(|v| (3, v.tag)));
+        }
+        // Only check entity references if we have any entity info at all
+        let has_entity_info = self.entities.is_some() || self.partitioned_entities.is_some();
+
+        let mut node_tags = HashSet::new();
+        for block in &self.node_blocks {
+            let key = (block.entity_dim(), block.entity_tag());
+            if has_entity_info && !entity_tags.contains(&key) {
+                report.node_blocks_missing_entity.push(key);
+            }
+            for node in &block.nodes {
+                if !node_tags.insert(node.tag) {
+                    report.duplicate_node_tags.push(node.tag);
+                }
+            }
+        }
+
+        let blocks = self
+            .element_blocks
+            .iter()
+            .map(|b| (b.entity_dim, b.entity_tag))
+            .chain(
+                self.compact_element_blocks
+                    .iter()
+                    .map(|b| (b.entity_dim, b.entity_tag)),
+            );
+        for key in blocks {
+            if has_entity_info && !entity_tags.contains(&key) {
+                report.element_blocks_missing_entity.push(key);
+            }
+        }
+
+        let mut element_tags = HashSet::new();
+        let mut used = HashSet::new();
+        let elements = self
+            .element_blocks
+            .iter()
+            .flat_map(|b| b.elements.iter().map(|e| (e.tag, e.nodes.as_slice())))
+            .chain(self.compact_element_blocks.iter().flat_map(|b| b.iter()));
+        for (tag, nodes) in elements {
+            if !element_tags.insert(tag) {
+                report.duplicate_element_tags.push(tag);
+            }
+            for &node in nodes {
+                if !node_tags.contains(&node) {
+                    report.missing_nodes.push((tag, node));
+                }
+                if orphans {
+                    used.insert(node);
+                }
+            }
+        }
+
+        for set in &self.node_sets {
+            for tag in set.iter().filter(|tag| !node_tags.contains(tag)) {
+                report.node_sets_missing_node.push((set.name.clone(), tag));
+            }
+        }
+        for set in &self.element_sets {
+            for tag in set.iter().filter(|tag| !element_tags.contains(tag)) {
+                report
+                    .element_sets_missing_element
+                    .push((set.name.clone(), tag));
+            }
+        }
+
+        if orphans {
+            report.orphan_nodes = self
+                .node_blocks
+                .iter()
+                .flat_map(|b| b.nodes.iter().map(|n| n.tag))
+                .filter(|tag| !used.contains(tag))
+                .collect();
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::NodeTag;
+
+    /// A unit square of four triangles around a centre node
+    const SQUARE: &str = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+        $Nodes\n1 5 1 5\n2 1 0 5\n1\n2\n3\n4\n5\n\
+        0 0 0\n1 0 0\n1 1 0\n0 1 0\n0.5 0.5 0\n$EndNodes\n\
+        $Elements\n1 4 1 4\n2 1 2 4\n1 1 2 5\n2 2 3 5\n3 3 4 5\n4 4 1 5\n$EndElements\n";
+
+    #[test]
+    fn test_validate_all_of_valid_mesh() {
+        let report = crate::parse_msh(SQUARE).unwrap().validate_all();
+        assert!(report.is_valid());
+        assert_eq!(report.num_issues(), 0);
+    }
+
+    #[test]
+    fn test_validate_all_collects_every_problem() {
+        let mut mesh = crate::parse_msh(SQUARE).unwrap();
+
+        let block = mesh.element_blocks.last_mut().unwrap();
+        block.elements[0].nodes[0] = NodeTag(1_000_000);
+        block.elements[1].nodes[0] = NodeTag(1_000_001);
+        let tag = block.elements[2].tag;
+        block.elements[3].tag = tag;
+        let mut extra = mesh.node_blocks[0].nodes[0].clone();
+        extra.tag = NodeTag(2_000_000);
+        mesh.node_blocks[0].nodes.push(extra);
+
+        let report = mesh.validate_all();
+        assert!(!report.is_valid());
+        assert_eq!(report.duplicate_element_tags, [tag]);
+        assert_eq!(
+            report.missing_nodes.iter().map(|m| m.1).collect::<Vec<_>>(),
+            [NodeTag(1_000_000), NodeTag(1_000_001)]
+        );
+        // Nodes 1 and 2 are left out of the first two triangles, but not unused
+        assert_eq!(report.orphan_nodes, [NodeTag(2_000_000)]);
+        assert_eq!(report.num_issues(), 4);
+        // The first category in field order decides the error of `validate`
+        let error = mesh.validate().unwrap_err().to_string();
+        assert!(error.contains(&format!("Duplicate element tag: {}", tag)));
+    }
+}
@@ -0,0 +1,373 @@
+//! Post-processing views: grouping by time step and comparison
+//!
+//! Transient results repeat a `$NodeData` or `$ElementData` section for every
+//! time step. [`Mesh::node_views`] and [`Mesh::element_views`] group these
+//! records by view name into [`PostProcessingView`]s with sorted steps:
+//!
+//! ```no_run
+//! use gmsh_parser::parse_msh_file;
+//!
+//! let mesh = parse_msh_file("transient.msh").unwrap();
+//! let temperature = mesh.node_view("T").unwrap();
+//! for step in temperature.time_steps() {
+//!     println!("step {} at t = {:?}", step, temperature.time(step));
+//! }
+//! ```
+//!
+//! [`compare`] is intended for regression testing of solver results stored as
+//! MSH views:
+//!
+//! ```no_run
+//! use gmsh_parser::parse_msh_file;
+//! use gmsh_parser::views::{compare, Norm};
+//!
+//! let a = parse_msh_file("reference.msh").unwrap();
+//! let b = parse_msh_file("result.msh").unwrap();
+//! let cmp = compare(a.field("T").unwrap(), b.field("T").unwrap(), Norm::L2);
+//! assert!(cmp.is_exact_match() || cmp.relative_difference() < 1e-6);
+//! ```
+
+use crate::types::{ElementData, ElementNodeData, Mesh, NodeData, NodeTag};
+use std::collections::HashMap;
+
+/// Norm used to measure the difference between two views
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Norm {
+    /// Sum of absolute values
+    L1,
+    /// Square root of the sum of squares
+    L2,
+    /// Maximum absolute value
+    Linf,
+}
+
+impl Norm {
+    fn accumulate(self, acc: f64, value: f64) -> f64 {
+        match self {
+            Norm::L1 => acc + value.abs(),
+            Norm::L2 => acc + value * value,
+            Norm::Linf => acc.max(value.abs()),
+        }
+    }
+
+    fn finish(self, acc: f64) -> f64 {
+        match self {
+            Norm::L2 => acc.sqrt(),
+            Norm::L1 | Norm::Linf => acc,
+        }
+    }
+}
+
+/// Result of comparing two node data views
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewComparison {
+    pub norm: Norm,
+    /// Norm of `b - a` over the matched nodes, all components included
+    pub difference: f64,
+    /// Norm of `a` over the matched nodes
+    pub reference: f64,
+    /// Number of node tags present in both views with the same number of components
+    pub matched: usize,
+    /// Node tags present only in the first view
+    pub only_in_a: Vec<NodeTag>,
+    /// Node tags present only in the second view
+    pub only_in_b: Vec<NodeTag>,
+    /// Node tags whose number of components differs between the views
+    pub component_mismatch: Vec<NodeTag>,
+}
+
+impl ViewComparison {
+    /// `difference / reference`, or the absolute difference if the reference is zero
+    pub fn relative_difference(&self) -> f64 {
+        if self.reference > 0.0 {
+            self.difference / self.reference
+        } else {
+            self.difference
+        }
+    }
+
+    /// Whether both views cover the same nodes
+    pub fn tags_match(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.component_mismatch.is_empty()
+    }
+
+    /// Whether both views cover the same nodes with identical values
+    pub fn is_exact_match(&self) -> bool {
+        self.tags_match() && self.difference == 0.0
+    }
+}
+
+/// Compare two node data views on their common node tags
+///
+/// Nodes present in only one of the views, or with a different number of
+/// components, are reported and left out of the norms.
+pub fn compare(a: &NodeData, b: &NodeData, norm: Norm) -> ViewComparison {
+    let values_b: HashMap<NodeTag, &Vec<f64>> = b.data.iter().map(|(t, v)| (*t, v)).collect();
+
+    let mut difference = 0.0;
+    let mut reference = 0.0;
+    let mut matched = 0;
+    let mut only_in_a = Vec::new();
+    let mut component_mismatch = Vec::new();
+
+    for (tag, va) in &a.data {
+        match values_b.get(tag) {
+            None => only_in_a.push(*tag),
+            Some(vb) if vb.len() != va.len() => component_mismatch.push(*tag),
+            Some(vb) => {
+                matched += 1;
+                for (x, y) in va.iter().zip(vb.iter()) {
+                    difference = norm.accumulate(difference, y - x);
+                    reference = norm.accumulate(reference, *x);
+                }
+            }
+        }
+    }
+
+    let tags_a: std::collections::HashSet<NodeTag> = a.data.iter().map(|(t, _)| *t).collect();
+    let mut only_in_b: Vec<NodeTag> = b
+        .data
+        .iter()
+        .map(|(t, _)| *t)
+        .filter(|t| !tags_a.contains(t))
+        .collect();
+    only_in_a.sort_unstable();
+    only_in_b.sort_unstable();
+    component_mismatch.sort_unstable();
+
+    ViewComparison {
+        norm,
+        difference: norm.finish(difference),
+        reference: norm.finish(reference),
+        matched,
+        only_in_a,
+        only_in_b,
+        component_mismatch,
+    }
+}
+
+/// Common tags of the `$NodeData`, `$ElementData` and `$ElementNodeData`
+/// records
+pub trait ViewRecord {
+    fn string_tags(&self) -> &[String];
+    fn real_tags(&self) -> &[f64];
+    fn integer_tags(&self) -> &[i32];
+
+    /// View name (first string tag)
+    fn view_name(&self) -> Option<&str> {
+        self.string_tags().first().map(String::as_str)
+    }
+
+    /// Time value (first real tag)
+    fn time(&self) -> Option<f64> {
+        self.real_tags().first().copied()
+    }
+
+    /// Time step (first integer tag), 0 if missing
+    fn step(&self) -> i32 {
+        self.integer_tags().first().copied().unwrap_or(0)
+    }
+}
+
+macro_rules! impl_view_record {
+    ($($record:ty),*) => {
+        $(
+            impl ViewRecord for $record {
+                fn string_tags(&self) -> &[String] {
+                    &self.string_tags
+                }
+
+                fn real_tags(&self) -> &[f64] {
+                    &self.real_tags
+                }
+
+                fn integer_tags(&self) -> &[i32] {
+                    &self.integer_tags
+                }
+            }
+        )*
+    };
+}
+
+impl_view_record!(NodeData, ElementData, ElementNodeData);
+
+/// The records of one view, sorted by time step
+///
+/// Records of the same step, e.g. one per partition, keep their file order.
+#[derive(Debug, Clone)]
+pub struct PostProcessingView<'a, D> {
+    pub name: &'a str,
+    records: Vec<&'a D>,
+}
+
+impl<'a, D: ViewRecord> PostProcessingView<'a, D> {
+    /// Group `records` by view name, views in order of first appearance
+    pub fn group(records: &'a [D]) -> Vec<Self> {
+        let mut views: Vec<Self> = Vec::new();
+        for record in records {
+            let name = record.view_name().unwrap_or_default();
+            match views.iter_mut().find(|v| v.name == name) {
+                Some(view) => view.records.push(record),
+                None => views.push(Self {
+                    name,
+                    records: vec![record],
+                }),
+            }
+        }
+        for view in &mut views {
+            view.records.sort_by_key(|r| r.step());
+        }
+        views
+    }
+
+    /// All records, sorted by time step
+    pub fn records(&self) -> &[&'a D] {
+        &self.records
+    }
+
+    /// Distinct time steps, in increasing order
+    pub fn time_steps(&self) -> Vec<i32> {
+        let mut steps: Vec<i32> = self.records.iter().map(|r| r.step()).collect();
+        steps.dedup();
+        steps
+    }
+
+    /// Number of distinct time steps
+    pub fn num_steps(&self) -> usize {
+        self.time_steps().len()
+    }
+
+    /// Records of time step `step`, several for partitioned data
+    pub fn step(&self, step: i32) -> &[&'a D] {
+        let start = self.records.partition_point(|r| r.step() < step);
+        let end = self.records.partition_point(|r| r.step() <= step);
+        &self.records[start..end]
+    }
+
+    /// Time value of step `step`
+    pub fn time(&self, step: i32) -> Option<f64> {
+        self.step(step).first()?.time()
+    }
+
+    /// Records of the last step whose time is at most `time`
+    pub fn at_time(&self, time: f64) -> &[&'a D] {
+        let step = self
+            .records
+            .iter()
+            .rev()
+            .find(|r| r.time().is_some_and(|t| t <= time))
+            .map(|r| r.step());
+        step.map_or(&[], |step| self.step(step))
+    }
+
+    /// Records of the last time step
+    pub fn last(&self) -> &[&'a D] {
+        self.records.last().map_or(&[], |r| self.step(r.step()))
+    }
+}
+
+impl Mesh {
+    /// `$NodeData` records grouped by view, see [`PostProcessingView`]
+    pub fn node_views(&self) -> Vec<PostProcessingView<'_, NodeData>> {
+        PostProcessingView::group(&self.node_data)
+    }
+
+    /// `$ElementData` records grouped by view
+    pub fn element_views(&self) -> Vec<PostProcessingView<'_, ElementData>> {
+        PostProcessingView::group(&self.element_data)
+    }
+
+    /// `$ElementNodeData` records grouped by view
+    pub fn element_node_views(&self) -> Vec<PostProcessingView<'_, ElementNodeData>> {
+        PostProcessingView::group(&self.element_node_data)
+    }
+
+    /// The node data view called `name`
+    pub fn node_view(&self, name: &str) -> Option<PostProcessingView<'_, NodeData>> {
+        self.node_views().into_iter().find(|v| v.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+
+    const VIEW: &str = r#"$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$Nodes
+1 3 1 3
+0 1 0 3
+1
+2
+3
+0 0 0
+1 0 0
+2 0 0
+$EndNodes
+$NodeData
+1
+"T"
+1
+0.0
+3
+0
+1
+3
+1 1.0
+2 2.0
+3 3.0
+$EndNodeData
+$NodeData
+1
+"T"
+1
+1.0
+3
+1
+1
+2
+1 1.0
+2 5.0
+$EndNodeData
+"#;
+
+    #[test]
+    fn test_compare_time_steps() {
+        let mesh = parse_msh(VIEW).unwrap();
+        let first = mesh.field_steps("T").next().unwrap();
+        let last = mesh.field("T").unwrap();
+        assert_eq!(last.time_step(), Some(1));
+
+        assert!(compare(first, first, Norm::L2).is_exact_match());
+
+        let l2 = compare(first, last, Norm::L2);
+        assert_eq!(l2.matched, 2);
+        assert_eq!(l2.only_in_a, vec![3]);
+        assert!(l2.only_in_b.is_empty());
+        assert!((l2.difference - 3.0).abs() < 1e-12);
+        assert!((l2.reference - 5.0_f64.sqrt()).abs() < 1e-12);
+        assert_eq!(compare(first, last, Norm::L1).reference, 3.0);
+        assert_eq!(compare(last, first, Norm::Linf).difference, 3.0);
+
+        assert!(mesh.field("missing").is_none());
+    }
+
+    #[test]
+    fn test_views_group_time_steps() {
+        // Steps written out of order, as restarted simulations may do
+        let reordered = VIEW.replacen("\"T\"\n1\n0.0\n3\n0", "\"T\"\n1\n2.0\n3\n2", 1);
+        let mesh = parse_msh(reordered).unwrap();
+        let views = mesh.node_views();
+        assert_eq!(views.len(), 1);
+        let view = mesh.node_view("T").unwrap();
+        assert_eq!(view.time_steps(), [1, 2]);
+        assert_eq!(view.time(2), Some(2.0));
+        assert_eq!(view.step(1)[0].data.len(), 2);
+        assert_eq!(view.at_time(1.5)[0].step(), 1);
+        assert!(view.at_time(0.5).is_empty());
+        assert_eq!(view.last()[0].data.len(), 3);
+        assert!(mesh.element_views().is_empty());
+    }
+}
@@ -0,0 +1,211 @@
+//! Writing meshes as ASCII MSH 4.1
+//!
+//! A parsed mesh can be modified in memory and written back:
+//!
+//! ```no_run
+//! use gmsh_parser::parse_msh_file;
+//! use gmsh_parser::writer::write_msh_file;
+//!
+//! let mut mesh = parse_msh_file("model.msh").unwrap();
+//! mesh.quantize_coordinates(8);
+//! write_msh_file(&mesh, "model_rounded.msh").unwrap();
+//! ```
+//!
+//! Header counts and tag ranges are recomputed from the data, unless
+//! [`WriteOptions::keep_declared_headers`] asks for the ones read from the file
+//! (see [`Mesh::nodes_header`](crate::Mesh::nodes_header)). Reals are written
+//! in their shortest round-trip form unless [`WriteOptions::precision`] says
+//! otherwise, so parsing the output gives back the same values.
+//! [`Mesh::quantize_coordinates`](crate::Mesh::quantize_coordinates) rounds the
+//! coordinates of the mesh itself instead.
+
+mod msh;
+mod number;
+mod partition;
+mod quantize;
+
+pub use number::{FormatNumber, NumberFormatter, RealPrecision};
+pub use quantize::{round_significant, QuantizationReport};
+
+use crate::error::Result;
+use crate::types::Mesh;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Options shared by the text writers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Digits of every real written: node and parametric coordinates, entity
+    /// bounding boxes, parametrizations and post-processing values
+    ///
+    /// [`RealPrecision::Significant`] shortens ASCII output considerably for
+    /// meshes whose values carry more digits than the model's real precision
+    /// (e.g. archival copies). The exporter applies it to point coordinates and
+    /// field values.
+    pub precision: RealPrecision,
+    /// Write the node and element counts and tag ranges declared in the
+    /// parsed file instead of recomputing them
+    ///
+    /// Reproduces the input headers, inconsistent ones included. The number of
+    /// entity blocks is always recomputed, as parsing depends on it.
+    pub keep_declared_headers: bool,
+}
+
+impl WriteOptions {
+    pub fn with_precision(mut self, precision: RealPrecision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn keep_declared_headers(mut self) -> Self {
+        self.keep_declared_headers = true;
+        self
+    }
+
+    /// A number formatter using these options
+    pub fn number_formatter(&self) -> NumberFormatter {
+        NumberFormatter::new(self.precision)
+    }
+}
+
+/// Write `mesh` as ASCII MSH 4.1
+///
+/// # Errors
+/// Returns an error if a name cannot be quoted, see [`write_msh_to`].
+pub fn write_msh(mesh: &Mesh) -> Result<String> {
+    let mut out = Vec::new();
+    write_msh_to(mesh, &WriteOptions::default(), &mut out)?;
+    // The writer only emits UTF-8
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Write `mesh` to `path` as ASCII MSH 4.1
+///
+/// # Errors
+/// Returns an error if the file cannot be written or a name cannot be quoted,
+/// see [`write_msh_to`].
+pub fn write_msh_file(mesh: &Mesh, path: impl AsRef<Path>) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_msh_to(mesh, &WriteOptions::default(), &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write `mesh` to `writer` as ASCII MSH 4.1 using `options`
+///
+/// # Errors
+/// Returns an error if writing fails, or if a physical name, set name, view
+/// string tag or other quoted name holds a line break, which MSH files cannot
+/// represent.
+pub fn write_msh_to<W: Write>(mesh: &Mesh, options: &WriteOptions, writer: &mut W) -> Result<()> {
+    msh::write_mesh(mesh, options, writer)?;
+    Ok(())
+}
+
+/// Check that `name` can be written between double quotes
+///
+/// The parser reads a quoted name up to the last quote of its line, without
+/// escapes, so inner quotes read back unchanged but line breaks cannot be
+/// represented.
+pub(crate) fn check_quotable(name: &str) -> io::Result<()> {
+    if name.contains(['\n', '\r']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} cannot be quoted: it holds a line break", name),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_msh;
+    use crate::types::NodeTag;
+
+    #[test]
+    fn test_write_renumbered_mesh() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 2 1 2\n0 1 0 2\n1\n2\n0 0 0\n0.1 0 0\n$EndNodes\n\
+            $Elements\n1 1 1 1\n1 1 1 1\n1 1 2\n$EndElements\n";
+        let mut mesh = parse_msh(content).unwrap();
+        for node in mesh.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+            node.tag = node.tag + 1000;
+        }
+        for element in mesh.element_blocks.iter_mut().flat_map(|b| &mut b.elements) {
+            for node in &mut element.nodes {
+                *node = *node + 1000;
+            }
+        }
+
+        let written = write_msh(&mesh).unwrap();
+        let reparsed = parse_msh(&written).unwrap();
+        reparsed.validate().unwrap();
+        let first = &reparsed.node_blocks[0].nodes[0];
+        assert_eq!(first.tag, NodeTag(1001));
+        assert_eq!(first.x, mesh.node_blocks[0].nodes[0].x);
+        assert_eq!(write_msh(&reparsed).unwrap(), written);
+    }
+
+    #[test]
+    fn test_precision_applies_to_all_reals() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 1 1 1\n0 1 0 1\n1\n0.123456 2.71828 -1e-7\n$EndNodes\n\
+            $NodeData\n1\n\"T\"\n1\n0.0\n3\n0\n1\n1\n1 3.14159\n$EndNodeData\n";
+        let mesh = parse_msh(content).unwrap();
+        let options = WriteOptions::default().with_precision(RealPrecision::Significant(3));
+        let mut out = Vec::new();
+        write_msh_to(&mesh, &options, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\n0.123 2.72 -1e-7\n"));
+        assert!(out.contains("\n1 3.14\n"));
+    }
+
+    #[test]
+    fn test_keep_declared_headers() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 3 1 5\n0 1 0 2\n1\n2\n0 0 0\n1 0 0\n$EndNodes\n";
+        let options = crate::ParserOptions::default().validate_metadata(false);
+        let mesh = crate::parse_msh_with(content, &options).unwrap();
+
+        let header = |options: &WriteOptions| {
+            let mut out = Vec::new();
+            write_msh_to(&mesh, options, &mut out).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            out.lines()
+                .skip_while(|l| *l != "$Nodes")
+                .nth(1)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(header(&WriteOptions::default()), "1 2 1 2");
+        assert_eq!(
+            header(&WriteOptions::default().keep_declared_headers()),
+            "1 3 1 5"
+        );
+    }
+
+    #[test]
+    fn test_unquotable_names_are_rejected() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $PhysicalNames\n1\n2 1 \"wall\"\n$EndPhysicalNames\n";
+        let mut mesh = parse_msh(content).unwrap();
+        mesh.physical_names[0].name = "left\nwall".to_string();
+        let result = write_msh_to(&mesh, &WriteOptions::default(), &mut Vec::new());
+        assert!(matches!(result, Err(crate::ParseError::IoError(_))));
+
+        assert!(write_msh(&mesh).is_err());
+    }
+
+    #[test]
+    fn test_inner_quotes_round_trip() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $PhysicalNames\n1\n2 1 \"a\"b\"\n$EndPhysicalNames\n";
+        let mesh = parse_msh(content).unwrap();
+        assert_eq!(mesh.physical_names[0].name, "a\"b");
+        let reparsed = parse_msh(write_msh(&mesh).unwrap()).unwrap();
+        assert_eq!(reparsed.physical_names[0].name, "a\"b");
+    }
+}
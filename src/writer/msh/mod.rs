@@ -0,0 +1,460 @@
+//! ASCII MSH 4.1 output
+//!
+//! Sections are written in the order Gmsh uses. Header counts and tag ranges
+//! are recomputed from the data, so a mesh can be modified freely before it
+//! is written. Unknown sections kept in [`Mesh::raw_sections`] come last.
+
+mod partitioned_entities;
+
+use super::{check_quotable, FormatNumber, NumberFormatter, WriteOptions};
+use crate::sets::{write_element_sets, write_node_sets};
+use crate::types::{ElementData, ElementNodeData, Entities, Mesh, NodeData, Parametrizations};
+use std::io::{self, Write};
+
+/// Writes MSH text value by value, separating values on a line with spaces
+struct MshWriter<'w, W: Write> {
+    out: &'w mut W,
+    numbers: NumberFormatter,
+    line_started: bool,
+}
+
+impl<'w, W: Write> MshWriter<'w, W> {
+    fn new(out: &'w mut W, options: &WriteOptions) -> Self {
+        Self {
+            out,
+            numbers: options.number_formatter(),
+            line_started: false,
+        }
+    }
+
+    fn separate(&mut self) -> io::Result<()> {
+        if self.line_started {
+            self.out.write_all(b" ")?;
+        }
+        self.line_started = true;
+        Ok(())
+    }
+
+    fn value<T: FormatNumber>(&mut self, value: T) -> io::Result<&mut Self> {
+        self.separate()?;
+        self.out.write_all(self.numbers.format(value).as_bytes())?;
+        Ok(self)
+    }
+
+    fn values<T: FormatNumber>(
+        &mut self,
+        values: impl IntoIterator<Item = T>,
+    ) -> io::Result<&mut Self> {
+        for value in values {
+            self.value(value)?;
+        }
+        Ok(self)
+    }
+
+    /// A count followed by the values it counts
+    fn counted<T: FormatNumber>(&mut self, values: &[T]) -> io::Result<&mut Self> {
+        self.value(values.len())?.values(values.iter().copied())
+    }
+
+    fn quoted(&mut self, text: &str) -> io::Result<&mut Self> {
+        check_quotable(text)?;
+        self.separate()?;
+        write!(self.out, "\"{}\"", text)?;
+        Ok(self)
+    }
+
+    fn end_line(&mut self) -> io::Result<()> {
+        self.line_started = false;
+        self.out.write_all(b"\n")
+    }
+
+    /// A line holding a single value
+    fn line<T: FormatNumber>(&mut self, value: T) -> io::Result<()> {
+        self.value(value)?.end_line()
+    }
+
+    fn section(
+        &mut self,
+        name: &str,
+        body: impl FnOnce(&mut Self) -> io::Result<()>,
+    ) -> io::Result<()> {
+        writeln!(self.out, "${}", name)?;
+        body(self)?;
+        writeln!(self.out, "$End{}", name)
+    }
+}
+
+/// Write `mesh` as ASCII MSH 4.1
+pub(crate) fn write_mesh<W: Write>(
+    mesh: &Mesh,
+    options: &WriteOptions,
+    out: &mut W,
+) -> io::Result<()> {
+    let mut w = MshWriter::new(out, options);
+
+    w.section("MeshFormat", |w| {
+        let version = &mesh.format.version;
+        writeln!(
+            w.out,
+            "{}.{} 0 {}",
+            version.major, version.minor, mesh.format.data_size
+        )
+    })?;
+
+    if !mesh.physical_names.is_empty() {
+        w.section("PhysicalNames", |w| {
+            w.line(mesh.physical_names.len())?;
+            for name in &mesh.physical_names {
+                w.value(name.dimension as i32)?
+                    .value(name.tag)?
+                    .quoted(&name.name)?
+                    .end_line()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    if let Some(entities) = &mesh.entities {
+        w.section("Entities", |w| write_entities(w, entities))?;
+    }
+    if let Some(partitioned) = &mesh.partitioned_entities {
+        w.section("PartitionedEntities", |w| {
+            partitioned_entities::write(w, partitioned)
+        })?;
+    }
+
+    // The parser rejects empty $Nodes and $Elements sections but accepts missing ones
+    if !mesh.node_blocks.is_empty() {
+        w.section("Nodes", |w| write_nodes(w, mesh, options))?;
+    }
+    if !mesh.element_blocks.is_empty() || !mesh.compact_element_blocks.is_empty() {
+        w.section("Elements", |w| write_elements(w, mesh, options))?;
+    }
+
+    if !mesh.periodic_links.is_empty() {
+        w.section("Periodic", |w| {
+            w.line(mesh.periodic_links.len())?;
+            for link in &mesh.periodic_links {
+                w.value(link.entity_dim as i32)?
+                    .value(link.entity_tag)?
+                    .value(link.entity_tag_master)?
+                    .end_line()?;
+                w.counted(&link.affine_transform)?.end_line()?;
+                w.line(link.node_correspondences.len())?;
+                for &(node, master) in &link.node_correspondences {
+                    w.value(node)?.value(master)?.end_line()?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    if !mesh.ghost_elements.is_empty() {
+        w.section("GhostElements", |w| {
+            w.line(mesh.ghost_elements.len())?;
+            for ghost in &mesh.ghost_elements {
+                w.value(ghost.element_tag)?
+                    .value(ghost.partition_tag)?
+                    .counted(&ghost.ghost_partition_tags)?
+                    .end_line()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    if let Some(parametrizations) = &mesh.parametrizations {
+        w.section("Parametrizations", |w| {
+            write_parametrizations(w, parametrizations)
+        })?;
+    }
+
+    for scheme in &mesh.interpolation_schemes {
+        w.section("InterpolationScheme", |w| {
+            w.quoted(&scheme.name)?.end_line()?;
+            w.line(scheme.topologies.len())?;
+            for topology in &scheme.topologies {
+                w.line(topology.element_topology as i32)?;
+                w.line(topology.matrices.len())?;
+                for matrix in &topology.matrices {
+                    w.value(matrix.num_rows)?
+                        .value(matrix.num_columns)?
+                        .values(matrix.values.iter().copied())?
+                        .end_line()?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    for view in &mesh.node_data {
+        w.section("NodeData", |w| write_node_data(w, view))?;
+    }
+    for view in &mesh.element_data {
+        w.section("ElementData", |w| write_element_data(w, view))?;
+    }
+    for view in &mesh.element_node_data {
+        w.section("ElementNodeData", |w| write_element_node_data(w, view))?;
+    }
+
+    if !mesh.node_sets.is_empty() {
+        write_node_sets(w.out, &mesh.node_sets)?;
+    }
+    if !mesh.element_sets.is_empty() {
+        write_element_sets(w.out, &mesh.element_sets)?;
+    }
+
+    if let Some(frame) = &mesh.coordinate_system {
+        w.section("CoordinateSystem", |w| {
+            w.quoted(&frame.name)?.end_line()?;
+            writeln!(w.out, "{}", frame.unit.map_or("-", |unit| unit.symbol()))?;
+            for row in &frame.transform.matrix {
+                w.values(row.iter().copied())?.end_line()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    for section in &mesh.raw_sections {
+        w.section(&section.name, |w| {
+            for line in &section.lines {
+                writeln!(w.out, "{}", line)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+fn write_entities<W: Write>(w: &mut MshWriter<'_, W>, entities: &Entities) -> io::Result<()> {
+    w.value(entities.points.len())?
+        .value(entities.curves.len())?
+        .value(entities.surfaces.len())?
+        .value(entities.volumes.len())?
+        .end_line()?;
+    for point in &entities.points {
+        w.value(point.tag)?
+            .values([point.x, point.y, point.z])?
+            .counted(&point.physical_tags)?
+            .end_line()?;
+    }
+    for curve in &entities.curves {
+        w.value(curve.tag)?
+            .values([curve.min_x, curve.min_y, curve.min_z])?
+            .values([curve.max_x, curve.max_y, curve.max_z])?
+            .counted(&curve.physical_tags)?
+            .counted(&curve.bounding_points)?
+            .end_line()?;
+    }
+    for surface in &entities.surfaces {
+        w.value(surface.tag)?
+            .values([surface.min_x, surface.min_y, surface.min_z])?
+            .values([surface.max_x, surface.max_y, surface.max_z])?
+            .counted(&surface.physical_tags)?
+            .counted(&surface.bounding_curves)?
+            .end_line()?;
+    }
+    for volume in &entities.volumes {
+        w.value(volume.tag)?
+            .values([volume.min_x, volume.min_y, volume.min_z])?
+            .values([volume.max_x, volume.max_y, volume.max_z])?
+            .counted(&volume.physical_tags)?
+            .counted(&volume.bounding_surfaces)?
+            .end_line()?;
+    }
+    Ok(())
+}
+
+fn write_nodes<W: Write>(
+    w: &mut MshWriter<'_, W>,
+    mesh: &Mesh,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    let computed = mesh.computed_nodes_header();
+    let header = match mesh.nodes_header {
+        Some(declared) if options.keep_declared_headers => declared,
+        _ => computed,
+    };
+    w.value(computed.num_entity_blocks)?
+        .value(header.count)?
+        .value(header.min_tag)?
+        .value(header.max_tag)?
+        .end_line()?;
+
+    for block in &mesh.node_blocks {
+        w.value(block.entity_dim())?
+            .value(block.entity_tag)?
+            .value(u8::from(block.parametric))?
+            .value(block.nodes.len())?
+            .end_line()?;
+        for node in &block.nodes {
+            w.line(node.tag)?;
+        }
+        for node in &block.nodes {
+            w.values([node.x, node.y, node.z])?;
+            if let Some(parametric) = &node.parametric_coords {
+                w.values(parametric.iter().copied())?;
+            }
+            w.end_line()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_elements<W: Write>(
+    w: &mut MshWriter<'_, W>,
+    mesh: &Mesh,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    let computed = mesh.computed_elements_header();
+    let header = match mesh.elements_header {
+        Some(declared) if options.keep_declared_headers => declared,
+        _ => computed,
+    };
+    w.value(computed.num_entity_blocks)?
+        .value(header.count)?
+        .value(header.min_tag)?
+        .value(header.max_tag)?
+        .end_line()?;
+
+    for block in mesh.element_block_refs() {
+        w.value(block.entity_dim())?
+            .value(block.entity_tag())?
+            .value(block.element_type().to_i32())?
+            .value(block.len())?
+            .end_line()?;
+        for element in block.elements() {
+            w.value(element.tag())?
+                .values(element.nodes().iter().copied())?
+                .end_line()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_parametrizations<W: Write>(
+    w: &mut MshWriter<'_, W>,
+    parametrizations: &Parametrizations,
+) -> io::Result<()> {
+    w.value(parametrizations.curves.len())?
+        .value(parametrizations.surfaces.len())?
+        .end_line()?;
+    for curve in &parametrizations.curves {
+        w.line(curve.curve_tag)?;
+        w.line(curve.nodes.len())?;
+        for node in &curve.nodes {
+            w.values([node.x, node.y, node.z, node.u])?.end_line()?;
+        }
+    }
+    for surface in &parametrizations.surfaces {
+        w.line(surface.surface_tag)?;
+        w.value(surface.nodes.len())?
+            .value(surface.triangles.len())?
+            .end_line()?;
+        for node in &surface.nodes {
+            w.values([node.x, node.y, node.z, node.u, node.v])?
+                .values([node.curv_max_x, node.curv_max_y, node.curv_max_z])?
+                .values([node.curv_min_x, node.curv_min_y, node.curv_min_z])?
+                .end_line()?;
+        }
+        for triangle in &surface.triangles {
+            w.values([
+                triangle.node_index1,
+                triangle.node_index2,
+                triangle.node_index3,
+            ])?
+            .end_line()?;
+        }
+    }
+    Ok(())
+}
+
+/// String, real and integer tags of a view
+///
+/// The number of components and of data lines are taken from the data, so
+/// that the header always describes what follows.
+fn write_view_tags<W: Write>(
+    w: &mut MshWriter<'_, W>,
+    string_tags: &[String],
+    real_tags: &[f64],
+    integer_tags: &[i32],
+    components: Option<usize>,
+    entities: usize,
+) -> io::Result<()> {
+    w.line(string_tags.len())?;
+    for tag in string_tags {
+        w.quoted(tag)?.end_line()?;
+    }
+    w.line(real_tags.len())?;
+    for &tag in real_tags {
+        w.line(tag)?;
+    }
+
+    let mut integer_tags = integer_tags.to_vec();
+    if integer_tags.len() < 3 {
+        integer_tags.resize(3, 0);
+        integer_tags[1] = components.unwrap_or(1) as i32;
+    }
+    integer_tags[2] = entities as i32;
+    w.line(integer_tags.len())?;
+    for tag in integer_tags {
+        w.line(tag)?;
+    }
+    Ok(())
+}
+
+fn write_node_data<W: Write>(w: &mut MshWriter<'_, W>, view: &NodeData) -> io::Result<()> {
+    let components = view.data.first().map(|(_, values)| values.len());
+    write_view_tags(
+        w,
+        &view.string_tags,
+        &view.real_tags,
+        &view.integer_tags,
+        components,
+        view.data.len(),
+    )?;
+    for (tag, values) in &view.data {
+        w.value(*tag)?.values(values.iter().copied())?.end_line()?;
+    }
+    Ok(())
+}
+
+fn write_element_data<W: Write>(w: &mut MshWriter<'_, W>, view: &ElementData) -> io::Result<()> {
+    let components = view.data.first().map(|(_, values)| values.len());
+    write_view_tags(
+        w,
+        &view.string_tags,
+        &view.real_tags,
+        &view.integer_tags,
+        components,
+        view.data.len(),
+    )?;
+    for (tag, values) in &view.data {
+        w.value(*tag)?.values(values.iter().copied())?.end_line()?;
+    }
+    Ok(())
+}
+
+fn write_element_node_data<W: Write>(
+    w: &mut MshWriter<'_, W>,
+    view: &ElementNodeData,
+) -> io::Result<()> {
+    let components = view
+        .data
+        .first()
+        .map(|(_, nodes, values)| values.len() / (*nodes).max(1));
+    write_view_tags(
+        w,
+        &view.string_tags,
+        &view.real_tags,
+        &view.integer_tags,
+        components,
+        view.data.len(),
+    )?;
+    for (tag, nodes, values) in &view.data {
+        w.value(*tag)?
+            .value(*nodes)?
+            .values(values.iter().copied())?
+            .end_line()?;
+    }
+    Ok(())
+}
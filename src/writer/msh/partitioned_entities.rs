@@ -0,0 +1,91 @@
+//! `$PartitionedEntities` output, for meshes partitioned by Gmsh
+
+use super::MshWriter;
+use crate::types::PartitionedEntities;
+use std::io::{self, Write};
+
+/// Write the body of `$PartitionedEntities`: ghost entities, then partitioned
+/// points, curves, surfaces and volumes with their parent, partitions and
+/// bounding data
+pub(super) fn write<W: Write>(
+    w: &mut MshWriter<'_, W>,
+    partitioned: &PartitionedEntities,
+) -> io::Result<()> {
+    w.line(partitioned.num_partitions)?;
+    w.line(partitioned.ghost_entities.len())?;
+    for ghost in &partitioned.ghost_entities {
+        w.value(ghost.tag)?.value(ghost.partition)?.end_line()?;
+    }
+    w.value(partitioned.points.len())?
+        .value(partitioned.curves.len())?
+        .value(partitioned.surfaces.len())?
+        .value(partitioned.volumes.len())?
+        .end_line()?;
+    for point in &partitioned.points {
+        w.value(point.tag)?
+            .value(point.parent_dim as i32)?
+            .value(point.parent_tag)?
+            .counted(&point.partition_tags)?
+            .values([point.x, point.y, point.z])?
+            .counted(&point.physical_tags)?
+            .end_line()?;
+    }
+    for curve in &partitioned.curves {
+        w.value(curve.tag)?
+            .value(curve.parent_dim as i32)?
+            .value(curve.parent_tag)?
+            .counted(&curve.partition_tags)?
+            .values([curve.min_x, curve.min_y, curve.min_z])?
+            .values([curve.max_x, curve.max_y, curve.max_z])?
+            .counted(&curve.physical_tags)?
+            .counted(&curve.bounding_points)?
+            .end_line()?;
+    }
+    for surface in &partitioned.surfaces {
+        w.value(surface.tag)?
+            .value(surface.parent_dim as i32)?
+            .value(surface.parent_tag)?
+            .counted(&surface.partition_tags)?
+            .values([surface.min_x, surface.min_y, surface.min_z])?
+            .values([surface.max_x, surface.max_y, surface.max_z])?
+            .counted(&surface.physical_tags)?
+            .counted(&surface.bounding_curves)?
+            .end_line()?;
+    }
+    for volume in &partitioned.volumes {
+        w.value(volume.tag)?
+            .value(volume.parent_dim as i32)?
+            .value(volume.parent_tag)?
+            .counted(&volume.partition_tags)?
+            .values([volume.min_x, volume.min_y, volume.min_z])?
+            .values([volume.max_x, volume.max_y, volume.max_z])?
+            .counted(&volume.physical_tags)?
+            .counted(&volume.bounding_surfaces)?
+            .end_line()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_msh;
+    use crate::writer::write_msh;
+
+    #[test]
+    fn test_partitioned_entities_round_trip() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $PartitionedEntities\n2\n1\n3 2\n1 1 0 0\n\
+            1 0 1 1 1 0 0 0 0\n\
+            2 1 1 2 1 2 0 0 0 1 0 0 1 5 2 1 -1\n\
+            $EndPartitionedEntities\n";
+        let mesh = parse_msh(content).unwrap();
+        let written = write_msh(&mesh).unwrap();
+        assert!(written.contains("$PartitionedEntities\n2\n1\n3 2\n1 1 0 0\n"));
+
+        let reparsed = parse_msh(written).unwrap();
+        assert_eq!(reparsed.partitioned_entities, mesh.partitioned_entities);
+        let partitioned = reparsed.partitioned_entities.unwrap();
+        assert_eq!(partitioned.curves[0].partition_tags, [1, 2]);
+        assert_eq!(partitioned.curves[0].max_x, 1.0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Number formatting for the text writers
+//!
+//! Reals are printed with Ryu and integers with itoa, into buffers reused from
+//! one value to the next. The output does not depend on the platform or locale,
+//! and no allocation happens per value.
+
+use super::quantize::round_significant_with;
+use crate::types::{ElementTag, EntityTag, NodeTag, PhysicalTag};
+
+/// How many digits of real numbers are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RealPrecision {
+    /// Shortest representation that reads back to the same `f64`
+    #[default]
+    Shortest,
+    /// Round to this many significant digits (clamped to `1..=17`), see
+    /// [`round_significant`](super::round_significant)
+    Significant(u32),
+}
+
+/// Formats numbers into reusable buffers
+pub struct NumberFormatter {
+    precision: RealPrecision,
+    reals: ryu::Buffer,
+    integers: itoa::Buffer,
+    scratch: String,
+}
+
+impl NumberFormatter {
+    pub fn new(precision: RealPrecision) -> Self {
+        Self {
+            precision,
+            reals: ryu::Buffer::new(),
+            integers: itoa::Buffer::new(),
+            scratch: String::new(),
+        }
+    }
+
+    pub fn precision(&self) -> RealPrecision {
+        self.precision
+    }
+
+    /// Format any supported number
+    pub fn format<T: FormatNumber>(&mut self, value: T) -> &str {
+        value.format_with(self)
+    }
+
+    /// Format a real number
+    ///
+    /// Integral values are written without a trailing `.0`; infinities and NaN
+    /// as `inf`, `-inf` and `NaN`.
+    pub fn real(&mut self, value: f64) -> &str {
+        let value = match self.precision {
+            RealPrecision::Shortest => value,
+            RealPrecision::Significant(digits) => {
+                round_significant_with(value, digits, &mut self.scratch)
+            }
+        };
+        let text = self.reals.format(value);
+        text.strip_suffix(".0").unwrap_or(text)
+    }
+}
+
+impl Default for NumberFormatter {
+    fn default() -> Self {
+        Self::new(RealPrecision::default())
+    }
+}
+
+/// Numbers the [`NumberFormatter`] can write
+pub trait FormatNumber: Copy {
+    fn format_with(self, formatter: &mut NumberFormatter) -> &str;
+}
+
+impl FormatNumber for f64 {
+    fn format_with(self, formatter: &mut NumberFormatter) -> &str {
+        formatter.real(self)
+    }
+}
+
+macro_rules! format_integer {
+    ($($int:ty),*) => {
+        $(
+            impl FormatNumber for $int {
+                fn format_with(self, formatter: &mut NumberFormatter) -> &str {
+                    formatter.integers.format(self)
+                }
+            }
+        )*
+    };
+}
+
+format_integer!(u8, i32, u32, i64, u64, usize);
+
+macro_rules! format_tag {
+    ($($tag:ty),*) => {
+        $(
+            impl FormatNumber for $tag {
+                fn format_with(self, formatter: &mut NumberFormatter) -> &str {
+                    self.0.format_with(formatter)
+                }
+            }
+        )*
+    };
+}
+
+format_tag!(NodeTag, ElementTag, EntityTag, PhysicalTag);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortest_reals_round_trip() {
+        let mut formatter = NumberFormatter::default();
+        assert_eq!(formatter.real(1.0), "1");
+        assert_eq!(formatter.real(-0.5), "-0.5");
+        assert_eq!(formatter.real(1e-7), "1e-7");
+        assert_eq!(formatter.real(f64::INFINITY), "inf");
+        for value in [0.1, 1.0 / 3.0, 6.02214076e23, -2.5e-300] {
+            assert_eq!(formatter.real(value).parse::<f64>().unwrap(), value);
+        }
+        assert_eq!(formatter.format(NodeTag(42)), "42");
+        assert_eq!(formatter.format(-7i32), "-7");
+    }
+
+    #[test]
+    fn test_significant_digits() {
+        let mut formatter = NumberFormatter::new(RealPrecision::Significant(4));
+        assert_eq!(formatter.real(1.0 / 3.0), "0.3333");
+        assert_eq!(formatter.real(123456.0), "123500");
+        assert_eq!(formatter.real(-2.0), "-2");
+        assert_eq!(formatter.real(0.0), "0");
+    }
+}
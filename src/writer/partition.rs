@@ -0,0 +1,288 @@
+//! One mesh per partition, mirroring Gmsh's `Mesh.PartitionSplitMeshFiles`
+
+use super::write_msh_file;
+use crate::error::{ParseError, Result};
+use crate::types::{
+    ElementBlockCompact, ElementBlockRef, ElementTag, EntityTag, Mesh, NodeBlock, NodeTag,
+    PartitionedEntities,
+};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+impl Mesh {
+    /// Split a partitioned mesh into one mesh per partition
+    ///
+    /// Partition `p` keeps the node and element blocks, compact ones included,
+    /// of the partitioned entities that belong to `p`, plus any node of another
+    /// block referenced by its elements. Its `$PartitionedEntities` lists only
+    /// those entities, the entities of the borrowed nodes and the ghost entities
+    /// of `p`; ghost elements, periodic links, post-processing data and sets are
+    /// restricted to what the partition contains. Physical names, model
+    /// entities, parametrizations, interpolation schemes and the coordinate
+    /// system are copied as they are.
+    ///
+    /// Returns `(partition tag, mesh)` pairs in increasing partition order.
+    ///
+    /// # Errors
+    /// Returns an error if the mesh has no `$PartitionedEntities` section.
+    pub fn split_partitions(&self) -> Result<Vec<(i32, Mesh)>> {
+        let partitioned = self
+            .partitioned_entities
+            .as_ref()
+            .ok_or_else(|| ParseError::MissingSection("PartitionedEntities".to_string()))?;
+
+        Ok((1..=partitioned.num_partitions as i32)
+            .map(|partition| (partition, self.partition(partitioned, partition)))
+            .collect())
+    }
+
+    /// Write one MSH file per partition, named `{prefix}_{partition}.msh`
+    ///
+    /// Returns the paths of the written files, see [`Mesh::split_partitions`].
+    ///
+    /// # Errors
+    /// Returns an error if the mesh is not partitioned or a file cannot be written.
+    pub fn write_partitioned(&self, prefix: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for (partition, mesh) in self.split_partitions()? {
+            let mut name = OsString::from(prefix.as_ref());
+            name.push(format!("_{}.msh", partition));
+            let path = PathBuf::from(name);
+            write_msh_file(&mesh, &path)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    fn partition(&self, partitioned: &PartitionedEntities, partition: i32) -> Mesh {
+        let no_borrowed = HashSet::new();
+        let owned = partitioned.restrict_to(partition, &no_borrowed);
+        let entities: HashSet<(i32, EntityTag)> = owned.entity_keys().collect();
+
+        let blocks: Vec<ElementBlockRef> = self
+            .element_block_refs()
+            .filter(|b| entities.contains(&(b.entity_dim(), b.entity_tag())))
+            .collect();
+        let element_tags: HashSet<ElementTag> = blocks
+            .iter()
+            .flat_map(|b| b.elements().map(|e| e.tag()))
+            .collect();
+
+        let (kept, others): (Vec<&NodeBlock>, Vec<&NodeBlock>) = self
+            .node_blocks
+            .iter()
+            .partition(|b| entities.contains(&(b.entity_dim(), b.entity_tag)));
+        let mut node_blocks: Vec<NodeBlock> = kept.into_iter().cloned().collect();
+        let mut node_tags: HashSet<NodeTag> = node_blocks
+            .iter()
+            .flat_map(|b| b.nodes.iter().map(|n| n.tag))
+            .collect();
+        let missing: HashSet<NodeTag> = blocks
+            .iter()
+            .flat_map(|b| b.elements().flat_map(|e| e.nodes().iter().copied()))
+            .filter(|tag| !node_tags.contains(tag))
+            .collect();
+
+        let mut borrowed = HashSet::new();
+        if !missing.is_empty() {
+            for block in others {
+                let nodes: Vec<_> = block
+                    .nodes
+                    .iter()
+                    .filter(|n| missing.contains(&n.tag))
+                    .cloned()
+                    .collect();
+                if !nodes.is_empty() {
+                    borrowed.insert((block.entity_dim(), block.entity_tag));
+                    node_blocks.push(NodeBlock {
+                        nodes,
+                        ..block.clone()
+                    });
+                }
+            }
+            node_tags.extend(missing);
+        }
+
+        let mut mesh = Mesh::new(self.format.clone());
+        mesh.physical_names = self.physical_names.clone();
+        mesh.entities = self.entities.clone();
+        mesh.partitioned_entities = Some(partitioned.restrict_to(partition, &borrowed));
+        mesh.node_blocks = node_blocks;
+        // Compact blocks stay compact, at their position among the kept blocks
+        for (position, block) in blocks.into_iter().enumerate() {
+            match block {
+                ElementBlockRef::Expanded(block) => mesh.element_blocks.push(block.clone()),
+                ElementBlockRef::Compact(block) => {
+                    mesh.compact_element_blocks.push(ElementBlockCompact {
+                        position,
+                        ..block.clone()
+                    })
+                }
+            }
+        }
+        mesh.periodic_links = self
+            .periodic_links
+            .iter()
+            .filter(|l| entities.contains(&(l.entity_dim as i32, l.entity_tag)))
+            .cloned()
+            .collect();
+        mesh.ghost_elements = self
+            .ghost_elements
+            .iter()
+            .filter(|g| element_tags.contains(&g.element_tag))
+            .cloned()
+            .collect();
+        mesh.parametrizations = self.parametrizations.clone();
+        mesh.interpolation_schemes = self.interpolation_schemes.clone();
+        mesh.coordinate_system = self.coordinate_system.clone();
+
+        mesh.node_data = self.node_data.clone();
+        for view in &mut mesh.node_data {
+            view.data.retain(|(tag, _)| node_tags.contains(tag));
+        }
+        mesh.element_data = self.element_data.clone();
+        for view in &mut mesh.element_data {
+            view.data.retain(|(tag, _)| element_tags.contains(tag));
+        }
+        mesh.element_node_data = self.element_node_data.clone();
+        for view in &mut mesh.element_node_data {
+            view.data.retain(|(tag, _, _)| element_tags.contains(tag));
+        }
+
+        mesh.node_sets = self.node_sets.clone();
+        for set in &mut mesh.node_sets {
+            set.tags.retain(|tag| node_tags.contains(tag));
+        }
+        mesh.raw_sections = self.raw_sections.clone();
+        mesh.element_sets = self.element_sets.clone();
+        for set in &mut mesh.element_sets {
+            set.tags.retain(|tag| element_tags.contains(tag));
+        }
+        mesh
+    }
+}
+
+impl PartitionedEntities {
+    /// The entities belonging to `partition` or listed in `borrowed`, and the
+    /// ghost entities of `partition`
+    fn restrict_to(
+        &self,
+        partition: i32,
+        borrowed: &HashSet<(i32, EntityTag)>,
+    ) -> PartitionedEntities {
+        let keep = |dim: i32, tag: EntityTag, partition_tags: &[i32]| {
+            partition_tags.contains(&partition) || borrowed.contains(&(dim, tag))
+        };
+        PartitionedEntities {
+            num_partitions: self.num_partitions,
+            ghost_entities: self
+                .ghost_entities
+                .iter()
+                .filter(|g| g.partition == partition)
+                .cloned()
+                .collect(),
+            points: self
+                .points
+                .iter()
+                .filter(|e| keep(0, e.tag, &e.partition_tags))
+                .cloned()
+                .collect(),
+            curves: self
+                .curves
+                .iter()
+                .filter(|e| keep(1, e.tag, &e.partition_tags))
+                .cloned()
+                .collect(),
+            surfaces: self
+                .surfaces
+                .iter()
+                .filter(|e| keep(2, e.tag, &e.partition_tags))
+                .cloned()
+                .collect(),
+            volumes: self
+                .volumes
+                .iter()
+                .filter(|e| keep(3, e.tag, &e.partition_tags))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// `(dim, tag)` of every partitioned entity
+    fn entity_keys(&self) -> impl Iterator<Item = (i32, EntityTag)> + '_ {
+        let points = self.points.iter().map(|e| (0, e.tag));
+        let curves = self.curves.iter().map(|e| (1, e.tag));
+        let surfaces = self.surfaces.iter().map(|e| (2, e.tag));
+        let volumes = self.volumes.iter().map(|e| (3, e.tag));
+        points.chain(curves).chain(surfaces).chain(volumes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::PARTITIONED;
+    use crate::types::{CoordinateSystem, ElementTag, EntityTag, Mesh};
+    use crate::{parse_msh, parse_msh_file};
+
+    #[test]
+    fn test_split_partitions() {
+        let mesh = parse_msh(PARTITIONED).unwrap();
+        let parts = mesh.split_partitions().unwrap();
+        assert_eq!(parts.len(), 2);
+        for (_, part) in &parts {
+            part.validate().unwrap();
+            assert_eq!(part.num_elements(), 1);
+        }
+        let surfaces = |part: &Mesh| -> Vec<EntityTag> {
+            let partitioned = part.partitioned_entities.as_ref().unwrap();
+            partitioned.surfaces.iter().map(|s| s.tag).collect()
+        };
+        assert_eq!(surfaces(&parts[0].1), [EntityTag(2)]);
+        // The second partition borrows nodes 1 and 3 from surface 2
+        assert_eq!(parts[1].1.num_nodes(), 3);
+        assert_eq!(surfaces(&parts[1].1), [EntityTag(2), EntityTag(3)]);
+    }
+
+    #[test]
+    fn test_split_keeps_coordinate_system() {
+        let mut mesh = parse_msh(PARTITIONED).unwrap();
+        mesh.coordinate_system = Some(CoordinateSystem::new("part 1"));
+        for (_, part) in mesh.split_partitions().unwrap() {
+            assert_eq!(part.coordinate_system, mesh.coordinate_system);
+        }
+    }
+
+    #[test]
+    fn test_split_compact_partitions() {
+        let mut mesh = parse_msh(PARTITIONED).unwrap();
+        mesh.compact_storage();
+        let parts = mesh.split_partitions().unwrap();
+        for (partition, part) in &parts {
+            part.validate().unwrap();
+            assert!(part.element_blocks.is_empty());
+            assert_eq!(part.compact_element_blocks.len(), 1);
+            let tags: Vec<ElementTag> = part.elements().map(|e| e.tag()).collect();
+            assert_eq!(tags, [ElementTag(*partition as u64)]);
+        }
+        assert_eq!(parts[1].1.num_nodes(), 3);
+    }
+
+    #[test]
+    fn test_write_partitioned() {
+        let mesh = parse_msh(PARTITIONED).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let paths = mesh.write_partitioned(dir.path().join("square")).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].ends_with("square_1.msh"));
+        assert!(paths[1].ends_with("square_2.msh"));
+        let part = parse_msh_file(&paths[1]).unwrap();
+        assert_eq!(part.element_blocks[0].elements[0].nodes.len(), 3);
+    }
+
+    #[test]
+    fn test_split_requires_partitions() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n";
+        assert!(parse_msh(content).unwrap().split_partitions().is_err());
+    }
+}
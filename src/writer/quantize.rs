@@ -0,0 +1,107 @@
+//! Rounding of node coordinates to significant digits
+
+use crate::geometry::distance;
+use crate::types::{Change, Mesh, NodeTag};
+use std::fmt::Write;
+
+/// Round `value` to `digits` significant decimal digits
+///
+/// Rounding goes through the decimal representation, so the result prints
+/// with at most `digits` significant digits. Zero, infinities and NaN are
+/// returned unchanged; `digits` is clamped to `1..=17`.
+pub fn round_significant(value: f64, digits: u32) -> f64 {
+    round_significant_with(value, digits, &mut String::new())
+}
+
+/// [`round_significant`] formatting into `scratch`, so that rounding many
+/// values does not allocate for each
+pub(crate) fn round_significant_with(value: f64, digits: u32, scratch: &mut String) -> f64 {
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+    let precision = digits.clamp(1, 17) as usize - 1;
+    scratch.clear();
+    let _ = write!(scratch, "{:.*e}", precision, value);
+    scratch.parse().unwrap_or(value)
+}
+
+/// Effect of rounding the node coordinates of a mesh
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuantizationReport {
+    pub significant_digits: u32,
+    /// Number of nodes whose position changed
+    pub nodes_moved: usize,
+    /// Largest distance a node moved
+    pub max_displacement: f64,
+    /// Tag of the node that moved the most
+    pub max_displacement_node: Option<NodeTag>,
+}
+
+impl Mesh {
+    /// Round all node coordinates to `digits` significant digits
+    ///
+    /// Parametric coordinates are left untouched; entity bounding boxes are
+    /// recomputed if any node moved. The rounding is recorded in the
+    /// [`Mesh::change_log`].
+    pub fn quantize_coordinates(&mut self, digits: u32) -> QuantizationReport {
+        let mut report = QuantizationReport {
+            significant_digits: digits,
+            ..Default::default()
+        };
+
+        for node in self.node_blocks.iter_mut().flat_map(|b| b.nodes.iter_mut()) {
+            let before = [node.x, node.y, node.z];
+            node.x = round_significant(node.x, digits);
+            node.y = round_significant(node.y, digits);
+            node.z = round_significant(node.z, digits);
+
+            let moved = distance(before, [node.x, node.y, node.z]);
+            if moved > 0.0 {
+                report.nodes_moved += 1;
+                if moved > report.max_displacement {
+                    report.max_displacement = moved;
+                    report.max_displacement_node = Some(node.tag);
+                }
+            }
+        }
+
+        if report.nodes_moved > 0 {
+            if let Some(entities) = self.entities.as_mut() {
+                entities.recompute_bounds(&self.node_blocks);
+            }
+        }
+        self.record_change(
+            Change::new("quantize_coordinates")
+                .with_parameter("significant_digits", digits)
+                .with_count("nodes_moved", report.nodes_moved),
+        );
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_significant() {
+        assert_eq!(round_significant(0.123456789, 3), 0.123);
+        assert_eq!(round_significant(-98765.4321, 2), -99000.0);
+        assert_eq!(round_significant(1.0000000000000002, 6), 1.0);
+        assert_eq!(round_significant(0.0, 3), 0.0);
+        assert_eq!(round_significant(1.23456e-300, 2), 1.2e-300);
+    }
+
+    #[test]
+    fn test_quantize_reports_displacement() {
+        let content = "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n\
+            $Nodes\n1 2 1 2\n0 1 0 2\n1\n2\n0 0 0\n0.123456 0 0\n$EndNodes\n";
+        let mut mesh = crate::parse_msh(content).unwrap();
+        let report = mesh.quantize_coordinates(3);
+
+        assert_eq!(report.nodes_moved, 1);
+        assert!((report.max_displacement - 0.000456).abs() < 1e-12);
+        assert_eq!(mesh.node_blocks[0].nodes[1].x, 0.123);
+        assert_eq!(mesh.quantize_coordinates(3).nodes_moved, 0);
+    }
+}
@@ -0,0 +1,12 @@
+// Helpers shared by the integration tests
+
+use gmsh_parser::types::ElementType;
+use gmsh_parser::Mesh;
+
+pub fn count_elements(mesh: &Mesh, element_type: ElementType) -> usize {
+    mesh.element_blocks
+        .iter()
+        .filter(|b| b.element_type == element_type)
+        .map(|b| b.elements.len())
+        .sum()
+}
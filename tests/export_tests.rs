@@ -0,0 +1,117 @@
+// VTU export, flat arrays, the Gmsh API views, the meshio-style IR and the
+// mesh summary, checked on t1.msh
+
+mod common;
+
+use common::count_elements;
+use gmsh_parser::types::ElementType;
+use gmsh_parser::{parse_msh_file, Mesh};
+
+const T1: &str = "tests/data/gmsh_official/t1.msh";
+
+#[test]
+fn test_export_vtu_t1() {
+    use gmsh_parser::export::ExportFormat;
+    use gmsh_parser::filter::ElementFilter;
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let mut out = Vec::new();
+    mesh.export()
+        .filter(ElementFilter::dimension(2))
+        .format(ExportFormat::Vtu)
+        .to_writer(&mut out)
+        .unwrap();
+    let cells = format!(
+        "NumberOfCells=\"{}\"",
+        count_elements(&mesh, ElementType::Triangle3)
+    );
+    assert!(String::from_utf8(out).unwrap().contains(&cells));
+}
+
+#[test]
+fn test_arrays_t1() {
+    use gmsh_parser::export::ArrayOptions;
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let arrays = mesh.to_arrays(&ArrayOptions::default()).unwrap();
+    assert_eq!(arrays.num_nodes(), mesh.num_nodes());
+    assert_eq!(arrays.num_elements(), mesh.num_elements());
+    // The last element maps back to its node tags
+    let block = mesh.element_blocks.last().unwrap();
+    let expected = &block.elements.last().unwrap().nodes;
+    let last = arrays.num_elements() - 1;
+    let tags: Vec<_> = arrays
+        .element_nodes(last)
+        .iter()
+        .map(|&i| arrays.node_tags[i])
+        .collect();
+    assert_eq!(&tags, expected);
+}
+
+#[test]
+fn test_gmsh_api_t1() {
+    use gmsh_parser::compat::gmsh_api::{get_elements_by_type, get_nodes, get_physical_groups};
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let (all, _, _) = get_nodes(&mesh, -1, -1, false, false);
+    assert_eq!(all.len(), mesh.num_nodes());
+    let (interior, _, _) = get_nodes(&mesh, 2, 1, false, false);
+    let (closure, _, _) = get_nodes(&mesh, 2, 1, true, false);
+    assert!(interior.len() < closure.len());
+    let (elements, nodes) = get_elements_by_type(&mesh, 2, 1);
+    assert_eq!(
+        elements.len(),
+        count_elements(&mesh, ElementType::Triangle3)
+    );
+    assert!(nodes.iter().all(|n| closure.contains(n)));
+
+    let groups = get_physical_groups(&mesh, -1);
+    for name in &mesh.physical_names {
+        assert!(groups.contains(&(name.dimension as i32, name.tag)));
+    }
+}
+
+#[test]
+fn test_unstructured_mesh_t1() {
+    use gmsh_parser::ir::UnstructuredMesh;
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let ir = UnstructuredMesh::from(&mesh);
+    assert_eq!(ir.points.len(), mesh.num_nodes());
+    assert_eq!(ir.num_cells(), mesh.num_elements());
+    assert_eq!(ir.field_data.len(), mesh.physical_names.len());
+
+    let back = Mesh::try_from(&ir).unwrap();
+    assert_eq!(back.num_elements(), mesh.num_elements());
+    for name in &mesh.physical_names {
+        let key = (name.dimension as i32, name.tag);
+        let before = mesh.physical_group(key).unwrap().num_elements();
+        assert_eq!(back.physical_group(key).unwrap().num_elements(), before);
+    }
+    assert_eq!(UnstructuredMesh::from(back), ir);
+}
+
+#[test]
+fn test_summary_t1() {
+    let mesh = parse_msh_file(T1).unwrap();
+    let summary = mesh.summary();
+    assert_eq!(summary.num_nodes, mesh.num_nodes());
+    let by_type: usize = summary.elements_by_type.iter().map(|t| t.1).sum();
+    assert_eq!(by_type, mesh.num_elements());
+    let by_dim: usize = summary.dimensions.iter().map(|d| d.elements).sum();
+    assert_eq!(by_dim, mesh.num_elements());
+
+    for name in &mesh.physical_names {
+        let key = (name.dimension as i32, name.tag);
+        let group = summary
+            .physical_groups
+            .iter()
+            .find(|g| (g.dim, g.tag) == key)
+            .unwrap();
+        assert_eq!(group.name.as_ref(), Some(&name.name));
+        assert_eq!(
+            group.elements,
+            mesh.physical_group(key).unwrap().num_elements()
+        );
+    }
+}
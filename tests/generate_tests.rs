@@ -0,0 +1,119 @@
+// Extrusion, mirroring, merging and node renumbering (contiguous and RCM),
+// checked on t1.msh and box.msh
+
+mod common;
+
+use common::count_elements;
+use gmsh_parser::types::ElementType;
+use gmsh_parser::{parse_msh_file, Mesh};
+
+const T1: &str = "tests/data/gmsh_official/t1.msh";
+const BOX: &str = "tests/data/valid/box.msh";
+
+#[test]
+fn test_extrude_t1() {
+    let mesh = parse_msh_file(T1).unwrap();
+    let extruded = gmsh_parser::generate::extrude(&mesh, [0.0, 0.0, 0.2], 3).unwrap();
+    assert_eq!(
+        count_elements(&extruded, ElementType::Prism6),
+        count_elements(&mesh, ElementType::Triangle3) * 3
+    );
+    assert!(extruded
+        .physical_names
+        .iter()
+        .any(|pn| pn.dimension as i32 == 3 && pn.name == "My surface"));
+}
+
+#[test]
+fn test_mirror_t1() {
+    use gmsh_parser::geometry::Plane;
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let on_plane = mesh
+        .node_blocks
+        .iter()
+        .flat_map(|b| b.nodes.iter())
+        .filter(|n| (n.x - 0.1).abs() < 1e-12)
+        .count();
+    let plane = Plane::new([0.1, 0.0, 0.0], [1.0, 0.0, 0.0]);
+    let mirrored = gmsh_parser::generate::mirror(&mesh, &plane).unwrap();
+    assert_eq!(mirrored.num_nodes(), 2 * mesh.num_nodes() - on_plane);
+}
+
+#[test]
+fn test_merge_t1() {
+    use gmsh_parser::generate::MergeOptions;
+
+    let part = parse_msh_file(T1).unwrap();
+    let mut neighbour = part.clone();
+    for n in neighbour.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+        n.x += 0.1;
+    }
+    if let Some(entities) = neighbour.entities.as_mut() {
+        entities.recompute_bounds(&neighbour.node_blocks);
+        for p in &mut entities.points {
+            p.x += 0.1;
+        }
+    }
+
+    let merged = part.merge(&neighbour, &MergeOptions::default()).unwrap();
+    assert_eq!(merged.num_nodes(), 2 * part.num_nodes());
+    merged.validate().unwrap();
+
+    let fused = part
+        .merge(&neighbour, &MergeOptions::default().fuse_nodes())
+        .unwrap();
+    let on_interface = part
+        .node_blocks
+        .iter()
+        .flat_map(|b| &b.nodes)
+        .filter(|n| (n.x - 0.1).abs() < 1e-12)
+        .count();
+    assert_eq!(fused.num_nodes(), 2 * part.num_nodes() - on_interface);
+    assert!(fused.num_elements() < merged.num_elements());
+}
+
+#[test]
+fn test_renumber_contiguous_box() {
+    use gmsh_parser::types::{ElementTag, NodeTag};
+
+    let mut mesh = parse_msh_file(BOX).unwrap();
+    for n in mesh.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+        n.tag = NodeTag(n.tag.0 * 3);
+    }
+    for e in mesh.element_blocks.iter_mut().flat_map(|b| &mut b.elements) {
+        e.tag = ElementTag(e.tag.0 * 2 + 5);
+        e.nodes.iter_mut().for_each(|n| *n = NodeTag(n.0 * 3));
+    }
+    for ghost in &mut mesh.ghost_elements {
+        ghost.element_tag = ElementTag(ghost.element_tag.0 * 2 + 5);
+    }
+
+    mesh.renumber_contiguous();
+    assert!(!mesh.tag_gaps().needs_renumbering());
+    mesh.validate().unwrap();
+    let mut ghosts = mesh.ghost_elements.clone();
+    ghosts.sort_by_key(|g| (g.partition_tag, g.element_tag));
+    assert_eq!(mesh.compute_ghost_elements().unwrap(), ghosts);
+}
+
+#[test]
+fn test_renumber_rcm_t1() {
+    // Largest difference between the tags of two nodes of an element
+    let bandwidth = |mesh: &Mesh| {
+        mesh.element_blocks
+            .iter()
+            .flat_map(|b| &b.elements)
+            .map(|e| {
+                let tags = e.nodes.iter().map(|n| n.0);
+                tags.clone().max().unwrap() - tags.min().unwrap()
+            })
+            .max()
+            .unwrap()
+    };
+    let mut mesh = parse_msh_file(T1).unwrap();
+    let before = bandwidth(&mesh);
+    mesh.renumber_rcm();
+    mesh.validate().unwrap();
+    assert!(bandwidth(&mesh) < before);
+}
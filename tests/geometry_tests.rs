@@ -0,0 +1,123 @@
+// Closest points, bounding boxes, profiles, overlap detection, rigid
+// transforms, scaling and unit conversion, checked on t1.msh, t13.msh, t18.msh
+// and box.msh
+
+mod common;
+
+use common::count_elements;
+use gmsh_parser::parse_msh_file;
+use gmsh_parser::types::ElementType;
+
+const T1: &str = "tests/data/gmsh_official/t1.msh";
+const BOX: &str = "tests/data/valid/box.msh";
+
+#[test]
+fn test_closest_point_t13() {
+    let mesh = parse_msh_file("tests/data/gmsh_official/t13.msh").unwrap();
+    let surface = &mesh.parametrizations.as_ref().unwrap().surfaces[0];
+    for node in surface.nodes.iter().step_by(7) {
+        let p = [node.x, node.y, node.z];
+        let (u, v, q) =
+            gmsh_parser::geometry::closest_point(&mesh, surface.surface_tag, p).unwrap();
+        assert!((0..3).all(|i| (q[i] - p[i]).abs() < 1e-12));
+        assert!((u - node.u).abs() < 1e-9 && (v - node.v).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_bounding_boxes_t1() {
+    use gmsh_parser::types::PhysicalTag;
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let (min, max) = mesh.bounding_box().unwrap();
+    assert_eq!(min, [0.0; 3]);
+    assert!((max[0] - 0.1).abs() < 1e-12 && (max[1] - 0.3).abs() < 1e-12);
+
+    let groups = mesh.physical_group_bounding_boxes();
+    assert_eq!(groups[&(2, PhysicalTag(6))], (min, max));
+    assert_eq!(groups[&(1, PhysicalTag(5))], (min, max));
+}
+
+#[test]
+fn test_profile_box_and_t1() {
+    use gmsh_parser::geometry::{profile, Axis};
+
+    // box.msh is a unit cube of tetrahedra
+    let mesh = parse_msh_file(BOX).unwrap();
+    let stations = profile(&mesh, &Axis::new([0.0; 3], [0.0, 0.0, 1.0]), 4).unwrap();
+    assert!(stations.iter().all(|s| (s.area - 1.0).abs() < 1e-9));
+
+    // t1 is a 0.1 x 0.3 rectangle, cut into segments across its length
+    let mesh = parse_msh_file(T1).unwrap();
+    let stations = profile(&mesh, &Axis::new([0.0; 3], [0.0, 1.0, 0.0]), 3).unwrap();
+    assert!(stations.iter().all(|s| (s.area - 0.1).abs() < 1e-9));
+}
+
+#[test]
+fn test_oriented_bbox_t1() {
+    use gmsh_parser::geometry::{oriented_bbox, Affine3, Axis};
+
+    let mut mesh = parse_msh_file(T1).unwrap();
+    let rotation = Affine3::rotation(&Axis::new([0.0; 3], [0.0, 0.0, 1.0]), 0.5).unwrap();
+    for node in mesh.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+        [node.x, node.y, node.z] = rotation.apply([node.x, node.y, node.z]);
+    }
+
+    // The nodes are not symmetric, so the principal axes are slightly tilted
+    let obb = oriented_bbox(&mesh).unwrap();
+    assert!((obb.half_extents[0] - 0.15).abs() < 1e-3, "{:?}", obb);
+    assert!((obb.half_extents[1] - 0.05).abs() < 1e-3, "{:?}", obb);
+    let long_axis = rotation.apply_vector([0.0, 1.0, 0.0]);
+    let alignment: f64 = (0..3).map(|k| obb.axes[0][k] * long_axis[k]).sum();
+    assert!(alignment.abs() > 0.999);
+}
+
+#[test]
+fn test_overlapping_cubes_box() {
+    use gmsh_parser::geometry::{overlaps, Affine3};
+
+    let cube = parse_msh_file(BOX).unwrap();
+    let mut shifted = cube.clone();
+    let shift = Affine3::translation([0.5, 0.0, 0.0]);
+    for node in shifted.node_blocks.iter_mut().flat_map(|b| &mut b.nodes) {
+        [node.x, node.y, node.z] = shift.apply([node.x, node.y, node.z]);
+    }
+    let report = overlaps(&cube, &shifted);
+    // Tetrahedra of the cube near x = 0 are clear of the shifted copy
+    let first: std::collections::BTreeSet<_> = report.intersecting.iter().map(|p| p.0).collect();
+    assert!(!first.is_empty());
+    assert!(first.len() < count_elements(&cube, ElementType::Tetrahedron4));
+}
+
+#[test]
+fn test_rotation_keeps_periodic_links_t18() {
+    use gmsh_parser::geometry::Axis;
+
+    let mut mesh = parse_msh_file("tests/data/gmsh_official/t18.msh").unwrap();
+    mesh.rotate(&Axis::new([1.0, 2.0, 3.0], [1.0, 1.0, 0.0]), 0.7)
+        .unwrap();
+    mesh.translate([10.0, 0.0, 0.0]);
+    assert!(mesh.check_periodic_links().is_empty());
+}
+
+#[test]
+fn test_scale_t1() {
+    let mut mesh = parse_msh_file(T1).unwrap();
+    mesh.scale([2.0, -1.0, 1.0]);
+    let surface = &mesh.entities.as_ref().unwrap().surfaces[0];
+    for node in mesh.node_blocks.iter().flat_map(|b| &b.nodes) {
+        assert!(node.x >= surface.min_x - 1e-9 && node.x <= surface.max_x + 1e-9);
+        assert!(node.y >= surface.min_y - 1e-9 && node.y <= surface.max_y + 1e-9);
+    }
+}
+
+#[test]
+fn test_convert_units_t1() {
+    use gmsh_parser::geometry::LengthUnit;
+
+    let mut mesh = parse_msh_file(T1).unwrap();
+    mesh.convert_units(LengthUnit::Millimetre, LengthUnit::Metre);
+    let surface = &mesh.entities.as_ref().unwrap().surfaces[0];
+    assert!((surface.max_y - 0.3e-3).abs() < 1e-15);
+    assert!((mesh.bounding_box().unwrap().1[0] - 0.1e-3).abs() < 1e-15);
+}
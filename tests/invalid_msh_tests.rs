@@ -47,3 +47,124 @@ generate_invalid_tests!(
     // (test_invalid_bad_version, "bad_version.msh"),
     // (test_invalid_missing_end, "missing_end.msh"),
 );
+
+// ============================================================
+// Malformed input must be reported as an error, never abort the host
+// ============================================================
+const SMALL_MESH: &str = "$MeshFormat
+4.1 0 8
+$EndMeshFormat
+$PhysicalNames
+1
+2 1 \"Surface\"
+$EndPhysicalNames
+$Entities
+0 0 1 0
+1 0 0 0 1 1 0 1 1 0
+$EndEntities
+$Nodes
+1 3 1 3
+2 1 0 3
+1
+2
+3
+0 0 0
+1 0 0
+0 1 0
+$EndNodes
+$Elements
+1 1 1 1
+2 1 2 1
+1 1 2 3
+$EndElements
+$NodeData
+1
+\"temperature\"
+1
+0.0
+3
+0
+1
+3
+1 10.0
+2 20.0
+3 30.0
+$EndNodeData
+$ElementNodeData
+1
+\"gradient\"
+1
+0.0
+3
+0
+1
+1
+1 3 1.0 2.0 3.0
+$EndElementNodeData
+$InterpolationScheme
+\"scheme\"
+1
+3
+1
+2 2 1 0 0 1
+$EndInterpolationScheme
+";
+
+const WEIRD_TOKENS: [&str; 7] = [
+    "-1",
+    "0",
+    "18446744073709551615",
+    "4294967296",
+    "nan",
+    "1e400",
+    "é",
+];
+
+fn assert_no_panic(input: &str) {
+    let result = std::panic::catch_unwind(|| {
+        let _ = gmsh_parser::parse_msh(input);
+    });
+    assert!(result.is_ok(), "parser panicked on input:\n{}", input);
+}
+
+#[test]
+fn test_malformed_input_never_panics() {
+    gmsh_parser::parse_msh(SMALL_MESH).expect("base mesh should parse");
+
+    for input in [
+        "",
+        "\n\n\n",
+        "$",
+        "$MeshFormat",
+        "$MeshFormat\n4.1 0 8",
+        "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Unknown",
+        "\u{feff}$MeshFormat\n4.1 0 8\n$EndMeshFormat\n",
+        "$MeshFormat\r\n4.1 0 8\r\n$EndMeshFormat\r\n$Nodes\r\n",
+        "$MeshFormat\n4.1.1.1 0 8\n$EndMeshFormat\n",
+        "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Nodes\n1 1 1 1\n",
+        "$MeshFormat\n4.1 0 8\n$EndMeshFormat\n$Elements\n1 1 1 1\n2 1 999 1\n",
+    ] {
+        assert_no_panic(input);
+    }
+
+    // Every truncation of a valid mesh
+    for (offset, _) in SMALL_MESH.char_indices() {
+        assert_no_panic(&SMALL_MESH[..offset]);
+    }
+
+    // Every token replaced by values that overflow, underflow or are not numbers
+    let lines: Vec<&str> = SMALL_MESH.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        for t in 0..tokens.len() {
+            for weird in WEIRD_TOKENS {
+                let mut replaced = tokens.clone();
+                replaced[t] = weird;
+                let mut mutated = lines.clone();
+                let new_line = replaced.join(" ");
+                mutated[i] = &new_line;
+                assert_no_panic(&mutated.join("\n"));
+            }
+        }
+    }
+}
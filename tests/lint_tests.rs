@@ -0,0 +1,49 @@
+// Element quality, entity/node consistency, tied interfaces and full
+// validation, checked on t1.msh and box.msh
+
+mod common;
+
+use common::count_elements;
+use gmsh_parser::parse_msh_file;
+use gmsh_parser::types::ElementType;
+
+const T1: &str = "tests/data/gmsh_official/t1.msh";
+const BOX: &str = "tests/data/valid/box.msh";
+
+#[test]
+fn test_quality_report_t1() {
+    let mesh = parse_msh_file(T1).unwrap();
+    let report = mesh.quality_report();
+    assert_eq!(report.dimension, 2);
+    assert_eq!(report.count, count_elements(&mesh, ElementType::Triangle3));
+    assert!(report.inverted.is_empty());
+    assert!(report.skewness.max < 1.0);
+}
+
+#[test]
+fn test_entity_nodes_t1_and_box() {
+    use gmsh_parser::lint::check_entity_nodes;
+
+    assert!(check_entity_nodes(&parse_msh_file(T1).unwrap()).is_empty());
+    assert!(check_entity_nodes(&parse_msh_file(BOX).unwrap()).is_empty());
+}
+
+#[test]
+fn test_tied_interface_box() {
+    use gmsh_parser::lint::check_tied_interface;
+    use gmsh_parser::types::PhysicalTag;
+
+    // The left side of the cube is tied to the solid
+    let mesh = parse_msh_file(BOX).unwrap();
+    let issues = check_tied_interface(&mesh, (2, PhysicalTag(1)), (3, PhysicalTag(7))).unwrap();
+    assert!(issues.is_empty());
+}
+
+#[test]
+fn test_validate_all_t1_and_box() {
+    for path in [T1, BOX] {
+        let report = parse_msh_file(path).unwrap().validate_all();
+        assert!(report.is_valid(), "{}", path);
+        assert!(report.orphan_nodes.is_empty(), "{}", path);
+    }
+}
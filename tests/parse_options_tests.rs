@@ -0,0 +1,140 @@
+// Streaming, async parsing, block filters, indexed files, progress reporting,
+// sinks and probing, checked on t1.msh and box.msh
+
+use gmsh_parser::parse_msh_file;
+
+const T1: &str = "tests/data/gmsh_official/t1.msh";
+const BOX: &str = "tests/data/valid/box.msh";
+
+#[test]
+fn test_streaming_t1() {
+    use gmsh_parser::parser::{Event, StreamingParser};
+
+    let content = std::fs::read_to_string(T1).unwrap();
+    let mesh = gmsh_parser::parse_msh(&content).unwrap();
+    let mut node_tags = Vec::new();
+    let mut element_tags = Vec::new();
+    for event in StreamingParser::new(&content).unwrap() {
+        match event.unwrap() {
+            Event::Node(node) => node_tags.push(node.tag),
+            Event::Element(element) => element_tags.push(element.tag),
+            _ => {}
+        }
+    }
+
+    let nodes = mesh.node_blocks.iter().flat_map(|b| &b.nodes);
+    assert_eq!(node_tags, nodes.map(|n| n.tag).collect::<Vec<_>>());
+    let elements = mesh.element_blocks.iter().flat_map(|b| &b.elements);
+    assert_eq!(element_tags, elements.map(|e| e.tag).collect::<Vec<_>>());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_async_parse_matches_sync_t1() {
+    let mesh = gmsh_parser::parse_msh_file_async(T1).await.unwrap();
+    let expected = parse_msh_file(T1).unwrap();
+    assert_eq!(mesh.num_nodes(), expected.num_nodes());
+    assert_eq!(mesh.num_elements(), expected.num_elements());
+}
+
+#[test]
+fn test_block_filter_box() {
+    use gmsh_parser::types::PhysicalTag;
+    use gmsh_parser::{parse_msh_file_with, BlockFilter, ParserOptions};
+
+    let full = parse_msh_file(BOX).unwrap();
+    let left = full.physical_group("Left").unwrap();
+    let options = ParserOptions::default()
+        .block_filter(BlockFilter::default().physical_group(2, PhysicalTag(1)));
+    let mesh = parse_msh_file_with(BOX, &options).unwrap();
+    assert_eq!(mesh.num_elements(), left.num_elements());
+    assert!(mesh.num_nodes() < full.num_nodes());
+}
+
+#[test]
+fn test_indexed_file_box() {
+    use gmsh_parser::parser::IndexedFile;
+
+    let mesh = parse_msh_file(BOX).unwrap();
+    let mut file = IndexedFile::open(BOX).unwrap();
+    assert_eq!(file.node_blocks().len(), mesh.node_blocks.len());
+    let last = mesh.element_blocks.len() - 1;
+    let block = file.read_element_block(last).unwrap();
+    let expected = &mesh.element_blocks[last].elements;
+    assert_eq!(block.elements.len(), expected.len());
+    assert!(block
+        .elements
+        .iter()
+        .zip(expected)
+        .all(|(a, b)| a.tag == b.tag && a.nodes == b.nodes));
+}
+
+#[test]
+fn test_progress_reports_t1() {
+    use gmsh_parser::{parse_msh_file_with, ParserOptions};
+    use std::sync::{Arc, Mutex};
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&reports);
+    let options = ParserOptions::default().progress(move |p| sink.lock().unwrap().push(p));
+    let mesh = parse_msh_file_with(T1, &options).unwrap();
+
+    let reports = reports.lock().unwrap();
+    let last = reports.last().unwrap();
+    assert_eq!(last.fraction(), 1.0);
+    assert_eq!(
+        last.blocks_parsed,
+        mesh.node_blocks.len() + mesh.element_blocks.len()
+    );
+}
+
+#[test]
+fn test_sinks_t1() {
+    use gmsh_parser::types::{ElementTag, NodeTag};
+    use gmsh_parser::{parse_msh_file_into, ElementSink, NodeSink};
+
+    #[derive(Default)]
+    struct Collect {
+        coordinates: Vec<f64>,
+        connectivity: Vec<NodeTag>,
+    }
+    impl NodeSink for Collect {
+        fn node(&mut self, _: NodeTag, coordinates: [f64; 3], _: &[f64]) {
+            self.coordinates.extend(coordinates);
+        }
+    }
+    impl ElementSink for Collect {
+        fn element(&mut self, _: ElementTag, nodes: &[NodeTag]) {
+            self.connectivity.extend_from_slice(nodes);
+        }
+    }
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let (mut nodes, mut elements) = (Collect::default(), Collect::default());
+    parse_msh_file_into(T1, &mut nodes, &mut elements).unwrap();
+    let coordinates: Vec<_> = mesh
+        .node_blocks
+        .iter()
+        .flat_map(|b| &b.nodes)
+        .flat_map(|n| [n.x, n.y, n.z])
+        .collect();
+    assert_eq!(nodes.coordinates, coordinates);
+    let connectivity: Vec<_> = mesh
+        .element_blocks
+        .iter()
+        .flat_map(|b| &b.elements)
+        .flat_map(|e| e.nodes.iter().copied())
+        .collect();
+    assert_eq!(elements.connectivity, connectivity);
+}
+
+#[test]
+fn test_probe_t1() {
+    let mesh = parse_msh_file(T1).unwrap();
+    let info = gmsh_parser::probe_msh_file(T1).unwrap();
+    assert_eq!(info.num_nodes(), mesh.num_nodes());
+    assert_eq!(info.num_elements(), mesh.num_elements());
+    assert_eq!(info.nodes_header, mesh.nodes_header);
+    assert_eq!(info.physical_names.len(), mesh.physical_names.len());
+    assert_eq!(info.size, std::fs::metadata(T1).unwrap().len());
+}
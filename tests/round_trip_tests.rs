@@ -0,0 +1,122 @@
+use gmsh_parser::roundtrip::assert_lossless;
+use gmsh_parser::writer::{write_msh, write_msh_file};
+use gmsh_parser::{parse_msh, parse_msh_file, Mesh};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const T1: &str = "tests/data/gmsh_official/t1.msh";
+
+fn msh_files(dir: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .expect("Failed to read test data directory")
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("msh"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "No .msh files found in {}", dir);
+    files
+}
+
+/// Check that `path` survives a round trip through the writer, and that
+/// writing the reparsed mesh reproduces the same text
+fn assert_round_trip(path: &Path) {
+    assert_lossless(path);
+    let written = write_msh(&parse_msh_file(path).unwrap()).unwrap();
+    let reparsed = parse_msh(&written).unwrap();
+    assert_eq!(write_msh(&reparsed).unwrap(), written, "{:?}", path);
+}
+
+#[test]
+fn test_round_trip_valid_files() {
+    for path in msh_files("tests/data/valid") {
+        assert_round_trip(&path);
+    }
+}
+
+#[test]
+fn test_round_trip_gmsh_tutorials() {
+    // view5.msh is in the MSH 2 format, which the parser does not read
+    let tutorials = msh_files("tests/data/gmsh_official")
+        .into_iter()
+        .filter(|p| p.file_name().and_then(|s| s.to_str()) != Some("view5.msh"));
+    for path in tutorials {
+        assert_round_trip(&path);
+    }
+}
+
+#[test]
+fn test_write_msh_file() {
+    let mesh = parse_msh_file("tests/data/valid/box.msh").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("box.msh");
+    write_msh_file(&mesh, &path).unwrap();
+
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with("$MeshFormat\n4.1 0 8\n$EndMeshFormat\n"));
+    assert_eq!(written, write_msh(&mesh).unwrap());
+}
+
+#[test]
+fn test_write_partitioned_t21() {
+    let mesh = parse_msh_file("tests/data/gmsh_official/t21.msh").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let paths = mesh.write_partitioned(dir.path().join("t21")).unwrap();
+    assert_eq!(paths.len(), 3);
+    assert!(paths[0].ends_with("t21_1.msh"));
+
+    let surface_elements = |mesh: &Mesh| -> usize {
+        let blocks = mesh.element_blocks.iter().filter(|b| b.entity_dim == 2);
+        blocks.map(|b| b.elements.len()).sum()
+    };
+    let mut total = 0;
+    for (index, path) in paths.iter().enumerate() {
+        let part = parse_msh_file(path).unwrap();
+        part.validate().unwrap();
+        let partitioned = part.partitioned_entities.as_ref().unwrap();
+        assert_eq!(partitioned.num_partitions, 3);
+        let partition = index as i32 + 1;
+        assert!(partitioned
+            .surfaces
+            .iter()
+            .all(|s| s.partition_tags.contains(&partition)));
+        total += surface_elements(&part);
+    }
+    assert_eq!(total, surface_elements(&mesh));
+}
+
+#[test]
+fn test_coordinate_system_t1() {
+    use gmsh_parser::geometry::{Affine3, LengthUnit};
+    use gmsh_parser::types::CoordinateSystem;
+
+    let mut mesh = parse_msh_file(T1).unwrap();
+    mesh.coordinate_system = Some(
+        CoordinateSystem::new("part 1")
+            .with_unit(LengthUnit::Millimetre)
+            .with_transform(Affine3::translation([100.0, 0.0, 0.0])),
+    );
+    let reparsed = parse_msh(write_msh(&mesh).unwrap()).unwrap();
+    assert_eq!(reparsed.coordinate_system, mesh.coordinate_system);
+    assert_eq!(reparsed.num_nodes(), mesh.num_nodes());
+}
+
+#[test]
+fn test_read_and_write_any_t1() {
+    use gmsh_parser::registry::{read_any, write_any};
+
+    let dir = tempfile::tempdir().unwrap();
+    let mesh = read_any(T1).unwrap();
+    assert_eq!(mesh.num_nodes(), parse_msh_file(T1).unwrap().num_nodes());
+
+    // Unknown extension, recognized by content
+    let renamed = dir.path().join("t1.mesh");
+    std::fs::copy(T1, &renamed).unwrap();
+    assert_eq!(read_any(&renamed).unwrap().num_nodes(), mesh.num_nodes());
+
+    let written = dir.path().join("t1.msh");
+    write_any(&written, &mesh).unwrap();
+    let back = read_any(&written).unwrap();
+    assert_eq!(back.num_nodes(), mesh.num_nodes());
+    assert_eq!(back.num_elements(), mesh.num_elements());
+}
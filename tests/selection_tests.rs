@@ -0,0 +1,93 @@
+// Node and element sets, element filters, physical groups, selections and
+// material ids, checked on t1.msh, t2.msh and box.msh
+
+mod common;
+
+use common::count_elements;
+use gmsh_parser::parse_msh_file;
+use gmsh_parser::types::ElementType;
+
+const T1: &str = "tests/data/gmsh_official/t1.msh";
+const BOX: &str = "tests/data/valid/box.msh";
+
+#[test]
+fn test_sets_t1() {
+    use gmsh_parser::types::PhysicalTag;
+    use gmsh_parser::{ElementSet, NodeSet};
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let surface = ElementSet::from_physical_name(&mesh, "My surface").unwrap();
+    assert_eq!(surface.tag, Some(6));
+    let all_nodes = NodeSet::from_predicate(&mesh, "all", |_| true);
+    assert_eq!(surface.node_set(&mesh).tags, all_nodes.tags);
+
+    // Curves 1, 2 and 4 (bottom, right and left sides) form physical group 5
+    let boundary = NodeSet::from_physical_group(&mesh, 1, PhysicalTag(5)).unwrap();
+    let top = NodeSet::from_predicate(&mesh, "top", |n| (n.y - 0.3).abs() < 1e-12);
+    assert_eq!(top.difference(&boundary).len(), top.len() - 2);
+}
+
+#[test]
+fn test_filter_t1() {
+    use gmsh_parser::filter::ElementFilter;
+    use gmsh_parser::types::PhysicalTag;
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let triangles = ElementFilter::of_type(ElementType::Triangle3);
+    let surface = ElementFilter::physical_group(2, PhysicalTag(6));
+    assert_eq!(
+        mesh.count_elements(&triangles),
+        mesh.count_elements(&surface)
+    );
+
+    let lower = triangles.and(ElementFilter::within_box([-1.0; 3], [1.0, 0.15, 1.0]));
+    let extracted = mesh.extract(&lower).unwrap();
+    assert_eq!(extracted.num_elements(), mesh.count_elements(&lower));
+    assert!(extracted
+        .node_blocks
+        .iter()
+        .flat_map(|b| b.nodes.iter())
+        .all(|n| n.y < 0.3));
+}
+
+#[test]
+fn test_physical_group_box() {
+    use gmsh_parser::NodeSet;
+
+    let mesh = parse_msh_file(BOX).unwrap();
+    let left = mesh.physical_group("Left").unwrap();
+    assert!(left.num_elements() > 0);
+    let nodes = NodeSet::from_predicate(&mesh, "x = 0", |n| n.x.abs() < 1e-12);
+    assert_eq!(left.node_tags, nodes.tags);
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn test_selections_t1_and_t2() {
+    use gmsh_parser::selections::{load_for, save, sidecar_path, Selections};
+    use gmsh_parser::types::PhysicalTag;
+    use gmsh_parser::NodeSet;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = sidecar_path(dir.path().join("t1.msh"));
+    let mut mesh = parse_msh_file(T1).unwrap();
+    mesh.node_sets
+        .push(NodeSet::from_physical_group(&mesh, 1, PhysicalTag(5)).unwrap());
+    save(&path, &Selections::from_mesh(&mesh)).unwrap();
+
+    let regenerated = parse_msh_file(T1).unwrap();
+    assert!(load_for(&path, &regenerated).unwrap().is_some());
+    let other = parse_msh_file("tests/data/gmsh_official/t2.msh").unwrap();
+    assert!(load_for(&path, &other).unwrap().is_none());
+}
+
+#[test]
+fn test_material_ids_t1() {
+    use gmsh_parser::materials::{Material, MaterialTable};
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let table = MaterialTable::new().assign("My surface", Material::new("steel", 7));
+    table.validate(&mesh).unwrap();
+    let ids = table.material_ids(&mesh).unwrap();
+    assert_eq!(ids.len(), count_elements(&mesh, ElementType::Triangle3));
+}
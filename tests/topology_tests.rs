@@ -0,0 +1,154 @@
+// Region growing, feature edges, surface segmentation, volume identification,
+// computed ghost elements, the entity graph and periodic node maps, checked on
+// t1.msh, t18.msh and box.msh
+
+use gmsh_parser::parse_msh_file;
+use gmsh_parser::types::ElementType;
+use std::collections::HashMap;
+
+const T1: &str = "tests/data/gmsh_official/t1.msh";
+const BOX: &str = "tests/data/valid/box.msh";
+
+#[test]
+fn test_grow_region_t1() {
+    use gmsh_parser::filter::ElementFilter;
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let triangles = ElementFilter::of_type(ElementType::Triangle3);
+    let seed = mesh.iter_elements(&triangles).next().unwrap().tag();
+    let region = gmsh_parser::topology::grow_region(&mesh, seed, |_| true).unwrap();
+    assert_eq!(region.len(), mesh.count_elements(&triangles));
+}
+
+#[test]
+fn test_feature_edges_box() {
+    let mesh = parse_msh_file(BOX).unwrap();
+    let coords: HashMap<_, _> = mesh
+        .node_blocks
+        .iter()
+        .flat_map(|b| &b.nodes)
+        .map(|n| (n.tag, [n.x, n.y, n.z]))
+        .collect();
+    let length =
+        |a: [f64; 3], b: [f64; 3]| (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt();
+    let edges = gmsh_parser::topology::feature_edges(&mesh, 30f64.to_radians());
+    // The twelve edges of the unit cube
+    let total_length: f64 = edges
+        .iter()
+        .map(|e| length(coords[&e.nodes[0]], coords[&e.nodes[1]]))
+        .sum();
+    assert!((total_length - 12.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_segment_surfaces_box() {
+    let mesh = parse_msh_file(BOX).unwrap();
+    let patches = gmsh_parser::topology::segment_surfaces(&mesh, 30f64.to_radians());
+    assert_eq!(patches.len(), 6);
+    let surface_elements: usize = mesh
+        .element_blocks
+        .iter()
+        .filter(|b| b.entity_dim == 2)
+        .map(|b| b.elements.len())
+        .sum();
+    assert_eq!(
+        patches.iter().map(|p| p.len()).sum::<usize>(),
+        surface_elements
+    );
+}
+
+#[test]
+fn test_identify_volumes_box() {
+    let mesh = parse_msh_file(BOX).unwrap();
+    let regions = gmsh_parser::topology::identify_volumes(&mesh);
+    let volume_elements: usize = mesh
+        .element_blocks
+        .iter()
+        .filter(|b| b.entity_dim == 3)
+        .map(|b| b.elements.len())
+        .sum();
+    assert_eq!(regions.len(), 1);
+    assert_eq!(regions[0].len(), volume_elements);
+}
+
+#[test]
+fn test_computed_ghost_elements_box() {
+    let mesh = parse_msh_file(BOX).unwrap();
+    let mut expected: Vec<_> = mesh
+        .ghost_elements
+        .iter()
+        .map(|g| {
+            (
+                g.partition_tag,
+                g.element_tag,
+                g.ghost_partition_tags.clone(),
+            )
+        })
+        .collect();
+    expected.sort();
+    let computed: Vec<_> = mesh
+        .compute_ghost_elements()
+        .unwrap()
+        .into_iter()
+        .map(|g| (g.partition_tag, g.element_tag, g.ghost_partition_tags))
+        .collect();
+    // The computed ghost elements match the ones Gmsh wrote
+    assert_eq!(computed, expected);
+}
+
+#[test]
+fn test_entity_graph_t1() {
+    use gmsh_parser::types::EntityTag;
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let graph = mesh.entity_graph().unwrap();
+    let closure = graph.closure(2, EntityTag(1));
+    assert_eq!(closure.iter().filter(|(dim, _)| *dim == 1).count(), 4);
+    assert!(closure.iter().filter(|(dim, _)| *dim == 0).count() >= 4);
+}
+
+#[test]
+fn test_oriented_boundary_t1() {
+    use gmsh_parser::types::{EntityTag, Orientation};
+
+    let mesh = parse_msh_file(T1).unwrap();
+    let graph = mesh.entity_graph().unwrap();
+    assert_eq!(
+        graph.oriented_boundary(2, EntityTag(1)),
+        [
+            (EntityTag(4), Orientation::Forward),
+            (EntityTag(1), Orientation::Forward),
+            (EntityTag(2), Orientation::Reversed),
+            (EntityTag(3), Orientation::Forward),
+        ]
+    );
+}
+
+#[test]
+fn test_periodic_node_map_t18() {
+    let mesh = parse_msh_file("tests/data/gmsh_official/t18.msh").unwrap();
+    let map = mesh.periodic_node_map().unwrap();
+    let coordinates: HashMap<_, _> = mesh
+        .node_blocks
+        .iter()
+        .flat_map(|b| b.nodes.iter().map(|n| (n.tag, [n.x, n.y, n.z])))
+        .collect();
+    for link in &mesh.periodic_links {
+        for (slave, master) in &link.node_correspondences {
+            // Chains end on a node that is no slave itself
+            let root = map.get(master).unwrap_or(master);
+            assert_eq!(map.get(slave), Some(root));
+            assert!(!map.contains_key(root));
+            if let Some(image) = link.master_to_slave(coordinates[master]) {
+                let gap = (0..3).map(|k| (image[k] - coordinates[slave][k]).abs());
+                assert!(gap.fold(0.0, f64::max) < 1e-6);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_periodic_links_t18() {
+    let mesh = parse_msh_file("tests/data/gmsh_official/t18.msh").unwrap();
+    assert!(mesh.check_periodic_links().is_empty());
+}